@@ -0,0 +1,30 @@
+//! One-off migration after deploying the DynamoDB GSIs: adds the attributes the
+//! indexes are keyed on to items written before them, in every tenant, and the
+//! host items organizations are looked up by.
+//!
+//! ```bash
+//! DYNAMO_TABLE_SHORTLINKS=shortlinks-dev \
+//! DYNAMO_TABLE_COUNTERS=counters-dev \
+//! DYNAMO_TABLE_AUDIT=audit-log-dev \
+//! DYNAMO_TABLE_ORGANIZATIONS=organizations-dev \
+//!   cargo run -p aws-dynamo --example backfill_indexes
+//! ```
+
+use aws_dynamo::DynamoRepo;
+
+fn main() {
+    let repo = match DynamoRepo::from_env() {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("dynamo init failed: {e}");
+            std::process::exit(1);
+        }
+    };
+    match repo.backfill_index_attributes() {
+        Ok(count) => println!("updated {count} items"),
+        Err(e) => {
+            eprintln!("backfill failed: {e}");
+            std::process::exit(1);
+        }
+    }
+}
@@ -5,20 +5,70 @@
 //! - Maintains a monotonic counter item (`name = "global"`) in the Counters table
 //!   to support Base62 slug generation in higher layers.
 //! - Provides `from_env()` wiring for Lambda/apps using env vars:
-//!   `DYNAMO_TABLE_SHORTLINKS`, `DYNAMO_TABLE_COUNTERS`.
+//!   `DYNAMO_TABLE_SHORTLINKS`, `DYNAMO_TABLE_COUNTERS`, and
+//!   `DYNAMO_ENDPOINT_URL` to talk to DynamoDB Local instead of AWS.
+//! - Partitions data per tenant by prefixing each table's partition key with
+//!   `<tenant>#` (see `domain::tenant`); the default tenant stays unprefixed.
+//!   Every read drops items of other tenants, so scans and GSI queries never
+//!   cross tenants. Organizations live in their own global table, next to one
+//!   `host#<host>` item per host holding a copy of the organization, so a
+//!   request's organization is a single `GetItem`.
+//! - Slugs are keyed by `Slug::key`, so links on extra short domains keep their
+//!   own slug space; the domains themselves live in the ShortDomains table.
+//! - Aliases are items in the Shortlinks table holding only `slug` and
+//!   `alias_of` (the target's key), so links and aliases share one slug space
+//!   and a link is found with a single read (two when reached through an alias).
+//! - Reserved slug namespaces live in the Namespaces table keyed by `path`.
+//! - Links carry `target_key` and `target_host` (`ShortLink::target_key`,
+//!   `ShortLink::target_host`). Exact targets are queried through the
+//!   `target_key-index` GSI sorted by `created_at`; hosts and prefixes through
+//!   `target_host-index`, sorted by `target_key` so a prefix is `begins_with`.
+//!   Links written before the attributes existed are found once updated or
+//!   backfilled (see below).
+//! - No listing scans a table: they `Query` GSIs (infra/sam/template.yaml) and
+//!   follow `LastEvaluatedKey` until enough items of the tenant matched. Links
+//!   are listed by `tenant`, `created_by`, `group_id` (newest first) and
+//!   `alias_of`; groups by `created_by` and `parent_id`; memberships by
+//!   `user_email`; audit entries by `tenant`, `actor_email` and `target_id`.
+//!   Links and audit entries carry the plain `tenant` id for this;
+//!   `DynamoRepo::backfill_index_attributes` adds the index attributes to items
+//!   written before (`cargo run -p aws-dynamo --example backfill_indexes`).
+//! - A whole tenant is listed from `tenant-index`, so each organization's
+//!   links share one GSI partition. That is the only way to read a tenant's
+//!   links newest first without a Scan, and only admin listings and search use
+//!   it: redirects are `GetItem`s on the table and never touch the index. A
+//!   tenant whose link writes outgrow a single partition would need the key
+//!   sharded (`tenant#n`) and the shards merged on read. Narrower keys win
+//!   when given: the creator's or group's index, and the `created_at` range.
+//!   Other filters run after the read, so a page costs what was read to fill
+//!   it: `query_page` keeps reading until the page is full and the cursor is
+//!   the last key read, so pages never overlap and only the last can be short.
+//! - Paginated listings sort by `created_at` in DynamoDB and leave `total`
+//!   out: counting would re-read every match of the index. Listings of what a user can access merge the
+//!   user's and their groups' indexes from the cursor on, plus the links
+//!   shared with them. Other sorts read every match
+//!   and sort and page in memory.
 //!
 //! Notes:
 //! - The domain `LinkRepository` trait is synchronous. We bridge to the async AWS
 //!   SDK using an internal `tokio::runtime::Runtime` and `block_on`.
 
-use aws_sdk_dynamodb::{types::AttributeValue, Client};
+use aws_sdk_dynamodb::operation::query::builders::QueryFluentBuilder;
+use aws_sdk_dynamodb::types::{
+    AttributeValue, ConditionCheck, Delete, Put, TransactWriteItem, Update,
+};
+use aws_sdk_dynamodb::Client;
 use aws_smithy_types::error::metadata::ProvideErrorMetadata;
 use domain::{
-    AuditAction, AuditEntry, AuditRepository, ClickEvent, ClickRepository, CoreError, GroupMember,
-    GroupRepository, GroupRole, LinkGroup, LinkRepository, ListOptions, ListResult, ShortLink,
-    Slug, UserEmail,
+    hierarchy, tenant, AccessScope, AuditAction, AuditEntry, AuditRepository, ClickEvent,
+    ClickRepository, CoreError, DomainRepository, GroupInvitation, GroupLinkDisposition,
+    GroupMember, GroupRepository, GroupRole, GroupSettings, InvitationRepository, InvitationStatus,
+    LinkGrant, LinkGrantRepository, LinkGroup, LinkRepository, LinkSort, LinkStatus,
+    LinkVisibility, ListOptions, ListResult, Namespace, NamespaceRepository, Organization,
+    OrganizationRepository, OrganizationSettings, ShortDomain, ShortLink, Slug, SortDirection,
+    TargetMatch, TenantId, TenantScoped, UserEmail,
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Configuration for DynamoDB table names.
@@ -28,8 +78,13 @@ pub struct DynamoTables {
     pub counters: String,
     pub groups: String,
     pub group_members: String,
+    pub link_grants: String,
+    pub invitations: String,
     pub clicks: String,
     pub audit: String,
+    pub organizations: String,
+    pub domains: String,
+    pub namespaces: String,
 }
 
 impl DynamoTables {
@@ -40,8 +95,13 @@ impl DynamoTables {
             counters: counters.into(),
             groups: "Groups".into(),
             group_members: "GroupMembers".into(),
+            link_grants: "LinkGrants".into(),
+            invitations: "GroupInvitations".into(),
             clicks: "Clicks".into(),
             audit: "AuditLog".into(),
+            organizations: "Organizations".into(),
+            domains: "ShortDomains".into(),
+            namespaces: "Namespaces".into(),
         }
     }
 
@@ -54,15 +114,30 @@ impl DynamoTables {
         let groups = std::env::var("DYNAMO_TABLE_GROUPS").unwrap_or_else(|_| "Groups".into());
         let group_members =
             std::env::var("DYNAMO_TABLE_GROUP_MEMBERS").unwrap_or_else(|_| "GroupMembers".into());
+        let link_grants =
+            std::env::var("DYNAMO_TABLE_LINK_GRANTS").unwrap_or_else(|_| "LinkGrants".into());
+        let invitations =
+            std::env::var("DYNAMO_TABLE_INVITATIONS").unwrap_or_else(|_| "GroupInvitations".into());
         let clicks = std::env::var("DYNAMO_TABLE_CLICKS").unwrap_or_else(|_| "Clicks".into());
         let audit = std::env::var("DYNAMO_TABLE_AUDIT").unwrap_or_else(|_| "AuditLog".into());
+        let organizations =
+            std::env::var("DYNAMO_TABLE_ORGANIZATIONS").unwrap_or_else(|_| "Organizations".into());
+        let domains =
+            std::env::var("DYNAMO_TABLE_DOMAINS").unwrap_or_else(|_| "ShortDomains".into());
+        let namespaces =
+            std::env::var("DYNAMO_TABLE_NAMESPACES").unwrap_or_else(|_| "Namespaces".into());
         Ok(Self {
             shortlinks,
             counters,
             groups,
             group_members,
+            link_grants,
+            invitations,
             clicks,
             audit,
+            organizations,
+            domains,
+            namespaces,
         })
     }
 }
@@ -77,8 +152,14 @@ pub struct DynamoRepo {
     table_counters: String,
     table_groups: String,
     table_group_members: String,
+    table_link_grants: String,
+    table_invitations: String,
     table_clicks: String,
     table_audit: String,
+    table_organizations: String,
+    table_domains: String,
+    table_namespaces: String,
+    tenant: TenantId,
     client: Client,
     // Optional runtime - None when running inside Lambda (reuses existing runtime)
     rt: Option<std::sync::Arc<tokio::runtime::Runtime>>,
@@ -96,8 +177,14 @@ impl DynamoRepo {
             table_counters: tables.counters,
             table_groups: tables.groups,
             table_group_members: tables.group_members,
+            table_link_grants: tables.link_grants,
+            table_invitations: tables.invitations,
             table_clicks: tables.clicks,
             table_audit: tables.audit,
+            table_organizations: tables.organizations,
+            table_domains: tables.domains,
+            table_namespaces: tables.namespaces,
+            tenant: TenantId::default(),
             client,
             rt,
         })
@@ -105,19 +192,44 @@ impl DynamoRepo {
 
     /// Construct with table names but create a default AWS SDK client using env/IMDS.
     pub fn new(tables: DynamoTables) -> Result<Self, CoreError> {
+        Self::load(
+            tables,
+            aws_config::defaults(aws_config::BehaviorVersion::latest()),
+        )
+    }
+
+    /// Like [`DynamoRepo::new`], but sends requests to `endpoint` instead of
+    /// AWS, e.g. DynamoDB Local at `http://localhost:8000`. Credentials still
+    /// come from env/profile (DynamoDB Local accepts any); the region defaults
+    /// to `us-east-1` when none is configured.
+    pub fn with_endpoint(tables: DynamoTables, endpoint: &str) -> Result<Self, CoreError> {
+        let region =
+            aws_config::meta::region::RegionProviderChain::default_provider().or_else("us-east-1");
+        Self::load(
+            tables,
+            aws_config::defaults(aws_config::BehaviorVersion::latest())
+                .endpoint_url(endpoint)
+                .region(region),
+        )
+    }
+
+    fn load(tables: DynamoTables, loader: aws_config::ConfigLoader) -> Result<Self, CoreError> {
         let rt = Self::maybe_create_runtime()?;
-        let conf = Self::block_on_with_rt(
-            &rt,
-            aws_config::load_defaults(aws_config::BehaviorVersion::latest()),
-        );
+        let conf = Self::block_on_with_rt(&rt, loader.load());
         let client = Client::new(&conf);
         Ok(Self {
             table_shortlinks: tables.shortlinks,
             table_counters: tables.counters,
             table_groups: tables.groups,
             table_group_members: tables.group_members,
+            table_link_grants: tables.link_grants,
+            table_invitations: tables.invitations,
             table_clicks: tables.clicks,
             table_audit: tables.audit,
+            table_organizations: tables.organizations,
+            table_domains: tables.domains,
+            table_namespaces: tables.namespaces,
+            tenant: TenantId::default(),
             client,
             rt,
         })
@@ -128,11 +240,20 @@ impl DynamoRepo {
     /// - `DYNAMO_TABLE_COUNTERS`
     /// - `DYNAMO_TABLE_GROUPS` (optional, defaults to "Groups")
     /// - `DYNAMO_TABLE_GROUP_MEMBERS` (optional, defaults to "GroupMembers")
+    /// - `DYNAMO_TABLE_LINK_GRANTS` (optional, defaults to "LinkGrants")
+    /// - `DYNAMO_TABLE_INVITATIONS` (optional, defaults to "GroupInvitations")
     /// - `DYNAMO_TABLE_CLICKS` (optional, defaults to "Clicks")
     /// - `DYNAMO_TABLE_AUDIT` (optional, defaults to "AuditLog")
+    /// - `DYNAMO_TABLE_ORGANIZATIONS` (optional, defaults to "Organizations")
+    /// - `DYNAMO_TABLE_DOMAINS` (optional, defaults to "ShortDomains")
+    /// - `DYNAMO_TABLE_NAMESPACES` (optional, defaults to "Namespaces")
+    /// - `DYNAMO_ENDPOINT_URL` (optional, see [`DynamoRepo::with_endpoint`])
     pub fn from_env() -> Result<Self, CoreError> {
         let tables = DynamoTables::from_env()?;
-        Self::new(tables)
+        match std::env::var("DYNAMO_ENDPOINT_URL") {
+            Ok(endpoint) if !endpoint.is_empty() => Self::with_endpoint(tables, &endpoint),
+            _ => Self::new(tables),
+        }
     }
 
     /// Check if we're inside a Tokio runtime. If yes, return None (reuse existing).
@@ -198,32 +319,571 @@ impl DynamoRepo {
         v.parse::<u64>()
             .map_err(|e| CoreError::Repository(format!("parse counter: {e}")))
     }
-}
 
-impl LinkRepository for DynamoRepo {
-    fn get(&self, slug: &Slug) -> Result<Option<ShortLink>, CoreError> {
+    /// Partition-key value of `key` in this handle's tenant.
+    fn key_value(&self, key: &str) -> AttributeValue {
+        AttributeValue::S(tenant::scoped_key(&self.tenant, key))
+    }
+
+    /// Prefix the partition-key attribute `attr` of an item about to be written.
+    fn scope_item(
+        &self,
+        mut item: HashMap<String, AttributeValue>,
+        attr: &str,
+    ) -> HashMap<String, AttributeValue> {
+        if let Some(key) = item.get(attr).and_then(|v| v.as_s().ok()) {
+            let scoped = self.key_value(key);
+            item.insert(attr.into(), scoped);
+        }
+        item
+    }
+
+    /// The item with the tenant prefix stripped from `attr`, or `None` when it
+    /// belongs to another tenant.
+    fn unscope_item(
+        &self,
+        item: &HashMap<String, AttributeValue>,
+        attr: &str,
+    ) -> Option<HashMap<String, AttributeValue>> {
+        let stored = item.get(attr)?.as_s().ok()?;
+        let key = tenant::unscoped_key(&self.tenant, stored)?.to_string();
+        let mut item = item.clone();
+        item.insert(attr.into(), AttributeValue::S(key));
+        Some(item)
+    }
+
+    fn unscoped_items<'a>(
+        &'a self,
+        items: &'a [HashMap<String, AttributeValue>],
+        attr: &'a str,
+    ) -> impl Iterator<Item = HashMap<String, AttributeValue>> + 'a {
+        items
+            .iter()
+            .filter_map(move |it| self.unscope_item(it, attr))
+    }
+
+    /// This handle's tenant as the `tenant` attribute, the partition key of the
+    /// `tenant-index` GSIs listing a tenant's links and audit entries.
+    fn tenant_value(&self) -> AttributeValue {
+        AttributeValue::S(self.tenant.as_str().to_string())
+    }
+
+    /// Run `query` page by page, keeping this tenant's items (with `attr`
+    /// unscoped) until `limit` of them were found or the results ran out. A
+    /// query's `Limit` counts items read before filtering, so a single page can
+    /// come back short.
+    fn query_items(
+        &self,
+        query: QueryFluentBuilder,
+        attr: &str,
+        limit: Option<usize>,
+    ) -> Result<Vec<HashMap<String, AttributeValue>>, CoreError> {
+        let mut res = Vec::new();
+        let mut start_key = None;
+        while limit.is_none_or(|l| res.len() < l) {
+            let fut = query
+                .clone()
+                .set_exclusive_start_key(start_key.take())
+                .send();
+            let out = self.block_on(fut).map_err(map_sdk_err)?;
+            res.extend(self.unscoped_items(out.items(), attr));
+            match out.last_evaluated_key() {
+                Some(k) if !k.is_empty() => start_key = Some(k.clone()),
+                _ => break,
+            }
+        }
+        if let Some(limit) = limit {
+            res.truncate(limit);
+        }
+        Ok(res)
+    }
+
+    /// Query a Shortlinks GSI on `key_condition`, newest first for the indexes
+    /// sorted by `created_at`.
+    fn links_query(&self, index: &str, key_condition: &str) -> QueryFluentBuilder {
+        self.client
+            .query()
+            .table_name(&self.table_shortlinks)
+            .index_name(index)
+            .key_condition_expression(key_condition)
+            .scan_index_forward(false)
+    }
+
+    /// Links found by `query`, skipping items that fail to parse.
+    fn query_links(
+        &self,
+        query: QueryFluentBuilder,
+        limit: Option<usize>,
+    ) -> Result<Vec<ShortLink>, CoreError> {
+        Ok(self
+            .query_items(query, "slug", limit)?
+            .iter()
+            .filter_map(|it| item_to_domain(it).ok())
+            .collect())
+    }
+
+    /// One page of `query`: up to `limit` of this tenant's items starting after
+    /// `cursor`, and the cursor of the next page. Each request's `Limit` is the
+    /// number still wanted, so the last `LastEvaluatedKey` marks exactly where
+    /// the page ended. DynamoDB returns that key even when the page ended on
+    /// the final item, so a full page looks ahead and drops the cursor when
+    /// nothing follows.
+    fn query_page(
+        &self,
+        query: QueryFluentBuilder,
+        attr: &str,
+        limit: usize,
+        cursor: Option<&str>,
+    ) -> Result<(Vec<Item>, Option<String>), CoreError> {
+        let mut res = Vec::new();
+        let mut start_key = cursor.map(decode_start_key).transpose()?;
+        while res.len() < limit {
+            let fut = query
+                .clone()
+                .limit(i32::try_from(limit - res.len()).unwrap_or(i32::MAX))
+                .set_exclusive_start_key(start_key.take())
+                .send();
+            let out = self.block_on(fut).map_err(|e| {
+                // A cursor that decodes but names the wrong keys is rejected here
+                if cursor.is_some() && e.code() == Some("ValidationException") {
+                    CoreError::InvalidCursor
+                } else {
+                    map_sdk_err(e)
+                }
+            })?;
+            res.extend(self.unscoped_items(out.items(), attr));
+            match out.last_evaluated_key() {
+                Some(k) if !k.is_empty() => start_key = Some(k.clone()),
+                _ => break,
+            }
+        }
+        if let Some(key) = start_key.take() {
+            if self.has_items_after(&query, attr, limit, key.clone())? {
+                start_key = Some(key);
+            }
+        }
+        Ok((res, start_key.as_ref().map(encode_start_key)))
+    }
+
+    /// Whether `query` finds any of this tenant's items after `start_key`,
+    /// reading `limit` items at a time until one turns up or the results end.
+    fn has_items_after(
+        &self,
+        query: &QueryFluentBuilder,
+        attr: &str,
+        limit: usize,
+        start_key: Item,
+    ) -> Result<bool, CoreError> {
+        let mut start_key = Some(start_key);
+        while let Some(key) = start_key.take() {
+            let fut = query
+                .clone()
+                .limit(i32::try_from(limit.max(1)).unwrap_or(i32::MAX))
+                .set_exclusive_start_key(Some(key))
+                .send();
+            let out = self.block_on(fut).map_err(map_sdk_err)?;
+            if self.unscoped_items(out.items(), attr).next().is_some() {
+                return Ok(true);
+            }
+            start_key = out.last_evaluated_key().filter(|k| !k.is_empty()).cloned();
+        }
+        Ok(false)
+    }
+
+    /// The Shortlinks item stored under `key`, either a link or an alias.
+    fn get_shortlinks_item(
+        &self,
+        key: &str,
+    ) -> Result<Option<HashMap<String, AttributeValue>>, CoreError> {
         let table = self.table_shortlinks.clone();
-        let key_slug = slug.as_str().to_string();
+        let key_slug = self.key_value(key);
         let fut = async {
             self.client
                 .get_item()
                 .table_name(table)
-                .key("slug", AttributeValue::S(key_slug))
+                .key("slug", key_slug)
                 .send()
                 .await
         };
         let out = self.block_on(fut).map_err(map_sdk_err)?;
-        if let Some(item) = out.item() {
-            Ok(Some(item_to_domain(item)?))
-        } else {
-            Ok(None)
+        Ok(out.item().and_then(|it| self.unscope_item(it, "slug")))
+    }
+
+    /// The links `scope` can access that pass `options`: the user's own from
+    /// `created_by-index`, each group's from `group_id-index` and the shared
+    /// ones by key. Sorted by `created_at`, each index is read from the
+    /// cursor's second on and only until it can no longer change the page;
+    /// the reads are then merged and paged here. Other sorts
+    /// read everything the user can access and page it in memory.
+    fn list_accessible(
+        &self,
+        options: &ListOptions,
+        scope: &AccessScope,
+    ) -> Result<ListResult<ShortLink>, CoreError> {
+        let after = match options.cursor {
+            Some(ref c) if options.sort == LinkSort::CreatedAt => Some(
+                domain::cursor::decode_link_position(c, options.sort, options.direction)?,
+            ),
+            _ => None,
+        };
+        let mut queries = vec![(
+            "created_by-index",
+            "created_by = :owner",
+            AttributeValue::S(scope.user.as_str().to_string()),
+        )];
+        for gid in &scope.group_ids {
+            queries.push((
+                "group_id-index",
+                "group_id = :owner",
+                AttributeValue::S(gid.clone()),
+            ));
+        }
+        let shared: Vec<String> = scope
+            .shared_slugs
+            .iter()
+            .map(|s| s.key().into_owned())
+            .collect();
+        let mut links = self.batch_get_links(&shared)?;
+        for (index, key_condition, owner) in queries {
+            let query = self
+                .links_query(index, key_condition)
+                .expression_attribute_values(":owner", owner);
+            if options.sort != LinkSort::CreatedAt {
+                links.extend(self.query_links(query, None)?);
+                continue;
+            }
+            links.extend(self.links_after(query, options, after.as_ref())?);
+        }
+
+        let mut seen = HashSet::new();
+        links.retain(|l| seen.insert(l.slug.key().into_owned()) && options.matches(l));
+        if options.sort != LinkSort::CreatedAt {
+            return options.page(links);
+        }
+        Ok(ListResult {
+            total: None,
+            ..options.page(links)?
+        })
+    }
+
+    /// The links of `query` (a GSI sorted by `created_at`) that pass
+    /// `options` and come after the cursor position `after`, in listing order.
+    /// Links created in the same second may come back in any order while a
+    /// page sorts them by key, so reading stops only once more than a page
+    /// matched and the read moved past the second of the first link beyond
+    /// the page, or when `LastEvaluatedKey` is absent.
+    fn links_after(
+        &self,
+        query: QueryFluentBuilder,
+        options: &ListOptions,
+        after: Option<&(Option<u128>, String)>,
+    ) -> Result<Vec<ShortLink>, CoreError> {
+        let desc = options.direction == SortDirection::Desc;
+        let mut query = query.scan_index_forward(!desc);
+        if let Some((Some(nanos), _)) = after {
+            let secs = u64::try_from(nanos / 1_000_000_000).unwrap_or(u64::MAX);
+            let key_condition = format!(
+                "{} AND created_at {} :after",
+                query
+                    .get_key_condition_expression()
+                    .clone()
+                    .unwrap_or_default(),
+                if desc { "<=" } else { ">=" }
+            );
+            query = query
+                .key_condition_expression(key_condition)
+                .expression_attribute_values(":after", AttributeValue::N(secs.to_string()));
+        }
+        let is_after = |l: &ShortLink| {
+            after.is_none_or(|a| {
+                let position = (options.sort.value(l), l.slug.key().into_owned());
+                if desc {
+                    position < *a
+                } else {
+                    position > *a
+                }
+            })
+        };
+        // Without a cursor the page may start at an offset
+        let wanted = options.limit + after.map_or(options.offset, |_| 0);
+        let query = query.limit(i32::try_from(wanted + 1).unwrap_or(i32::MAX));
+
+        let mut res: Vec<ShortLink> = Vec::new();
+        let mut last_read = None;
+        let mut start_key = None;
+        loop {
+            let fut = query
+                .clone()
+                .set_exclusive_start_key(start_key.take())
+                .send();
+            let out = self.block_on(fut).map_err(map_sdk_err)?;
+            for item in self.unscoped_items(out.items(), "slug") {
+                let Ok(link) = item_to_domain(&item) else {
+                    continue;
+                };
+                last_read = Some(system_time_to_secs(link.created_at));
+                if options.matches(&link) && is_after(&link) {
+                    res.push(link);
+                }
+            }
+            if let Some(beyond) = res.get(wanted) {
+                if last_read != Some(system_time_to_secs(beyond.created_at)) {
+                    break;
+                }
+            }
+            match out.last_evaluated_key() {
+                Some(k) if !k.is_empty() => start_key = Some(k.clone()),
+                _ => break,
+            }
+        }
+        Ok(res)
+    }
+
+    /// The links stored under `keys`, read with BatchGetItem 100 keys at a
+    /// time. Missing keys and aliases are skipped.
+    fn batch_get_links(&self, keys: &[String]) -> Result<Vec<ShortLink>, CoreError> {
+        use aws_sdk_dynamodb::types::KeysAndAttributes;
+
+        let table = &self.table_shortlinks;
+        let mut res = Vec::new();
+        for chunk in keys.chunks(100) {
+            let keys = chunk
+                .iter()
+                .map(|k| HashMap::from([("slug".to_string(), self.key_value(k))]))
+                .collect();
+            let mut pending = Some(
+                KeysAndAttributes::builder()
+                    .set_keys(Some(keys))
+                    .build()
+                    .map_err(|e| CoreError::Repository(format!("dynamo request: {e}")))?,
+            );
+            // Keys left unprocessed under load come back to be asked for again
+            while let Some(request) = pending.take() {
+                let fut = self
+                    .client
+                    .batch_get_item()
+                    .request_items(table, request)
+                    .send();
+                let out = self.block_on(fut).map_err(map_sdk_err)?;
+                if let Some(items) = out.responses().and_then(|r| r.get(table)) {
+                    res.extend(
+                        self.unscoped_items(items, "slug")
+                            .filter_map(|it| item_to_domain(&it).ok()),
+                    );
+                }
+                pending = out
+                    .unprocessed_keys()
+                    .and_then(|u| u.get(table))
+                    .filter(|k| !k.keys().is_empty())
+                    .cloned();
+            }
+        }
+        Ok(res)
+    }
+
+    /// Run `items` as one TransactWriteItems call. When a condition fails,
+    /// `on_failed` gets the index of the first item whose condition did not
+    /// hold; a transaction cancelled by a concurrent one is a conflict.
+    fn transact_write(
+        &self,
+        items: Vec<TransactWriteItem>,
+        on_failed: impl FnOnce(usize) -> CoreError,
+    ) -> Result<(), CoreError> {
+        use aws_sdk_dynamodb::operation::transact_write_items::TransactWriteItemsError;
+
+        let fut = self
+            .client
+            .transact_write_items()
+            .set_transact_items(Some(items))
+            .send();
+        let err = match self.block_on(fut) {
+            Ok(_) => return Ok(()),
+            Err(e) => e,
+        };
+        if let Some(TransactWriteItemsError::TransactionCanceledException(ex)) =
+            err.as_service_error()
+        {
+            let reasons = ex.cancellation_reasons();
+            if let Some(failed) = reasons
+                .iter()
+                .position(|r| r.code() == Some("ConditionalCheckFailed"))
+            {
+                return Err(on_failed(failed));
+            }
+            if reasons
+                .iter()
+                .any(|r| r.code() == Some("TransactionConflict"))
+            {
+                return Err(CoreError::Conflict("concurrent update, try again".into()));
+            }
+        }
+        Err(map_sdk_err(err))
+    }
+
+    /// Add the attributes the GSIs are keyed on to items written before those
+    /// indexes existed, in every tenant: `tenant` on links and audit entries,
+    /// `target_key` and `target_host` on links. Links whose `group_id` was
+    /// stored as NULL lose it, since GSI keys cannot be NULL. Organizations
+    /// missing a host item are written again to add them. Returns how many
+    /// items changed; running it again only picks up what is still missing.
+    pub fn backfill_index_attributes(&self) -> Result<usize, CoreError> {
+        let mut count = 0;
+        for item in self.scan_all(
+            &self.table_shortlinks,
+            "attribute_exists(original_url) AND (attribute_not_exists(tenant) \
+             OR attribute_not_exists(target_key) OR attribute_type(group_id, :null))",
+            Some(HashMap::from([(
+                ":null".to_string(),
+                AttributeValue::S("NULL".into()),
+            )])),
+        )? {
+            let (Some(slug), Some(url)) = (
+                item.get("slug").and_then(|v| v.as_s().ok()),
+                item.get("original_url").and_then(|v| v.as_s().ok()),
+            ) else {
+                continue;
+            };
+            let mut update_expr = "SET tenant = :tenant, target_key = :tk".to_string();
+            let host = domain::validate::url_host(url);
+            if host.is_some() {
+                update_expr.push_str(", target_host = :th");
+            }
+            if item.get("group_id").is_some_and(|v| v.is_null()) {
+                update_expr.push_str(" REMOVE group_id");
+            }
+            let table = self.table_shortlinks.clone();
+            let fut = async {
+                let mut req = self
+                    .client
+                    .update_item()
+                    .table_name(table)
+                    .key("slug", AttributeValue::S(slug.clone()))
+                    .update_expression(update_expr)
+                    .expression_attribute_values(":tenant", stored_key_tenant(slug))
+                    .expression_attribute_values(
+                        ":tk",
+                        AttributeValue::S(domain::validate::canonical_url(url)),
+                    );
+                if let Some(host) = host {
+                    req = req.expression_attribute_values(":th", AttributeValue::S(host));
+                }
+                req.send().await
+            };
+            self.block_on(fut).map_err(map_sdk_err)?;
+            count += 1;
+        }
+        for item in self.scan_all(&self.table_audit, "attribute_not_exists(tenant)", None)? {
+            let Some(id) = item.get("id").and_then(|v| v.as_s().ok()) else {
+                continue;
+            };
+            let table = self.table_audit.clone();
+            let fut = async {
+                self.client
+                    .update_item()
+                    .table_name(table)
+                    .key("id", AttributeValue::S(id.clone()))
+                    .update_expression("SET tenant = :tenant")
+                    .expression_attribute_values(":tenant", stored_key_tenant(id))
+                    .send()
+                    .await
+            };
+            self.block_on(fut).map_err(map_sdk_err)?;
+            count += 1;
+        }
+        for org in self.list_organizations()? {
+            let mut missing = false;
+            for host in &org.hosts {
+                if self.get_organization_by_host(host)?.is_none() {
+                    missing = true;
+                    break;
+                }
+            }
+            if missing {
+                self.put_organization(org)?;
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    /// Every item of `table` matching `filter`, across tenants.
+    fn scan_all(
+        &self,
+        table: &str,
+        filter: &str,
+        values: Option<HashMap<String, AttributeValue>>,
+    ) -> Result<Vec<HashMap<String, AttributeValue>>, CoreError> {
+        let mut res = Vec::new();
+        let mut start_key = None;
+        loop {
+            let fut = self
+                .client
+                .scan()
+                .table_name(table)
+                .filter_expression(filter)
+                .set_expression_attribute_values(values.clone())
+                .set_exclusive_start_key(start_key.take())
+                .send();
+            let out = self.block_on(fut).map_err(map_sdk_err)?;
+            res.extend(out.items().iter().cloned());
+            match out.last_evaluated_key() {
+                Some(k) if !k.is_empty() => start_key = Some(k.clone()),
+                _ => break,
+            }
+        }
+        Ok(res)
+    }
+
+    /// Write the alias item `alias -> target` under `condition`.
+    fn put_alias_item(&self, alias: &str, target: &str, condition: &str) -> Result<(), CoreError> {
+        let table = self.table_shortlinks.clone();
+        let fut = async {
+            self.client
+                .put_item()
+                .table_name(table)
+                .item("slug", self.key_value(alias))
+                .item("alias_of", AttributeValue::S(target.to_string()))
+                .condition_expression(condition)
+                .send()
+                .await
+        };
+        self.block_on(fut).map_err(|e| match e.as_service_error() {
+            Some(se) if se.code() == Some("ConditionalCheckFailedException") => {
+                CoreError::AlreadyExists
+            }
+            _ => map_sdk_err(e),
+        })?;
+        Ok(())
+    }
+}
+
+impl TenantScoped for DynamoRepo {
+    fn for_tenant(&self, tenant: &TenantId) -> Self {
+        Self {
+            tenant: tenant.clone(),
+            ..self.clone()
+        }
+    }
+
+    fn tenant(&self) -> &TenantId {
+        &self.tenant
+    }
+}
+
+impl LinkRepository for DynamoRepo {
+    fn get(&self, slug: &Slug) -> Result<Option<ShortLink>, CoreError> {
+        let mut item = self.get_shortlinks_item(&slug.key())?;
+        if let Some(target) = item.as_ref().and_then(alias_target) {
+            item = self.get_shortlinks_item(&target)?;
         }
+        item.map(|it| item_to_domain(&it)).transpose()
     }
 
     fn put(&self, link: ShortLink) -> Result<(), CoreError> {
         // Always use a conditional put to avoid accidental overwrite
         let table = self.table_shortlinks.clone();
-        let item = domain_to_item(&link);
+        let mut item = self.scope_item(domain_to_item(&link), "slug");
+        item.insert("tenant".into(), self.tenant_value());
         let fut = async {
             self.client
                 .put_item()
@@ -244,22 +904,16 @@ impl LinkRepository for DynamoRepo {
     }
 
     fn list(&self, limit: usize) -> Result<Vec<ShortLink>, CoreError> {
-        let table = self.table_shortlinks.clone();
-        let lim = limit as i32;
-        let fut = async { self.client.scan().table_name(table).limit(lim).send().await };
-        let out = self.block_on(fut).map_err(map_sdk_err)?;
-        let mut res = Vec::new();
-        for it in out.items().iter() {
-            if let Ok(sl) = item_to_domain(it) {
-                res.push(sl);
-            }
-        }
-        Ok(res)
+        let query = self
+            .links_query("tenant-index", "tenant = :tenant")
+            .filter_expression("attribute_not_exists(deleted_at)")
+            .expression_attribute_values(":tenant", self.tenant_value());
+        self.query_links(query, Some(limit))
     }
 
     fn update(&self, link: &ShortLink) -> Result<(), CoreError> {
         let table = self.table_shortlinks.clone();
-        let slug = link.slug.as_str().to_string();
+        let slug = self.key_value(&link.slug.key());
         let original_url = link.original_url.clone();
         let is_active = link.is_active;
         let updated_at = link.updated_at.map(system_time_to_secs);
@@ -268,19 +922,47 @@ impl LinkRepository for DynamoRepo {
         let description = link.description.clone();
         let redirect_delay = link.redirect_delay;
         let group_id = link.group_id.clone();
+        let visibility = link.visibility.as_str().to_string();
+        let target_key = link.target_key();
+        let target_host = link.target_host();
+        // GSI key attributes cannot be NULL, so unset ones are removed
+        let mut set_parts = vec![
+            "original_url = :url",
+            "is_active = :active",
+            "updated_at = :ts",
+            "expires_at = :exp",
+            "activate_at = :act",
+            "description = :desc",
+            "redirect_delay = :delay",
+            "visibility = :vis",
+            "target_key = :tk",
+            "tenant = :tenant",
+        ];
+        let mut remove_parts = Vec::new();
+        match group_id {
+            Some(_) => set_parts.push("group_id = :gid"),
+            None => remove_parts.push("group_id"),
+        }
+        match target_host {
+            Some(_) => set_parts.push("target_host = :th"),
+            None => remove_parts.push("target_host"),
+        }
+        let mut update_expr = format!("SET {}", set_parts.join(", "));
+        if !remove_parts.is_empty() {
+            update_expr.push_str(&format!(" REMOVE {}", remove_parts.join(", ")));
+        }
 
         let fut = async {
             let mut req = self
                 .client
                 .update_item()
                 .table_name(table)
-                .key("slug", AttributeValue::S(slug))
-                .update_expression(
-                    "SET original_url = :url, is_active = :active, updated_at = :ts, \
-                     expires_at = :exp, activate_at = :act, description = :desc, \
-                     redirect_delay = :delay, group_id = :gid",
-                )
+                .key("slug", slug)
+                .update_expression(update_expr)
                 .expression_attribute_values(":url", AttributeValue::S(original_url))
+                .expression_attribute_values(":tenant", self.tenant_value())
+                .expression_attribute_values(":tk", AttributeValue::S(target_key))
+                .expression_attribute_values(":vis", AttributeValue::S(visibility))
                 .expression_attribute_values(":active", AttributeValue::Bool(is_active))
                 .condition_expression("attribute_exists(slug)");
 
@@ -315,10 +997,12 @@ impl LinkRepository for DynamoRepo {
                 }
                 None => req.expression_attribute_values(":delay", AttributeValue::Null(true)),
             };
-            req = match group_id {
-                Some(gid) => req.expression_attribute_values(":gid", AttributeValue::S(gid)),
-                None => req.expression_attribute_values(":gid", AttributeValue::Null(true)),
-            };
+            if let Some(gid) = group_id {
+                req = req.expression_attribute_values(":gid", AttributeValue::S(gid));
+            }
+            if let Some(host) = target_host {
+                req = req.expression_attribute_values(":th", AttributeValue::S(host));
+            }
 
             req.send().await
         };
@@ -330,17 +1014,21 @@ impl LinkRepository for DynamoRepo {
     }
 
     fn increment_click(&self, slug: &Slug) -> Result<(), CoreError> {
+        self.add_clicks(slug, 1)
+    }
+
+    fn add_clicks(&self, slug: &Slug, clicks: u64) -> Result<(), CoreError> {
         let table = self.table_shortlinks.clone();
-        let slug_str = slug.as_str().to_string();
+        let slug_str = self.key_value(&slug.key());
 
         let fut = async {
             self.client
                 .update_item()
                 .table_name(table)
-                .key("slug", AttributeValue::S(slug_str))
+                .key("slug", slug_str)
                 .update_expression("SET click_count = if_not_exists(click_count, :zero) + :inc")
                 .expression_attribute_values(":zero", AttributeValue::N("0".into()))
-                .expression_attribute_values(":inc", AttributeValue::N("1".into()))
+                .expression_attribute_values(":inc", AttributeValue::N(clicks.to_string()))
                 .condition_expression("attribute_exists(slug)")
                 .send()
                 .await
@@ -357,40 +1045,23 @@ impl LinkRepository for DynamoRepo {
         email: &UserEmail,
         limit: usize,
     ) -> Result<Vec<ShortLink>, CoreError> {
-        let table = self.table_shortlinks.clone();
-        let lim = limit as i32;
-        let email_str = email.as_str().to_string();
-
-        let fut = async {
-            self.client
-                .scan()
-                .table_name(table)
-                .limit(lim)
-                .filter_expression("created_by = :email AND attribute_not_exists(deleted_at)")
-                .expression_attribute_values(":email", AttributeValue::S(email_str))
-                .send()
-                .await
-        };
-        let out = self.block_on(fut).map_err(map_sdk_err)?;
-        let mut res = Vec::new();
-        for it in out.items().iter() {
-            if let Ok(sl) = item_to_domain(it) {
-                res.push(sl);
-            }
-        }
-        Ok(res)
+        let query = self
+            .links_query("created_by-index", "created_by = :email")
+            .filter_expression("attribute_not_exists(deleted_at)")
+            .expression_attribute_values(":email", AttributeValue::S(email.as_str().to_string()));
+        self.query_links(query, Some(limit))
     }
 
     fn delete(&self, slug: &Slug, deleted_at: SystemTime) -> Result<(), CoreError> {
         let table = self.table_shortlinks.clone();
-        let slug_str = slug.as_str().to_string();
+        let slug_str = self.key_value(&slug.key());
         let deleted_at_secs = system_time_to_secs(deleted_at);
 
         let fut = async {
             self.client
                 .update_item()
                 .table_name(table)
-                .key("slug", AttributeValue::S(slug_str))
+                .key("slug", slug_str)
                 .update_expression("SET deleted_at = :ts")
                 .expression_attribute_values(":ts", AttributeValue::N(deleted_at_secs.to_string()))
                 .condition_expression("attribute_exists(slug) AND attribute_not_exists(deleted_at)")
@@ -401,53 +1072,130 @@ impl LinkRepository for DynamoRepo {
             Some(se) if se.code() == Some("ConditionalCheckFailedException") => CoreError::NotFound,
             _ => map_sdk_err(e),
         })?;
-        Ok(())
+        self.drop_grants(slug)
     }
 
     fn search(&self, query: &str, limit: usize) -> Result<Vec<ShortLink>, CoreError> {
-        let table = self.table_shortlinks.clone();
-        let lim = limit as i32;
-        let q = query.to_lowercase();
-
-        let fut = async {
-            self.client.scan()
-                .table_name(table)
-                .limit(lim)
-                .filter_expression("attribute_not_exists(deleted_at) AND (contains(#slug, :q) OR contains(original_url, :q) OR contains(description, :q))")
-                .expression_attribute_names("#slug", "slug")
-                .expression_attribute_values(":q", AttributeValue::S(q))
-                .send()
-                .await
-        };
-        let out = self.block_on(fut).map_err(map_sdk_err)?;
-        let mut res = Vec::new();
-        for it in out.items().iter() {
-            if let Ok(sl) = item_to_domain(it) {
-                res.push(sl);
-            }
-        }
-        Ok(res)
+        let query = self
+            .links_query("tenant-index", "tenant = :tenant")
+            .filter_expression(
+                "attribute_not_exists(deleted_at) AND (contains(#slug, :q) \
+                 OR contains(original_url, :q) OR contains(description, :q))",
+            )
+            .expression_attribute_names("#slug", "slug")
+            .expression_attribute_values(":tenant", self.tenant_value())
+            .expression_attribute_values(":q", AttributeValue::S(query.to_lowercase()));
+        self.query_links(query, Some(limit))
     }
 
     fn list_paginated(&self, options: &ListOptions) -> Result<ListResult<ShortLink>, CoreError> {
-        // DynamoDB doesn't support offset-based pagination well, so we fetch all and filter
-        // For production at scale, consider using a GSI or different pagination strategy
-        let table = self.table_shortlinks.clone();
+        // Pages are read from the narrowest index (the creator's, the group's or
+        // the whole tenant's) in creation order and resume from the encoded
+        // `LastEvaluatedKey`. Offsets are not supported. Only `created_at` is
+        // sorted by DynamoDB: the indexes have no other sort keys, so the other
+        // orders (and accessible scopes) read every match and page here.
+        if let Some(ref scope) = options.accessible_to {
+            return self.list_accessible(options, scope);
+        }
         let mut filter_parts = Vec::new();
         let mut expr_values: HashMap<String, AttributeValue> = HashMap::new();
         let mut expr_names: HashMap<String, String> = HashMap::new();
 
-        if !options.include_deleted {
-            filter_parts.push("attribute_not_exists(deleted_at)".to_string());
-        }
-        if let Some(ref email) = options.created_by {
-            filter_parts.push("created_by = :email".to_string());
+        let (index, mut key_condition) = if let Some(ref email) = options.created_by {
             expr_values.insert(
                 ":email".into(),
                 AttributeValue::S(email.as_str().to_string()),
             );
+            ("created_by-index", "created_by = :email".to_string())
+        } else if let Some(ref gid) = options.group_id {
+            expr_values.insert(":gid".into(), AttributeValue::S(gid.clone()));
+            ("group_id-index", "group_id = :gid".to_string())
+        } else {
+            expr_values.insert(":tenant".into(), self.tenant_value());
+            ("tenant-index", "tenant = :tenant".to_string())
+        };
+        // Every index is sorted by `created_at`, a key attribute, so its range
+        // narrows the key condition instead of the filter
+        let secs = |t: SystemTime| AttributeValue::N(system_time_to_secs(t).to_string());
+        match (options.created.from, options.created.to) {
+            (Some(from), Some(to)) => {
+                let Some(last) = system_time_to_secs(to).checked_sub(1) else {
+                    return Ok(empty_page());
+                };
+                if last < system_time_to_secs(from) {
+                    return Ok(empty_page());
+                }
+                key_condition.push_str(" AND created_at BETWEEN :created_from AND :created_last");
+                expr_values.insert(":created_from".into(), secs(from));
+                expr_values.insert(":created_last".into(), AttributeValue::N(last.to_string()));
+            }
+            (Some(from), None) => {
+                key_condition.push_str(" AND created_at >= :created_from");
+                expr_values.insert(":created_from".into(), secs(from));
+            }
+            (None, Some(to)) => {
+                key_condition.push_str(" AND created_at < :created_to");
+                expr_values.insert(":created_to".into(), secs(to));
+            }
+            (None, None) => {}
+        }
+        let status_filter = match options.status {
+            None if options.include_deleted => None,
+            None => Some("attribute_not_exists(deleted_at)"),
+            Some(LinkStatus::Deleted) => Some("attribute_exists(deleted_at)"),
+            Some(LinkStatus::Inactive) => {
+                Some("attribute_not_exists(deleted_at) AND is_active = :false")
+            }
+            Some(LinkStatus::Active) => Some(
+                "attribute_not_exists(deleted_at) AND (attribute_not_exists(is_active) OR is_active = :true) \
+                 AND (attribute_not_exists(expires_at) OR expires_at > :now) \
+                 AND (attribute_not_exists(activate_at) OR activate_at <= :now)",
+            ),
+            Some(LinkStatus::Expired) => {
+                Some("attribute_not_exists(deleted_at) AND expires_at <= :now")
+            }
+            Some(LinkStatus::Scheduled) => {
+                Some("attribute_not_exists(deleted_at) AND activate_at > :now")
+            }
+        };
+        if let Some(status_filter) = status_filter {
+            for (name, value) in [
+                (":now", secs(options.status_time())),
+                (":true", AttributeValue::Bool(true)),
+                (":false", AttributeValue::Bool(false)),
+            ] {
+                if status_filter.contains(name) {
+                    expr_values.insert(name.into(), value);
+                }
+            }
+            filter_parts.push(format!("({status_filter})"));
+        }
+        for (attr, range) in [
+            ("updated_at", &options.updated),
+            ("expires_at", &options.expires),
+        ] {
+            if let Some(from) = range.from {
+                filter_parts.push(format!("{attr} >= :{attr}_from"));
+                expr_values.insert(format!(":{attr}_from"), secs(from));
+            }
+            if let Some(to) = range.to {
+                filter_parts.push(format!("{attr} < :{attr}_to"));
+                expr_values.insert(format!(":{attr}_to"), secs(to));
+            }
+        }
+        if let Some(ref host) = options.target_host {
+            filter_parts.push("target_host = :host".to_string());
+            expr_values.insert(":host".into(), AttributeValue::S(host.to_lowercase()));
         }
-        if let Some(ref gid) = options.group_id {
+        // The creator and group indexes span tenants; dropping other tenants'
+        // links in the filter keeps them out of the page
+        if !expr_values.contains_key(":tenant") {
+            filter_parts.push("tenant = :tenant".to_string());
+            expr_values.insert(":tenant".into(), self.tenant_value());
+        }
+        // Key attributes cannot be filtered on, so the group only becomes a
+        // filter when the creator's index is used
+        if let (Some(_), Some(ref gid)) = (&options.created_by, &options.group_id) {
             filter_parts.push("group_id = :gid".to_string());
             expr_values.insert(":gid".into(), AttributeValue::S(gid.clone()));
         }
@@ -460,71 +1208,81 @@ impl LinkRepository for DynamoRepo {
             expr_values.insert(":q".into(), AttributeValue::S(q.to_lowercase()));
         }
 
-        let filter_expr = if filter_parts.is_empty() {
-            None
-        } else {
-            Some(filter_parts.join(" AND "))
-        };
-
-        let fut = async {
-            let mut req = self.client.scan().table_name(table);
-            if let Some(expr) = filter_expr {
-                req = req.filter_expression(expr);
-            }
-            for (k, v) in expr_values {
-                req = req.expression_attribute_values(k, v);
-            }
-            for (k, v) in expr_names {
-                req = req.expression_attribute_names(k, v);
-            }
-            req.send().await
-        };
-        let out = self.block_on(fut).map_err(map_sdk_err)?;
-        let mut all_items: Vec<ShortLink> = out
-            .items()
+        let mut query = self
+            .links_query(index, &key_condition)
+            .scan_index_forward(options.direction == SortDirection::Asc)
+            .set_expression_attribute_values(Some(expr_values));
+        if !filter_parts.is_empty() {
+            query = query.filter_expression(filter_parts.join(" AND "));
+        }
+        if !expr_names.is_empty() {
+            query = query.set_expression_attribute_names(Some(expr_names));
+        }
+        if options.sort != LinkSort::CreatedAt {
+            return options.page(self.query_links(query, None)?);
+        }
+        let (items, next_cursor) =
+            self.query_page(query, "slug", options.limit, options.cursor.as_deref())?;
+        let items = items
             .iter()
             .filter_map(|it| item_to_domain(it).ok())
             .collect();
 
-        // Sort by created_at desc
-        all_items.sort_by(|a, b| b.created_at.cmp(&a.created_at));
-
-        let total = all_items.len();
-        let has_more = options.offset + options.limit < total;
-        let items: Vec<_> = all_items
-            .into_iter()
-            .skip(options.offset)
-            .take(options.limit)
-            .collect();
-
         Ok(ListResult {
             items,
-            total,
-            has_more,
+            total: None,
+            has_more: next_cursor.is_some(),
+            next_cursor,
         })
     }
 
     fn list_by_group(&self, group_id: &str, limit: usize) -> Result<Vec<ShortLink>, CoreError> {
-        let table = self.table_shortlinks.clone();
-        let lim = limit as i32;
+        // Group link limits rely on this count, so it keeps paging until
+        // `limit` links matched
+        let query = self
+            .links_query("group_id-index", "group_id = :gid")
+            .filter_expression("attribute_not_exists(deleted_at)")
+            .expression_attribute_values(":gid", AttributeValue::S(group_id.to_string()));
+        self.query_links(query, Some(limit))
+    }
 
-        let fut = async {
-            self.client
-                .scan()
-                .table_name(table)
-                .limit(lim)
-                .filter_expression("group_id = :gid AND attribute_not_exists(deleted_at)")
-                .expression_attribute_values(":gid", AttributeValue::S(group_id.to_string()))
-                .send()
-                .await
+    fn list_by_target(
+        &self,
+        key: &str,
+        mode: TargetMatch,
+        limit: usize,
+    ) -> Result<Vec<ShortLink>, CoreError> {
+        // Uses the `target_key-index` and `target_host-index` GSIs (see
+        // infra/sam/template.yaml). Exact matches come back oldest first and
+        // stop once `limit` links matched; the host index is ordered by target,
+        // so host and prefix matches are read in full and sorted here.
+        let (index, condition, host) = match mode {
+            TargetMatch::Exact => ("target_key-index", "target_key = :k", None),
+            TargetMatch::Prefix => (
+                "target_host-index",
+                "target_host = :h AND begins_with(target_key, :k)",
+                Some(
+                    domain::validate::url_host(key)
+                        .ok_or_else(|| CoreError::InvalidUrl("missing host".into()))?,
+                ),
+            ),
+            TargetMatch::Host => ("target_host-index", "target_host = :k", None),
         };
-        let out = self.block_on(fut).map_err(map_sdk_err)?;
-        let mut res = Vec::new();
-        for it in out.items().iter() {
-            if let Ok(sl) = item_to_domain(it) {
-                res.push(sl);
-            }
+        let mut query = self
+            .client
+            .query()
+            .table_name(&self.table_shortlinks)
+            .index_name(index)
+            .key_condition_expression(condition)
+            .filter_expression("attribute_not_exists(deleted_at)")
+            .expression_attribute_values(":k", AttributeValue::S(key.to_string()));
+        if let Some(host) = host {
+            query = query.expression_attribute_values(":h", AttributeValue::S(host));
         }
+        let read_limit = (mode == TargetMatch::Exact).then_some(limit);
+        let mut res = self.query_links(query, read_limit)?;
+        res.sort_by_key(|link| link.created_at);
+        res.truncate(limit);
         Ok(res)
     }
 
@@ -533,12 +1291,12 @@ impl LinkRepository for DynamoRepo {
         let mut count = 0;
         for slug in slugs {
             let table = self.table_shortlinks.clone();
-            let slug_str = slug.as_str().to_string();
+            let slug_str = self.key_value(&slug.key());
             let fut = async {
                 self.client
                     .update_item()
                     .table_name(table)
-                    .key("slug", AttributeValue::S(slug_str))
+                    .key("slug", slug_str)
                     .update_expression("SET deleted_at = :ts")
                     .expression_attribute_values(
                         ":ts",
@@ -551,6 +1309,7 @@ impl LinkRepository for DynamoRepo {
                     .await
             };
             if self.block_on(fut).is_ok() {
+                self.drop_grants(slug)?;
                 count += 1;
             }
         }
@@ -567,12 +1326,12 @@ impl LinkRepository for DynamoRepo {
         let mut count = 0;
         for slug in slugs {
             let table = self.table_shortlinks.clone();
-            let slug_str = slug.as_str().to_string();
+            let slug_str = self.key_value(&slug.key());
             let fut = async {
                 self.client
                     .update_item()
                     .table_name(table)
-                    .key("slug", AttributeValue::S(slug_str))
+                    .key("slug", slug_str)
                     .update_expression("SET is_active = :active, updated_at = :ts")
                     .expression_attribute_values(":active", AttributeValue::Bool(is_active))
                     .expression_attribute_values(
@@ -589,6 +1348,96 @@ impl LinkRepository for DynamoRepo {
         }
         Ok(count)
     }
+
+    fn add_alias(&self, alias: &Slug, target: &Slug) -> Result<(), CoreError> {
+        let target = target.key();
+        match self.get_shortlinks_item(&target)? {
+            Some(item) if alias_target(&item).is_none() => {}
+            _ => return Err(CoreError::NotFound),
+        }
+        self.put_alias_item(&alias.key(), &target, "attribute_not_exists(slug)")
+    }
+
+    fn remove_alias(&self, alias: &Slug) -> Result<(), CoreError> {
+        let table = self.table_shortlinks.clone();
+        let fut = async {
+            self.client
+                .delete_item()
+                .table_name(table)
+                .key("slug", self.key_value(&alias.key()))
+                .condition_expression("attribute_exists(alias_of)")
+                .send()
+                .await
+        };
+        self.block_on(fut).map_err(|e| match e.as_service_error() {
+            Some(se) if se.code() == Some("ConditionalCheckFailedException") => CoreError::NotFound,
+            _ => map_sdk_err(e),
+        })?;
+        Ok(())
+    }
+
+    fn list_aliases(&self, target: &Slug) -> Result<Vec<Slug>, CoreError> {
+        let query = self
+            .client
+            .query()
+            .table_name(&self.table_shortlinks)
+            .index_name("alias_of-index")
+            .key_condition_expression("alias_of = :target")
+            .expression_attribute_values(":target", AttributeValue::S(target.key().into()));
+        let mut res = Vec::new();
+        for it in self.query_items(query, "slug", None)? {
+            if let Some(alias) = it.get("slug").and_then(|v| v.as_s().ok()) {
+                res.push(Slug::from_key(alias)?);
+            }
+        }
+        res.sort_by(|a, b| a.key().cmp(&b.key()));
+        Ok(res)
+    }
+
+    fn rename(&self, old: &Slug, new: &Slug) -> Result<ShortLink, CoreError> {
+        let old_key = old.key();
+        let mut link = match self.get_shortlinks_item(&old_key)? {
+            Some(item) if alias_target(&item).is_none() => item_to_domain(&item)?,
+            _ => return Err(CoreError::NotFound),
+        };
+        let aliases = self.list_aliases(old)?;
+        link.slug = new.clone();
+        // No transaction: the link is copied first, so a failure part-way leaves
+        // the old slug resolving rather than losing the link.
+        self.put(link.clone())?;
+        let new_key = new.key();
+        self.put_alias_item(&old_key, &new_key, "attribute_exists(original_url)")
+            .map_err(|e| match e {
+                CoreError::AlreadyExists => CoreError::NotFound,
+                e => e,
+            })?;
+        for alias in aliases {
+            self.put_alias_item(&alias.key(), &new_key, "attribute_exists(alias_of)")?;
+        }
+        // Collaborators follow the link to its new slug
+        for grant in self.list_grants(old)? {
+            let email = grant.user_email.clone();
+            self.put_grant(LinkGrant {
+                slug: new.clone(),
+                ..grant
+            })?;
+            self.remove_grant(old, &email)?;
+        }
+        Ok(link)
+    }
+}
+
+/// The `tenant` attribute of an item stored under the partition key `stored`.
+fn stored_key_tenant(stored: &str) -> AttributeValue {
+    let tenant = stored
+        .split_once(tenant::KEY_SEPARATOR)
+        .map_or(TenantId::DEFAULT, |(t, _)| t);
+    AttributeValue::S(tenant.to_string())
+}
+
+/// The key an alias item points at; `None` for link items.
+fn alias_target(item: &HashMap<String, AttributeValue>) -> Option<String> {
+    item.get("alias_of").and_then(|v| v.as_s().ok()).cloned()
 }
 
 fn map_sdk_err<E: ProvideErrorMetadata + std::fmt::Display>(e: E) -> CoreError {
@@ -600,6 +1449,55 @@ fn map_sdk_err<E: ProvideErrorMetadata + std::fmt::Display>(e: E) -> CoreError {
     CoreError::Repository(format!("dynamo error: {e}"))
 }
 
+/// A listing with nothing in it.
+fn empty_page() -> ListResult<ShortLink> {
+    ListResult {
+        items: Vec::new(),
+        total: Some(0),
+        has_more: false,
+        next_cursor: None,
+    }
+}
+
+/// A DynamoDB item (or key) by attribute name.
+type Item = HashMap<String, AttributeValue>;
+
+/// Cursor carrying a `LastEvaluatedKey`: name, type and value of each key
+/// attribute (all strings or numbers).
+fn encode_start_key(key: &Item) -> String {
+    let mut attrs: Vec<_> = key.iter().collect();
+    attrs.sort_by_key(|(name, _)| name.as_str());
+    let mut parts = Vec::new();
+    for (name, value) in attrs {
+        let (kind, value) = match value {
+            AttributeValue::N(n) => ("N", n.as_str()),
+            AttributeValue::S(s) => ("S", s.as_str()),
+            _ => continue,
+        };
+        parts.extend([name.as_str(), kind, value]);
+    }
+    domain::cursor::encode(&parts)
+}
+
+/// Inverse of [`encode_start_key`].
+fn decode_start_key(cursor: &str) -> Result<Item, CoreError> {
+    let parts = domain::cursor::decode(cursor)?;
+    if !parts.len().is_multiple_of(3) {
+        return Err(CoreError::InvalidCursor);
+    }
+    parts
+        .chunks(3)
+        .map(|attr| {
+            let value = match attr[1].as_str() {
+                "N" if attr[2].parse::<u64>().is_ok() => AttributeValue::N(attr[2].clone()),
+                "S" => AttributeValue::S(attr[2].clone()),
+                _ => return Err(CoreError::InvalidCursor),
+            };
+            Ok((attr[0].clone(), value))
+        })
+        .collect()
+}
+
 fn system_time_to_secs(t: SystemTime) -> u64 {
     t.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
 }
@@ -612,7 +1510,7 @@ fn domain_to_item(link: &ShortLink) -> HashMap<String, AttributeValue> {
     let mut m = HashMap::new();
     m.insert(
         "slug".into(),
-        AttributeValue::S(link.slug.as_str().to_string()),
+        AttributeValue::S(link.slug.key().into_owned()),
     );
     m.insert(
         "original_url".into(),
@@ -667,6 +1565,14 @@ fn domain_to_item(link: &ShortLink) -> HashMap<String, AttributeValue> {
     if let Some(ref group_id) = link.group_id {
         m.insert("group_id".into(), AttributeValue::S(group_id.clone()));
     }
+    m.insert(
+        "visibility".into(),
+        AttributeValue::S(link.visibility.as_str().to_string()),
+    );
+    m.insert("target_key".into(), AttributeValue::S(link.target_key()));
+    if let Some(host) = link.target_host() {
+        m.insert("target_host".into(), AttributeValue::S(host));
+    }
     m
 }
 
@@ -732,8 +1638,13 @@ fn item_to_domain(item: &HashMap<String, AttributeValue>) -> Result<ShortLink, C
         .get("group_id")
         .and_then(|v| v.as_s().ok())
         .map(|s| s.to_string());
+    let visibility = item
+        .get("visibility")
+        .and_then(|v| v.as_s().ok())
+        .and_then(|s| LinkVisibility::parse(s))
+        .unwrap_or_default();
 
-    let slug = Slug::new(slug.to_string())
+    let slug = Slug::from_key(slug)
         .map_err(|e| CoreError::Repository(format!("bad slug in item: {e}")))?;
     let created_by = UserEmail::new(created_by.to_string())
         .map_err(|_| CoreError::Repository("bad created_by".into()))?;
@@ -756,6 +1667,7 @@ fn item_to_domain(item: &HashMap<String, AttributeValue>) -> Result<ShortLink, C
         redirect_delay,
         deleted_at,
         group_id,
+        visibility,
     })
 }
 
@@ -778,9 +1690,64 @@ fn group_to_item(group: &LinkGroup) -> HashMap<String, AttributeValue> {
     if let Some(ref desc) = group.description {
         m.insert("description".into(), AttributeValue::S(desc.clone()));
     }
+    if let Some(ref parent) = group.parent_id {
+        m.insert("parent_id".into(), AttributeValue::S(parent.clone()));
+    }
+    for (name, value) in group_settings_attrs(&group.settings) {
+        if let Some(v) = value {
+            m.insert(name.into(), v);
+        }
+    }
     m
 }
 
+/// Group settings as (attribute, value) pairs; `None` means "not set".
+fn group_settings_attrs(s: &GroupSettings) -> [(&'static str, Option<AttributeValue>); 5] {
+    [
+        (
+            "default_expiry_secs",
+            s.default_expiry_secs
+                .map(|v| AttributeValue::N(v.to_string())),
+        ),
+        (
+            "default_redirect_delay",
+            s.default_redirect_delay
+                .map(|v| AttributeValue::N(v.to_string())),
+        ),
+        (
+            "allowed_hosts",
+            (!s.allowed_hosts.is_empty()).then(|| {
+                AttributeValue::L(
+                    s.allowed_hosts
+                        .iter()
+                        .map(|h| AttributeValue::S(h.clone()))
+                        .collect(),
+                )
+            }),
+        ),
+        ("slug_prefix", s.slug_prefix.clone().map(AttributeValue::S)),
+        (
+            "max_links",
+            s.max_links.map(|v| AttributeValue::N(v.to_string())),
+        ),
+    ]
+}
+
+fn item_to_group_settings(item: &HashMap<String, AttributeValue>) -> GroupSettings {
+    let get_n = |key: &str| item.get(key).and_then(|v| v.as_n().ok());
+    GroupSettings {
+        default_expiry_secs: get_n("default_expiry_secs").and_then(|s| s.parse().ok()),
+        default_redirect_delay: get_n("default_redirect_delay").and_then(|s| s.parse().ok()),
+        allowed_hosts: item
+            .get("allowed_hosts")
+            .and_then(|v| v.as_l().ok())
+            .map(|l| l.iter().filter_map(|h| h.as_s().ok().cloned()).collect())
+            .unwrap_or_default(),
+        slug_prefix: item.get("slug_prefix").and_then(|v| v.as_s().ok()).cloned(),
+        max_links: get_n("max_links").and_then(|s| s.parse().ok()),
+    }
+}
+
 fn item_to_group(item: &HashMap<String, AttributeValue>) -> Result<LinkGroup, CoreError> {
     let id = item
         .get("id")
@@ -805,6 +1772,7 @@ fn item_to_group(item: &HashMap<String, AttributeValue>) -> Result<LinkGroup, Co
         .get("description")
         .and_then(|v| v.as_s().ok())
         .map(|s| s.to_string());
+    let parent_id = item.get("parent_id").and_then(|v| v.as_s().ok()).cloned();
 
     let created_by = UserEmail::new(created_by.to_string())
         .map_err(|_| CoreError::Repository("bad created_by".into()))?;
@@ -815,6 +1783,8 @@ fn item_to_group(item: &HashMap<String, AttributeValue>) -> Result<LinkGroup, Co
         description,
         created_at: secs_to_system_time(created_at),
         created_by,
+        settings: item_to_group_settings(item),
+        parent_id,
     })
 }
 
@@ -886,7 +1856,7 @@ fn item_to_member(item: &HashMap<String, AttributeValue>) -> Result<GroupMember,
 impl GroupRepository for DynamoRepo {
     fn create_group(&self, group: LinkGroup) -> Result<(), CoreError> {
         let table = self.table_groups.clone();
-        let item = group_to_item(&group);
+        let item = self.scope_item(group_to_item(&group), "id");
         let fut = async {
             self.client
                 .put_item()
@@ -907,32 +1877,42 @@ impl GroupRepository for DynamoRepo {
 
     fn get_group(&self, id: &str) -> Result<Option<LinkGroup>, CoreError> {
         let table = self.table_groups.clone();
-        let id_str = id.to_string();
+        let id_str = self.key_value(id);
         let fut = async {
             self.client
                 .get_item()
                 .table_name(table)
-                .key("id", AttributeValue::S(id_str))
+                .key("id", id_str)
                 .send()
                 .await
         };
         let out = self.block_on(fut).map_err(map_sdk_err)?;
-        if let Some(item) = out.item() {
-            Ok(Some(item_to_group(item)?))
+        if let Some(item) = out.item().and_then(|it| self.unscope_item(it, "id")) {
+            Ok(Some(item_to_group(&item)?))
         } else {
             Ok(None)
         }
     }
 
-    fn list_groups(&self, _user_email: &UserEmail) -> Result<Vec<LinkGroup>, CoreError> {
-        // List all groups (filtering by membership is done via get_user_groups)
-        let table = self.table_groups.clone();
-        let fut = async { self.client.scan().table_name(table).send().await };
-        let out = self.block_on(fut).map_err(map_sdk_err)?;
-        let mut res = Vec::new();
-        for it in out.items().iter() {
-            if let Ok(g) = item_to_group(it) {
-                res.push(g);
+    fn list_groups(&self, user_email: &UserEmail) -> Result<Vec<LinkGroup>, CoreError> {
+        // Groups the user created or is a member of
+        let query = self
+            .client
+            .query()
+            .table_name(&self.table_groups)
+            .index_name("created_by-index")
+            .key_condition_expression("created_by = :email")
+            .expression_attribute_values(":email", AttributeValue::S(user_email.as_str().into()));
+        let mut res: Vec<LinkGroup> = self
+            .query_items(query, "id", None)?
+            .iter()
+            .filter_map(|it| item_to_group(it).ok())
+            .collect();
+        for membership in self.query_memberships(user_email)? {
+            if res.iter().all(|g| g.id != membership.group_id) {
+                if let Some(group) = self.get_group(&membership.group_id)? {
+                    res.push(group);
+                }
             }
         }
         Ok(res)
@@ -940,20 +1920,45 @@ impl GroupRepository for DynamoRepo {
 
     fn update_group(&self, group: &LinkGroup) -> Result<(), CoreError> {
         let table = self.table_groups.clone();
-        let id = group.id.clone();
+        let id = self.key_value(&group.id);
         let name = group.name.clone();
         let desc = group.description.clone();
 
+        // Set the attributes that are present and remove the ones that were cleared
+        let mut set_parts = vec!["#n = :name".to_string(), "description = :desc".to_string()];
+        let mut remove_parts = Vec::new();
+        let mut values = HashMap::new();
+        let parent = ("parent_id", group.parent_id.clone().map(AttributeValue::S));
+        for (attr, value) in group_settings_attrs(&group.settings)
+            .into_iter()
+            .chain([parent])
+        {
+            match value {
+                Some(v) => {
+                    set_parts.push(format!("{attr} = :{attr}"));
+                    values.insert(format!(":{attr}"), v);
+                }
+                None => remove_parts.push(attr),
+            }
+        }
+        let mut update_expr = format!("SET {}", set_parts.join(", "));
+        if !remove_parts.is_empty() {
+            update_expr.push_str(&format!(" REMOVE {}", remove_parts.join(", ")));
+        }
+
         let fut = async {
             let mut req = self
                 .client
                 .update_item()
                 .table_name(table)
-                .key("id", AttributeValue::S(id))
-                .update_expression("SET #n = :name, description = :desc")
+                .key("id", id)
+                .update_expression(update_expr)
                 .expression_attribute_names("#n", "name")
                 .expression_attribute_values(":name", AttributeValue::S(name))
                 .condition_expression("attribute_exists(id)");
+            for (k, v) in values {
+                req = req.expression_attribute_values(k, v);
+            }
 
             if let Some(d) = desc {
                 req = req.expression_attribute_values(":desc", AttributeValue::S(d));
@@ -970,14 +1975,75 @@ impl GroupRepository for DynamoRepo {
         Ok(())
     }
 
-    fn delete_group(&self, id: &str) -> Result<(), CoreError> {
+    fn list_child_groups(&self, parent_id: &str) -> Result<Vec<LinkGroup>, CoreError> {
+        let query = self
+            .client
+            .query()
+            .table_name(&self.table_groups)
+            .index_name("parent_id-index")
+            .key_condition_expression("parent_id = :pid")
+            .expression_attribute_values(":pid", AttributeValue::S(parent_id.to_string()));
+        Ok(self
+            .query_items(query, "id", None)?
+            .iter()
+            .filter_map(|it| item_to_group(it).ok())
+            .collect())
+    }
+
+    fn delete_group(
+        &self,
+        id: &str,
+        links: &GroupLinkDisposition,
+        at: SystemTime,
+    ) -> Result<usize, CoreError> {
+        let Some(group) = self.get_group(id)? else {
+            return Err(CoreError::NotFound);
+        };
+        if let GroupLinkDisposition::Reassign(target) = links {
+            if target == id {
+                return Err(CoreError::Conflict(
+                    "cannot reassign links to the group being deleted".into(),
+                ));
+            }
+            if self.get_group(target)?.is_none() {
+                return Err(CoreError::NotFound);
+            }
+        }
+
+        // No cross-table transaction here: links are moved first so a failure
+        // part-way leaves the group (and a retry) in place rather than orphans.
+        let slugs = self.group_link_slugs(id)?;
+        for slug in &slugs {
+            self.apply_link_disposition(slug, id, links, at)?;
+        }
+        for member in self.list_members(id)? {
+            let table = self.table_group_members.clone();
+            let fut = async {
+                self.client
+                    .delete_item()
+                    .table_name(table)
+                    .key("group_id", self.key_value(&member.group_id))
+                    .key(
+                        "user_email",
+                        AttributeValue::S(member.user_email.as_str().to_string()),
+                    )
+                    .send()
+                    .await
+            };
+            self.block_on(fut).map_err(map_sdk_err)?;
+        }
+        // Subgroups move up a level
+        for child in self.list_child_groups(id)? {
+            self.set_group_parent(&child.id, group.parent_id.as_deref())?;
+        }
+
         let table = self.table_groups.clone();
-        let id_str = id.to_string();
+        let id_str = self.key_value(id);
         let fut = async {
             self.client
                 .delete_item()
                 .table_name(table)
-                .key("id", AttributeValue::S(id_str))
+                .key("id", id_str)
                 .condition_expression("attribute_exists(id)")
                 .send()
                 .await
@@ -986,61 +2052,144 @@ impl GroupRepository for DynamoRepo {
             Some(se) if se.code() == Some("ConditionalCheckFailedException") => CoreError::NotFound,
             _ => map_sdk_err(e),
         })?;
-        Ok(())
+        Ok(slugs.len())
     }
 
     fn add_member(&self, member: GroupMember) -> Result<(), CoreError> {
+        // Never overwrite an existing membership, which may hold a higher role
         let table = self.table_group_members.clone();
-        let item = member_to_item(&member);
+        let item = self.scope_item(member_to_item(&member), "group_id");
         let fut = async {
             self.client
                 .put_item()
                 .table_name(table)
                 .set_item(Some(item))
+                .condition_expression("attribute_not_exists(user_email)")
                 .send()
                 .await
         };
-        self.block_on(fut).map_err(map_sdk_err)?;
+        self.block_on(fut).map_err(|e| match e.as_service_error() {
+            Some(se) if se.code() == Some("ConditionalCheckFailedException") => {
+                CoreError::AlreadyExists
+            }
+            _ => map_sdk_err(e),
+        })?;
         Ok(())
     }
 
     fn remove_member(&self, group_id: &str, user_email: &UserEmail) -> Result<(), CoreError> {
-        let table = self.table_group_members.clone();
-        let gid = group_id.to_string();
-        let email = user_email.as_str().to_string();
-        let fut = async {
-            self.client
-                .delete_item()
-                .table_name(table)
-                .key("group_id", AttributeValue::S(gid))
-                .key("user_email", AttributeValue::S(email))
-                .send()
-                .await
-        };
-        self.block_on(fut).map_err(map_sdk_err)?;
-        Ok(())
+        let members = self.list_members(group_id)?;
+        let target = members
+            .iter()
+            .find(|m| m.user_email.as_str() == user_email.as_str())
+            .ok_or(CoreError::NotFound)?;
+
+        // The delete only goes through if the member still holds the role we
+        // read, and removing an admin also checks that another admin is still
+        // one. Two admins removing each other cannot both succeed.
+        let mut items = vec![TransactWriteItem::builder()
+            .delete(
+                Delete::builder()
+                    .table_name(&self.table_group_members)
+                    .key("group_id", self.key_value(group_id))
+                    .key("user_email", AttributeValue::S(user_email.as_str().into()))
+                    .condition_expression("#role = :role")
+                    .expression_attribute_names("#role", "role")
+                    .expression_attribute_values(
+                        ":role",
+                        AttributeValue::S(target.role.as_str().into()),
+                    )
+                    .build()
+                    .map_err(|e| CoreError::Repository(format!("dynamo request: {e}")))?,
+            )
+            .build()];
+        if target.role == GroupRole::Admin {
+            let other = members
+                .iter()
+                .find(|m| m.role == GroupRole::Admin && m.user_email != target.user_email)
+                .ok_or_else(|| CoreError::Conflict("group must keep at least one admin".into()))?;
+            items.push(
+                TransactWriteItem::builder()
+                    .condition_check(
+                        ConditionCheck::builder()
+                            .table_name(&self.table_group_members)
+                            .key("group_id", self.key_value(group_id))
+                            .key(
+                                "user_email",
+                                AttributeValue::S(other.user_email.as_str().into()),
+                            )
+                            .condition_expression("#role = :admin")
+                            .expression_attribute_names("#role", "role")
+                            .expression_attribute_values(
+                                ":admin",
+                                AttributeValue::S(GroupRole::Admin.as_str().into()),
+                            )
+                            .build()
+                            .map_err(|e| CoreError::Repository(format!("dynamo request: {e}")))?,
+                    )
+                    .build(),
+            );
+        }
+        self.transact_write(items, |_| {
+            CoreError::Conflict("group members changed, try again".into())
+        })
+    }
+
+    fn transfer_ownership(&self, group_id: &str, new_owner: &UserEmail) -> Result<(), CoreError> {
+        // Owner and admin role change together, and only while the group and
+        // the membership both exist.
+        let items = vec![
+            TransactWriteItem::builder()
+                .update(
+                    Update::builder()
+                        .table_name(&self.table_groups)
+                        .key("id", self.key_value(group_id))
+                        .update_expression("SET created_by = :owner")
+                        .expression_attribute_values(
+                            ":owner",
+                            AttributeValue::S(new_owner.as_str().into()),
+                        )
+                        .condition_expression("attribute_exists(id)")
+                        .build()
+                        .map_err(|e| CoreError::Repository(format!("dynamo request: {e}")))?,
+                )
+                .build(),
+            TransactWriteItem::builder()
+                .update(
+                    Update::builder()
+                        .table_name(&self.table_group_members)
+                        .key("group_id", self.key_value(group_id))
+                        .key("user_email", AttributeValue::S(new_owner.as_str().into()))
+                        .update_expression("SET #role = :admin")
+                        .expression_attribute_names("#role", "role")
+                        .expression_attribute_values(
+                            ":admin",
+                            AttributeValue::S(GroupRole::Admin.as_str().into()),
+                        )
+                        .condition_expression("attribute_exists(user_email)")
+                        .build()
+                        .map_err(|e| CoreError::Repository(format!("dynamo request: {e}")))?,
+                )
+                .build(),
+        ];
+        self.transact_write(items, |failed| match failed {
+            0 => CoreError::NotFound,
+            _ => CoreError::Conflict("new owner must be a group member".into()),
+        })
     }
 
     fn list_members(&self, group_id: &str) -> Result<Vec<GroupMember>, CoreError> {
-        let table = self.table_group_members.clone();
-        let gid = group_id.to_string();
-        let fut = async {
-            self.client
-                .query()
-                .table_name(table)
-                .key_condition_expression("group_id = :gid")
-                .expression_attribute_values(":gid", AttributeValue::S(gid))
-                .send()
-                .await
-        };
-        let out = self.block_on(fut).map_err(map_sdk_err)?;
-        let mut res = Vec::new();
-        for it in out.items().iter() {
-            if let Ok(m) = item_to_member(it) {
-                res.push(m);
-            }
-        }
-        Ok(res)
+        let query = self
+            .client
+            .query()
+            .table_name(&self.table_group_members)
+            .key_condition_expression("group_id = :gid")
+            .expression_attribute_values(":gid", self.key_value(group_id));
+        Ok(self
+            .query_items(query, "group_id", None)?
+            .iter()
+            .filter_map(|it| item_to_member(it).ok())
+            .collect())
     }
 
     fn get_member(
@@ -1049,20 +2198,20 @@ impl GroupRepository for DynamoRepo {
         user_email: &UserEmail,
     ) -> Result<Option<GroupMember>, CoreError> {
         let table = self.table_group_members.clone();
-        let gid = group_id.to_string();
+        let gid = self.key_value(group_id);
         let email = user_email.as_str().to_string();
         let fut = async {
             self.client
                 .get_item()
                 .table_name(table)
-                .key("group_id", AttributeValue::S(gid))
+                .key("group_id", gid)
                 .key("user_email", AttributeValue::S(email))
                 .send()
                 .await
         };
         let out = self.block_on(fut).map_err(map_sdk_err)?;
-        if let Some(item) = out.item() {
-            Ok(Some(item_to_member(item)?))
+        if let Some(item) = out.item().and_then(|it| self.unscope_item(it, "group_id")) {
+            Ok(Some(item_to_member(&item)?))
         } else {
             Ok(None)
         }
@@ -1072,70 +2221,540 @@ impl GroupRepository for DynamoRepo {
         &self,
         user_email: &UserEmail,
     ) -> Result<Vec<(LinkGroup, GroupRole)>, CoreError> {
-        // First, get all memberships for this user via scan (GSI on user_email would be better)
-        let table = self.table_group_members.clone();
-        let email = user_email.as_str().to_string();
+        let mut results = Vec::new();
+        for member in self.query_memberships(user_email)? {
+            if let Ok(Some(group)) = self.get_group(&member.group_id) {
+                results.push((group, member.role));
+            }
+        }
+        hierarchy::with_inherited(self, results)
+    }
+}
+
+impl DynamoRepo {
+    /// The user's memberships, through the GroupMembers `user_email-index` GSI.
+    fn query_memberships(&self, user_email: &UserEmail) -> Result<Vec<GroupMember>, CoreError> {
+        let query = self
+            .client
+            .query()
+            .table_name(&self.table_group_members)
+            .index_name("user_email-index")
+            .key_condition_expression("user_email = :email")
+            .expression_attribute_values(":email", AttributeValue::S(user_email.as_str().into()));
+        Ok(self
+            .query_items(query, "group_id", None)?
+            .iter()
+            .filter_map(|it| item_to_member(it).ok())
+            .collect())
+    }
+
+    /// Every slug (deleted or not) that still points at `group_id`. Reads the
+    /// whole index, unlike `list_by_group`.
+    fn group_link_slugs(&self, group_id: &str) -> Result<Vec<String>, CoreError> {
+        let query = self
+            .links_query("group_id-index", "group_id = :gid")
+            .projection_expression("#slug")
+            .expression_attribute_names("#slug", "slug")
+            .expression_attribute_values(":gid", AttributeValue::S(group_id.to_string()));
+        Ok(self
+            .query_items(query, "slug", None)?
+            .iter()
+            .filter_map(|it| it.get("slug")?.as_s().ok().cloned())
+            .collect())
+    }
+
+    fn set_group_parent(&self, id: &str, parent: Option<&str>) -> Result<(), CoreError> {
+        let table = self.table_groups.clone();
         let fut = async {
-            self.client
-                .scan()
+            let req = self
+                .client
+                .update_item()
                 .table_name(table)
-                .filter_expression("user_email = :email")
-                .expression_attribute_values(":email", AttributeValue::S(email))
-                .send()
-                .await
+                .key("id", self.key_value(id))
+                .condition_expression("attribute_exists(id)");
+            let req = match parent {
+                Some(p) => req
+                    .update_expression("SET parent_id = :pid")
+                    .expression_attribute_values(":pid", AttributeValue::S(p.to_string())),
+                None => req.update_expression("REMOVE parent_id"),
+            };
+            req.send().await
         };
-        let out = self.block_on(fut).map_err(map_sdk_err)?;
+        self.block_on(fut).map_err(|e| match e.as_service_error() {
+            Some(se) if se.code() == Some("ConditionalCheckFailedException") => CoreError::NotFound,
+            _ => map_sdk_err(e),
+        })?;
+        Ok(())
+    }
 
-        let mut results = Vec::new();
-        for item in out.items().iter() {
-            if let Ok(member) = item_to_member(item) {
-                if let Ok(Some(group)) = self.get_group(&member.group_id) {
-                    results.push((group, member.role));
+    fn apply_link_disposition(
+        &self,
+        slug: &str,
+        group_id: &str,
+        links: &GroupLinkDisposition,
+        at: SystemTime,
+    ) -> Result<(), CoreError> {
+        let table = self.table_shortlinks.clone();
+        let ts = AttributeValue::N(system_time_to_secs(at).to_string());
+        let fut = async {
+            let req = self
+                .client
+                .update_item()
+                .table_name(table)
+                .key("slug", self.key_value(slug))
+                // Skip links that were moved elsewhere since the scan
+                .condition_expression("group_id = :gid")
+                .expression_attribute_values(":gid", AttributeValue::S(group_id.to_string()))
+                .expression_attribute_values(":ts", ts);
+            let req = match links {
+                GroupLinkDisposition::Reassign(target) => req
+                    .update_expression("SET group_id = :target, updated_at = :ts")
+                    .expression_attribute_values(":target", AttributeValue::S(target.clone())),
+                GroupLinkDisposition::Detach => {
+                    req.update_expression("SET updated_at = :ts REMOVE group_id")
                 }
+                GroupLinkDisposition::SoftDelete => req.update_expression(
+                    "SET updated_at = :ts, deleted_at = if_not_exists(deleted_at, :ts) REMOVE group_id",
+                ),
+            };
+            req.send().await
+        };
+        match self.block_on(fut) {
+            Ok(_) if matches!(links, GroupLinkDisposition::SoftDelete) => {
+                self.drop_grants(&Slug::from_key(slug)?)
             }
+            Ok(_) => Ok(()),
+            Err(e) => match e.as_service_error() {
+                Some(se) if se.code() == Some("ConditionalCheckFailedException") => Ok(()),
+                _ => Err(map_sdk_err(e)),
+            },
         }
-        Ok(results)
     }
 }
 
 // -------------------------
-// Click Repository
+// Invitation Repository
 // -------------------------
 
-fn click_to_item(event: &ClickEvent) -> HashMap<String, AttributeValue> {
+fn invitation_to_item(inv: &GroupInvitation) -> HashMap<String, AttributeValue> {
     let mut m = HashMap::new();
+    m.insert("id".into(), AttributeValue::S(inv.id.clone()));
+    m.insert("group_id".into(), AttributeValue::S(inv.group_id.clone()));
     m.insert(
-        "slug".into(),
-        AttributeValue::S(event.slug.as_str().to_string()),
+        "email".into(),
+        AttributeValue::S(inv.email.as_str().to_string()),
     );
     m.insert(
-        "clicked_at".into(),
-        AttributeValue::N(system_time_to_secs(event.clicked_at).to_string()),
+        "role".into(),
+        AttributeValue::S(inv.role.as_str().to_string()),
     );
-    if let Some(ref ua) = event.user_agent {
-        m.insert("user_agent".into(), AttributeValue::S(ua.clone()));
-    }
-    if let Some(ref referrer) = event.referrer {
-        m.insert("referrer".into(), AttributeValue::S(referrer.clone()));
-    }
-    if let Some(ref country) = event.country {
-        m.insert("country".into(), AttributeValue::S(country.clone()));
+    m.insert(
+        "invited_by".into(),
+        AttributeValue::S(inv.invited_by.as_str().to_string()),
+    );
+    m.insert(
+        "created_at".into(),
+        AttributeValue::N(system_time_to_secs(inv.created_at).to_string()),
+    );
+    m.insert(
+        "expires_at".into(),
+        AttributeValue::N(system_time_to_secs(inv.expires_at).to_string()),
+    );
+    m.insert(
+        "status".into(),
+        AttributeValue::S(inv.status.as_str().to_string()),
+    );
+    if let Some(t) = inv.responded_at {
+        m.insert(
+            "responded_at".into(),
+            AttributeValue::N(system_time_to_secs(t).to_string()),
+        );
     }
     m
 }
 
-fn item_to_click(item: &HashMap<String, AttributeValue>) -> Result<ClickEvent, CoreError> {
-    let slug = item
-        .get("slug")
-        .and_then(|v| v.as_s().ok())
-        .ok_or_else(|| CoreError::Repository("click missing slug".into()))?;
-    let clicked_at = item
-        .get("clicked_at")
-        .and_then(|v| v.as_n().ok())
-        .and_then(|s| s.parse::<u64>().ok())
-        .ok_or_else(|| CoreError::Repository("click missing clicked_at".into()))?;
-    let user_agent = item
-        .get("user_agent")
+fn item_to_invitation(
+    item: &HashMap<String, AttributeValue>,
+) -> Result<GroupInvitation, CoreError> {
+    let get_s = |key: &str| {
+        item.get(key)
+            .and_then(|v| v.as_s().ok())
+            .ok_or_else(|| CoreError::Repository(format!("invitation missing {key}")))
+    };
+    let get_n = |key: &str| {
+        item.get(key)
+            .and_then(|v| v.as_n().ok())
+            .and_then(|s| s.parse::<u64>().ok())
+    };
+
+    let email = UserEmail::new(get_s("email")?.to_string())
+        .map_err(|_| CoreError::Repository("bad email".into()))?;
+    let invited_by = UserEmail::new(get_s("invited_by")?.to_string())
+        .map_err(|_| CoreError::Repository("bad invited_by".into()))?;
+    let role =
+        GroupRole::parse(get_s("role")?).ok_or_else(|| CoreError::Repository("bad role".into()))?;
+    let status = InvitationStatus::parse(get_s("status")?)
+        .ok_or_else(|| CoreError::Repository("bad invitation status".into()))?;
+
+    Ok(GroupInvitation {
+        id: get_s("id")?.to_string(),
+        group_id: get_s("group_id")?.to_string(),
+        email,
+        role,
+        invited_by,
+        created_at: secs_to_system_time(
+            get_n("created_at")
+                .ok_or_else(|| CoreError::Repository("invitation missing created_at".into()))?,
+        ),
+        expires_at: secs_to_system_time(
+            get_n("expires_at")
+                .ok_or_else(|| CoreError::Repository("invitation missing expires_at".into()))?,
+        ),
+        status,
+        responded_at: get_n("responded_at").map(secs_to_system_time),
+    })
+}
+
+impl DynamoRepo {
+    /// Query one of the invitation GSIs (`email-index`, `group_id-index`).
+    fn query_invitations(
+        &self,
+        index: &str,
+        attr: &str,
+        value: &str,
+    ) -> Result<Vec<GroupInvitation>, CoreError> {
+        let table = self.table_invitations.clone();
+        let fut = async {
+            self.client
+                .query()
+                .table_name(table)
+                .index_name(index)
+                .key_condition_expression(format!("{attr} = :v"))
+                .expression_attribute_values(":v", AttributeValue::S(value.to_string()))
+                .send()
+                .await
+        };
+        let out = self.block_on(fut).map_err(map_sdk_err)?;
+        let mut res: Vec<_> = self
+            .unscoped_items(out.items(), "id")
+            .filter_map(|it| item_to_invitation(&it).ok())
+            .collect();
+        res.sort_by_key(|x| std::cmp::Reverse(x.created_at));
+        Ok(res)
+    }
+}
+
+impl InvitationRepository for DynamoRepo {
+    fn create_invitation(&self, invitation: GroupInvitation) -> Result<(), CoreError> {
+        let table = self.table_invitations.clone();
+        let item = self.scope_item(invitation_to_item(&invitation), "id");
+        let fut = async {
+            self.client
+                .put_item()
+                .table_name(table)
+                .set_item(Some(item))
+                .condition_expression("attribute_not_exists(id)")
+                .send()
+                .await
+        };
+        self.block_on(fut).map_err(|e| match e.as_service_error() {
+            Some(se) if se.code() == Some("ConditionalCheckFailedException") => {
+                CoreError::AlreadyExists
+            }
+            _ => map_sdk_err(e),
+        })?;
+        Ok(())
+    }
+
+    fn get_invitation(&self, id: &str) -> Result<Option<GroupInvitation>, CoreError> {
+        let table = self.table_invitations.clone();
+        let id_str = self.key_value(id);
+        let fut = async {
+            self.client
+                .get_item()
+                .table_name(table)
+                .key("id", id_str)
+                .send()
+                .await
+        };
+        let out = self.block_on(fut).map_err(map_sdk_err)?;
+        if let Some(item) = out.item().and_then(|it| self.unscope_item(it, "id")) {
+            Ok(Some(item_to_invitation(&item)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn list_invitations_for_user(
+        &self,
+        email: &UserEmail,
+    ) -> Result<Vec<GroupInvitation>, CoreError> {
+        self.query_invitations("email-index", "email", email.as_str())
+    }
+
+    fn list_invitations_for_group(
+        &self,
+        group_id: &str,
+    ) -> Result<Vec<GroupInvitation>, CoreError> {
+        self.query_invitations("group_id-index", "group_id", group_id)
+    }
+
+    fn respond_invitation(
+        &self,
+        id: &str,
+        status: InvitationStatus,
+        responded_at: SystemTime,
+    ) -> Result<(), CoreError> {
+        let table = self.table_invitations.clone();
+        let id_str = self.key_value(id);
+        let fut = async {
+            self.client
+                .update_item()
+                .table_name(table)
+                .key("id", id_str)
+                .update_expression("SET #status = :status, responded_at = :ts")
+                .condition_expression("#status = :pending")
+                .expression_attribute_names("#status", "status")
+                .expression_attribute_values(
+                    ":status",
+                    AttributeValue::S(status.as_str().to_string()),
+                )
+                .expression_attribute_values(
+                    ":ts",
+                    AttributeValue::N(system_time_to_secs(responded_at).to_string()),
+                )
+                .expression_attribute_values(
+                    ":pending",
+                    AttributeValue::S(InvitationStatus::Pending.as_str().to_string()),
+                )
+                .send()
+                .await
+        };
+        self.block_on(fut).map_err(|e| match e.as_service_error() {
+            Some(se) if se.code() == Some("ConditionalCheckFailedException") => CoreError::NotFound,
+            _ => map_sdk_err(e),
+        })?;
+        Ok(())
+    }
+}
+
+// -------------------------
+// Link Grant Repository
+// -------------------------
+
+fn grant_to_item(grant: &LinkGrant) -> HashMap<String, AttributeValue> {
+    let mut m = HashMap::new();
+    m.insert(
+        "slug".into(),
+        AttributeValue::S(grant.slug.key().into_owned()),
+    );
+    m.insert(
+        "user_email".into(),
+        AttributeValue::S(grant.user_email.as_str().to_string()),
+    );
+    m.insert(
+        "role".into(),
+        AttributeValue::S(grant.role.as_str().to_string()),
+    );
+    m.insert(
+        "granted_at".into(),
+        AttributeValue::N(system_time_to_secs(grant.granted_at).to_string()),
+    );
+    m.insert(
+        "granted_by".into(),
+        AttributeValue::S(grant.granted_by.as_str().to_string()),
+    );
+    m
+}
+
+fn item_to_grant(item: &HashMap<String, AttributeValue>) -> Result<LinkGrant, CoreError> {
+    let slug = item
+        .get("slug")
+        .and_then(|v| v.as_s().ok())
+        .ok_or_else(|| CoreError::Repository("grant missing slug".into()))?;
+    let user_email = item
+        .get("user_email")
+        .and_then(|v| v.as_s().ok())
+        .ok_or_else(|| CoreError::Repository("grant missing user_email".into()))?;
+    let role_str = item
+        .get("role")
+        .and_then(|v| v.as_s().ok())
+        .ok_or_else(|| CoreError::Repository("grant missing role".into()))?;
+    let granted_at = item
+        .get("granted_at")
+        .and_then(|v| v.as_n().ok())
+        .and_then(|s| s.parse::<u64>().ok())
+        .ok_or_else(|| CoreError::Repository("grant missing granted_at".into()))?;
+    let granted_by = item
+        .get("granted_by")
+        .and_then(|v| v.as_s().ok())
+        .ok_or_else(|| CoreError::Repository("grant missing granted_by".into()))?;
+
+    let slug = Slug::from_key(slug).map_err(|e| CoreError::Repository(format!("bad slug: {e}")))?;
+    let user_email = UserEmail::new(user_email.to_string())
+        .map_err(|_| CoreError::Repository("bad user_email".into()))?;
+    let granted_by = UserEmail::new(granted_by.to_string())
+        .map_err(|_| CoreError::Repository("bad granted_by".into()))?;
+    let role =
+        GroupRole::parse(role_str).ok_or_else(|| CoreError::Repository("bad role".into()))?;
+
+    Ok(LinkGrant {
+        slug,
+        user_email,
+        role,
+        granted_at: secs_to_system_time(granted_at),
+        granted_by,
+    })
+}
+
+impl DynamoRepo {
+    /// Remove a deleted link's grants: collaborators lose access along with
+    /// the link.
+    fn drop_grants(&self, slug: &Slug) -> Result<(), CoreError> {
+        for grant in self.list_grants(slug)? {
+            match self.remove_grant(slug, &grant.user_email) {
+                Ok(()) | Err(CoreError::NotFound) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl LinkGrantRepository for DynamoRepo {
+    fn put_grant(&self, grant: LinkGrant) -> Result<(), CoreError> {
+        let table = self.table_link_grants.clone();
+        let item = self.scope_item(grant_to_item(&grant), "slug");
+        let fut = async {
+            self.client
+                .put_item()
+                .table_name(table)
+                .set_item(Some(item))
+                .send()
+                .await
+        };
+        self.block_on(fut).map_err(map_sdk_err)?;
+        Ok(())
+    }
+
+    fn remove_grant(&self, slug: &Slug, user_email: &UserEmail) -> Result<(), CoreError> {
+        let table = self.table_link_grants.clone();
+        let slug_str = self.key_value(&slug.key());
+        let email = user_email.as_str().to_string();
+        let fut = async {
+            self.client
+                .delete_item()
+                .table_name(table)
+                .key("slug", slug_str)
+                .key("user_email", AttributeValue::S(email))
+                .condition_expression("attribute_exists(slug)")
+                .send()
+                .await
+        };
+        self.block_on(fut).map_err(|e| match e.as_service_error() {
+            Some(se) if se.code() == Some("ConditionalCheckFailedException") => CoreError::NotFound,
+            _ => map_sdk_err(e),
+        })?;
+        Ok(())
+    }
+
+    fn list_grants(&self, slug: &Slug) -> Result<Vec<LinkGrant>, CoreError> {
+        let query = self
+            .client
+            .query()
+            .table_name(&self.table_link_grants)
+            .key_condition_expression("slug = :slug")
+            .expression_attribute_values(":slug", self.key_value(&slug.key()));
+        Ok(self
+            .query_items(query, "slug", None)?
+            .iter()
+            .filter_map(|it| item_to_grant(it).ok())
+            .collect())
+    }
+
+    fn get_grant(
+        &self,
+        slug: &Slug,
+        user_email: &UserEmail,
+    ) -> Result<Option<LinkGrant>, CoreError> {
+        let table = self.table_link_grants.clone();
+        let slug_str = self.key_value(&slug.key());
+        let email = user_email.as_str().to_string();
+        let fut = async {
+            self.client
+                .get_item()
+                .table_name(table)
+                .key("slug", slug_str)
+                .key("user_email", AttributeValue::S(email))
+                .send()
+                .await
+        };
+        let out = self.block_on(fut).map_err(map_sdk_err)?;
+        if let Some(item) = out.item().and_then(|it| self.unscope_item(it, "slug")) {
+            Ok(Some(item_to_grant(&item)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn list_grants_for_user(&self, user_email: &UserEmail) -> Result<Vec<LinkGrant>, CoreError> {
+        // Uses the `user_email-index` GSI (see infra/sam/template.yaml)
+        let query = self
+            .client
+            .query()
+            .table_name(&self.table_link_grants)
+            .index_name("user_email-index")
+            .key_condition_expression("user_email = :email")
+            .expression_attribute_values(
+                ":email",
+                AttributeValue::S(user_email.as_str().to_string()),
+            );
+        Ok(self
+            .query_items(query, "slug", None)?
+            .iter()
+            .filter_map(|it| item_to_grant(it).ok())
+            .collect())
+    }
+}
+
+// -------------------------
+// Click Repository
+// -------------------------
+
+fn click_to_item(event: &ClickEvent) -> HashMap<String, AttributeValue> {
+    let mut m = HashMap::new();
+    m.insert(
+        "slug".into(),
+        AttributeValue::S(event.slug.key().into_owned()),
+    );
+    m.insert(
+        "clicked_at".into(),
+        AttributeValue::N(system_time_to_secs(event.clicked_at).to_string()),
+    );
+    if let Some(ref ua) = event.user_agent {
+        m.insert("user_agent".into(), AttributeValue::S(ua.clone()));
+    }
+    if let Some(ref referrer) = event.referrer {
+        m.insert("referrer".into(), AttributeValue::S(referrer.clone()));
+    }
+    if let Some(ref country) = event.country {
+        m.insert("country".into(), AttributeValue::S(country.clone()));
+    }
+    m
+}
+
+fn item_to_click(item: &HashMap<String, AttributeValue>) -> Result<ClickEvent, CoreError> {
+    let slug = item
+        .get("slug")
+        .and_then(|v| v.as_s().ok())
+        .ok_or_else(|| CoreError::Repository("click missing slug".into()))?;
+    let clicked_at = item
+        .get("clicked_at")
+        .and_then(|v| v.as_n().ok())
+        .and_then(|s| s.parse::<u64>().ok())
+        .ok_or_else(|| CoreError::Repository("click missing clicked_at".into()))?;
+    let user_agent = item
+        .get("user_agent")
         .and_then(|v| v.as_s().ok())
         .map(|s| s.to_string());
     let referrer = item
@@ -1147,8 +2766,7 @@ fn item_to_click(item: &HashMap<String, AttributeValue>) -> Result<ClickEvent, C
         .and_then(|v| v.as_s().ok())
         .map(|s| s.to_string());
 
-    let slug =
-        Slug::new(slug.to_string()).map_err(|e| CoreError::Repository(format!("bad slug: {e}")))?;
+    let slug = Slug::from_key(slug).map_err(|e| CoreError::Repository(format!("bad slug: {e}")))?;
 
     Ok(ClickEvent {
         slug,
@@ -1162,7 +2780,7 @@ fn item_to_click(item: &HashMap<String, AttributeValue>) -> Result<ClickEvent, C
 impl ClickRepository for DynamoRepo {
     fn record_click(&self, event: ClickEvent) -> Result<(), CoreError> {
         let table = self.table_clicks.clone();
-        let item = click_to_item(&event);
+        let item = self.scope_item(click_to_item(&event), "slug");
         let fut = async {
             self.client
                 .put_item()
@@ -1177,7 +2795,7 @@ impl ClickRepository for DynamoRepo {
 
     fn get_clicks(&self, slug: &Slug, limit: usize) -> Result<Vec<ClickEvent>, CoreError> {
         let table = self.table_clicks.clone();
-        let slug_str = slug.as_str().to_string();
+        let slug_str = self.key_value(&slug.key());
         let lim = limit as i32;
         let fut = async {
             self.client
@@ -1185,7 +2803,7 @@ impl ClickRepository for DynamoRepo {
                 .table_name(table)
                 .key_condition_expression("#slug = :slug")
                 .expression_attribute_names("#slug", "slug")
-                .expression_attribute_values(":slug", AttributeValue::S(slug_str))
+                .expression_attribute_values(":slug", slug_str)
                 .scan_index_forward(false) // Most recent first
                 .limit(lim)
                 .send()
@@ -1193,8 +2811,8 @@ impl ClickRepository for DynamoRepo {
         };
         let out = self.block_on(fut).map_err(map_sdk_err)?;
         let mut res = Vec::new();
-        for it in out.items().iter() {
-            if let Ok(c) = item_to_click(it) {
+        for it in self.unscoped_items(out.items(), "slug") {
+            if let Ok(c) = item_to_click(&it) {
                 res.push(c);
             }
         }
@@ -1203,7 +2821,7 @@ impl ClickRepository for DynamoRepo {
 
     fn get_click_count_since(&self, slug: &Slug, since: SystemTime) -> Result<u64, CoreError> {
         let table = self.table_clicks.clone();
-        let slug_str = slug.as_str().to_string();
+        let slug_str = self.key_value(&slug.key());
         let since_secs = system_time_to_secs(since);
         let fut = async {
             self.client
@@ -1211,7 +2829,7 @@ impl ClickRepository for DynamoRepo {
                 .table_name(table)
                 .key_condition_expression("#slug = :slug AND clicked_at >= :since")
                 .expression_attribute_names("#slug", "slug")
-                .expression_attribute_values(":slug", AttributeValue::S(slug_str))
+                .expression_attribute_values(":slug", slug_str)
                 .expression_attribute_values(":since", AttributeValue::N(since_secs.to_string()))
                 .select(aws_sdk_dynamodb::types::Select::Count)
                 .send()
@@ -1224,14 +2842,14 @@ impl ClickRepository for DynamoRepo {
     fn get_clicks_by_day(&self, slug: &Slug, days: usize) -> Result<Vec<(String, u64)>, CoreError> {
         // Get all clicks for this slug, then aggregate by day
         let table = self.table_clicks.clone();
-        let slug_str = slug.as_str().to_string();
+        let slug_str = self.key_value(&slug.key());
         let fut = async {
             self.client
                 .query()
                 .table_name(table)
                 .key_condition_expression("#slug = :slug")
                 .expression_attribute_names("#slug", "slug")
-                .expression_attribute_values(":slug", AttributeValue::S(slug_str))
+                .expression_attribute_values(":slug", slug_str)
                 .send()
                 .await
         };
@@ -1348,7 +2966,8 @@ fn item_to_audit(item: &HashMap<String, AttributeValue>) -> Result<AuditEntry, C
 impl AuditRepository for DynamoRepo {
     fn log(&self, entry: AuditEntry) -> Result<(), CoreError> {
         let table = self.table_audit.clone();
-        let item = audit_to_item(&entry);
+        let mut item = self.scope_item(audit_to_item(&entry), "id");
+        item.insert("tenant".into(), self.tenant_value());
         let fut = async {
             self.client
                 .put_item()
@@ -1367,30 +2986,12 @@ impl AuditRepository for DynamoRepo {
         target_id: &str,
         limit: usize,
     ) -> Result<Vec<AuditEntry>, CoreError> {
-        let table = self.table_audit.clone();
-        let tt = target_type.to_string();
-        let ti = target_id.to_string();
-        let lim = limit as i32;
-        let fut = async {
-            self.client
-                .scan()
-                .table_name(table)
-                .filter_expression("target_type = :tt AND target_id = :ti")
-                .expression_attribute_values(":tt", AttributeValue::S(tt))
-                .expression_attribute_values(":ti", AttributeValue::S(ti))
-                .limit(lim)
-                .send()
-                .await
-        };
-        let out = self.block_on(fut).map_err(map_sdk_err)?;
-        let mut res: Vec<_> = out
-            .items()
-            .iter()
-            .filter_map(|it| item_to_audit(it).ok())
-            .collect();
-        res.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
-        res.truncate(limit);
-        Ok(res)
+        let query = self
+            .audit_query("target_id-index", "target_id = :ti")
+            .filter_expression("target_type = :tt")
+            .expression_attribute_values(":ti", AttributeValue::S(target_id.to_string()))
+            .expression_attribute_values(":tt", AttributeValue::S(target_type.to_string()));
+        self.query_audit(query, limit)
     }
 
     fn list_by_actor(
@@ -1398,43 +2999,505 @@ impl AuditRepository for DynamoRepo {
         actor_email: &UserEmail,
         limit: usize,
     ) -> Result<Vec<AuditEntry>, CoreError> {
-        let table = self.table_audit.clone();
-        let email = actor_email.as_str().to_string();
-        let lim = limit as i32;
-        let fut = async {
-            self.client
-                .scan()
-                .table_name(table)
-                .filter_expression("actor_email = :email")
-                .expression_attribute_values(":email", AttributeValue::S(email))
-                .limit(lim)
-                .send()
-                .await
-        };
-        let out = self.block_on(fut).map_err(map_sdk_err)?;
-        let mut res: Vec<_> = out
-            .items()
-            .iter()
-            .filter_map(|it| item_to_audit(it).ok())
-            .collect();
-        res.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
-        res.truncate(limit);
-        Ok(res)
+        let query = self
+            .audit_query("actor_email-index", "actor_email = :email")
+            .expression_attribute_values(
+                ":email",
+                AttributeValue::S(actor_email.as_str().to_string()),
+            );
+        self.query_audit(query, limit)
     }
 
     fn list_recent(&self, limit: usize) -> Result<Vec<AuditEntry>, CoreError> {
-        let table = self.table_audit.clone();
-        let fut = async { self.client.scan().table_name(table).send().await };
-        let out = self.block_on(fut).map_err(map_sdk_err)?;
-        let mut res: Vec<_> = out
-            .items()
+        let query = self
+            .audit_query("tenant-index", "tenant = :tenant")
+            .expression_attribute_values(":tenant", self.tenant_value());
+        self.query_audit(query, limit)
+    }
+}
+
+impl DynamoRepo {
+    /// Query an AuditLog GSI on `key_condition`, newest first (they are all
+    /// sorted by `timestamp`).
+    fn audit_query(&self, index: &str, key_condition: &str) -> QueryFluentBuilder {
+        self.client
+            .query()
+            .table_name(&self.table_audit)
+            .index_name(index)
+            .key_condition_expression(key_condition)
+            .scan_index_forward(false)
+    }
+
+    fn query_audit(
+        &self,
+        query: QueryFluentBuilder,
+        limit: usize,
+    ) -> Result<Vec<AuditEntry>, CoreError> {
+        Ok(self
+            .query_items(query, "id", Some(limit))?
             .iter()
             .filter_map(|it| item_to_audit(it).ok())
-            .collect();
-        res.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
-        res.truncate(limit);
+            .collect())
+    }
+}
+
+// -------------------------
+// Organization Repository
+// -------------------------
+
+fn strings_attr(values: &[String]) -> AttributeValue {
+    AttributeValue::L(
+        values
+            .iter()
+            .map(|v| AttributeValue::S(v.clone()))
+            .collect(),
+    )
+}
+
+fn item_strings(item: &HashMap<String, AttributeValue>, key: &str) -> Vec<String> {
+    item.get(key)
+        .and_then(|v| v.as_l().ok())
+        .map(|l| l.iter().filter_map(|v| v.as_s().ok().cloned()).collect())
+        .unwrap_or_default()
+}
+
+fn organization_to_item(org: &Organization) -> HashMap<String, AttributeValue> {
+    let admins: Vec<String> = org.admins.iter().map(|a| a.as_str().to_string()).collect();
+    let mut m = HashMap::new();
+    m.insert("id".into(), AttributeValue::S(org.id.as_str().to_string()));
+    m.insert("name".into(), AttributeValue::S(org.name.clone()));
+    m.insert("hosts".into(), strings_attr(&org.hosts));
+    m.insert("auth_domains".into(), strings_attr(&org.auth_domains));
+    m.insert("admins".into(), strings_attr(&admins));
+    m.insert(
+        "allowed_hosts".into(),
+        strings_attr(&org.settings.allowed_hosts),
+    );
+    if let Some(ref domain) = org.settings.shortlink_domain {
+        m.insert("shortlink_domain".into(), AttributeValue::S(domain.clone()));
+    }
+    m.insert(
+        "created_at".into(),
+        AttributeValue::N(system_time_to_secs(org.created_at).to_string()),
+    );
+    m
+}
+
+fn item_to_organization(item: &HashMap<String, AttributeValue>) -> Result<Organization, CoreError> {
+    // Host items are keyed by host and name their organization in `org_id`
+    let id = item
+        .get("org_id")
+        .or_else(|| item.get("id"))
+        .and_then(|v| v.as_s().ok())
+        .ok_or_else(|| CoreError::Repository("organization missing id".into()))?;
+    let name = item
+        .get("name")
+        .and_then(|v| v.as_s().ok())
+        .ok_or_else(|| CoreError::Repository("organization missing name".into()))?
+        .to_string();
+    let created_at = item
+        .get("created_at")
+        .and_then(|v| v.as_n().ok())
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or_default();
+    let admins = item_strings(item, "admins")
+        .into_iter()
+        .map(|a| UserEmail::new(a).map_err(|_| CoreError::Repository("bad admin email".into())))
+        .collect::<Result<_, _>>()?;
+
+    Ok(Organization {
+        id: TenantId::new(id.to_string())
+            .map_err(|e| CoreError::Repository(format!("bad tenant: {e}")))?,
+        name,
+        hosts: item_strings(item, "hosts"),
+        auth_domains: item_strings(item, "auth_domains"),
+        admins,
+        settings: OrganizationSettings {
+            shortlink_domain: item
+                .get("shortlink_domain")
+                .and_then(|v| v.as_s().ok())
+                .cloned(),
+            allowed_hosts: item_strings(item, "allowed_hosts"),
+        },
+        created_at: secs_to_system_time(created_at),
+    })
+}
+
+/// Key of the Organizations item pointing `host` at its organization. `#`
+/// never appears in tenant ids, so these keys cannot clash with organizations.
+fn org_host_key(host: &str) -> String {
+    format!("host{}{}", tenant::KEY_SEPARATOR, host.to_ascii_lowercase())
+}
+
+impl OrganizationRepository for DynamoRepo {
+    fn put_organization(&self, org: Organization) -> Result<(), CoreError> {
+        let previous = self.get_organization(&org.id)?;
+        let item = organization_to_item(&org);
+        let hosts: HashSet<String> = org.hosts.iter().map(|h| org_host_key(h)).collect();
+        let put = |item: HashMap<String, AttributeValue>| {
+            Put::builder()
+                .table_name(&self.table_organizations)
+                .set_item(Some(item))
+                .build()
+                .map(|put| TransactWriteItem::builder().put(put).build())
+                .map_err(|e| CoreError::Repository(format!("dynamo request: {e}")))
+        };
+
+        // The organization and its host items change together
+        let mut items = vec![put(item.clone())?];
+        for key in &hosts {
+            let mut host_item = item.clone();
+            host_item.insert("id".into(), AttributeValue::S(key.clone()));
+            host_item.insert("org_id".into(), AttributeValue::S(org.id.as_str().into()));
+            items.push(put(host_item)?);
+        }
+        for old in previous.iter().flat_map(|p| &p.hosts) {
+            let key = org_host_key(old);
+            if hosts.contains(&key) {
+                continue;
+            }
+            let delete = Delete::builder()
+                .table_name(&self.table_organizations)
+                .key("id", AttributeValue::S(key))
+                .build()
+                .map_err(|e| CoreError::Repository(format!("dynamo request: {e}")))?;
+            items.push(TransactWriteItem::builder().delete(delete).build());
+        }
+        self.transact_write(items, |_| {
+            CoreError::Conflict("organization changed, try again".into())
+        })
+    }
+
+    fn get_organization(&self, id: &TenantId) -> Result<Option<Organization>, CoreError> {
+        let table = self.table_organizations.clone();
+        let fut = async {
+            self.client
+                .get_item()
+                .table_name(table)
+                .key("id", AttributeValue::S(id.as_str().to_string()))
+                .send()
+                .await
+        };
+        let out = self.block_on(fut).map_err(map_sdk_err)?;
+        if let Some(item) = out.item() {
+            Ok(Some(item_to_organization(item)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn list_organizations(&self) -> Result<Vec<Organization>, CoreError> {
+        let mut res = Vec::new();
+        let mut start_key = None;
+        loop {
+            let table = self.table_organizations.clone();
+            let key = start_key.take();
+            let fut = async {
+                self.client
+                    .scan()
+                    .table_name(table)
+                    .set_exclusive_start_key(key)
+                    .send()
+                    .await
+            };
+            let out = self.block_on(fut).map_err(map_sdk_err)?;
+            res.extend(
+                out.items()
+                    .iter()
+                    .filter(|it| !it.contains_key("org_id"))
+                    .filter_map(|it| item_to_organization(it).ok()),
+            );
+            match out.last_evaluated_key() {
+                Some(k) if !k.is_empty() => start_key = Some(k.clone()),
+                _ => break,
+            }
+        }
+        res.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(res)
+    }
+
+    fn get_organization_by_host(&self, host: &str) -> Result<Option<Organization>, CoreError> {
+        let host = host.split(':').next().unwrap_or(host);
+        let fut = self
+            .client
+            .get_item()
+            .table_name(&self.table_organizations)
+            .key("id", AttributeValue::S(org_host_key(host)))
+            .send();
+        let out = self.block_on(fut).map_err(map_sdk_err)?;
+        out.item().map(item_to_organization).transpose()
+    }
+}
+
+// -------------------------
+// Domain Repository
+// -------------------------
+
+fn short_domain_to_item(domain: &ShortDomain) -> HashMap<String, AttributeValue> {
+    let users: Vec<String> = domain
+        .allowed_users
+        .iter()
+        .map(|u| u.as_str().to_string())
+        .collect();
+    let mut m = HashMap::new();
+    m.insert("host".into(), AttributeValue::S(domain.host.clone()));
+    m.insert("allowed_users".into(), strings_attr(&users));
+    m.insert(
+        "allowed_groups".into(),
+        strings_attr(&domain.allowed_groups),
+    );
+    m.insert(
+        "created_at".into(),
+        AttributeValue::N(system_time_to_secs(domain.created_at).to_string()),
+    );
+    m.insert(
+        "created_by".into(),
+        AttributeValue::S(domain.created_by.as_str().to_string()),
+    );
+    m
+}
+
+fn item_to_short_domain(item: &HashMap<String, AttributeValue>) -> Result<ShortDomain, CoreError> {
+    let email = |e: String| {
+        UserEmail::new(e).map_err(|_| CoreError::Repository("bad domain user email".into()))
+    };
+    let host = item
+        .get("host")
+        .and_then(|v| v.as_s().ok())
+        .ok_or_else(|| CoreError::Repository("domain missing host".into()))?
+        .to_string();
+    let created_at = item
+        .get("created_at")
+        .and_then(|v| v.as_n().ok())
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or_default();
+    let created_by = item
+        .get("created_by")
+        .and_then(|v| v.as_s().ok())
+        .ok_or_else(|| CoreError::Repository("domain missing created_by".into()))?
+        .to_string();
+    Ok(ShortDomain {
+        host,
+        allowed_users: item_strings(item, "allowed_users")
+            .into_iter()
+            .map(email)
+            .collect::<Result<_, _>>()?,
+        allowed_groups: item_strings(item, "allowed_groups"),
+        created_at: secs_to_system_time(created_at),
+        created_by: email(created_by)?,
+    })
+}
+
+impl DomainRepository for DynamoRepo {
+    fn put_domain(&self, domain: ShortDomain) -> Result<(), CoreError> {
+        let table = self.table_domains.clone();
+        let item = self.scope_item(short_domain_to_item(&domain), "host");
+        let fut = async {
+            self.client
+                .put_item()
+                .table_name(table)
+                .set_item(Some(item))
+                .send()
+                .await
+        };
+        self.block_on(fut).map_err(map_sdk_err)?;
+        Ok(())
+    }
+
+    fn get_domain(&self, host: &str) -> Result<Option<ShortDomain>, CoreError> {
+        let table = self.table_domains.clone();
+        let fut = async {
+            self.client
+                .get_item()
+                .table_name(table)
+                .key("host", self.key_value(host))
+                .send()
+                .await
+        };
+        let out = self.block_on(fut).map_err(map_sdk_err)?;
+        match out.item().and_then(|it| self.unscope_item(it, "host")) {
+            Some(item) => Ok(Some(item_to_short_domain(&item)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn list_domains(&self) -> Result<Vec<ShortDomain>, CoreError> {
+        let mut res = Vec::new();
+        let mut start_key = None;
+        loop {
+            let table = self.table_domains.clone();
+            let key = start_key.take();
+            let fut = async {
+                self.client
+                    .scan()
+                    .table_name(table)
+                    .set_exclusive_start_key(key)
+                    .send()
+                    .await
+            };
+            let out = self.block_on(fut).map_err(map_sdk_err)?;
+            res.extend(
+                self.unscoped_items(out.items(), "host")
+                    .filter_map(|it| item_to_short_domain(&it).ok()),
+            );
+            match out.last_evaluated_key() {
+                Some(k) if !k.is_empty() => start_key = Some(k.clone()),
+                _ => break,
+            }
+        }
+        res.sort_by(|a, b| a.host.cmp(&b.host));
+        Ok(res)
+    }
+
+    fn delete_domain(&self, host: &str) -> Result<(), CoreError> {
+        let table = self.table_domains.clone();
+        let fut = async {
+            self.client
+                .delete_item()
+                .table_name(table)
+                .key("host", self.key_value(host))
+                .condition_expression("attribute_exists(host)")
+                .send()
+                .await
+        };
+        match self.block_on(fut) {
+            Ok(_) => Ok(()),
+            Err(e) if e.code() == Some("ConditionalCheckFailedException") => {
+                Err(CoreError::NotFound)
+            }
+            Err(e) => Err(map_sdk_err(e)),
+        }
+    }
+}
+
+fn namespace_to_item(namespace: &Namespace) -> HashMap<String, AttributeValue> {
+    let mut m = HashMap::new();
+    m.insert("path".into(), AttributeValue::S(namespace.path.clone()));
+    m.insert(
+        "group_id".into(),
+        AttributeValue::S(namespace.group_id.clone()),
+    );
+    m.insert(
+        "created_at".into(),
+        AttributeValue::N(system_time_to_secs(namespace.created_at).to_string()),
+    );
+    m.insert(
+        "created_by".into(),
+        AttributeValue::S(namespace.created_by.as_str().to_string()),
+    );
+    m
+}
+
+fn item_to_namespace(item: &HashMap<String, AttributeValue>) -> Result<Namespace, CoreError> {
+    let s = |name: &str| {
+        item.get(name)
+            .and_then(|v| v.as_s().ok())
+            .cloned()
+            .ok_or_else(|| CoreError::Repository(format!("namespace missing {name}")))
+    };
+    let created_at = item
+        .get("created_at")
+        .and_then(|v| v.as_n().ok())
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or_default();
+    Ok(Namespace {
+        path: s("path")?,
+        group_id: s("group_id")?,
+        created_at: secs_to_system_time(created_at),
+        created_by: UserEmail::new(s("created_by")?)
+            .map_err(|_| CoreError::Repository("bad namespace email".into()))?,
+    })
+}
+
+// `path` is a DynamoDB reserved word, so condition expressions name it `#p`.
+impl NamespaceRepository for DynamoRepo {
+    fn put_namespace(&self, namespace: Namespace) -> Result<(), CoreError> {
+        let table = self.table_namespaces.clone();
+        let item = self.scope_item(namespace_to_item(&namespace), "path");
+        let fut = async {
+            self.client
+                .put_item()
+                .table_name(table)
+                .set_item(Some(item))
+                .condition_expression("attribute_not_exists(#p)")
+                .expression_attribute_names("#p", "path")
+                .send()
+                .await
+        };
+        self.block_on(fut).map_err(|e| match e.as_service_error() {
+            Some(se) if se.code() == Some("ConditionalCheckFailedException") => {
+                CoreError::AlreadyExists
+            }
+            _ => map_sdk_err(e),
+        })?;
+        Ok(())
+    }
+
+    fn get_namespace(&self, path: &str) -> Result<Option<Namespace>, CoreError> {
+        let table = self.table_namespaces.clone();
+        let fut = async {
+            self.client
+                .get_item()
+                .table_name(table)
+                .key("path", self.key_value(path))
+                .send()
+                .await
+        };
+        let out = self.block_on(fut).map_err(map_sdk_err)?;
+        match out.item().and_then(|it| self.unscope_item(it, "path")) {
+            Some(item) => Ok(Some(item_to_namespace(&item)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn list_namespaces(&self) -> Result<Vec<Namespace>, CoreError> {
+        let mut res = Vec::new();
+        let mut start_key = None;
+        loop {
+            let table = self.table_namespaces.clone();
+            let key = start_key.take();
+            let fut = async {
+                self.client
+                    .scan()
+                    .table_name(table)
+                    .set_exclusive_start_key(key)
+                    .send()
+                    .await
+            };
+            let out = self.block_on(fut).map_err(map_sdk_err)?;
+            res.extend(
+                self.unscoped_items(out.items(), "path")
+                    .filter_map(|it| item_to_namespace(&it).ok()),
+            );
+            match out.last_evaluated_key() {
+                Some(k) if !k.is_empty() => start_key = Some(k.clone()),
+                _ => break,
+            }
+        }
+        res.sort_by(|a, b| a.path.cmp(&b.path));
         Ok(res)
     }
+
+    fn delete_namespace(&self, path: &str) -> Result<(), CoreError> {
+        let table = self.table_namespaces.clone();
+        let fut = async {
+            self.client
+                .delete_item()
+                .table_name(table)
+                .key("path", self.key_value(path))
+                .condition_expression("attribute_exists(#p)")
+                .expression_attribute_names("#p", "path")
+                .send()
+                .await
+        };
+        match self.block_on(fut) {
+            Ok(_) => Ok(()),
+            Err(e) if e.code() == Some("ConditionalCheckFailedException") => {
+                Err(CoreError::NotFound)
+            }
+            Err(e) => Err(map_sdk_err(e)),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -1466,6 +3529,30 @@ mod tests {
         assert_eq!(link.is_active, link2.is_active);
         assert_eq!(link.updated_at, link2.updated_at);
         assert_eq!(link.expires_at, link2.expires_at);
+        assert_eq!(link.visibility, link2.visibility);
+        assert_eq!(
+            item.get("target_key").and_then(|v| v.as_s().ok()),
+            Some(&link.target_key())
+        );
+        assert_eq!(
+            item.get("target_host").and_then(|v| v.as_s().ok()),
+            link.target_host().as_ref()
+        );
+    }
+
+    #[test]
+    fn visibility_item_mapping() {
+        let mut link = sample_link();
+        link.visibility = LinkVisibility::Workspace;
+        let item = domain_to_item(&link);
+        assert_eq!(
+            item.get("visibility").and_then(|v| v.as_s().ok()),
+            Some(&"workspace".to_string())
+        );
+        assert_eq!(
+            item_to_domain(&item).unwrap().visibility,
+            LinkVisibility::Workspace
+        );
     }
 
     #[test]
@@ -1489,5 +3576,829 @@ mod tests {
         assert!(link.is_active); // default
         assert!(link.updated_at.is_none()); // default
         assert!(link.expires_at.is_none()); // default
+        assert_eq!(link.visibility, LinkVisibility::Public); // default
+    }
+
+    #[test]
+    fn group_item_mapping_with_settings() {
+        let mut group = LinkGroup {
+            id: "grp_1".into(),
+            name: "Marketing".into(),
+            description: None,
+            created_at: UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000),
+            created_by: UserEmail::new("user@example.com").unwrap(),
+            settings: GroupSettings::default(),
+            parent_id: None,
+        };
+        let item = group_to_item(&group);
+        assert!(!item.contains_key("allowed_hosts"));
+        assert!(!item.contains_key("parent_id"));
+        assert_eq!(item_to_group(&item).unwrap(), group);
+
+        group.settings = GroupSettings {
+            default_expiry_secs: Some(86_400),
+            default_redirect_delay: Some(5),
+            allowed_hosts: vec!["*.example.com".into(), "example.org".into()],
+            slug_prefix: Some("mkt-".into()),
+            max_links: Some(100),
+        };
+        group.parent_id = Some("grp_parent".into());
+        assert_eq!(item_to_group(&group_to_item(&group)).unwrap(), group);
+    }
+
+    #[test]
+    fn grant_item_mapping() {
+        let grant = LinkGrant {
+            slug: Slug::new("shared").unwrap(),
+            user_email: UserEmail::new("bob@example.com").unwrap(),
+            role: GroupRole::Editor,
+            granted_at: UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000),
+            granted_by: UserEmail::new("user@example.com").unwrap(),
+        };
+        let item = grant_to_item(&grant);
+        assert_eq!(
+            item.get("role").and_then(|v| v.as_s().ok()),
+            Some(&"editor".to_string())
+        );
+        assert_eq!(item_to_grant(&item).unwrap(), grant);
+    }
+
+    #[test]
+    fn invitation_item_mapping() {
+        let inv = GroupInvitation {
+            id: "inv_1".into(),
+            group_id: "grp_1".into(),
+            email: UserEmail::new("bob@example.com").unwrap(),
+            role: GroupRole::Viewer,
+            invited_by: UserEmail::new("user@example.com").unwrap(),
+            created_at: UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000),
+            expires_at: UNIX_EPOCH + std::time::Duration::from_secs(1_700_604_800),
+            status: InvitationStatus::Pending,
+            responded_at: None,
+        };
+        let item = invitation_to_item(&inv);
+        assert!(!item.contains_key("responded_at"));
+        assert_eq!(item_to_invitation(&item).unwrap(), inv);
+
+        let answered = GroupInvitation {
+            status: InvitationStatus::Accepted,
+            responded_at: Some(inv.created_at),
+            ..inv
+        };
+        assert_eq!(
+            item_to_invitation(&invitation_to_item(&answered)).unwrap(),
+            answered
+        );
+    }
+
+    #[test]
+    fn organization_item_roundtrip() {
+        let org = Organization {
+            id: TenantId::new("acme").unwrap(),
+            name: "Acme".into(),
+            hosts: vec!["go.acme.com".into()],
+            auth_domains: vec!["acme.com".into()],
+            admins: vec![UserEmail::new("boss@acme.com").unwrap()],
+            settings: OrganizationSettings {
+                shortlink_domain: Some("go.acme.com".into()),
+                allowed_hosts: Vec::new(),
+            },
+            created_at: secs_to_system_time(1_700_000_000),
+        };
+        assert_eq!(
+            item_to_organization(&organization_to_item(&org)).unwrap(),
+            org
+        );
+    }
+
+    #[test]
+    fn short_domain_items_roundtrip() {
+        let by = UserEmail::new("boss@acme.com").unwrap();
+        let domain = ShortDomain {
+            host: "jpro.link".into(),
+            allowed_users: vec![by.clone()],
+            allowed_groups: vec!["grp_mkt".into()],
+            created_at: secs_to_system_time(1_700_000_000),
+            created_by: by,
+        };
+        assert_eq!(
+            item_to_short_domain(&short_domain_to_item(&domain)).unwrap(),
+            domain
+        );
+
+        let mut link = sample_link();
+        link.slug = link.slug.on_domain(Some("jpro.link".into()));
+        let item = domain_to_item(&link);
+        assert_eq!(
+            item.get("slug").unwrap().as_s().unwrap(),
+            "jpro.link:abc123"
+        );
+        assert_eq!(item_to_domain(&item).unwrap().slug, link.slug);
+    }
+
+    #[test]
+    fn namespace_items_roundtrip() {
+        let namespace = Namespace {
+            path: "events/2026".into(),
+            group_id: "grp_events".into(),
+            created_at: secs_to_system_time(1_700_000_000),
+            created_by: UserEmail::new("boss@acme.com").unwrap(),
+        };
+        assert_eq!(
+            item_to_namespace(&namespace_to_item(&namespace)).unwrap(),
+            namespace
+        );
+    }
+
+    #[test]
+    fn alias_items_are_skipped_as_links() {
+        let mut alias = HashMap::new();
+        alias.insert("slug".to_string(), AttributeValue::S("tpyo".into()));
+        alias.insert("alias_of".to_string(), AttributeValue::S("typo".into()));
+        assert_eq!(alias_target(&alias).as_deref(), Some("typo"));
+        // Scans parse items leniently, so aliases never show up as links
+        assert!(item_to_domain(&alias).is_err());
+
+        let link = ShortLink::new(
+            Slug::new("typo").unwrap(),
+            "https://example.com".into(),
+            UNIX_EPOCH,
+            UserEmail::new("user@example.com").unwrap(),
+        );
+        assert_eq!(alias_target(&domain_to_item(&link)), None);
+    }
+
+    #[test]
+    fn stored_keys_name_their_tenant() {
+        assert_eq!(
+            stored_key_tenant("acme#abc"),
+            AttributeValue::S("acme".into())
+        );
+        assert_eq!(
+            stored_key_tenant("abc"),
+            AttributeValue::S(TenantId::DEFAULT.into())
+        );
+    }
+
+    #[test]
+    fn start_keys_roundtrip_through_cursors() {
+        let key = HashMap::from([
+            ("slug".to_string(), AttributeValue::S("acme#docs".into())),
+            (
+                "created_at".to_string(),
+                AttributeValue::N("1700000000".into()),
+            ),
+            ("tenant".to_string(), AttributeValue::S("acme".into())),
+        ]);
+        assert_eq!(decode_start_key(&encode_start_key(&key)).unwrap(), key);
+        for bad in [
+            "zz".to_string(),
+            domain::cursor::encode(&["slug", "S"]),
+            domain::cursor::encode(&["created_at", "N", "soon"]),
+            domain::cursor::encode(&["slug", "B", "x"]),
+        ] {
+            assert!(matches!(
+                decode_start_key(&bad),
+                Err(CoreError::InvalidCursor)
+            ));
+        }
+    }
+
+    /// A repo on fresh tables with the indexes from infra/sam/template.yaml.
+    fn dynamodb_local_repo(endpoint: &str) -> DynamoRepo {
+        use aws_sdk_dynamodb::config::{BehaviorVersion, Credentials, Region};
+        use aws_sdk_dynamodb::types::{
+            AttributeDefinition, BillingMode, GlobalSecondaryIndex, KeySchemaElement, KeyType,
+            Projection, ProjectionType, ScalarAttributeType,
+        };
+
+        let config = aws_sdk_dynamodb::Config::builder()
+            .behavior_version(BehaviorVersion::latest())
+            .region(Region::new("us-east-1"))
+            .credentials_provider(Credentials::new("local", "local", None, None, "test"))
+            .endpoint_url(endpoint)
+            .build();
+        let suffix = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let mut tables = DynamoTables::new(format!("links-{suffix}"), format!("counters-{suffix}"));
+        tables.groups = format!("groups-{suffix}");
+        tables.group_members = format!("members-{suffix}");
+        tables.link_grants = format!("grants-{suffix}");
+        tables.audit = format!("audit-{suffix}");
+        tables.organizations = format!("orgs-{suffix}");
+        let repo = DynamoRepo::with_client(tables.clone(), Client::from_conf(config)).unwrap();
+
+        let key_schema = |hash: &str, range: Option<&str>| {
+            std::iter::once((hash, KeyType::Hash))
+                .chain(range.map(|r| (r, KeyType::Range)))
+                .map(|(name, key_type)| {
+                    KeySchemaElement::builder()
+                        .attribute_name(name)
+                        .key_type(key_type)
+                        .build()
+                        .unwrap()
+                })
+                .collect::<Vec<_>>()
+        };
+        type Index<'a> = (&'a str, &'a str, Option<&'a str>);
+        let create = |table: &str, key: (&str, Option<&str>), numbers: &[&str], gsis: &[Index]| {
+            let mut attrs: Vec<&str> = std::iter::once(key.0).chain(key.1).collect();
+            for (_, hash, range) in gsis {
+                attrs.extend(std::iter::once(*hash).chain(*range));
+            }
+            attrs.sort();
+            attrs.dedup();
+            let mut req = repo
+                .client
+                .create_table()
+                .table_name(table)
+                .billing_mode(BillingMode::PayPerRequest)
+                .set_key_schema(Some(key_schema(key.0, key.1)));
+            for attr in attrs {
+                let kind = if numbers.contains(&attr) {
+                    ScalarAttributeType::N
+                } else {
+                    ScalarAttributeType::S
+                };
+                req = req.attribute_definitions(
+                    AttributeDefinition::builder()
+                        .attribute_name(attr)
+                        .attribute_type(kind)
+                        .build()
+                        .unwrap(),
+                );
+            }
+            for (name, hash, range) in gsis {
+                req = req.global_secondary_indexes(
+                    GlobalSecondaryIndex::builder()
+                        .index_name(*name)
+                        .set_key_schema(Some(key_schema(hash, *range)))
+                        .projection(
+                            Projection::builder()
+                                .projection_type(ProjectionType::All)
+                                .build(),
+                        )
+                        .build()
+                        .unwrap(),
+                );
+            }
+            repo.block_on(req.send()).unwrap();
+        };
+        create(
+            &tables.shortlinks,
+            ("slug", None),
+            &["created_at"],
+            &[
+                ("tenant-index", "tenant", Some("created_at")),
+                ("created_by-index", "created_by", Some("created_at")),
+                ("group_id-index", "group_id", Some("created_at")),
+                ("alias_of-index", "alias_of", None),
+                ("target_key-index", "target_key", Some("created_at")),
+                ("target_host-index", "target_host", Some("target_key")),
+            ],
+        );
+        create(
+            &tables.groups,
+            ("id", None),
+            &[],
+            &[
+                ("created_by-index", "created_by", None),
+                ("parent_id-index", "parent_id", None),
+            ],
+        );
+        create(
+            &tables.group_members,
+            ("group_id", Some("user_email")),
+            &[],
+            &[("user_email-index", "user_email", None)],
+        );
+        create(
+            &tables.link_grants,
+            ("slug", Some("user_email")),
+            &[],
+            &[("user_email-index", "user_email", None)],
+        );
+        create(&tables.organizations, ("id", None), &[], &[]);
+        create(
+            &tables.audit,
+            ("id", None),
+            &["timestamp"],
+            &[
+                ("tenant-index", "tenant", Some("timestamp")),
+                ("actor_email-index", "actor_email", Some("timestamp")),
+                ("target_id-index", "target_id", Some("timestamp")),
+            ],
+        );
+        repo
+    }
+
+    /// Runs against DynamoDB Local when `DYNAMODB_LOCAL_ENDPOINT` is set, e.g.
+    /// `docker run -p 8001:8000 amazon/dynamodb-local` with
+    /// `DYNAMODB_LOCAL_ENDPOINT=http://localhost:8001`.
+    #[test]
+    fn listings_query_indexes_on_dynamodb_local() {
+        let Ok(endpoint) = std::env::var("DYNAMODB_LOCAL_ENDPOINT") else {
+            return;
+        };
+        let repo = dynamodb_local_repo(&endpoint);
+        let acme = repo.for_tenant(&TenantId::new("acme").unwrap());
+        let alice = UserEmail::new("alice@example.com").unwrap();
+        let bob = UserEmail::new("bob@example.com").unwrap();
+        let link = |slug: &str, secs: u64, by: &UserEmail, group: Option<&str>| ShortLink {
+            group_id: group.map(str::to_string),
+            ..ShortLink::new(
+                Slug::new(slug).unwrap(),
+                format!("https://example.com/{slug}"),
+                secs_to_system_time(1_700_000_000 + secs),
+                by.clone(),
+            )
+        };
+        let slugs = |links: Vec<ShortLink>| -> Vec<String> {
+            links.iter().map(|l| l.slug.as_str().to_string()).collect()
+        };
+
+        repo.put(link("docs", 1, &alice, Some("g1"))).unwrap();
+        repo.put(link("blog", 2, &alice, None)).unwrap();
+        repo.put(link("news", 3, &bob, Some("g1"))).unwrap();
+        repo.put(link("gone", 4, &alice, None)).unwrap();
+        repo.delete(
+            &Slug::new("gone").unwrap(),
+            secs_to_system_time(1_700_000_100),
+        )
+        .unwrap();
+        acme.put(link("acme-docs", 5, &alice, Some("g1"))).unwrap();
+
+        assert_eq!(slugs(repo.list(10).unwrap()), ["news", "blog", "docs"]);
+        assert_eq!(slugs(repo.list(1).unwrap()), ["news"]);
+        assert_eq!(slugs(acme.list(10).unwrap()), ["acme-docs"]);
+        assert_eq!(
+            slugs(repo.list_by_creator(&alice, 10).unwrap()),
+            ["blog", "docs"]
+        );
+        assert_eq!(
+            slugs(repo.list_by_group("g1", 10).unwrap()),
+            ["news", "docs"]
+        );
+        assert_eq!(slugs(repo.search("blog", 10).unwrap()), ["blog"]);
+        let page = repo
+            .list_paginated(&ListOptions {
+                limit: 10,
+                created_by: Some(alice.clone()),
+                group_id: Some("g1".into()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(
+            (slugs(page.items), page.total),
+            (vec!["docs".to_string()], Some(1))
+        );
+        let page = |cursor: Option<String>| {
+            repo.list_paginated(&ListOptions {
+                limit: 1,
+                include_deleted: true,
+                cursor,
+                ..Default::default()
+            })
+            .unwrap()
+        };
+        let first = page(None);
+        assert_eq!(
+            (slugs(first.items), first.total),
+            (vec!["gone".to_string()], Some(4))
+        );
+        let second = page(first.next_cursor);
+        assert_eq!(
+            (slugs(second.items), second.total),
+            (vec!["news".to_string()], None)
+        );
+        assert!(second.has_more);
+        let list = |options: ListOptions| {
+            slugs(
+                repo.list_paginated(&ListOptions {
+                    limit: 10,
+                    ..options
+                })
+                .unwrap()
+                .items,
+            )
+        };
+        assert_eq!(
+            list(ListOptions {
+                status: Some(LinkStatus::Deleted),
+                ..Default::default()
+            }),
+            ["gone"]
+        );
+        assert_eq!(
+            list(ListOptions {
+                direction: SortDirection::Asc,
+                created: domain::TimeRange {
+                    from: Some(secs_to_system_time(1_700_000_002)),
+                    to: None,
+                },
+                ..Default::default()
+            }),
+            ["blog", "news"]
+        );
+        assert_eq!(
+            list(ListOptions {
+                sort: LinkSort::UpdatedAt,
+                target_host: Some("example.com".into()),
+                ..Default::default()
+            })
+            .len(),
+            3
+        );
+
+        // Accessible listings merge own, group and shared links of this tenant
+        let carol = UserEmail::new("carol@example.com").unwrap();
+        let accessible = |cursor: Option<String>| {
+            repo.list_paginated(&ListOptions {
+                limit: 2,
+                accessible_to: Some(AccessScope {
+                    user: carol.clone(),
+                    group_ids: vec!["g1".into()],
+                    shared_slugs: ["blog", "news", "missing"]
+                        .into_iter()
+                        .map(|s| Slug::new(s).unwrap())
+                        .collect(),
+                }),
+                cursor,
+                ..Default::default()
+            })
+            .unwrap()
+        };
+        let first = accessible(None);
+        assert_eq!(
+            (slugs(first.items), first.total),
+            (vec!["news".to_string(), "blog".to_string()], Some(3))
+        );
+        let second = accessible(first.next_cursor);
+        assert_eq!(slugs(second.items), ["docs"]);
+        assert!(!second.has_more);
+
+        // Moving a link out of its group removes it from the group index
+        let mut docs = repo.get(&Slug::new("docs").unwrap()).unwrap().unwrap();
+        docs.group_id = None;
+        repo.update(&docs).unwrap();
+        assert_eq!(slugs(repo.list_by_group("g1", 10).unwrap()), ["news"]);
+
+        repo.add_alias(&Slug::new("dox").unwrap(), &docs.slug)
+            .unwrap();
+        assert_eq!(
+            repo.list_aliases(&docs.slug).unwrap(),
+            [Slug::new("dox").unwrap()]
+        );
+        assert_eq!(slugs(repo.list(10).unwrap()), ["news", "blog", "docs"]);
+
+        let group = |id: &str, parent: Option<&str>| LinkGroup {
+            id: id.into(),
+            name: id.into(),
+            description: None,
+            created_at: UNIX_EPOCH,
+            created_by: bob.clone(),
+            settings: GroupSettings::default(),
+            parent_id: parent.map(str::to_string),
+        };
+        repo.create_group(group("g1", None)).unwrap();
+        repo.create_group(group("g2", Some("g1"))).unwrap();
+        acme.create_group(group("g3", None)).unwrap();
+        repo.add_member(GroupMember {
+            group_id: "g2".into(),
+            user_email: alice.clone(),
+            role: GroupRole::Editor,
+            added_at: UNIX_EPOCH,
+            added_by: bob.clone(),
+        })
+        .unwrap();
+        let ids = |groups: Vec<LinkGroup>| -> Vec<String> {
+            let mut ids: Vec<String> = groups.into_iter().map(|g| g.id).collect();
+            ids.sort();
+            ids
+        };
+        assert_eq!(ids(repo.list_child_groups("g1").unwrap()), ["g2"]);
+        assert_eq!(ids(repo.list_groups(&alice).unwrap()), ["g2"]);
+        assert_eq!(ids(repo.list_groups(&bob).unwrap()), ["g1", "g2"]);
+        let user_groups = repo.get_user_groups(&alice).unwrap();
+        assert_eq!(
+            ids(user_groups.into_iter().map(|(g, _)| g).collect()),
+            ["g2"]
+        );
+
+        // Membership changes run as conditional transactions
+        assert!(matches!(
+            repo.add_member(GroupMember {
+                group_id: "g2".into(),
+                user_email: alice.clone(),
+                role: GroupRole::Viewer,
+                added_at: UNIX_EPOCH,
+                added_by: bob.clone(),
+            }),
+            Err(CoreError::AlreadyExists)
+        ));
+        assert!(matches!(
+            repo.transfer_ownership("g2", &bob),
+            Err(CoreError::Conflict(_))
+        ));
+        assert!(matches!(
+            repo.transfer_ownership("missing", &alice),
+            Err(CoreError::NotFound)
+        ));
+        repo.transfer_ownership("g2", &alice).unwrap();
+        assert_eq!(repo.get_group("g2").unwrap().unwrap().created_by, alice);
+        let role = |who: &UserEmail| repo.get_member("g2", who).unwrap().map(|m| m.role);
+        assert_eq!(role(&alice), Some(GroupRole::Admin));
+        assert!(matches!(
+            repo.remove_member("g2", &alice),
+            Err(CoreError::Conflict(_))
+        ));
+        repo.add_member(GroupMember {
+            group_id: "g2".into(),
+            user_email: bob.clone(),
+            role: GroupRole::Admin,
+            added_at: UNIX_EPOCH,
+            added_by: alice.clone(),
+        })
+        .unwrap();
+        repo.remove_member("g2", &alice).unwrap();
+        assert_eq!(role(&alice), None);
+        assert!(matches!(
+            repo.remove_member("g2", &alice),
+            Err(CoreError::NotFound)
+        ));
+
+        let entry = |id: &str, secs: u64, actor: &UserEmail, target: &str| AuditEntry {
+            id: id.into(),
+            timestamp: secs_to_system_time(1_700_000_000 + secs),
+            actor_email: actor.clone(),
+            action: AuditAction::Update,
+            target_type: "link".into(),
+            target_id: target.into(),
+            changes: None,
+        };
+        repo.log(entry("a1", 1, &alice, "docs")).unwrap();
+        repo.log(entry("a2", 2, &bob, "docs")).unwrap();
+        repo.log(entry("a3", 3, &alice, "blog")).unwrap();
+        acme.log(entry("a4", 4, &alice, "docs")).unwrap();
+        let audit_ids = |entries: Vec<AuditEntry>| -> Vec<String> {
+            entries.into_iter().map(|e| e.id).collect()
+        };
+        assert_eq!(audit_ids(repo.list_recent(2).unwrap()), ["a3", "a2"]);
+        assert_eq!(
+            audit_ids(repo.list_by_actor(&alice, 10).unwrap()),
+            ["a3", "a1"]
+        );
+        assert_eq!(
+            audit_ids(repo.list_for_target("link", "docs", 10).unwrap()),
+            ["a2", "a1"]
+        );
+
+        // Links written before the tenant index only show up once backfilled
+        let mut old = domain_to_item(&link("old", 0, &bob, None));
+        old.remove("target_key");
+        repo.block_on(
+            repo.client
+                .put_item()
+                .table_name(&repo.table_shortlinks)
+                .set_item(Some(old))
+                .send(),
+        )
+        .unwrap();
+        assert_eq!(slugs(repo.list(10).unwrap()), ["news", "blog", "docs"]);
+        assert_eq!(repo.backfill_index_attributes().unwrap(), 1);
+        assert_eq!(
+            slugs(repo.list(10).unwrap()),
+            ["news", "blog", "docs", "old"]
+        );
+        assert_eq!(
+            slugs(
+                repo.list_by_target("https://example.com/old", TargetMatch::Exact, 10)
+                    .unwrap()
+            ),
+            ["old"]
+        );
+        assert_eq!(repo.backfill_index_attributes().unwrap(), 0);
+
+        // Organizations are found by host through their host items
+        let mut org = Organization {
+            id: TenantId::new("acme").unwrap(),
+            hosts: vec!["go.acme.com".into(), "Links.acme.com".into()],
+            ..Organization::fallback()
+        };
+        repo.put_organization(org.clone()).unwrap();
+        let by_host = |host: &str| {
+            repo.get_organization_by_host(host)
+                .unwrap()
+                .map(|o| o.id.as_str().to_string())
+        };
+        assert_eq!(by_host("links.acme.com:443").as_deref(), Some("acme"));
+        assert_eq!(by_host("go.globex.com"), None);
+        org.hosts = vec!["go.acme.com".into()];
+        repo.put_organization(org.clone()).unwrap();
+        assert_eq!(by_host("links.acme.com"), None);
+        assert_eq!(repo.list_organizations().unwrap(), [org]);
+    }
+
+    #[test]
+    fn deleting_links_drops_their_grants_on_dynamodb_local() {
+        let Ok(endpoint) = std::env::var("DYNAMODB_LOCAL_ENDPOINT") else {
+            return;
+        };
+        let repo = dynamodb_local_repo(&endpoint);
+        domain::adapters::conformance::deleting_links_drops_their_grants(&repo, &repo, &repo);
+    }
+
+    /// Filtered listings read `tenant-index` page by page and drop what the
+    /// filter rejects, so cursors have to resume mid-partition and a page may
+    /// take several reads to fill.
+    #[test]
+    fn filtered_listings_page_through_tenant_index_on_dynamodb_local() {
+        let Ok(endpoint) = std::env::var("DYNAMODB_LOCAL_ENDPOINT") else {
+            return;
+        };
+        let repo = dynamodb_local_repo(&endpoint);
+        let acme = repo.for_tenant(&TenantId::new("acme").unwrap());
+        let alice = UserEmail::new("alice@example.com").unwrap();
+        let link = |slug: &str, secs: u64, host: &str| {
+            ShortLink::new(
+                Slug::new(slug).unwrap(),
+                format!("https://{host}/{slug}"),
+                secs_to_system_time(1_700_000_000 + secs),
+                alice.clone(),
+            )
+        };
+        for (i, host) in [
+            "other.org",
+            "example.com",
+            "example.com",
+            "other.org",
+            "other.org",
+            "example.com",
+            "example.com",
+            "example.com",
+            "other.org",
+            "example.com",
+        ]
+        .iter()
+        .enumerate()
+        {
+            repo.put(link(&format!("l{i}"), i as u64, host)).unwrap();
+        }
+        repo.delete(
+            &Slug::new("l7").unwrap(),
+            secs_to_system_time(1_700_000_100),
+        )
+        .unwrap();
+        acme.put(link("acme", 20, "example.com")).unwrap();
+
+        // Every page of the listing, following cursors until there are none
+        let walk = |direction: SortDirection| {
+            let mut pages = Vec::new();
+            let mut cursor = None;
+            loop {
+                let page = repo
+                    .list_paginated(&ListOptions {
+                        limit: 2,
+                        target_host: Some("example.com".into()),
+                        direction,
+                        cursor: cursor.take(),
+                        ..Default::default()
+                    })
+                    .unwrap();
+                let slugs: Vec<String> = page
+                    .items
+                    .iter()
+                    .map(|l| l.slug.as_str().to_string())
+                    .collect();
+                pages.push((slugs, page.total, page.has_more));
+                match page.next_cursor {
+                    Some(next) => cursor = Some(next),
+                    None => return pages,
+                }
+            }
+        };
+        let page = |slugs: &[&str], total: Option<usize>, has_more: bool| {
+            (
+                slugs.iter().map(|s| s.to_string()).collect(),
+                total,
+                has_more,
+            )
+        };
+        assert_eq!(
+            walk(SortDirection::Desc),
+            [
+                page(&["l9", "l6"], None, true),
+                page(&["l5", "l2"], None, true),
+                page(&["l1"], None, false),
+            ]
+        );
+        assert_eq!(
+            walk(SortDirection::Asc),
+            [
+                page(&["l1", "l2"], None, true),
+                page(&["l5", "l6"], None, true),
+                page(&["l9"], None, false),
+            ]
+        );
+
+        // A page ending on the last match has no cursor to an empty page
+        let all = repo
+            .list_paginated(&ListOptions {
+                limit: 5,
+                target_host: Some("example.com".into()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(all.items.len(), 5);
+        assert!(!all.has_more);
+        assert_eq!(all.next_cursor, None);
+    }
+
+    #[test]
+    fn accessible_listings_page_through_indexes_on_dynamodb_local() {
+        let Ok(endpoint) = std::env::var("DYNAMODB_LOCAL_ENDPOINT") else {
+            return;
+        };
+        let repo = dynamodb_local_repo(&endpoint);
+        let alice = UserEmail::new("alice@example.com").unwrap();
+        let bob = UserEmail::new("bob@example.com").unwrap();
+        let link = |slug: &str, secs: u64, owner: &UserEmail, group: Option<&str>| {
+            let mut link = ShortLink::new(
+                Slug::new(slug).unwrap(),
+                format!("https://example.com/{slug}"),
+                secs_to_system_time(1_700_000_000 + secs),
+                owner.clone(),
+            );
+            link.group_id = group.map(str::to_string);
+            link
+        };
+        for l in [
+            link("a0", 0, &alice, None),
+            link("g1", 1, &bob, Some("team")),
+            link("a2", 2, &alice, None),
+            link("g2", 2, &bob, Some("team")),
+            link("b3", 3, &bob, None),
+            link("b4", 4, &bob, None),
+        ] {
+            repo.put(l).unwrap();
+        }
+        let scope = AccessScope {
+            user: alice.clone(),
+            group_ids: vec!["team".into()],
+            shared_slugs: vec![Slug::new("b3").unwrap()],
+        };
+
+        let walk = |limit: usize, direction: SortDirection| {
+            let mut pages = Vec::new();
+            let mut cursor = None;
+            loop {
+                let page = repo
+                    .list_paginated(&ListOptions {
+                        limit,
+                        direction,
+                        accessible_to: Some(scope.clone()),
+                        cursor: cursor.take(),
+                        ..Default::default()
+                    })
+                    .unwrap();
+                assert_eq!(page.total, None);
+                let slugs: Vec<String> = page
+                    .items
+                    .iter()
+                    .map(|l| l.slug.as_str().to_string())
+                    .collect();
+                pages.push((slugs, page.has_more));
+                match page.next_cursor {
+                    Some(next) => cursor = Some(next),
+                    None => return pages,
+                }
+            }
+        };
+        let page = |slugs: &[&str], has_more: bool| {
+            (slugs.iter().map(|s| s.to_string()).collect(), has_more)
+        };
+        // a2 and g2 share a second and come from different indexes
+        assert_eq!(
+            walk(2, SortDirection::Desc),
+            [
+                page(&["b3", "g2"], true),
+                page(&["a2", "g1"], true),
+                page(&["a0"], false),
+            ]
+        );
+        assert_eq!(
+            walk(2, SortDirection::Asc),
+            [
+                page(&["a0", "g1"], true),
+                page(&["a2", "g2"], true),
+                page(&["b3"], false),
+            ]
+        );
+        assert_eq!(
+            walk(5, SortDirection::Desc),
+            [page(&["b3", "g2", "a2", "g1", "a0"], false)]
+        );
     }
 }
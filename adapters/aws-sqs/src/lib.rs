@@ -0,0 +1,608 @@
+//! aws-sqs — Amazon SQS transport for batched click counting.
+//!
+//! Purpose
+//! - Implements `domain::clicks::ClickQueue` on an SQS queue: redirect Lambdas
+//!   send their click batches to it and `click-aggregator` applies them.
+//! - Works with anything speaking the SQS JSON protocol: SQS, ElasticMQ,
+//!   LocalStack.
+//!
+//! Notes
+//! - Requests go to the origin of the queue URL, so
+//!   `http://localhost:9324/000000000000/clicks` talks to a local ElasticMQ.
+//!   They are signed with SigV4 using the default AWS credential chain; the
+//!   region comes from the AWS config, else from the queue URL's host.
+//! - Each batch is one JSON message. `receive` long-polls for up to the wait
+//!   time (20 seconds by default) and skips messages it can't decode; give the
+//!   queue a dead-letter queue to keep them.
+//! - The `ClickQueue` port is synchronous. Like `aws-dynamo`, calls block on
+//!   the current Tokio runtime, or on one of our own outside of it.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use aws_config::SdkConfig;
+use aws_credential_types::provider::{ProvideCredentials, SharedCredentialsProvider};
+use aws_credential_types::Credentials;
+use aws_sigv4::http_request::{sign, SignableBody, SignableRequest, SigningSettings};
+use aws_sigv4::sign::v4;
+use domain::clicks::{ClickBatch, ClickCount, ClickQueue, QueuedBatch};
+use domain::{ClickEvent, CoreError, Slug, TenantId};
+use serde_json::{json, Value};
+
+/// Most messages SQS returns or deletes per call.
+const MAX_MESSAGES: usize = 10;
+
+/// How long before expiry cached credentials are refreshed.
+const CREDENTIALS_MARGIN: Duration = Duration::from_secs(300);
+
+/// Click queue on an SQS queue.
+pub struct SqsClickQueue {
+    queue_url: String,
+    endpoint: String,
+    region: String,
+    credentials: SharedCredentialsProvider,
+    cached: Mutex<Option<Credentials>>,
+    http: reqwest::Client,
+    wait_time: Duration,
+    timeout: Duration,
+    // Optional runtime - None when running inside Lambda (reuses existing runtime)
+    rt: Option<Arc<tokio::runtime::Runtime>>,
+}
+
+impl SqsClickQueue {
+    /// Queue at `queue_url`, with credentials and region from env/profile/IMDS.
+    pub fn new(queue_url: &str) -> Result<Self, CoreError> {
+        let rt = maybe_create_runtime()?;
+        let loader = aws_config::defaults(aws_config::BehaviorVersion::latest());
+        let conf = block_on_with_rt(&rt, loader.load());
+        Self::build(queue_url, &conf, rt)
+    }
+
+    /// Queue at `queue_url`, with credentials and region from `conf`.
+    pub fn with_config(queue_url: &str, conf: &SdkConfig) -> Result<Self, CoreError> {
+        Self::build(queue_url, conf, maybe_create_runtime()?)
+    }
+
+    /// Queue from `CLICK_QUEUE_URL`.
+    pub fn from_env() -> Result<Self, CoreError> {
+        let url = std::env::var("CLICK_QUEUE_URL")
+            .map_err(|_| CoreError::Repository("CLICK_QUEUE_URL not set".into()))?;
+        Self::new(&url)
+    }
+
+    /// How long `receive` waits for a message on an empty queue (at most 20
+    /// seconds).
+    pub fn with_wait_time(mut self, wait_time: Duration) -> Self {
+        self.wait_time = wait_time.min(Duration::from_secs(20));
+        self
+    }
+
+    /// How long `send` and `ack` wait for SQS (default 5 seconds).
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn build(
+        queue_url: &str,
+        conf: &SdkConfig,
+        rt: Option<Arc<tokio::runtime::Runtime>>,
+    ) -> Result<Self, CoreError> {
+        let invalid = || CoreError::Repository(format!("invalid queue url: {queue_url}"));
+        let (scheme, rest) = queue_url.split_once("://").ok_or_else(invalid)?;
+        let host = rest.split('/').next().filter(|h| !h.is_empty());
+        let host = host.ok_or_else(invalid)?;
+        let region = conf
+            .region()
+            .map(|r| r.as_ref().to_string())
+            .or_else(|| region_from_host(host))
+            .ok_or_else(|| CoreError::Repository("no AWS region configured".into()))?;
+        let credentials = conf
+            .credentials_provider()
+            .ok_or_else(|| CoreError::Repository("no AWS credentials configured".into()))?;
+        Ok(Self {
+            queue_url: queue_url.to_string(),
+            endpoint: format!("{scheme}://{host}/"),
+            region,
+            credentials,
+            cached: Mutex::new(None),
+            http: reqwest::Client::new(),
+            wait_time: Duration::from_secs(20),
+            timeout: Duration::from_secs(5),
+            rt,
+        })
+    }
+
+    /// Run an async future, using either our owned runtime or the current runtime.
+    fn block_on<F: std::future::Future>(&self, fut: F) -> F::Output {
+        block_on_with_rt(&self.rt, fut)
+    }
+
+    async fn credentials(&self) -> Result<Credentials, CoreError> {
+        let cached = self.cached.lock().ok().and_then(|c| c.clone());
+        if let Some(creds) = cached.filter(|c| {
+            c.expiry()
+                .is_none_or(|at| at > SystemTime::now() + CREDENTIALS_MARGIN)
+        }) {
+            return Ok(creds);
+        }
+        let creds = self
+            .credentials
+            .provide_credentials()
+            .await
+            .map_err(|e| CoreError::Repository(format!("aws credentials: {e}")))?;
+        if let Ok(mut cached) = self.cached.lock() {
+            *cached = Some(creds.clone());
+        }
+        Ok(creds)
+    }
+
+    /// Call the SQS `action` with `params` (the queue URL is added).
+    fn call(&self, action: &str, mut params: Value, timeout: Duration) -> Result<Value, CoreError> {
+        params["QueueUrl"] = json!(self.queue_url);
+        let body = params.to_string();
+        let target = format!("AmazonSQS.{action}");
+        let headers = [
+            ("content-type", "application/x-amz-json-1.0"),
+            ("x-amz-target", target.as_str()),
+        ];
+        let fut = async {
+            let identity = self.credentials().await?.into();
+            let signing_params = v4::SigningParams::builder()
+                .identity(&identity)
+                .region(&self.region)
+                .name("sqs")
+                .time(SystemTime::now())
+                .settings(SigningSettings::default())
+                .build()
+                .map_err(|e| CoreError::Repository(format!("sqs signing: {e}")))?
+                .into();
+            let signable = SignableRequest::new(
+                "POST",
+                &self.endpoint,
+                headers.iter().copied(),
+                SignableBody::Bytes(body.as_bytes()),
+            )
+            .map_err(|e| CoreError::Repository(format!("sqs signing: {e}")))?;
+            let (signature, _) = sign(signable, &signing_params)
+                .map_err(|e| CoreError::Repository(format!("sqs signing: {e}")))?
+                .into_parts();
+
+            let mut request = self
+                .http
+                .post(&self.endpoint)
+                .timeout(timeout)
+                .body(body.clone());
+            for (name, value) in headers.iter().copied().chain(signature.headers()) {
+                request = request.header(name, value);
+            }
+            let response = request
+                .send()
+                .await
+                .map_err(|e| CoreError::Repository(format!("sqs {action}: {e}")))?;
+            let status = response.status();
+            let bytes = response
+                .bytes()
+                .await
+                .map_err(|e| CoreError::Repository(format!("sqs {action}: {e}")))?;
+            let reply: Value = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+            if !status.is_success() {
+                let kind = reply["__type"].as_str().unwrap_or("error");
+                let kind = kind.rsplit('#').next().unwrap_or(kind);
+                let message = reply["message"].as_str().unwrap_or_default();
+                return Err(CoreError::Repository(format!(
+                    "sqs {action}: {status} {kind} {message}"
+                )));
+            }
+            Ok(reply)
+        };
+        self.block_on(fut)
+    }
+}
+
+impl ClickQueue for SqsClickQueue {
+    fn send(&self, batch: &ClickBatch) -> Result<(), CoreError> {
+        let params = json!({ "MessageBody": encode(batch).to_string() });
+        self.call("SendMessage", params, self.timeout)?;
+        Ok(())
+    }
+
+    fn receive(&self, max: usize) -> Result<Vec<QueuedBatch>, CoreError> {
+        if max == 0 {
+            return Ok(Vec::new());
+        }
+        let params = json!({
+            "MaxNumberOfMessages": max.min(MAX_MESSAGES),
+            "WaitTimeSeconds": self.wait_time.as_secs(),
+        });
+        let reply = self.call("ReceiveMessage", params, self.wait_time + self.timeout)?;
+        let messages = reply["Messages"].as_array().cloned().unwrap_or_default();
+        Ok(messages
+            .iter()
+            .filter_map(|m| {
+                let receipt = m["ReceiptHandle"].as_str()?.to_string();
+                let batch = decode(m["Body"].as_str()?).ok()?;
+                Some(QueuedBatch { receipt, batch })
+            })
+            .collect())
+    }
+
+    fn ack(&self, receipts: &[String]) -> Result<(), CoreError> {
+        for chunk in receipts.chunks(MAX_MESSAGES) {
+            let entries: Vec<Value> = chunk
+                .iter()
+                .enumerate()
+                .map(|(i, receipt)| json!({ "Id": i.to_string(), "ReceiptHandle": receipt }))
+                .collect();
+            let params = json!({ "Entries": entries });
+            let reply = self.call("DeleteMessageBatch", params, self.timeout)?;
+            if let Some(failed) = reply["Failed"].as_array().and_then(|f| f.first()) {
+                return Err(CoreError::Repository(format!(
+                    "sqs DeleteMessageBatch: {} {}",
+                    failed["Code"].as_str().unwrap_or("error"),
+                    failed["Message"].as_str().unwrap_or_default()
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Region of an AWS queue host like `sqs.eu-west-1.amazonaws.com`.
+fn region_from_host(host: &str) -> Option<String> {
+    let rest = host.strip_prefix("sqs.")?;
+    let (region, _) = rest.split_once(".amazonaws.com")?;
+    (!region.is_empty() && !region.contains('.')).then(|| region.to_string())
+}
+
+/// Check if we're inside a Tokio runtime. If yes, return None (reuse existing).
+/// If no, create a new runtime.
+fn maybe_create_runtime() -> Result<Option<Arc<tokio::runtime::Runtime>>, CoreError> {
+    if tokio::runtime::Handle::try_current().is_ok() {
+        Ok(None)
+    } else {
+        let rt = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .map_err(|e| CoreError::Repository(format!("tokio runtime init: {e}")))?;
+        Ok(Some(Arc::new(rt)))
+    }
+}
+
+fn block_on_with_rt<F: std::future::Future>(
+    rt: &Option<Arc<tokio::runtime::Runtime>>,
+    fut: F,
+) -> F::Output {
+    match rt {
+        Some(rt) => rt.block_on(fut),
+        None => tokio::task::block_in_place(|| tokio::runtime::Handle::current().block_on(fut)),
+    }
+}
+
+// ============ Encoding ============
+
+fn millis(t: SystemTime) -> u64 {
+    t.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn encode(batch: &ClickBatch) -> Value {
+    let counts: Vec<Value> = batch
+        .counts
+        .iter()
+        .map(|c| {
+            json!({
+                "tenant": c.tenant.as_str(),
+                "slug": c.slug.key(),
+                "clicks": c.clicks,
+            })
+        })
+        .collect();
+    let events: Vec<Value> = batch
+        .events
+        .iter()
+        .map(|(tenant, e)| {
+            json!({
+                "tenant": tenant.as_str(),
+                "slug": e.slug.key(),
+                "clicked_at": millis(e.clicked_at),
+                "user_agent": e.user_agent,
+                "referrer": e.referrer,
+                "country": e.country,
+            })
+        })
+        .collect();
+    json!({ "counts": counts, "events": events })
+}
+
+fn decode(body: &str) -> Result<ClickBatch, CoreError> {
+    let bad = || CoreError::Repository("bad click batch".into());
+    let value: Value = serde_json::from_str(body).map_err(|_| bad())?;
+    let list = |field: &str| value[field].as_array().cloned().ok_or_else(bad);
+    let text = |v: &Value, field: &str| v[field].as_str().map(str::to_string).ok_or_else(bad);
+    let counts = list("counts")?
+        .iter()
+        .map(|c| {
+            Ok(ClickCount {
+                tenant: TenantId::new(text(c, "tenant")?)?,
+                slug: Slug::from_key(&text(c, "slug")?)?,
+                clicks: c["clicks"].as_u64().ok_or_else(bad)?,
+            })
+        })
+        .collect::<Result<_, CoreError>>()?;
+    let events = list("events")?
+        .iter()
+        .map(|e| {
+            let event = ClickEvent {
+                slug: Slug::from_key(&text(e, "slug")?)?,
+                clicked_at: UNIX_EPOCH
+                    + Duration::from_millis(e["clicked_at"].as_u64().ok_or_else(bad)?),
+                user_agent: e["user_agent"].as_str().map(str::to_string),
+                referrer: e["referrer"].as_str().map(str::to_string),
+                country: e["country"].as_str().map(str::to_string),
+            };
+            Ok((TenantId::new(text(e, "tenant")?)?, event))
+        })
+        .collect::<Result<_, CoreError>>()?;
+    Ok(ClickBatch { counts, events })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use domain::adapters::memory_repo::{InMemoryClickRepo, InMemoryRepo};
+    use domain::clicks::ClickAggregator;
+    use domain::{LinkRepository, ShortLink, TenantScoped, UserEmail};
+    use std::collections::{HashMap, VecDeque};
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+
+    const QUEUE_PATH: &str = "/000000000000/clicks";
+
+    fn batch() -> ClickBatch {
+        let acme = TenantId::new("acme").unwrap();
+        ClickBatch {
+            counts: vec![ClickCount {
+                tenant: acme.clone(),
+                slug: Slug::new("docs")
+                    .unwrap()
+                    .on_domain(Some("go.acme.com".into())),
+                clicks: 3,
+            }],
+            events: vec![(
+                acme,
+                ClickEvent {
+                    slug: Slug::new("docs").unwrap(),
+                    clicked_at: UNIX_EPOCH + Duration::from_millis(1_700_000_000_123),
+                    user_agent: Some("curl/8".into()),
+                    referrer: None,
+                    country: Some("NO".into()),
+                },
+            )],
+        }
+    }
+
+    fn config() -> SdkConfig {
+        SdkConfig::builder()
+            .region(aws_config::Region::new("eu-west-1"))
+            .credentials_provider(SharedCredentialsProvider::new(Credentials::new(
+                "AKIDTEST", "secret", None, None, "test",
+            )))
+            .behavior_version(aws_config::BehaviorVersion::latest())
+            .build()
+    }
+
+    /// Messages of the stand-in queue: waiting (receipt, body) pairs and
+    /// received ones by receipt.
+    #[derive(Default)]
+    struct Messages {
+        waiting: VecDeque<(String, String)>,
+        in_flight: HashMap<String, String>,
+        sent: usize,
+    }
+
+    impl Messages {
+        fn answer(&mut self, action: &str, params: &Value) -> Value {
+            match action {
+                "AmazonSQS.SendMessage" => {
+                    self.sent += 1;
+                    let body = params["MessageBody"].as_str().unwrap();
+                    self.waiting
+                        .push_back((format!("r{}", self.sent), body.to_string()));
+                    json!({ "MessageId": self.sent.to_string() })
+                }
+                "AmazonSQS.ReceiveMessage" => {
+                    let max = params["MaxNumberOfMessages"].as_u64().unwrap() as usize;
+                    let mut out = Vec::new();
+                    while out.len() < max {
+                        let Some((receipt, body)) = self.waiting.pop_front() else {
+                            break;
+                        };
+                        out.push(json!({ "ReceiptHandle": receipt, "Body": body }));
+                        self.in_flight.insert(receipt, body);
+                    }
+                    json!({ "Messages": out })
+                }
+                "AmazonSQS.DeleteMessageBatch" => {
+                    let mut ok = Vec::new();
+                    for entry in params["Entries"].as_array().unwrap() {
+                        self.in_flight
+                            .remove(entry["ReceiptHandle"].as_str().unwrap());
+                        ok.push(json!({ "Id": entry["Id"] }));
+                    }
+                    json!({ "Successful": ok, "Failed": [] })
+                }
+                other => panic!("unexpected action {other}"),
+            }
+        }
+    }
+
+    type Shared<T> = Arc<Mutex<T>>;
+
+    /// Local stand-in for SQS: answers `SendMessage`, `ReceiveMessage` and
+    /// `DeleteMessageBatch` for the queue at [`QUEUE_PATH`] from memory, and
+    /// ignores visibility timeouts. Returns the queue URL, the `Authorization`
+    /// headers it received and its messages.
+    fn stand_in() -> (String, Shared<Vec<String>>, Shared<Messages>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let queue_url = format!("http://{}{QUEUE_PATH}", listener.local_addr().unwrap());
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&log);
+        let url = queue_url.clone();
+        let messages = Arc::new(Mutex::new(Messages::default()));
+        let held = Arc::clone(&messages);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let (url, seen, messages) = (url.clone(), seen.clone(), messages.clone());
+                std::thread::spawn(move || {
+                    let mut conn = BufReader::new(stream.unwrap());
+                    loop {
+                        let mut line = String::new();
+                        if conn.read_line(&mut line).unwrap_or(0) == 0 {
+                            return;
+                        }
+                        let mut headers = HashMap::new();
+                        loop {
+                            line.clear();
+                            conn.read_line(&mut line).unwrap();
+                            let Some((name, value)) = line.trim_end().split_once(": ") else {
+                                break;
+                            };
+                            headers.insert(name.to_lowercase(), value.to_string());
+                        }
+                        let mut body = vec![0; headers["content-length"].parse().unwrap()];
+                        conn.read_exact(&mut body).unwrap();
+                        let params: Value = serde_json::from_slice(&body).unwrap();
+                        let auth = headers.get("authorization").cloned();
+                        seen.lock().unwrap().push(auth.unwrap_or_default());
+
+                        let (status, reply) = if params["QueueUrl"] == json!(url) {
+                            let mut messages = messages.lock().unwrap();
+                            ("200 OK", messages.answer(&headers["x-amz-target"], &params))
+                        } else {
+                            let error = json!({
+                                "__type": "com.amazonaws.sqs#QueueDoesNotExist",
+                                "message": "The specified queue does not exist.",
+                            });
+                            ("400 Bad Request", error)
+                        };
+                        let reply = reply.to_string();
+                        write!(
+                            conn.get_mut(),
+                            "HTTP/1.1 {status}\r\ncontent-type: application/x-amz-json-1.0\r\ncontent-length: {}\r\n\r\n{reply}",
+                            reply.len()
+                        )
+                        .unwrap();
+                    }
+                });
+            }
+        });
+        (queue_url, log, held)
+    }
+
+    #[test]
+    fn batches_round_trip_through_a_stand_in() {
+        let (url, log, _) = stand_in();
+        let queue = SqsClickQueue::with_config(&url, &config()).unwrap();
+        queue.send(&batch()).unwrap();
+        queue.send(&ClickBatch::default()).unwrap();
+
+        let received = queue.receive(10).unwrap();
+        assert_eq!(received.len(), 2);
+        assert_eq!(received[0].batch, batch());
+        assert!(received[1].batch.is_empty());
+        let receipts: Vec<String> = received.into_iter().map(|q| q.receipt).collect();
+        queue.ack(&receipts).unwrap();
+        assert!(queue.receive(10).unwrap().is_empty());
+
+        let auth = log.lock().unwrap();
+        assert_eq!(auth.len(), 5);
+        assert!(auth
+            .iter()
+            .all(|a| a.starts_with("AWS4-HMAC-SHA256 Credential=AKIDTEST/")
+                && a.contains("/eu-west-1/sqs/aws4_request")));
+    }
+
+    #[test]
+    fn aggregator_applies_and_acknowledges_batches_from_sqs() {
+        let (url, _, messages) = stand_in();
+        let queue = Arc::new(SqsClickQueue::with_config(&url, &config()).unwrap());
+        for _ in 0..12 {
+            queue.send(&batch()).unwrap();
+        }
+        let acme = TenantId::new("acme").unwrap();
+        let links = InMemoryRepo::new();
+        let docs = ShortLink::new(
+            Slug::new("docs")
+                .unwrap()
+                .on_domain(Some("go.acme.com".into())),
+            "https://example.com".into(),
+            UNIX_EPOCH,
+            UserEmail::new("a@acme.com").unwrap(),
+        );
+        let links = links.for_tenant(&acme);
+        links.put(docs.clone()).unwrap();
+        let aggregator =
+            ClickAggregator::new(queue, links.for_tenant(&acme), InMemoryClickRepo::new());
+
+        assert_eq!(aggregator.drain(MAX_MESSAGES).unwrap(), 12);
+        let messages = messages.lock().unwrap();
+        assert!(messages.waiting.is_empty());
+        assert!(messages.in_flight.is_empty(), "every batch acknowledged");
+        let counted = links.get(&docs.slug).unwrap().unwrap();
+        assert_eq!(counted.click_count, 36);
+    }
+
+    #[test]
+    fn sqs_errors_are_reported() {
+        let (url, _, _) = stand_in();
+        let queue = SqsClickQueue::with_config(&format!("{url}-missing"), &config()).unwrap();
+        let err = queue.send(&batch()).unwrap_err();
+        assert!(
+            err.to_string().contains("QueueDoesNotExist"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn region_comes_from_aws_queue_hosts() {
+        assert_eq!(
+            region_from_host("sqs.eu-north-1.amazonaws.com").as_deref(),
+            Some("eu-north-1")
+        );
+        assert_eq!(region_from_host("localhost:9324"), None);
+        let conf = SdkConfig::builder()
+            .credentials_provider(SharedCredentialsProvider::new(Credentials::new(
+                "AKIDTEST", "secret", None, None, "test",
+            )))
+            .build();
+        let queue =
+            SqsClickQueue::with_config("https://sqs.us-west-2.amazonaws.com/1/clicks", &conf)
+                .unwrap();
+        assert_eq!(queue.region, "us-west-2");
+        assert_eq!(queue.endpoint, "https://sqs.us-west-2.amazonaws.com/");
+        assert!(SqsClickQueue::with_config("http://localhost:9324/1/clicks", &conf).is_err());
+    }
+
+    /// Runs against a real SQS-compatible queue when `SQS_TEST_QUEUE_URL` is
+    /// set, e.g. ElasticMQ (`docker run -p 9324:9324 softwaremill/elasticmq`,
+    /// queue `http://localhost:9324/000000000000/clicks`) with any
+    /// `AWS_ACCESS_KEY_ID`/`AWS_SECRET_ACCESS_KEY` and `AWS_REGION`. Uses a
+    /// queue nothing else reads.
+    #[test]
+    fn round_trip_against_sqs() {
+        let Ok(url) = std::env::var("SQS_TEST_QUEUE_URL") else {
+            return;
+        };
+        let queue = SqsClickQueue::new(&url)
+            .unwrap()
+            .with_wait_time(Duration::from_secs(1));
+        queue.send(&batch()).unwrap();
+        let received = queue.receive(10).unwrap();
+        assert!(received.iter().any(|q| q.batch == batch()));
+        let receipts: Vec<String> = received.into_iter().map(|q| q.receipt).collect();
+        queue.ack(&receipts).unwrap();
+    }
+}
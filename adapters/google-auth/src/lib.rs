@@ -9,6 +9,11 @@
 //!
 //! API
 //! - `verify(id_token, expected_aud, allowed_domain)` → `Result<VerifiedUser, AuthError>`
+//! - `issue_session(user, secret, ttl)` / `verify_session(token, secret, allowed_domain)` —
+//!   HS256-signed session tokens used to remember a visitor (e.g. in a cookie) after
+//!   they signed in once. The domain checks are re-applied on every verification.
+//! - `token_domain(token)` — unverified peek at the sign-in domain, used to pick
+//!   the organization (and thus the allowed domain) before verifying.
 //!
 //! Notes
 //! - Uses blocking networking via `reqwest` to fetch JWKS and caches keys in
@@ -17,7 +22,7 @@
 
 use base64::Engine;
 use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::{LazyLock, Mutex};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
@@ -27,6 +32,8 @@ use tracing::trace;
 pub struct VerifiedUser {
     pub email: String,
     pub sub: String,
+    /// Hosted domain claim (`hd`) for Google Workspace accounts, if present.
+    pub hd: Option<String>,
 }
 
 #[derive(Debug, thiserror::Error, PartialEq, Eq)]
@@ -49,7 +56,7 @@ pub enum AuthError {
     Network,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 struct Claims {
     // Registered claims
     sub: String,
@@ -141,6 +148,22 @@ fn verify_claims_only(
     apply_domain_checks(claims, allowed_domain)
 }
 
+/// Sign-in domain a token claims (`hd`, else the email domain), read WITHOUT
+/// verifying it. Only use this to choose which rules to verify against; the
+/// subsequent `verify_async`/`verify_session` call enforces the domain.
+pub fn token_domain(token: &str) -> Option<String> {
+    let payload_b64 = token.split('.').nth(1)?;
+    let payload_bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(payload_b64.as_bytes())
+        .ok()?;
+    let payload: serde_json::Value = serde_json::from_slice(&payload_bytes).ok()?;
+    if let Some(hd) = payload.get("hd").and_then(|v| v.as_str()) {
+        return Some(hd.to_ascii_lowercase());
+    }
+    let email = payload.get("email")?.as_str()?;
+    email.rsplit_once('@').map(|(_, d)| d.to_ascii_lowercase())
+}
+
 fn apply_domain_checks(claims: Claims, allowed_domain: &str) -> Result<VerifiedUser, AuthError> {
     // Email checks
     let email = claims.email.ok_or(AuthError::InvalidPayload("email"))?;
@@ -150,7 +173,7 @@ fn apply_domain_checks(claims: Claims, allowed_domain: &str) -> Result<VerifiedU
 
     // Domain enforcement: prefer `hd`, fallback to email domain
     let domain_ok = match claims.hd {
-        Some(hd) => hd.eq_ignore_ascii_case(allowed_domain),
+        Some(ref hd) => hd.eq_ignore_ascii_case(allowed_domain),
         None => email
             .rsplit_once('@')
             .map(|(_, d)| d.eq_ignore_ascii_case(allowed_domain))
@@ -163,9 +186,84 @@ fn apply_domain_checks(claims: Claims, allowed_domain: &str) -> Result<VerifiedU
     Ok(VerifiedUser {
         email,
         sub: claims.sub,
+        hd: claims.hd,
+    })
+}
+
+// ---- Sessions ----
+
+const SESSION_ISSUER: &str = "url-shortener";
+const SESSION_AUDIENCE: &str = "url-shortener-session";
+
+/// Issue a signed session token for a user that has already been verified.
+///
+/// The token is an HS256 JWT carrying the email, `sub` and `hd` claims so that
+/// `verify_session` can run the same domain checks as an ID token verification.
+pub fn issue_session(
+    user: &VerifiedUser,
+    secret: &[u8],
+    ttl: Duration,
+) -> Result<String, AuthError> {
+    let exp = SystemTime::now()
+        .checked_add(ttl)
+        .ok_or(AuthError::Malformed)?
+        .duration_since(UNIX_EPOCH)
+        .map_err(|_| AuthError::Malformed)?
+        .as_secs();
+    let claims = Claims {
+        sub: user.sub.clone(),
+        aud: serde_json::Value::String(SESSION_AUDIENCE.into()),
+        exp: Some(exp),
+        iss: Some(SESSION_ISSUER.into()),
+        email: Some(user.email.clone()),
+        email_verified: Some(true),
+        hd: user.hd.clone(),
+    };
+    jsonwebtoken::encode(
+        &jsonwebtoken::Header::new(Algorithm::HS256),
+        &claims,
+        &jsonwebtoken::EncodingKey::from_secret(secret),
+    )
+    .map_err(|_| AuthError::Malformed)
+}
+
+/// Verify a session token issued by `issue_session`.
+/// - Validates the HS256 signature, issuer, audience and expiry.
+/// - Re-applies the domain checks so a changed `allowed_domain` takes effect immediately.
+pub fn verify_session(
+    token: &str,
+    secret: &[u8],
+    allowed_domain: &str,
+) -> Result<VerifiedUser, AuthError> {
+    let claims = decode_session_claims(token, secret)?;
+    apply_domain_checks(claims, allowed_domain)
+}
+
+/// Decode a session token without domain enforcement.
+/// Only intended for the debug auth mode where no allowed domain is configured.
+pub fn decode_session(token: &str, secret: &[u8]) -> Result<VerifiedUser, AuthError> {
+    let claims = decode_session_claims(token, secret)?;
+    Ok(VerifiedUser {
+        email: claims.email.ok_or(AuthError::InvalidPayload("email"))?,
+        sub: claims.sub,
+        hd: claims.hd,
     })
 }
 
+fn decode_session_claims(token: &str, secret: &[u8]) -> Result<Claims, AuthError> {
+    let mut validation = Validation::new(Algorithm::HS256);
+    validation.set_audience(&[SESSION_AUDIENCE]);
+    validation.set_issuer(&[SESSION_ISSUER]);
+    let token_data = decode::<Claims>(token, &DecodingKey::from_secret(secret), &validation)
+        .map_err(|e| match e.kind() {
+            jsonwebtoken::errors::ErrorKind::InvalidSignature => AuthError::SignatureInvalid,
+            jsonwebtoken::errors::ErrorKind::ExpiredSignature => AuthError::Expired,
+            jsonwebtoken::errors::ErrorKind::InvalidAudience => AuthError::BadAudience,
+            _ => AuthError::Malformed,
+        })?;
+    Ok(token_data.claims)
+}
+
 // ---- JWKS cache & fetch ----
 
 const JWKS_URL: &str = "https://www.googleapis.com/oauth2/v3/certs";
@@ -308,6 +406,16 @@ mod tests {
         assert_eq!(u.email, "user@acme.com");
     }
 
+    #[test]
+    fn token_domain_peeks_hd_then_email() {
+        let with_hd =
+            token_with_payload(&serde_json::json!({"email":"u@gmail.com","hd":"Acme.com"}));
+        assert_eq!(token_domain(&with_hd).as_deref(), Some("acme.com"));
+        let email_only = token_with_payload(&serde_json::json!({"email":"u@globex.com"}));
+        assert_eq!(token_domain(&email_only).as_deref(), Some("globex.com"));
+        assert_eq!(token_domain("not-a-token"), None);
+    }
+
     #[test]
     fn audience_can_be_array() {
         let exp = (SystemTime::now()
@@ -345,6 +453,50 @@ mod tests {
         assert!(matches!(err, AuthError::DomainNotAllowed));
     }
 
+    #[test]
+    fn session_roundtrip_and_domain_recheck() {
+        let user = VerifiedUser {
+            email: "user@acme.com".into(),
+            sub: "u1".into(),
+            hd: Some("acme.com".into()),
+        };
+        let tok = issue_session(&user, b"secret-1", Duration::from_secs(300)).unwrap();
+        assert_eq!(verify_session(&tok, b"secret-1", "acme.com").unwrap(), user);
+
+        // Wrong secret and wrong domain are both rejected
+        assert_eq!(
+            verify_session(&tok, b"secret-2", "acme.com").unwrap_err(),
+            AuthError::SignatureInvalid
+        );
+        assert_eq!(
+            verify_session(&tok, b"secret-1", "other.com").unwrap_err(),
+            AuthError::DomainNotAllowed
+        );
+    }
+
+    #[test]
+    fn session_rejects_id_token_shape() {
+        // A token signed with the right secret but for a different audience is not a session
+        let claims = serde_json::json!({
+            "sub": "x",
+            "aud": "client-1",
+            "iss": SESSION_ISSUER,
+            "exp": 4_000_000_000u64,
+            "email": "u@acme.com",
+            "email_verified": true
+        });
+        let tok = jsonwebtoken::encode(
+            &jsonwebtoken::Header::new(Algorithm::HS256),
+            &claims,
+            &jsonwebtoken::EncodingKey::from_secret(b"s"),
+        )
+        .unwrap();
+        assert_eq!(
+            verify_session(&tok, b"s", "acme.com").unwrap_err(),
+            AuthError::BadAudience
+        );
+    }
+
     // Signature path tests using a synthetic RSA keypair and JWKS override
     #[tokio::test]
     async fn signature_verification_success_and_failures() {
@@ -0,0 +1,267 @@
+//! Versioned schema migrations.
+//!
+//! Works like the SQLite adapter's: [`MIGRATIONS`] lists every change to the
+//! schema, oldest first, and a step's version is its position in the list,
+//! starting at 1. Opening a database applies the steps it has not seen, each
+//! in its own transaction that also records it in `schema_version`. Steps are
+//! only ever appended: once released, a step must not change.
+//!
+//! Every step first takes a transaction-scoped advisory lock, so servers
+//! starting together against the same database apply each step exactly once.
+
+use std::time::SystemTime;
+
+use domain::CoreError;
+use postgres::{Client, Transaction};
+
+use crate::{map_pgerr, system_time_to_secs};
+
+/// One step of the schema history.
+struct Migration {
+    name: &'static str,
+    apply: fn(&mut Transaction) -> Result<(), CoreError>,
+}
+
+const MIGRATIONS: &[Migration] = &[Migration {
+    name: "baseline",
+    apply: baseline,
+}];
+
+/// Schema version this build writes: the number of known migrations.
+pub const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// Key of the advisory lock held while migrating.
+const MIGRATION_LOCK: i64 = 0x7368_6f72_746c_6e6b;
+
+/// Name of the migration that brings a database to `version`, if it exists.
+pub fn migration_name(version: u32) -> Option<&'static str> {
+    let index = usize::try_from(version).ok()?.checked_sub(1)?;
+    MIGRATIONS.get(index).map(|m| m.name)
+}
+
+/// Version recorded in the database: 0 for a new one.
+pub(crate) fn current_version(client: &mut impl postgres::GenericClient) -> Result<u32, CoreError> {
+    let tracked: bool = client
+        .query_one("SELECT to_regclass('schema_version') IS NOT NULL", &[])
+        .map_err(map_pgerr)?
+        .get(0);
+    if !tracked {
+        return Ok(0);
+    }
+    let version: i64 = client
+        .query_one("SELECT COALESCE(MAX(version), 0) FROM schema_version", &[])
+        .map_err(map_pgerr)?
+        .get(0);
+    u32::try_from(version).map_err(|_| CoreError::Repository("bad schema version".into()))
+}
+
+/// Apply pending migrations and return the resulting version. Refuses
+/// databases already migrated past [`SCHEMA_VERSION`] by a newer build.
+pub(crate) fn migrate(client: &mut Client) -> Result<u32, CoreError> {
+    client
+        .batch_execute(
+            "CREATE TABLE IF NOT EXISTS schema_version (
+                version BIGINT PRIMARY KEY,
+                name TEXT NOT NULL,
+                applied_at BIGINT NOT NULL
+            );",
+        )
+        .map_err(map_pgerr)?;
+    loop {
+        let mut tx = client.transaction().map_err(map_pgerr)?;
+        tx.execute("SELECT pg_advisory_xact_lock($1)", &[&MIGRATION_LOCK])
+            .map_err(map_pgerr)?;
+        let current = current_version(&mut tx)?;
+        if current > SCHEMA_VERSION {
+            return Err(CoreError::Repository(format!(
+                "database schema version {current} is newer than this build supports ({SCHEMA_VERSION}); upgrade before opening it"
+            )));
+        }
+        let Some(step) = MIGRATIONS.get(current as usize) else {
+            return Ok(current);
+        };
+        let version = current + 1;
+        (step.apply)(&mut tx).map_err(|e| {
+            CoreError::Repository(format!(
+                "schema migration {version} ({}) failed: {e}",
+                step.name
+            ))
+        })?;
+        tx.execute(
+            "INSERT INTO schema_version(version, name, applied_at) VALUES ($1, $2, $3)",
+            &[
+                &i64::from(version),
+                &step.name,
+                &(system_time_to_secs(SystemTime::now()) as i64),
+            ],
+        )
+        .map_err(map_pgerr)?;
+        tx.commit().map_err(map_pgerr)?;
+    }
+}
+
+/// Version 1: links, groups, sharing, analytics, audit and organizations.
+///
+/// Timestamps are seconds since the UNIX epoch, as in the SQLite adapter, so
+/// both share list cursors. Slugs and target keys use the "C" collation: list
+/// cursors and target prefix ranges compare them byte by byte. The generated
+/// `search_vector` weighs slug, URL (without scheme), description and creator
+/// like the SQLite search index; words are split at every non-alphanumeric
+/// character so URL and path segments are searchable on their own.
+fn baseline(tx: &mut Transaction) -> Result<(), CoreError> {
+    tx.batch_execute(
+        r#"
+        CREATE SEQUENCE global_counter;
+        CREATE TABLE shortlinks (
+            tenant TEXT NOT NULL,
+            slug TEXT COLLATE "C" NOT NULL,
+            original_url TEXT NOT NULL,
+            created_at BIGINT NOT NULL,
+            created_by TEXT NOT NULL,
+            click_count BIGINT NOT NULL DEFAULT 0,
+            is_active BOOLEAN NOT NULL DEFAULT TRUE,
+            updated_at BIGINT,
+            expires_at BIGINT,
+            description TEXT,
+            activate_at BIGINT,
+            redirect_delay BIGINT,
+            deleted_at BIGINT,
+            group_id TEXT,
+            visibility TEXT NOT NULL DEFAULT 'public',
+            target_key TEXT COLLATE "C",
+            target_host TEXT,
+            search_vector TSVECTOR GENERATED ALWAYS AS (
+                setweight(to_tsvector('simple', regexp_replace(slug, '[^[:alnum:]]+', ' ', 'g')), 'A') ||
+                setweight(to_tsvector('simple', regexp_replace(regexp_replace(original_url, '^[^:/]+://', ''), '[^[:alnum:]]+', ' ', 'g')), 'B') ||
+                setweight(to_tsvector('simple', regexp_replace(coalesce(description, ''), '[^[:alnum:]]+', ' ', 'g')), 'C') ||
+                setweight(to_tsvector('simple', regexp_replace(created_by, '[^[:alnum:]]+', ' ', 'g')), 'D')
+            ) STORED,
+            PRIMARY KEY (tenant, slug)
+        );
+        CREATE INDEX idx_shortlinks_tenant_created ON shortlinks(tenant, created_at, slug);
+        CREATE INDEX idx_shortlinks_created_by ON shortlinks(tenant, created_by, created_at);
+        CREATE INDEX idx_shortlinks_group_id ON shortlinks(tenant, group_id, created_at);
+        CREATE INDEX idx_shortlinks_target_key ON shortlinks(tenant, target_key, created_at);
+        CREATE INDEX idx_shortlinks_target_host ON shortlinks(tenant, target_host, created_at);
+        CREATE INDEX idx_shortlinks_search ON shortlinks USING GIN (search_vector);
+
+        CREATE TABLE slug_aliases (
+            tenant TEXT NOT NULL,
+            alias TEXT COLLATE "C" NOT NULL,
+            slug TEXT COLLATE "C" NOT NULL,
+            PRIMARY KEY (tenant, alias)
+        );
+        CREATE INDEX idx_slug_aliases_slug ON slug_aliases(tenant, slug);
+
+        CREATE TABLE link_groups (
+            tenant TEXT NOT NULL,
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            description TEXT,
+            created_at BIGINT NOT NULL,
+            created_by TEXT NOT NULL,
+            default_expiry_secs BIGINT,
+            default_redirect_delay BIGINT,
+            allowed_hosts TEXT,
+            slug_prefix TEXT,
+            max_links BIGINT,
+            parent_id TEXT
+        );
+        CREATE INDEX idx_link_groups_tenant ON link_groups(tenant, created_by);
+        CREATE INDEX idx_link_groups_parent_id ON link_groups(tenant, parent_id);
+
+        CREATE TABLE group_members (
+            tenant TEXT NOT NULL,
+            group_id TEXT NOT NULL,
+            user_email TEXT NOT NULL,
+            role TEXT NOT NULL DEFAULT 'viewer',
+            added_at BIGINT NOT NULL,
+            added_by TEXT NOT NULL,
+            PRIMARY KEY (group_id, user_email)
+        );
+        CREATE INDEX idx_group_members_user ON group_members(tenant, user_email);
+
+        CREATE TABLE group_invitations (
+            tenant TEXT NOT NULL,
+            id TEXT PRIMARY KEY,
+            group_id TEXT NOT NULL,
+            email TEXT NOT NULL,
+            role TEXT NOT NULL,
+            invited_by TEXT NOT NULL,
+            created_at BIGINT NOT NULL,
+            expires_at BIGINT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'pending',
+            responded_at BIGINT
+        );
+        CREATE INDEX idx_group_invitations_email ON group_invitations(tenant, email);
+        CREATE INDEX idx_group_invitations_group ON group_invitations(tenant, group_id);
+
+        CREATE TABLE link_grants (
+            tenant TEXT NOT NULL,
+            slug TEXT COLLATE "C" NOT NULL,
+            user_email TEXT NOT NULL,
+            role TEXT NOT NULL DEFAULT 'viewer',
+            granted_at BIGINT NOT NULL,
+            granted_by TEXT NOT NULL,
+            PRIMARY KEY (tenant, slug, user_email)
+        );
+        CREATE INDEX idx_link_grants_user ON link_grants(tenant, user_email);
+
+        CREATE TABLE click_events (
+            id BIGSERIAL PRIMARY KEY,
+            tenant TEXT NOT NULL,
+            slug TEXT COLLATE "C" NOT NULL,
+            clicked_at BIGINT NOT NULL,
+            user_agent TEXT,
+            referrer TEXT,
+            country TEXT
+        );
+        CREATE INDEX idx_click_events_slug ON click_events(tenant, slug, clicked_at);
+
+        CREATE TABLE audit_log (
+            tenant TEXT NOT NULL,
+            id TEXT PRIMARY KEY,
+            timestamp BIGINT NOT NULL,
+            actor_email TEXT NOT NULL,
+            action TEXT NOT NULL,
+            target_type TEXT NOT NULL,
+            target_id TEXT NOT NULL,
+            changes TEXT
+        );
+        CREATE INDEX idx_audit_log_target ON audit_log(tenant, target_type, target_id, timestamp);
+        CREATE INDEX idx_audit_log_actor ON audit_log(tenant, actor_email, timestamp);
+        CREATE INDEX idx_audit_log_recent ON audit_log(tenant, timestamp);
+
+        CREATE TABLE organizations (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            hosts TEXT,
+            auth_domains TEXT,
+            admins TEXT,
+            shortlink_domain TEXT,
+            allowed_hosts TEXT,
+            created_at BIGINT NOT NULL
+        );
+
+        CREATE TABLE short_domains (
+            tenant TEXT NOT NULL,
+            host TEXT NOT NULL,
+            allowed_users TEXT,
+            allowed_groups TEXT,
+            created_at BIGINT NOT NULL,
+            created_by TEXT NOT NULL,
+            PRIMARY KEY (tenant, host)
+        );
+
+        CREATE TABLE namespaces (
+            tenant TEXT NOT NULL,
+            path TEXT NOT NULL,
+            group_id TEXT NOT NULL,
+            created_at BIGINT NOT NULL,
+            created_by TEXT NOT NULL,
+            PRIMARY KEY (tenant, path)
+        );
+        "#,
+    )
+    .map_err(map_pgerr)
+}
@@ -0,0 +1,506 @@
+//! redis-cache — Redis-compatible backend for the link lookup cache.
+//!
+//! Purpose
+//! - Implements `domain::cache::LinkCacheBackend`, so processes wrapping their
+//!   repository in `CachedLinkRepo` share cached lookups (and invalidations).
+//! - Works with any server speaking RESP: Redis, Valkey, KeyDB, ElastiCache.
+//!
+//! Notes
+//! - Speaks the protocol directly over one blocking TCP connection, opened on
+//!   first use and reopened after an error; only `GET`, `SET … PX` and `DEL`
+//!   are needed. No TLS: keep the server on a private network.
+//! - Entries are JSON under `<prefix><tenant-scoped slug key>` (prefix
+//!   `shortlink:` by default); a lookup that found nothing is stored as `null`.
+//! - URLs look like `redis://[:password@]host[:port][/db]`.
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use domain::cache::{CachedLink, LinkCacheBackend};
+use domain::{CoreError, LinkVisibility, ShortLink, Slug, UserEmail};
+use serde_json::{json, Value};
+
+/// Link cache on a Redis-compatible server.
+pub struct RedisLinkCache {
+    addr: String,
+    password: Option<String>,
+    db: u32,
+    prefix: String,
+    timeout: Duration,
+    conn: Mutex<Option<BufReader<TcpStream>>>,
+}
+
+impl RedisLinkCache {
+    /// Cache on the server at `url`. Nothing is sent until the first lookup,
+    /// so an unreachable server only costs cache hits.
+    pub fn new(url: &str) -> Result<Self, CoreError> {
+        let invalid = || CoreError::Repository(format!("invalid redis url: {url}"));
+        let rest = url.strip_prefix("redis://").ok_or_else(invalid)?;
+        let (auth, rest) = match rest.rsplit_once('@') {
+            Some((auth, rest)) => (Some(auth), rest),
+            None => (None, rest),
+        };
+        let (host, db) = match rest.split_once('/') {
+            Some((host, "")) => (host, 0),
+            Some((host, db)) => (host, db.parse().map_err(|_| invalid())?),
+            None => (rest, 0),
+        };
+        if host.is_empty() {
+            return Err(invalid());
+        }
+        let addr = if host
+            .rsplit_once(':')
+            .is_some_and(|(_, p)| p.parse::<u16>().is_ok())
+        {
+            host.to_string()
+        } else {
+            format!("{host}:6379")
+        };
+        // `user:password` or `:password`; users other than the default aren't supported
+        let password = auth
+            .map(|a| a.split_once(':').map_or(a, |(_, p)| p))
+            .filter(|p| !p.is_empty())
+            .map(str::to_string);
+        Ok(Self {
+            addr,
+            password,
+            db,
+            prefix: "shortlink:".into(),
+            timeout: Duration::from_millis(250),
+            conn: Mutex::new(None),
+        })
+    }
+
+    /// Namespace keys with `prefix` instead of `shortlink:`.
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// How long to wait for the server to connect or answer (default 250 ms).
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn open(&self) -> Result<BufReader<TcpStream>, CoreError> {
+        let addr = self
+            .addr
+            .to_socket_addrs()
+            .map_err(map_ioerr)?
+            .next()
+            .ok_or_else(|| CoreError::Repository(format!("redis host not found: {}", self.addr)))?;
+        let stream = TcpStream::connect_timeout(&addr, self.timeout).map_err(map_ioerr)?;
+        stream
+            .set_read_timeout(Some(self.timeout))
+            .map_err(map_ioerr)?;
+        stream
+            .set_write_timeout(Some(self.timeout))
+            .map_err(map_ioerr)?;
+        stream.set_nodelay(true).map_err(map_ioerr)?;
+        let mut conn = BufReader::new(stream);
+        if let Some(ref password) = self.password {
+            call(&mut conn, &[b"AUTH", password.as_bytes()])?;
+        }
+        if self.db != 0 {
+            call(&mut conn, &[b"SELECT", self.db.to_string().as_bytes()])?;
+        }
+        Ok(conn)
+    }
+
+    /// Send one command, reconnecting once if a kept connection went stale.
+    fn command(&self, args: &[&[u8]]) -> Result<Reply, CoreError> {
+        let mut slot = self
+            .conn
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let reused = slot.is_some();
+        let mut conn = match slot.take() {
+            Some(conn) => conn,
+            None => self.open()?,
+        };
+        let reply = match call(&mut conn, args) {
+            Err(Failure::Io(_)) if reused => {
+                conn = self.open()?;
+                call(&mut conn, args)
+            }
+            reply => reply,
+        };
+        match reply {
+            Ok(reply) => {
+                *slot = Some(conn);
+                Ok(reply)
+            }
+            // The server answered, so the connection is still in step
+            Err(failure @ Failure::Server(_)) => {
+                *slot = Some(conn);
+                Err(failure.into())
+            }
+            Err(failure) => Err(failure.into()),
+        }
+    }
+
+    fn key(&self, key: &str) -> String {
+        format!("{}{key}", self.prefix)
+    }
+}
+
+impl LinkCacheBackend for RedisLinkCache {
+    fn get(&self, key: &str) -> Result<Option<CachedLink>, CoreError> {
+        match self.command(&[b"GET", self.key(key).as_bytes()])? {
+            Reply::Bulk(None) => Ok(None),
+            Reply::Bulk(Some(data)) => decode(&data).map(Some),
+            other => Err(unexpected(&other)),
+        }
+    }
+
+    fn put(&self, key: &str, entry: &CachedLink, ttl: Duration) -> Result<(), CoreError> {
+        // PX 0 is rejected, and an entry that expires at once needn't be stored
+        let millis = ttl.as_millis();
+        if millis == 0 {
+            return Ok(());
+        }
+        let value = encode(entry).to_string();
+        self.command(&[
+            b"SET",
+            self.key(key).as_bytes(),
+            value.as_bytes(),
+            b"PX",
+            millis.to_string().as_bytes(),
+        ])?;
+        Ok(())
+    }
+
+    fn remove(&self, keys: &[String]) -> Result<(), CoreError> {
+        if keys.is_empty() {
+            return Ok(());
+        }
+        let keys: Vec<String> = keys.iter().map(|k| self.key(k)).collect();
+        let mut args: Vec<&[u8]> = vec![b"DEL"];
+        args.extend(keys.iter().map(|k| k.as_bytes()));
+        self.command(&args)?;
+        Ok(())
+    }
+}
+
+// ============ Protocol ============
+
+/// Reply to a command; only bulk strings carry anything we read.
+#[derive(Debug)]
+enum Reply {
+    Status,
+    Integer,
+    Bulk(Option<Vec<u8>>),
+    // Commands arrive as arrays, so the test stand-in reads them
+    #[cfg_attr(not(test), allow(dead_code))]
+    Array(Option<Vec<Reply>>),
+}
+
+enum Failure {
+    /// The connection broke or the reply made no sense.
+    Io(String),
+    /// The server answered with an error.
+    Server(String),
+}
+
+impl From<Failure> for CoreError {
+    fn from(failure: Failure) -> Self {
+        match failure {
+            Failure::Io(message) => CoreError::Repository(format!("redis io error: {message}")),
+            Failure::Server(message) => CoreError::Repository(format!("redis error: {message}")),
+        }
+    }
+}
+
+fn map_ioerr(e: std::io::Error) -> CoreError {
+    CoreError::Repository(format!("redis io error: {e}"))
+}
+
+fn unexpected(reply: &Reply) -> CoreError {
+    CoreError::Repository(format!("unexpected redis reply: {reply:?}"))
+}
+
+fn call(conn: &mut BufReader<TcpStream>, args: &[&[u8]]) -> Result<Reply, Failure> {
+    let mut request = format!("*{}\r\n", args.len()).into_bytes();
+    for arg in args {
+        request.extend_from_slice(format!("${}\r\n", arg.len()).as_bytes());
+        request.extend_from_slice(arg);
+        request.extend_from_slice(b"\r\n");
+    }
+    let io = |e: std::io::Error| Failure::Io(e.to_string());
+    conn.get_mut().write_all(&request).map_err(io)?;
+    read_reply(conn)
+}
+
+fn read_reply(conn: &mut impl BufRead) -> Result<Reply, Failure> {
+    let io = |e: std::io::Error| Failure::Io(e.to_string());
+    let mut line = String::new();
+    if conn.read_line(&mut line).map_err(io)? == 0 {
+        return Err(Failure::Io("connection closed".into()));
+    }
+    let line = line.trim_end_matches(['\r', '\n']);
+    let (kind, rest) = line.split_at(line.len().min(1));
+    let length = || -> Result<i64, Failure> {
+        rest.parse()
+            .map_err(|_| Failure::Io(format!("bad length in reply: {line}")))
+    };
+    match kind {
+        "+" => Ok(Reply::Status),
+        "-" => Err(Failure::Server(rest.to_string())),
+        ":" => length().map(|_| Reply::Integer),
+        "$" => match usize::try_from(length()?) {
+            Err(_) => Ok(Reply::Bulk(None)),
+            Ok(len) => {
+                let mut data = vec![0; len + 2];
+                conn.read_exact(&mut data).map_err(io)?;
+                data.truncate(len);
+                Ok(Reply::Bulk(Some(data)))
+            }
+        },
+        "*" => match usize::try_from(length()?) {
+            Err(_) => Ok(Reply::Array(None)),
+            Ok(len) => (0..len)
+                .map(|_| read_reply(conn))
+                .collect::<Result<_, _>>()
+                .map(|items| Reply::Array(Some(items))),
+        },
+        _ => Err(Failure::Io(format!("unknown reply: {line}"))),
+    }
+}
+
+// ============ Encoding ============
+
+fn millis(t: SystemTime) -> u64 {
+    t.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn from_millis(value: &Value) -> Option<SystemTime> {
+    value
+        .as_u64()
+        .map(|ms| UNIX_EPOCH + Duration::from_millis(ms))
+}
+
+fn encode(entry: &CachedLink) -> Value {
+    let CachedLink::Found(link) = entry else {
+        return Value::Null;
+    };
+    json!({
+        "slug": link.slug.key(),
+        "original_url": link.original_url,
+        "created_at": millis(link.created_at),
+        "created_by": link.created_by.as_str(),
+        "click_count": link.click_count,
+        "is_active": link.is_active,
+        "updated_at": link.updated_at.map(millis),
+        "expires_at": link.expires_at.map(millis),
+        "description": link.description,
+        "activate_at": link.activate_at.map(millis),
+        "redirect_delay": link.redirect_delay,
+        "deleted_at": link.deleted_at.map(millis),
+        "group_id": link.group_id,
+        "visibility": link.visibility.as_str(),
+    })
+}
+
+fn decode(data: &[u8]) -> Result<CachedLink, CoreError> {
+    let bad = || CoreError::Repository("bad cached link".into());
+    let value: Value = serde_json::from_slice(data).map_err(|_| bad())?;
+    if value.is_null() {
+        return Ok(CachedLink::Missing);
+    }
+    let text = |field: &str| value[field].as_str().ok_or_else(bad);
+    let optional_text = |field: &str| value[field].as_str().map(str::to_string);
+    Ok(CachedLink::Found(Box::new(ShortLink {
+        slug: Slug::from_key(text("slug")?)?,
+        original_url: text("original_url")?.to_string(),
+        created_at: from_millis(&value["created_at"]).ok_or_else(bad)?,
+        created_by: UserEmail::new(text("created_by")?)?,
+        click_count: value["click_count"].as_u64().ok_or_else(bad)?,
+        is_active: value["is_active"].as_bool().ok_or_else(bad)?,
+        updated_at: from_millis(&value["updated_at"]),
+        expires_at: from_millis(&value["expires_at"]),
+        description: optional_text("description"),
+        activate_at: from_millis(&value["activate_at"]),
+        redirect_delay: value["redirect_delay"]
+            .as_u64()
+            .and_then(|d| u32::try_from(d).ok()),
+        deleted_at: from_millis(&value["deleted_at"]),
+        group_id: optional_text("group_id"),
+        visibility: LinkVisibility::parse(text("visibility")?).unwrap_or_default(),
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::net::TcpListener;
+    use std::sync::Arc;
+
+    /// Local stand-in for a Redis server: answers `AUTH`, `SELECT`, `GET`,
+    /// `SET` and `DEL` from a map and ignores expiry. Returns its URL and the
+    /// commands it received.
+    fn stand_in(password: Option<&'static str>) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = match password {
+            Some(p) => format!("redis://:{p}@{}/2", listener.local_addr().unwrap()),
+            None => format!("redis://{}", listener.local_addr().unwrap()),
+        };
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&log);
+        std::thread::spawn(move || {
+            let mut data: HashMap<Vec<u8>, Vec<u8>> = HashMap::new();
+            for stream in listener.incoming() {
+                let mut conn = BufReader::new(stream.unwrap());
+                let mut authed = password.is_none();
+                while let Ok(Reply::Array(Some(args))) = read_reply(&mut conn) {
+                    let args: Vec<Vec<u8>> = args
+                        .into_iter()
+                        .map(|a| match a {
+                            Reply::Bulk(Some(a)) => a,
+                            other => panic!("bad argument {other:?}"),
+                        })
+                        .collect();
+                    let name = String::from_utf8_lossy(&args[0]).to_uppercase();
+                    seen.lock().unwrap().push(name.clone());
+                    let reply = match name.as_str() {
+                        "AUTH" => {
+                            authed = Some(args[1].as_slice()) == password.map(str::as_bytes);
+                            if authed {
+                                "+OK\r\n".into()
+                            } else {
+                                "-WRONGPASS invalid password\r\n".into()
+                            }
+                        }
+                        _ if !authed => "-NOAUTH Authentication required.\r\n".into(),
+                        "SELECT" => "+OK\r\n".into(),
+                        "GET" => match data.get(&args[1]) {
+                            Some(v) => {
+                                format!("${}\r\n{}\r\n", v.len(), String::from_utf8_lossy(v))
+                            }
+                            None => "$-1\r\n".into(),
+                        },
+                        "SET" => {
+                            data.insert(args[1].clone(), args[2].clone());
+                            "+OK\r\n".into()
+                        }
+                        "DEL" => {
+                            let n = args[1..]
+                                .iter()
+                                .filter(|k| data.remove(*k).is_some())
+                                .count();
+                            format!(":{n}\r\n")
+                        }
+                        _ => "-ERR unknown command\r\n".into(),
+                    };
+                    if conn.get_mut().write_all(reply.as_bytes()).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+        (url, log)
+    }
+
+    fn sample_link() -> ShortLink {
+        let mut link = ShortLink::new(
+            Slug::new("docs")
+                .unwrap()
+                .on_domain(Some("go.acme.com".into())),
+            "https://example.com/docs".into(),
+            UNIX_EPOCH + Duration::from_millis(1_700_000_000_123),
+            UserEmail::new("a@acme.com").unwrap(),
+        );
+        link.click_count = 7;
+        link.expires_at = Some(UNIX_EPOCH + Duration::from_secs(1_800_000_000));
+        link.description = Some("Team docs".into());
+        link.redirect_delay = Some(3);
+        link.visibility = LinkVisibility::Workspace;
+        link
+    }
+
+    fn roundtrip(cache: &RedisLinkCache) {
+        let found = CachedLink::Found(Box::new(sample_link()));
+        let ttl = Duration::from_secs(60);
+        assert_eq!(cache.get("acme#docs").unwrap(), None);
+        cache.put("acme#docs", &found, ttl).unwrap();
+        cache.put("acme#nope", &CachedLink::Missing, ttl).unwrap();
+        assert_eq!(cache.get("acme#docs").unwrap(), Some(found));
+        assert_eq!(cache.get("acme#nope").unwrap(), Some(CachedLink::Missing));
+        cache
+            .remove(&["acme#docs".into(), "acme#nope".into()])
+            .unwrap();
+        assert_eq!(cache.get("acme#docs").unwrap(), None);
+        assert_eq!(cache.get("acme#nope").unwrap(), None);
+    }
+
+    #[test]
+    fn urls_are_parsed() {
+        let cache = RedisLinkCache::new("redis://:s3cret@cache.internal:6380/3").unwrap();
+        assert_eq!(
+            (cache.addr.as_str(), cache.password.as_deref(), cache.db),
+            ("cache.internal:6380", Some("s3cret"), 3)
+        );
+        let cache = RedisLinkCache::new("redis://default:pw@localhost").unwrap();
+        assert_eq!(
+            (cache.addr.as_str(), cache.password.as_deref(), cache.db),
+            ("localhost:6379", Some("pw"), 0)
+        );
+        assert!(RedisLinkCache::new("http://localhost").is_err());
+        assert!(RedisLinkCache::new("redis:///1").is_err());
+        assert!(RedisLinkCache::new("redis://localhost/x").is_err());
+    }
+
+    #[test]
+    fn entries_roundtrip_through_a_stand_in_server() {
+        let (url, log) = stand_in(Some("s3cret"));
+        let cache = RedisLinkCache::new(&url).unwrap();
+        roundtrip(&cache);
+        // One connection, authenticated and switched to the database once
+        let log = log.lock().unwrap();
+        assert_eq!(log[..2], ["AUTH", "SELECT"]);
+        assert_eq!(log.iter().filter(|c| *c == "AUTH").count(), 1);
+
+        let (url, _) = stand_in(Some("s3cret"));
+        let wrong = RedisLinkCache::new(&url.replace("s3cret", "guess")).unwrap();
+        assert!(matches!(
+            wrong.get("docs"),
+            Err(CoreError::Repository(m)) if m.contains("WRONGPASS")
+        ));
+    }
+
+    #[test]
+    fn unreachable_servers_fail_fast() {
+        // Bound then dropped, so nothing listens there
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let cache = RedisLinkCache::new(&format!("redis://{addr}")).unwrap();
+        assert!(matches!(
+            cache.get("docs"),
+            Err(CoreError::Repository(m)) if m.contains("redis io error")
+        ));
+    }
+
+    /// Runs against a real server when `REDIS_TEST_URL` is set, e.g.
+    /// `docker run -p 6380:6379 redis` with `REDIS_TEST_URL=redis://localhost:6380`.
+    #[test]
+    fn entries_roundtrip_and_expire_on_redis() {
+        let Ok(url) = std::env::var("REDIS_TEST_URL") else {
+            return;
+        };
+        let prefix = format!("test:{}:{}:", std::process::id(), millis(SystemTime::now()));
+        let cache = RedisLinkCache::new(&url).unwrap().with_prefix(prefix);
+        roundtrip(&cache);
+        cache
+            .put("short", &CachedLink::Missing, Duration::from_millis(50))
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(150));
+        assert_eq!(cache.get("short").unwrap(), None);
+    }
+}
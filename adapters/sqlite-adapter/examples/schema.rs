@@ -0,0 +1,48 @@
+//! Report or apply the schema migrations of a SQLite database.
+//!
+//! ```bash
+//! # Version of the database and the migrations it is missing
+//! DB_PATH=./data/shortlinks.db cargo run -p sqlite-adapter --example schema -- status
+//! # Apply them without starting a server
+//! DB_PATH=./data/shortlinks.db cargo run -p sqlite-adapter --example schema -- migrate
+//! ```
+//!
+//! `DB_PATH` defaults to `./data/shortlinks.db`, as for the server.
+
+use sqlite_adapter::{migrations, schema_version_at, SqliteRepo, SCHEMA_VERSION};
+
+fn main() {
+    let path = std::env::var("DB_PATH").unwrap_or_else(|_| "./data/shortlinks.db".to_string());
+    let command = std::env::args().nth(1).unwrap_or_else(|| "status".into());
+    match command.as_str() {
+        "status" => {
+            let version = match schema_version_at(&path) {
+                Ok(version) => version,
+                Err(e) => {
+                    eprintln!("cannot read {path}: {e}");
+                    std::process::exit(1);
+                }
+            };
+            println!("{path}: schema version {version} (this build: {SCHEMA_VERSION})");
+            if version > SCHEMA_VERSION {
+                println!("written by a newer build; this one will refuse to open it");
+            }
+            for pending in version + 1..=SCHEMA_VERSION {
+                if let Some(name) = migrations::migration_name(pending) {
+                    println!("pending: {pending} {name}");
+                }
+            }
+        }
+        "migrate" => match SqliteRepo::new(&path).and_then(|repo| repo.schema_version()) {
+            Ok(version) => println!("{path}: schema version {version}"),
+            Err(e) => {
+                eprintln!("migration failed: {e}");
+                std::process::exit(1);
+            }
+        },
+        other => {
+            eprintln!("unknown command {other:?}; expected status or migrate");
+            std::process::exit(2);
+        }
+    }
+}
@@ -10,48 +10,107 @@
 //! Notes
 //! - Uses `rusqlite` with the `bundled` feature for portability.
 //! - Stores timestamps as seconds since UNIX_EPOCH (u64).
+//! - File databases run in WAL mode. Writes share one connection; reads use a
+//!   small pool of read-only connections and so never queue behind a write or
+//!   each other (see [`SqliteOptions`]).
+//! - Every tenant-owned table carries a `tenant` column; a handle only touches
+//!   rows of its own tenant (see [`TenantScoped`]). Organizations are global.
+//! - Slugs are stored by [`Slug::key`], so links on extra short domains keep
+//!   their own slug space in the same tables.
+//! - Every connection loads the `rarray` table-valued function, so lists of
+//!   values (such as an access scope's groups) bind as one parameter.
+//! - `slug_aliases` maps extra slugs to the slug a link is stored under; `get`
+//!   follows it in the same query.
+//! - `namespaces` holds slug prefixes reserved by a group; the primary key
+//!   turns a second reservation of the same path into `AlreadyExists`.
+//! - `shortlinks.target_key` and `target_host` hold [`ShortLink::target_key`]
+//!   and [`ShortLink::target_host`], indexed for `list_by_target` (prefixes are
+//!   a range over `target_key`). Rows written before they existed are backfilled.
+//! - The schema is versioned: opening a database applies the pending steps of
+//!   [`migrations`] and refuses a database written by a newer build. Use
+//!   [`SqliteRepo::schema_version`] or [`schema_version_at`] to inspect it, or
+//!   `cargo run -p sqlite-adapter --example schema` from the command line.
 
 use std::path::Path;
+use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use domain::{
-    AuditEntry, AuditRepository, ClickEvent, ClickRepository, CoreError, GroupMember,
-    GroupRepository, GroupRole, LinkGroup, LinkRepository, ListOptions, ListResult, ShortLink,
-    Slug, UserEmail,
+    hierarchy, AuditEntry, AuditRepository, ClickEvent, ClickRepository, CoreError,
+    DomainRepository, GroupInvitation, GroupLinkDisposition, GroupMember, GroupRepository,
+    GroupRole, GroupSettings, InvitationRepository, InvitationStatus, LinkGrant,
+    LinkGrantRepository, LinkGroup, LinkRepository, LinkSort, LinkStatus, LinkVisibility,
+    ListOptions, ListResult, Namespace, NamespaceRepository, Organization, OrganizationRepository,
+    OrganizationSettings, SearchHit, ShortDomain, ShortLink, Slug, SortDirection, TargetMatch,
+    TenantId, TenantScoped, UserEmail,
 };
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OpenFlags};
+
+pub mod migrations;
+mod pool;
+
+pub use migrations::SCHEMA_VERSION;
+pub use pool::SqliteOptions;
+
+use pool::Pool;
 
 /// SQLite-backed repository for local development.
 pub struct SqliteRepo {
-    conn: std::sync::Mutex<Connection>,
+    pool: Arc<Pool>,
+    tenant: TenantId,
+}
+
+impl TenantScoped for SqliteRepo {
+    fn for_tenant(&self, tenant: &TenantId) -> Self {
+        Self {
+            pool: Arc::clone(&self.pool),
+            tenant: tenant.clone(),
+        }
+    }
+
+    fn tenant(&self) -> &TenantId {
+        &self.tenant
+    }
 }
 
 impl SqliteRepo {
-    /// Open (or create) a SQLite database at the given path and ensure schema.
+    /// Open (or create) a SQLite database at the given path with default
+    /// [`SqliteOptions`] and migrate it to [`SCHEMA_VERSION`].
     pub fn new<P: AsRef<Path>>(path: P) -> Result<Self, CoreError> {
-        let conn = Connection::open(path).map_err(map_sqerr)?;
-        init_schema(&conn)?;
+        Self::open(path, &SqliteOptions::default())
+    }
+
+    /// Like [`SqliteRepo::new`], with the connection pool tuned by `options`.
+    pub fn open<P: AsRef<Path>>(path: P, options: &SqliteOptions) -> Result<Self, CoreError> {
+        let pool = Pool::open(path.as_ref(), options, |conn| {
+            migrations::migrate(conn).map(drop)
+        })?;
         Ok(Self {
-            conn: std::sync::Mutex::new(conn),
+            pool: Arc::new(pool),
+            tenant: TenantId::default(),
         })
     }
 
-    /// Construct from env var `DB_PATH` (defaults to `./data/shortlinks.db`).
+    /// Construct from env var `DB_PATH` (defaults to `./data/shortlinks.db`),
+    /// with the pool tuned by [`SqliteOptions::from_env`].
     pub fn from_env() -> Result<Self, CoreError> {
         let path = std::env::var("DB_PATH").unwrap_or_else(|_| "./data/shortlinks.db".to_string());
         // Ensure directory exists
         if let Some(dir) = std::path::Path::new(&path).parent() {
             let _ = std::fs::create_dir_all(dir);
         }
-        Self::new(path)
+        Self::open(path, &SqliteOptions::from_env())
+    }
+
+    /// Schema version of the open database.
+    pub fn schema_version(&self) -> Result<u32, CoreError> {
+        let conn = self.pool.reader()?;
+        migrations::current_version(&conn)
     }
 
     /// Atomically increment the global counter and return the new value.
     pub fn increment_global_counter(&self) -> Result<u64, CoreError> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let conn = self.pool.writer()?;
         let tx = conn.unchecked_transaction().map_err(map_sqerr)?;
         // Ensure counter row exists
         tx.execute(
@@ -77,87 +136,126 @@ impl SqliteRepo {
     }
 }
 
-fn init_schema(conn: &Connection) -> Result<(), CoreError> {
-    conn.execute_batch(
-        r#"
-        CREATE TABLE IF NOT EXISTS shortlinks (
-            slug TEXT PRIMARY KEY,
-            original_url TEXT NOT NULL,
-            created_at INTEGER NOT NULL,
-            created_by TEXT NOT NULL,
-            click_count INTEGER NOT NULL DEFAULT 0,
-            is_active INTEGER NOT NULL DEFAULT 1,
-            updated_at INTEGER,
-            expires_at INTEGER,
-            description TEXT,
-            activate_at INTEGER,
-            redirect_delay INTEGER,
-            deleted_at INTEGER,
-            group_id TEXT
-        );
-        CREATE TABLE IF NOT EXISTS counters (
-            name TEXT PRIMARY KEY,
-            value INTEGER NOT NULL
-        );
-        CREATE TABLE IF NOT EXISTS link_groups (
-            id TEXT PRIMARY KEY,
-            name TEXT NOT NULL,
-            description TEXT,
-            created_at INTEGER NOT NULL,
-            created_by TEXT NOT NULL
-        );
-        CREATE TABLE IF NOT EXISTS group_members (
-            group_id TEXT NOT NULL,
-            user_email TEXT NOT NULL,
-            role TEXT NOT NULL DEFAULT 'viewer',
-            added_at INTEGER NOT NULL,
-            added_by TEXT NOT NULL,
-            PRIMARY KEY (group_id, user_email)
-        );
-        CREATE TABLE IF NOT EXISTS click_events (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            slug TEXT NOT NULL,
-            clicked_at INTEGER NOT NULL,
-            user_agent TEXT,
-            referrer TEXT,
-            country TEXT
-        );
-        CREATE INDEX IF NOT EXISTS idx_click_events_slug ON click_events(slug);
-        CREATE INDEX IF NOT EXISTS idx_click_events_clicked_at ON click_events(clicked_at);
-        CREATE TABLE IF NOT EXISTS audit_log (
-            id TEXT PRIMARY KEY,
-            timestamp INTEGER NOT NULL,
-            actor_email TEXT NOT NULL,
-            action TEXT NOT NULL,
-            target_type TEXT NOT NULL,
-            target_id TEXT NOT NULL,
-            changes TEXT
-        );
-        CREATE INDEX IF NOT EXISTS idx_audit_log_target ON audit_log(target_type, target_id);
-        CREATE INDEX IF NOT EXISTS idx_audit_log_actor ON audit_log(actor_email);
-        "#,
-    )
-    .map_err(map_sqerr)?;
-    // Migration: add new columns if they don't exist (for existing databases)
-    let _ = conn.execute(
-        "ALTER TABLE shortlinks ADD COLUMN click_count INTEGER NOT NULL DEFAULT 0",
-        [],
-    );
-    let _ = conn.execute(
-        "ALTER TABLE shortlinks ADD COLUMN is_active INTEGER NOT NULL DEFAULT 1",
-        [],
-    );
-    let _ = conn.execute("ALTER TABLE shortlinks ADD COLUMN updated_at INTEGER", []);
-    let _ = conn.execute("ALTER TABLE shortlinks ADD COLUMN expires_at INTEGER", []);
-    let _ = conn.execute("ALTER TABLE shortlinks ADD COLUMN description TEXT", []);
-    let _ = conn.execute("ALTER TABLE shortlinks ADD COLUMN activate_at INTEGER", []);
-    let _ = conn.execute(
-        "ALTER TABLE shortlinks ADD COLUMN redirect_delay INTEGER",
-        [],
-    );
-    let _ = conn.execute("ALTER TABLE shortlinks ADD COLUMN deleted_at INTEGER", []);
-    let _ = conn.execute("ALTER TABLE shortlinks ADD COLUMN group_id TEXT", []);
-    Ok(())
+/// Weighted bm25 over the `shortlinks_fts` columns (slug, URL, description,
+/// creator); lower is better.
+const SEARCH_RANK: &str = "bm25(shortlinks_fts, 10.0, 4.0, 2.0, 1.0)";
+
+/// `values` as the parameter of a `rarray(?)` table-valued function.
+fn string_array(values: impl Iterator<Item = String>) -> rusqlite::vtab::array::Array {
+    std::rc::Rc::new(values.map(rusqlite::types::Value::from).collect())
+}
+
+/// SQL conditions (joined with AND) and their parameters selecting the links
+/// of `tenant` that pass the filters of `options`; paging is left to callers.
+/// `search` matches whole words or word prefixes via the full-text index.
+fn link_conditions(
+    tenant: &TenantId,
+    options: &ListOptions,
+) -> (Vec<String>, Vec<Box<dyn rusqlite::ToSql>>) {
+    let mut conditions = vec!["tenant = ?1".to_string()];
+    let mut params_values: Vec<Box<dyn rusqlite::ToSql>> =
+        vec![Box::new(tenant.as_str().to_string())];
+
+    let now = format!("?{}", params_values.len() + 1);
+    let status_condition = match options.status {
+        None if options.include_deleted => None,
+        None => Some("deleted_at IS NULL".to_string()),
+        Some(LinkStatus::Deleted) => Some("deleted_at IS NOT NULL".to_string()),
+        Some(LinkStatus::Inactive) => Some("deleted_at IS NULL AND is_active = 0".to_string()),
+        Some(LinkStatus::Active) => Some(format!(
+            "deleted_at IS NULL AND is_active = 1 AND (expires_at IS NULL OR expires_at > {now}) AND (activate_at IS NULL OR activate_at <= {now})"
+        )),
+        Some(LinkStatus::Expired) => {
+            Some(format!("deleted_at IS NULL AND expires_at <= {now}"))
+        }
+        Some(LinkStatus::Scheduled) => {
+            Some(format!("deleted_at IS NULL AND activate_at > {now}"))
+        }
+    };
+    if matches!(
+        options.status,
+        Some(LinkStatus::Active | LinkStatus::Expired | LinkStatus::Scheduled)
+    ) {
+        params_values.push(Box::new(system_time_to_secs(options.status_time()) as i64));
+    }
+    conditions.extend(status_condition);
+    if let Some(ref email) = options.created_by {
+        conditions.push(format!("created_by = ?{}", params_values.len() + 1));
+        params_values.push(Box::new(email.as_str().to_string()));
+    }
+    if let Some(ref gid) = options.group_id {
+        conditions.push(format!("group_id = ?{}", params_values.len() + 1));
+        params_values.push(Box::new(gid.clone()));
+    }
+    if let Some(ref scope) = options.accessible_to {
+        // Each list binds as a single array, however many groups or shared
+        // links the user has
+        let mut any_of = vec![format!("created_by = ?{}", params_values.len() + 1)];
+        params_values.push(Box::new(scope.user.as_str().to_string()));
+        if !scope.group_ids.is_empty() {
+            any_of.push(format!("group_id IN rarray(?{})", params_values.len() + 1));
+            params_values.push(Box::new(string_array(scope.group_ids.iter().cloned())));
+        }
+        if !scope.shared_slugs.is_empty() {
+            any_of.push(format!("slug IN rarray(?{})", params_values.len() + 1));
+            params_values.push(Box::new(string_array(
+                scope.shared_slugs.iter().map(|s| s.key().into_owned()),
+            )));
+        }
+        conditions.push(format!("({})", any_of.join(" OR ")));
+    }
+    if let Some(ref q) = options.search {
+        match fts_query(q) {
+            Some(query) => {
+                conditions.push(format!(
+                    "shortlinks.rowid IN (SELECT rowid FROM shortlinks_fts WHERE shortlinks_fts MATCH ?{})",
+                    params_values.len() + 1
+                ));
+                params_values.push(Box::new(query));
+            }
+            None => conditions.push("0".to_string()),
+        }
+    }
+    for (column, range) in [
+        ("created_at", &options.created),
+        ("updated_at", &options.updated),
+        ("expires_at", &options.expires),
+    ] {
+        if let Some(from) = range.from {
+            conditions.push(format!("{column} >= ?{}", params_values.len() + 1));
+            params_values.push(Box::new(system_time_to_secs(from) as i64));
+        }
+        if let Some(to) = range.to {
+            conditions.push(format!("{column} < ?{}", params_values.len() + 1));
+            params_values.push(Box::new(system_time_to_secs(to) as i64));
+        }
+    }
+    if let Some(ref host) = options.target_host {
+        conditions.push(format!("target_host = ?{}", params_values.len() + 1));
+        params_values.push(Box::new(host.to_lowercase()));
+    }
+
+    (conditions, params_values)
+}
+
+/// FTS5 query for free text: every word must match, as a prefix so results
+/// show up while typing. Words are quoted, so FTS operators in the input are
+/// searched for literally. `None` when the input has no words.
+fn fts_query(input: &str) -> Option<String> {
+    let terms: Vec<String> = input
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(|t| format!("\"{t}\"*"))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+/// Schema version of the database at `path`, read without migrating it: 0
+/// when the file predates versioning. Fails if the file does not exist.
+pub fn schema_version_at<P: AsRef<Path>>(path: P) -> Result<u32, CoreError> {
+    let conn =
+        Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY).map_err(map_sqerr)?;
+    migrations::current_version(&conn)
 }
 
 fn map_sqerr<E: std::fmt::Display>(e: E) -> CoreError {
@@ -173,6 +271,9 @@ fn secs_to_system_time(secs: u64) -> SystemTime {
     UNIX_EPOCH + Duration::from_secs(secs)
 }
 
+/// Column list read by `row_to_shortlink`, in index order.
+const SHORTLINKS_COLUMNS: &str = "slug, original_url, created_at, created_by, click_count, is_active, updated_at, expires_at, description, activate_at, redirect_delay, deleted_at, group_id, visibility";
+
 fn row_to_shortlink(row: &rusqlite::Row) -> Result<ShortLink, CoreError> {
     let slug_str: String = row.get(0).map_err(map_sqerr)?;
     let orig: String = row.get(1).map_err(map_sqerr)?;
@@ -187,9 +288,10 @@ fn row_to_shortlink(row: &rusqlite::Row) -> Result<ShortLink, CoreError> {
     let redirect_delay: Option<i64> = row.get(10).map_err(map_sqerr)?;
     let deleted_at: Option<i64> = row.get(11).map_err(map_sqerr)?;
     let group_id: Option<String> = row.get(12).map_err(map_sqerr)?;
+    let visibility: String = row.get(13).map_err(map_sqerr)?;
 
-    let s =
-        Slug::new(slug_str).map_err(|e| CoreError::Repository(format!("bad slug in db: {e}")))?;
+    let s = Slug::from_key(&slug_str)
+        .map_err(|e| CoreError::Repository(format!("bad slug in db: {e}")))?;
     let u = UserEmail::new(by).map_err(|_| CoreError::Repository("bad created_by".into()))?;
     Ok(ShortLink {
         slug: s,
@@ -205,18 +307,18 @@ fn row_to_shortlink(row: &rusqlite::Row) -> Result<ShortLink, CoreError> {
         redirect_delay: redirect_delay.map(|t| t as u32),
         deleted_at: deleted_at.map(|t| secs_to_system_time(t as u64)),
         group_id,
+        visibility: LinkVisibility::parse(&visibility).unwrap_or_default(),
     })
 }
 
 impl LinkRepository for SqliteRepo {
     fn get(&self, slug: &Slug) -> Result<Option<ShortLink>, CoreError> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
-        let mut stmt = conn.prepare("SELECT slug, original_url, created_at, created_by, click_count, is_active, updated_at, expires_at, description, activate_at, redirect_delay, deleted_at, group_id FROM shortlinks WHERE slug = ?1")
+        let conn = self.pool.reader()?;
+        let mut stmt = conn.prepare("SELECT slug, original_url, created_at, created_by, click_count, is_active, updated_at, expires_at, description, activate_at, redirect_delay, deleted_at, group_id, visibility FROM shortlinks WHERE tenant = ?2 AND slug = COALESCE((SELECT slug FROM slug_aliases WHERE tenant = ?2 AND alias = ?1), ?1)")
+            .map_err(map_sqerr)?;
+        let mut rows = stmt
+            .query(params![slug.key(), self.tenant.as_str()])
             .map_err(map_sqerr)?;
-        let mut rows = stmt.query(params![slug.as_str()]).map_err(map_sqerr)?;
         if let Some(row) = rows.next().map_err(map_sqerr)? {
             Ok(Some(row_to_shortlink(row)?))
         } else {
@@ -225,19 +327,19 @@ impl LinkRepository for SqliteRepo {
     }
 
     fn put(&self, link: ShortLink) -> Result<(), CoreError> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let conn = self.pool.writer()?;
         let updated_at_secs: Option<i64> = link.updated_at.map(|t| system_time_to_secs(t) as i64);
         let expires_at_secs: Option<i64> = link.expires_at.map(|t| system_time_to_secs(t) as i64);
         let activate_at_secs: Option<i64> = link.activate_at.map(|t| system_time_to_secs(t) as i64);
         let deleted_at_secs: Option<i64> = link.deleted_at.map(|t| system_time_to_secs(t) as i64);
         let redirect_delay: Option<i64> = link.redirect_delay.map(|t| t as i64);
+        if is_alias(&conn, self.tenant.as_str(), &link.slug.key())? {
+            return Err(CoreError::AlreadyExists);
+        }
         let res = conn.execute(
-            "INSERT INTO shortlinks(slug, original_url, created_at, created_by, click_count, is_active, updated_at, expires_at, description, activate_at, redirect_delay, deleted_at, group_id) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+            "INSERT INTO shortlinks(slug, original_url, created_at, created_by, click_count, is_active, updated_at, expires_at, description, activate_at, redirect_delay, deleted_at, group_id, visibility, tenant, target_key, target_host) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)",
             params![
-                link.slug.as_str(),
+                link.slug.key(),
                 link.original_url,
                 system_time_to_secs(link.created_at) as i64,
                 link.created_by.as_str(),
@@ -250,6 +352,10 @@ impl LinkRepository for SqliteRepo {
                 redirect_delay,
                 deleted_at_secs,
                 link.group_id,
+                link.visibility.as_str(),
+                self.tenant.as_str(),
+                link.target_key(),
+                link.target_host(),
             ],
         );
         match res {
@@ -266,13 +372,12 @@ impl LinkRepository for SqliteRepo {
     }
 
     fn list(&self, limit: usize) -> Result<Vec<ShortLink>, CoreError> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
-        let mut stmt = conn.prepare("SELECT slug, original_url, created_at, created_by, click_count, is_active, updated_at, expires_at, description, activate_at, redirect_delay, deleted_at, group_id FROM shortlinks WHERE deleted_at IS NULL ORDER BY created_at DESC LIMIT ?1")
+        let conn = self.pool.reader()?;
+        let mut stmt = conn.prepare("SELECT slug, original_url, created_at, created_by, click_count, is_active, updated_at, expires_at, description, activate_at, redirect_delay, deleted_at, group_id, visibility FROM shortlinks WHERE tenant = ?2 AND deleted_at IS NULL ORDER BY created_at DESC LIMIT ?1")
+            .map_err(map_sqerr)?;
+        let mut rows = stmt
+            .query(params![limit as i64, self.tenant.as_str()])
             .map_err(map_sqerr)?;
-        let mut rows = stmt.query(params![limit as i64]).map_err(map_sqerr)?;
         let mut out = Vec::new();
         while let Some(row) = rows.next().map_err(map_sqerr)? {
             out.push(row_to_shortlink(row)?);
@@ -281,17 +386,14 @@ impl LinkRepository for SqliteRepo {
     }
 
     fn update(&self, link: &ShortLink) -> Result<(), CoreError> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let conn = self.pool.writer()?;
         let updated_at_secs: Option<i64> = link.updated_at.map(|t| system_time_to_secs(t) as i64);
         let expires_at_secs: Option<i64> = link.expires_at.map(|t| system_time_to_secs(t) as i64);
         let activate_at_secs: Option<i64> = link.activate_at.map(|t| system_time_to_secs(t) as i64);
         let redirect_delay: Option<i64> = link.redirect_delay.map(|t| t as i64);
         let changed = conn.execute(
-            "UPDATE shortlinks SET original_url = ?1, is_active = ?2, updated_at = ?3, expires_at = ?4, description = ?5, activate_at = ?6, redirect_delay = ?7, group_id = ?8 WHERE slug = ?9",
-            params![link.original_url, link.is_active as i64, updated_at_secs, expires_at_secs, link.description, activate_at_secs, redirect_delay, link.group_id, link.slug.as_str()],
+            "UPDATE shortlinks SET original_url = ?1, is_active = ?2, updated_at = ?3, expires_at = ?4, description = ?5, activate_at = ?6, redirect_delay = ?7, group_id = ?8, visibility = ?9, target_key = ?12, target_host = ?13 WHERE slug = ?10 AND tenant = ?11",
+            params![link.original_url, link.is_active as i64, updated_at_secs, expires_at_secs, link.description, activate_at_secs, redirect_delay, link.group_id, link.visibility.as_str(), link.slug.key(), self.tenant.as_str(), link.target_key(), link.target_host()],
         ).map_err(map_sqerr)?;
         if changed == 0 {
             Err(CoreError::NotFound)
@@ -301,14 +403,11 @@ impl LinkRepository for SqliteRepo {
     }
 
     fn increment_click(&self, slug: &Slug) -> Result<(), CoreError> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let conn = self.pool.writer()?;
         let changed = conn
             .execute(
-                "UPDATE shortlinks SET click_count = click_count + 1 WHERE slug = ?1",
-                params![slug.as_str()],
+                "UPDATE shortlinks SET click_count = click_count + 1 WHERE slug = ?1 AND tenant = ?2",
+                params![slug.key(), self.tenant.as_str()],
             )
             .map_err(map_sqerr)?;
         if changed == 0 {
@@ -323,14 +422,11 @@ impl LinkRepository for SqliteRepo {
         email: &UserEmail,
         limit: usize,
     ) -> Result<Vec<ShortLink>, CoreError> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
-        let mut stmt = conn.prepare("SELECT slug, original_url, created_at, created_by, click_count, is_active, updated_at, expires_at, description, activate_at, redirect_delay, deleted_at, group_id FROM shortlinks WHERE created_by = ?1 AND deleted_at IS NULL ORDER BY created_at DESC LIMIT ?2")
+        let conn = self.pool.reader()?;
+        let mut stmt = conn.prepare("SELECT slug, original_url, created_at, created_by, click_count, is_active, updated_at, expires_at, description, activate_at, redirect_delay, deleted_at, group_id, visibility FROM shortlinks WHERE created_by = ?1 AND tenant = ?3 AND deleted_at IS NULL ORDER BY created_at DESC LIMIT ?2")
             .map_err(map_sqerr)?;
         let mut rows = stmt
-            .query(params![email.as_str(), limit as i64])
+            .query(params![email.as_str(), limit as i64, self.tenant.as_str()])
             .map_err(map_sqerr)?;
         let mut out = Vec::new();
         while let Some(row) = rows.next().map_err(map_sqerr)? {
@@ -340,123 +436,229 @@ impl LinkRepository for SqliteRepo {
     }
 
     fn delete(&self, slug: &Slug, deleted_at: SystemTime) -> Result<(), CoreError> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let conn = self.pool.writer()?;
         let deleted_at_secs = system_time_to_secs(deleted_at) as i64;
-        let changed = conn
+        let tx = conn.unchecked_transaction().map_err(map_sqerr)?;
+        let changed = tx
             .execute(
-                "UPDATE shortlinks SET deleted_at = ?1 WHERE slug = ?2 AND deleted_at IS NULL",
-                params![deleted_at_secs, slug.as_str()],
+                "UPDATE shortlinks SET deleted_at = ?1 WHERE slug = ?2 AND tenant = ?3 AND deleted_at IS NULL",
+                params![deleted_at_secs, slug.key(), self.tenant.as_str()],
             )
             .map_err(map_sqerr)?;
         if changed == 0 {
-            Err(CoreError::NotFound)
-        } else {
-            Ok(())
+            return Err(CoreError::NotFound);
         }
+        // Collaborators lose access along with the link
+        tx.execute(
+            "DELETE FROM link_grants WHERE slug = ?1 AND tenant = ?2",
+            params![slug.key(), self.tenant.as_str()],
+        )
+        .map_err(map_sqerr)?;
+        tx.commit().map_err(map_sqerr)
     }
 
     fn search(&self, query: &str, limit: usize) -> Result<Vec<ShortLink>, CoreError> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
-        let pattern = format!("%{}%", query.to_lowercase());
-        let mut stmt = conn.prepare("SELECT slug, original_url, created_at, created_by, click_count, is_active, updated_at, expires_at, description, activate_at, redirect_delay, deleted_at, group_id FROM shortlinks WHERE deleted_at IS NULL AND (LOWER(slug) LIKE ?1 OR LOWER(original_url) LIKE ?1 OR LOWER(description) LIKE ?1) ORDER BY created_at DESC LIMIT ?2")
-            .map_err(map_sqerr)?;
-        let mut rows = stmt
-            .query(params![pattern, limit as i64])
-            .map_err(map_sqerr)?;
-        let mut out = Vec::new();
-        while let Some(row) = rows.next().map_err(map_sqerr)? {
-            out.push(row_to_shortlink(row)?);
-        }
-        Ok(out)
+        let hits = self.search_ranked(&ListOptions {
+            limit,
+            search: Some(query.to_string()),
+            ..Default::default()
+        })?;
+        Ok(hits.items.into_iter().map(|hit| hit.link).collect())
     }
 
     fn list_paginated(&self, options: &ListOptions) -> Result<ListResult<ShortLink>, CoreError> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let conn = self.pool.reader()?;
 
-        // Build WHERE clause dynamically
-        let mut conditions = Vec::new();
-        let mut params_values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+        let (conditions, mut params_values) = link_conditions(&self.tenant, options);
+        let where_clause = format!("WHERE {}", conditions.join(" AND "));
 
-        if !options.include_deleted {
-            conditions.push("deleted_at IS NULL".to_string());
-        }
-        if let Some(ref email) = options.created_by {
-            conditions.push(format!("created_by = ?{}", params_values.len() + 1));
-            params_values.push(Box::new(email.as_str().to_string()));
-        }
-        if let Some(ref gid) = options.group_id {
-            conditions.push(format!("group_id = ?{}", params_values.len() + 1));
-            params_values.push(Box::new(gid.clone()));
+        // Count total
+        let count_sql = format!("SELECT COUNT(*) FROM shortlinks {}", where_clause);
+        let total: i64 = {
+            let mut stmt = conn.prepare(&count_sql).map_err(map_sqerr)?;
+            let params_refs: Vec<&dyn rusqlite::ToSql> =
+                params_values.iter().map(|b| b.as_ref()).collect();
+            stmt.query_row(params_refs.as_slice(), |r| r.get(0))
+                .map_err(map_sqerr)?
+        };
+
+        // Keyset pagination: resume after the cursor's (sort value, slug) and
+        // fetch one extra row to learn whether another page follows. Missing
+        // times sort as -1, below every stored one.
+        let sort_column = match options.sort {
+            LinkSort::CreatedAt => "created_at",
+            LinkSort::UpdatedAt => "COALESCE(updated_at, -1)",
+            LinkSort::ClickCount => "click_count",
+            LinkSort::ExpiresAt => "COALESCE(expires_at, -1)",
+        };
+        let (order, after) = match options.direction {
+            SortDirection::Asc => ("ASC", ">"),
+            SortDirection::Desc => ("DESC", "<"),
+        };
+        let mut page_clause = where_clause;
+        if let Some(ref cursor) = options.cursor {
+            let (value, slug) =
+                domain::cursor::decode_link_position(cursor, options.sort, options.direction)?;
+            let value = match value {
+                Some(v) => i64::try_from(v).map_err(|_| CoreError::InvalidCursor)?,
+                None => -1,
+            };
+            let (a, b) = (params_values.len() + 1, params_values.len() + 2);
+            page_clause.push_str(&format!(
+                " AND ({sort_column} {after} ?{a} OR ({sort_column} = ?{a} AND slug {after} ?{b}))"
+            ));
+            params_values.push(Box::new(value));
+            params_values.push(Box::new(slug));
         }
-        if let Some(ref q) = options.search {
-            let pattern = format!("%{}%", q.to_lowercase());
-            let idx = params_values.len() + 1;
-            conditions.push(format!("(LOWER(slug) LIKE ?{} OR LOWER(original_url) LIKE ?{} OR LOWER(description) LIKE ?{})", idx, idx, idx));
-            params_values.push(Box::new(pattern));
+        let select_sql = format!(
+            "SELECT slug, original_url, created_at, created_by, click_count, is_active, updated_at, expires_at, description, activate_at, redirect_delay, deleted_at, group_id, visibility FROM shortlinks {} ORDER BY {sort_column} {order}, slug {order} LIMIT ?{}",
+            page_clause,
+            params_values.len() + 1
+        );
+        params_values.push(Box::new(options.limit as i64 + 1));
+
+        let mut stmt = conn.prepare(&select_sql).map_err(map_sqerr)?;
+        let params_refs: Vec<&dyn rusqlite::ToSql> =
+            params_values.iter().map(|b| b.as_ref()).collect();
+        let mut rows = stmt.query(params_refs.as_slice()).map_err(map_sqerr)?;
+        let mut items = Vec::new();
+        while let Some(row) = rows.next().map_err(map_sqerr)? {
+            items.push(row_to_shortlink(row)?);
         }
 
-        let where_clause = if conditions.is_empty() {
-            String::new()
-        } else {
-            format!("WHERE {}", conditions.join(" AND "))
+        let has_more = items.len() > options.limit;
+        items.truncate(options.limit);
+        let next_cursor = match items.last() {
+            Some(last) if has_more => {
+                let secs = |t: SystemTime| u128::from(system_time_to_secs(t));
+                let value = match options.sort {
+                    LinkSort::CreatedAt => Some(secs(last.created_at)),
+                    LinkSort::UpdatedAt => last.updated_at.map(secs),
+                    LinkSort::ClickCount => Some(u128::from(last.click_count)),
+                    LinkSort::ExpiresAt => last.expires_at.map(secs),
+                };
+                Some(domain::cursor::encode_link_position(
+                    options.sort,
+                    options.direction,
+                    value,
+                    &last.slug.key(),
+                ))
+            }
+            _ => None,
+        };
+        Ok(ListResult {
+            items,
+            total: Some(total as usize),
+            has_more,
+            next_cursor,
+        })
+    }
+
+    fn search_ranked(&self, options: &ListOptions) -> Result<ListResult<SearchHit>, CoreError> {
+        let Some(ref search) = options.search else {
+            return self.list_paginated(options).map(Into::into);
+        };
+        let Some(query) = fts_query(search) else {
+            return Ok(ListResult {
+                items: Vec::new(),
+                total: Some(0),
+                has_more: false,
+                next_cursor: None,
+            });
         };
+        let conn = self.pool.reader()?;
+
+        // The MATCH has to sit in this query for bm25() and snippet() to see it
+        let unsearched = ListOptions {
+            search: None,
+            ..options.clone()
+        };
+        let (mut conditions, mut params_values) = link_conditions(&self.tenant, &unsearched);
+        conditions.push(format!("shortlinks_fts MATCH ?{}", params_values.len() + 1));
+        params_values.push(Box::new(query));
+        let from_where = format!(
+            "FROM shortlinks_fts JOIN shortlinks ON shortlinks.rowid = shortlinks_fts.rowid WHERE {}",
+            conditions.join(" AND ")
+        );
 
-        // Count total
-        let count_sql = format!("SELECT COUNT(*) FROM shortlinks {}", where_clause);
         let total: i64 = {
-            let mut stmt = conn.prepare(&count_sql).map_err(map_sqerr)?;
+            let mut stmt = conn
+                .prepare(&format!("SELECT COUNT(*) {from_where}"))
+                .map_err(map_sqerr)?;
             let params_refs: Vec<&dyn rusqlite::ToSql> =
                 params_values.iter().map(|b| b.as_ref()).collect();
             stmt.query_row(params_refs.as_slice(), |r| r.get(0))
                 .map_err(map_sqerr)?
         };
 
-        // Fetch items
         let select_sql = format!(
-            "SELECT slug, original_url, created_at, created_by, click_count, is_active, updated_at, expires_at, description, activate_at, redirect_delay, deleted_at, group_id FROM shortlinks {} ORDER BY created_at DESC LIMIT ?{} OFFSET ?{}",
-            where_clause,
+            "SELECT {SHORTLINKS_COLUMNS}, snippet(shortlinks_fts, -1, '<mark>', '</mark>', '…', 12) {from_where} ORDER BY {SEARCH_RANK}, created_at DESC LIMIT ?{} OFFSET ?{}",
             params_values.len() + 1,
             params_values.len() + 2
         );
         params_values.push(Box::new(options.limit as i64));
         params_values.push(Box::new(options.offset as i64));
-
         let mut stmt = conn.prepare(&select_sql).map_err(map_sqerr)?;
         let params_refs: Vec<&dyn rusqlite::ToSql> =
             params_values.iter().map(|b| b.as_ref()).collect();
         let mut rows = stmt.query(params_refs.as_slice()).map_err(map_sqerr)?;
         let mut items = Vec::new();
         while let Some(row) = rows.next().map_err(map_sqerr)? {
-            items.push(row_to_shortlink(row)?);
+            items.push(SearchHit {
+                link: row_to_shortlink(row)?,
+                snippet: row.get(14).map_err(map_sqerr)?,
+            });
         }
 
         let has_more = options.offset + items.len() < total as usize;
         Ok(ListResult {
             items,
-            total: total as usize,
+            total: Some(total as usize),
             has_more,
+            next_cursor: None,
         })
     }
 
     fn list_by_group(&self, group_id: &str, limit: usize) -> Result<Vec<ShortLink>, CoreError> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
-        let mut stmt = conn.prepare("SELECT slug, original_url, created_at, created_by, click_count, is_active, updated_at, expires_at, description, activate_at, redirect_delay, deleted_at, group_id FROM shortlinks WHERE group_id = ?1 AND deleted_at IS NULL ORDER BY created_at DESC LIMIT ?2")
+        let conn = self.pool.reader()?;
+        let mut stmt = conn.prepare("SELECT slug, original_url, created_at, created_by, click_count, is_active, updated_at, expires_at, description, activate_at, redirect_delay, deleted_at, group_id, visibility FROM shortlinks WHERE group_id = ?1 AND tenant = ?3 AND deleted_at IS NULL ORDER BY created_at DESC LIMIT ?2")
             .map_err(map_sqerr)?;
         let mut rows = stmt
-            .query(params![group_id, limit as i64])
+            .query(params![group_id, limit as i64, self.tenant.as_str()])
+            .map_err(map_sqerr)?;
+        let mut out = Vec::new();
+        while let Some(row) = rows.next().map_err(map_sqerr)? {
+            out.push(row_to_shortlink(row)?);
+        }
+        Ok(out)
+    }
+
+    fn list_by_target(
+        &self,
+        key: &str,
+        mode: TargetMatch,
+        limit: usize,
+    ) -> Result<Vec<ShortLink>, CoreError> {
+        let conn = self.pool.reader()?;
+        // A prefix is the index range [key, key + U+10FFFF)
+        let (condition, upper) = match mode {
+            TargetMatch::Exact => ("target_key = ?2", None),
+            TargetMatch::Prefix => (
+                "target_key >= ?2 AND target_key < ?4",
+                Some(format!("{key}{}", char::MAX)),
+            ),
+            TargetMatch::Host => ("target_host = ?2", None),
+        };
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {SHORTLINKS_COLUMNS} FROM shortlinks WHERE tenant = ?1 AND {condition} AND deleted_at IS NULL ORDER BY created_at ASC LIMIT ?3"
+            ))
             .map_err(map_sqerr)?;
+        let mut rows = match upper {
+            Some(upper) => stmt.query(params![self.tenant.as_str(), key, limit as i64, upper]),
+            None => stmt.query(params![self.tenant.as_str(), key, limit as i64]),
+        }
+        .map_err(map_sqerr)?;
         let mut out = Vec::new();
         while let Some(row) = rows.next().map_err(map_sqerr)? {
             out.push(row_to_shortlink(row)?);
@@ -465,21 +667,27 @@ impl LinkRepository for SqliteRepo {
     }
 
     fn bulk_delete(&self, slugs: &[Slug], deleted_at: SystemTime) -> Result<usize, CoreError> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let conn = self.pool.writer()?;
         let deleted_at_secs = system_time_to_secs(deleted_at) as i64;
+        let tx = conn.unchecked_transaction().map_err(map_sqerr)?;
         let mut count = 0;
         for slug in slugs {
-            let changed = conn
+            let changed = tx
                 .execute(
-                    "UPDATE shortlinks SET deleted_at = ?1 WHERE slug = ?2 AND deleted_at IS NULL",
-                    params![deleted_at_secs, slug.as_str()],
+                    "UPDATE shortlinks SET deleted_at = ?1 WHERE slug = ?2 AND tenant = ?3 AND deleted_at IS NULL",
+                    params![deleted_at_secs, slug.key(), self.tenant.as_str()],
+                )
+                .map_err(map_sqerr)?;
+            if changed > 0 {
+                tx.execute(
+                    "DELETE FROM link_grants WHERE slug = ?1 AND tenant = ?2",
+                    params![slug.key(), self.tenant.as_str()],
                 )
                 .map_err(map_sqerr)?;
+            }
             count += changed;
         }
+        tx.commit().map_err(map_sqerr)?;
         Ok(count)
     }
 
@@ -489,41 +697,158 @@ impl LinkRepository for SqliteRepo {
         is_active: bool,
         updated_at: SystemTime,
     ) -> Result<usize, CoreError> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let conn = self.pool.writer()?;
         let updated_at_secs = system_time_to_secs(updated_at) as i64;
         let mut count = 0;
         for slug in slugs {
             let changed = conn
                 .execute(
-                    "UPDATE shortlinks SET is_active = ?1, updated_at = ?2 WHERE slug = ?3",
-                    params![is_active as i64, updated_at_secs, slug.as_str()],
+                    "UPDATE shortlinks SET is_active = ?1, updated_at = ?2 WHERE slug = ?3 AND tenant = ?4",
+                    params![is_active as i64, updated_at_secs, slug.key(), self.tenant.as_str()],
                 )
                 .map_err(map_sqerr)?;
             count += changed;
         }
         Ok(count)
     }
+
+    fn add_alias(&self, alias: &Slug, target: &Slug) -> Result<(), CoreError> {
+        let conn = self.pool.writer()?;
+        let tenant = self.tenant.as_str();
+        let alias = alias.key();
+        if link_exists(&conn, tenant, &alias)? || is_alias(&conn, tenant, &alias)? {
+            return Err(CoreError::AlreadyExists);
+        }
+        if !link_exists(&conn, tenant, &target.key())? {
+            return Err(CoreError::NotFound);
+        }
+        conn.execute(
+            "INSERT INTO slug_aliases(tenant, alias, slug) VALUES (?1, ?2, ?3)",
+            params![tenant, alias, target.key()],
+        )
+        .map_err(map_sqerr)?;
+        Ok(())
+    }
+
+    fn remove_alias(&self, alias: &Slug) -> Result<(), CoreError> {
+        let conn = self.pool.writer()?;
+        let changed = conn
+            .execute(
+                "DELETE FROM slug_aliases WHERE tenant = ?1 AND alias = ?2",
+                params![self.tenant.as_str(), alias.key()],
+            )
+            .map_err(map_sqerr)?;
+        if changed == 0 {
+            Err(CoreError::NotFound)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn list_aliases(&self, target: &Slug) -> Result<Vec<Slug>, CoreError> {
+        let conn = self.pool.reader()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT alias FROM slug_aliases WHERE tenant = ?1 AND slug = ?2 ORDER BY alias",
+            )
+            .map_err(map_sqerr)?;
+        let mut rows = stmt
+            .query(params![self.tenant.as_str(), target.key()])
+            .map_err(map_sqerr)?;
+        let mut out = Vec::new();
+        while let Some(row) = rows.next().map_err(map_sqerr)? {
+            let alias: String = row.get(0).map_err(map_sqerr)?;
+            out.push(Slug::from_key(&alias)?);
+        }
+        Ok(out)
+    }
+
+    fn rename(&self, old: &Slug, new: &Slug) -> Result<ShortLink, CoreError> {
+        let conn = self.pool.writer()?;
+        let tenant = self.tenant.as_str();
+        let (old_key, new_key) = (old.key(), new.key());
+        let tx = conn.unchecked_transaction().map_err(map_sqerr)?;
+        if link_exists(&tx, tenant, &new_key)? || is_alias(&tx, tenant, &new_key)? {
+            return Err(CoreError::AlreadyExists);
+        }
+        let changed = tx
+            .execute(
+                "UPDATE shortlinks SET slug = ?1 WHERE tenant = ?2 AND slug = ?3",
+                params![new_key, tenant, old_key],
+            )
+            .map_err(map_sqerr)?;
+        if changed == 0 {
+            return Err(CoreError::NotFound);
+        }
+        tx.execute(
+            "UPDATE slug_aliases SET slug = ?1 WHERE tenant = ?2 AND slug = ?3",
+            params![new_key, tenant, old_key],
+        )
+        .map_err(map_sqerr)?;
+        tx.execute(
+            "INSERT INTO slug_aliases(tenant, alias, slug) VALUES (?1, ?2, ?3)",
+            params![tenant, old_key, new_key],
+        )
+        .map_err(map_sqerr)?;
+        tx.execute(
+            "UPDATE link_grants SET slug = ?1 WHERE tenant = ?2 AND slug = ?3",
+            params![new_key, tenant, old_key],
+        )
+        .map_err(map_sqerr)?;
+        let link = tx
+            .query_row(
+                &format!(
+                    "SELECT {SHORTLINKS_COLUMNS} FROM shortlinks WHERE tenant = ?1 AND slug = ?2"
+                ),
+                params![tenant, new_key],
+                |row| Ok(row_to_shortlink(row)),
+            )
+            .map_err(map_sqerr)??;
+        tx.commit().map_err(map_sqerr)?;
+        Ok(link)
+    }
+}
+
+fn link_exists(conn: &Connection, tenant: &str, key: &str) -> Result<bool, CoreError> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM shortlinks WHERE tenant = ?1 AND slug = ?2",
+        params![tenant, key],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|n| n > 0)
+    .map_err(map_sqerr)
+}
+
+fn is_alias(conn: &Connection, tenant: &str, key: &str) -> Result<bool, CoreError> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM slug_aliases WHERE tenant = ?1 AND alias = ?2",
+        params![tenant, key],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|n| n > 0)
+    .map_err(map_sqerr)
 }
 
 // ============ GroupRepository ============
 
 impl GroupRepository for SqliteRepo {
     fn create_group(&self, group: LinkGroup) -> Result<(), CoreError> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let conn = self.pool.writer()?;
         let res = conn.execute(
-            "INSERT INTO link_groups(id, name, description, created_at, created_by) VALUES (?1, ?2, ?3, ?4, ?5)",
+            "INSERT INTO link_groups(id, name, description, created_at, created_by, default_expiry_secs, default_redirect_delay, allowed_hosts, slug_prefix, max_links, parent_id, tenant) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
             params![
                 group.id,
                 group.name,
                 group.description,
                 system_time_to_secs(group.created_at) as i64,
                 group.created_by.as_str(),
+                group.settings.default_expiry_secs.map(|s| s as i64),
+                group.settings.default_redirect_delay,
+                hosts_to_column(&group.settings.allowed_hosts),
+                group.settings.slug_prefix,
+                group.settings.max_links,
+                group.parent_id,
+                self.tenant.as_str(),
             ],
         );
         match res {
@@ -540,13 +865,15 @@ impl GroupRepository for SqliteRepo {
     }
 
     fn get_group(&self, id: &str) -> Result<Option<LinkGroup>, CoreError> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
-        let mut stmt = conn.prepare("SELECT id, name, description, created_at, created_by FROM link_groups WHERE id = ?1")
+        let conn = self.pool.reader()?;
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {GROUP_COLUMNS} FROM link_groups WHERE id = ?1 AND tenant = ?2"
+            ))
+            .map_err(map_sqerr)?;
+        let mut rows = stmt
+            .query(params![id, self.tenant.as_str()])
             .map_err(map_sqerr)?;
-        let mut rows = stmt.query(params![id]).map_err(map_sqerr)?;
         if let Some(row) = rows.next().map_err(map_sqerr)? {
             Ok(Some(row_to_group(row)?))
         } else {
@@ -555,18 +882,17 @@ impl GroupRepository for SqliteRepo {
     }
 
     fn list_groups(&self, user_email: &UserEmail) -> Result<Vec<LinkGroup>, CoreError> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
-        let mut stmt = conn.prepare(
-            "SELECT DISTINCT g.id, g.name, g.description, g.created_at, g.created_by FROM link_groups g
-             LEFT JOIN group_members m ON g.id = m.group_id
-             WHERE g.created_by = ?1 OR m.user_email = ?1
+        let conn = self.pool.reader()?;
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT DISTINCT {GROUP_COLUMNS_G} FROM link_groups g
+             LEFT JOIN group_members m ON g.id = m.group_id AND m.tenant = g.tenant
+             WHERE g.tenant = ?2 AND (g.created_by = ?1 OR m.user_email = ?1)
              ORDER BY g.name"
-        ).map_err(map_sqerr)?;
+            ))
+            .map_err(map_sqerr)?;
         let mut rows = stmt
-            .query(params![user_email.as_str()])
+            .query(params![user_email.as_str(), self.tenant.as_str()])
             .map_err(map_sqerr)?;
         let mut out = Vec::new();
         while let Some(row) = rows.next().map_err(map_sqerr)? {
@@ -576,14 +902,22 @@ impl GroupRepository for SqliteRepo {
     }
 
     fn update_group(&self, group: &LinkGroup) -> Result<(), CoreError> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let conn = self.pool.writer()?;
         let changed = conn
             .execute(
-                "UPDATE link_groups SET name = ?1, description = ?2 WHERE id = ?3",
-                params![group.name, group.description, group.id],
+                "UPDATE link_groups SET name = ?1, description = ?2, default_expiry_secs = ?3, default_redirect_delay = ?4, allowed_hosts = ?5, slug_prefix = ?6, max_links = ?7, parent_id = ?8 WHERE id = ?9 AND tenant = ?10",
+                params![
+                    group.name,
+                    group.description,
+                    group.settings.default_expiry_secs.map(|s| s as i64),
+                    group.settings.default_redirect_delay,
+                    hosts_to_column(&group.settings.allowed_hosts),
+                    group.settings.slug_prefix,
+                    group.settings.max_links,
+                    group.parent_id,
+                    group.id,
+                    self.tenant.as_str()
+                ],
             )
             .map_err(map_sqerr)?;
         if changed == 0 {
@@ -593,42 +927,116 @@ impl GroupRepository for SqliteRepo {
         }
     }
 
-    fn delete_group(&self, id: &str) -> Result<(), CoreError> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
-        // Delete members first
-        conn.execute("DELETE FROM group_members WHERE group_id = ?1", params![id])
+    fn list_child_groups(&self, parent_id: &str) -> Result<Vec<LinkGroup>, CoreError> {
+        let conn = self.pool.reader()?;
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {GROUP_COLUMNS} FROM link_groups WHERE parent_id = ?1 AND tenant = ?2 ORDER BY name"
+            ))
             .map_err(map_sqerr)?;
-        let changed = conn
-            .execute("DELETE FROM link_groups WHERE id = ?1", params![id])
+        let mut rows = stmt
+            .query(params![parent_id, self.tenant.as_str()])
             .map_err(map_sqerr)?;
-        if changed == 0 {
-            Err(CoreError::NotFound)
-        } else {
-            Ok(())
+        let mut out = Vec::new();
+        while let Some(row) = rows.next().map_err(map_sqerr)? {
+            out.push(row_to_group(row)?);
+        }
+        Ok(out)
+    }
+
+    fn delete_group(
+        &self,
+        id: &str,
+        links: &GroupLinkDisposition,
+        at: SystemTime,
+    ) -> Result<usize, CoreError> {
+        let conn = self.pool.writer()?;
+        let tenant = self.tenant.as_str();
+        let tx = conn.unchecked_transaction().map_err(map_sqerr)?;
+        let group_exists = |gid: &str| -> Result<bool, CoreError> {
+            tx.query_row(
+                "SELECT COUNT(*) FROM link_groups WHERE id = ?1 AND tenant = ?2",
+                params![gid, tenant],
+                |row| row.get::<_, i64>(0),
+            )
+            .map(|n| n > 0)
+            .map_err(map_sqerr)
+        };
+        if !group_exists(id)? {
+            return Err(CoreError::NotFound);
+        }
+
+        let at_secs = system_time_to_secs(at) as i64;
+        let affected = match links {
+            GroupLinkDisposition::Reassign(target) => {
+                if target == id {
+                    return Err(CoreError::Conflict(
+                        "cannot reassign links to the group being deleted".into(),
+                    ));
+                }
+                if !group_exists(target)? {
+                    return Err(CoreError::NotFound);
+                }
+                tx.execute(
+                    "UPDATE shortlinks SET group_id = ?1, updated_at = ?2 WHERE group_id = ?3 AND tenant = ?4",
+                    params![target, at_secs, id, tenant],
+                )
+            }
+            GroupLinkDisposition::Detach => tx.execute(
+                "UPDATE shortlinks SET group_id = NULL, updated_at = ?1 WHERE group_id = ?2 AND tenant = ?3",
+                params![at_secs, id, tenant],
+            ),
+            GroupLinkDisposition::SoftDelete => {
+                // Collaborators lose access along with the links
+                tx.execute(
+                    "DELETE FROM link_grants WHERE tenant = ?2 AND slug IN (SELECT slug FROM shortlinks WHERE group_id = ?1 AND tenant = ?2)",
+                    params![id, tenant],
+                )
+                .map_err(map_sqerr)?;
+                tx.execute(
+                    "UPDATE shortlinks SET group_id = NULL, updated_at = ?1, deleted_at = COALESCE(deleted_at, ?1) WHERE group_id = ?2 AND tenant = ?3",
+                    params![at_secs, id, tenant],
+                )
+            }
         }
+        .map_err(map_sqerr)?;
+
+        tx.execute(
+            "DELETE FROM group_members WHERE group_id = ?1 AND tenant = ?2",
+            params![id, tenant],
+        )
+        .map_err(map_sqerr)?;
+        // Subgroups move up a level
+        tx.execute(
+            "UPDATE link_groups SET parent_id = (SELECT parent_id FROM link_groups WHERE id = ?1 AND tenant = ?2) WHERE parent_id = ?1 AND tenant = ?2",
+            params![id, tenant],
+        )
+        .map_err(map_sqerr)?;
+        tx.execute(
+            "DELETE FROM link_groups WHERE id = ?1 AND tenant = ?2",
+            params![id, tenant],
+        )
+        .map_err(map_sqerr)?;
+        tx.commit().map_err(map_sqerr)?;
+        Ok(affected)
     }
 
     fn add_member(&self, member: GroupMember) -> Result<(), CoreError> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let conn = self.pool.writer()?;
         let role_str = match member.role {
             GroupRole::Viewer => "viewer",
             GroupRole::Editor => "editor",
             GroupRole::Admin => "admin",
         };
         let res = conn.execute(
-            "INSERT INTO group_members(group_id, user_email, role, added_at, added_by) VALUES (?1, ?2, ?3, ?4, ?5)",
+            "INSERT INTO group_members(group_id, user_email, role, added_at, added_by, tenant) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
             params![
                 member.group_id,
                 member.user_email.as_str(),
                 role_str,
                 system_time_to_secs(member.added_at) as i64,
                 member.added_by.as_str(),
+                self.tenant.as_str(),
             ],
         );
         match res {
@@ -645,14 +1053,26 @@ impl GroupRepository for SqliteRepo {
     }
 
     fn remove_member(&self, group_id: &str, user_email: &UserEmail) -> Result<(), CoreError> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let conn = self.pool.writer()?;
+        // The connection mutex serializes this check with the delete below
+        let (is_admin, admins): (bool, i64) = conn
+            .query_row(
+                "SELECT
+                    EXISTS(SELECT 1 FROM group_members WHERE group_id = ?1 AND user_email = ?2 AND tenant = ?3 AND role = 'admin'),
+                    (SELECT COUNT(*) FROM group_members WHERE group_id = ?1 AND tenant = ?3 AND role = 'admin')",
+                params![group_id, user_email.as_str(), self.tenant.as_str()],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .map_err(map_sqerr)?;
+        if is_admin && admins <= 1 {
+            return Err(CoreError::Conflict(
+                "group must keep at least one admin".into(),
+            ));
+        }
         let changed = conn
             .execute(
-                "DELETE FROM group_members WHERE group_id = ?1 AND user_email = ?2",
-                params![group_id, user_email.as_str()],
+                "DELETE FROM group_members WHERE group_id = ?1 AND user_email = ?2 AND tenant = ?3",
+                params![group_id, user_email.as_str(), self.tenant.as_str()],
             )
             .map_err(map_sqerr)?;
         if changed == 0 {
@@ -662,15 +1082,41 @@ impl GroupRepository for SqliteRepo {
         }
     }
 
+    fn transfer_ownership(&self, group_id: &str, new_owner: &UserEmail) -> Result<(), CoreError> {
+        let conn = self.pool.writer()?;
+        let tx = conn.unchecked_transaction().map_err(map_sqerr)?;
+        let promoted = tx
+            .execute(
+                "UPDATE group_members SET role = 'admin' WHERE group_id = ?1 AND user_email = ?2 AND tenant = ?3",
+                params![group_id, new_owner.as_str(), self.tenant.as_str()],
+            )
+            .map_err(map_sqerr)?;
+        let changed = tx
+            .execute(
+                "UPDATE link_groups SET created_by = ?1 WHERE id = ?2 AND tenant = ?3",
+                params![new_owner.as_str(), group_id, self.tenant.as_str()],
+            )
+            .map_err(map_sqerr)?;
+        if changed == 0 {
+            return Err(CoreError::NotFound);
+        }
+        if promoted == 0 {
+            return Err(CoreError::Conflict(
+                "new owner must be a group member".into(),
+            ));
+        }
+        tx.commit().map_err(map_sqerr)?;
+        Ok(())
+    }
+
     fn list_members(&self, group_id: &str) -> Result<Vec<GroupMember>, CoreError> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let conn = self.pool.reader()?;
         let mut stmt = conn.prepare(
-            "SELECT group_id, user_email, role, added_at, added_by FROM group_members WHERE group_id = ?1"
+            "SELECT group_id, user_email, role, added_at, added_by FROM group_members WHERE group_id = ?1 AND tenant = ?2"
         ).map_err(map_sqerr)?;
-        let mut rows = stmt.query(params![group_id]).map_err(map_sqerr)?;
+        let mut rows = stmt
+            .query(params![group_id, self.tenant.as_str()])
+            .map_err(map_sqerr)?;
         let mut out = Vec::new();
         while let Some(row) = rows.next().map_err(map_sqerr)? {
             out.push(row_to_member(row)?);
@@ -683,15 +1129,12 @@ impl GroupRepository for SqliteRepo {
         group_id: &str,
         user_email: &UserEmail,
     ) -> Result<Option<GroupMember>, CoreError> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let conn = self.pool.reader()?;
         let mut stmt = conn.prepare(
-            "SELECT group_id, user_email, role, added_at, added_by FROM group_members WHERE group_id = ?1 AND user_email = ?2"
+            "SELECT group_id, user_email, role, added_at, added_by FROM group_members WHERE group_id = ?1 AND user_email = ?2 AND tenant = ?3"
         ).map_err(map_sqerr)?;
         let mut rows = stmt
-            .query(params![group_id, user_email.as_str()])
+            .query(params![group_id, user_email.as_str(), self.tenant.as_str()])
             .map_err(map_sqerr)?;
         if let Some(row) = rows.next().map_err(map_sqerr)? {
             Ok(Some(row_to_member(row)?))
@@ -704,20 +1147,19 @@ impl GroupRepository for SqliteRepo {
         &self,
         user_email: &UserEmail,
     ) -> Result<Vec<(LinkGroup, GroupRole)>, CoreError> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let conn = self.pool.reader()?;
 
         let mut result = Vec::new();
 
         // Groups where user is creator (Admin role)
         {
-            let mut stmt = conn.prepare(
-                "SELECT id, name, description, created_at, created_by FROM link_groups WHERE created_by = ?1"
-            ).map_err(map_sqerr)?;
+            let mut stmt = conn
+                .prepare(&format!(
+                    "SELECT {GROUP_COLUMNS} FROM link_groups WHERE created_by = ?1 AND tenant = ?2"
+                ))
+                .map_err(map_sqerr)?;
             let mut rows = stmt
-                .query(params![user_email.as_str()])
+                .query(params![user_email.as_str(), self.tenant.as_str()])
                 .map_err(map_sqerr)?;
             while let Some(row) = rows.next().map_err(map_sqerr)? {
                 result.push((row_to_group(row)?, GroupRole::Admin));
@@ -727,25 +1169,39 @@ impl GroupRepository for SqliteRepo {
         // Groups where user is a member (not creator)
         {
             let mut stmt = conn
-                .prepare(
-                    "SELECT g.id, g.name, g.description, g.created_at, g.created_by, m.role
+                .prepare(&format!(
+                    "SELECT {GROUP_COLUMNS_G}, m.role
                  FROM link_groups g
-                 JOIN group_members m ON g.id = m.group_id
-                 WHERE m.user_email = ?1 AND g.created_by != ?1",
-                )
+                 JOIN group_members m ON g.id = m.group_id AND m.tenant = g.tenant
+                 WHERE m.user_email = ?1 AND g.created_by != ?1 AND g.tenant = ?2"
+                ))
                 .map_err(map_sqerr)?;
             let mut rows = stmt
-                .query(params![user_email.as_str()])
+                .query(params![user_email.as_str(), self.tenant.as_str()])
                 .map_err(map_sqerr)?;
             while let Some(row) = rows.next().map_err(map_sqerr)? {
                 let group = row_to_group(row)?;
-                let role_str: String = row.get(5).map_err(map_sqerr)?;
+                let role_str: String = row.get(11).map_err(map_sqerr)?;
                 let role = str_to_role(&role_str);
                 result.push((group, role));
             }
         }
+        drop(conn);
 
-        Ok(result)
+        hierarchy::with_inherited(self, result)
+    }
+}
+
+/// Column list read by `row_to_group`, in index order.
+const GROUP_COLUMNS: &str = "id, name, description, created_at, created_by, default_expiry_secs, default_redirect_delay, allowed_hosts, slug_prefix, max_links, parent_id";
+const GROUP_COLUMNS_G: &str = "g.id, g.name, g.description, g.created_at, g.created_by, g.default_expiry_secs, g.default_redirect_delay, g.allowed_hosts, g.slug_prefix, g.max_links, g.parent_id";
+
+/// Allowed host patterns are stored newline-separated; NULL means "any host".
+fn hosts_to_column(hosts: &[String]) -> Option<String> {
+    if hosts.is_empty() {
+        None
+    } else {
+        Some(hosts.join("\n"))
     }
 }
 
@@ -755,6 +1211,12 @@ fn row_to_group(row: &rusqlite::Row) -> Result<LinkGroup, CoreError> {
     let description: Option<String> = row.get(2).map_err(map_sqerr)?;
     let created_at: i64 = row.get(3).map_err(map_sqerr)?;
     let created_by: String = row.get(4).map_err(map_sqerr)?;
+    let default_expiry_secs: Option<i64> = row.get(5).map_err(map_sqerr)?;
+    let default_redirect_delay: Option<u32> = row.get(6).map_err(map_sqerr)?;
+    let allowed_hosts: Option<String> = row.get(7).map_err(map_sqerr)?;
+    let slug_prefix: Option<String> = row.get(8).map_err(map_sqerr)?;
+    let max_links: Option<u32> = row.get(9).map_err(map_sqerr)?;
+    let parent_id: Option<String> = row.get(10).map_err(map_sqerr)?;
     Ok(LinkGroup {
         id,
         name,
@@ -762,6 +1224,16 @@ fn row_to_group(row: &rusqlite::Row) -> Result<LinkGroup, CoreError> {
         created_at: secs_to_system_time(created_at as u64),
         created_by: UserEmail::new(created_by)
             .map_err(|_| CoreError::Repository("bad email".into()))?,
+        settings: GroupSettings {
+            default_expiry_secs: default_expiry_secs.map(|s| s as u64),
+            default_redirect_delay,
+            allowed_hosts: allowed_hosts
+                .map(|h| h.lines().map(str::to_string).collect())
+                .unwrap_or_default(),
+            slug_prefix,
+            max_links,
+        },
+        parent_id,
     })
 }
 
@@ -790,37 +1262,287 @@ fn str_to_role(s: &str) -> GroupRole {
     }
 }
 
+// ============ InvitationRepository ============
+
+impl InvitationRepository for SqliteRepo {
+    fn create_invitation(&self, invitation: GroupInvitation) -> Result<(), CoreError> {
+        let conn = self.pool.writer()?;
+        let res = conn.execute(
+            "INSERT INTO group_invitations(id, group_id, email, role, invited_by, created_at, expires_at, status, responded_at, tenant) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                invitation.id,
+                invitation.group_id,
+                invitation.email.as_str(),
+                invitation.role.as_str(),
+                invitation.invited_by.as_str(),
+                system_time_to_secs(invitation.created_at) as i64,
+                system_time_to_secs(invitation.expires_at) as i64,
+                invitation.status.as_str(),
+                invitation.responded_at.map(|t| system_time_to_secs(t) as i64),
+                self.tenant.as_str(),
+            ],
+        );
+        match res {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                if let rusqlite::Error::SqliteFailure(err, _) = &e {
+                    if err.code == rusqlite::ErrorCode::ConstraintViolation {
+                        return Err(CoreError::AlreadyExists);
+                    }
+                }
+                Err(map_sqerr(e))
+            }
+        }
+    }
+
+    fn get_invitation(&self, id: &str) -> Result<Option<GroupInvitation>, CoreError> {
+        let conn = self.pool.reader()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, group_id, email, role, invited_by, created_at, expires_at, status, responded_at FROM group_invitations WHERE id = ?1 AND tenant = ?2",
+            )
+            .map_err(map_sqerr)?;
+        let mut rows = stmt
+            .query(params![id, self.tenant.as_str()])
+            .map_err(map_sqerr)?;
+        if let Some(row) = rows.next().map_err(map_sqerr)? {
+            Ok(Some(row_to_invitation(row)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn list_invitations_for_user(
+        &self,
+        email: &UserEmail,
+    ) -> Result<Vec<GroupInvitation>, CoreError> {
+        let conn = self.pool.reader()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, group_id, email, role, invited_by, created_at, expires_at, status, responded_at FROM group_invitations WHERE email = ?1 AND tenant = ?2 ORDER BY created_at DESC",
+            )
+            .map_err(map_sqerr)?;
+        let mut rows = stmt
+            .query(params![email.as_str(), self.tenant.as_str()])
+            .map_err(map_sqerr)?;
+        let mut out = Vec::new();
+        while let Some(row) = rows.next().map_err(map_sqerr)? {
+            out.push(row_to_invitation(row)?);
+        }
+        Ok(out)
+    }
+
+    fn list_invitations_for_group(
+        &self,
+        group_id: &str,
+    ) -> Result<Vec<GroupInvitation>, CoreError> {
+        let conn = self.pool.reader()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, group_id, email, role, invited_by, created_at, expires_at, status, responded_at FROM group_invitations WHERE group_id = ?1 AND tenant = ?2 ORDER BY created_at DESC",
+            )
+            .map_err(map_sqerr)?;
+        let mut rows = stmt
+            .query(params![group_id, self.tenant.as_str()])
+            .map_err(map_sqerr)?;
+        let mut out = Vec::new();
+        while let Some(row) = rows.next().map_err(map_sqerr)? {
+            out.push(row_to_invitation(row)?);
+        }
+        Ok(out)
+    }
+
+    fn respond_invitation(
+        &self,
+        id: &str,
+        status: InvitationStatus,
+        responded_at: SystemTime,
+    ) -> Result<(), CoreError> {
+        let conn = self.pool.writer()?;
+        let changed = conn
+            .execute(
+                "UPDATE group_invitations SET status = ?1, responded_at = ?2 WHERE id = ?3 AND tenant = ?4 AND status = 'pending'",
+                params![
+                    status.as_str(),
+                    system_time_to_secs(responded_at) as i64,
+                    id,
+                    self.tenant.as_str()
+                ],
+            )
+            .map_err(map_sqerr)?;
+        if changed == 0 {
+            Err(CoreError::NotFound)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+fn row_to_invitation(row: &rusqlite::Row) -> Result<GroupInvitation, CoreError> {
+    let id: String = row.get(0).map_err(map_sqerr)?;
+    let group_id: String = row.get(1).map_err(map_sqerr)?;
+    let email: String = row.get(2).map_err(map_sqerr)?;
+    let role_str: String = row.get(3).map_err(map_sqerr)?;
+    let invited_by: String = row.get(4).map_err(map_sqerr)?;
+    let created_at: i64 = row.get(5).map_err(map_sqerr)?;
+    let expires_at: i64 = row.get(6).map_err(map_sqerr)?;
+    let status_str: String = row.get(7).map_err(map_sqerr)?;
+    let responded_at: Option<i64> = row.get(8).map_err(map_sqerr)?;
+    Ok(GroupInvitation {
+        id,
+        group_id,
+        email: UserEmail::new(email).map_err(|_| CoreError::Repository("bad email".into()))?,
+        role: str_to_role(&role_str),
+        invited_by: UserEmail::new(invited_by)
+            .map_err(|_| CoreError::Repository("bad email".into()))?,
+        created_at: secs_to_system_time(created_at as u64),
+        expires_at: secs_to_system_time(expires_at as u64),
+        status: InvitationStatus::parse(&status_str)
+            .ok_or_else(|| CoreError::Repository("bad invitation status".into()))?,
+        responded_at: responded_at.map(|t| secs_to_system_time(t as u64)),
+    })
+}
+
+// ============ LinkGrantRepository ============
+
+impl LinkGrantRepository for SqliteRepo {
+    fn put_grant(&self, grant: LinkGrant) -> Result<(), CoreError> {
+        let conn = self.pool.writer()?;
+        conn.execute(
+            "INSERT INTO link_grants(slug, user_email, role, granted_at, granted_by, tenant) VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(tenant, slug, user_email) DO UPDATE SET role = excluded.role, granted_at = excluded.granted_at, granted_by = excluded.granted_by",
+            params![
+                grant.slug.key(),
+                grant.user_email.as_str(),
+                grant.role.as_str(),
+                system_time_to_secs(grant.granted_at) as i64,
+                grant.granted_by.as_str(),
+                self.tenant.as_str(),
+            ],
+        )
+        .map_err(map_sqerr)?;
+        Ok(())
+    }
+
+    fn remove_grant(&self, slug: &Slug, user_email: &UserEmail) -> Result<(), CoreError> {
+        let conn = self.pool.writer()?;
+        let changed = conn
+            .execute(
+                "DELETE FROM link_grants WHERE slug = ?1 AND user_email = ?2 AND tenant = ?3",
+                params![slug.key(), user_email.as_str(), self.tenant.as_str()],
+            )
+            .map_err(map_sqerr)?;
+        if changed == 0 {
+            Err(CoreError::NotFound)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn list_grants(&self, slug: &Slug) -> Result<Vec<LinkGrant>, CoreError> {
+        let conn = self.pool.reader()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT slug, user_email, role, granted_at, granted_by FROM link_grants WHERE slug = ?1 AND tenant = ?2 ORDER BY granted_at",
+            )
+            .map_err(map_sqerr)?;
+        let mut rows = stmt
+            .query(params![slug.key(), self.tenant.as_str()])
+            .map_err(map_sqerr)?;
+        let mut out = Vec::new();
+        while let Some(row) = rows.next().map_err(map_sqerr)? {
+            out.push(row_to_grant(row)?);
+        }
+        Ok(out)
+    }
+
+    fn get_grant(
+        &self,
+        slug: &Slug,
+        user_email: &UserEmail,
+    ) -> Result<Option<LinkGrant>, CoreError> {
+        let conn = self.pool.reader()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT slug, user_email, role, granted_at, granted_by FROM link_grants WHERE slug = ?1 AND user_email = ?2 AND tenant = ?3",
+            )
+            .map_err(map_sqerr)?;
+        let mut rows = stmt
+            .query(params![
+                slug.key(),
+                user_email.as_str(),
+                self.tenant.as_str()
+            ])
+            .map_err(map_sqerr)?;
+        if let Some(row) = rows.next().map_err(map_sqerr)? {
+            Ok(Some(row_to_grant(row)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn list_grants_for_user(&self, user_email: &UserEmail) -> Result<Vec<LinkGrant>, CoreError> {
+        let conn = self.pool.reader()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT slug, user_email, role, granted_at, granted_by FROM link_grants WHERE user_email = ?1 AND tenant = ?2",
+            )
+            .map_err(map_sqerr)?;
+        let mut rows = stmt
+            .query(params![user_email.as_str(), self.tenant.as_str()])
+            .map_err(map_sqerr)?;
+        let mut out = Vec::new();
+        while let Some(row) = rows.next().map_err(map_sqerr)? {
+            out.push(row_to_grant(row)?);
+        }
+        Ok(out)
+    }
+}
+
+fn row_to_grant(row: &rusqlite::Row) -> Result<LinkGrant, CoreError> {
+    let slug: String = row.get(0).map_err(map_sqerr)?;
+    let user_email: String = row.get(1).map_err(map_sqerr)?;
+    let role_str: String = row.get(2).map_err(map_sqerr)?;
+    let granted_at: i64 = row.get(3).map_err(map_sqerr)?;
+    let granted_by: String = row.get(4).map_err(map_sqerr)?;
+    Ok(LinkGrant {
+        slug: Slug::from_key(&slug)
+            .map_err(|e| CoreError::Repository(format!("bad slug in db: {e}")))?,
+        user_email: UserEmail::new(user_email)
+            .map_err(|_| CoreError::Repository("bad email".into()))?,
+        role: str_to_role(&role_str),
+        granted_at: secs_to_system_time(granted_at as u64),
+        granted_by: UserEmail::new(granted_by)
+            .map_err(|_| CoreError::Repository("bad email".into()))?,
+    })
+}
+
 // ============ ClickRepository ============
 
 impl ClickRepository for SqliteRepo {
     fn record_click(&self, event: ClickEvent) -> Result<(), CoreError> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let conn = self.pool.writer()?;
         conn.execute(
-            "INSERT INTO click_events(slug, clicked_at, user_agent, referrer, country) VALUES (?1, ?2, ?3, ?4, ?5)",
+            "INSERT INTO click_events(slug, clicked_at, user_agent, referrer, country, tenant) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
             params![
-                event.slug.as_str(),
+                event.slug.key(),
                 system_time_to_secs(event.clicked_at) as i64,
                 event.user_agent,
                 event.referrer,
                 event.country,
+                self.tenant.as_str(),
             ],
         ).map_err(map_sqerr)?;
         Ok(())
     }
 
     fn get_clicks(&self, slug: &Slug, limit: usize) -> Result<Vec<ClickEvent>, CoreError> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let conn = self.pool.reader()?;
         let mut stmt = conn.prepare(
-            "SELECT slug, clicked_at, user_agent, referrer, country FROM click_events WHERE slug = ?1 ORDER BY clicked_at DESC LIMIT ?2"
+            "SELECT slug, clicked_at, user_agent, referrer, country FROM click_events WHERE slug = ?1 AND tenant = ?3 ORDER BY clicked_at DESC LIMIT ?2"
         ).map_err(map_sqerr)?;
         let mut rows = stmt
-            .query(params![slug.as_str(), limit as i64])
+            .query(params![slug.key(), limit as i64, self.tenant.as_str()])
             .map_err(map_sqerr)?;
         let mut out = Vec::new();
         while let Some(row) = rows.next().map_err(map_sqerr)? {
@@ -830,15 +1552,12 @@ impl ClickRepository for SqliteRepo {
     }
 
     fn get_click_count_since(&self, slug: &Slug, since: SystemTime) -> Result<u64, CoreError> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let conn = self.pool.reader()?;
         let since_secs = system_time_to_secs(since) as i64;
         let count: i64 = conn
             .query_row(
-                "SELECT COUNT(*) FROM click_events WHERE slug = ?1 AND clicked_at >= ?2",
-                params![slug.as_str(), since_secs],
+                "SELECT COUNT(*) FROM click_events WHERE slug = ?1 AND tenant = ?3 AND clicked_at >= ?2",
+                params![slug.key(), since_secs, self.tenant.as_str()],
                 |r| r.get(0),
             )
             .map_err(map_sqerr)?;
@@ -846,10 +1565,7 @@ impl ClickRepository for SqliteRepo {
     }
 
     fn get_clicks_by_day(&self, slug: &Slug, days: usize) -> Result<Vec<(String, u64)>, CoreError> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let conn = self.pool.reader()?;
         let cutoff = SystemTime::now()
             .checked_sub(Duration::from_secs(days as u64 * 24 * 60 * 60))
             .unwrap_or(UNIX_EPOCH);
@@ -859,13 +1575,13 @@ impl ClickRepository for SqliteRepo {
             .prepare(
                 "SELECT date(clicked_at, 'unixepoch') as day, COUNT(*) as cnt
              FROM click_events
-             WHERE slug = ?1 AND clicked_at >= ?2
+             WHERE slug = ?1 AND tenant = ?3 AND clicked_at >= ?2
              GROUP BY day
              ORDER BY day",
             )
             .map_err(map_sqerr)?;
         let mut rows = stmt
-            .query(params![slug.as_str(), cutoff_secs])
+            .query(params![slug.key(), cutoff_secs, self.tenant.as_str()])
             .map_err(map_sqerr)?;
         let mut out = Vec::new();
         while let Some(row) = rows.next().map_err(map_sqerr)? {
@@ -884,7 +1600,8 @@ fn row_to_click(row: &rusqlite::Row) -> Result<ClickEvent, CoreError> {
     let referrer: Option<String> = row.get(3).map_err(map_sqerr)?;
     let country: Option<String> = row.get(4).map_err(map_sqerr)?;
     Ok(ClickEvent {
-        slug: Slug::new(slug_str).map_err(|e| CoreError::Repository(format!("bad slug: {e}")))?,
+        slug: Slug::from_key(&slug_str)
+            .map_err(|e| CoreError::Repository(format!("bad slug: {e}")))?,
         clicked_at: secs_to_system_time(clicked_at as u64),
         user_agent,
         referrer,
@@ -896,13 +1613,10 @@ fn row_to_click(row: &rusqlite::Row) -> Result<ClickEvent, CoreError> {
 
 impl AuditRepository for SqliteRepo {
     fn log(&self, entry: AuditEntry) -> Result<(), CoreError> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let conn = self.pool.writer()?;
         let action_str = format!("{:?}", entry.action);
         conn.execute(
-            "INSERT INTO audit_log(id, timestamp, actor_email, action, target_type, target_id, changes) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            "INSERT INTO audit_log(id, timestamp, actor_email, action, target_type, target_id, changes, tenant) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
             params![
                 entry.id,
                 system_time_to_secs(entry.timestamp) as i64,
@@ -911,6 +1625,7 @@ impl AuditRepository for SqliteRepo {
                 entry.target_type,
                 entry.target_id,
                 entry.changes,
+                self.tenant.as_str(),
             ],
         ).map_err(map_sqerr)?;
         Ok(())
@@ -922,19 +1637,21 @@ impl AuditRepository for SqliteRepo {
         target_id: &str,
         limit: usize,
     ) -> Result<Vec<AuditEntry>, CoreError> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let conn = self.pool.reader()?;
         let mut stmt = conn
             .prepare(
                 "SELECT id, timestamp, actor_email, action, target_type, target_id, changes
-             FROM audit_log WHERE target_type = ?1 AND target_id = ?2
+             FROM audit_log WHERE target_type = ?1 AND target_id = ?2 AND tenant = ?4
              ORDER BY timestamp DESC LIMIT ?3",
             )
             .map_err(map_sqerr)?;
         let mut rows = stmt
-            .query(params![target_type, target_id, limit as i64])
+            .query(params![
+                target_type,
+                target_id,
+                limit as i64,
+                self.tenant.as_str()
+            ])
             .map_err(map_sqerr)?;
         let mut out = Vec::new();
         while let Some(row) = rows.next().map_err(map_sqerr)? {
@@ -948,19 +1665,20 @@ impl AuditRepository for SqliteRepo {
         actor_email: &UserEmail,
         limit: usize,
     ) -> Result<Vec<AuditEntry>, CoreError> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let conn = self.pool.reader()?;
         let mut stmt = conn
             .prepare(
                 "SELECT id, timestamp, actor_email, action, target_type, target_id, changes
-             FROM audit_log WHERE actor_email = ?1
+             FROM audit_log WHERE actor_email = ?1 AND tenant = ?3
              ORDER BY timestamp DESC LIMIT ?2",
             )
             .map_err(map_sqerr)?;
         let mut rows = stmt
-            .query(params![actor_email.as_str(), limit as i64])
+            .query(params![
+                actor_email.as_str(),
+                limit as i64,
+                self.tenant.as_str()
+            ])
             .map_err(map_sqerr)?;
         let mut out = Vec::new();
         while let Some(row) = rows.next().map_err(map_sqerr)? {
@@ -970,17 +1688,16 @@ impl AuditRepository for SqliteRepo {
     }
 
     fn list_recent(&self, limit: usize) -> Result<Vec<AuditEntry>, CoreError> {
-        let conn = self
-            .conn
-            .lock()
-            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let conn = self.pool.reader()?;
         let mut stmt = conn
             .prepare(
                 "SELECT id, timestamp, actor_email, action, target_type, target_id, changes
-             FROM audit_log ORDER BY timestamp DESC LIMIT ?1",
+             FROM audit_log WHERE tenant = ?2 ORDER BY timestamp DESC LIMIT ?1",
             )
             .map_err(map_sqerr)?;
-        let mut rows = stmt.query(params![limit as i64]).map_err(map_sqerr)?;
+        let mut rows = stmt
+            .query(params![limit as i64, self.tenant.as_str()])
+            .map_err(map_sqerr)?;
         let mut out = Vec::new();
         while let Some(row) = rows.next().map_err(map_sqerr)? {
             out.push(row_to_audit(row)?);
@@ -1003,8 +1720,14 @@ fn row_to_audit(row: &rusqlite::Row) -> Result<AuditEntry, CoreError> {
         "Create" => AuditAction::Create,
         "Update" => AuditAction::Update,
         "Delete" => AuditAction::Delete,
+        "Restore" => AuditAction::Restore,
         "Activate" => AuditAction::Activate,
         "Deactivate" => AuditAction::Deactivate,
+        "AddMember" => AuditAction::AddMember,
+        "RemoveMember" => AuditAction::RemoveMember,
+        "Invite" => AuditAction::Invite,
+        "AcceptInvite" => AuditAction::AcceptInvite,
+        "DeclineInvite" => AuditAction::DeclineInvite,
         _ => AuditAction::Create, // fallback
     };
 
@@ -1020,6 +1743,272 @@ fn row_to_audit(row: &rusqlite::Row) -> Result<AuditEntry, CoreError> {
     })
 }
 
+// ============ OrganizationRepository ============
+
+impl OrganizationRepository for SqliteRepo {
+    fn put_organization(&self, org: Organization) -> Result<(), CoreError> {
+        let conn = self.pool.writer()?;
+        let admins: Vec<String> = org.admins.iter().map(|a| a.as_str().to_string()).collect();
+        conn.execute(
+            "INSERT OR REPLACE INTO organizations(id, name, hosts, auth_domains, admins, shortlink_domain, allowed_hosts, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                org.id.as_str(),
+                org.name,
+                hosts_to_column(&org.hosts),
+                hosts_to_column(&org.auth_domains),
+                hosts_to_column(&admins),
+                org.settings.shortlink_domain,
+                hosts_to_column(&org.settings.allowed_hosts),
+                system_time_to_secs(org.created_at) as i64,
+            ],
+        )
+        .map_err(map_sqerr)?;
+        Ok(())
+    }
+
+    fn get_organization(&self, id: &TenantId) -> Result<Option<Organization>, CoreError> {
+        let conn = self.pool.reader()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, name, hosts, auth_domains, admins, shortlink_domain, allowed_hosts, created_at FROM organizations WHERE id = ?1",
+            )
+            .map_err(map_sqerr)?;
+        let mut rows = stmt.query(params![id.as_str()]).map_err(map_sqerr)?;
+        if let Some(row) = rows.next().map_err(map_sqerr)? {
+            Ok(Some(row_to_organization(row)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn list_organizations(&self) -> Result<Vec<Organization>, CoreError> {
+        let conn = self.pool.reader()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, name, hosts, auth_domains, admins, shortlink_domain, allowed_hosts, created_at FROM organizations ORDER BY id",
+            )
+            .map_err(map_sqerr)?;
+        let mut rows = stmt.query([]).map_err(map_sqerr)?;
+        let mut out = Vec::new();
+        while let Some(row) = rows.next().map_err(map_sqerr)? {
+            out.push(row_to_organization(row)?);
+        }
+        Ok(out)
+    }
+}
+
+// ============ DomainRepository ============
+
+impl DomainRepository for SqliteRepo {
+    fn put_domain(&self, domain: ShortDomain) -> Result<(), CoreError> {
+        let conn = self.pool.writer()?;
+        let users: Vec<String> = domain
+            .allowed_users
+            .iter()
+            .map(|u| u.as_str().to_string())
+            .collect();
+        conn.execute(
+            "INSERT OR REPLACE INTO short_domains(tenant, host, allowed_users, allowed_groups, created_at, created_by) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                self.tenant.as_str(),
+                domain.host,
+                hosts_to_column(&users),
+                hosts_to_column(&domain.allowed_groups),
+                system_time_to_secs(domain.created_at) as i64,
+                domain.created_by.as_str(),
+            ],
+        )
+        .map_err(map_sqerr)?;
+        Ok(())
+    }
+
+    fn get_domain(&self, host: &str) -> Result<Option<ShortDomain>, CoreError> {
+        let conn = self.pool.reader()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT host, allowed_users, allowed_groups, created_at, created_by FROM short_domains WHERE tenant = ?1 AND host = ?2",
+            )
+            .map_err(map_sqerr)?;
+        let mut rows = stmt
+            .query(params![self.tenant.as_str(), host])
+            .map_err(map_sqerr)?;
+        if let Some(row) = rows.next().map_err(map_sqerr)? {
+            Ok(Some(row_to_domain(row)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn list_domains(&self) -> Result<Vec<ShortDomain>, CoreError> {
+        let conn = self.pool.reader()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT host, allowed_users, allowed_groups, created_at, created_by FROM short_domains WHERE tenant = ?1 ORDER BY host",
+            )
+            .map_err(map_sqerr)?;
+        let mut rows = stmt
+            .query(params![self.tenant.as_str()])
+            .map_err(map_sqerr)?;
+        let mut out = Vec::new();
+        while let Some(row) = rows.next().map_err(map_sqerr)? {
+            out.push(row_to_domain(row)?);
+        }
+        Ok(out)
+    }
+
+    fn delete_domain(&self, host: &str) -> Result<(), CoreError> {
+        let conn = self.pool.writer()?;
+        let n = conn
+            .execute(
+                "DELETE FROM short_domains WHERE tenant = ?1 AND host = ?2",
+                params![self.tenant.as_str(), host],
+            )
+            .map_err(map_sqerr)?;
+        if n == 0 {
+            return Err(CoreError::NotFound);
+        }
+        Ok(())
+    }
+}
+
+fn row_to_domain(row: &rusqlite::Row) -> Result<ShortDomain, CoreError> {
+    let lines = |idx: usize| -> Result<Vec<String>, CoreError> {
+        let col: Option<String> = row.get(idx).map_err(map_sqerr)?;
+        Ok(col
+            .map(|c| c.lines().map(str::to_string).collect())
+            .unwrap_or_default())
+    };
+    let email =
+        |e: String| UserEmail::new(e).map_err(|_| CoreError::Repository("bad email".into()));
+    let host: String = row.get(0).map_err(map_sqerr)?;
+    let created_at: i64 = row.get(3).map_err(map_sqerr)?;
+    let created_by: String = row.get(4).map_err(map_sqerr)?;
+    Ok(ShortDomain {
+        host,
+        allowed_users: lines(1)?.into_iter().map(email).collect::<Result<_, _>>()?,
+        allowed_groups: lines(2)?,
+        created_at: secs_to_system_time(created_at as u64),
+        created_by: email(created_by)?,
+    })
+}
+
+// ============ NamespaceRepository ============
+
+impl NamespaceRepository for SqliteRepo {
+    fn put_namespace(&self, namespace: Namespace) -> Result<(), CoreError> {
+        let conn = self.pool.writer()?;
+        let res = conn.execute(
+            "INSERT INTO namespaces(tenant, path, group_id, created_at, created_by) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                self.tenant.as_str(),
+                namespace.path,
+                namespace.group_id,
+                system_time_to_secs(namespace.created_at) as i64,
+                namespace.created_by.as_str(),
+            ],
+        );
+        match res {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                if let rusqlite::Error::SqliteFailure(err, _) = &e {
+                    if err.code == rusqlite::ErrorCode::ConstraintViolation {
+                        return Err(CoreError::AlreadyExists);
+                    }
+                }
+                Err(map_sqerr(e))
+            }
+        }
+    }
+
+    fn get_namespace(&self, path: &str) -> Result<Option<Namespace>, CoreError> {
+        let conn = self.pool.reader()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT path, group_id, created_at, created_by FROM namespaces WHERE tenant = ?1 AND path = ?2",
+            )
+            .map_err(map_sqerr)?;
+        let mut rows = stmt
+            .query(params![self.tenant.as_str(), path])
+            .map_err(map_sqerr)?;
+        if let Some(row) = rows.next().map_err(map_sqerr)? {
+            Ok(Some(row_to_namespace(row)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn list_namespaces(&self) -> Result<Vec<Namespace>, CoreError> {
+        let conn = self.pool.reader()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT path, group_id, created_at, created_by FROM namespaces WHERE tenant = ?1 ORDER BY path",
+            )
+            .map_err(map_sqerr)?;
+        let mut rows = stmt
+            .query(params![self.tenant.as_str()])
+            .map_err(map_sqerr)?;
+        let mut out = Vec::new();
+        while let Some(row) = rows.next().map_err(map_sqerr)? {
+            out.push(row_to_namespace(row)?);
+        }
+        Ok(out)
+    }
+
+    fn delete_namespace(&self, path: &str) -> Result<(), CoreError> {
+        let conn = self.pool.writer()?;
+        let n = conn
+            .execute(
+                "DELETE FROM namespaces WHERE tenant = ?1 AND path = ?2",
+                params![self.tenant.as_str(), path],
+            )
+            .map_err(map_sqerr)?;
+        if n == 0 {
+            return Err(CoreError::NotFound);
+        }
+        Ok(())
+    }
+}
+
+fn row_to_namespace(row: &rusqlite::Row) -> Result<Namespace, CoreError> {
+    let created_at: i64 = row.get(2).map_err(map_sqerr)?;
+    let created_by: String = row.get(3).map_err(map_sqerr)?;
+    Ok(Namespace {
+        path: row.get(0).map_err(map_sqerr)?,
+        group_id: row.get(1).map_err(map_sqerr)?,
+        created_at: secs_to_system_time(created_at as u64),
+        created_by: UserEmail::new(created_by)
+            .map_err(|_| CoreError::Repository("bad email".into()))?,
+    })
+}
+
+fn row_to_organization(row: &rusqlite::Row) -> Result<Organization, CoreError> {
+    let lines = |idx: usize| -> Result<Vec<String>, CoreError> {
+        let col: Option<String> = row.get(idx).map_err(map_sqerr)?;
+        Ok(col
+            .map(|c| c.lines().map(str::to_string).collect())
+            .unwrap_or_default())
+    };
+    let id: String = row.get(0).map_err(map_sqerr)?;
+    let name: String = row.get(1).map_err(map_sqerr)?;
+    let shortlink_domain: Option<String> = row.get(5).map_err(map_sqerr)?;
+    let created_at: i64 = row.get(7).map_err(map_sqerr)?;
+    Ok(Organization {
+        id: TenantId::new(id).map_err(|e| CoreError::Repository(format!("bad tenant: {e}")))?,
+        name,
+        hosts: lines(2)?,
+        auth_domains: lines(3)?,
+        admins: lines(4)?
+            .into_iter()
+            .map(|a| UserEmail::new(a).map_err(|_| CoreError::Repository("bad email".into())))
+            .collect::<Result<_, _>>()?,
+        settings: OrganizationSettings {
+            shortlink_domain,
+            allowed_hosts: lines(6)?,
+        },
+        created_at: secs_to_system_time(created_at as u64),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1047,6 +2036,72 @@ mod tests {
         assert!(got.is_active);
     }
 
+    #[test]
+    fn schema_version_is_reported_and_newer_schemas_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("t.db");
+        assert!(schema_version_at(&path).is_err());
+        let repo = SqliteRepo::new(&path).unwrap();
+        assert_eq!(repo.schema_version().unwrap(), SCHEMA_VERSION);
+        assert_eq!(schema_version_at(&path).unwrap(), SCHEMA_VERSION);
+        repo.pool
+            .writer()
+            .unwrap()
+            .execute(
+                "INSERT INTO schema_version(version, name, applied_at) VALUES (?1, 'future', 0)",
+                [SCHEMA_VERSION + 1],
+            )
+            .unwrap();
+        drop(repo);
+        assert_eq!(schema_version_at(&path).unwrap(), SCHEMA_VERSION + 1);
+        assert!(matches!(
+            SqliteRepo::new(&path),
+            Err(CoreError::Repository(m)) if m.contains("newer than this build")
+        ));
+    }
+
+    #[test]
+    fn reads_do_not_wait_for_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = SqliteOptions {
+            readers: 1,
+            busy_timeout: Duration::from_millis(50),
+        };
+        let repo = SqliteRepo::open(dir.path().join("t.db"), &options).unwrap();
+        let slug = Slug::new("docs").unwrap();
+        let by = UserEmail::new("a@acme.com").unwrap();
+        repo.put(ShortLink::new(
+            slug.clone(),
+            "https://docs.example".into(),
+            UNIX_EPOCH,
+            by,
+        ))
+        .unwrap();
+
+        // Reads see the last commit while a write transaction is open
+        let writer = repo.pool.writer().unwrap();
+        writer
+            .execute_batch("BEGIN IMMEDIATE; UPDATE shortlinks SET description = 'pending';")
+            .unwrap();
+        assert_eq!(repo.get(&slug).unwrap().unwrap().description, None);
+        writer.execute_batch("COMMIT").unwrap();
+        drop(writer);
+        assert_eq!(
+            repo.get(&slug).unwrap().unwrap().description.as_deref(),
+            Some("pending")
+        );
+
+        // With every reader checked out, reads give up after the busy timeout
+        let held = repo.pool.reader().unwrap();
+        assert!(matches!(repo.get(&slug), Err(CoreError::Repository(_))));
+        drop(held);
+        assert!(repo.get(&slug).unwrap().is_some());
+
+        // In-memory databases have no readers and share the writer
+        let memory = SqliteRepo::new(":memory:").unwrap();
+        assert_eq!(memory.list(10).unwrap().len(), 0);
+    }
+
     #[test]
     fn put_duplicate_conflict() {
         let (repo, _dir) = tmp_db();
@@ -1122,6 +2177,28 @@ mod tests {
         assert!(!got.is_active);
     }
 
+    #[test]
+    fn visibility_roundtrip() {
+        let (repo, _dir) = tmp_db();
+        let mut link = ShortLink::new(
+            Slug::new("internal").unwrap(),
+            "https://wiki.acme.com".into(),
+            SystemTime::UNIX_EPOCH,
+            UserEmail::new("u@acme.com").unwrap(),
+        );
+        repo.put(link.clone()).unwrap();
+        assert_eq!(
+            repo.get(&link.slug).unwrap().unwrap().visibility,
+            LinkVisibility::Public
+        );
+
+        link.visibility = LinkVisibility::Workspace;
+        repo.update(&link).unwrap();
+        let got = repo.get(&link.slug).unwrap().unwrap();
+        assert_eq!(got.visibility, LinkVisibility::Workspace);
+        assert!(got.requires_login());
+    }
+
     #[test]
     fn list_by_creator_works() {
         let (repo, _dir) = tmp_db();
@@ -1156,4 +2233,768 @@ mod tests {
         let user2_links = repo.list_by_creator(&user2, 10).unwrap();
         assert_eq!(user2_links.len(), 2);
     }
+
+    #[test]
+    fn link_grants_upsert_list_remove() {
+        let (repo, _dir) = tmp_db();
+        let slug = Slug::new("shared").unwrap();
+        let bob = UserEmail::new("bob@acme.com").unwrap();
+        let grant = LinkGrant {
+            slug: slug.clone(),
+            user_email: bob.clone(),
+            role: GroupRole::Viewer,
+            granted_at: UNIX_EPOCH + Duration::from_secs(10),
+            granted_by: UserEmail::new("u@acme.com").unwrap(),
+        };
+        repo.put_grant(grant.clone()).unwrap();
+        repo.put_grant(LinkGrant {
+            role: GroupRole::Editor,
+            ..grant
+        })
+        .unwrap();
+
+        let grants = repo.list_grants(&slug).unwrap();
+        assert_eq!(grants.len(), 1);
+        assert_eq!(grants[0].role, GroupRole::Editor);
+        assert_eq!(
+            repo.get_grant(&slug, &bob).unwrap().unwrap().granted_at,
+            UNIX_EPOCH + Duration::from_secs(10)
+        );
+
+        repo.remove_grant(&slug, &bob).unwrap();
+        assert!(repo.get_grant(&slug, &bob).unwrap().is_none());
+        assert!(matches!(
+            repo.remove_grant(&slug, &bob),
+            Err(CoreError::NotFound)
+        ));
+    }
+
+    #[test]
+    fn deleting_links_drops_their_grants() {
+        let (repo, _dir) = tmp_db();
+        domain::adapters::conformance::deleting_links_drops_their_grants(&repo, &repo, &repo);
+    }
+
+    #[test]
+    fn link_grants_follow_rename_and_delete() {
+        let (repo, _dir) = tmp_db();
+        let (old, new) = (Slug::new("tpyo").unwrap(), Slug::new("typo").unwrap());
+        let bob = UserEmail::new("bob@acme.com").unwrap();
+        repo.put(ShortLink::new(
+            old.clone(),
+            "https://example.com".into(),
+            UNIX_EPOCH,
+            UserEmail::new("u@acme.com").unwrap(),
+        ))
+        .unwrap();
+        repo.put_grant(LinkGrant {
+            slug: old.clone(),
+            user_email: bob.clone(),
+            role: GroupRole::Editor,
+            granted_at: UNIX_EPOCH,
+            granted_by: UserEmail::new("u@acme.com").unwrap(),
+        })
+        .unwrap();
+
+        repo.rename(&old, &new).unwrap();
+        assert!(repo.get_grant(&old, &bob).unwrap().is_none());
+        assert!(repo.get_grant(&new, &bob).unwrap().is_some());
+
+        repo.delete(&new, UNIX_EPOCH).unwrap();
+        assert!(repo.list_grants_for_user(&bob).unwrap().is_empty());
+    }
+
+    #[test]
+    fn group_settings_roundtrip() {
+        let (repo, _dir) = tmp_db();
+        let alice = UserEmail::new("alice@acme.com").unwrap();
+        let mut group = LinkGroup {
+            id: "grp_mkt".into(),
+            name: "Marketing".into(),
+            description: None,
+            created_at: UNIX_EPOCH,
+            created_by: alice.clone(),
+            settings: GroupSettings::default(),
+            parent_id: None,
+        };
+        repo.create_group(group.clone()).unwrap();
+        assert_eq!(repo.get_group("grp_mkt").unwrap().unwrap(), group);
+
+        group.settings = GroupSettings {
+            default_expiry_secs: Some(3600),
+            default_redirect_delay: Some(2),
+            allowed_hosts: vec!["*.acme.com".into(), "acme.org".into()],
+            slug_prefix: Some("mkt-".into()),
+            max_links: Some(50),
+        };
+        repo.update_group(&group).unwrap();
+        assert_eq!(repo.get_group("grp_mkt").unwrap().unwrap(), group);
+        let (listed, role) = repo.get_user_groups(&alice).unwrap().remove(0);
+        assert_eq!(listed.settings, group.settings);
+        assert_eq!(role, GroupRole::Admin);
+
+        group.settings = GroupSettings::default();
+        repo.update_group(&group).unwrap();
+        assert_eq!(repo.get_group("grp_mkt").unwrap().unwrap(), group);
+    }
+
+    #[test]
+    fn group_lifecycle_safeguards() {
+        let (repo, _dir) = tmp_db();
+        let alice = UserEmail::new("alice@acme.com").unwrap();
+        let bob = UserEmail::new("bob@acme.com").unwrap();
+        for id in ["grp_1", "grp_2"] {
+            repo.create_group(LinkGroup {
+                id: id.into(),
+                name: id.into(),
+                description: None,
+                created_at: UNIX_EPOCH,
+                created_by: alice.clone(),
+                settings: GroupSettings::default(),
+                parent_id: None,
+            })
+            .unwrap();
+            repo.add_member(GroupMember {
+                group_id: id.into(),
+                user_email: alice.clone(),
+                role: GroupRole::Admin,
+                added_at: UNIX_EPOCH,
+                added_by: alice.clone(),
+            })
+            .unwrap();
+        }
+        repo.add_member(GroupMember {
+            group_id: "grp_1".into(),
+            user_email: bob.clone(),
+            role: GroupRole::Editor,
+            added_at: UNIX_EPOCH,
+            added_by: alice.clone(),
+        })
+        .unwrap();
+
+        assert!(matches!(
+            repo.remove_member("grp_1", &alice),
+            Err(CoreError::Conflict(_))
+        ));
+        repo.transfer_ownership("grp_1", &bob).unwrap();
+        assert_eq!(repo.get_group("grp_1").unwrap().unwrap().created_by, bob);
+        repo.remove_member("grp_1", &alice).unwrap();
+
+        let mut link = ShortLink::new(
+            Slug::new("grouped").unwrap(),
+            "https://example.com".into(),
+            UNIX_EPOCH,
+            bob.clone(),
+        );
+        link.group_id = Some("grp_1".into());
+        repo.put(link.clone()).unwrap();
+
+        let at = UNIX_EPOCH + Duration::from_secs(10);
+        assert!(matches!(
+            repo.delete_group(
+                "grp_1",
+                &GroupLinkDisposition::Reassign("missing".into()),
+                at
+            ),
+            Err(CoreError::NotFound)
+        ));
+        assert!(repo.get_group("grp_1").unwrap().is_some());
+        let moved = repo
+            .delete_group("grp_1", &GroupLinkDisposition::Reassign("grp_2".into()), at)
+            .unwrap();
+        assert_eq!(moved, 1);
+        assert!(repo.list_members("grp_1").unwrap().is_empty());
+        let got = repo.get(&link.slug).unwrap().unwrap();
+        assert_eq!(got.group_id.as_deref(), Some("grp_2"));
+
+        repo.delete_group("grp_2", &GroupLinkDisposition::SoftDelete, at)
+            .unwrap();
+        let got = repo.get(&link.slug).unwrap().unwrap();
+        assert_eq!(got.group_id, None);
+        assert_eq!(got.deleted_at, Some(at));
+    }
+
+    #[test]
+    fn nested_groups_inherit_and_reparent() {
+        let (repo, _dir) = tmp_db();
+        let alice = UserEmail::new("alice@acme.com").unwrap();
+        let bob = UserEmail::new("bob@acme.com").unwrap();
+        for (id, parent) in [
+            ("dept", None),
+            ("team", Some("dept")),
+            ("squad", Some("team")),
+        ] {
+            repo.create_group(LinkGroup {
+                id: id.into(),
+                name: id.into(),
+                description: None,
+                created_at: UNIX_EPOCH,
+                created_by: alice.clone(),
+                settings: GroupSettings::default(),
+                parent_id: parent.map(str::to_string),
+            })
+            .unwrap();
+        }
+        repo.add_member(GroupMember {
+            group_id: "dept".into(),
+            user_email: bob.clone(),
+            role: GroupRole::Editor,
+            added_at: UNIX_EPOCH,
+            added_by: alice.clone(),
+        })
+        .unwrap();
+
+        assert_eq!(
+            repo.effective_role("squad", &bob).unwrap(),
+            Some(GroupRole::Editor)
+        );
+        let mut ids: Vec<_> = repo
+            .get_user_groups(&bob)
+            .unwrap()
+            .into_iter()
+            .map(|(g, _)| g.id)
+            .collect();
+        ids.sort();
+        assert_eq!(ids, ["dept", "squad", "team"]);
+
+        repo.delete_group("team", &GroupLinkDisposition::Detach, UNIX_EPOCH)
+            .unwrap();
+        let squad = repo.get_group("squad").unwrap().unwrap();
+        assert_eq!(squad.parent_id.as_deref(), Some("dept"));
+        assert_eq!(repo.list_child_groups("dept").unwrap(), vec![squad]);
+    }
+
+    #[test]
+    fn invitation_lifecycle_and_audit() {
+        use domain::AuditAction;
+
+        let (repo, _dir) = tmp_db();
+        let bob = UserEmail::new("bob@acme.com").unwrap();
+        let inv = GroupInvitation {
+            id: "inv_1".into(),
+            group_id: "grp_1".into(),
+            email: bob.clone(),
+            role: GroupRole::Editor,
+            invited_by: UserEmail::new("u@acme.com").unwrap(),
+            created_at: UNIX_EPOCH,
+            expires_at: UNIX_EPOCH + Duration::from_secs(60),
+            status: InvitationStatus::Pending,
+            responded_at: None,
+        };
+        repo.create_invitation(inv.clone()).unwrap();
+        assert!(matches!(
+            repo.create_invitation(inv.clone()),
+            Err(CoreError::AlreadyExists)
+        ));
+        assert_eq!(repo.list_invitations_for_user(&bob).unwrap(), vec![inv]);
+
+        repo.respond_invitation(
+            "inv_1",
+            InvitationStatus::Accepted,
+            UNIX_EPOCH + Duration::from_secs(5),
+        )
+        .unwrap();
+        assert!(matches!(
+            repo.respond_invitation("inv_1", InvitationStatus::Declined, UNIX_EPOCH),
+            Err(CoreError::NotFound)
+        ));
+        let got = repo.get_invitation("inv_1").unwrap().unwrap();
+        assert_eq!(got.status, InvitationStatus::Accepted);
+        assert_eq!(got.responded_at, Some(UNIX_EPOCH + Duration::from_secs(5)));
+
+        repo.log(AuditEntry {
+            id: "a1".into(),
+            timestamp: UNIX_EPOCH,
+            actor_email: bob,
+            action: AuditAction::AcceptInvite,
+            target_type: "group_invitation".into(),
+            target_id: "inv_1".into(),
+            changes: None,
+        })
+        .unwrap();
+        let entries = repo
+            .list_for_target("group_invitation", "inv_1", 10)
+            .unwrap();
+        assert_eq!(entries[0].action, AuditAction::AcceptInvite);
+    }
+
+    #[test]
+    fn list_paginated_accessible_scope() {
+        let (repo, _dir) = tmp_db();
+        let me = UserEmail::new("me@acme.com").unwrap();
+        let other = UserEmail::new("other@acme.com").unwrap();
+        let mk = |slug: &str, secs: u64, by: &UserEmail, group: Option<&str>| {
+            let mut l = ShortLink::new(
+                Slug::new(slug).unwrap(),
+                format!("https://e/{slug}"),
+                UNIX_EPOCH + Duration::from_secs(secs),
+                by.clone(),
+            );
+            l.group_id = group.map(str::to_string);
+            l
+        };
+        repo.put(mk("own", 1, &me, None)).unwrap();
+        repo.put(mk("mine", 1, &me, None)).unwrap();
+        repo.put(mk("grp", 2, &other, Some("g1"))).unwrap();
+        repo.put(mk("shared", 3, &other, None)).unwrap();
+        repo.put(mk("hidden", 4, &other, Some("g2"))).unwrap();
+
+        let scope = domain::AccessScope {
+            user: me.clone(),
+            group_ids: vec!["g1".into()],
+            shared_slugs: vec![Slug::new("shared").unwrap()],
+        };
+        let page = |cursor: Option<String>| {
+            repo.list_paginated(&ListOptions {
+                limit: 2,
+                cursor,
+                accessible_to: Some(scope.clone()),
+                ..Default::default()
+            })
+        };
+
+        let first = page(None).unwrap();
+        assert_eq!(first.total, Some(4));
+        assert!(first.has_more);
+        let slugs: Vec<_> = first.items.iter().map(|l| l.slug.as_str()).collect();
+        assert_eq!(slugs, vec!["shared", "grp"]);
+
+        // "own" and "mine" share a timestamp; the slug keeps their order stable.
+        let second = page(first.next_cursor).unwrap();
+        assert!(!second.has_more);
+        assert!(second.next_cursor.is_none());
+        let slugs: Vec<_> = second.items.iter().map(|l| l.slug.as_str()).collect();
+        assert_eq!(slugs, vec!["own", "mine"]);
+
+        assert!(matches!(
+            page(Some("bogus".into())),
+            Err(CoreError::InvalidCursor)
+        ));
+
+        // Scopes larger than SQLite's bound-parameter limit still list
+        let mut wide = scope.clone();
+        wide.group_ids
+            .extend((0..40_000).map(|i| format!("other-{i}")));
+        wide.shared_slugs
+            .extend((0..40_000).map(|i| Slug::new(format!("other-{i}")).unwrap()));
+        let all = repo
+            .list_paginated(&ListOptions {
+                limit: 10,
+                accessible_to: Some(wide),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(all.total, Some(4));
+    }
+
+    #[test]
+    fn list_paginated_sorts_and_filters() {
+        let (repo, _dir) = tmp_db();
+        let me = UserEmail::new("me@acme.com").unwrap();
+        let at = |secs: u64| UNIX_EPOCH + Duration::from_secs(secs);
+        let mk = |slug: &str, created: u64, clicks: u64, url: &str| {
+            let mut l = ShortLink::new(
+                Slug::new(slug).unwrap(),
+                url.to_string(),
+                at(created),
+                me.clone(),
+            );
+            l.click_count = clicks;
+            l
+        };
+        let mut expired = mk("expired", 1, 5, "https://docs.acme.com/a");
+        expired.expires_at = Some(at(50));
+        let mut off = mk("off", 2, 7, "https://blog.acme.com/b");
+        off.is_active = false;
+        let mut later = mk("later", 3, 0, "https://docs.acme.com/c");
+        later.activate_at = Some(at(500));
+        let mut gone = mk("gone", 4, 9, "https://docs.acme.com/d");
+        gone.deleted_at = Some(at(60));
+        for link in [
+            expired,
+            off,
+            later,
+            gone,
+            mk("live", 5, 7, "https://Docs.acme.com/e"),
+        ] {
+            repo.put(link).unwrap();
+        }
+
+        let list = |options: ListOptions| -> Vec<String> {
+            repo.list_paginated(&ListOptions {
+                limit: 10,
+                now: Some(at(100)),
+                ..options
+            })
+            .unwrap()
+            .items
+            .iter()
+            .map(|l| l.slug.as_str().to_string())
+            .collect()
+        };
+        let with_status = |status| ListOptions {
+            status: Some(status),
+            ..Default::default()
+        };
+        assert_eq!(list(with_status(LinkStatus::Active)), ["live"]);
+        assert_eq!(list(with_status(LinkStatus::Inactive)), ["off"]);
+        assert_eq!(list(with_status(LinkStatus::Expired)), ["expired"]);
+        assert_eq!(list(with_status(LinkStatus::Scheduled)), ["later"]);
+        assert_eq!(list(with_status(LinkStatus::Deleted)), ["gone"]);
+        assert_eq!(
+            list(ListOptions {
+                created: domain::TimeRange {
+                    from: Some(at(2)),
+                    to: Some(at(5)),
+                },
+                ..Default::default()
+            }),
+            ["later", "off"]
+        );
+        assert_eq!(
+            list(ListOptions {
+                target_host: Some("docs.acme.com".into()),
+                sort: LinkSort::ExpiresAt,
+                ..Default::default()
+            }),
+            ["expired", "live", "later"]
+        );
+
+        // Most clicked first, paged two at a time; "off" and "live" tie on
+        // clicks and are ordered by slug
+        let page = |cursor| {
+            repo.list_paginated(&ListOptions {
+                limit: 2,
+                sort: LinkSort::ClickCount,
+                cursor,
+                ..Default::default()
+            })
+            .unwrap()
+        };
+        let first = page(None);
+        let slugs: Vec<_> = first.items.iter().map(|l| l.slug.as_str()).collect();
+        assert_eq!(slugs, ["off", "live"]);
+        let second = page(first.next_cursor);
+        let slugs: Vec<_> = second.items.iter().map(|l| l.slug.as_str()).collect();
+        assert_eq!(slugs, ["expired", "later"]);
+        assert!(second.next_cursor.is_none());
+
+        let oldest = list(ListOptions {
+            direction: SortDirection::Asc,
+            ..Default::default()
+        });
+        assert_eq!(oldest, ["expired", "off", "later", "live"]);
+    }
+
+    #[test]
+    fn search_ranks_words_and_follows_writes() {
+        let (repo, _dir) = tmp_db();
+        let alice = UserEmail::new("alice@acme.com").unwrap();
+        let bob = UserEmail::new("bob@acme.com").unwrap();
+        let mk = |slug: &str, url: &str, description: Option<&str>, by: &UserEmail| {
+            let mut l = ShortLink::new(
+                Slug::new(slug).unwrap(),
+                url.to_string(),
+                UNIX_EPOCH + Duration::from_secs(1),
+                by.clone(),
+            );
+            l.description = description.map(str::to_string);
+            l
+        };
+        repo.put(mk(
+            "handbook",
+            "https://docs.acme.com/handbook",
+            Some("Onboarding guide for new hires"),
+            &alice,
+        ))
+        .unwrap();
+        repo.put(mk("onboarding", "https://hr.acme.com/start", None, &bob))
+            .unwrap();
+        repo.put(mk("wiki", "https://wiki.acme.com/home", None, &alice))
+            .unwrap();
+        let slugs = |query: &str| -> Vec<String> {
+            repo.search(query, 10)
+                .unwrap()
+                .iter()
+                .map(|l| l.slug.as_str().to_string())
+                .collect()
+        };
+
+        // A slug match outranks a description match; words match by prefix
+        assert_eq!(slugs("onboarding"), ["onboarding", "handbook"]);
+        assert_eq!(slugs("onb"), ["onboarding", "handbook"]);
+        assert_eq!(slugs("docs handbook"), ["handbook"]);
+        assert_eq!(slugs("bob"), ["onboarding"]);
+        assert!(slugs("https").is_empty());
+        assert!(slugs("\"NOT\" OR (").is_empty());
+
+        let hits = repo
+            .search_ranked(&ListOptions {
+                limit: 10,
+                search: Some("hires".into()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(hits.total, Some(1));
+        let snippet = hits.items[0].snippet.as_deref().unwrap();
+        assert!(snippet.contains("<mark>hires</mark>"), "{snippet}");
+
+        // Updates, renames and deletes keep the index in step
+        let mut wiki = repo.get(&Slug::new("wiki").unwrap()).unwrap().unwrap();
+        wiki.description = Some("Engineering runbooks".into());
+        repo.update(&wiki).unwrap();
+        assert_eq!(slugs("runbooks"), ["wiki"]);
+        repo.rename(&Slug::new("wiki").unwrap(), &Slug::new("kb").unwrap())
+            .unwrap();
+        assert_eq!(slugs("kb"), ["kb"]);
+        repo.delete(&Slug::new("kb").unwrap(), UNIX_EPOCH).unwrap();
+        assert!(slugs("runbooks").is_empty());
+        let page = repo
+            .list_paginated(&ListOptions {
+                limit: 10,
+                search: Some("acme".into()),
+                include_deleted: true,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(page.total, Some(3));
+    }
+
+    #[test]
+    fn tenants_are_isolated_and_legacy_rows_move_to_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("legacy.db");
+        {
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE shortlinks (slug TEXT PRIMARY KEY, original_url TEXT NOT NULL, created_at INTEGER NOT NULL, created_by TEXT NOT NULL);
+                 INSERT INTO shortlinks VALUES ('old', 'https://old.example', 1, 'a@acme.com');",
+            )
+            .unwrap();
+        }
+        let repo = SqliteRepo::new(&path).unwrap();
+        let acme = repo.for_tenant(&TenantId::new("acme").unwrap());
+        let slug = Slug::new("old").unwrap();
+        assert!(repo.get(&slug).unwrap().is_some());
+        assert!(acme.get(&slug).unwrap().is_none());
+        // Legacy rows get their target key backfilled
+        assert_eq!(
+            repo.list_by_target("https://old.example/", TargetMatch::Exact, 10)
+                .unwrap()
+                .len(),
+            1
+        );
+        // ...and are indexed for search
+        assert_eq!(repo.search("old", 10).unwrap().len(), 1);
+        assert_eq!(repo.schema_version().unwrap(), SCHEMA_VERSION);
+
+        // The same slug can exist in both tenants
+        let by = UserEmail::new("a@acme.com").unwrap();
+        acme.put(ShortLink::new(
+            slug.clone(),
+            "https://acme.example".into(),
+            UNIX_EPOCH,
+            by.clone(),
+        ))
+        .unwrap();
+        assert_eq!(
+            acme.get(&slug).unwrap().unwrap().original_url,
+            "https://acme.example"
+        );
+        assert_eq!(
+            repo.get(&slug).unwrap().unwrap().original_url,
+            "https://old.example"
+        );
+        assert_eq!(acme.list(10).unwrap().len(), 1);
+        acme.delete(&slug, UNIX_EPOCH).unwrap();
+        assert!(repo.get(&slug).unwrap().unwrap().deleted_at.is_none());
+
+        let org = Organization {
+            id: TenantId::new("acme").unwrap(),
+            name: "Acme".into(),
+            hosts: vec!["go.acme.com".into()],
+            auth_domains: vec!["acme.com".into(), "acme.io".into()],
+            admins: vec![by],
+            ..Organization::fallback()
+        };
+        repo.put_organization(org.clone()).unwrap();
+        assert_eq!(acme.get_organization(&org.id).unwrap(), Some(org.clone()));
+        assert_eq!(repo.list_organizations().unwrap(), vec![org]);
+    }
+
+    #[test]
+    fn links_are_found_by_canonical_target() {
+        let (repo, _dir) = tmp_db();
+        let acme = repo.for_tenant(&TenantId::new("acme").unwrap());
+        let by = UserEmail::new("user@example.com").unwrap();
+        let mk = |slug: &str, url: &str, secs: u64| {
+            ShortLink::new(
+                Slug::new(slug).unwrap(),
+                url.into(),
+                UNIX_EPOCH + Duration::from_secs(secs),
+                by.clone(),
+            )
+        };
+        repo.put(mk("second", "https://Example.com:443/docs#intro", 2))
+            .unwrap();
+        repo.put(mk("first", "https://example.com/docs", 1))
+            .unwrap();
+        repo.put(mk("gone", "https://example.com/docs", 3)).unwrap();
+        repo.delete(&Slug::new("gone").unwrap(), UNIX_EPOCH)
+            .unwrap();
+        acme.put(mk("acme", "https://example.com/docs", 4)).unwrap();
+        let matches = |key: &str, mode| -> Vec<String> {
+            repo.list_by_target(key, mode, 10)
+                .unwrap()
+                .into_iter()
+                .map(|l| l.slug.as_str().to_string())
+                .collect()
+        };
+        let slugs = |url: &str| matches(&domain::validate::canonical_url(url), TargetMatch::Exact);
+        assert_eq!(slugs("HTTPS://EXAMPLE.COM/docs"), ["first", "second"]);
+        repo.put(mk("nested", "http://EXAMPLE.com:8080/docs/api", 5))
+            .unwrap();
+        repo.put(mk("near", "https://example.com/docsite", 6))
+            .unwrap();
+        assert_eq!(
+            matches("https://example.com/docs/", TargetMatch::Prefix),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            matches("https://example.com/docs", TargetMatch::Prefix),
+            ["first", "second", "near"]
+        );
+        assert_eq!(
+            matches("example.com", TargetMatch::Host),
+            ["first", "second", "nested", "near"]
+        );
+
+        let mut moved = repo.get(&Slug::new("first").unwrap()).unwrap().unwrap();
+        moved.original_url = "https://example.com/elsewhere".into();
+        repo.update(&moved).unwrap();
+        assert_eq!(slugs("https://example.com/docs"), ["second"]);
+        assert_eq!(slugs("https://example.com/elsewhere"), ["first"]);
+    }
+
+    #[test]
+    fn namespaces_are_reserved_once_per_tenant() {
+        let (repo, _dir) = tmp_db();
+        let ns = Namespace {
+            path: "events/2026".into(),
+            group_id: "grp_events".into(),
+            created_at: UNIX_EPOCH,
+            created_by: UserEmail::new("admin@example.com").unwrap(),
+        };
+        repo.put_namespace(ns.clone()).unwrap();
+        assert!(matches!(
+            repo.put_namespace(ns.clone()),
+            Err(CoreError::AlreadyExists)
+        ));
+        let kickoff = Slug::new("events/2026/kickoff").unwrap();
+        assert_eq!(repo.reserved_namespace(&kickoff).unwrap(), Some(ns.clone()));
+        assert_eq!(repo.list_namespaces().unwrap(), vec![ns.clone()]);
+
+        let acme = repo.for_tenant(&TenantId::new("acme").unwrap());
+        assert_eq!(acme.reserved_namespace(&kickoff).unwrap(), None);
+        acme.put_namespace(ns).unwrap();
+
+        repo.delete_namespace("events/2026").unwrap();
+        assert!(matches!(
+            repo.delete_namespace("events/2026"),
+            Err(CoreError::NotFound)
+        ));
+        assert_eq!(acme.list_namespaces().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn short_domains_keep_their_own_slugs() {
+        let (repo, _dir) = tmp_db();
+        let by = UserEmail::new("admin@example.com").unwrap();
+        let domain = ShortDomain {
+            host: "jpro.link".into(),
+            allowed_users: vec![by.clone()],
+            allowed_groups: vec!["grp_mkt".into()],
+            created_at: UNIX_EPOCH,
+            created_by: by.clone(),
+        };
+        repo.put_domain(domain.clone()).unwrap();
+        assert_eq!(repo.get_domain("jpro.link").unwrap(), Some(domain.clone()));
+        let acme = repo.for_tenant(&TenantId::new("acme").unwrap());
+        assert!(acme.list_domains().unwrap().is_empty());
+
+        let plain = Slug::new("docs").unwrap();
+        let jpro = plain.clone().on_domain(Some(domain.host.clone()));
+        for (slug, url) in [
+            (&plain, "https://one.example"),
+            (&jpro, "https://two.example"),
+        ] {
+            repo.put(ShortLink::new(
+                slug.clone(),
+                url.into(),
+                UNIX_EPOCH,
+                by.clone(),
+            ))
+            .unwrap();
+        }
+        let got = repo.get(&jpro).unwrap().unwrap();
+        assert_eq!(got.slug, jpro);
+        assert_eq!(got.original_url, "https://two.example");
+        assert_eq!(
+            repo.get(&plain).unwrap().unwrap().original_url,
+            "https://one.example"
+        );
+
+        repo.delete_domain("jpro.link").unwrap();
+        assert!(matches!(
+            repo.delete_domain("jpro.link"),
+            Err(CoreError::NotFound)
+        ));
+    }
+
+    #[test]
+    fn renamed_links_forward_through_aliases() {
+        let (repo, _dir) = tmp_db();
+        let by = UserEmail::new("user@example.com").unwrap();
+        let slug = |s: &str| Slug::new(s).unwrap();
+        repo.put(ShortLink::new(
+            slug("tpyo"),
+            "https://example.com".into(),
+            UNIX_EPOCH,
+            by.clone(),
+        ))
+        .unwrap();
+        repo.add_alias(&slug("extra"), &slug("tpyo")).unwrap();
+        repo.increment_click(&slug("tpyo")).unwrap();
+
+        let renamed = repo.rename(&slug("tpyo"), &slug("typo")).unwrap();
+        assert_eq!(renamed.slug, slug("typo"));
+        assert_eq!(renamed.click_count, 1);
+        for s in ["tpyo", "extra", "typo"] {
+            assert_eq!(repo.get(&slug(s)).unwrap().unwrap().slug, slug("typo"));
+        }
+        assert_eq!(
+            repo.list_aliases(&slug("typo")).unwrap(),
+            vec![slug("extra"), slug("tpyo")]
+        );
+        assert!(matches!(
+            repo.put(ShortLink::new(
+                slug("extra"),
+                "https://other.example".into(),
+                UNIX_EPOCH,
+                by,
+            )),
+            Err(CoreError::AlreadyExists)
+        ));
+        assert!(matches!(
+            repo.rename(&slug("typo"), &slug("tpyo")),
+            Err(CoreError::AlreadyExists)
+        ));
+
+        // Aliases are per tenant
+        let acme = repo.for_tenant(&TenantId::new("acme").unwrap());
+        assert!(acme.get(&slug("extra")).unwrap().is_none());
+
+        repo.remove_alias(&slug("extra")).unwrap();
+        assert!(repo.get(&slug("extra")).unwrap().is_none());
+    }
 }
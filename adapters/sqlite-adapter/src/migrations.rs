@@ -0,0 +1,505 @@
+//! Versioned schema migrations.
+//!
+//! [`MIGRATIONS`] lists every change to the schema, oldest first; a step's
+//! version is its position in the list, starting at 1. Opening a database
+//! applies the steps it has not seen, each in its own transaction that also
+//! records it in `schema_version`, so a failing step leaves the database at
+//! the previous version. Steps are only ever appended: once released, a step
+//! must not change.
+//!
+//! Databases created before versioning have no `schema_version` table and
+//! start at version 0. The baseline step copes with any shape they may be in
+//! (missing columns, tables not keyed by tenant), so they upgrade like a new
+//! database does.
+
+use std::time::SystemTime;
+
+use domain::CoreError;
+use rusqlite::{params, Connection, OptionalExtension, TransactionBehavior};
+
+use crate::{map_sqerr, system_time_to_secs};
+
+/// One step of the schema history.
+struct Migration {
+    name: &'static str,
+    apply: fn(&Connection) -> Result<(), CoreError>,
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        name: "baseline",
+        apply: baseline,
+    },
+    Migration {
+        name: "full-text search index",
+        apply: search_index,
+    },
+];
+
+/// Schema version this build writes: the number of known migrations.
+pub const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// Name of the migration that brings a database to `version`, if it exists.
+pub fn migration_name(version: u32) -> Option<&'static str> {
+    let index = usize::try_from(version).ok()?.checked_sub(1)?;
+    MIGRATIONS.get(index).map(|m| m.name)
+}
+
+/// Version recorded in `conn`: 0 for a new database or one created before
+/// versioning.
+pub(crate) fn current_version(conn: &Connection) -> Result<u32, CoreError> {
+    let tracked = conn
+        .query_row(
+            "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'schema_version'",
+            [],
+            |_| Ok(()),
+        )
+        .optional()
+        .map_err(map_sqerr)?
+        .is_some();
+    if !tracked {
+        return Ok(0);
+    }
+    conn.query_row(
+        "SELECT COALESCE(MAX(version), 0) FROM schema_version",
+        [],
+        |row| row.get(0),
+    )
+    .map_err(map_sqerr)
+}
+
+/// Apply pending migrations and return the resulting version. Refuses
+/// databases already migrated past [`SCHEMA_VERSION`] by a newer build.
+///
+/// Each step takes the write lock before re-reading the version, so processes
+/// opening the same file at once apply every step exactly once between them.
+pub(crate) fn migrate(conn: &Connection) -> Result<u32, CoreError> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            applied_at INTEGER NOT NULL
+        );",
+    )
+    .map_err(map_sqerr)?;
+    loop {
+        let tx = rusqlite::Transaction::new_unchecked(conn, TransactionBehavior::Immediate)
+            .map_err(map_sqerr)?;
+        let current = current_version(&tx)?;
+        if current > SCHEMA_VERSION {
+            return Err(CoreError::Repository(format!(
+                "database schema version {current} is newer than this build supports ({SCHEMA_VERSION}); upgrade before opening it"
+            )));
+        }
+        let Some(step) = MIGRATIONS.get(current as usize) else {
+            return Ok(current);
+        };
+        let version = current + 1;
+        (step.apply)(&tx).map_err(|e| {
+            CoreError::Repository(format!(
+                "schema migration {version} ({}) failed: {e}",
+                step.name
+            ))
+        })?;
+        tx.execute(
+            "INSERT INTO schema_version(version, name, applied_at) VALUES (?1, ?2, ?3)",
+            params![
+                version,
+                step.name,
+                system_time_to_secs(SystemTime::now()) as i64
+            ],
+        )
+        .map_err(map_sqerr)?;
+        tx.commit().map_err(map_sqerr)?;
+    }
+}
+
+/// Slugs are unique per tenant. Shared by the baseline tables and the rebuild
+/// of pre-tenant ones.
+const SHORTLINKS_TABLE: &str = r#"
+        CREATE TABLE IF NOT EXISTS shortlinks (
+            tenant TEXT NOT NULL DEFAULT 'default',
+            slug TEXT NOT NULL,
+            original_url TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            created_by TEXT NOT NULL,
+            click_count INTEGER NOT NULL DEFAULT 0,
+            is_active INTEGER NOT NULL DEFAULT 1,
+            updated_at INTEGER,
+            expires_at INTEGER,
+            description TEXT,
+            activate_at INTEGER,
+            redirect_delay INTEGER,
+            deleted_at INTEGER,
+            group_id TEXT,
+            visibility TEXT NOT NULL DEFAULT 'public',
+            target_key TEXT,
+            target_host TEXT,
+            PRIMARY KEY (tenant, slug)
+        );
+"#;
+const LINK_GRANTS_TABLE: &str = r#"
+        CREATE TABLE IF NOT EXISTS link_grants (
+            tenant TEXT NOT NULL DEFAULT 'default',
+            slug TEXT NOT NULL,
+            user_email TEXT NOT NULL,
+            role TEXT NOT NULL DEFAULT 'viewer',
+            granted_at INTEGER NOT NULL,
+            granted_by TEXT NOT NULL,
+            PRIMARY KEY (tenant, slug, user_email)
+        );
+"#;
+/// Columns copied out of tables created before tenants existed.
+const SHORTLINKS_COLUMNS: &str = "slug, original_url, created_at, created_by, click_count, is_active, updated_at, expires_at, description, activate_at, redirect_delay, deleted_at, group_id, visibility";
+const LINK_GRANTS_COLUMNS: &str = "slug, user_email, role, granted_at, granted_by";
+
+/// Tables whose rows belong to a tenant and which only need the column added.
+const TENANT_TABLES: [&str; 5] = [
+    "link_groups",
+    "group_members",
+    "group_invitations",
+    "click_events",
+    "audit_log",
+];
+
+/// Columns added to tables after their first release, with their definitions.
+const ADDED_COLUMNS: [(&str, &str, &str); 18] = [
+    ("shortlinks", "click_count", "INTEGER NOT NULL DEFAULT 0"),
+    ("shortlinks", "is_active", "INTEGER NOT NULL DEFAULT 1"),
+    ("shortlinks", "updated_at", "INTEGER"),
+    ("shortlinks", "expires_at", "INTEGER"),
+    ("shortlinks", "description", "TEXT"),
+    ("shortlinks", "activate_at", "INTEGER"),
+    ("shortlinks", "redirect_delay", "INTEGER"),
+    ("shortlinks", "deleted_at", "INTEGER"),
+    ("shortlinks", "group_id", "TEXT"),
+    ("shortlinks", "visibility", "TEXT NOT NULL DEFAULT 'public'"),
+    ("shortlinks", "target_key", "TEXT"),
+    ("shortlinks", "target_host", "TEXT"),
+    ("link_groups", "default_expiry_secs", "INTEGER"),
+    ("link_groups", "default_redirect_delay", "INTEGER"),
+    ("link_groups", "allowed_hosts", "TEXT"),
+    ("link_groups", "slug_prefix", "TEXT"),
+    ("link_groups", "max_links", "INTEGER"),
+    ("link_groups", "parent_id", "TEXT"),
+];
+
+/// Version 1: the schema as it stood when versioning was introduced. New
+/// databases get every table as is; older ones get the columns, tenant keys
+/// and indexes added since they were created.
+fn baseline(conn: &Connection) -> Result<(), CoreError> {
+    conn.execute_batch(SHORTLINKS_TABLE).map_err(map_sqerr)?;
+    conn.execute_batch(LINK_GRANTS_TABLE).map_err(map_sqerr)?;
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS counters (
+            name TEXT PRIMARY KEY,
+            value INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS link_groups (
+            tenant TEXT NOT NULL DEFAULT 'default',
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            description TEXT,
+            created_at INTEGER NOT NULL,
+            created_by TEXT NOT NULL,
+            default_expiry_secs INTEGER,
+            default_redirect_delay INTEGER,
+            allowed_hosts TEXT,
+            slug_prefix TEXT,
+            max_links INTEGER,
+            parent_id TEXT
+        );
+        CREATE TABLE IF NOT EXISTS group_members (
+            tenant TEXT NOT NULL DEFAULT 'default',
+            group_id TEXT NOT NULL,
+            user_email TEXT NOT NULL,
+            role TEXT NOT NULL DEFAULT 'viewer',
+            added_at INTEGER NOT NULL,
+            added_by TEXT NOT NULL,
+            PRIMARY KEY (group_id, user_email)
+        );
+        CREATE TABLE IF NOT EXISTS group_invitations (
+            tenant TEXT NOT NULL DEFAULT 'default',
+            id TEXT PRIMARY KEY,
+            group_id TEXT NOT NULL,
+            email TEXT NOT NULL,
+            role TEXT NOT NULL,
+            invited_by TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            expires_at INTEGER NOT NULL,
+            status TEXT NOT NULL DEFAULT 'pending',
+            responded_at INTEGER
+        );
+        CREATE INDEX IF NOT EXISTS idx_group_invitations_email ON group_invitations(email);
+        CREATE INDEX IF NOT EXISTS idx_group_invitations_group ON group_invitations(group_id);
+        CREATE TABLE IF NOT EXISTS click_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            tenant TEXT NOT NULL DEFAULT 'default',
+            slug TEXT NOT NULL,
+            clicked_at INTEGER NOT NULL,
+            user_agent TEXT,
+            referrer TEXT,
+            country TEXT
+        );
+        CREATE INDEX IF NOT EXISTS idx_click_events_slug ON click_events(slug);
+        CREATE INDEX IF NOT EXISTS idx_click_events_clicked_at ON click_events(clicked_at);
+        CREATE TABLE IF NOT EXISTS audit_log (
+            tenant TEXT NOT NULL DEFAULT 'default',
+            id TEXT PRIMARY KEY,
+            timestamp INTEGER NOT NULL,
+            actor_email TEXT NOT NULL,
+            action TEXT NOT NULL,
+            target_type TEXT NOT NULL,
+            target_id TEXT NOT NULL,
+            changes TEXT
+        );
+        CREATE INDEX IF NOT EXISTS idx_audit_log_target ON audit_log(target_type, target_id);
+        CREATE INDEX IF NOT EXISTS idx_audit_log_actor ON audit_log(actor_email);
+        CREATE TABLE IF NOT EXISTS organizations (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            hosts TEXT,
+            auth_domains TEXT,
+            admins TEXT,
+            shortlink_domain TEXT,
+            allowed_hosts TEXT,
+            created_at INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS short_domains (
+            tenant TEXT NOT NULL,
+            host TEXT NOT NULL,
+            allowed_users TEXT,
+            allowed_groups TEXT,
+            created_at INTEGER NOT NULL,
+            created_by TEXT NOT NULL,
+            PRIMARY KEY (tenant, host)
+        );
+        CREATE TABLE IF NOT EXISTS slug_aliases (
+            tenant TEXT NOT NULL,
+            alias TEXT NOT NULL,
+            slug TEXT NOT NULL,
+            PRIMARY KEY (tenant, alias)
+        );
+        CREATE INDEX IF NOT EXISTS idx_slug_aliases_slug ON slug_aliases(tenant, slug);
+        CREATE TABLE IF NOT EXISTS namespaces (
+            tenant TEXT NOT NULL,
+            path TEXT NOT NULL,
+            group_id TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            created_by TEXT NOT NULL,
+            PRIMARY KEY (tenant, path)
+        );
+        "#,
+    )
+    .map_err(map_sqerr)?;
+    for (table, column, definition) in ADDED_COLUMNS {
+        add_column(conn, table, column, definition)?;
+    }
+    migrate_tenant_columns(conn)?;
+    backfill_target_keys(conn)?;
+    // Indexes backing the "accessible links" listing (created after the column migrations)
+    conn.execute_batch(
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_shortlinks_created_by ON shortlinks(created_by, created_at);
+        CREATE INDEX IF NOT EXISTS idx_shortlinks_group_id ON shortlinks(group_id, created_at);
+        CREATE INDEX IF NOT EXISTS idx_link_groups_parent_id ON link_groups(parent_id);
+        CREATE INDEX IF NOT EXISTS idx_link_grants_user ON link_grants(user_email);
+        CREATE INDEX IF NOT EXISTS idx_link_groups_tenant ON link_groups(tenant);
+        CREATE INDEX IF NOT EXISTS idx_shortlinks_target_key ON shortlinks(tenant, target_key, created_at);
+        CREATE INDEX IF NOT EXISTS idx_shortlinks_target_host ON shortlinks(tenant, target_host, created_at);
+        CREATE INDEX IF NOT EXISTS idx_shortlinks_tenant_created ON shortlinks(tenant, created_at, slug);
+        "#,
+    )
+    .map_err(map_sqerr)
+}
+
+/// Move databases created before organizations existed into the default
+/// tenant. Tables keyed by slug are rebuilt so the key includes the tenant.
+fn migrate_tenant_columns(conn: &Connection) -> Result<(), CoreError> {
+    for (table, ddl, columns) in [
+        ("shortlinks", SHORTLINKS_TABLE, SHORTLINKS_COLUMNS),
+        ("link_grants", LINK_GRANTS_TABLE, LINK_GRANTS_COLUMNS),
+    ] {
+        if has_column(conn, table, "tenant")? {
+            continue;
+        }
+        conn.execute_batch(&format!(
+            "ALTER TABLE {table} RENAME TO {table}_untenanted;
+             {ddl}
+             INSERT INTO {table}({columns}) SELECT {columns} FROM {table}_untenanted;
+             DROP TABLE {table}_untenanted;"
+        ))
+        .map_err(map_sqerr)?;
+    }
+    for table in TENANT_TABLES {
+        add_column(conn, table, "tenant", "TEXT NOT NULL DEFAULT 'default'")?;
+    }
+    Ok(())
+}
+
+/// Fill `target_key` and `target_host` for links stored before they were tracked.
+fn backfill_target_keys(conn: &Connection) -> Result<(), CoreError> {
+    let rows: Vec<(i64, String)> = {
+        let mut stmt = conn
+            .prepare("SELECT rowid, original_url FROM shortlinks WHERE target_key IS NULL OR (target_host IS NULL AND original_url LIKE '%://_%')")
+            .map_err(map_sqerr)?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(map_sqerr)?;
+        rows.collect::<Result<_, _>>().map_err(map_sqerr)?
+    };
+    for (rowid, url) in rows {
+        conn.execute(
+            "UPDATE shortlinks SET target_key = ?1, target_host = ?2 WHERE rowid = ?3",
+            params![
+                domain::validate::canonical_url(&url),
+                domain::validate::url_host(&url),
+                rowid
+            ],
+        )
+        .map_err(map_sqerr)?;
+    }
+    Ok(())
+}
+
+/// Version 2: full-text index over the searchable fields of each link, keyed
+/// by the link's rowid. Triggers keep it in step with every write to
+/// `shortlinks`; click counts and other unsearched columns do not touch it.
+/// Steps that rebuild `shortlinks` must recreate the triggers.
+fn search_index(conn: &Connection) -> Result<(), CoreError> {
+    conn.execute_batch(&format!(
+        "DROP TABLE IF EXISTS shortlinks_fts;
+         CREATE VIRTUAL TABLE shortlinks_fts USING fts5(slug_terms, url_terms, description_terms, creator_terms, prefix = '2 3');
+         INSERT INTO shortlinks_fts(rowid, slug_terms, url_terms, description_terms, creator_terms)
+             SELECT rowid, slug, {url}, description, created_by FROM shortlinks;
+         DROP TRIGGER IF EXISTS shortlinks_fts_insert;
+         DROP TRIGGER IF EXISTS shortlinks_fts_update;
+         DROP TRIGGER IF EXISTS shortlinks_fts_delete;
+         CREATE TRIGGER shortlinks_fts_insert AFTER INSERT ON shortlinks BEGIN
+             INSERT INTO shortlinks_fts(rowid, slug_terms, url_terms, description_terms, creator_terms)
+                 VALUES (new.rowid, new.slug, {new_url}, new.description, new.created_by);
+         END;
+         CREATE TRIGGER shortlinks_fts_update AFTER UPDATE OF slug, original_url, description, created_by ON shortlinks BEGIN
+             UPDATE shortlinks_fts SET slug_terms = new.slug, url_terms = {new_url}, description_terms = new.description, creator_terms = new.created_by
+                 WHERE rowid = new.rowid;
+         END;
+         CREATE TRIGGER shortlinks_fts_delete AFTER DELETE ON shortlinks BEGIN
+             DELETE FROM shortlinks_fts WHERE rowid = old.rowid;
+         END;",
+        url = url_terms(""),
+        new_url = url_terms("new.")
+    ))
+    .map_err(map_sqerr)
+}
+
+/// The words of a target URL worth searching: everything after the scheme.
+/// `row` is `new` or `old` inside a trigger, or empty for a plain column.
+fn url_terms(row: &str) -> String {
+    let url = format!("{row}original_url");
+    format!("CASE WHEN instr({url}, '://') > 0 THEN substr({url}, instr({url}, '://') + 3) ELSE {url} END")
+}
+
+/// Add `column` to `table` unless an earlier release already did.
+fn add_column(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<(), CoreError> {
+    if has_column(conn, table, column)? {
+        return Ok(());
+    }
+    conn.execute_batch(&format!(
+        "ALTER TABLE {table} ADD COLUMN {column} {definition}"
+    ))
+    .map_err(map_sqerr)
+}
+
+fn has_column(conn: &Connection, table: &str, column: &str) -> Result<bool, CoreError> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info(?1) WHERE name = ?2",
+        params![table, column],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|n| n > 0)
+    .map_err(map_sqerr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn versions(conn: &Connection) -> Vec<(u32, String)> {
+        let mut stmt = conn
+            .prepare("SELECT version, name FROM schema_version ORDER BY version")
+            .unwrap();
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap();
+        rows.collect::<Result<_, _>>().unwrap()
+    }
+
+    #[test]
+    fn new_databases_apply_every_step_once() {
+        let conn = Connection::open_in_memory().unwrap();
+        assert_eq!(current_version(&conn).unwrap(), 0);
+        assert_eq!(migrate(&conn).unwrap(), SCHEMA_VERSION);
+        assert_eq!(migrate(&conn).unwrap(), SCHEMA_VERSION);
+        let expected: Vec<(u32, String)> = (1..=SCHEMA_VERSION)
+            .map(|v| (v, migration_name(v).unwrap().to_string()))
+            .collect();
+        assert_eq!(versions(&conn), expected);
+        assert_eq!(migration_name(0), None);
+        assert_eq!(migration_name(SCHEMA_VERSION + 1), None);
+    }
+
+    #[test]
+    fn pending_steps_run_from_the_recorded_version() {
+        let conn = Connection::open_in_memory().unwrap();
+        migrate(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO shortlinks(slug, original_url, created_at, created_by) VALUES ('docs', 'https://docs.example', 1, 'a@acme.com');
+             DROP TABLE shortlinks_fts;
+             DELETE FROM schema_version WHERE version > 1;",
+        )
+        .unwrap();
+        assert_eq!(current_version(&conn).unwrap(), 1);
+        assert_eq!(migrate(&conn).unwrap(), SCHEMA_VERSION);
+        let indexed: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM shortlinks_fts WHERE shortlinks_fts MATCH 'docs'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(indexed, 1);
+    }
+
+    #[test]
+    fn newer_schemas_are_refused() {
+        let conn = Connection::open_in_memory().unwrap();
+        migrate(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO schema_version(version, name, applied_at) VALUES (?1, 'future', 0)",
+            [SCHEMA_VERSION + 1],
+        )
+        .unwrap();
+        let err = migrate(&conn).unwrap_err();
+        assert!(matches!(err, CoreError::Repository(ref m) if m.contains("newer than this build")));
+    }
+
+    #[test]
+    fn failed_steps_leave_the_previous_version() {
+        let conn = Connection::open_in_memory().unwrap();
+        // A view can't take the baseline's columns, so the step fails midway
+        conn.execute_batch("CREATE VIEW shortlinks AS SELECT 1 AS slug;")
+            .unwrap();
+        let err = migrate(&conn).unwrap_err();
+        assert!(
+            matches!(err, CoreError::Repository(ref m) if m.contains("migration 1 (baseline)"))
+        );
+        assert_eq!(current_version(&conn).unwrap(), 0);
+        assert!(!has_column(&conn, "counters", "name").unwrap());
+    }
+}
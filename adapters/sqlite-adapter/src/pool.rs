@@ -0,0 +1,171 @@
+//! Connections shared by every handle on one database file.
+//!
+//! SQLite runs one write transaction at a time, so writes go through a single
+//! connection behind a mutex. In WAL mode readers neither block the writer nor
+//! each other, so reads check out one of several read-only connections and
+//! only wait when all of them are busy. Other processes on the same file are
+//! waited for up to the busy timeout instead of failing with "database is
+//! locked" straight away.
+
+use std::ops::Deref;
+use std::path::Path;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::Duration;
+
+use domain::CoreError;
+use rusqlite::{Connection, OpenFlags};
+
+use crate::map_sqerr;
+
+/// Tuning for [`SqliteRepo::open`](crate::SqliteRepo::open).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SqliteOptions {
+    /// Read-only connections serving reads next to the writer. With 0, and
+    /// always for in-memory databases, reads share the writer's connection.
+    pub readers: usize,
+    /// How long a statement waits on a lock held elsewhere before failing.
+    /// Reads also wait this long for a free reader.
+    pub busy_timeout: Duration,
+}
+
+impl Default for SqliteOptions {
+    fn default() -> Self {
+        Self {
+            readers: 4,
+            busy_timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+impl SqliteOptions {
+    /// Defaults overridden by `SQLITE_READERS` and `SQLITE_BUSY_TIMEOUT_MS`.
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let var = |name: &str| std::env::var(name).ok()?.trim().parse().ok();
+        Self {
+            readers: var("SQLITE_READERS")
+                .and_then(|n| usize::try_from(n).ok())
+                .unwrap_or(defaults.readers),
+            busy_timeout: var("SQLITE_BUSY_TIMEOUT_MS")
+                .map(Duration::from_millis)
+                .unwrap_or(defaults.busy_timeout),
+        }
+    }
+}
+
+pub(crate) struct Pool {
+    writer: Mutex<Connection>,
+    idle_readers: Mutex<Vec<Connection>>,
+    reader_returned: Condvar,
+    readers: usize,
+    busy_timeout: Duration,
+}
+
+/// A connection checked out for reading; goes back to the pool on drop.
+pub(crate) enum Reader<'a> {
+    Pooled {
+        pool: &'a Pool,
+        conn: Option<Connection>,
+    },
+    Shared(MutexGuard<'a, Connection>),
+}
+
+impl Pool {
+    /// Open the writer on `path`, let `prepare` bring the schema up to date
+    /// through it, then open the readers.
+    pub(crate) fn open(
+        path: &Path,
+        options: &SqliteOptions,
+        prepare: impl FnOnce(&Connection) -> Result<(), CoreError>,
+    ) -> Result<Self, CoreError> {
+        let writer = Connection::open(path).map_err(map_sqerr)?;
+        writer
+            .busy_timeout(options.busy_timeout)
+            .map_err(map_sqerr)?;
+        rusqlite::vtab::array::load_module(&writer).map_err(map_sqerr)?;
+        // In-memory databases answer "memory" and stay private to the writer
+        let journal: String = writer
+            .query_row("PRAGMA journal_mode = WAL", [], |row| row.get(0))
+            .map_err(map_sqerr)?;
+        let wal = journal.eq_ignore_ascii_case("wal");
+        if wal {
+            writer
+                .pragma_update(None, "synchronous", "NORMAL")
+                .map_err(map_sqerr)?;
+        }
+        prepare(&writer)?;
+
+        let readers = if wal { options.readers } else { 0 };
+        let idle = (0..readers)
+            .map(|_| {
+                let conn = Connection::open_with_flags(
+                    path,
+                    OpenFlags::SQLITE_OPEN_READ_ONLY
+                        | OpenFlags::SQLITE_OPEN_NO_MUTEX
+                        | OpenFlags::SQLITE_OPEN_URI,
+                )
+                .map_err(map_sqerr)?;
+                conn.busy_timeout(options.busy_timeout).map_err(map_sqerr)?;
+                rusqlite::vtab::array::load_module(&conn).map_err(map_sqerr)?;
+                Ok(conn)
+            })
+            .collect::<Result<Vec<_>, CoreError>>()?;
+        Ok(Self {
+            writer: Mutex::new(writer),
+            idle_readers: Mutex::new(idle),
+            reader_returned: Condvar::new(),
+            readers,
+            busy_timeout: options.busy_timeout,
+        })
+    }
+
+    /// The connection all writes go through.
+    pub(crate) fn writer(&self) -> Result<MutexGuard<'_, Connection>, CoreError> {
+        self.writer.lock().map_err(|_| poisoned())
+    }
+
+    /// A connection for reads, which sees every write committed before it.
+    pub(crate) fn reader(&self) -> Result<Reader<'_>, CoreError> {
+        if self.readers == 0 {
+            return self.writer().map(Reader::Shared);
+        }
+        let idle = self.idle_readers.lock().map_err(|_| poisoned())?;
+        let (mut idle, _) = self
+            .reader_returned
+            .wait_timeout_while(idle, self.busy_timeout, |idle| idle.is_empty())
+            .map_err(|_| poisoned())?;
+        let conn = idle
+            .pop()
+            .ok_or_else(|| CoreError::Repository("timed out waiting for a sqlite reader".into()))?;
+        Ok(Reader::Pooled {
+            pool: self,
+            conn: Some(conn),
+        })
+    }
+}
+
+impl Deref for Reader<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        match self {
+            Reader::Pooled { conn, .. } => conn.as_ref().expect("reader returned twice"),
+            Reader::Shared(conn) => conn,
+        }
+    }
+}
+
+impl Drop for Reader<'_> {
+    fn drop(&mut self) {
+        if let Reader::Pooled { pool, conn } = self {
+            if let (Some(conn), Ok(mut idle)) = (conn.take(), pool.idle_readers.lock()) {
+                idle.push(conn);
+                pool.reader_returned.notify_one();
+            }
+        }
+    }
+}
+
+fn poisoned() -> CoreError {
+    CoreError::Repository("mutex poisoned".into())
+}
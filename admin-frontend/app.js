@@ -57,6 +57,8 @@ let currentPage = 0;
 let pageSize = 50;
 let totalLinks = 0;
 let hasMore = false;
+// Cursor to request each page with (from the previous page's next_cursor)
+let pageCursors = [null];
 let searchDebounce = null;
 
 // Helper to format expiry date for display
@@ -87,6 +89,21 @@ function isoToLocal(isoString) {
   return local.toISOString().slice(0, 16);
 }
 
+// Links on an extra short domain are addressed as "host:slug" in the admin API
+// Search snippets are plain text with matches wrapped in <mark>; escape the
+// text and keep only those tags
+function snippetHtml(snippet) {
+  const escaped = snippet
+    .replace(/&/g, '&amp;')
+    .replace(/</g, '&lt;')
+    .replace(/>/g, '&gt;');
+  return escaped.replace(/&lt;(\/?)mark&gt;/g, '<$1mark>');
+}
+
+function linkKey(link) {
+  return link.domain ? `${link.domain}:${link.slug}` : link.slug;
+}
+
 async function loadLinks() {
   const out = document.querySelector('#listTbl tbody');
   out.innerHTML = '';
@@ -95,10 +112,17 @@ async function loadLinks() {
   const filterByGroup = document.getElementById('filterByGroup').value;
   const search = document.getElementById('searchInput').value.trim();
 
-  let url = `/api/links?limit=${pageSize}&offset=${currentPage * pageSize}`;
+  const cursor = pageCursors[currentPage];
+  let url = cursor
+    ? `/api/links?limit=${pageSize}&cursor=${encodeURIComponent(cursor)}`
+    : `/api/links?limit=${pageSize}&offset=${currentPage * pageSize}`;
   if (filterBy) url += `&created_by=${encodeURIComponent(filterBy)}`;
   if (filterByGroup) url += `&group_id=${encodeURIComponent(filterByGroup)}`;
   if (search) url += `&search=${encodeURIComponent(search)}`;
+  const status = document.getElementById('filterByStatus').value;
+  if (status) url += `&status=${status}`;
+  const [sort, order] = document.getElementById('sortBy').value.split(':');
+  if (sort) url += `&sort=${sort}&order=${order}`;
 
   const r = await api(url);
   if (!r.ok) {
@@ -108,8 +132,11 @@ async function loadLinks() {
 
   const links = r.body?.links || [];
   allLinks = links;
-  totalLinks = r.body?.total || links.length;
+  // Later cursor pages may leave the total out; keep the first page's
+  if (r.body?.total !== undefined) totalLinks = r.body.total;
+  else if (!cursor) totalLinks = links.length;
   hasMore = r.body?.has_more || false;
+  pageCursors[currentPage + 1] = r.body?.next_cursor || null;
 
   // Get user info from response
   if (r.body?.user) {
@@ -152,8 +179,8 @@ async function loadLinks() {
       const descTitle = l.description ? ` title="${l.description.replace(/"/g, '&quot;')}"` : '';
       const groupName = l.group_id ? (allGroups.find(g => g.id === l.group_id)?.name || l.group_id) : '-';
       tr.innerHTML = `
-        <td><input type="checkbox" class="link-select" data-slug="${l.slug}" /></td>
-        <td${descTitle}>${l.slug}${l.description ? ' *' : ''}</td>
+        <td><input type="checkbox" class="link-select" data-slug="${linkKey(l)}" /></td>
+        <td${descTitle}>${l.slug}${l.description ? ' *' : ''}${l.snippet ? `<div class="muted">${snippetHtml(l.snippet)}</div>` : ''}</td>
         <td><a href="${l.short_url}" target="_blank" rel="noreferrer">${l.short_url}</a> <button class="copy-btn" onclick="copyToClipboard('${l.short_url}', this)" title="Copy">📋</button></td>
         <td style="max-width:300px;overflow:hidden;text-overflow:ellipsis;white-space:nowrap;" title="${l.original_url}">${l.original_url}</td>
         <td>${l.click_count}</td>
@@ -163,14 +190,15 @@ async function loadLinks() {
         <td>${new Date(l.created_at).toLocaleDateString()}</td>
         <td>${l.created_by}</td>
         <td>
-          <button onclick="openEditModal('${l.slug}')">Edit</button>
-          <button onclick="toggleLink('${l.slug}', ${l.is_active})">${l.is_active ? 'Off' : 'On'}</button>
+          <button onclick="openEditModal('${linkKey(l)}')">Edit</button>
+          <button onclick="renameLink('${linkKey(l)}')">Rename</button>
+          <button onclick="toggleLink('${linkKey(l)}', ${l.is_active})">${l.is_active ? 'Off' : 'On'}</button>
           <button onclick="showQrCode('${l.slug}', '${l.short_url}')">QR</button>
         </td>
       `;
     } else {
       tr.innerHTML = `
-        <td><input type="checkbox" class="link-select" data-slug="${l.slug}" /></td>
+        <td><input type="checkbox" class="link-select" data-slug="${linkKey(l)}" /></td>
         <td>${l.slug}</td>
         <td><a href="${l.short_url}" target="_blank" rel="noreferrer">${l.short_url}</a> <button class="copy-btn" onclick="copyToClipboard('${l.short_url}', this)" title="Copy">📋</button></td>
         <td>${l.original_url}</td>
@@ -255,7 +283,7 @@ function updateSelectedCount() {
 }
 
 function openEditModal(slug) {
-  const link = allLinks.find(l => l.slug === slug);
+  const link = allLinks.find(l => linkKey(l) === slug);
   if (!link) return;
 
   document.getElementById('editSlug').textContent = slug;
@@ -296,7 +324,7 @@ async function saveEdit() {
   payload.redirect_delay = redirectDelayValue ? parseInt(redirectDelayValue, 10) : null;
   payload.group_id = groupValue || null;
 
-  const r = await api(`/api/links/${slug}`, {
+  const r = await api(`/api/links/${encodeURIComponent(slug)}`, {
     method: 'PATCH',
     body: JSON.stringify(payload)
   });
@@ -312,7 +340,7 @@ async function saveEdit() {
 async function deleteLink(slug) {
   if (!confirm(`Delete link "${slug}"? This cannot be undone.`)) return;
 
-  const r = await api(`/api/links/${slug}`, { method: 'DELETE' });
+  const r = await api(`/api/links/${encodeURIComponent(slug)}`, { method: 'DELETE' });
 
   if (r.ok || r.status === 204) {
     closeEditModal();
@@ -323,7 +351,7 @@ async function deleteLink(slug) {
 }
 
 async function toggleLink(slug, currentActive) {
-  const r = await api(`/api/links/${slug}`, {
+  const r = await api(`/api/links/${encodeURIComponent(slug)}`, {
     method: 'PATCH',
     body: JSON.stringify({ is_active: !currentActive })
   });
@@ -335,6 +363,24 @@ async function toggleLink(slug, currentActive) {
   }
 }
 
+// The old slug keeps forwarding to the link after a rename
+async function renameLink(slug) {
+  const link = allLinks.find(l => linkKey(l) === slug);
+  const newSlug = prompt('New slug (the old one keeps working):', link ? link.slug : '');
+  if (!newSlug || (link && newSlug === link.slug)) return;
+
+  const r = await api(`/api/links/${encodeURIComponent(slug)}/rename`, {
+    method: 'POST',
+    body: JSON.stringify({ slug: newSlug })
+  });
+
+  if (r.ok) {
+    await loadLinks();
+  } else {
+    alert(`Error ${r.status}: ${(r.body?.error?.message) || 'failed'}`);
+  }
+}
+
 // Bulk operations
 async function bulkDelete() {
   const slugs = getSelectedSlugs();
@@ -486,18 +532,20 @@ let currentGroupId = null;
 
 // Populate all group dropdowns with current groups
 function populateGroupDropdowns() {
-  const dropdowns = ['createGroup', 'editGroup', 'filterByGroup'];
+  const dropdowns = ['createGroup', 'editGroup', 'filterByGroup', 'groupParent'];
   for (const id of dropdowns) {
     const el = document.getElementById(id);
     if (!el) continue;
     const currentValue = el.value;
     el.innerHTML = id === 'filterByGroup'
       ? '<option value="">All groups</option>'
-      : '<option value="">No group</option>';
+      : id === 'groupParent'
+        ? '<option value="">None (top level)</option>'
+        : '<option value="">No group</option>';
     for (const g of allGroups) {
       const opt = document.createElement('option');
       opt.value = g.id;
-      opt.textContent = g.name;
+      opt.textContent = '\u00a0\u00a0'.repeat(g.depth || 0) + g.name;
       el.appendChild(opt);
     }
     el.value = currentValue;
@@ -530,7 +578,7 @@ async function loadGroups() {
       : '';
     const canManage = g.role === 'admin' || currentUser?.is_admin;
     tr.innerHTML = `
-      <td><strong>${g.name}</strong></td>
+      <td style="padding-left:${0.5 + 1.5 * (g.depth || 0)}rem;">${g.depth ? '└ ' : ''}<strong>${g.name}</strong></td>
       <td class="muted">${g.description || '-'}</td>
       <td>${roleBadge}</td>
       <td>${new Date(g.created_at).toLocaleDateString()}</td>
@@ -548,6 +596,7 @@ function openGroupCreateModal() {
   document.getElementById('groupModalTitle').textContent = 'Create Group';
   document.getElementById('groupName').value = '';
   document.getElementById('groupDesc').value = '';
+  document.getElementById('groupParent').value = '';
   document.getElementById('groupModal').dataset.mode = 'create';
   document.getElementById('groupModal').dataset.groupId = '';
   document.getElementById('groupModal').style.display = 'block';
@@ -560,6 +609,7 @@ function openGroupEditModal(groupId) {
   document.getElementById('groupModalTitle').textContent = 'Edit Group';
   document.getElementById('groupName').value = group.name;
   document.getElementById('groupDesc').value = group.description || '';
+  document.getElementById('groupParent').value = group.parent_id || '';
   document.getElementById('groupModal').dataset.mode = 'edit';
   document.getElementById('groupModal').dataset.groupId = groupId;
   document.getElementById('groupModal').style.display = 'block';
@@ -575,12 +625,19 @@ async function saveGroup() {
   const groupId = modal.dataset.groupId;
   const name = document.getElementById('groupName').value.trim();
   const description = document.getElementById('groupDesc').value.trim();
+  const parentId = document.getElementById('groupParent').value;
 
   if (!name) { alert('Name is required'); return; }
 
   const payload = { name };
   if (description) payload.description = description;
   else payload.description = null;
+  // An empty parent moves the group back to the top level
+  if (mode === 'create') {
+    if (parentId) payload.parent_id = parentId;
+  } else {
+    payload.parent_id = parentId;
+  }
 
   let r;
   if (mode === 'create') {
@@ -598,9 +655,21 @@ async function saveGroup() {
 }
 
 async function deleteGroup(groupId) {
-  if (!confirm('Delete this group? Members will lose access to grouped links.')) return;
-
-  const r = await api(`/api/groups/${groupId}`, { method: 'DELETE' });
+  const choice = prompt(
+    'Delete this group? Members will lose access.\n\n' +
+    'What should happen to its links? Type one of:\n' +
+    '  detach - keep them, owned by their creators\n' +
+    '  soft_delete - delete them (restorable)\n' +
+    '  <group id> - move them to another group',
+    'detach'
+  );
+  if (!choice) return;
+
+  const trimmed = choice.trim();
+  const query = (trimmed === 'detach' || trimmed === 'soft_delete')
+    ? `links=${trimmed}`
+    : `links=reassign&target_group_id=${encodeURIComponent(trimmed)}`;
+  const r = await api(`/api/groups/${groupId}?${query}`, { method: 'DELETE' });
 
   if (r.ok || r.status === 204) {
     await loadGroups();
@@ -680,6 +749,7 @@ async function addMember() {
 
   if (r.ok) {
     document.getElementById('addMemberEmail').value = '';
+    alert(`Invitation sent to ${email}. They will join once they accept.`);
     await loadMembers();
   } else {
     alert(`Error ${r.status}: ${(r.body?.error?.message) || 'failed'}`);
@@ -711,16 +781,21 @@ async function createLink() {
   const alias = document.getElementById('alias').value.trim();
   const description = document.getElementById('createDesc').value.trim();
   const group_id = document.getElementById('createGroup').value;
+  const slug_style = document.getElementById('slugStyle').value;
 
   const payload = { original_url };
   if (alias) payload.alias = alias;
+  else if (slug_style !== 'base62') payload.slug_style = slug_style;
+  if (!alias && document.getElementById('dedupe').checked) payload.dedupe = true;
   if (description) payload.description = description;
   if (group_id) payload.group_id = group_id;
 
   const r = await api('/api/links', { method: 'POST', body: JSON.stringify(payload) });
   const out = document.getElementById('createOut');
   if (r.ok) {
-    out.textContent = `Created ${r.body.short_url}`;
+    out.textContent = r.status === 200
+      ? `Reused existing ${r.body.short_url}`
+      : `Created ${r.body.short_url}`;
     document.getElementById('orig').value = '';
     document.getElementById('alias').value = '';
     document.getElementById('createDesc').value = '';
@@ -731,6 +806,37 @@ async function createLink() {
   }
 }
 
+// Tell whether the typed alias is free before the link is created
+async function checkAlias() {
+  const alias = document.getElementById('alias').value.trim();
+  const hint = document.getElementById('aliasHint');
+  if (!alias) { hint.textContent = ''; return; }
+  const params = new URLSearchParams({ alias });
+  const group_id = document.getElementById('createGroup').value;
+  if (group_id) params.set('group_id', group_id);
+  const r = await api(`/api/slugs/check?${params}`);
+  if (!r.ok) { hint.textContent = ''; return; }
+  if (r.body.available) hint.textContent = `"${alias}" is available`;
+  else if (r.body.taken) hint.textContent = `"${alias}" is already taken`;
+  else hint.textContent = `"${alias}": ${r.body.violations.join(', ')}`;
+}
+
+// Offer slugs derived from the target URL while no alias is typed
+async function suggestAliases() {
+  const url = document.getElementById('orig').value.trim();
+  const hint = document.getElementById('aliasHint');
+  if (!url || document.getElementById('alias').value.trim()) return;
+  const r = await api(`/api/slugs/suggest?${new URLSearchParams({ url })}`);
+  if (!r.ok || !r.body.suggestions.length) { hint.textContent = ''; return; }
+  hint.textContent = 'Suggestions: ';
+  for (const s of r.body.suggestions) {
+    const btn = document.createElement('button');
+    btn.textContent = s.slug;
+    btn.onclick = () => { document.getElementById('alias').value = s.slug; checkAlias(); };
+    hint.appendChild(btn);
+  }
+}
+
 function prevPage() {
   if (currentPage > 0) {
     currentPage--;
@@ -755,9 +861,13 @@ function onSearchInput() {
 
 // Event handlers
 document.getElementById('createBtn').onclick = createLink;
+document.getElementById('alias').onblur = checkAlias;
+document.getElementById('orig').onblur = suggestAliases;
 document.getElementById('refresh').onclick = loadLinks;
 document.getElementById('filterBy').onchange = () => { currentPage = 0; loadLinks(); };
 document.getElementById('filterByGroup').onchange = () => { currentPage = 0; loadLinks(); };
+document.getElementById('filterByStatus').onchange = () => { currentPage = 0; loadLinks(); };
+document.getElementById('sortBy').onchange = () => { currentPage = 0; loadLinks(); };
 document.getElementById('editCancel').onclick = closeEditModal;
 document.getElementById('editSave').onclick = saveEdit;
 document.getElementById('editDelete').onclick = () => deleteLink(document.getElementById('editModal').dataset.slug);
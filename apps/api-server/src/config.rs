@@ -4,6 +4,7 @@
 //! on misconfiguration rather than at request time.
 
 use axum::http::HeaderValue;
+use domain::slug::WordSlugGenerator;
 use std::env;
 use std::fmt;
 use std::path::PathBuf;
@@ -34,12 +35,20 @@ pub enum StorageProvider {
     Memory,
     /// SQLite file-based storage
     Sqlite,
+    /// PostgreSQL server (requires the `postgres` feature)
+    Postgres,
+    /// AWS DynamoDB or DynamoDB Local (requires the `dynamo` feature)
+    Dynamo,
 }
 
 impl StorageProvider {
     fn from_str(s: &str) -> Self {
         if s.eq_ignore_ascii_case("sqlite") {
             Self::Sqlite
+        } else if s.eq_ignore_ascii_case("postgres") || s.eq_ignore_ascii_case("postgresql") {
+            Self::Postgres
+        } else if s.eq_ignore_ascii_case("dynamo") || s.eq_ignore_ascii_case("dynamodb") {
+            Self::Dynamo
         } else {
             Self::Memory
         }
@@ -102,14 +111,30 @@ pub struct Config {
     /// Storage provider
     pub storage_provider: StorageProvider,
     /// SQLite database path (when using sqlite storage)
-    #[allow(dead_code)] // For future use with SQLite adapter config
+    #[cfg_attr(not(feature = "sqlite"), allow(dead_code))]
     pub db_path: Option<PathBuf>,
+    /// PostgreSQL connection URL (required for postgres storage)
+    #[cfg_attr(not(feature = "postgres"), allow(dead_code))]
+    pub database_url: Option<String>,
     /// Log format
     pub log_format: LogFormat,
     /// Custom shortlink domain for generated URLs
     pub shortlink_domain: Option<String>,
+    /// Secret for signing visitor session cookies (workspace-only links)
+    pub session_secret: Option<String>,
+    /// Issue visitor sessions for any typed email in debug auth mode (DEBUG_SESSIONS)
+    pub debug_sessions: bool,
+    /// Lifetime of a visitor session cookie in seconds (default: 12 hours)
+    pub session_ttl_secs: u64,
+    /// How long organization lookups are cached in seconds (default: 30)
+    pub org_cache_ttl_secs: u64,
+    /// Generator for `slug_style: "words"` (default: full dictionary, 2 words, '-')
+    pub word_slugger: WordSlugGenerator,
 }
 
+/// Session secret used with DEBUG_SESSIONS when SESSION_SECRET is not set.
+const DEV_SESSION_SECRET: &str = "insecure-dev-session-secret";
+
 impl Config {
     /// Load and validate configuration from environment variables.
     ///
@@ -171,6 +196,15 @@ impl Config {
         // DB path (for sqlite)
         let db_path = env::var("DB_PATH").ok().map(PathBuf::from);
 
+        // Database URL (for postgres)
+        let database_url = env::var("DATABASE_URL").ok().filter(|s| !s.is_empty());
+        if storage_provider == StorageProvider::Postgres && database_url.is_none() {
+            return Err(ConfigError {
+                field: "DATABASE_URL",
+                message: "Required when STORAGE_PROVIDER=postgres".into(),
+            });
+        }
+
         // Log format
         let log_format =
             LogFormat::from_str(&env::var("LOG_FORMAT").unwrap_or_else(|_| "pretty".into()));
@@ -178,6 +212,31 @@ impl Config {
         // Shortlink domain
         let shortlink_domain = env::var("SHORTLINK_DOMAIN").ok().filter(|s| !s.is_empty());
 
+        // Session secret: debug auth mode only signs sessions when DEBUG_SESSIONS opts in
+        let debug_sessions = matches!(
+            env::var("DEBUG_SESSIONS")
+                .unwrap_or_default()
+                .to_lowercase()
+                .as_str(),
+            "1" | "true" | "yes"
+        );
+        let session_secret = session_secret(
+            &auth_provider,
+            env::var("SESSION_SECRET").ok().filter(|s| !s.is_empty()),
+            debug_sessions,
+        );
+        let session_ttl_secs = env::var("SESSION_TTL_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(12 * 60 * 60);
+        let org_cache_ttl_secs = env::var("ORG_CACHE_TTL_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(30);
+
+        // Word slug generator
+        let word_slugger = word_slugger_from_env()?;
+
         Ok(Self {
             port,
             auth_provider,
@@ -187,8 +246,14 @@ impl Config {
             cors_allow_origin,
             storage_provider,
             db_path,
+            database_url,
             log_format,
             shortlink_domain,
+            session_secret,
+            debug_sessions,
+            session_ttl_secs,
+            org_cache_ttl_secs,
+            word_slugger,
         })
     }
 
@@ -205,6 +270,26 @@ impl Config {
                      Set ALLOWED_DOMAIN for domain restriction."
                 );
             }
+            if self.debug_sessions {
+                tracing::warn!(
+                    "DEBUG_SESSIONS is set: Visitor sessions are issued for any email typed \
+                     into the sign-in page. DO NOT USE IN PRODUCTION."
+                );
+            }
+        }
+        match self.session_secret.as_deref() {
+            Some(DEV_SESSION_SECRET) => tracing::warn!(
+                "SESSION_SECRET not set: Visitor sessions are signed with a built-in dev secret. \
+                 DO NOT USE IN PRODUCTION."
+            ),
+            None if self.auth_provider == AuthProvider::None => tracing::warn!(
+                "Visitor sessions are disabled in debug auth mode: Workspace-only links cannot \
+                 be followed. Set DEBUG_SESSIONS=true to sign in with a typed email."
+            ),
+            None => {
+                tracing::warn!("SESSION_SECRET not set: Workspace-only links cannot be followed.")
+            }
+            Some(_) => {}
         }
         if self.insecure_skip_signature {
             tracing::warn!(
@@ -215,6 +300,55 @@ impl Config {
     }
 }
 
+/// Resolve the visitor session secret. Debug auth mode would accept any typed
+/// email as a sign-in, so it only gets a secret (falling back to a fixed dev
+/// value) when DEBUG_SESSIONS opts in.
+fn session_secret(
+    auth_provider: &AuthProvider,
+    configured: Option<String>,
+    debug_sessions: bool,
+) -> Option<String> {
+    match auth_provider {
+        AuthProvider::None if !debug_sessions => None,
+        AuthProvider::None => configured.or_else(|| Some(DEV_SESSION_SECRET.to_string())),
+        AuthProvider::Google => configured,
+    }
+}
+
+/// Build the word slug generator from SLUG_WORD_DICTIONARY_SIZE,
+/// SLUG_WORD_COUNT and SLUG_WORD_SEPARATOR; unset values keep the defaults.
+fn word_slugger_from_env() -> Result<WordSlugGenerator, ConfigError> {
+    let default = WordSlugGenerator::default();
+    let number = |field: &'static str, fallback: usize| match env::var(field) {
+        Ok(s) => s.trim().parse::<usize>().map_err(|_| ConfigError {
+            field,
+            message: format!("Expected a number, got '{}'", s),
+        }),
+        Err(_) => Ok(fallback),
+    };
+    let dictionary_size = number("SLUG_WORD_DICTIONARY_SIZE", default.dictionary_size())?;
+    let word_count = number("SLUG_WORD_COUNT", default.word_count())?;
+    let separator = match env::var("SLUG_WORD_SEPARATOR") {
+        Ok(s) => {
+            let mut chars = s.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => c,
+                _ => {
+                    return Err(ConfigError {
+                        field: "SLUG_WORD_SEPARATOR",
+                        message: format!("Expected a single character, got '{}'", s),
+                    })
+                }
+            }
+        }
+        Err(_) => default.separator(),
+    };
+    WordSlugGenerator::new(dictionary_size, word_count, separator).map_err(|e| ConfigError {
+        field: "SLUG_WORD_*",
+        message: e.to_string(),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -233,12 +367,46 @@ mod tests {
         assert_eq!(StorageProvider::from_str("memory"), StorageProvider::Memory);
         assert_eq!(StorageProvider::from_str("sqlite"), StorageProvider::Sqlite);
         assert_eq!(StorageProvider::from_str("SQLITE"), StorageProvider::Sqlite);
+        assert_eq!(
+            StorageProvider::from_str("postgres"),
+            StorageProvider::Postgres
+        );
+        assert_eq!(
+            StorageProvider::from_str("PostgreSQL"),
+            StorageProvider::Postgres
+        );
+        assert_eq!(StorageProvider::from_str("dynamo"), StorageProvider::Dynamo);
+        assert_eq!(
+            StorageProvider::from_str("DynamoDB"),
+            StorageProvider::Dynamo
+        );
         assert_eq!(
             StorageProvider::from_str("anything"),
             StorageProvider::Memory
         );
     }
 
+    #[test]
+    fn debug_auth_sessions_are_opt_in() {
+        let none = AuthProvider::None;
+        assert_eq!(session_secret(&none, None, false), None);
+        assert_eq!(session_secret(&none, Some("s".into()), false), None);
+        assert_eq!(
+            session_secret(&none, None, true).as_deref(),
+            Some(DEV_SESSION_SECRET)
+        );
+        assert_eq!(
+            session_secret(&none, Some("s".into()), true).as_deref(),
+            Some("s")
+        );
+        let google = AuthProvider::Google;
+        assert_eq!(session_secret(&google, None, true), None);
+        assert_eq!(
+            session_secret(&google, Some("s".into()), false).as_deref(),
+            Some("s")
+        );
+    }
+
     #[test]
     fn log_format_parsing() {
         assert_eq!(LogFormat::from_str("pretty"), LogFormat::Pretty);
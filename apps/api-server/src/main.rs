@@ -4,6 +4,39 @@
 //! - Auth: Google ID token verification or disabled (debug) mode via X-Debug-User.
 //! - Storage: In-memory (default) or SQLite (file) when the `sqlite` feature is enabled.
 //! - CORS: Configurable via CORS_ALLOW_ORIGIN (origin string) for admin frontend.
+//! - Workspace-only links: visitors sign in once via `POST /auth/session` and are
+//!   remembered with a signed `sl_session` cookie (SESSION_SECRET). In debug
+//!   auth mode the typed email is trusted, so sessions need DEBUG_SESSIONS=true.
+//! - Group settings: `PATCH /api/groups/:id` sets per-group link defaults and
+//!   limits, enforced when links are created in or moved into the group.
+//! - Organizations: each request is scoped to the organization serving its Host,
+//!   or else the one whose sign-in domain matches the caller. Links and groups are
+//!   only visible within that organization; unknown callers use the default one.
+//! - Short domains: `GET /api/domains` and `PUT|DELETE /api/domains/:host` manage
+//!   extra hosts links can be created on (`domain` on create). Redirects resolve
+//!   by (Host, slug); admin paths address such links as `host:slug`.
+//! - Aliases: `POST /api/links/:slug/rename` moves a link to a new slug and keeps
+//!   the old one forwarding; `/api/links/:slug/aliases` lists, adds and removes
+//!   extra slugs sharing the link's target, settings and clicks.
+//! - Namespaces: slugs may contain `/` (`hr/onboarding`). `GET /api/namespaces`
+//!   and `PUT|DELETE /api/namespaces/*path` let group admins reserve a prefix so
+//!   only the group's editors create slugs under it. Admin paths take such slugs
+//!   percent-encoded (`hr%2Fonboarding`).
+//! - Slug helpers: `GET /api/slugs/check?alias=` reports whether an alias is free
+//!   and which rules it breaks; `GET /api/slugs/suggest?url=` proposes readable
+//!   slugs for a target that are not taken yet.
+//! - Reverse lookup: `GET /api/links/by-target?url=&match=exact|prefix|host`
+//!   lists the visible links pointing at a destination;
+//!   `POST /api/links/by-target/repoint` moves the editable ones to a new one.
+//! - `GET /api/links?scope=accessible` lists every link the caller can access
+//!   (own, group, shared), each with the caller's `role`.
+//! - Paging: `GET /api/links` returns `next_cursor`; pass it back as `cursor`
+//!   for the next page. `offset` is only honoured by the in-memory store.
+//!   `sort=created_at|updated_at|click_count|expires_at` with `order=asc|desc`,
+//!   `status=active|inactive|expired|scheduled|deleted`, `created_from`/`_to`
+//!   (likewise `updated_`, `expires_`; RFC 3339) and `host` narrow the list.
+//!   A `search` without `sort` is ranked by relevance (full-text with SQLite)
+//!   and pages by `offset`; each link then carries a highlighted `snippet`.
 //!
 //! Contract follows docs/spec_admin_api.md for create/list fields and behavior where practical.
 //!
@@ -12,9 +45,12 @@
 //! # pretty logs (default); PORT optional
 //! cargo run -p api-server
 //!
-//! # with Dynamo adapter enabled (requires env vars)
+//! # with Dynamo adapter enabled (requires env vars); DYNAMO_ENDPOINT_URL
+//! # points it at DynamoDB Local instead of AWS
+//! STORAGE_PROVIDER=dynamo \
 //! DYNAMO_TABLE_SHORTLINKS=shortlinks \
 //! DYNAMO_TABLE_COUNTERS=counters \
+//! DYNAMO_ENDPOINT_URL=http://localhost:8000 \
 //!   cargo run -p api-server --features dynamo
 //! ```
 //!
@@ -28,16 +64,26 @@ use std::sync::{Arc, Mutex};
 
 use axum::http::HeaderValue;
 use axum::{
-    extract::{Path, Query, State},
-    http::{HeaderMap, StatusCode},
-    response::{IntoResponse, Redirect},
+    async_trait,
+    extract::{FromRequestParts, Path, Query},
+    http::{request::Parts, HeaderMap, StatusCode},
+    response::{Html, IntoResponse, Redirect, Response},
     routing::{get, post},
     Json, Router,
 };
-use domain::adapters::memory_repo::InMemoryRepo;
-use domain::slug::Base62SlugGenerator;
+use domain::adapters::memory_repo::{
+    InMemoryDomainRepo, InMemoryGroupRepo, InMemoryLinkGrantRepo, InMemoryNamespaceRepo,
+    InMemoryOrganizationRepo, InMemoryRepo,
+};
+use domain::slug::{Base62SlugGenerator, SlugStyle, WordSlugGenerator};
+use domain::tenant::OrganizationCache;
 use domain::SlugGenerator;
-use domain::{Clock, CoreError, LinkRepository, Slug, UserEmail};
+use domain::{
+    tenant, AccessScope, Clock, CoreError, DomainRepository, GroupMember, GroupRepository,
+    GroupRole, GroupSettings, LinkGrant, LinkGrantRepository, LinkGroup, LinkRepository,
+    LinkVisibility, Namespace, NamespaceRepository, Organization, OrganizationRepository,
+    ShortDomain, Slug, TargetMatch, TenantId, TenantScoped, UserEmail,
+};
 use google_auth::{AuthError as GAuthError, VerifiedUser};
 use serde::{Deserialize, Serialize};
 use tower_http::{
@@ -48,41 +94,271 @@ use tower_http::{
 use tracing::{error, info, warn};
 use tracing_subscriber::{fmt, prelude::*, EnvFilter};
 
+/// Run blocking storage work from an async handler. On the multi-threaded
+/// runtime the worker hands its queued tasks to another thread while `f`
+/// waits on the database, so other requests keep being served; elsewhere (the
+/// single-threaded test runtime) `f` simply runs. The Postgres client drives
+/// its own runtime, so it must only be called from the multi-threaded one.
+#[cfg(any(feature = "sqlite", feature = "postgres"))]
+fn blocking<T>(f: impl FnOnce() -> T) -> T {
+    use tokio::runtime::{Handle, RuntimeFlavor};
+    match Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(f)
+        }
+        _ => f(),
+    }
+}
+
 // Local repo abstraction supporting memory or sqlite (feature-gated).
 enum RepoKind {
     Memory(InMemoryRepo),
     #[cfg(feature = "sqlite")]
     Sqlite(sqlite_adapter::SqliteRepo),
+    #[cfg(feature = "postgres")]
+    Postgres(postgres_adapter::PostgresRepo),
+    // Blocks in place on its own while it waits for the SDK
+    #[cfg(feature = "dynamo")]
+    Dynamo(Box<aws_dynamo::DynamoRepo>),
 }
 
 #[derive(Clone)]
 struct AnyRepo {
     kind: Arc<RepoKind>,
-    counter: Arc<Mutex<u64>>, // used when Memory; ignored when Sqlite which has its own counter
+    counter: Arc<Mutex<u64>>, // used when Memory; ignored by databases, which have its own counter
+    grants: Arc<InMemoryLinkGrantRepo>, // used when Memory; databases store grants itself
+    groups: Arc<InMemoryGroupRepo>, // used when Memory; databases store groups itself
+    orgs: Arc<InMemoryOrganizationRepo>, // used when Memory; databases store organizations itself
+    domains: Arc<InMemoryDomainRepo>, // used when Memory; databases store domains itself
+    namespaces: Arc<InMemoryNamespaceRepo>, // used when Memory; databases store namespaces itself
 }
 
 #[allow(dead_code)]
 impl AnyRepo {
     fn memory() -> Self {
+        let links = InMemoryRepo::new();
         Self {
-            kind: Arc::new(RepoKind::Memory(InMemoryRepo::new())),
             counter: Arc::new(Mutex::new(0)),
+            grants: Arc::new(links.grants()),
+            groups: Arc::new(links.groups()),
+            kind: Arc::new(RepoKind::Memory(links)),
+            orgs: Arc::new(InMemoryOrganizationRepo::new()),
+            domains: Arc::new(InMemoryDomainRepo::new()),
+            namespaces: Arc::new(InMemoryNamespaceRepo::new()),
         }
     }
 
+    /// SQLite at `path` (`DB_PATH`), or the adapter's default location.
     #[cfg(feature = "sqlite")]
-    fn sqlite_from_env() -> Result<Self, CoreError> {
+    fn sqlite(path: Option<&std::path::Path>) -> Result<Self, CoreError> {
+        let repo = match path {
+            Some(path) => {
+                if let Some(dir) = path.parent() {
+                    let _ = std::fs::create_dir_all(dir);
+                }
+                sqlite_adapter::SqliteRepo::open(path, &sqlite_adapter::SqliteOptions::from_env())?
+            }
+            None => sqlite_adapter::SqliteRepo::from_env()?,
+        };
         Ok(Self {
-            kind: Arc::new(RepoKind::Sqlite(sqlite_adapter::SqliteRepo::from_env()?)),
+            kind: Arc::new(RepoKind::Sqlite(repo)),
             counter: Arc::new(Mutex::new(0)),
+            grants: Arc::new(InMemoryLinkGrantRepo::new()),
+            groups: Arc::new(InMemoryGroupRepo::new()),
+            orgs: Arc::new(InMemoryOrganizationRepo::new()),
+            domains: Arc::new(InMemoryDomainRepo::new()),
+            namespaces: Arc::new(InMemoryNamespaceRepo::new()),
         })
     }
 
+    #[cfg(feature = "postgres")]
+    fn postgres(url: &str) -> Result<Self, CoreError> {
+        let options = postgres_adapter::PostgresOptions::from_env();
+        Ok(Self {
+            kind: Arc::new(RepoKind::Postgres(postgres_adapter::PostgresRepo::connect(
+                url, &options,
+            )?)),
+            counter: Arc::new(Mutex::new(0)),
+            grants: Arc::new(InMemoryLinkGrantRepo::new()),
+            groups: Arc::new(InMemoryGroupRepo::new()),
+            orgs: Arc::new(InMemoryOrganizationRepo::new()),
+            domains: Arc::new(InMemoryDomainRepo::new()),
+            namespaces: Arc::new(InMemoryNamespaceRepo::new()),
+        })
+    }
+
+    #[cfg(feature = "dynamo")]
+    fn dynamo_from_env() -> Result<Self, CoreError> {
+        Ok(Self::dynamo(aws_dynamo::DynamoRepo::from_env()?))
+    }
+
+    #[cfg(feature = "dynamo")]
+    fn dynamo(repo: aws_dynamo::DynamoRepo) -> Self {
+        Self {
+            kind: Arc::new(RepoKind::Dynamo(Box::new(repo))),
+            counter: Arc::new(Mutex::new(0)),
+            grants: Arc::new(InMemoryLinkGrantRepo::new()),
+            groups: Arc::new(InMemoryGroupRepo::new()),
+            orgs: Arc::new(InMemoryOrganizationRepo::new()),
+            domains: Arc::new(InMemoryDomainRepo::new()),
+            namespaces: Arc::new(InMemoryNamespaceRepo::new()),
+        }
+    }
+
+    /// Handle on the same storage that only sees `tenant`'s data.
+    fn for_tenant(&self, tenant: &TenantId) -> Self {
+        let kind = match &*self.kind {
+            RepoKind::Memory(r) => RepoKind::Memory(r.for_tenant(tenant)),
+            #[cfg(feature = "sqlite")]
+            RepoKind::Sqlite(r) => RepoKind::Sqlite(r.for_tenant(tenant)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => RepoKind::Postgres(r.for_tenant(tenant)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => RepoKind::Dynamo(Box::new(r.for_tenant(tenant))),
+        };
+        Self {
+            kind: Arc::new(kind),
+            counter: Arc::clone(&self.counter),
+            grants: Arc::new(self.grants.for_tenant(tenant)),
+            groups: Arc::new(self.groups.for_tenant(tenant)),
+            orgs: Arc::clone(&self.orgs),
+            domains: Arc::new(self.domains.for_tenant(tenant)),
+            namespaces: Arc::new(self.namespaces.for_tenant(tenant)),
+        }
+    }
+
+    fn put_domain(&self, domain: ShortDomain) -> Result<(), CoreError> {
+        match &*self.kind {
+            RepoKind::Memory(_) => self.domains.put_domain(domain),
+            #[cfg(feature = "sqlite")]
+            RepoKind::Sqlite(r) => blocking(|| r.put_domain(domain)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.put_domain(domain)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.put_domain(domain),
+        }
+    }
+
+    fn get_domain(&self, host: &str) -> Result<Option<ShortDomain>, CoreError> {
+        match &*self.kind {
+            RepoKind::Memory(_) => self.domains.get_domain(host),
+            #[cfg(feature = "sqlite")]
+            RepoKind::Sqlite(r) => blocking(|| r.get_domain(host)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.get_domain(host)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.get_domain(host),
+        }
+    }
+
+    fn list_domains(&self) -> Result<Vec<ShortDomain>, CoreError> {
+        match &*self.kind {
+            RepoKind::Memory(_) => self.domains.list_domains(),
+            #[cfg(feature = "sqlite")]
+            RepoKind::Sqlite(r) => blocking(|| r.list_domains()),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.list_domains()),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.list_domains(),
+        }
+    }
+
+    fn delete_domain(&self, host: &str) -> Result<(), CoreError> {
+        match &*self.kind {
+            RepoKind::Memory(_) => self.domains.delete_domain(host),
+            #[cfg(feature = "sqlite")]
+            RepoKind::Sqlite(r) => blocking(|| r.delete_domain(host)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.delete_domain(host)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.delete_domain(host),
+        }
+    }
+
+    fn put_namespace(&self, namespace: Namespace) -> Result<(), CoreError> {
+        match &*self.kind {
+            RepoKind::Memory(_) => self.namespaces.put_namespace(namespace),
+            #[cfg(feature = "sqlite")]
+            RepoKind::Sqlite(r) => blocking(|| r.put_namespace(namespace)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.put_namespace(namespace)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.put_namespace(namespace),
+        }
+    }
+
+    fn get_namespace(&self, path: &str) -> Result<Option<Namespace>, CoreError> {
+        match &*self.kind {
+            RepoKind::Memory(_) => self.namespaces.get_namespace(path),
+            #[cfg(feature = "sqlite")]
+            RepoKind::Sqlite(r) => blocking(|| r.get_namespace(path)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.get_namespace(path)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.get_namespace(path),
+        }
+    }
+
+    fn list_namespaces(&self) -> Result<Vec<Namespace>, CoreError> {
+        match &*self.kind {
+            RepoKind::Memory(_) => self.namespaces.list_namespaces(),
+            #[cfg(feature = "sqlite")]
+            RepoKind::Sqlite(r) => blocking(|| r.list_namespaces()),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.list_namespaces()),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.list_namespaces(),
+        }
+    }
+
+    fn delete_namespace(&self, path: &str) -> Result<(), CoreError> {
+        match &*self.kind {
+            RepoKind::Memory(_) => self.namespaces.delete_namespace(path),
+            #[cfg(feature = "sqlite")]
+            RepoKind::Sqlite(r) => blocking(|| r.delete_namespace(path)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.delete_namespace(path)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.delete_namespace(path),
+        }
+    }
+
+    fn reserved_namespace(&self, slug: &Slug) -> Result<Option<Namespace>, CoreError> {
+        match &*self.kind {
+            RepoKind::Memory(_) => self.namespaces.reserved_namespace(slug),
+            #[cfg(feature = "sqlite")]
+            RepoKind::Sqlite(r) => blocking(|| r.reserved_namespace(slug)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.reserved_namespace(slug)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.reserved_namespace(slug),
+        }
+    }
+
+    fn get_user_groups(
+        &self,
+        user_email: &UserEmail,
+    ) -> Result<Vec<(LinkGroup, GroupRole)>, CoreError> {
+        match &*self.kind {
+            RepoKind::Memory(_) => self.groups.get_user_groups(user_email),
+            #[cfg(feature = "sqlite")]
+            RepoKind::Sqlite(r) => blocking(|| r.get_user_groups(user_email)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.get_user_groups(user_email)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.get_user_groups(user_email),
+        }
+    }
+
     fn get(&self, slug: &Slug) -> Result<Option<domain::ShortLink>, CoreError> {
         match &*self.kind {
             RepoKind::Memory(r) => r.get(slug),
             #[cfg(feature = "sqlite")]
-            RepoKind::Sqlite(r) => r.get(slug),
+            RepoKind::Sqlite(r) => blocking(|| r.get(slug)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.get(slug)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.get(slug),
         }
     }
 
@@ -90,7 +366,11 @@ impl AnyRepo {
         match &*self.kind {
             RepoKind::Memory(r) => r.put(link),
             #[cfg(feature = "sqlite")]
-            RepoKind::Sqlite(r) => r.put(link),
+            RepoKind::Sqlite(r) => blocking(|| r.put(link)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.put(link)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.put(link),
         }
     }
 
@@ -98,7 +378,11 @@ impl AnyRepo {
         match &*self.kind {
             RepoKind::Memory(r) => r.list(limit),
             #[cfg(feature = "sqlite")]
-            RepoKind::Sqlite(r) => r.list(limit),
+            RepoKind::Sqlite(r) => blocking(|| r.list(limit)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.list(limit)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.list(limit),
         }
     }
 
@@ -106,7 +390,11 @@ impl AnyRepo {
         match &*self.kind {
             RepoKind::Memory(r) => r.update(link),
             #[cfg(feature = "sqlite")]
-            RepoKind::Sqlite(r) => r.update(link),
+            RepoKind::Sqlite(r) => blocking(|| r.update(link)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.update(link)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.update(link),
         }
     }
 
@@ -114,7 +402,11 @@ impl AnyRepo {
         match &*self.kind {
             RepoKind::Memory(r) => r.increment_click(slug),
             #[cfg(feature = "sqlite")]
-            RepoKind::Sqlite(r) => r.increment_click(slug),
+            RepoKind::Sqlite(r) => blocking(|| r.increment_click(slug)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.increment_click(slug)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.increment_click(slug),
         }
     }
 
@@ -126,7 +418,11 @@ impl AnyRepo {
         match &*self.kind {
             RepoKind::Memory(r) => r.list_by_creator(email, limit),
             #[cfg(feature = "sqlite")]
-            RepoKind::Sqlite(r) => r.list_by_creator(email, limit),
+            RepoKind::Sqlite(r) => blocking(|| r.list_by_creator(email, limit)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.list_by_creator(email, limit)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.list_by_creator(email, limit),
         }
     }
 
@@ -142,7 +438,11 @@ impl AnyRepo {
                 Ok(id)
             }
             #[cfg(feature = "sqlite")]
-            RepoKind::Sqlite(r) => r.increment_global_counter(),
+            RepoKind::Sqlite(r) => blocking(|| r.increment_global_counter()),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.increment_global_counter()),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.increment_global_counter(),
         }
     }
 
@@ -154,7 +454,59 @@ impl AnyRepo {
         match &*self.kind {
             RepoKind::Memory(r) => r.delete(slug, deleted_at),
             #[cfg(feature = "sqlite")]
-            RepoKind::Sqlite(r) => r.delete(slug, deleted_at),
+            RepoKind::Sqlite(r) => blocking(|| r.delete(slug, deleted_at)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.delete(slug, deleted_at)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.delete(slug, deleted_at),
+        }
+    }
+
+    fn add_alias(&self, alias: &Slug, target: &Slug) -> Result<(), CoreError> {
+        match &*self.kind {
+            RepoKind::Memory(r) => r.add_alias(alias, target),
+            #[cfg(feature = "sqlite")]
+            RepoKind::Sqlite(r) => blocking(|| r.add_alias(alias, target)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.add_alias(alias, target)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.add_alias(alias, target),
+        }
+    }
+
+    fn remove_alias(&self, alias: &Slug) -> Result<(), CoreError> {
+        match &*self.kind {
+            RepoKind::Memory(r) => r.remove_alias(alias),
+            #[cfg(feature = "sqlite")]
+            RepoKind::Sqlite(r) => blocking(|| r.remove_alias(alias)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.remove_alias(alias)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.remove_alias(alias),
+        }
+    }
+
+    fn list_aliases(&self, target: &Slug) -> Result<Vec<Slug>, CoreError> {
+        match &*self.kind {
+            RepoKind::Memory(r) => r.list_aliases(target),
+            #[cfg(feature = "sqlite")]
+            RepoKind::Sqlite(r) => blocking(|| r.list_aliases(target)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.list_aliases(target)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.list_aliases(target),
+        }
+    }
+
+    fn rename(&self, old: &Slug, new: &Slug) -> Result<domain::ShortLink, CoreError> {
+        match &*self.kind {
+            RepoKind::Memory(r) => r.rename(old, new),
+            #[cfg(feature = "sqlite")]
+            RepoKind::Sqlite(r) => blocking(|| r.rename(old, new)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.rename(old, new)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.rename(old, new),
         }
     }
 
@@ -162,7 +514,11 @@ impl AnyRepo {
         match &*self.kind {
             RepoKind::Memory(r) => r.search(query, limit),
             #[cfg(feature = "sqlite")]
-            RepoKind::Sqlite(r) => r.search(query, limit),
+            RepoKind::Sqlite(r) => blocking(|| r.search(query, limit)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.search(query, limit)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.search(query, limit),
         }
     }
 
@@ -173,7 +529,26 @@ impl AnyRepo {
         match &*self.kind {
             RepoKind::Memory(r) => r.list_paginated(options),
             #[cfg(feature = "sqlite")]
-            RepoKind::Sqlite(r) => r.list_paginated(options),
+            RepoKind::Sqlite(r) => blocking(|| r.list_paginated(options)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.list_paginated(options)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.list_paginated(options),
+        }
+    }
+
+    fn search_ranked(
+        &self,
+        options: &domain::ListOptions,
+    ) -> Result<domain::ListResult<domain::SearchHit>, CoreError> {
+        match &*self.kind {
+            RepoKind::Memory(r) => r.search_ranked(options),
+            #[cfg(feature = "sqlite")]
+            RepoKind::Sqlite(r) => blocking(|| r.search_ranked(options)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.search_ranked(options)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.search_ranked(options),
         }
     }
 
@@ -185,7 +560,28 @@ impl AnyRepo {
         match &*self.kind {
             RepoKind::Memory(r) => r.list_by_group(group_id, limit),
             #[cfg(feature = "sqlite")]
-            RepoKind::Sqlite(r) => r.list_by_group(group_id, limit),
+            RepoKind::Sqlite(r) => blocking(|| r.list_by_group(group_id, limit)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.list_by_group(group_id, limit)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.list_by_group(group_id, limit),
+        }
+    }
+
+    fn list_by_target(
+        &self,
+        key: &str,
+        mode: TargetMatch,
+        limit: usize,
+    ) -> Result<Vec<domain::ShortLink>, CoreError> {
+        match &*self.kind {
+            RepoKind::Memory(r) => r.list_by_target(key, mode, limit),
+            #[cfg(feature = "sqlite")]
+            RepoKind::Sqlite(r) => blocking(|| r.list_by_target(key, mode, limit)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.list_by_target(key, mode, limit)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.list_by_target(key, mode, limit),
         }
     }
 
@@ -197,7 +593,11 @@ impl AnyRepo {
         match &*self.kind {
             RepoKind::Memory(r) => r.bulk_delete(slugs, deleted_at),
             #[cfg(feature = "sqlite")]
-            RepoKind::Sqlite(r) => r.bulk_delete(slugs, deleted_at),
+            RepoKind::Sqlite(r) => blocking(|| r.bulk_delete(slugs, deleted_at)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.bulk_delete(slugs, deleted_at)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.bulk_delete(slugs, deleted_at),
         }
     }
 
@@ -210,7 +610,191 @@ impl AnyRepo {
         match &*self.kind {
             RepoKind::Memory(r) => r.bulk_update_active(slugs, is_active, updated_at),
             #[cfg(feature = "sqlite")]
-            RepoKind::Sqlite(r) => r.bulk_update_active(slugs, is_active, updated_at),
+            RepoKind::Sqlite(r) => blocking(|| r.bulk_update_active(slugs, is_active, updated_at)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => {
+                blocking(|| r.bulk_update_active(slugs, is_active, updated_at))
+            }
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.bulk_update_active(slugs, is_active, updated_at),
+        }
+    }
+
+    fn put_grant(&self, grant: LinkGrant) -> Result<(), CoreError> {
+        match &*self.kind {
+            RepoKind::Memory(_) => self.grants.put_grant(grant),
+            #[cfg(feature = "sqlite")]
+            RepoKind::Sqlite(r) => blocking(|| r.put_grant(grant)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.put_grant(grant)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.put_grant(grant),
+        }
+    }
+
+    fn remove_grant(&self, slug: &Slug, user_email: &UserEmail) -> Result<(), CoreError> {
+        match &*self.kind {
+            RepoKind::Memory(_) => self.grants.remove_grant(slug, user_email),
+            #[cfg(feature = "sqlite")]
+            RepoKind::Sqlite(r) => blocking(|| r.remove_grant(slug, user_email)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.remove_grant(slug, user_email)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.remove_grant(slug, user_email),
+        }
+    }
+
+    fn list_grants(&self, slug: &Slug) -> Result<Vec<LinkGrant>, CoreError> {
+        match &*self.kind {
+            RepoKind::Memory(_) => self.grants.list_grants(slug),
+            #[cfg(feature = "sqlite")]
+            RepoKind::Sqlite(r) => blocking(|| r.list_grants(slug)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.list_grants(slug)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.list_grants(slug),
+        }
+    }
+
+    fn get_grant(
+        &self,
+        slug: &Slug,
+        user_email: &UserEmail,
+    ) -> Result<Option<LinkGrant>, CoreError> {
+        match &*self.kind {
+            RepoKind::Memory(_) => self.grants.get_grant(slug, user_email),
+            #[cfg(feature = "sqlite")]
+            RepoKind::Sqlite(r) => blocking(|| r.get_grant(slug, user_email)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.get_grant(slug, user_email)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.get_grant(slug, user_email),
+        }
+    }
+
+    fn list_grants_for_user(&self, user_email: &UserEmail) -> Result<Vec<LinkGrant>, CoreError> {
+        match &*self.kind {
+            RepoKind::Memory(_) => self.grants.list_grants_for_user(user_email),
+            #[cfg(feature = "sqlite")]
+            RepoKind::Sqlite(r) => blocking(|| r.list_grants_for_user(user_email)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.list_grants_for_user(user_email)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.list_grants_for_user(user_email),
+        }
+    }
+
+    fn create_group(&self, group: LinkGroup) -> Result<(), CoreError> {
+        match &*self.kind {
+            RepoKind::Memory(_) => self.groups.create_group(group),
+            #[cfg(feature = "sqlite")]
+            RepoKind::Sqlite(r) => blocking(|| r.create_group(group)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.create_group(group)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.create_group(group),
+        }
+    }
+
+    fn get_group(&self, id: &str) -> Result<Option<LinkGroup>, CoreError> {
+        match &*self.kind {
+            RepoKind::Memory(_) => self.groups.get_group(id),
+            #[cfg(feature = "sqlite")]
+            RepoKind::Sqlite(r) => blocking(|| r.get_group(id)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.get_group(id)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.get_group(id),
+        }
+    }
+
+    fn update_group(&self, group: &LinkGroup) -> Result<(), CoreError> {
+        match &*self.kind {
+            RepoKind::Memory(_) => self.groups.update_group(group),
+            #[cfg(feature = "sqlite")]
+            RepoKind::Sqlite(r) => blocking(|| r.update_group(group)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.update_group(group)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.update_group(group),
+        }
+    }
+
+    fn add_member(&self, member: GroupMember) -> Result<(), CoreError> {
+        match &*self.kind {
+            RepoKind::Memory(_) => self.groups.add_member(member),
+            #[cfg(feature = "sqlite")]
+            RepoKind::Sqlite(r) => blocking(|| r.add_member(member)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.add_member(member)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.add_member(member),
+        }
+    }
+
+    fn effective_role(
+        &self,
+        group_id: &str,
+        user_email: &UserEmail,
+    ) -> Result<Option<GroupRole>, CoreError> {
+        match &*self.kind {
+            RepoKind::Memory(_) => self.groups.effective_role(group_id, user_email),
+            #[cfg(feature = "sqlite")]
+            RepoKind::Sqlite(r) => blocking(|| r.effective_role(group_id, user_email)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.effective_role(group_id, user_email)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.effective_role(group_id, user_email),
+        }
+    }
+}
+
+impl OrganizationRepository for AnyRepo {
+    fn put_organization(&self, org: Organization) -> Result<(), CoreError> {
+        match &*self.kind {
+            RepoKind::Memory(_) => self.orgs.put_organization(org),
+            #[cfg(feature = "sqlite")]
+            RepoKind::Sqlite(r) => blocking(|| r.put_organization(org)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.put_organization(org)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.put_organization(org),
+        }
+    }
+
+    fn get_organization(&self, id: &TenantId) -> Result<Option<Organization>, CoreError> {
+        match &*self.kind {
+            RepoKind::Memory(_) => self.orgs.get_organization(id),
+            #[cfg(feature = "sqlite")]
+            RepoKind::Sqlite(r) => blocking(|| r.get_organization(id)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.get_organization(id)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.get_organization(id),
+        }
+    }
+
+    fn list_organizations(&self) -> Result<Vec<Organization>, CoreError> {
+        match &*self.kind {
+            RepoKind::Memory(_) => self.orgs.list_organizations(),
+            #[cfg(feature = "sqlite")]
+            RepoKind::Sqlite(r) => blocking(|| r.list_organizations()),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.list_organizations()),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.list_organizations(),
+        }
+    }
+
+    fn get_organization_by_host(&self, host: &str) -> Result<Option<Organization>, CoreError> {
+        match &*self.kind {
+            RepoKind::Memory(_) => self.orgs.get_organization_by_host(host),
+            #[cfg(feature = "sqlite")]
+            RepoKind::Sqlite(r) => blocking(|| r.get_organization_by_host(host)),
+            #[cfg(feature = "postgres")]
+            RepoKind::Postgres(r) => blocking(|| r.get_organization_by_host(host)),
+            #[cfg(feature = "dynamo")]
+            RepoKind::Dynamo(r) => r.get_organization_by_host(host),
         }
     }
 }
@@ -219,11 +803,100 @@ impl AnyRepo {
 struct AppState {
     repo: AnyRepo,
     slugger: Base62SlugGenerator,
+    word_slugger: WordSlugGenerator,
     clock: StdClock,
     auth_provider: config::AuthProvider,
     allowed_domain: Option<String>,
     google_oauth_client_id: Option<String>,
     shortlink_domain: Option<String>,
+    session_secret: Option<String>,
+    session_ttl_secs: u64,
+    /// Organizations by host, shared by all requests.
+    orgs: Arc<OrganizationCache>,
+    /// Organization the request belongs to; set by the [`Tenant`] extractor.
+    org: Organization,
+}
+
+impl AppState {
+    /// Scope the state to the organization serving a request.
+    fn for_request(&self, headers: &HeaderMap) -> Result<AppState, CoreError> {
+        let host = headers
+            .get(axum::http::header::HOST)
+            .and_then(|v| v.to_str().ok());
+        let claimed = claimed_domain(headers);
+        let org = self
+            .orgs
+            .resolve(&self.repo, host, claimed.as_deref())?
+            .unwrap_or_else(Organization::fallback);
+
+        let mut state = self.clone();
+        state.repo = self.repo.for_tenant(&org.id);
+        if let Some(domain) = &org.settings.shortlink_domain {
+            state.shortlink_domain = Some(domain.clone());
+        }
+        if !org.auth_domains.is_empty() {
+            // The claim only picks which of the organization's domains the
+            // caller is verified against
+            state.allowed_domain = claimed
+                .filter(|d| org.allows_domain(d))
+                .or_else(|| org.auth_domains.first().cloned());
+        }
+        state.org = org;
+        Ok(state)
+    }
+
+    fn is_admin(&self, email: &str) -> bool {
+        is_admin(email) || self.org.is_admin(email)
+    }
+
+    /// The request's host when it is one of the organization's short domains;
+    /// `None` means the default domain.
+    fn short_domain(&self, headers: &HeaderMap) -> Result<Option<String>, CoreError> {
+        let Some(host) = headers
+            .get(axum::http::header::HOST)
+            .and_then(|v| v.to_str().ok())
+            .map(|h| h.split(':').next().unwrap_or(h).to_ascii_lowercase())
+        else {
+            return Ok(None);
+        };
+        Ok(self.repo.get_domain(&host)?.map(|d| d.host))
+    }
+}
+
+/// Sign-in domain claimed by the request's credentials. Unverified; only used
+/// to pick the organization.
+fn claimed_domain(headers: &HeaderMap) -> Option<String> {
+    let header = |name| headers.get(name).and_then(|v| v.to_str().ok());
+    if let Some(token) =
+        header(axum::http::header::AUTHORIZATION.as_str()).and_then(|v| v.strip_prefix("Bearer "))
+    {
+        return google_auth::token_domain(token);
+    }
+    header("x-debug-user")
+        .and_then(tenant::email_domain)
+        .map(str::to_ascii_lowercase)
+}
+
+/// Application state scoped to the organization serving the request.
+struct Tenant(AppState);
+
+#[async_trait]
+impl FromRequestParts<AppState> for Tenant {
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Response> {
+        state.for_request(&parts.headers).map(Tenant).map_err(|e| {
+            error!(err=?e, "organization lookup error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(http_common::json_error_with_message(
+                    "internal",
+                    "server error",
+                )),
+            )
+                .into_response()
+        })
+    }
 }
 
 #[derive(Clone)]
@@ -248,22 +921,38 @@ async fn main() {
     init_tracing(&cfg);
     cfg.warn_if_insecure();
 
-    let repo = build_repo_from_env(&cfg);
+    // Serving from memory instead would silently drop every write, so a
+    // storage that cannot be opened (or has a newer schema) stops startup
+    let repo = match build_repo_from_env(&cfg) {
+        Ok(r) => r,
+        Err(e) => {
+            error!(err = %e, storage = ?cfg.storage_provider, "storage init failed");
+            std::process::exit(1);
+        }
+    };
     let state = AppState {
         repo,
         slugger: Base62SlugGenerator::new(5),
+        word_slugger: cfg.word_slugger,
         clock: StdClock,
         auth_provider: cfg.auth_provider.clone(),
         allowed_domain: cfg.allowed_domain.clone(),
         google_oauth_client_id: cfg.google_oauth_client_id.clone(),
         shortlink_domain: cfg.shortlink_domain.clone(),
+        session_secret: cfg.session_secret.clone(),
+        session_ttl_secs: cfg.session_ttl_secs,
+        orgs: Arc::new(OrganizationCache::new(std::time::Duration::from_secs(
+            cfg.org_cache_ttl_secs,
+        ))),
+        org: Organization::fallback(),
     };
 
     // Request ID header name
     let x_request_id = axum::http::HeaderName::from_static("x-request-id");
 
     let mut app = Router::new()
-        .route("/:slug", get(get_slug))
+        .route("/*slug", get(get_slug))
+        .route("/auth/session", post(create_session))
         .route(
             "/api/links",
             post(create_link).get(list_links).options(preflight_links),
@@ -274,6 +963,30 @@ async fn main() {
                 .delete(delete_link)
                 .options(preflight_link),
         )
+        .route(
+            "/api/links/:slug/collaborators",
+            get(list_link_collaborators)
+                .post(add_link_collaborator)
+                .options(preflight_link),
+        )
+        .route(
+            "/api/links/:slug/collaborators/:email",
+            axum::routing::delete(remove_link_collaborator).options(preflight_link),
+        )
+        .route(
+            "/api/links/:slug/rename",
+            post(rename_link).options(preflight_link),
+        )
+        .route(
+            "/api/links/:slug/aliases",
+            get(list_link_aliases)
+                .post(add_link_alias)
+                .options(preflight_link),
+        )
+        .route(
+            "/api/links/:slug/aliases/:alias",
+            axum::routing::delete(remove_link_alias).options(preflight_link),
+        )
         .route(
             "/api/links/bulk/delete",
             post(bulk_delete_links).options(preflight_links),
@@ -286,6 +999,40 @@ async fn main() {
             "/api/links/bulk/deactivate",
             post(bulk_deactivate_links).options(preflight_links),
         )
+        .route(
+            "/api/links/by-target",
+            get(links_by_target).options(preflight_links),
+        )
+        .route(
+            "/api/links/by-target/repoint",
+            post(repoint_links_by_target).options(preflight_links),
+        )
+        .route(
+            "/api/groups/:id",
+            axum::routing::patch(update_group).options(preflight_link),
+        )
+        .route("/api/domains", get(list_domains).options(preflight_links))
+        .route(
+            "/api/domains/:host",
+            axum::routing::put(put_domain)
+                .delete(delete_domain)
+                .options(preflight_link),
+        )
+        .route(
+            "/api/namespaces",
+            get(list_namespaces).options(preflight_links),
+        )
+        .route("/api/slugs/check", get(check_slug).options(preflight_links))
+        .route(
+            "/api/slugs/suggest",
+            get(suggest_slugs).options(preflight_links),
+        )
+        .route(
+            "/api/namespaces/*path",
+            axum::routing::put(put_namespace)
+                .delete(delete_namespace)
+                .options(preflight_link),
+        )
         .route("/api/me", get(get_me).options(preflight_links))
         .layer(PropagateRequestIdLayer::new(x_request_id.clone()))
         .layer(
@@ -315,6 +1062,7 @@ async fn main() {
             .allow_methods([
                 axum::http::Method::GET,
                 axum::http::Method::POST,
+                axum::http::Method::PUT,
                 axum::http::Method::PATCH,
                 axum::http::Method::DELETE,
                 axum::http::Method::OPTIONS,
@@ -364,18 +1112,20 @@ fn init_tracing(cfg: &config::Config) {
     }
 }
 
-// Construct a repository instance based on config and feature flags.
-fn build_repo_from_env(cfg: &config::Config) -> AnyRepo {
+// Construct a repository instance based on config and feature flags. Errors
+// when the configured storage cannot be opened.
+fn build_repo_from_env(cfg: &config::Config) -> Result<AnyRepo, CoreError> {
     match cfg.storage_provider {
         #[cfg(feature = "sqlite")]
-        config::StorageProvider::Sqlite => match AnyRepo::sqlite_from_env() {
-            Ok(r) => r,
-            Err(e) => {
-                eprintln!("failed to init SqliteRepo from env: {e}");
-                AnyRepo::memory()
-            }
-        },
-        _ => AnyRepo::memory(),
+        config::StorageProvider::Sqlite => AnyRepo::sqlite(cfg.db_path.as_deref()),
+        #[cfg(feature = "postgres")]
+        config::StorageProvider::Postgres => {
+            let url = cfg.database_url.as_deref().unwrap_or_default();
+            blocking(|| AnyRepo::postgres(url))
+        }
+        #[cfg(feature = "dynamo")]
+        config::StorageProvider::Dynamo => AnyRepo::dynamo_from_env(),
+        _ => Ok(AnyRepo::memory()),
     }
 }
 
@@ -392,9 +1142,20 @@ struct CreateLinkReq {
     redirect_delay: Option<u32>,
     #[serde(default)]
     group_id: Option<String>,
-}
-
-#[derive(Deserialize)]
+    #[serde(default)]
+    visibility: Option<String>,
+    /// Extra short domain to create the link on; the default domain when unset.
+    #[serde(default)]
+    domain: Option<String>,
+    /// How to generate the slug when no alias is given: "base62" (default) or "words".
+    #[serde(default)]
+    slug_style: Option<String>,
+    /// Return an existing link to the same target (200) instead of generating a new slug.
+    #[serde(default)]
+    dedupe: bool,
+}
+
+#[derive(Deserialize)]
 struct UpdateLinkReq {
     #[serde(default)]
     original_url: Option<String>,
@@ -410,6 +1171,145 @@ struct UpdateLinkReq {
     redirect_delay: Option<Option<u32>>,
     #[serde(default)]
     group_id: Option<Option<String>>,
+    #[serde(default)]
+    visibility: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct AddCollaboratorReq {
+    email: String,
+    #[serde(default = "default_collaborator_role")]
+    role: String,
+}
+
+fn default_collaborator_role() -> String {
+    "editor".into()
+}
+
+#[derive(Deserialize)]
+struct RenameLinkReq {
+    slug: String,
+}
+
+#[derive(Deserialize)]
+struct AddAliasReq {
+    alias: String,
+}
+
+#[derive(Serialize)]
+struct AliasOut {
+    slug: String,
+    short_url: String,
+}
+
+#[derive(Serialize)]
+struct AliasListOut {
+    aliases: Vec<AliasOut>,
+}
+
+#[derive(Serialize)]
+struct CollaboratorOut {
+    email: String,
+    role: String,
+    granted_at: String,
+    granted_by: String,
+}
+
+#[derive(Serialize)]
+struct CollaboratorListOut {
+    collaborators: Vec<CollaboratorOut>,
+}
+
+fn grant_to_out(grant: &LinkGrant) -> CollaboratorOut {
+    CollaboratorOut {
+        email: grant.user_email.as_str().to_string(),
+        role: grant.role.as_str().to_string(),
+        granted_at: http_common::system_time_to_rfc3339(grant.granted_at),
+        granted_by: grant.granted_by.as_str().to_string(),
+    }
+}
+
+#[derive(Deserialize)]
+struct UpdateGroupReq {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    description: Option<Option<String>>,
+    /// Replaces the group's settings as a whole; omitted fields are cleared.
+    #[serde(default)]
+    settings: Option<GroupSettingsBody>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct GroupSettingsBody {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    default_expiry_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    default_redirect_delay: Option<u32>,
+    #[serde(default)]
+    allowed_hosts: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    slug_prefix: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_links: Option<u32>,
+}
+
+impl From<&GroupSettings> for GroupSettingsBody {
+    fn from(s: &GroupSettings) -> Self {
+        Self {
+            default_expiry_secs: s.default_expiry_secs,
+            default_redirect_delay: s.default_redirect_delay,
+            allowed_hosts: s.allowed_hosts.clone(),
+            slug_prefix: s.slug_prefix.clone(),
+            max_links: s.max_links,
+        }
+    }
+}
+
+impl From<GroupSettingsBody> for GroupSettings {
+    fn from(b: GroupSettingsBody) -> Self {
+        Self {
+            default_expiry_secs: b.default_expiry_secs,
+            default_redirect_delay: b.default_redirect_delay,
+            allowed_hosts: b
+                .allowed_hosts
+                .into_iter()
+                .map(|h| h.trim().to_ascii_lowercase())
+                .collect(),
+            slug_prefix: b.slug_prefix.filter(|p| !p.is_empty()),
+            max_links: b.max_links,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct GroupOut {
+    id: String,
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    created_at: String,
+    created_by: String,
+    settings: GroupSettingsBody,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent_id: Option<String>,
+}
+
+fn group_to_out(group: &LinkGroup) -> GroupOut {
+    GroupOut {
+        id: group.id.clone(),
+        name: group.name.clone(),
+        description: group.description.clone(),
+        created_at: http_common::system_time_to_rfc3339(group.created_at),
+        created_by: group.created_by.as_str().to_string(),
+        settings: GroupSettingsBody::from(&group.settings),
+        parent_id: group.parent_id.clone(),
+    }
+}
+
+#[derive(Deserialize)]
+struct SessionReq {
+    credential: String,
 }
 
 #[derive(Deserialize)]
@@ -425,6 +1325,9 @@ struct BulkResultOut {
 #[derive(Serialize)]
 struct LinkOut {
     slug: String,
+    /// Short domain of the link; unset for the default domain.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    domain: Option<String>,
     short_url: String,
     original_url: String,
     created_at: String,
@@ -443,13 +1346,26 @@ struct LinkOut {
     redirect_delay: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     group_id: Option<String>,
+    visibility: &'static str,
+    /// Matching excerpt when listed by a ranked search; matched words are
+    /// wrapped in `<mark>`, everything else is unescaped text.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    snippet: Option<String>,
+    /// Caller's effective role on the link; only set by `scope=accessible` listings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    role: Option<&'static str>,
 }
 
 #[derive(Serialize)]
 struct ListOut {
     links: Vec<LinkOut>,
-    total: usize,
+    /// Left out on later cursor pages of stores that only count the first.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    total: Option<usize>,
     has_more: bool,
+    /// Pass as `cursor` to fetch the next page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next_cursor: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     user: Option<UserInfo>,
 }
@@ -460,6 +1376,14 @@ struct UserInfo {
     is_admin: bool,
 }
 
+#[derive(Serialize)]
+struct MeOut {
+    #[serde(flatten)]
+    user: UserInfo,
+    tenant: String,
+    organization: String,
+}
+
 fn link_to_out(
     link: domain::ShortLink,
     headers: &HeaderMap,
@@ -467,7 +1391,8 @@ fn link_to_out(
 ) -> LinkOut {
     LinkOut {
         slug: link.slug.as_str().to_string(),
-        short_url: build_short_url(headers, link.slug.as_str(), shortlink_domain),
+        domain: link.slug.domain().map(str::to_string),
+        short_url: build_short_url(headers, &link.slug, shortlink_domain),
         original_url: link.original_url,
         created_at: http_common::system_time_to_rfc3339(link.created_at),
         created_by: link.created_by.as_str().to_string(),
@@ -479,6 +1404,9 @@ fn link_to_out(
         activate_at: link.activate_at.map(http_common::system_time_to_rfc3339),
         redirect_delay: link.redirect_delay,
         group_id: link.group_id,
+        visibility: link.visibility.as_str(),
+        snippet: None,
+        role: None,
     }
 }
 
@@ -490,9 +1418,49 @@ fn is_admin(email: &str) -> bool {
         .any(|a| a.eq_ignore_ascii_case(email))
 }
 
-async fn get_slug(State(state): State<AppState>, Path(slug): Path<String>) -> impl IntoResponse {
-    match Slug::new(slug.clone()) {
+async fn get_slug(
+    Tenant(state): Tenant,
+    headers: HeaderMap,
+    Path(slug): Path<String>,
+) -> impl IntoResponse {
+    let short_domain = match state.short_domain(&headers) {
+        Ok(d) => d,
+        Err(e) => {
+            error!(err=?e, "get domain error");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(http_common::json_err("error")),
+            )
+                .into_response();
+        }
+    };
+    match Slug::new(slug.clone()).map(|s| s.on_domain(short_domain)) {
         Ok(s) => match state.repo.get(&s) {
+            Ok(Some(link)) if link.requires_login() => {
+                match verify_visitor(&headers, &state).await {
+                    Ok(user) => {
+                        info!(slug = %s.as_str(), user = %user.email, redirect_to = %link.original_url, "resolve ok (workspace)");
+                        (
+                            [(axum::http::header::CACHE_CONTROL, "private, no-store")],
+                            Redirect::permanent(&link.original_url),
+                        )
+                            .into_response()
+                    }
+                    Err(AuthHttp::Unauthorized) if state.session_secret.is_none() => login_page(
+                        StatusCode::SERVICE_UNAVAILABLE,
+                        &state,
+                        Some("Sign-in is not configured for this service."),
+                    ),
+                    Err(AuthHttp::Unauthorized) => {
+                        login_page(StatusCode::UNAUTHORIZED, &state, None)
+                    }
+                    Err(AuthHttp::Forbidden) => login_page(
+                        StatusCode::FORBIDDEN,
+                        &state,
+                        Some("Your account is not part of this workspace."),
+                    ),
+                }
+            }
             Ok(Some(link)) => {
                 info!(slug = %s.as_str(), redirect_to = %link.original_url, "resolve ok");
                 Redirect::permanent(&link.original_url).into_response()
@@ -525,8 +1493,65 @@ async fn get_slug(State(state): State<AppState>, Path(slug): Path<String>) -> im
     }
 }
 
+/// Attempts at finding a free generated slug before giving up.
+const SLUG_GENERATION_ATTEMPTS: usize = 10;
+
+/// Generate a slug in `style` that is free on `short_domain`. Word slugs can
+/// collide with existing links, so taken ones are retried with a fresh counter
+/// value.
+fn generate_slug(
+    state: &AppState,
+    style: SlugStyle,
+    short_domain: &Option<String>,
+) -> Result<Slug, CoreError> {
+    for _ in 0..SLUG_GENERATION_ATTEMPTS {
+        let id = state.repo.increment_global_counter()?;
+        let slug = match style {
+            SlugStyle::Base62 => state.slugger.next_slug(id),
+            SlugStyle::Words => state.word_slugger.next_slug(id),
+        };
+        let candidate = slug.clone().on_domain(short_domain.clone());
+        if state.repo.get(&candidate)?.is_none() {
+            return Ok(slug);
+        }
+    }
+    Err(CoreError::Repository(
+        "no free slug after repeated attempts".into(),
+    ))
+}
+
+/// Links considered when looking for an existing link to the same target.
+const DEDUPE_CANDIDATES: usize = 100;
+
+/// The oldest link `owner` can reuse instead of creating a new one to `url`
+/// (see [`domain::ShortLink::dedupes`]). Group links are only reused for
+/// members of the group.
+fn find_duplicate_link(
+    state: &AppState,
+    url: &str,
+    owner: &UserEmail,
+    group_id: Option<&str>,
+    short_domain: Option<&str>,
+) -> Result<Option<domain::ShortLink>, CoreError> {
+    if let Some(gid) = group_id {
+        if !state.is_admin(owner.as_str()) && state.repo.effective_role(gid, owner)?.is_none() {
+            return Ok(None);
+        }
+    }
+    let now = state.clock.now();
+    Ok(state
+        .repo
+        .list_by_target(
+            &domain::validate::canonical_url(url),
+            TargetMatch::Exact,
+            DEDUPE_CANDIDATES,
+        )?
+        .into_iter()
+        .find(|link| link.dedupes(owner, group_id, short_domain, now)))
+}
+
 async fn create_link(
-    State(state): State<AppState>,
+    Tenant(state): Tenant,
     headers: HeaderMap,
     Json(body): Json<CreateLinkReq>,
 ) -> impl IntoResponse {
@@ -587,14 +1612,108 @@ async fn create_link(
             .into_response();
     }
 
+    let err = |status: StatusCode, code: &str, message: &str| {
+        (
+            status,
+            Json(http_common::json_error_with_message(code, message)),
+        )
+            .into_response()
+    };
+    let slug_style = match body.slug_style.as_deref().map(SlugStyle::parse) {
+        None => SlugStyle::default(),
+        Some(Some(style)) => style,
+        Some(None) => {
+            return err(
+                StatusCode::BAD_REQUEST,
+                "invalid_request",
+                "invalid slug_style, use: base62 or words",
+            )
+        }
+    };
+
+    // Links on an extra short domain need a registered domain the caller may use
+    let short_domain = match body.domain.as_deref().filter(|d| !d.trim().is_empty()) {
+        None => None,
+        Some(d) => {
+            let Ok(host) = domain::normalize_host(d) else {
+                return err(StatusCode::BAD_REQUEST, "invalid_request", "invalid domain");
+            };
+            let registered = match state.repo.get_domain(&host) {
+                Ok(Some(sd)) => sd,
+                Ok(None) => {
+                    return err(StatusCode::BAD_REQUEST, "invalid_request", "unknown domain")
+                }
+                Err(e) => {
+                    error!(err=?e, "get domain error");
+                    return err(
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        "internal",
+                        "server error",
+                    );
+                }
+            };
+            if !state.is_admin(&verified.email) {
+                let group_ids: Vec<String> = match state.repo.get_user_groups(&user_email) {
+                    Ok(groups) => groups.into_iter().map(|(g, _)| g.id).collect(),
+                    Err(e) => {
+                        error!(err=?e, "get user groups error");
+                        return err(
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                            "internal",
+                            "server error",
+                        );
+                    }
+                };
+                if !registered.may_use(&user_email, &group_ids) {
+                    return err(
+                        StatusCode::FORBIDDEN,
+                        "forbidden",
+                        "not allowed to create links on this domain",
+                    );
+                }
+            }
+            Some(host)
+        }
+    };
+
+    // Hand out an existing link to the same target instead of minting a slug
+    if body.dedupe && body.alias.is_none() {
+        match find_duplicate_link(
+            &state,
+            &body.original_url,
+            &user_email,
+            body.group_id.as_deref(),
+            short_domain.as_deref(),
+        ) {
+            Ok(Some(existing)) => {
+                info!(slug = %existing.slug.as_str(), "create deduplicated");
+                return (
+                    StatusCode::OK,
+                    Json(link_to_out(existing, &headers, &state.shortlink_domain)),
+                )
+                    .into_response();
+            }
+            Ok(None) => {}
+            Err(e) => {
+                error!(err=?e, "dedupe lookup error");
+                return err(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "internal",
+                    "server error",
+                );
+            }
+        }
+    }
+
     // Determine slug
+    let generated_slug = body.alias.is_none();
     let slug = if let Some(alias) = &body.alias {
         if !http_common::is_valid_alias(alias) {
             return (
                 StatusCode::BAD_REQUEST,
                 Json(http_common::json_error_with_message(
                     "invalid_request",
-                    "alias must be 3-32 characters, alphanumeric with hyphens/underscores",
+                    "alias must be 3-32 characters, alphanumeric with hyphens/underscores (namespaces separated by /)",
                 )),
             )
                 .into_response();
@@ -613,26 +1732,31 @@ async fn create_link(
             }
         }
     } else {
-        let id = match state.repo.increment_global_counter() {
-            Ok(v) => v,
+        match generate_slug(&state, slug_style, &short_domain) {
+            Ok(s) => s,
             Err(e) => {
-                error!(err=?e, "counter error");
-                return (
+                error!(err=?e, "slug generation error");
+                return err(
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(http_common::json_error_with_message(
-                        "internal",
-                        "counter failure",
-                    )),
-                )
-                    .into_response();
+                    "internal",
+                    "could not generate a unique slug",
+                );
             }
-        };
-        state.slugger.next_slug(id)
+        }
     };
 
+    if let Some(resp) = check_namespace(&state, &slug, &verified.email) {
+        return resp;
+    }
+
     // Persist
     let created_at = state.clock.now();
-    let mut link = domain::ShortLink::new(slug, body.original_url.clone(), created_at, user_email);
+    let mut link = domain::ShortLink::new(
+        slug.on_domain(short_domain),
+        body.original_url.clone(),
+        created_at,
+        user_email,
+    );
 
     // Apply optional fields
     link.description = body.description;
@@ -641,6 +1765,26 @@ async fn create_link(
         .and_then(|s| http_common::parse_rfc3339(&s).ok());
     link.redirect_delay = body.redirect_delay;
     link.group_id = body.group_id;
+    if let Some(v) = body.visibility {
+        link.visibility = match LinkVisibility::parse(&v) {
+            Some(v) => v,
+            None => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(http_common::json_error_with_message(
+                        "invalid_request",
+                        "invalid visibility, use: public or workspace",
+                    )),
+                )
+                    .into_response()
+            }
+        };
+    }
+    if let Some(resp) =
+        enforce_link_settings(&state, &mut link, GroupCheck::Create { generated_slug })
+    {
+        return resp;
+    }
 
     match state.repo.put(link.clone()) {
         Ok(()) => {
@@ -681,6 +1825,166 @@ async fn create_link(
     }
 }
 
+/// Render the sign-in page shown in place of a workspace-only link.
+fn login_page(status: StatusCode, state: &AppState, message: Option<&str>) -> Response {
+    let client_id = match state.auth_provider {
+        config::AuthProvider::Google => state.google_oauth_client_id.as_deref(),
+        config::AuthProvider::None => None,
+    };
+    (
+        status,
+        [(axum::http::header::CACHE_CONTROL, "no-store")],
+        Html(http_common::login_page_html(
+            client_id,
+            "/auth/session",
+            message,
+        )),
+    )
+        .into_response()
+}
+
+/// Exchange a credential for a visitor session cookie.
+///
+/// The credential is a Google ID token, or an email address when AUTH_PROVIDER=none.
+/// It goes through the same checks as the admin API's request authentication.
+async fn create_session(Tenant(state): Tenant, Json(body): Json<SessionReq>) -> impl IntoResponse {
+    let Some(secret) = state.session_secret.as_deref() else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(http_common::json_error_with_message(
+                "not_configured",
+                "visitor sessions are not configured",
+            )),
+        )
+            .into_response();
+    };
+
+    // Present the credential the way the admin API would receive it
+    let (name, value) = match state.auth_provider {
+        config::AuthProvider::None => ("x-debug-user", body.credential),
+        config::AuthProvider::Google => ("authorization", format!("Bearer {}", body.credential)),
+    };
+    let mut creds = HeaderMap::new();
+    match HeaderValue::from_str(&value) {
+        Ok(v) => {
+            creds.insert(name, v);
+        }
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(http_common::json_error_with_message(
+                    "invalid_request",
+                    "invalid credential",
+                )),
+            )
+                .into_response()
+        }
+    }
+
+    let verified = match verify_request_user(
+        &creds,
+        &state.auth_provider,
+        &state.allowed_domain,
+        &state.google_oauth_client_id,
+    )
+    .await
+    {
+        Ok(v) => v,
+        Err(AuthHttp::Unauthorized) => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(http_common::json_error_with_message(
+                    "unauthorized",
+                    "missing or invalid token",
+                )),
+            )
+                .into_response()
+        }
+        Err(AuthHttp::Forbidden) => {
+            return (
+                StatusCode::FORBIDDEN,
+                Json(http_common::json_error_with_message(
+                    "forbidden",
+                    "domain not allowed",
+                )),
+            )
+                .into_response()
+        }
+    };
+
+    let ttl = std::time::Duration::from_secs(state.session_ttl_secs);
+    match google_auth::issue_session(&verified, secret.as_bytes(), ttl) {
+        Ok(token) => {
+            info!(user = %verified.email, "session issued");
+            (
+                StatusCode::NO_CONTENT,
+                [(
+                    axum::http::header::SET_COOKIE,
+                    http_common::session_cookie(&token, state.session_ttl_secs),
+                )],
+            )
+                .into_response()
+        }
+        Err(e) => {
+            error!(err=?e, "session issue error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(http_common::json_error_with_message(
+                    "internal",
+                    "server error",
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Verify the visitor of a workspace-only link.
+///
+/// Accepts the session cookie issued by `create_session`, falling back to the
+/// credentials the admin API accepts (Bearer token or X-Debug-User). Like
+/// `create_session`, debug auth mode only trusts X-Debug-User when visitor
+/// sessions are enabled (DEBUG_SESSIONS).
+async fn verify_visitor(headers: &HeaderMap, state: &AppState) -> Result<VerifiedUser, AuthHttp> {
+    let cookie = headers
+        .get(axum::http::header::COOKIE)
+        .and_then(|v| v.to_str().ok());
+    if let (Some(token), Some(secret)) = (
+        http_common::get_cookie(cookie, http_common::SESSION_COOKIE),
+        state.session_secret.as_deref(),
+    ) {
+        let res = if !state.org.auth_domains.is_empty() {
+            // The token's own domain (hosted domain first) picks the rules,
+            // then `verify_session` enforces them as lambda-redirect does
+            match google_auth::token_domain(&token).filter(|d| state.org.allows_domain(d)) {
+                Some(dom) => google_auth::verify_session(&token, secret.as_bytes(), &dom),
+                None => Err(GAuthError::DomainNotAllowed),
+            }
+        } else {
+            // Without ALLOWED_DOMAIN (debug mode only) any signed-in visitor is accepted
+            match state.allowed_domain.as_deref() {
+                Some(dom) => google_auth::verify_session(&token, secret.as_bytes(), dom),
+                None => google_auth::decode_session(&token, secret.as_bytes()),
+            }
+        };
+        match res {
+            Ok(u) => return Ok(u),
+            Err(GAuthError::DomainNotAllowed) => return Err(AuthHttp::Forbidden),
+            Err(e) => warn!(err=?e, "invalid session cookie"),
+        }
+    }
+    if state.auth_provider == config::AuthProvider::None && state.session_secret.is_none() {
+        return Err(AuthHttp::Unauthorized);
+    }
+    verify_request_user(
+        headers,
+        &state.auth_provider,
+        &state.allowed_domain,
+        &state.google_oauth_client_id,
+    )
+    .await
+}
+
 enum AuthHttp {
     Unauthorized,
     Forbidden,
@@ -710,6 +2014,7 @@ async fn verify_request_user(
         return Ok(VerifiedUser {
             email: email.to_string(),
             sub: "debug".into(),
+            hd: None,
         });
     }
 
@@ -740,32 +2045,140 @@ async fn verify_request_user(
 #[derive(Deserialize)]
 struct ListQuery {
     limit: Option<usize>,
+    /// Only honoured by the in-memory store; the others page with `cursor`.
     offset: Option<usize>,
+    cursor: Option<String>,
     search: Option<String>,
     created_by: Option<String>,
     group_id: Option<String>,
     include_deleted: Option<bool>,
+    sort: Option<String>,
+    order: Option<String>,
+    status: Option<String>,
+    created_from: Option<String>,
+    created_to: Option<String>,
+    updated_from: Option<String>,
+    updated_to: Option<String>,
+    expires_from: Option<String>,
+    expires_to: Option<String>,
+    host: Option<String>,
+    /// `accessible` lists every link the caller can access (own, group, shared).
+    scope: Option<String>,
 }
 
-async fn list_links(
-    State(state): State<AppState>,
-    headers: HeaderMap,
-    Query(q): Query<ListQuery>,
-) -> impl IntoResponse {
-    // Auth
-    let verified = match verify_request_user(
-        &headers,
-        &state.auth_provider,
-        &state.allowed_domain,
-        &state.google_oauth_client_id,
-    )
-    .await
-    {
-        Ok(v) => v,
-        Err(AuthHttp::Unauthorized) => {
-            return (
-                StatusCode::UNAUTHORIZED,
-                Json(http_common::json_error_with_message(
+/// The caller's roles behind a `scope=accessible` listing.
+struct AccessRoles {
+    user: UserEmail,
+    groups: std::collections::HashMap<String, GroupRole>,
+    grants: std::collections::HashMap<Slug, GroupRole>,
+}
+
+impl AccessRoles {
+    fn load(state: &AppState, user: UserEmail) -> Result<Self, CoreError> {
+        let groups = state
+            .repo
+            .get_user_groups(&user)?
+            .into_iter()
+            .map(|(g, role)| (g.id, role))
+            .collect();
+        let grants = state
+            .repo
+            .list_grants_for_user(&user)?
+            .into_iter()
+            .map(|g| (g.slug, g.role))
+            .collect();
+        Ok(Self {
+            user,
+            groups,
+            grants,
+        })
+    }
+
+    fn scope(&self) -> AccessScope {
+        AccessScope {
+            user: self.user.clone(),
+            group_ids: self.groups.keys().cloned().collect(),
+            shared_slugs: self.grants.keys().cloned().collect(),
+        }
+    }
+
+    /// Owner beats everything, else the strongest of the group role and the
+    /// per-link grant.
+    fn role_on(&self, link: &domain::ShortLink) -> &'static str {
+        if link.created_by.as_str() == self.user.as_str() {
+            return "owner";
+        }
+        let via_group = link.group_id.as_ref().and_then(|g| self.groups.get(g));
+        via_group
+            .max(self.grants.get(&link.slug))
+            .map_or("viewer", |r| r.as_str())
+    }
+}
+
+/// Apply the sort, status, date range and target host parameters of a link
+/// listing to `options`, or return the message of the 400 to send.
+fn apply_list_filters(q: &ListQuery, options: &mut domain::ListOptions) -> Result<(), String> {
+    if let Some(ref sort) = q.sort {
+        options.sort = domain::LinkSort::parse(sort)
+            .ok_or("invalid sort, use: created_at, updated_at, click_count or expires_at")?;
+    }
+    if let Some(ref order) = q.order {
+        options.direction =
+            domain::SortDirection::parse(order).ok_or("invalid order, use: asc or desc")?;
+    }
+    if let Some(ref status) = q.status {
+        options.status = Some(
+            domain::LinkStatus::parse(status)
+                .ok_or("invalid status, use: active, inactive, expired, scheduled or deleted")?,
+        );
+    }
+    let time = |name: &str, value: &Option<String>| {
+        value
+            .as_deref()
+            .map(|s| {
+                http_common::parse_rfc3339(s)
+                    .map_err(|_| format!("invalid {name}, use an RFC 3339 time"))
+            })
+            .transpose()
+    };
+    options.created = domain::TimeRange {
+        from: time("created_from", &q.created_from)?,
+        to: time("created_to", &q.created_to)?,
+    };
+    options.updated = domain::TimeRange {
+        from: time("updated_from", &q.updated_from)?,
+        to: time("updated_to", &q.updated_to)?,
+    };
+    options.expires = domain::TimeRange {
+        from: time("expires_from", &q.expires_from)?,
+        to: time("expires_to", &q.expires_to)?,
+    };
+    if let Some(ref host) = q.host {
+        options.target_host =
+            Some(domain::normalize_host(host).map_err(|_| "invalid host".to_string())?);
+    }
+    Ok(())
+}
+
+async fn list_links(
+    Tenant(state): Tenant,
+    headers: HeaderMap,
+    Query(q): Query<ListQuery>,
+) -> impl IntoResponse {
+    // Auth
+    let verified = match verify_request_user(
+        &headers,
+        &state.auth_provider,
+        &state.allowed_domain,
+        &state.google_oauth_client_id,
+    )
+    .await
+    {
+        Ok(v) => v,
+        Err(AuthHttp::Unauthorized) => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(http_common::json_error_with_message(
                     "unauthorized",
                     "missing or invalid token",
                 )),
@@ -805,25 +2218,75 @@ async fn list_links(
         .created_by
         .as_ref()
         .and_then(|e| UserEmail::new(e.clone()).ok());
-    let options = domain::ListOptions {
+    let mut options = domain::ListOptions {
         limit,
         offset,
         search: q.search.clone(),
         created_by,
         group_id: q.group_id.clone(),
         include_deleted: q.include_deleted.unwrap_or(false),
+        cursor: q.cursor.clone(),
+        now: Some(state.clock.now()),
+        ..Default::default()
+    };
+    if let Err(msg) = apply_list_filters(&q, &mut options) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(http_common::json_error_with_message(
+                "invalid_request",
+                &msg,
+            )),
+        )
+            .into_response();
+    }
+    let access = match q.scope.as_deref() {
+        Some("accessible") => {
+            let loaded = UserEmail::new(verified.email.clone())
+                .and_then(|user| AccessRoles::load(&state, user));
+            match loaded {
+                Ok(access) => {
+                    options.accessible_to = Some(access.scope());
+                    Some(access)
+                }
+                Err(e) => {
+                    error!(err=?e, "load access scope error");
+                    return (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(http_common::json_error_with_message(
+                            "internal",
+                            "server error",
+                        )),
+                    )
+                        .into_response();
+                }
+            }
+        }
+        _ => None,
     };
 
-    match state.repo.list_paginated(&options) {
+    // Free-text searches without an explicit order come back best match first
+    let ranked = q.sort.is_none()
+        && q.cursor.is_none()
+        && q.search.as_deref().is_some_and(|s| !s.trim().is_empty());
+    let result = if ranked {
+        state.repo.search_ranked(&options)
+    } else {
+        state.repo.list_paginated(&options).map(Into::into)
+    };
+    match result {
         Ok(result) => {
             let links: Vec<LinkOut> = result
                 .items
                 .into_iter()
-                .map(|l| link_to_out(l, &headers, &state.shortlink_domain))
+                .map(|hit| LinkOut {
+                    snippet: hit.snippet,
+                    role: access.as_ref().map(|a| a.role_on(&hit.link)),
+                    ..link_to_out(hit.link, &headers, &state.shortlink_domain)
+                })
                 .collect();
             let user_info = UserInfo {
                 email: verified.email.clone(),
-                is_admin: is_admin(&verified.email),
+                is_admin: state.is_admin(&verified.email),
             };
             (
                 StatusCode::OK,
@@ -831,11 +2294,20 @@ async fn list_links(
                     links,
                     total: result.total,
                     has_more: result.has_more,
+                    next_cursor: result.next_cursor,
                     user: Some(user_info),
                 }),
             )
                 .into_response()
         }
+        Err(CoreError::InvalidCursor) => (
+            StatusCode::BAD_REQUEST,
+            Json(http_common::json_error_with_message(
+                "invalid_request",
+                "invalid cursor",
+            )),
+        )
+            .into_response(),
         Err(e) => {
             error!(err=?e, "list error");
             (
@@ -859,7 +2331,7 @@ async fn preflight_link() -> impl IntoResponse {
 }
 
 async fn update_link(
-    State(state): State<AppState>,
+    Tenant(state): Tenant,
     headers: HeaderMap,
     Path(slug_str): Path<String>,
     Json(body): Json<UpdateLinkReq>,
@@ -897,7 +2369,7 @@ async fn update_link(
     };
 
     // Parse slug
-    let slug = match Slug::new(slug_str.clone()) {
+    let slug = match Slug::from_key(&slug_str) {
         Ok(s) => s,
         Err(_) => {
             return (
@@ -934,9 +2406,12 @@ async fn update_link(
         }
     };
 
-    // Check ownership or admin
-    let user_is_admin = is_admin(&verified.email);
-    if link.created_by.as_str() != verified.email && !user_is_admin {
+    // Check ownership, admin, or an editor grant on this link
+    let user_is_admin = state.is_admin(&verified.email);
+    if link.created_by.as_str() != verified.email
+        && !user_is_admin
+        && !has_editor_grant(&state, &link.slug, &verified.email)
+    {
         return (
             StatusCode::FORBIDDEN,
             Json(http_common::json_error_with_message(
@@ -948,6 +2423,8 @@ async fn update_link(
     }
 
     // Apply updates
+    let previous_group = link.group_id.clone();
+    let url_changed = body.original_url.is_some();
     if let Some(url) = body.original_url {
         if let Err(e) = domain::validate::validate_original_url(&url) {
             return (
@@ -979,6 +2456,29 @@ async fn update_link(
     if let Some(gid) = body.group_id {
         link.group_id = gid;
     }
+    if let Some(v) = body.visibility {
+        link.visibility = match LinkVisibility::parse(&v) {
+            Some(v) => v,
+            None => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(http_common::json_error_with_message(
+                        "invalid_request",
+                        "invalid visibility, use: public or workspace",
+                    )),
+                )
+                    .into_response()
+            }
+        };
+    }
+    let moved_in = link.group_id != previous_group;
+    if url_changed || moved_in {
+        if let Some(resp) =
+            enforce_link_settings(&state, &mut link, GroupCheck::Update { moved_in })
+        {
+            return resp;
+        }
+    }
     link.updated_at = Some(state.clock.now());
 
     // Save
@@ -1011,7 +2511,7 @@ async fn update_link(
 }
 
 async fn delete_link(
-    State(state): State<AppState>,
+    Tenant(state): Tenant,
     headers: HeaderMap,
     Path(slug_str): Path<String>,
 ) -> impl IntoResponse {
@@ -1048,7 +2548,7 @@ async fn delete_link(
     };
 
     // Parse slug
-    let slug = match Slug::new(slug_str.clone()) {
+    let slug = match Slug::from_key(&slug_str) {
         Ok(s) => s,
         Err(_) => {
             return (
@@ -1085,9 +2585,12 @@ async fn delete_link(
         }
     };
 
-    // Check ownership or admin
-    let user_is_admin = is_admin(&verified.email);
-    if link.created_by.as_str() != verified.email && !user_is_admin {
+    // Check ownership, admin, or an editor grant on this link
+    let user_is_admin = state.is_admin(&verified.email);
+    if link.created_by.as_str() != verified.email
+        && !user_is_admin
+        && !has_editor_grant(&state, &link.slug, &verified.email)
+    {
         return (
             StatusCode::FORBIDDEN,
             Json(http_common::json_error_with_message(
@@ -1100,7 +2603,7 @@ async fn delete_link(
 
     // Soft delete
     let deleted_at = state.clock.now();
-    match state.repo.delete(&slug, deleted_at) {
+    match state.repo.delete(&link.slug, deleted_at) {
         Ok(()) => {
             info!(slug = %slug_str, "delete ok");
             (StatusCode::NO_CONTENT, ()).into_response()
@@ -1124,12 +2627,122 @@ async fn delete_link(
     }
 }
 
-async fn bulk_delete_links(
-    State(state): State<AppState>,
+/// Which group rules apply to a link being written.
+enum GroupCheck {
+    /// New link: fill in group defaults (and prefix a generated slug), then
+    /// check the rules and the link limit.
+    Create { generated_slug: bool },
+    /// Existing link whose URL or group changed; the limit only applies when it
+    /// is moved into the group.
+    Update { moved_in: bool },
+}
+
+/// Apply the organization's link rules and the settings of the link's group.
+/// Returns the error response to send when the link breaks one of them.
+fn enforce_link_settings(
+    state: &AppState,
+    link: &mut domain::ShortLink,
+    check: GroupCheck,
+) -> Option<Response> {
+    if let Err(e) = state.org.settings.check_link(link) {
+        return Some(
+            (
+                StatusCode::BAD_REQUEST,
+                Json(http_common::json_error_with_message(
+                    "invalid_request",
+                    &format!("{}", e),
+                )),
+            )
+                .into_response(),
+        );
+    }
+    // Links outside a group have no further rules
+    let group_id = link.group_id.clone()?;
+    let internal = || {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(http_common::json_error_with_message(
+                "internal",
+                "server error",
+            )),
+        )
+            .into_response()
+    };
+    let group = match state.repo.get_group(&group_id) {
+        Ok(Some(g)) => g,
+        Ok(None) => {
+            return Some(
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(http_common::json_error_with_message(
+                        "invalid_request",
+                        "group not found",
+                    )),
+                )
+                    .into_response(),
+            )
+        }
+        Err(e) => {
+            error!(err=?e, "get group error");
+            return Some(internal());
+        }
+    };
+    let settings = &group.settings;
+
+    let counts_towards_limit = match check {
+        GroupCheck::Create { generated_slug } => {
+            if generated_slug {
+                link.slug = settings.prefix_slug(link.slug.clone());
+            }
+            settings.apply_defaults(link);
+            true
+        }
+        GroupCheck::Update { moved_in } => moved_in,
+    };
+
+    if let Err(e) = settings.check_link(link) {
+        return Some(
+            (
+                StatusCode::BAD_REQUEST,
+                Json(http_common::json_error_with_message(
+                    "invalid_request",
+                    &format!("{}", e),
+                )),
+            )
+                .into_response(),
+        );
+    }
+
+    if let (Some(max), true) = (settings.max_links, counts_towards_limit) {
+        match state.repo.list_by_group(&group_id, max as usize) {
+            Ok(links) if links.len() >= max as usize => {
+                return Some(
+                    (
+                        StatusCode::CONFLICT,
+                        Json(http_common::json_error_with_message(
+                            "conflict",
+                            &format!("group link limit of {max} reached"),
+                        )),
+                    )
+                        .into_response(),
+                )
+            }
+            Ok(_) => {}
+            Err(e) => {
+                error!(err=?e, "list by group error");
+                return Some(internal());
+            }
+        }
+    }
+    None
+}
+
+async fn update_group(
+    Tenant(state): Tenant,
     headers: HeaderMap,
-    Json(body): Json<BulkSlugsReq>,
+    Path(group_id): Path<String>,
+    Json(body): Json<UpdateGroupReq>,
 ) -> impl IntoResponse {
-    // Auth
     let verified = match verify_request_user(
         &headers,
         &state.auth_provider,
@@ -1161,44 +2774,88 @@ async fn bulk_delete_links(
         }
     };
 
-    // Only admins can bulk delete
-    if !is_admin(&verified.email) {
+    let mut group = match state.repo.get_group(&group_id) {
+        Ok(Some(g)) => g,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(http_common::json_err("not_found")),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            error!(err=?e, "get group error");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(http_common::json_error_with_message(
+                    "internal",
+                    "server error",
+                )),
+            )
+                .into_response();
+        }
+    };
+
+    // Group admins (and system admins) manage the group
+    let can_manage = state.is_admin(&verified.email)
+        || UserEmail::new(verified.email.clone())
+            .ok()
+            .and_then(|u| state.repo.effective_role(&group_id, &u).ok().flatten())
+            .is_some_and(|role| role.can_manage());
+    if !can_manage {
         return (
             StatusCode::FORBIDDEN,
             Json(http_common::json_error_with_message(
                 "forbidden",
-                "admin required for bulk operations",
+                "admin role required to update group",
             )),
         )
             .into_response();
     }
 
-    // Parse slugs
-    let slugs: Vec<Slug> = body
-        .slugs
-        .iter()
-        .filter_map(|s| Slug::new(s.clone()).ok())
-        .collect();
-
-    if slugs.is_empty() {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(http_common::json_error_with_message(
-                "invalid_request",
-                "no valid slugs provided",
-            )),
-        )
-            .into_response();
+    if let Some(name) = body.name {
+        if name.is_empty() || name.len() > 100 {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(http_common::json_error_with_message(
+                    "invalid_request",
+                    "name must be 1-100 characters",
+                )),
+            )
+                .into_response();
+        }
+        group.name = name;
+    }
+    if let Some(desc) = body.description {
+        group.description = desc;
+    }
+    if let Some(settings) = body.settings {
+        let settings = GroupSettings::from(settings);
+        if let Err(e) = settings.validate() {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(http_common::json_error_with_message(
+                    "invalid_request",
+                    &format!("{}", e),
+                )),
+            )
+                .into_response();
+        }
+        group.settings = settings;
     }
 
-    let deleted_at = state.clock.now();
-    match state.repo.bulk_delete(&slugs, deleted_at) {
-        Ok(affected) => {
-            info!(count = affected, "bulk delete ok");
-            (StatusCode::OK, Json(BulkResultOut { affected })).into_response()
+    match state.repo.update_group(&group) {
+        Ok(()) => {
+            info!(group_id = %group_id, "group updated");
+            (StatusCode::OK, Json(group_to_out(&group))).into_response()
         }
+        Err(CoreError::NotFound) => (
+            StatusCode::NOT_FOUND,
+            Json(http_common::json_err("not_found")),
+        )
+            .into_response(),
         Err(e) => {
-            error!(err=?e, "bulk delete error");
+            error!(err=?e, "update group error");
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(http_common::json_error_with_message(
@@ -1211,102 +2868,136 @@ async fn bulk_delete_links(
     }
 }
 
-async fn bulk_activate_links(
-    State(state): State<AppState>,
-    headers: HeaderMap,
-    Json(body): Json<BulkSlugsReq>,
-) -> impl IntoResponse {
-    bulk_update_active_impl(state, headers, body, true).await
-}
-
-async fn bulk_deactivate_links(
-    State(state): State<AppState>,
-    headers: HeaderMap,
-    Json(body): Json<BulkSlugsReq>,
-) -> impl IntoResponse {
-    bulk_update_active_impl(state, headers, body, false).await
+/// Whether `email` was granted editor access to the link directly.
+fn has_editor_grant(state: &AppState, slug: &Slug, email: &str) -> bool {
+    let Ok(user_email) = UserEmail::new(email.to_string()) else {
+        return false;
+    };
+    matches!(
+        state.repo.get_grant(slug, &user_email),
+        Ok(Some(grant)) if grant.role.can_edit()
+    )
 }
 
-async fn bulk_update_active_impl(
-    state: AppState,
+async fn list_link_collaborators(
+    Tenant(state): Tenant,
     headers: HeaderMap,
-    body: BulkSlugsReq,
-    is_active: bool,
+    Path(slug_str): Path<String>,
 ) -> impl IntoResponse {
-    // Auth
-    let verified = match verify_request_user(
-        &headers,
-        &state.auth_provider,
-        &state.allowed_domain,
-        &state.google_oauth_client_id,
-    )
-    .await
-    {
+    let (verified, link) = match load_link_for_sharing(&state, &headers, &slug_str).await {
         Ok(v) => v,
-        Err(AuthHttp::Unauthorized) => {
-            return (
-                StatusCode::UNAUTHORIZED,
-                Json(http_common::json_error_with_message(
-                    "unauthorized",
-                    "missing or invalid token",
-                )),
-            )
-                .into_response()
-        }
-        Err(AuthHttp::Forbidden) => {
+        Err(resp) => return resp,
+    };
+
+    let grants = match state.repo.list_grants(&link.slug) {
+        Ok(g) => g,
+        Err(e) => {
+            error!(err=?e, "list grants error");
             return (
-                StatusCode::FORBIDDEN,
+                StatusCode::INTERNAL_SERVER_ERROR,
                 Json(http_common::json_error_with_message(
-                    "forbidden",
-                    "domain not allowed",
+                    "internal",
+                    "server error",
                 )),
             )
-                .into_response()
+                .into_response();
         }
     };
 
-    // Only admins can bulk update
-    if !is_admin(&verified.email) {
+    // Those who manage collaborators and the collaborators themselves may see the list
+    let is_collaborator = grants
+        .iter()
+        .any(|g| g.user_email.as_str() == verified.email);
+    if !is_collaborator && !can_manage_collaborators(&state, &link, &verified.email) {
         return (
             StatusCode::FORBIDDEN,
             Json(http_common::json_error_with_message(
                 "forbidden",
-                "admin required for bulk operations",
+                "not link owner",
             )),
         )
             .into_response();
     }
 
-    // Parse slugs
-    let slugs: Vec<Slug> = body
-        .slugs
-        .iter()
-        .filter_map(|s| Slug::new(s.clone()).ok())
-        .collect();
-
-    if slugs.is_empty() {
+    let out = CollaboratorListOut {
+        collaborators: grants.iter().map(grant_to_out).collect(),
+    };
+    (StatusCode::OK, Json(out)).into_response()
+}
+
+async fn add_link_collaborator(
+    Tenant(state): Tenant,
+    headers: HeaderMap,
+    Path(slug_str): Path<String>,
+    Json(body): Json<AddCollaboratorReq>,
+) -> impl IntoResponse {
+    let (verified, link) = match load_link_for_sharing(&state, &headers, &slug_str).await {
+        Ok(v) => v,
+        Err(resp) => return resp,
+    };
+
+    if !can_manage_collaborators(&state, &link, &verified.email) {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(http_common::json_error_with_message(
+                "forbidden",
+                "not link owner",
+            )),
+        )
+            .into_response();
+    }
+
+    let Ok(collaborator) = UserEmail::new(body.email) else {
         return (
             StatusCode::BAD_REQUEST,
             Json(http_common::json_error_with_message(
                 "invalid_request",
-                "no valid slugs provided",
+                "invalid email",
             )),
         )
             .into_response();
-    }
+    };
+    let role = match GroupRole::parse(&body.role) {
+        Some(r @ (GroupRole::Viewer | GroupRole::Editor)) => r,
+        _ => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(http_common::json_error_with_message(
+                    "invalid_request",
+                    "invalid role, use: viewer or editor",
+                )),
+            )
+                .into_response()
+        }
+    };
+    let granted_by = match UserEmail::new(verified.email.clone()) {
+        Ok(u) => u,
+        Err(_) => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(http_common::json_error_with_message(
+                    "unauthorized",
+                    "invalid user email",
+                )),
+            )
+                .into_response()
+        }
+    };
 
-    let updated_at = state.clock.now();
-    match state.repo.bulk_update_active(&slugs, is_active, updated_at) {
-        Ok(affected) => {
-            info!(
-                count = affected,
-                is_active = is_active,
-                "bulk update active ok"
-            );
-            (StatusCode::OK, Json(BulkResultOut { affected })).into_response()
+    let grant = LinkGrant {
+        slug: link.slug,
+        user_email: collaborator,
+        role,
+        granted_at: state.clock.now(),
+        granted_by,
+    };
+    match state.repo.put_grant(grant.clone()) {
+        Ok(()) => {
+            info!(slug = %slug_str, collaborator = %grant.user_email.as_str(), "collaborator added");
+            (StatusCode::CREATED, Json(grant_to_out(&grant))).into_response()
         }
         Err(e) => {
-            error!(err=?e, "bulk update error");
+            error!(err=?e, "put grant error");
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(http_common::json_error_with_message(
@@ -1319,140 +3010,3010 @@ async fn bulk_update_active_impl(
     }
 }
 
-async fn get_me(State(state): State<AppState>, headers: HeaderMap) -> impl IntoResponse {
-    // Auth
-    let verified = match verify_request_user(
-        &headers,
-        &state.auth_provider,
-        &state.allowed_domain,
-        &state.google_oauth_client_id,
-    )
-    .await
-    {
+async fn remove_link_collaborator(
+    Tenant(state): Tenant,
+    headers: HeaderMap,
+    Path((slug_str, email_str)): Path<(String, String)>,
+) -> impl IntoResponse {
+    let (verified, link) = match load_link_for_sharing(&state, &headers, &slug_str).await {
         Ok(v) => v,
-        Err(AuthHttp::Unauthorized) => {
-            return (
-                StatusCode::UNAUTHORIZED,
-                Json(http_common::json_error_with_message(
-                    "unauthorized",
-                    "missing or invalid token",
-                )),
-            )
-                .into_response()
+        Err(resp) => return resp,
+    };
+
+    let Ok(collaborator) = UserEmail::new(email_str) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(http_common::json_error_with_message(
+                "invalid_request",
+                "invalid email",
+            )),
+        )
+            .into_response();
+    };
+
+    // Collaborators may always remove themselves
+    let is_self = collaborator.as_str() == verified.email;
+    if !is_self && !can_manage_collaborators(&state, &link, &verified.email) {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(http_common::json_error_with_message(
+                "forbidden",
+                "not link owner",
+            )),
+        )
+            .into_response();
+    }
+
+    match state.repo.remove_grant(&link.slug, &collaborator) {
+        Ok(()) => {
+            info!(slug = %slug_str, collaborator = %collaborator.as_str(), "collaborator removed");
+            (StatusCode::NO_CONTENT, ()).into_response()
         }
-        Err(AuthHttp::Forbidden) => {
-            return (
-                StatusCode::FORBIDDEN,
+        Err(CoreError::NotFound) => (
+            StatusCode::NOT_FOUND,
+            Json(http_common::json_err("not_found")),
+        )
+            .into_response(),
+        Err(e) => {
+            error!(err=?e, "remove grant error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
                 Json(http_common::json_error_with_message(
-                    "forbidden",
-                    "domain not allowed",
+                    "internal",
+                    "server error",
                 )),
             )
                 .into_response()
         }
-    };
+    }
+}
 
-    let user_info = UserInfo {
-        email: verified.email.clone(),
-        is_admin: is_admin(&verified.email),
-    };
-    (StatusCode::OK, Json(user_info)).into_response()
+/// Whether `email` may manage the link's collaborators; see
+/// [`domain::ShortLink::can_manage_collaborators`].
+fn can_manage_collaborators(state: &AppState, link: &domain::ShortLink, email: &str) -> bool {
+    let allowed = link.can_manage_collaborators(email, state.is_admin(email), |gid| {
+        match UserEmail::new(email) {
+            Ok(user) => state.repo.effective_role(gid, &user),
+            Err(_) => Ok(None),
+        }
+    });
+    allowed.unwrap_or_else(|e| {
+        error!(err=?e, "effective role error");
+        false
+    })
 }
 
-/// Build short URL using shortlink_domain from config, or Host header as fallback.
-fn build_short_url(headers: &HeaderMap, slug: &str, shortlink_domain: &Option<String>) -> String {
-    let host = shortlink_domain
-        .as_deref()
-        .or_else(|| headers.get("host").and_then(|v| v.to_str().ok()))
-        .unwrap_or("");
-    http_common::build_short_url_from_host(host, slug)
+/// Whether `email` may change the link: its owner, an admin or an editor.
+fn can_edit_link(state: &AppState, link: &domain::ShortLink, email: &str) -> bool {
+    link.created_by.as_str() == email
+        || state.is_admin(email)
+        || has_editor_grant(state, &link.slug, email)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use axum::body::Body;
-    use axum::http::{header, Request};
-    use tower::util::ServiceExt;
+/// Parse a new slug for `link`; it stays on the link's short domain.
+fn parse_new_slug(link: &domain::ShortLink, value: &str) -> Option<Slug> {
+    if !http_common::is_valid_alias(value) {
+        return None;
+    }
+    Slug::new(value)
+        .ok()
+        .map(|s| s.on_domain(link.slug.domain().map(str::to_string)))
+}
 
-    fn app() -> Router {
-        let state = AppState {
-            repo: AnyRepo::memory(),
-            slugger: Base62SlugGenerator::new(5),
-            clock: StdClock,
-            auth_provider: config::AuthProvider::None,
-            allowed_domain: None,
-            google_oauth_client_id: None,
-            shortlink_domain: None,
-        };
-        Router::new()
-            .route("/:slug", get(get_slug))
-            .route(
-                "/api/links",
-                post(create_link).get(list_links).options(preflight_links),
+/// Why `email` may not use `slug`: it lies in a namespace reserved by a group
+/// the caller is not an editor of. Admins may use any namespace.
+fn namespace_violation(
+    state: &AppState,
+    slug: &Slug,
+    email: &str,
+) -> Result<Option<String>, CoreError> {
+    if state.is_admin(email) {
+        return Ok(None);
+    }
+    let Some(namespace) = state.repo.reserved_namespace(slug)? else {
+        return Ok(None);
+    };
+    let role = match UserEmail::new(email) {
+        Ok(u) => state.repo.effective_role(&namespace.group_id, &u)?,
+        Err(_) => None,
+    };
+    if role.is_some_and(|r| r.can_edit()) {
+        return Ok(None);
+    }
+    Ok(Some(format!(
+        "namespace {} is reserved by another group",
+        namespace.path
+    )))
+}
+
+/// Refuse `slug` when it lies in a namespace `email` may not use.
+fn check_namespace(state: &AppState, slug: &Slug, email: &str) -> Option<Response> {
+    match namespace_violation(state, slug, email) {
+        Ok(None) => None,
+        Ok(Some(message)) => Some(
+            (
+                StatusCode::FORBIDDEN,
+                Json(http_common::json_error_with_message("forbidden", &message)),
             )
-            .with_state(state)
+                .into_response(),
+        ),
+        Err(e) => {
+            error!(err=?e, "namespace check error");
+            Some(
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(http_common::json_error_with_message(
+                        "internal",
+                        "server error",
+                    )),
+                )
+                    .into_response(),
+            )
+        }
     }
+}
 
-    #[tokio::test]
-    async fn create_and_resolve_flow() {
-        let router = app();
+async fn rename_link(
+    Tenant(state): Tenant,
+    headers: HeaderMap,
+    Path(slug_str): Path<String>,
+    Json(body): Json<RenameLinkReq>,
+) -> impl IntoResponse {
+    let (verified, link) = match load_link_for_sharing(&state, &headers, &slug_str).await {
+        Ok(v) => v,
+        Err(resp) => return resp,
+    };
+    if !can_edit_link(&state, &link, &verified.email) {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(http_common::json_error_with_message(
+                "forbidden",
+                "not link owner",
+            )),
+        )
+            .into_response();
+    }
+    let Some(new_slug) = parse_new_slug(&link, &body.slug) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(http_common::json_error_with_message(
+                "invalid_request",
+                "slug must be 3-32 characters, alphanumeric with hyphens/underscores (namespaces separated by /)",
+            )),
+        )
+            .into_response();
+    };
+    if let Some(resp) = check_namespace(&state, &new_slug, &verified.email) {
+        return resp;
+    }
 
-        // Create
-        let req = Request::builder()
-            .method("POST")
-            .uri("/api/links")
-            .header("content-type", "application/json")
-            .header("X-Debug-User", "user@example.com")
-            .body(Body::from("{\"original_url\":\"https://example.com\"}"))
-            .unwrap();
+    let internal = |e: CoreError| {
+        error!(err=?e, "rename error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(http_common::json_error_with_message(
+                "internal",
+                "server error",
+            )),
+        )
+            .into_response()
+    };
+    let renamed = match state.repo.rename(&link.slug, &new_slug) {
+        Ok(l) => l,
+        Err(CoreError::AlreadyExists) => {
+            return (
+                StatusCode::CONFLICT,
+                Json(http_common::json_error_with_message(
+                    "conflict",
+                    "slug already exists",
+                )),
+            )
+                .into_response()
+        }
+        Err(CoreError::NotFound) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(http_common::json_err("not_found")),
+            )
+                .into_response()
+        }
+        Err(e) => return internal(e),
+    };
 
-        let resp = router.clone().oneshot(req).await.unwrap();
-        assert_eq!(resp.status(), StatusCode::CREATED);
+    info!(slug = %link.slug.key(), renamed_to = %renamed.slug.key(), "rename ok");
+    (
+        StatusCode::OK,
+        Json(link_to_out(renamed, &headers, &state.shortlink_domain)),
+    )
+        .into_response()
+}
 
-        // List
-        let resp = router
-            .clone()
-            .oneshot(
-                Request::builder()
-                    .uri("/api/links")
-                    .header("X-Debug-User", "user@example.com")
-                    .body(Body::empty())
-                    .unwrap(),
+async fn list_link_aliases(
+    Tenant(state): Tenant,
+    headers: HeaderMap,
+    Path(slug_str): Path<String>,
+) -> impl IntoResponse {
+    let (verified, link) = match load_link_for_sharing(&state, &headers, &slug_str).await {
+        Ok(v) => v,
+        Err(resp) => return resp,
+    };
+    let user_email = UserEmail::new(verified.email.clone()).ok();
+    let is_collaborator =
+        user_email.is_some_and(|u| matches!(state.repo.get_grant(&link.slug, &u), Ok(Some(_))));
+    if !can_edit_link(&state, &link, &verified.email) && !is_collaborator {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(http_common::json_error_with_message(
+                "forbidden",
+                "not link owner",
+            )),
+        )
+            .into_response();
+    }
+    match state.repo.list_aliases(&link.slug) {
+        Ok(aliases) => {
+            let out = AliasListOut {
+                aliases: aliases
+                    .iter()
+                    .map(|a| AliasOut {
+                        slug: a.key().into_owned(),
+                        short_url: build_short_url(&headers, a, &state.shortlink_domain),
+                    })
+                    .collect(),
+            };
+            (StatusCode::OK, Json(out)).into_response()
+        }
+        Err(e) => {
+            error!(err=?e, "list aliases error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(http_common::json_error_with_message(
+                    "internal",
+                    "server error",
+                )),
             )
-            .await
-            .unwrap();
-        assert_eq!(resp.status(), StatusCode::OK);
+                .into_response()
+        }
+    }
+}
 
-        // Resolve should redirect (308)
-        // We don't know slug from response easily here without parsing; call service directly for a known slug path.
-        // Instead, create with custom slug and then resolve it
+async fn add_link_alias(
+    Tenant(state): Tenant,
+    headers: HeaderMap,
+    Path(slug_str): Path<String>,
+    Json(body): Json<AddAliasReq>,
+) -> impl IntoResponse {
+    let (verified, link) = match load_link_for_sharing(&state, &headers, &slug_str).await {
+        Ok(v) => v,
+        Err(resp) => return resp,
+    };
+    if !can_edit_link(&state, &link, &verified.email) {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(http_common::json_error_with_message(
+                "forbidden",
+                "not link owner",
+            )),
+        )
+            .into_response();
+    }
+    let Some(alias) = parse_new_slug(&link, &body.alias) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(http_common::json_error_with_message(
+                "invalid_request",
+                "alias must be 3-32 characters, alphanumeric with hyphens/underscores (namespaces separated by /)",
+            )),
+        )
+            .into_response();
+    };
+    if let Some(resp) = check_namespace(&state, &alias, &verified.email) {
+        return resp;
+    }
+    match state.repo.add_alias(&alias, &link.slug) {
+        Ok(()) => {
+            info!(slug = %link.slug.key(), alias = %alias.key(), "alias added");
+            let out = AliasOut {
+                slug: alias.key().into_owned(),
+                short_url: build_short_url(&headers, &alias, &state.shortlink_domain),
+            };
+            (StatusCode::CREATED, Json(out)).into_response()
+        }
+        Err(CoreError::AlreadyExists) => (
+            StatusCode::CONFLICT,
+            Json(http_common::json_error_with_message(
+                "conflict",
+                "alias already exists",
+            )),
+        )
+            .into_response(),
+        Err(CoreError::NotFound) => (
+            StatusCode::NOT_FOUND,
+            Json(http_common::json_err("not_found")),
+        )
+            .into_response(),
+        Err(e) => {
+            error!(err=?e, "add alias error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(http_common::json_error_with_message(
+                    "internal",
+                    "server error",
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+async fn remove_link_alias(
+    Tenant(state): Tenant,
+    headers: HeaderMap,
+    Path((slug_str, alias_str)): Path<(String, String)>,
+) -> impl IntoResponse {
+    let (verified, link) = match load_link_for_sharing(&state, &headers, &slug_str).await {
+        Ok(v) => v,
+        Err(resp) => return resp,
+    };
+    if !can_edit_link(&state, &link, &verified.email) {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(http_common::json_error_with_message(
+                "forbidden",
+                "not link owner",
+            )),
+        )
+            .into_response();
+    }
+    // Only aliases of this link can be removed through it
+    let alias = match Slug::from_key(&alias_str) {
+        Ok(a) => a,
+        Err(_) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(http_common::json_err("not_found")),
+            )
+                .into_response()
+        }
+    };
+    let result = state.repo.list_aliases(&link.slug).and_then(|aliases| {
+        if aliases.contains(&alias) {
+            state.repo.remove_alias(&alias)
+        } else {
+            Err(CoreError::NotFound)
+        }
+    });
+    match result {
+        Ok(()) => {
+            info!(slug = %link.slug.key(), alias = %alias.key(), "alias removed");
+            (StatusCode::NO_CONTENT, ()).into_response()
+        }
+        Err(CoreError::NotFound) => (
+            StatusCode::NOT_FOUND,
+            Json(http_common::json_err("not_found")),
+        )
+            .into_response(),
+        Err(e) => {
+            error!(err=?e, "remove alias error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(http_common::json_error_with_message(
+                    "internal",
+                    "server error",
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Authenticate the caller and load the link (following aliases) for the
+/// collaborator and alias endpoints.
+async fn load_link_for_sharing(
+    state: &AppState,
+    headers: &HeaderMap,
+    slug_str: &str,
+) -> Result<(VerifiedUser, domain::ShortLink), Response> {
+    let verified = match verify_request_user(
+        headers,
+        &state.auth_provider,
+        &state.allowed_domain,
+        &state.google_oauth_client_id,
+    )
+    .await
+    {
+        Ok(v) => v,
+        Err(AuthHttp::Unauthorized) => {
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                Json(http_common::json_error_with_message(
+                    "unauthorized",
+                    "missing or invalid token",
+                )),
+            )
+                .into_response())
+        }
+        Err(AuthHttp::Forbidden) => {
+            return Err((
+                StatusCode::FORBIDDEN,
+                Json(http_common::json_error_with_message(
+                    "forbidden",
+                    "domain not allowed",
+                )),
+            )
+                .into_response())
+        }
+    };
+
+    let Ok(slug) = Slug::from_key(slug_str) else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(http_common::json_error_with_message(
+                "invalid_request",
+                "invalid slug",
+            )),
+        )
+            .into_response());
+    };
+
+    match state.repo.get(&slug) {
+        Ok(Some(link)) => Ok((verified, link)),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(http_common::json_err("not_found")),
+        )
+            .into_response()),
+        Err(e) => {
+            error!(err=?e, "get error");
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(http_common::json_error_with_message(
+                    "internal",
+                    "server error",
+                )),
+            )
+                .into_response())
+        }
+    }
+}
+
+async fn bulk_delete_links(
+    Tenant(state): Tenant,
+    headers: HeaderMap,
+    Json(body): Json<BulkSlugsReq>,
+) -> impl IntoResponse {
+    // Auth
+    let verified = match verify_request_user(
+        &headers,
+        &state.auth_provider,
+        &state.allowed_domain,
+        &state.google_oauth_client_id,
+    )
+    .await
+    {
+        Ok(v) => v,
+        Err(AuthHttp::Unauthorized) => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(http_common::json_error_with_message(
+                    "unauthorized",
+                    "missing or invalid token",
+                )),
+            )
+                .into_response()
+        }
+        Err(AuthHttp::Forbidden) => {
+            return (
+                StatusCode::FORBIDDEN,
+                Json(http_common::json_error_with_message(
+                    "forbidden",
+                    "domain not allowed",
+                )),
+            )
+                .into_response()
+        }
+    };
+
+    // Only admins can bulk delete
+    if !state.is_admin(&verified.email) {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(http_common::json_error_with_message(
+                "forbidden",
+                "admin required for bulk operations",
+            )),
+        )
+            .into_response();
+    }
+
+    // Parse slugs
+    let slugs: Vec<Slug> = body
+        .slugs
+        .iter()
+        .filter_map(|s| Slug::from_key(s).ok())
+        .collect();
+
+    if slugs.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(http_common::json_error_with_message(
+                "invalid_request",
+                "no valid slugs provided",
+            )),
+        )
+            .into_response();
+    }
+
+    let deleted_at = state.clock.now();
+    match state.repo.bulk_delete(&slugs, deleted_at) {
+        Ok(affected) => {
+            info!(count = affected, "bulk delete ok");
+            (StatusCode::OK, Json(BulkResultOut { affected })).into_response()
+        }
+        Err(e) => {
+            error!(err=?e, "bulk delete error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(http_common::json_error_with_message(
+                    "internal",
+                    "server error",
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+async fn bulk_activate_links(
+    Tenant(state): Tenant,
+    headers: HeaderMap,
+    Json(body): Json<BulkSlugsReq>,
+) -> impl IntoResponse {
+    bulk_update_active_impl(state, headers, body, true).await
+}
+
+async fn bulk_deactivate_links(
+    Tenant(state): Tenant,
+    headers: HeaderMap,
+    Json(body): Json<BulkSlugsReq>,
+) -> impl IntoResponse {
+    bulk_update_active_impl(state, headers, body, false).await
+}
+
+async fn bulk_update_active_impl(
+    state: AppState,
+    headers: HeaderMap,
+    body: BulkSlugsReq,
+    is_active: bool,
+) -> impl IntoResponse {
+    // Auth
+    let verified = match verify_request_user(
+        &headers,
+        &state.auth_provider,
+        &state.allowed_domain,
+        &state.google_oauth_client_id,
+    )
+    .await
+    {
+        Ok(v) => v,
+        Err(AuthHttp::Unauthorized) => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(http_common::json_error_with_message(
+                    "unauthorized",
+                    "missing or invalid token",
+                )),
+            )
+                .into_response()
+        }
+        Err(AuthHttp::Forbidden) => {
+            return (
+                StatusCode::FORBIDDEN,
+                Json(http_common::json_error_with_message(
+                    "forbidden",
+                    "domain not allowed",
+                )),
+            )
+                .into_response()
+        }
+    };
+
+    // Only admins can bulk update
+    if !state.is_admin(&verified.email) {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(http_common::json_error_with_message(
+                "forbidden",
+                "admin required for bulk operations",
+            )),
+        )
+            .into_response();
+    }
+
+    // Parse slugs
+    let slugs: Vec<Slug> = body
+        .slugs
+        .iter()
+        .filter_map(|s| Slug::from_key(s).ok())
+        .collect();
+
+    if slugs.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(http_common::json_error_with_message(
+                "invalid_request",
+                "no valid slugs provided",
+            )),
+        )
+            .into_response();
+    }
+
+    let updated_at = state.clock.now();
+    match state.repo.bulk_update_active(&slugs, is_active, updated_at) {
+        Ok(affected) => {
+            info!(
+                count = affected,
+                is_active = is_active,
+                "bulk update active ok"
+            );
+            (StatusCode::OK, Json(BulkResultOut { affected })).into_response()
+        }
+        Err(e) => {
+            error!(err=?e, "bulk update error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(http_common::json_error_with_message(
+                    "internal",
+                    "server error",
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+async fn get_me(Tenant(state): Tenant, headers: HeaderMap) -> impl IntoResponse {
+    // Auth
+    let verified = match verify_request_user(
+        &headers,
+        &state.auth_provider,
+        &state.allowed_domain,
+        &state.google_oauth_client_id,
+    )
+    .await
+    {
+        Ok(v) => v,
+        Err(AuthHttp::Unauthorized) => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(http_common::json_error_with_message(
+                    "unauthorized",
+                    "missing or invalid token",
+                )),
+            )
+                .into_response()
+        }
+        Err(AuthHttp::Forbidden) => {
+            return (
+                StatusCode::FORBIDDEN,
+                Json(http_common::json_error_with_message(
+                    "forbidden",
+                    "domain not allowed",
+                )),
+            )
+                .into_response()
+        }
+    };
+
+    let out = MeOut {
+        user: UserInfo {
+            email: verified.email.clone(),
+            is_admin: state.is_admin(&verified.email),
+        },
+        tenant: state.org.id.as_str().to_string(),
+        organization: state.org.name.clone(),
+    };
+    (StatusCode::OK, Json(out)).into_response()
+}
+
+#[derive(Deserialize)]
+struct PutDomainReq {
+    #[serde(default)]
+    allowed_users: Vec<String>,
+    #[serde(default)]
+    allowed_groups: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct DomainOut {
+    host: String,
+    allowed_users: Vec<String>,
+    allowed_groups: Vec<String>,
+    created_at: String,
+    created_by: String,
+    /// Whether the caller may create links on the domain.
+    can_use: bool,
+}
+
+#[derive(Serialize)]
+struct DomainListOut {
+    domains: Vec<DomainOut>,
+}
+
+fn domain_to_out(domain: &ShortDomain, can_use: bool) -> DomainOut {
+    DomainOut {
+        host: domain.host.clone(),
+        allowed_users: domain
+            .allowed_users
+            .iter()
+            .map(|u| u.as_str().to_string())
+            .collect(),
+        allowed_groups: domain.allowed_groups.clone(),
+        created_at: http_common::system_time_to_rfc3339(domain.created_at),
+        created_by: domain.created_by.as_str().to_string(),
+        can_use,
+    }
+}
+
+/// Verify the caller of a short domain endpoint. Returns the error response to
+/// send when the caller is not signed in, or not an admin when `admin_only`.
+async fn verify_domain_caller(
+    state: &AppState,
+    headers: &HeaderMap,
+    admin_only: bool,
+) -> Result<UserEmail, Response> {
+    let err = |status: StatusCode, code: &str, message: &str| {
+        (
+            status,
+            Json(http_common::json_error_with_message(code, message)),
+        )
+            .into_response()
+    };
+    let verified = match verify_request_user(
+        headers,
+        &state.auth_provider,
+        &state.allowed_domain,
+        &state.google_oauth_client_id,
+    )
+    .await
+    {
+        Ok(v) => v,
+        Err(AuthHttp::Unauthorized) => {
+            return Err(err(
+                StatusCode::UNAUTHORIZED,
+                "unauthorized",
+                "missing or invalid token",
+            ))
+        }
+        Err(AuthHttp::Forbidden) => {
+            return Err(err(
+                StatusCode::FORBIDDEN,
+                "forbidden",
+                "domain not allowed",
+            ))
+        }
+    };
+    if admin_only && !state.is_admin(&verified.email) {
+        return Err(err(
+            StatusCode::FORBIDDEN,
+            "forbidden",
+            "admin access required",
+        ));
+    }
+    UserEmail::new(verified.email).map_err(|_| {
+        err(
+            StatusCode::UNAUTHORIZED,
+            "unauthorized",
+            "invalid user email in token",
+        )
+    })
+}
+
+async fn list_domains(Tenant(state): Tenant, headers: HeaderMap) -> impl IntoResponse {
+    let user_email = match verify_domain_caller(&state, &headers, false).await {
+        Ok(u) => u,
+        Err(resp) => return resp,
+    };
+    let internal = |e: CoreError| {
+        error!(err=?e, "list domains error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(http_common::json_error_with_message(
+                "internal",
+                "server error",
+            )),
+        )
+            .into_response()
+    };
+    let domains = match state.repo.list_domains() {
+        Ok(d) => d,
+        Err(e) => return internal(e),
+    };
+    let group_ids: Vec<String> = match state.repo.get_user_groups(&user_email) {
+        Ok(groups) => groups.into_iter().map(|(g, _)| g.id).collect(),
+        Err(e) => return internal(e),
+    };
+    let user_is_admin = state.is_admin(user_email.as_str());
+    let out = DomainListOut {
+        domains: domains
+            .iter()
+            .map(|d| domain_to_out(d, user_is_admin || d.may_use(&user_email, &group_ids)))
+            .collect(),
+    };
+    (StatusCode::OK, Json(out)).into_response()
+}
+
+async fn put_domain(
+    Tenant(state): Tenant,
+    headers: HeaderMap,
+    Path(host): Path<String>,
+    Json(body): Json<PutDomainReq>,
+) -> impl IntoResponse {
+    let created_by = match verify_domain_caller(&state, &headers, true).await {
+        Ok(u) => u,
+        Err(resp) => return resp,
+    };
+    let err = |status: StatusCode, code: &str, message: &str| {
+        (
+            status,
+            Json(http_common::json_error_with_message(code, message)),
+        )
+            .into_response()
+    };
+    let Ok(host) = domain::normalize_host(&host) else {
+        return err(StatusCode::BAD_REQUEST, "invalid_request", "invalid domain");
+    };
+    // Other organizations are picked by host, so they may only use their own
+    if !state.org.id.is_default() && !state.org.serves_host(&host) {
+        return err(
+            StatusCode::BAD_REQUEST,
+            "invalid_request",
+            "domain is not one of the organization's hosts",
+        );
+    }
+    let Ok(allowed_users) = body
+        .allowed_users
+        .into_iter()
+        .map(UserEmail::new)
+        .collect::<Result<Vec<_>, _>>()
+    else {
+        return err(
+            StatusCode::BAD_REQUEST,
+            "invalid_request",
+            "invalid user email",
+        );
+    };
+    for group_id in &body.allowed_groups {
+        match state.repo.get_group(group_id) {
+            Ok(Some(_)) => {}
+            Ok(None) => {
+                return err(
+                    StatusCode::BAD_REQUEST,
+                    "invalid_request",
+                    "group not found",
+                )
+            }
+            Err(e) => {
+                error!(err=?e, "get group error");
+                return err(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "internal",
+                    "server error",
+                );
+            }
+        }
+    }
+
+    let (created_at, created_by) = match state.repo.get_domain(&host) {
+        Ok(Some(existing)) => (existing.created_at, existing.created_by),
+        Ok(None) => (state.clock.now(), created_by),
+        Err(e) => {
+            error!(err=?e, "get domain error");
+            return err(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "internal",
+                "server error",
+            );
+        }
+    };
+    let short_domain = ShortDomain {
+        host,
+        allowed_users,
+        allowed_groups: body.allowed_groups,
+        created_at,
+        created_by,
+    };
+    match state.repo.put_domain(short_domain.clone()) {
+        Ok(()) => {
+            info!(host = %short_domain.host, "short domain saved");
+            (StatusCode::OK, Json(domain_to_out(&short_domain, true))).into_response()
+        }
+        Err(e) => {
+            error!(err=?e, "put domain error");
+            err(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "internal",
+                "server error",
+            )
+        }
+    }
+}
+
+async fn delete_domain(
+    Tenant(state): Tenant,
+    headers: HeaderMap,
+    Path(host): Path<String>,
+) -> impl IntoResponse {
+    if let Err(resp) = verify_domain_caller(&state, &headers, true).await {
+        return resp;
+    }
+    let host = host.to_ascii_lowercase();
+    match state.repo.delete_domain(&host) {
+        Ok(()) => {
+            info!(host = %host, "short domain deleted");
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Err(CoreError::NotFound) => (
+            StatusCode::NOT_FOUND,
+            Json(http_common::json_error_with_message(
+                "not_found",
+                "domain not found",
+            )),
+        )
+            .into_response(),
+        Err(e) => {
+            error!(err=?e, "delete domain error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(http_common::json_error_with_message(
+                    "internal",
+                    "server error",
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct SlugCheckQuery {
+    alias: String,
+    /// Short domain the link would be created on.
+    domain: Option<String>,
+    /// Group the link would be created in, for its `slug_prefix`.
+    group_id: Option<String>,
+}
+
+#[derive(Serialize)]
+struct SlugCheckOut {
+    alias: String,
+    /// Free to use: no violations and not taken.
+    available: bool,
+    /// A link or alias already uses the slug.
+    taken: bool,
+    violations: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct SlugSuggestQuery {
+    url: String,
+    /// Page title of the target, when the client knows it.
+    title: Option<String>,
+    domain: Option<String>,
+    limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+struct SlugSuggestOut {
+    suggestions: Vec<AliasOut>,
+}
+
+/// The registered short domain named by a query parameter; `Ok(None)` for the
+/// default domain, `NotFound` when it is not registered.
+fn query_short_domain(state: &AppState, domain: Option<&str>) -> Result<Option<String>, CoreError> {
+    let Some(d) = domain.filter(|d| !d.trim().is_empty()) else {
+        return Ok(None);
+    };
+    let host = domain::normalize_host(d).map_err(|_| CoreError::NotFound)?;
+    match state.repo.get_domain(&host)? {
+        Some(sd) => Ok(Some(sd.host)),
+        None => Err(CoreError::NotFound),
+    }
+}
+
+async fn check_slug(
+    Tenant(state): Tenant,
+    headers: HeaderMap,
+    Query(q): Query<SlugCheckQuery>,
+) -> impl IntoResponse {
+    let user_email = match verify_domain_caller(&state, &headers, false).await {
+        Ok(u) => u,
+        Err(resp) => return resp,
+    };
+    let internal = |e: CoreError| {
+        error!(err=?e, "check slug error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(http_common::json_error_with_message(
+                "internal",
+                "server error",
+            )),
+        )
+            .into_response()
+    };
+    let mut violations: Vec<String> = http_common::alias_violations(&q.alias)
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+    let mut taken = false;
+    if let (true, Ok(slug)) = (violations.is_empty(), Slug::new(q.alias.as_str())) {
+        match query_short_domain(&state, q.domain.as_deref()) {
+            Ok(short_domain) => {
+                taken = match state.repo.get(&slug.clone().on_domain(short_domain)) {
+                    Ok(link) => link.is_some(),
+                    Err(e) => return internal(e),
+                };
+            }
+            Err(CoreError::NotFound) => violations.push("unknown domain".into()),
+            Err(e) => return internal(e),
+        }
+        match namespace_violation(&state, &slug, user_email.as_str()) {
+            Ok(v) => violations.extend(v),
+            Err(e) => return internal(e),
+        }
+        if let Some(group_id) = &q.group_id {
+            match state.repo.get_group(group_id) {
+                Ok(Some(group)) => {
+                    if let Err(e) = group.settings.check_slug(&slug) {
+                        violations.push(e.to_string());
+                    }
+                }
+                Ok(None) => violations.push("group not found".into()),
+                Err(e) => return internal(e),
+            }
+        }
+    }
+    let out = SlugCheckOut {
+        available: violations.is_empty() && !taken,
+        alias: q.alias,
+        taken,
+        violations,
+    };
+    (StatusCode::OK, Json(out)).into_response()
+}
+
+async fn suggest_slugs(
+    Tenant(state): Tenant,
+    headers: HeaderMap,
+    Query(q): Query<SlugSuggestQuery>,
+) -> impl IntoResponse {
+    if let Err(resp) = verify_domain_caller(&state, &headers, false).await {
+        return resp;
+    }
+    let err = |status: StatusCode, code: &str, message: &str| {
+        (
+            status,
+            Json(http_common::json_error_with_message(code, message)),
+        )
+            .into_response()
+    };
+    if let Err(e) = domain::validate::validate_original_url(&q.url) {
+        return err(StatusCode::BAD_REQUEST, "invalid_request", &e.to_string());
+    }
+    let short_domain = match query_short_domain(&state, q.domain.as_deref()) {
+        Ok(d) => d,
+        Err(CoreError::NotFound) => {
+            return err(StatusCode::BAD_REQUEST, "invalid_request", "unknown domain")
+        }
+        Err(e) => {
+            error!(err=?e, "get domain error");
+            return err(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "internal",
+                "server error",
+            );
+        }
+    };
+    let limit = q.limit.unwrap_or(5).clamp(1, 20);
+    let mut suggestions = Vec::new();
+    for slug in domain::slug::suggest_slugs(&q.url, q.title.as_deref()) {
+        if suggestions.len() >= limit {
+            break;
+        }
+        let slug = slug.on_domain(short_domain.clone());
+        match state.repo.get(&slug) {
+            Ok(Some(_)) => {}
+            Ok(None) => suggestions.push(AliasOut {
+                slug: slug.key().into_owned(),
+                short_url: build_short_url(&headers, &slug, &state.shortlink_domain),
+            }),
+            Err(e) => {
+                error!(err=?e, "suggest slugs error");
+                return err(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "internal",
+                    "server error",
+                );
+            }
+        }
+    }
+    (StatusCode::OK, Json(SlugSuggestOut { suggestions })).into_response()
+}
+
+/// Links read per reverse lookup before filtering by what the caller may see.
+const TARGET_LOOKUP_LIMIT: usize = 1000;
+
+#[derive(Deserialize)]
+struct TargetQuery {
+    url: String,
+    /// `exact` (default), `prefix` or `host`.
+    #[serde(rename = "match")]
+    mode: Option<String>,
+    limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct RepointReq {
+    url: String,
+    #[serde(rename = "match")]
+    mode: Option<String>,
+    /// New destination; for `prefix` and `host` matches the rest of each
+    /// link's URL is kept (see [`TargetMatch::rewrite`]).
+    to: String,
+}
+
+#[derive(Serialize)]
+struct RepointOut {
+    affected: usize,
+    slugs: Vec<String>,
+    /// Matching links the caller may see but not change, or whose new target
+    /// breaks a group or organization rule.
+    skipped: Vec<String>,
+}
+
+/// Parse the `match` parameter and the lookup key for `url`, or the 400 to send.
+fn parse_target_lookup(
+    url: &str,
+    mode: Option<&str>,
+) -> Result<(TargetMatch, String), Box<Response>> {
+    let bad_request = |message: &str| {
+        Box::new(
+            (
+                StatusCode::BAD_REQUEST,
+                Json(http_common::json_error_with_message(
+                    "invalid_request",
+                    message,
+                )),
+            )
+                .into_response(),
+        )
+    };
+    let mode = match mode {
+        None => TargetMatch::default(),
+        Some(m) => TargetMatch::parse(m)
+            .ok_or_else(|| bad_request("invalid match, use: exact, prefix or host"))?,
+    };
+    let key = mode
+        .lookup_key(url)
+        .map_err(|e| bad_request(&e.to_string()))?;
+    Ok((mode, key))
+}
+
+/// Links matching `key` that `user` may see: every link for admins, otherwise
+/// their own, their groups' and the ones shared with them. Oldest first.
+fn visible_links_by_target(
+    state: &AppState,
+    user: &UserEmail,
+    key: &str,
+    mode: TargetMatch,
+) -> Result<Vec<domain::ShortLink>, CoreError> {
+    let links = state.repo.list_by_target(key, mode, TARGET_LOOKUP_LIMIT)?;
+    if state.is_admin(user.as_str()) {
+        return Ok(links);
+    }
+    let scope = AccessScope {
+        user: user.clone(),
+        group_ids: state
+            .repo
+            .get_user_groups(user)?
+            .into_iter()
+            .map(|(group, _)| group.id)
+            .collect(),
+        shared_slugs: state
+            .repo
+            .list_grants_for_user(user)?
+            .into_iter()
+            .map(|grant| grant.slug)
+            .collect(),
+    };
+    Ok(links.into_iter().filter(|l| scope.contains(l)).collect())
+}
+
+async fn links_by_target(
+    Tenant(state): Tenant,
+    headers: HeaderMap,
+    Query(q): Query<TargetQuery>,
+) -> impl IntoResponse {
+    let user_email = match verify_domain_caller(&state, &headers, false).await {
+        Ok(u) => u,
+        Err(resp) => return resp,
+    };
+    let (mode, key) = match parse_target_lookup(&q.url, q.mode.as_deref()) {
+        Ok(lookup) => lookup,
+        Err(resp) => return *resp,
+    };
+    let limit = q.limit.unwrap_or(100).clamp(1, 500);
+    match visible_links_by_target(&state, &user_email, &key, mode) {
+        Ok(mut links) => {
+            let total = links.len();
+            links.truncate(limit);
+            let out = ListOut {
+                links: links
+                    .into_iter()
+                    .map(|l| link_to_out(l, &headers, &state.shortlink_domain))
+                    .collect(),
+                total: Some(total),
+                has_more: total > limit,
+                next_cursor: None,
+                user: None,
+            };
+            (StatusCode::OK, Json(out)).into_response()
+        }
+        Err(e) => {
+            error!(err=?e, "links by target error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(http_common::json_error_with_message(
+                    "internal",
+                    "server error",
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+async fn repoint_links_by_target(
+    Tenant(state): Tenant,
+    headers: HeaderMap,
+    Json(body): Json<RepointReq>,
+) -> impl IntoResponse {
+    let user_email = match verify_domain_caller(&state, &headers, false).await {
+        Ok(u) => u,
+        Err(resp) => return resp,
+    };
+    let internal = |e: CoreError| {
+        error!(err=?e, "repoint links error");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(http_common::json_error_with_message(
+                "internal",
+                "server error",
+            )),
+        )
+            .into_response()
+    };
+    let (mode, key) = match parse_target_lookup(&body.url, body.mode.as_deref()) {
+        Ok(lookup) => lookup,
+        Err(resp) => return *resp,
+    };
+    if let Err(e) = domain::validate::validate_original_url(&body.to) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(http_common::json_error_with_message(
+                "invalid_request",
+                &format!("{}", e),
+            )),
+        )
+            .into_response();
+    }
+    let links = match visible_links_by_target(&state, &user_email, &key, mode) {
+        Ok(links) => links,
+        Err(e) => return internal(e),
+    };
+    let now = state.clock.now();
+    let mut out = RepointOut {
+        affected: 0,
+        slugs: Vec::new(),
+        skipped: Vec::new(),
+    };
+    for mut link in links {
+        let slug = link.slug.key().into_owned();
+        let url = mode.rewrite(&key, &link, &body.to);
+        if !can_edit_link(&state, &link, user_email.as_str())
+            || domain::validate::validate_original_url(&url).is_err()
+        {
+            out.skipped.push(slug);
+            continue;
+        }
+        link.original_url = url;
+        if enforce_link_settings(&state, &mut link, GroupCheck::Update { moved_in: false })
+            .is_some()
+        {
+            out.skipped.push(slug);
+            continue;
+        }
+        link.updated_at = Some(now);
+        match state.repo.update(&link) {
+            Ok(()) => out.slugs.push(slug),
+            Err(CoreError::NotFound) => out.skipped.push(slug),
+            Err(e) => return internal(e),
+        }
+    }
+    out.affected = out.slugs.len();
+    info!(count = out.affected, "repoint links ok");
+    (StatusCode::OK, Json(out)).into_response()
+}
+
+#[derive(Deserialize)]
+struct PutNamespaceReq {
+    group_id: String,
+}
+
+#[derive(Serialize)]
+struct NamespaceOut {
+    path: String,
+    group_id: String,
+    created_at: String,
+    created_by: String,
+}
+
+#[derive(Serialize)]
+struct NamespaceListOut {
+    namespaces: Vec<NamespaceOut>,
+}
+
+fn namespace_to_out(namespace: &Namespace) -> NamespaceOut {
+    NamespaceOut {
+        path: namespace.path.clone(),
+        group_id: namespace.group_id.clone(),
+        created_at: http_common::system_time_to_rfc3339(namespace.created_at),
+        created_by: namespace.created_by.as_str().to_string(),
+    }
+}
+
+/// Whether `user` may reserve or release namespaces for `group_id`: admins and
+/// managers of the group.
+fn can_manage_namespace(
+    state: &AppState,
+    group_id: &str,
+    user: &UserEmail,
+) -> Result<bool, CoreError> {
+    if state.is_admin(user.as_str()) {
+        return Ok(true);
+    }
+    Ok(state
+        .repo
+        .effective_role(group_id, user)?
+        .is_some_and(|r| r.can_manage()))
+}
+
+async fn list_namespaces(Tenant(state): Tenant, headers: HeaderMap) -> impl IntoResponse {
+    if let Err(resp) = verify_domain_caller(&state, &headers, false).await {
+        return resp;
+    }
+    match state.repo.list_namespaces() {
+        Ok(namespaces) => {
+            let out = NamespaceListOut {
+                namespaces: namespaces.iter().map(namespace_to_out).collect(),
+            };
+            (StatusCode::OK, Json(out)).into_response()
+        }
+        Err(e) => {
+            error!(err=?e, "list namespaces error");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(http_common::json_error_with_message(
+                    "internal",
+                    "server error",
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+async fn put_namespace(
+    Tenant(state): Tenant,
+    headers: HeaderMap,
+    Path(path): Path<String>,
+    Json(body): Json<PutNamespaceReq>,
+) -> impl IntoResponse {
+    let user_email = match verify_domain_caller(&state, &headers, false).await {
+        Ok(u) => u,
+        Err(resp) => return resp,
+    };
+    let err = |status: StatusCode, code: &str, message: &str| {
+        (
+            status,
+            Json(http_common::json_error_with_message(code, message)),
+        )
+            .into_response()
+    };
+    if Slug::new(path.as_str()).is_err() {
+        return err(
+            StatusCode::BAD_REQUEST,
+            "invalid_request",
+            "invalid namespace",
+        );
+    }
+    match state.repo.get_group(&body.group_id) {
+        Ok(Some(_)) => {}
+        Ok(None) => return err(StatusCode::NOT_FOUND, "not_found", "group not found"),
+        Err(e) => {
+            error!(err=?e, "get group error");
+            return err(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "internal",
+                "server error",
+            );
+        }
+    }
+    match can_manage_namespace(&state, &body.group_id, &user_email) {
+        Ok(true) => {}
+        Ok(false) => {
+            return err(
+                StatusCode::FORBIDDEN,
+                "forbidden",
+                "group admin access required",
+            )
+        }
+        Err(e) => {
+            error!(err=?e, "get role error");
+            return err(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "internal",
+                "server error",
+            );
+        }
+    }
+    let namespace = Namespace {
+        path,
+        group_id: body.group_id,
+        created_at: state.clock.now(),
+        created_by: user_email,
+    };
+    match state.repo.put_namespace(namespace.clone()) {
+        Ok(()) => {
+            info!(path = %namespace.path, group_id = %namespace.group_id, "namespace reserved");
+            (StatusCode::CREATED, Json(namespace_to_out(&namespace))).into_response()
+        }
+        Err(CoreError::AlreadyExists) => err(
+            StatusCode::CONFLICT,
+            "conflict",
+            "namespace already reserved",
+        ),
+        Err(e) => {
+            error!(err=?e, "put namespace error");
+            err(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "internal",
+                "server error",
+            )
+        }
+    }
+}
+
+async fn delete_namespace(
+    Tenant(state): Tenant,
+    headers: HeaderMap,
+    Path(path): Path<String>,
+) -> impl IntoResponse {
+    let user_email = match verify_domain_caller(&state, &headers, false).await {
+        Ok(u) => u,
+        Err(resp) => return resp,
+    };
+    let err = |status: StatusCode, code: &str, message: &str| {
+        (
+            status,
+            Json(http_common::json_error_with_message(code, message)),
+        )
+            .into_response()
+    };
+    let internal = |e: CoreError| {
+        error!(err=?e, "delete namespace error");
+        err(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "internal",
+            "server error",
+        )
+    };
+    let namespace = match state.repo.get_namespace(&path) {
+        Ok(Some(ns)) => ns,
+        Ok(None) => return err(StatusCode::NOT_FOUND, "not_found", "namespace not reserved"),
+        Err(e) => return internal(e),
+    };
+    match can_manage_namespace(&state, &namespace.group_id, &user_email) {
+        Ok(true) => {}
+        Ok(false) => {
+            return err(
+                StatusCode::FORBIDDEN,
+                "forbidden",
+                "group admin access required",
+            )
+        }
+        Err(e) => return internal(e),
+    }
+    match state.repo.delete_namespace(&path) {
+        Ok(()) => {
+            info!(path = %path, "namespace released");
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Err(CoreError::NotFound) => {
+            err(StatusCode::NOT_FOUND, "not_found", "namespace not reserved")
+        }
+        Err(e) => internal(e),
+    }
+}
+
+/// Build the short URL on the slug's own domain. Slugs on the default domain
+/// use shortlink_domain from config, or the Host header as fallback.
+fn build_short_url(headers: &HeaderMap, slug: &Slug, shortlink_domain: &Option<String>) -> String {
+    let host = slug
+        .domain()
+        .or(shortlink_domain.as_deref())
+        .or_else(|| headers.get("host").and_then(|v| v.to_str().ok()))
+        .unwrap_or("");
+    http_common::build_short_url_from_host(host, slug.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::{header, Request};
+    use tower::util::ServiceExt;
+
+    fn app() -> Router {
+        app_with_repo(AnyRepo::memory())
+    }
+
+    fn app_with_repo(repo: AnyRepo) -> Router {
+        router(state_with_repo(repo))
+    }
+
+    fn state_with_repo(repo: AnyRepo) -> AppState {
+        AppState {
+            repo,
+            slugger: Base62SlugGenerator::new(5),
+            word_slugger: WordSlugGenerator::default(),
+            clock: StdClock,
+            auth_provider: config::AuthProvider::None,
+            allowed_domain: None,
+            google_oauth_client_id: None,
+            shortlink_domain: None,
+            session_secret: Some("test-secret".into()),
+            session_ttl_secs: 60,
+            // Tests change organizations between requests
+            orgs: Arc::new(OrganizationCache::new(std::time::Duration::ZERO)),
+            org: Organization::fallback(),
+        }
+    }
+
+    fn router(state: AppState) -> Router {
+        Router::new()
+            .route("/*slug", get(get_slug))
+            .route("/auth/session", post(create_session))
+            .route(
+                "/api/links",
+                post(create_link).get(list_links).options(preflight_links),
+            )
+            .route(
+                "/api/links/:slug",
+                axum::routing::patch(update_link).delete(delete_link),
+            )
+            .route(
+                "/api/links/:slug/collaborators",
+                get(list_link_collaborators).post(add_link_collaborator),
+            )
+            .route(
+                "/api/links/:slug/collaborators/:email",
+                axum::routing::delete(remove_link_collaborator),
+            )
+            .route("/api/links/:slug/rename", post(rename_link))
+            .route(
+                "/api/links/:slug/aliases",
+                get(list_link_aliases).post(add_link_alias),
+            )
+            .route(
+                "/api/links/:slug/aliases/:alias",
+                axum::routing::delete(remove_link_alias),
+            )
+            .route("/api/links/by-target", get(links_by_target))
+            .route(
+                "/api/links/by-target/repoint",
+                post(repoint_links_by_target),
+            )
+            .route("/api/groups/:id", axum::routing::patch(update_group))
+            .route("/api/domains", get(list_domains))
+            .route(
+                "/api/domains/:host",
+                axum::routing::put(put_domain).delete(delete_domain),
+            )
+            .route("/api/namespaces", get(list_namespaces))
+            .route("/api/slugs/check", get(check_slug))
+            .route("/api/slugs/suggest", get(suggest_slugs))
+            .route(
+                "/api/namespaces/*path",
+                axum::routing::put(put_namespace).delete(delete_namespace),
+            )
+            .with_state(state)
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn sqlite_schemas_from_newer_builds_stop_startup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("links.db");
+        let mut cfg = config::Config::from_env().unwrap();
+        cfg.storage_provider = config::StorageProvider::Sqlite;
+        cfg.db_path = Some(path.clone());
+        assert!(build_repo_from_env(&cfg).is_ok());
+
+        rusqlite::Connection::open(&path)
+            .unwrap()
+            .execute(
+                "INSERT INTO schema_version(version, name, applied_at) VALUES (?1, 'future', 0)",
+                [sqlite_adapter::SCHEMA_VERSION + 1],
+            )
+            .unwrap();
+        assert!(matches!(
+            build_repo_from_env(&cfg),
+            Err(CoreError::Repository(m)) if m.contains("newer than this build")
+        ));
+    }
+
+    #[cfg(feature = "postgres")]
+    #[test]
+    fn unusable_postgres_databases_stop_startup() {
+        let mut cfg = config::Config::from_env().unwrap();
+        cfg.storage_provider = config::StorageProvider::Postgres;
+        cfg.database_url = Some("postgres://postgres@127.0.0.1:1/postgres".into());
+        assert!(build_repo_from_env(&cfg).is_err());
+
+        // A schema migrated by a newer build, when a test server is available
+        let Ok(url) = std::env::var("POSTGRES_TEST_URL") else {
+            return;
+        };
+        let schema = format!("api_server_test_{}", std::process::id());
+        let mut client = postgres::Client::connect(&url, postgres::NoTls).unwrap();
+        client
+            .batch_execute(&format!(
+                "DROP SCHEMA IF EXISTS {schema} CASCADE; CREATE SCHEMA {schema}"
+            ))
+            .unwrap();
+        let separator = if url.contains('?') { '&' } else { '?' };
+        cfg.database_url = Some(format!(
+            "{url}{separator}options=-c%20search_path%3D{schema}"
+        ));
+        assert!(build_repo_from_env(&cfg).is_ok());
+        client
+            .execute(
+                &format!(
+                    "INSERT INTO {schema}.schema_version(version, name, applied_at) VALUES ($1, 'future', 0)"
+                ),
+                &[&(i64::from(postgres_adapter::SCHEMA_VERSION) + 1)],
+            )
+            .unwrap();
+        let refused = build_repo_from_env(&cfg);
+        client
+            .batch_execute(&format!("DROP SCHEMA {schema} CASCADE"))
+            .unwrap();
+        assert!(matches!(
+            refused,
+            Err(CoreError::Repository(m)) if m.contains("newer than this build")
+        ));
+    }
+
+    // The server's runtime: DynamoRepo blocks in place on it
+    #[cfg(feature = "dynamo")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn unreachable_dynamodb_fails_requests_with_500() {
+        let tables = aws_dynamo::DynamoTables::new("links", "counters");
+        let repo = aws_dynamo::DynamoRepo::with_endpoint(tables, "http://127.0.0.1:9").unwrap();
+        let router = app_with_repo(AnyRepo::dynamo(repo));
+        for uri in ["/docs", "/api/links"] {
+            let req = Request::builder()
+                .uri(uri)
+                .header("X-Debug-User", "user@example.com")
+                .body(Body::empty())
+                .unwrap();
+            let resp = router.clone().oneshot(req).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR, "{uri}");
+        }
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn blocking_work_runs_on_either_runtime() {
+        assert_eq!(blocking(|| 1 + 1), 2);
+        let on_current_thread = tokio::task::spawn_blocking(|| {
+            tokio::runtime::Builder::new_current_thread()
+                .build()
+                .unwrap()
+                .block_on(async { blocking(|| 3) })
+        });
+        assert_eq!(on_current_thread.await.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn create_and_resolve_flow() {
+        let router = app();
+
+        // Create
+        let req = Request::builder()
+            .method("POST")
+            .uri("/api/links")
+            .header("content-type", "application/json")
+            .header("X-Debug-User", "user@example.com")
+            .body(Body::from("{\"original_url\":\"https://example.com\"}"))
+            .unwrap();
+
+        let resp = router.clone().oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::CREATED);
+
+        // List
+        let resp = router
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/api/links")
+                    .header("X-Debug-User", "user@example.com")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        // Resolve should redirect (308)
+        // We don't know slug from response easily here without parsing; call service directly for a known slug path.
+        // Instead, create with custom slug and then resolve it
+        let req = Request::builder()
+            .method("POST")
+            .uri("/api/links")
+            .header("content-type", "application/json")
+            .header("X-Debug-User", "user@example.com")
+            .body(Body::from(
+                "{\"original_url\":\"https://e2.com\",\"alias\":\"custom1\"}",
+            ))
+            .unwrap();
+        let resp = router.clone().oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        let resp = router
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/custom1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(
+            resp.headers().get(header::LOCATION).unwrap(),
+            "https://e2.com"
+        );
+    }
+
+    #[tokio::test]
+    async fn workspace_link_requires_session() {
+        let router = app();
+
+        let req = Request::builder()
+            .method("POST")
+            .uri("/api/links")
+            .header("content-type", "application/json")
+            .header("X-Debug-User", "user@example.com")
+            .body(Body::from(
+                "{\"original_url\":\"https://wiki.example.com\",\"alias\":\"wiki\",\"visibility\":\"workspace\"}",
+            ))
+            .unwrap();
+        let resp = router.clone().oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::CREATED);
+
+        // Anonymous visitors get the sign-in page
+        let resp = router
+            .clone()
+            .oneshot(Request::builder().uri("/wiki").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        assert!(resp.headers().get(header::LOCATION).is_none());
+
+        // Sign in and follow with the session cookie
+        let resp = router
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/auth/session")
+                    .header("content-type", "application/json")
+                    .body(Body::from("{\"credential\":\"visitor@example.com\"}"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+        let set_cookie = resp.headers().get(header::SET_COOKIE).unwrap();
+        let cookie = set_cookie.to_str().unwrap().split(';').next().unwrap();
+
+        let resp = router
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/wiki")
+                    .header(header::COOKIE, cookie)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(
+            resp.headers().get(header::LOCATION).unwrap(),
+            "https://wiki.example.com"
+        );
+    }
+
+    #[tokio::test]
+    async fn debug_header_opens_workspace_links_only_with_debug_sessions() {
+        let mut state = state_with_repo(AnyRepo::memory());
         let req = Request::builder()
             .method("POST")
             .uri("/api/links")
             .header("content-type", "application/json")
             .header("X-Debug-User", "user@example.com")
             .body(Body::from(
-                "{\"original_url\":\"https://e2.com\",\"alias\":\"custom1\"}",
+                "{\"original_url\":\"https://wiki.example.com\",\"alias\":\"wiki\",\"visibility\":\"workspace\"}",
+            ))
+            .unwrap();
+        let resp = router(state.clone()).oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        let visit = || {
+            Request::builder()
+                .uri("/wiki")
+                .header("X-Debug-User", "visitor@example.com")
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let resp = router(state.clone()).oneshot(visit()).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::PERMANENT_REDIRECT);
+
+        // Without DEBUG_SESSIONS debug auth mode has no session secret
+        state.session_secret = None;
+        let resp = router(state).oneshot(visit()).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(resp.headers().get(header::LOCATION).is_none());
+    }
+
+    #[tokio::test]
+    async fn workspace_sessions_are_checked_against_their_hosted_domain() {
+        let repo = AnyRepo::memory();
+        repo.put_organization(Organization {
+            id: TenantId::new("acme").unwrap(),
+            name: "acme".into(),
+            hosts: vec!["go.acme.test".into()],
+            auth_domains: vec!["acme.test".into()],
+            ..Organization::fallback()
+        })
+        .unwrap();
+        let router = app_with_repo(repo);
+        let req = Request::builder()
+            .method("POST")
+            .uri("/api/links")
+            .header(header::HOST, "go.acme.test")
+            .header("content-type", "application/json")
+            .header("X-Debug-User", "a@acme.test")
+            .body(Body::from(
+                "{\"original_url\":\"https://wiki.acme.test\",\"alias\":\"wiki\",\"visibility\":\"workspace\"}",
+            ))
+            .unwrap();
+        let resp = router.clone().oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        let visit = |hd: Option<&str>| {
+            let user = google_auth::VerifiedUser {
+                email: "x@acme.test".into(),
+                sub: "x".into(),
+                hd: hd.map(str::to_string),
+            };
+            let token = google_auth::issue_session(
+                &user,
+                b"test-secret",
+                std::time::Duration::from_secs(60),
+            )
+            .unwrap();
+            Request::builder()
+                .uri("/wiki")
+                .header(header::HOST, "go.acme.test")
+                .header(
+                    header::COOKIE,
+                    format!("{}={token}", http_common::SESSION_COOKIE),
+                )
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let resp = router.clone().oneshot(visit(None)).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::PERMANENT_REDIRECT);
+
+        // An account of another hosted domain is refused even though its
+        // email address is on an allowed domain
+        let resp = router
+            .oneshot(visit(Some("contractor.test")))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+        assert!(resp.headers().get(header::LOCATION).is_none());
+    }
+
+    #[tokio::test]
+    async fn accessible_scope_lists_own_and_shared_links_with_roles() {
+        let router = app();
+        let call = |method: &str, uri: &str, user: &str, body: &str| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .header("X-Debug-User", user)
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+        for (user, alias) in [
+            ("alice@example.com", "mine"),
+            ("bob@example.com", "shared"),
+            ("bob@example.com", "private"),
+        ] {
+            let body = format!(
+                "{{\"original_url\":\"https://example.com/{alias}\",\"alias\":\"{alias}\"}}"
+            );
+            let resp = router
+                .clone()
+                .oneshot(call("POST", "/api/links", user, &body))
+                .await
+                .unwrap();
+            assert_eq!(resp.status(), StatusCode::CREATED);
+        }
+        let resp = router
+            .clone()
+            .oneshot(call(
+                "POST",
+                "/api/links/shared/collaborators",
+                "bob@example.com",
+                "{\"email\":\"alice@example.com\",\"role\":\"viewer\"}",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::CREATED);
+
+        let resp = router
+            .clone()
+            .oneshot(call(
+                "GET",
+                "/api/links?scope=accessible&order=asc",
+                "alice@example.com",
+                "",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let links: Vec<(&str, &str)> = json["links"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|l| (l["slug"].as_str().unwrap(), l["role"].as_str().unwrap()))
+            .collect();
+        assert_eq!(links, [("mine", "owner"), ("shared", "viewer")]);
+    }
+
+    #[tokio::test]
+    async fn collaborators_can_edit_shared_link() {
+        let router = app();
+        let call = |method: &str, uri: &str, user: &str, body: &str| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .header("X-Debug-User", user)
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+        let patch = "{\"description\":\"edited\"}";
+
+        let resp = router
+            .clone()
+            .oneshot(call(
+                "POST",
+                "/api/links",
+                "owner@example.com",
+                "{\"original_url\":\"https://example.com\",\"alias\":\"shared\"}",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::CREATED);
+
+        // Not shared yet
+        let resp = router
+            .clone()
+            .oneshot(call("PATCH", "/api/links/shared", "bob@example.com", patch))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+        // Only the owner can share
+        let share = "{\"email\":\"bob@example.com\",\"role\":\"editor\"}";
+        let resp = router
+            .clone()
+            .oneshot(call(
+                "POST",
+                "/api/links/shared/collaborators",
+                "bob@example.com",
+                share,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+        let resp = router
+            .clone()
+            .oneshot(call(
+                "POST",
+                "/api/links/shared/collaborators",
+                "owner@example.com",
+                share,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::CREATED);
+
+        let resp = router
+            .clone()
+            .oneshot(call("PATCH", "/api/links/shared", "bob@example.com", patch))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let resp = router
+            .clone()
+            .oneshot(call(
+                "GET",
+                "/api/links/shared/collaborators",
+                "bob@example.com",
+                "",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["collaborators"][0]["email"], "bob@example.com");
+        assert_eq!(json["collaborators"][0]["role"], "editor");
+
+        // Revoking the grant removes edit access again
+        let resp = router
+            .clone()
+            .oneshot(call(
+                "DELETE",
+                "/api/links/shared/collaborators/bob@example.com",
+                "owner@example.com",
+                "",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+        let resp = router
+            .clone()
+            .oneshot(call("PATCH", "/api/links/shared", "bob@example.com", patch))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn group_settings_apply_to_new_links() {
+        let repo = AnyRepo::memory();
+        let owner = UserEmail::new("owner@example.com").unwrap();
+        repo.create_group(LinkGroup {
+            id: "grp_mkt".into(),
+            name: "Marketing".into(),
+            description: None,
+            created_at: std::time::SystemTime::UNIX_EPOCH,
+            created_by: owner.clone(),
+            settings: GroupSettings::default(),
+            parent_id: None,
+        })
+        .unwrap();
+        repo.add_member(GroupMember {
+            group_id: "grp_mkt".into(),
+            user_email: owner.clone(),
+            role: GroupRole::Admin,
+            added_at: std::time::SystemTime::UNIX_EPOCH,
+            added_by: owner,
+        })
+        .unwrap();
+        let router = app_with_repo(repo);
+        let call = |method: &str, uri: &str, user: &str, body: &str| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .header("X-Debug-User", user)
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+        let settings = r#"{"settings":{"slug_prefix":"mkt-","allowed_hosts":["*.example.com"],"default_redirect_delay":3,"max_links":1}}"#;
+
+        let resp = router
+            .clone()
+            .oneshot(call(
+                "PATCH",
+                "/api/groups/grp_mkt",
+                "bob@example.com",
+                settings,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+        let resp = router
+            .clone()
+            .oneshot(call(
+                "PATCH",
+                "/api/groups/grp_mkt",
+                "owner@example.com",
+                settings,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let create = |url: &str| {
+            call(
+                "POST",
+                "/api/links",
+                "owner@example.com",
+                &format!(r#"{{"original_url":"{url}","group_id":"grp_mkt"}}"#),
+            )
+        };
+        let resp = router
+            .clone()
+            .oneshot(create("https://evil.com"))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let resp = router
+            .clone()
+            .oneshot(create("https://www.example.com/launch"))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(json["slug"].as_str().unwrap().starts_with("mkt-"));
+        assert_eq!(json["redirect_delay"], 3);
+
+        // The group allows a single link
+        let resp = router
+            .clone()
+            .oneshot(create("https://www.example.com/other"))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn organizations_only_see_their_own_links() {
+        let repo = AnyRepo::memory();
+        for (id, host, domain) in [
+            ("acme", "go.acme.test", "acme.test"),
+            ("globex", "go.globex.test", "globex.test"),
+        ] {
+            repo.put_organization(Organization {
+                id: TenantId::new(id).unwrap(),
+                name: id.into(),
+                hosts: vec![host.into()],
+                auth_domains: vec![domain.into()],
+                ..Organization::fallback()
+            })
+            .unwrap();
+        }
+        let router = app_with_repo(repo);
+        let call = |method: &str, uri: &str, host: &str, user: &str, body: &str| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header(header::HOST, host)
+                .header("content-type", "application/json")
+                .header("X-Debug-User", user)
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+        let create = r#"{"original_url":"https://acme.test/docs","alias":"docs"}"#;
+
+        let resp = router
+            .clone()
+            .oneshot(call(
+                "POST",
+                "/api/links",
+                "go.acme.test",
+                "a@acme.test",
+                create,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::CREATED);
+
+        // Same slug is free in another organization and resolves separately
+        let resp = router
+            .clone()
+            .oneshot(call("GET", "/docs", "go.globex.test", "", ""))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        let resp = router
+            .clone()
+            .oneshot(call("GET", "/docs", "go.acme.test", "", ""))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::PERMANENT_REDIRECT);
+
+        // Users from another organization's domain are rejected
+        let resp = router
+            .clone()
+            .oneshot(call(
+                "GET",
+                "/api/links",
+                "go.acme.test",
+                "g@globex.test",
+                "",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+        // Without a known host the sign-in domain picks the organization
+        let resp = router
+            .clone()
+            .oneshot(call("GET", "/api/links", "localhost", "g@globex.test", ""))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["total"], 0);
+    }
+
+    #[tokio::test]
+    async fn renamed_links_keep_forwarding() {
+        let router = app();
+        let call = |method: &str, uri: &str, user: &str, body: &str| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .header("X-Debug-User", user)
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+        let owner = "owner@example.com";
+        for (method, uri, body, status) in [
+            (
+                "POST",
+                "/api/links",
+                r#"{"original_url":"https://example.com/q3","alias":"q3-reprot"}"#,
+                StatusCode::CREATED,
+            ),
+            (
+                "POST",
+                "/api/links/q3-reprot/collaborators",
+                r#"{"email":"ed@example.com"}"#,
+                StatusCode::CREATED,
+            ),
+            (
+                "POST",
+                "/api/links/q3-reprot/rename",
+                r#"{"slug":"q3-report"}"#,
+                StatusCode::OK,
+            ),
+            // Renaming onto a taken slug (here the forwarding alias) conflicts
+            (
+                "POST",
+                "/api/links/q3-report/rename",
+                r#"{"slug":"q3-reprot"}"#,
+                StatusCode::CONFLICT,
+            ),
+            (
+                "POST",
+                "/api/links/q3-report/aliases",
+                r#"{"alias":"quarterly"}"#,
+                StatusCode::CREATED,
+            ),
+        ] {
+            let resp = router
+                .clone()
+                .oneshot(call(method, uri, owner, body))
+                .await
+                .unwrap();
+            assert_eq!(resp.status(), status, "{method} {uri}");
+        }
+
+        // Every slug resolves to the same link
+        for slug in ["q3-reprot", "q3-report", "quarterly"] {
+            let resp = router
+                .clone()
+                .oneshot(call("GET", &format!("/{slug}"), "", ""))
+                .await
+                .unwrap();
+            assert_eq!(resp.status(), StatusCode::PERMANENT_REDIRECT);
+            assert_eq!(
+                resp.headers().get(header::LOCATION).unwrap(),
+                "https://example.com/q3"
+            );
+        }
+
+        // The collaborator moved along and may manage aliases through any slug
+        let resp = router
+            .clone()
+            .oneshot(call(
+                "GET",
+                "/api/links/quarterly/aliases",
+                "ed@example.com",
+                "",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let aliases: Vec<_> = json["aliases"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|a| a["slug"].as_str().unwrap())
+            .collect();
+        assert_eq!(aliases, ["q3-reprot", "quarterly"]);
+
+        let resp = router
+            .clone()
+            .oneshot(call(
+                "DELETE",
+                "/api/links/q3-report/aliases/quarterly",
+                "ed@example.com",
+                "",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+        let resp = router
+            .clone()
+            .oneshot(call("GET", "/quarterly", "", ""))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn short_domains_resolve_by_host() {
+        let repo = AnyRepo::memory();
+        repo.put_domain(ShortDomain {
+            host: "go.team.test".into(),
+            allowed_users: vec![UserEmail::new("a@example.com").unwrap()],
+            allowed_groups: vec![],
+            created_at: StdClock.now(),
+            created_by: UserEmail::new("a@example.com").unwrap(),
+        })
+        .unwrap();
+        let router = app_with_repo(repo);
+        let call = |method: &str, uri: &str, host: &str, user: &str, body: &str| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header(header::HOST, host)
+                .header("content-type", "application/json")
+                .header("X-Debug-User", user)
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+        let on_default = r#"{"original_url":"https://example.com/default","alias":"docs"}"#;
+        let on_team =
+            r#"{"original_url":"https://example.com/team","alias":"docs","domain":"go.team.test"}"#;
+
+        let resp = router
+            .clone()
+            .oneshot(call(
+                "POST",
+                "/api/links",
+                "localhost",
+                "a@example.com",
+                on_default,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::CREATED);
+
+        // Users outside the allow list may not use the domain
+        let resp = router
+            .clone()
+            .oneshot(call(
+                "POST",
+                "/api/links",
+                "localhost",
+                "b@example.com",
+                on_team,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+        // The same slug is free on the short domain
+        let resp = router
+            .clone()
+            .oneshot(call(
+                "POST",
+                "/api/links",
+                "localhost",
+                "a@example.com",
+                on_team,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["domain"], "go.team.test");
+        assert_eq!(json["short_url"], "https://go.team.test/docs");
+
+        for (host, target) in [
+            ("localhost", "https://example.com/default"),
+            ("go.team.test:443", "https://example.com/team"),
+        ] {
+            let resp = router
+                .clone()
+                .oneshot(call("GET", "/docs", host, "", ""))
+                .await
+                .unwrap();
+            assert_eq!(resp.status(), StatusCode::PERMANENT_REDIRECT);
+            assert_eq!(resp.headers().get(header::LOCATION).unwrap(), target);
+        }
+
+        // Admin paths address the link as host:slug
+        let resp = router
+            .clone()
+            .oneshot(call(
+                "DELETE",
+                "/api/links/go.team.test:docs",
+                "localhost",
+                "a@example.com",
+                "",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+        let resp = router
+            .clone()
+            .oneshot(call("GET", "/api/links", "localhost", "a@example.com", ""))
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["total"], 1);
+        assert!(json["links"][0].get("domain").is_none());
+    }
+    #[tokio::test]
+    async fn reserved_namespaces_belong_to_group_editors() {
+        let repo = AnyRepo::memory();
+        let owner = UserEmail::new("owner@example.com").unwrap();
+        repo.create_group(LinkGroup {
+            id: "grp_hr".into(),
+            name: "HR".into(),
+            description: None,
+            created_at: std::time::SystemTime::UNIX_EPOCH,
+            created_by: owner.clone(),
+            settings: GroupSettings::default(),
+            parent_id: None,
+        })
+        .unwrap();
+        for (email, role) in [
+            ("owner@example.com", GroupRole::Admin),
+            ("editor@example.com", GroupRole::Editor),
+        ] {
+            repo.add_member(GroupMember {
+                group_id: "grp_hr".into(),
+                user_email: UserEmail::new(email).unwrap(),
+                role,
+                added_at: std::time::SystemTime::UNIX_EPOCH,
+                added_by: owner.clone(),
+            })
+            .unwrap();
+        }
+        let router = app_with_repo(repo);
+        let call = |method: &str, uri: &str, user: &str, body: &str| {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .header("X-Debug-User", user)
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+        let reserve = r#"{"group_id":"grp_hr"}"#;
+        let create = r#"{"original_url":"https://example.com/hr","alias":"hr/onboarding"}"#;
+
+        for (user, status) in [
+            ("editor@example.com", StatusCode::FORBIDDEN),
+            ("owner@example.com", StatusCode::CREATED),
+            ("owner@example.com", StatusCode::CONFLICT),
+        ] {
+            let resp = router
+                .clone()
+                .oneshot(call("PUT", "/api/namespaces/hr", user, reserve))
+                .await
+                .unwrap();
+            assert_eq!(resp.status(), status);
+        }
+
+        for (user, status) in [
+            ("outsider@example.com", StatusCode::FORBIDDEN),
+            ("editor@example.com", StatusCode::CREATED),
+        ] {
+            let resp = router
+                .clone()
+                .oneshot(call("POST", "/api/links", user, create))
+                .await
+                .unwrap();
+            assert_eq!(resp.status(), status);
+        }
+
+        // Unreserved namespaces are open to everyone
+        let resp = router
+            .clone()
+            .oneshot(call(
+                "POST",
+                "/api/links",
+                "outsider@example.com",
+                r#"{"original_url":"https://example.com/ev","alias":"events/kickoff"}"#,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::CREATED);
+
+        let resp = router
+            .clone()
+            .oneshot(call("GET", "/hr/onboarding", "", ""))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(
+            resp.headers().get(header::LOCATION).unwrap(),
+            "https://example.com/hr"
+        );
+
+        let resp = router
+            .clone()
+            .oneshot(call(
+                "DELETE",
+                "/api/links/hr%2Fonboarding",
+                "editor@example.com",
+                "",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+        let resp = router
+            .clone()
+            .oneshot(call(
+                "DELETE",
+                "/api/namespaces/hr",
+                "owner@example.com",
+                "",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+    }
+
+    #[tokio::test]
+    async fn slug_check_and_suggestions_skip_taken_slugs() {
+        let router = app();
+        let call = |uri: &str| {
+            Request::builder()
+                .uri(uri)
+                .header("X-Debug-User", "user@example.com")
+                .body(Body::empty())
+                .unwrap()
+        };
+        let json = |resp: Response| async move {
+            let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        };
+        let create = Request::builder()
+            .method("POST")
+            .uri("/api/links")
+            .header("content-type", "application/json")
+            .header("X-Debug-User", "user@example.com")
+            .body(Body::from(
+                r#"{"original_url":"https://example.com/x","alias":"getting-started"}"#,
             ))
             .unwrap();
-        let resp = router.clone().oneshot(req).await.unwrap();
+        let resp = router.clone().oneshot(create).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::CREATED);
+
+        let resp = router
+            .clone()
+            .oneshot(call("/api/slugs/check?alias=getting-started"))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let out = json(resp).await;
+        assert_eq!(out["available"], false);
+        assert_eq!(out["taken"], true);
+        assert_eq!(out["violations"], serde_json::json!([]));
+
+        let out = json(
+            router
+                .clone()
+                .oneshot(call("/api/slugs/check?alias=a!"))
+                .await
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(out["available"], false);
+        assert_eq!(out["violations"].as_array().unwrap().len(), 2);
+
+        let resp = router
+            .clone()
+            .oneshot(call(
+                "/api/slugs/suggest?url=https%3A%2F%2Fwww.example.com%2Fdocs%2Fgetting-started&limit=2",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let out = json(resp).await;
+        let slugs: Vec<&str> = out["suggestions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| s["slug"].as_str().unwrap())
+            .collect();
+        assert_eq!(slugs, ["example-getting-started", "docs-getting-started"]);
+
+        let resp = router
+            .clone()
+            .oneshot(call("/api/slugs/suggest?url=ftp%3A%2F%2Fexample.com"))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn word_slugs_skip_taken_slugs() {
+        let router = app();
+        let create = |body: String| {
+            Request::builder()
+                .method("POST")
+                .uri("/api/links")
+                .header("content-type", "application/json")
+                .header("X-Debug-User", "user@example.com")
+                .body(Body::from(body))
+                .unwrap()
+        };
+        // Take the word slugs for the first two counter values
+        let words = WordSlugGenerator::default();
+        for id in 0..2 {
+            let body = format!(
+                r#"{{"original_url":"https://example.com/{id}","alias":"{}"}}"#,
+                words.next_slug(id).as_str()
+            );
+            let resp = router.clone().oneshot(create(body)).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::CREATED);
+        }
+
+        let resp = router
+            .clone()
+            .oneshot(create(
+                r#"{"original_url":"https://example.com/w","slug_style":"words"}"#.into(),
+            ))
+            .await
+            .unwrap();
         assert_eq!(resp.status(), StatusCode::CREATED);
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let out: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(out["slug"], words.next_slug(2).as_str());
+
+        let resp = router
+            .clone()
+            .oneshot(create(
+                r#"{"original_url":"https://example.com/e","slug_style":"emoji"}"#.into(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn dedupe_returns_existing_link_to_same_target() {
+        let router = app();
+        let create = |user: &str, body: &str| {
+            Request::builder()
+                .method("POST")
+                .uri("/api/links")
+                .header("content-type", "application/json")
+                .header("X-Debug-User", user)
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+        let send = |req: Request<Body>| {
+            let router = router.clone();
+            async move {
+                let resp = router.oneshot(req).await.unwrap();
+                let status = resp.status();
+                let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                let out: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                (status, out["slug"].as_str().unwrap_or_default().to_string())
+            }
+        };
+
+        let (status, first) = send(create(
+            "user@example.com",
+            r#"{"original_url":"https://Example.com/docs#top"}"#,
+        ))
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        let dedupe = r#"{"original_url":"https://example.com:443/docs","dedupe":true}"#;
+        assert_eq!(
+            send(create("user@example.com", dedupe)).await,
+            (StatusCode::OK, first.clone())
+        );
+        // Other users and callers without dedupe get their own slug
+        let (status, other) = send(create("other@example.com", dedupe)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_ne!(other, first);
+        let (status, again) = send(create(
+            "user@example.com",
+            r#"{"original_url":"https://example.com/docs"}"#,
+        ))
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_ne!(again, first);
+
+        // Inactive links are not reused
         let resp = router
             .clone()
             .oneshot(
                 Request::builder()
-                    .uri("/custom1")
-                    .body(Body::empty())
+                    .method("PATCH")
+                    .uri(format!("/api/links/{first}"))
+                    .header("content-type", "application/json")
+                    .header("X-Debug-User", "user@example.com")
+                    .body(Body::from(r#"{"is_active":false}"#))
                     .unwrap(),
             )
             .await
             .unwrap();
-        assert_eq!(resp.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(resp.status(), StatusCode::OK);
         assert_eq!(
-            resp.headers().get(header::LOCATION).unwrap(),
-            "https://e2.com"
+            send(create("user@example.com", dedupe)).await,
+            (StatusCode::OK, again)
+        );
+    }
+
+    #[tokio::test]
+    async fn link_listing_pages_sorts_and_filters() {
+        let router = app();
+        let send = |uri: String, body: Option<&str>| {
+            let router = router.clone();
+            let req = Request::builder()
+                .method(if body.is_some() { "POST" } else { "GET" })
+                .uri(uri)
+                .header("content-type", "application/json")
+                .header("X-Debug-User", "user@example.com")
+                .body(Body::from(body.unwrap_or_default().to_string()))
+                .unwrap();
+            async move {
+                let resp = router.oneshot(req).await.unwrap();
+                let status = resp.status();
+                let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                let out: serde_json::Value = serde_json::from_slice(&body).unwrap_or_default();
+                (status, out)
+            }
+        };
+        for alias in ["page-a", "page-b", "page-c"] {
+            let body =
+                format!(r#"{{"original_url":"https://example.com/{alias}","alias":"{alias}"}}"#);
+            let (status, _) = send("/api/links".into(), Some(&body)).await;
+            assert_eq!(status, StatusCode::CREATED);
+        }
+
+        let mut seen = Vec::new();
+        let mut uri = "/api/links?limit=2".to_string();
+        loop {
+            let (status, out) = send(uri.clone(), None).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(out["total"], 3);
+            for link in out["links"].as_array().unwrap() {
+                seen.push(link["slug"].as_str().unwrap().to_string());
+            }
+            match out["next_cursor"].as_str() {
+                Some(cursor) => uri = format!("/api/links?limit=2&cursor={cursor}"),
+                None => break,
+            }
+        }
+        seen.sort();
+        assert_eq!(seen, ["page-a", "page-b", "page-c"]);
+
+        let (status, _) = send("/api/links?cursor=nope".into(), None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (_, out) = send("/api/links?host=Example.com&order=asc".into(), None).await;
+        let slugs: Vec<_> = out["links"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|l| l["slug"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(slugs, ["page-a", "page-b", "page-c"]);
+        let (_, out) = send("/api/links?status=deleted".into(), None).await;
+        assert_eq!(out["total"], 0);
+        for bad in [
+            "sort=clicks",
+            "order=up",
+            "status=gone",
+            "created_from=yesterday",
+        ] {
+            let (status, _) = send(format!("/api/links?{bad}"), None).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{bad}");
+        }
+    }
+
+    #[tokio::test]
+    async fn links_are_found_and_repointed_by_target() {
+        let router = app();
+        let send = |method: &str, uri: &str, user: &str, body: &str| {
+            let router = router.clone();
+            let req = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .header("X-Debug-User", user)
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            async move {
+                let resp = router.oneshot(req).await.unwrap();
+                let status = resp.status();
+                let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                let out: serde_json::Value = serde_json::from_slice(&body).unwrap_or_default();
+                (status, out)
+            }
+        };
+        let slugs = |out: &serde_json::Value| -> Vec<String> {
+            out["links"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|l| l["slug"].as_str().unwrap().to_string())
+                .collect()
+        };
+        for (alias, user, url) in [
+            ("wiki-home", "user@example.com", "https://wiki.example.com/"),
+            (
+                "wiki-page",
+                "user@example.com",
+                "https://wiki.example.com/page?id=1",
+            ),
+            (
+                "wiki-other",
+                "other@example.com",
+                "https://wiki.example.com/page",
+            ),
+            ("docs-home", "user@example.com", "https://docs.example.com/"),
+        ] {
+            let body = format!(r#"{{"original_url":"{url}","alias":"{alias}"}}"#);
+            let (status, _) = send("POST", "/api/links", user, &body).await;
+            assert_eq!(status, StatusCode::CREATED);
+        }
+
+        let lookup = |query: &str| format!("/api/links/by-target?{query}");
+        let (status, out) = send(
+            "GET",
+            &lookup("url=https://WIKI.example.com"),
+            "user@example.com",
+            "",
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(slugs(&out), ["wiki-home"]);
+        let (_, out) = send(
+            "GET",
+            &lookup("url=https://wiki.example.com/page&match=prefix"),
+            "user@example.com",
+            "",
+        )
+        .await;
+        assert_eq!(slugs(&out), ["wiki-page"]);
+        // Other users' links stay hidden
+        let (_, out) = send(
+            "GET",
+            &lookup("url=wiki.example.com&match=host"),
+            "user@example.com",
+            "",
+        )
+        .await;
+        assert_eq!(slugs(&out), ["wiki-home", "wiki-page"]);
+        let (status, _) = send(
+            "GET",
+            &lookup("url=wiki.example.com&match=nearby"),
+            "user@example.com",
+            "",
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, out) = send(
+            "POST",
+            "/api/links/by-target/repoint",
+            "user@example.com",
+            r#"{"url":"wiki.example.com","match":"host","to":"https://kb.example.com"}"#,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(out["affected"], 2);
+        let (_, out) = send(
+            "GET",
+            &lookup("url=kb.example.com&match=host"),
+            "user@example.com",
+            "",
+        )
+        .await;
+        let urls: Vec<&str> = out["links"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|l| l["original_url"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            urls,
+            [
+                "https://kb.example.com/",
+                "https://kb.example.com/page?id=1"
+            ]
         );
+        let (_, out) = send(
+            "GET",
+            &lookup("url=wiki.example.com&match=host"),
+            "other@example.com",
+            "",
+        )
+        .await;
+        assert_eq!(slugs(&out), ["wiki-other"]);
     }
 }
 
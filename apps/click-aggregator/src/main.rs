@@ -0,0 +1,224 @@
+//! click-aggregator — applies batched click counts to DynamoDB.
+//!
+//! Purpose
+//! - Receives the click batches `lambda-redirect` sends to the SQS queue at
+//!   `CLICK_QUEUE_URL` and applies them with `domain::clicks::ClickAggregator`:
+//!   one counter update per link and batch, plus the batch's click events.
+//! - Reads the same `DYNAMO_TABLE_*` (and `DYNAMO_ENDPOINT_URL`) variables as
+//!   the Lambdas.
+//!
+//! Usage
+//! - `cargo run -p click-aggregator` long-polls the queue until stopped.
+//! - `cargo run -p click-aggregator -- --drain` exits once the queue is empty,
+//!   for running it as a scheduled job.
+//! - Deployed as a Lambda (`AWS_LAMBDA_RUNTIME_API` set, see
+//!   infra/sam/template.yaml), every invocation drains the queue; the stack
+//!   invokes it on a schedule.
+//!
+//! Notes
+//! - A batch that fails to apply stays on the queue, reduced to what it has
+//!   left to apply, and is retried once its visibility timeout passes, so its
+//!   clicks are not lost. A batch applied but not acknowledged (the process
+//!   stopped in between, or SQS failed the delete) is applied again in full,
+//!   counting its links twice.
+
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Duration;
+
+use aws_dynamo::DynamoRepo;
+use aws_sqs::SqsClickQueue;
+use domain::clicks::ClickAggregator;
+use domain::{ClickRepository, LinkRepository, TenantScoped};
+use lambda_runtime::{service_fn, LambdaEvent};
+use serde_json::{json, Value};
+use tracing::{error, info};
+use tracing_subscriber::{fmt, prelude::*, EnvFilter};
+
+/// Batches received per call (the SQS maximum).
+const RECEIVE_MAX: usize = 10;
+
+/// Pause after a failure, so an unreachable queue or table isn't hammered.
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+fn main() -> ExitCode {
+    init_tracing();
+    let drain = match std::env::args().nth(1).as_deref() {
+        None => false,
+        Some("--drain") => true,
+        Some(_) => {
+            eprintln!("usage: click-aggregator [--drain]");
+            return ExitCode::from(2);
+        }
+    };
+
+    if std::env::var_os("AWS_LAMBDA_RUNTIME_API").is_some() {
+        return serve_lambda();
+    }
+
+    let Some(aggregator) = aggregator_from_env() else {
+        return ExitCode::FAILURE;
+    };
+    info!(drain, "applying click batches");
+    let applied = run(&aggregator, drain, RETRY_DELAY);
+    info!(total = applied, "click queue drained");
+    ExitCode::SUCCESS
+}
+
+/// Aggregator for the queue at `CLICK_QUEUE_URL` and the DynamoDB tables.
+/// Failures are logged.
+fn aggregator_from_env() -> Option<ClickAggregator<DynamoRepo, DynamoRepo>> {
+    let repo = match DynamoRepo::from_env() {
+        Ok(repo) => repo,
+        Err(e) => {
+            error!(err = %e, "dynamo init error");
+            return None;
+        }
+    };
+    let queue = match SqsClickQueue::from_env() {
+        Ok(queue) => Arc::new(queue),
+        Err(e) => {
+            error!(err = %e, "click queue init error");
+            return None;
+        }
+    };
+    Some(ClickAggregator::new(queue, repo.clone(), repo))
+}
+
+/// Drain the queue on every Lambda invocation. The clients are built inside
+/// the runtime, so their blocking calls use it instead of a runtime of their
+/// own.
+fn serve_lambda() -> ExitCode {
+    let rt = match tokio::runtime::Runtime::new() {
+        Ok(rt) => rt,
+        Err(e) => {
+            error!(err = %e, "tokio runtime error");
+            return ExitCode::FAILURE;
+        }
+    };
+    rt.block_on(async {
+        let Some(aggregator) = aggregator_from_env() else {
+            return ExitCode::FAILURE;
+        };
+        let aggregator = &aggregator;
+        let handler = service_fn(move |_: LambdaEvent<Value>| async move {
+            let applied = tokio::task::block_in_place(|| run(aggregator, true, RETRY_DELAY));
+            info!(total = applied, "click queue drained");
+            Ok::<_, lambda_runtime::Error>(json!({ "batches": applied }))
+        });
+        match lambda_runtime::run(handler).await {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                error!(err = %e, "lambda runtime error");
+                ExitCode::FAILURE
+            }
+        }
+    })
+}
+
+/// Apply batches until the queue is found empty with `drain`, else forever,
+/// pausing `retry_delay` after failures. Returns how many were received.
+fn run<L, C>(aggregator: &ClickAggregator<L, C>, drain: bool, retry_delay: Duration) -> usize
+where
+    L: LinkRepository + TenantScoped,
+    C: ClickRepository + TenantScoped,
+{
+    let mut applied = 0;
+    loop {
+        match aggregator.run_once(RECEIVE_MAX) {
+            Ok(0) if drain => return applied,
+            Ok(0) => {}
+            Ok(n) => {
+                applied += n;
+                info!(batches = n, total = applied, "click batches applied");
+            }
+            Err(e) => {
+                error!(err = %e, "applying click batches failed");
+                std::thread::sleep(retry_delay);
+            }
+        }
+    }
+}
+
+fn init_tracing() {
+    let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    tracing_subscriber::registry()
+        .with(env_filter)
+        .with(fmt::layer().with_target(true).with_writer(std::io::stdout))
+        .init();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use domain::adapters::memory_queue::MemoryClickQueue;
+    use domain::adapters::memory_repo::{InMemoryClickRepo, InMemoryRepo};
+    use domain::clicks::{ClickBatch, ClickCount, ClickQueue, QueuedBatch};
+    use domain::{CoreError, ShortLink, Slug, TenantId, UserEmail};
+    use std::sync::Mutex;
+    use std::time::UNIX_EPOCH;
+
+    /// In-memory queue whose first `failures` receives fail.
+    struct FlakyQueue {
+        inner: MemoryClickQueue,
+        failures: Mutex<usize>,
+    }
+
+    impl ClickQueue for FlakyQueue {
+        fn send(&self, batch: &ClickBatch) -> Result<(), CoreError> {
+            self.inner.send(batch)
+        }
+
+        fn receive(&self, max: usize) -> Result<Vec<QueuedBatch>, CoreError> {
+            let mut failures = self.failures.lock().unwrap();
+            if *failures > 0 {
+                *failures -= 1;
+                return Err(CoreError::Repository("queue down".into()));
+            }
+            self.inner.receive(max)
+        }
+
+        fn ack(&self, receipts: &[String]) -> Result<(), CoreError> {
+            self.inner.ack(receipts)
+        }
+    }
+
+    #[test]
+    fn drain_applies_every_batch_and_retries_failures() {
+        let queue = Arc::new(FlakyQueue {
+            inner: MemoryClickQueue::new(),
+            failures: Mutex::new(2),
+        });
+        let slug = Slug::new("docs").unwrap();
+        for _ in 0..RECEIVE_MAX + 2 {
+            let counts = vec![ClickCount {
+                tenant: TenantId::default(),
+                slug: slug.clone(),
+                clicks: 1,
+            }];
+            let batch = ClickBatch {
+                counts,
+                events: Vec::new(),
+            };
+            queue.send(&batch).unwrap();
+        }
+        let links = InMemoryRepo::new();
+        let docs = ShortLink::new(
+            slug.clone(),
+            "https://example.com".into(),
+            UNIX_EPOCH,
+            UserEmail::new("a@example.com").unwrap(),
+        );
+        links.put(docs).unwrap();
+        let aggregator = ClickAggregator::new(
+            queue.clone(),
+            links.for_tenant(&TenantId::default()),
+            InMemoryClickRepo::new(),
+        );
+
+        assert_eq!(run(&aggregator, true, Duration::ZERO), RECEIVE_MAX + 2);
+        assert!(queue.inner.is_empty());
+        let counted = links.get(&slug).unwrap().unwrap().click_count;
+        assert_eq!(counted, RECEIVE_MAX as u64 + 2);
+    }
+}
@@ -3,8 +3,48 @@
 //! Purpose
 //! - Handle API Gateway HTTP API (v2) events for:
 //!   - `POST /api/links` — create short link (requires Google Bearer auth).
-//!   - `GET /api/links` — list links (requires Google Bearer auth).
+//!   - `GET /api/links` — list links (requires Google Bearer auth). With
+//!     `scope=accessible`, lists every link the caller can access (own, group, shared).
+//!     Pages are `limit` long; pass a response's `next_cursor` as `cursor` for the next.
+//!     `sort=created_at|updated_at|click_count|expires_at` with `order=asc|desc`,
+//!     `status=active|inactive|expired|scheduled|deleted`, `created_from`/`_to`
+//!     (likewise `updated_`, `expires_`; RFC 3339) and `host` narrow the list.
 //!   - `GET /api/me` — get current user info (email, is_admin).
+//!   - `GET|POST /api/links/{slug}/collaborators`, `DELETE .../collaborators/{email}` —
+//!     share a single link with specific users (per-link grants).
+//!   - `POST /api/links/{slug}/rename` — move a link to a new slug; the old slug
+//!     keeps forwarding. `GET|POST /api/links/{slug}/aliases` and
+//!     `DELETE .../aliases/{alias}` manage extra slugs sharing the link's target,
+//!     settings and clicks. Any alias also works in `/api/links/{slug}` paths.
+//!   - `POST /api/groups/{id}/members` — invite a user to a group; the member is
+//!     only added once the invitee accepts. `GET /api/groups/{id}/invitations`
+//!     lists the group's open invitations.
+//!   - `GET /api/invitations`, `POST /api/invitations/{id}/accept|decline` — the
+//!     caller's pending invitations and the acceptance flow.
+//!   - `DELETE /api/groups/{id}?links=reassign|detach|soft_delete` — delete a group,
+//!     choosing what happens to its links (`reassign` needs `target_group_id`).
+//!   - `POST /api/groups/{id}/transfer` — hand group ownership to another member.
+//!   - `PATCH /api/groups/{id}` — rename a group or replace its link `settings`
+//!     (default expiry/redirect delay, allowed hosts, slug prefix, link limit),
+//!     which are enforced when links are created in or moved into the group.
+//!   - Groups nest via `parent_id` (set on create or `PATCH`); `GET /api/groups`
+//!     lists the caller's groups as a tree, each with its `depth`.
+//!   - `GET /api/orgs`, `PUT /api/orgs/{id}` — manage organizations (tenants);
+//!     deployment admins only.
+//!   - `GET /api/domains`, `PUT|DELETE /api/domains/{host}` — the organization's
+//!     extra short domains and who may create links on them (admins manage).
+//!     Links on such a domain are created with `domain` and addressed in
+//!     `/api/links/{slug}` paths as `host:slug`.
+//!   - `GET /api/namespaces`, `PUT|DELETE /api/namespaces/{path}` — slug
+//!     namespaces (`hr` in `hr/onboarding`) reserved by a group; only the group's
+//!     editors create slugs under them. Group admins reserve and release them.
+//!     Namespaced slugs are percent-encoded in `/api/links/{slug}` paths.
+//!   - `GET /api/slugs/check?alias=` — whether an alias is free (`domain` and
+//!     `group_id` optional) and which rules it breaks. `GET /api/slugs/suggest?url=`
+//!     — readable slugs for a target (`title` optional) that are not taken yet.
+//!   - `GET /api/links/by-target?url=&match=exact|prefix|host` — the caller's
+//!     visible links pointing at a destination. `POST /api/links/by-target/repoint`
+//!     (`url`, `match`, `to`) moves the ones they may edit to a new destination.
 //! - Use `LinkService` with `DynamoRepo` for persistence.
 //! - Initialize structured logging compatible with Lambda.
 //!
@@ -14,9 +54,30 @@
 //!   adapter will run without signature verification; this process emits a WARN
 //!   at startup reminding not to use this mode in production.
 //!
+//! Tenancy
+//! - Every request is served for one organization, picked by the request host
+//!   or else by the sign-in domain of the bearer token; unmatched requests use
+//!   the default tenant. All reads and writes go to that tenant's partition.
+//! - Organization lookups are kept for `ORG_CACHE_TTL_SECS` (default 30), so
+//!   organization changes reach every container within that time.
+//! - An organization with `auth_domains` only accepts those sign-in domains;
+//!   otherwise `ALLOWED_DOMAIN` applies.
+//!
+//! Redirect cache
+//! - With `LINK_CACHE_REDIS_URL` (the server lambda-redirect shares lookups
+//!   through), link writes, renames, aliases and group deletions drop the
+//!   entries of the links they touch, so redirects see them on the next miss
+//!   of the redirect container's own cache (`LINK_CACHE_TTL_SECS`).
+//!
 //! Authorization
 //! - Regular users can only see/edit their own links.
-//! - Admins (listed in `ADMIN_EMAILS` env var) can see/edit all links.
+//! - Admins (listed in `ADMIN_EMAILS` env var, or in the organization's
+//!   `admins`) can see/edit all links of the organization.
+//! - Link collaborators with the `editor` role can edit/delete that single link.
+//! - Invitations can only be answered by the invited email address.
+//! - Every group keeps at least one admin; removing the last one is rejected.
+//! - A group role also applies to every subgroup; the highest role along the
+//!   group's ancestry wins. Nesting a group requires admin on the new parent.
 //! - `ADMIN_EMAILS` is a comma-separated list of email addresses.
 
 use lambda_http::{run, service_fn, Body, Error, Request, Response};
@@ -24,20 +85,53 @@ use tracing::{error, info, warn};
 use tracing_subscriber::{fmt, prelude::*, EnvFilter};
 
 use aws_dynamo::DynamoRepo;
-use domain::slug::Base62SlugGenerator;
+use domain::cache::{CacheOptions, CachedLinkRepo};
+use domain::hierarchy;
+use domain::slug::{Base62SlugGenerator, SlugStyle, WordSlugGenerator};
+use domain::tenant::OrganizationCache;
 use domain::LinkRepository;
 use domain::SlugGenerator;
 use domain::{
-    Clock, CoreError, GroupMember, GroupRepository, GroupRole, LinkGroup, Slug, UserEmail,
+    AuditAction, AuditEntry, AuditRepository, Clock, CoreError, DomainRepository, GroupInvitation,
+    GroupLinkDisposition, GroupMember, GroupRepository, GroupRole, GroupSettings,
+    InvitationRepository, InvitationStatus, LinkGrant, LinkGrantRepository, LinkGroup,
+    LinkVisibility, Namespace, NamespaceRepository, Organization, OrganizationRepository,
+    OrganizationSettings, ShortDomain, ShortLink, Slug, TargetMatch, TenantId, TenantScoped,
+    UserEmail,
 };
 use google_auth::{AuthError as GAuthError, VerifiedUser};
 use http_common::lambda::{get_host, resp, resp_with_error, with_cors};
+use std::sync::Arc;
 
 #[derive(Clone)]
 struct AppState {
+    /// Scoped to `org`'s tenant once the request is routed.
     repo: DynamoRepo,
+    /// `repo` for link writes, dropping the redirect cache entries they touch.
+    links: CachedLinkRepo<DynamoRepo>,
     slugger: Base62SlugGenerator,
+    word_slugger: WordSlugGenerator,
     clock: StdClock,
+    /// Organizations by host, shared by all requests the container serves.
+    orgs: Arc<OrganizationCache>,
+    org: Organization,
+}
+
+impl AppState {
+    /// Deployment admins (`ADMIN_EMAILS`) and the organization's own admins.
+    fn is_admin(&self, email: &str) -> bool {
+        is_admin(email) || self.org.is_admin(email)
+    }
+
+    /// Host used in short URLs: the organization's short-link domain, else the
+    /// request host.
+    fn short_host<'a>(&'a self, req: &'a Request) -> &'a str {
+        self.org
+            .settings
+            .shortlink_domain
+            .as_deref()
+            .unwrap_or_else(|| get_host(req))
+    }
 }
 
 #[derive(Clone)]
@@ -61,6 +155,17 @@ struct CreateLinkReq {
     redirect_delay: Option<u32>,
     #[serde(default)]
     group_id: Option<String>,
+    #[serde(default)]
+    visibility: Option<String>,
+    /// Extra short domain to create the link on; the default domain when unset.
+    #[serde(default)]
+    domain: Option<String>,
+    /// How to generate the slug when no alias is given: "base62" (default) or "words".
+    #[serde(default)]
+    slug_style: Option<String>,
+    /// Return an existing link to the same target (200) instead of generating a new slug.
+    #[serde(default)]
+    dedupe: bool,
 }
 
 #[derive(serde::Deserialize)]
@@ -79,6 +184,8 @@ struct UpdateLinkReq {
     redirect_delay: Option<Option<u32>>,
     #[serde(default)]
     group_id: Option<Option<String>>,
+    #[serde(default)]
+    visibility: Option<String>,
 }
 
 #[derive(serde::Deserialize)]
@@ -100,6 +207,13 @@ struct CreateGroupReq {
     name: String,
     #[serde(default)]
     description: Option<String>,
+    #[serde(default)]
+    parent_id: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct TransferGroupReq {
+    email: String,
 }
 
 #[derive(serde::Deserialize)]
@@ -108,6 +222,54 @@ struct UpdateGroupReq {
     name: Option<String>,
     #[serde(default)]
     description: Option<Option<String>>,
+    /// Replaces the group's settings as a whole; omitted fields are cleared.
+    #[serde(default)]
+    settings: Option<GroupSettingsBody>,
+    /// New parent group; an empty string moves the group to the top level.
+    #[serde(default)]
+    parent_id: Option<String>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+struct GroupSettingsBody {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    default_expiry_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    default_redirect_delay: Option<u32>,
+    #[serde(default)]
+    allowed_hosts: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    slug_prefix: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_links: Option<u32>,
+}
+
+impl From<&GroupSettings> for GroupSettingsBody {
+    fn from(s: &GroupSettings) -> Self {
+        Self {
+            default_expiry_secs: s.default_expiry_secs,
+            default_redirect_delay: s.default_redirect_delay,
+            allowed_hosts: s.allowed_hosts.clone(),
+            slug_prefix: s.slug_prefix.clone(),
+            max_links: s.max_links,
+        }
+    }
+}
+
+impl From<GroupSettingsBody> for GroupSettings {
+    fn from(b: GroupSettingsBody) -> Self {
+        Self {
+            default_expiry_secs: b.default_expiry_secs,
+            default_redirect_delay: b.default_redirect_delay,
+            allowed_hosts: b
+                .allowed_hosts
+                .into_iter()
+                .map(|h| h.trim().to_ascii_lowercase())
+                .collect(),
+            slug_prefix: b.slug_prefix.filter(|p| !p.is_empty()),
+            max_links: b.max_links,
+        }
+    }
 }
 
 #[derive(serde::Deserialize)]
@@ -115,12 +277,46 @@ struct AddMemberReq {
     email: String,
     #[serde(default = "default_role")]
     role: String,
+    #[serde(default)]
+    expires_in_days: Option<u32>,
 }
 
 fn default_role() -> String {
     "editor".into()
 }
 
+/// Invitations stay open for a week unless the inviter asks otherwise.
+const DEFAULT_INVITATION_DAYS: u32 = 7;
+const MAX_INVITATION_DAYS: u32 = 30;
+
+#[derive(serde::Deserialize)]
+struct AddCollaboratorReq {
+    email: String,
+    #[serde(default = "default_role")]
+    role: String,
+}
+
+#[derive(serde::Deserialize)]
+struct RenameLinkReq {
+    slug: String,
+}
+
+#[derive(serde::Deserialize)]
+struct AddAliasReq {
+    alias: String,
+}
+
+#[derive(serde::Serialize)]
+struct AliasOut {
+    slug: String,
+    short_url: String,
+}
+
+#[derive(serde::Serialize)]
+struct AliasListOut {
+    aliases: Vec<AliasOut>,
+}
+
 #[derive(serde::Serialize)]
 struct GroupOut {
     id: String,
@@ -131,6 +327,12 @@ struct GroupOut {
     created_by: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     role: Option<String>,
+    settings: GroupSettingsBody,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent_id: Option<String>,
+    /// Nesting level in tree listings (0 for top-level groups).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    depth: Option<usize>,
 }
 
 #[derive(serde::Serialize)]
@@ -151,6 +353,38 @@ struct MemberListOut {
     members: Vec<MemberOut>,
 }
 
+#[derive(serde::Serialize)]
+struct InvitationOut {
+    id: String,
+    group_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    group_name: Option<String>,
+    email: String,
+    role: String,
+    invited_by: String,
+    created_at: String,
+    expires_at: String,
+    status: String,
+}
+
+#[derive(serde::Serialize)]
+struct InvitationListOut {
+    invitations: Vec<InvitationOut>,
+}
+
+#[derive(serde::Serialize)]
+struct CollaboratorOut {
+    email: String,
+    role: String,
+    granted_at: String,
+    granted_by: String,
+}
+
+#[derive(serde::Serialize)]
+struct CollaboratorListOut {
+    collaborators: Vec<CollaboratorOut>,
+}
+
 fn group_to_out(group: &LinkGroup, role: Option<GroupRole>) -> GroupOut {
     GroupOut {
         id: group.id.clone(),
@@ -159,6 +393,9 @@ fn group_to_out(group: &LinkGroup, role: Option<GroupRole>) -> GroupOut {
         created_at: http_common::system_time_to_rfc3339(group.created_at),
         created_by: group.created_by.as_str().to_string(),
         role: role.map(|r| r.as_str().to_string()),
+        settings: GroupSettingsBody::from(&group.settings),
+        parent_id: group.parent_id.clone(),
+        depth: None,
     }
 }
 
@@ -171,9 +408,35 @@ fn member_to_out(member: &GroupMember) -> MemberOut {
     }
 }
 
+fn invitation_to_out(inv: &GroupInvitation, group_name: Option<String>) -> InvitationOut {
+    InvitationOut {
+        id: inv.id.clone(),
+        group_id: inv.group_id.clone(),
+        group_name,
+        email: inv.email.as_str().to_string(),
+        role: inv.role.as_str().to_string(),
+        invited_by: inv.invited_by.as_str().to_string(),
+        created_at: http_common::system_time_to_rfc3339(inv.created_at),
+        expires_at: http_common::system_time_to_rfc3339(inv.expires_at),
+        status: inv.status.as_str().to_string(),
+    }
+}
+
+fn grant_to_out(grant: &LinkGrant) -> CollaboratorOut {
+    CollaboratorOut {
+        email: grant.user_email.as_str().to_string(),
+        role: grant.role.as_str().to_string(),
+        granted_at: http_common::system_time_to_rfc3339(grant.granted_at),
+        granted_by: grant.granted_by.as_str().to_string(),
+    }
+}
+
 #[derive(serde::Serialize)]
 struct LinkOut {
     slug: String,
+    /// Short domain of the link; unset for the default domain.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    domain: Option<String>,
     short_url: String,
     original_url: String,
     created_at: String,
@@ -192,13 +455,22 @@ struct LinkOut {
     redirect_delay: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     group_id: Option<String>,
+    visibility: &'static str,
+    /// Caller's effective role on the link; only set by `scope=accessible` listings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    role: Option<&'static str>,
 }
 
 #[derive(serde::Serialize)]
 struct ListOut {
     links: Vec<LinkOut>,
-    total: usize,
+    /// Left out on later cursor pages of stores that only count the first.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    total: Option<usize>,
     has_more: bool,
+    /// Pass as `cursor` to fetch the next page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next_cursor: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     user: Option<UserInfo>,
 }
@@ -209,9 +481,167 @@ struct UserInfo {
     is_admin: bool,
 }
 
+#[derive(serde::Serialize)]
+struct MeOut {
+    #[serde(flatten)]
+    user: UserInfo,
+    tenant: String,
+    organization: String,
+}
+
+#[derive(serde::Deserialize)]
+struct PutDomainReq {
+    #[serde(default)]
+    allowed_users: Vec<String>,
+    #[serde(default)]
+    allowed_groups: Vec<String>,
+}
+
+#[derive(serde::Serialize)]
+struct DomainOut {
+    host: String,
+    allowed_users: Vec<String>,
+    allowed_groups: Vec<String>,
+    created_at: String,
+    created_by: String,
+    /// Whether the caller may create links on the domain.
+    can_use: bool,
+}
+
+#[derive(serde::Serialize)]
+struct DomainListOut {
+    domains: Vec<DomainOut>,
+}
+
+fn domain_to_out(domain: &ShortDomain, can_use: bool) -> DomainOut {
+    DomainOut {
+        host: domain.host.clone(),
+        allowed_users: domain
+            .allowed_users
+            .iter()
+            .map(|u| u.as_str().to_string())
+            .collect(),
+        allowed_groups: domain.allowed_groups.clone(),
+        created_at: http_common::system_time_to_rfc3339(domain.created_at),
+        created_by: domain.created_by.as_str().to_string(),
+        can_use,
+    }
+}
+
+#[derive(serde::Serialize)]
+struct SlugCheckOut {
+    alias: String,
+    /// Free to use: no violations and not taken.
+    available: bool,
+    /// A link or alias already uses the slug.
+    taken: bool,
+    violations: Vec<String>,
+}
+
+#[derive(serde::Serialize)]
+struct SlugSuggestOut {
+    suggestions: Vec<AliasOut>,
+}
+
+#[derive(serde::Deserialize)]
+struct RepointReq {
+    url: String,
+    /// `exact` (default), `prefix` or `host`.
+    #[serde(rename = "match")]
+    mode: Option<String>,
+    /// New destination; for `prefix` and `host` matches the rest of each
+    /// link's URL is kept (see [`domain::TargetMatch::rewrite`]).
+    to: String,
+}
+
+#[derive(serde::Serialize)]
+struct RepointOut {
+    affected: usize,
+    slugs: Vec<String>,
+    /// Matching links the caller may see but not change, or whose new target
+    /// breaks a group or organization rule.
+    skipped: Vec<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct PutNamespaceReq {
+    group_id: String,
+}
+
+#[derive(serde::Serialize)]
+struct NamespaceOut {
+    path: String,
+    group_id: String,
+    created_at: String,
+    created_by: String,
+}
+
+#[derive(serde::Serialize)]
+struct NamespaceListOut {
+    namespaces: Vec<NamespaceOut>,
+}
+
+fn namespace_to_out(namespace: &Namespace) -> NamespaceOut {
+    NamespaceOut {
+        path: namespace.path.clone(),
+        group_id: namespace.group_id.clone(),
+        created_at: http_common::system_time_to_rfc3339(namespace.created_at),
+        created_by: namespace.created_by.as_str().to_string(),
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct PutOrganizationReq {
+    name: String,
+    #[serde(default)]
+    hosts: Vec<String>,
+    #[serde(default)]
+    auth_domains: Vec<String>,
+    #[serde(default)]
+    admins: Vec<String>,
+    #[serde(default)]
+    shortlink_domain: Option<String>,
+    #[serde(default)]
+    allowed_hosts: Vec<String>,
+}
+
+#[derive(serde::Serialize)]
+struct OrganizationOut {
+    id: String,
+    name: String,
+    hosts: Vec<String>,
+    auth_domains: Vec<String>,
+    admins: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shortlink_domain: Option<String>,
+    allowed_hosts: Vec<String>,
+    created_at: String,
+}
+
+#[derive(serde::Serialize)]
+struct OrganizationListOut {
+    organizations: Vec<OrganizationOut>,
+}
+
+fn organization_to_out(org: &Organization) -> OrganizationOut {
+    OrganizationOut {
+        id: org.id.as_str().to_string(),
+        name: org.name.clone(),
+        hosts: org.hosts.clone(),
+        auth_domains: org.auth_domains.clone(),
+        admins: org.admins.iter().map(|a| a.as_str().to_string()).collect(),
+        shortlink_domain: org.settings.shortlink_domain.clone(),
+        allowed_hosts: org.settings.allowed_hosts.clone(),
+        created_at: http_common::system_time_to_rfc3339(org.created_at),
+    }
+}
+
+/// `host` is the default short domain; links on another domain use their own.
 fn link_to_out(link: domain::ShortLink, host: &str) -> LinkOut {
+    let host = link.slug.domain().unwrap_or(host);
     LinkOut {
         slug: link.slug.as_str().to_string(),
+        domain: link.slug.domain().map(str::to_string),
         short_url: http_common::build_short_url_from_host(host, link.slug.as_str()),
         original_url: link.original_url,
         created_at: http_common::system_time_to_rfc3339(link.created_at),
@@ -224,6 +654,8 @@ fn link_to_out(link: domain::ShortLink, host: &str) -> LinkOut {
         activate_at: link.activate_at.map(http_common::system_time_to_rfc3339),
         redirect_delay: link.redirect_delay,
         group_id: link.group_id,
+        visibility: link.visibility.as_str(),
+        role: None,
     }
 }
 
@@ -233,10 +665,15 @@ async fn main() -> Result<(), Error> {
     warn_if_insecure_skip_sig();
 
     let repo = DynamoRepo::from_env().map_err(|e| format!("dynamo init error: {e}"))?;
+    let links = link_cache_from_env(repo.clone()).map_err(|e| format!("cache init error: {e}"))?;
     let state = AppState {
         repo,
+        links,
         slugger: Base62SlugGenerator::new(5),
+        word_slugger: word_slugger_from_env().map_err(|e| format!("slug config error: {e}"))?,
         clock: StdClock,
+        orgs: Arc::new(org_cache_from_env()),
+        org: Organization::fallback(),
     };
 
     let handler = service_fn(move |req: Request| {
@@ -247,6 +684,61 @@ async fn main() -> Result<(), Error> {
     Ok(())
 }
 
+/// Link writes through the Redis-compatible cache at `LINK_CACHE_REDIS_URL`,
+/// when set. Nothing is cached here; the decorator only drops entries.
+fn link_cache_from_env(repo: DynamoRepo) -> Result<CachedLinkRepo<DynamoRepo>, CoreError> {
+    let options = CacheOptions {
+        capacity: 0,
+        ..CacheOptions::default()
+    };
+    let cache = CachedLinkRepo::new(repo, options);
+    match std::env::var("LINK_CACHE_REDIS_URL") {
+        Ok(url) if !url.is_empty() => {
+            Ok(cache.with_backend(Arc::new(redis_cache::RedisLinkCache::new(&url)?)))
+        }
+        _ => Ok(cache),
+    }
+}
+
+/// Organization cache from env: `ORG_CACHE_TTL_SECS` (default 30).
+fn org_cache_from_env() -> OrganizationCache {
+    std::env::var("ORG_CACHE_TTL_SECS")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .map(|secs| OrganizationCache::new(std::time::Duration::from_secs(secs)))
+        .unwrap_or_default()
+}
+
+/// Build the word slug generator from SLUG_WORD_DICTIONARY_SIZE,
+/// SLUG_WORD_COUNT and SLUG_WORD_SEPARATOR; unset values keep the defaults.
+fn word_slugger_from_env() -> Result<WordSlugGenerator, String> {
+    let default = WordSlugGenerator::default();
+    let number = |name: &str, fallback: usize| match std::env::var(name) {
+        Ok(s) => s
+            .trim()
+            .parse::<usize>()
+            .map_err(|_| format!("{name} must be a number, got '{s}'")),
+        Err(_) => Ok(fallback),
+    };
+    let dictionary_size = number("SLUG_WORD_DICTIONARY_SIZE", default.dictionary_size())?;
+    let word_count = number("SLUG_WORD_COUNT", default.word_count())?;
+    let separator = match std::env::var("SLUG_WORD_SEPARATOR") {
+        Ok(s) => {
+            let mut chars = s.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => c,
+                _ => {
+                    return Err(format!(
+                        "SLUG_WORD_SEPARATOR must be one character, got '{s}'"
+                    ))
+                }
+            }
+        }
+        Err(_) => default.separator(),
+    };
+    WordSlugGenerator::new(dictionary_size, word_count, separator).map_err(|e| e.to_string())
+}
+
 fn init_tracing() {
     let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
     tracing_subscriber::registry()
@@ -275,7 +767,29 @@ fn is_admin(email: &str) -> bool {
         .any(|admin| admin.eq_ignore_ascii_case(email))
 }
 
+/// Resolve the request's organization (host first, then the sign-in domain of
+/// the bearer token) and scope the repository to its tenant.
+fn scope_to_tenant(mut state: AppState, req: &Request) -> Result<AppState, CoreError> {
+    let host = Some(get_host(req)).filter(|h| !h.is_empty());
+    let token_domain = bearer_token(req).and_then(google_auth::token_domain);
+    let org = state
+        .orgs
+        .resolve(&state.repo, host, token_domain.as_deref())?
+        .unwrap_or_else(Organization::fallback);
+    state.repo = state.repo.for_tenant(&org.id);
+    state.links = state.links.for_tenant(&org.id);
+    state.org = org;
+    Ok(state)
+}
+
 async fn route(state: AppState, req: Request) -> Result<Response<Body>, Error> {
+    let state = match scope_to_tenant(state, &req) {
+        Ok(s) => s,
+        Err(e) => {
+            error!(err=?e, "organization lookup error");
+            return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+        }
+    };
     let raw_path = req.uri().path().to_string();
     let method = req.method().as_str().to_string();
 
@@ -310,6 +824,28 @@ async fn route(state: AppState, req: Request) -> Result<Response<Body>, Error> {
             ))),
         };
     }
+    if path == "/api/links/by-target" {
+        return match method.as_str() {
+            "OPTIONS" => Ok(with_cors(resp(204, None, None))),
+            "GET" => links_by_target(state, req).await,
+            _ => Ok(with_cors(resp(
+                405,
+                None,
+                Some(http_common::json_err("method_not_allowed")),
+            ))),
+        };
+    }
+    if path == "/api/links/by-target/repoint" {
+        return match method.as_str() {
+            "OPTIONS" => Ok(with_cors(resp(204, None, None))),
+            "POST" => repoint_links_by_target(state, req).await,
+            _ => Ok(with_cors(resp(
+                405,
+                None,
+                Some(http_common::json_err("method_not_allowed")),
+            ))),
+        };
+    }
     if path == "/api/links/bulk/deactivate" {
         return match method.as_str() {
             "OPTIONS" => Ok(with_cors(resp(204, None, None))),
@@ -322,10 +858,71 @@ async fn route(state: AppState, req: Request) -> Result<Response<Body>, Error> {
         };
     }
 
-    // Check if path is /api/links/{slug}
+    // Link collaborator routes: /api/links/{slug}/collaborators[/{email}]
     let slug_path_prefix = "/api/links/";
+    if path.starts_with(slug_path_prefix) && path.contains("/collaborators") {
+        let rest = &path[slug_path_prefix.len()..];
+        if let Some(idx) = rest.find("/collaborators") {
+            let slug = decode_path_slug(&rest[..idx]);
+            let after = &rest[idx + "/collaborators".len()..];
+
+            if after.is_empty() {
+                return match method.as_str() {
+                    "OPTIONS" => Ok(with_cors(resp(204, None, None))),
+                    "GET" => list_link_collaborators(state, req, slug).await,
+                    "POST" => add_link_collaborator(state, req, slug).await,
+                    _ => Ok(with_cors(resp(
+                        405,
+                        None,
+                        Some(http_common::json_err("method_not_allowed")),
+                    ))),
+                };
+            } else if after.starts_with('/') && after.len() > 1 {
+                let email = after[1..].to_string();
+                return match method.as_str() {
+                    "OPTIONS" => Ok(with_cors(resp(204, None, None))),
+                    "DELETE" => remove_link_collaborator(state, req, slug, email).await,
+                    _ => Ok(with_cors(resp(
+                        405,
+                        None,
+                        Some(http_common::json_err("method_not_allowed")),
+                    ))),
+                };
+            }
+        }
+    }
+
+    // Rename and alias routes: /api/links/{slug}/rename, /api/links/{slug}/aliases[/{alias}]
+    if let Some(rest) = path.strip_prefix(slug_path_prefix) {
+        let mut parts = rest.splitn(3, '/');
+        let (slug, action, alias) = (parts.next(), parts.next(), parts.next());
+        if let (Some(slug), Some(action)) = (slug.filter(|s| !s.is_empty()), action) {
+            let slug = decode_path_slug(slug);
+            match (action, alias, method.as_str()) {
+                ("rename", None, "OPTIONS") | ("aliases", _, "OPTIONS") => {
+                    return Ok(with_cors(resp(204, None, None)))
+                }
+                ("rename", None, "POST") => return rename_link(state, req, slug).await,
+                ("aliases", None, "GET") => return list_link_aliases(state, req, slug).await,
+                ("aliases", None, "POST") => return add_link_alias(state, req, slug).await,
+                ("aliases", Some(alias), "DELETE") if !alias.is_empty() => {
+                    return remove_link_alias(state, req, slug, alias.to_string()).await
+                }
+                ("rename", None, _) | ("aliases", _, _) => {
+                    return Ok(with_cors(resp(
+                        405,
+                        None,
+                        Some(http_common::json_err("method_not_allowed")),
+                    )))
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Check if path is /api/links/{slug}
     if path.starts_with(slug_path_prefix) && path.len() > slug_path_prefix.len() {
-        let slug = path[slug_path_prefix.len()..].to_string();
+        let slug = decode_path_slug(&path[slug_path_prefix.len()..]);
         return match method.as_str() {
             "OPTIONS" => Ok(with_cors(resp(204, None, None))),
             "PATCH" => update_link(state, req, slug).await,
@@ -375,35 +972,155 @@ async fn route(state: AppState, req: Request) -> Result<Response<Body>, Error> {
         }
     }
 
-    // Group routes: /api/groups/{id}
-    let groups_prefix = "/api/groups/";
-    if path.starts_with(groups_prefix)
-        && path.len() > groups_prefix.len()
-        && !path.contains("/members")
-    {
-        let group_id = path[groups_prefix.len()..].to_string();
-        return match method.as_str() {
-            "OPTIONS" => Ok(with_cors(resp(204, None, None))),
-            "GET" => get_group(state, req, group_id).await,
-            "PATCH" => update_group(state, req, group_id).await,
-            "DELETE" => delete_group(state, req, group_id).await,
-            _ => Ok(with_cors(resp(
-                405,
-                None,
-                Some(http_common::json_err("method_not_allowed")),
-            ))),
-        };
+    // Group invitations: /api/groups/{id}/invitations
+    if path.starts_with(group_members_prefix) && path.ends_with("/invitations") {
+        let group_id =
+            path[group_members_prefix.len()..path.len() - "/invitations".len()].to_string();
+        if !group_id.is_empty() && !group_id.contains('/') {
+            return match method.as_str() {
+                "OPTIONS" => Ok(with_cors(resp(204, None, None))),
+                "GET" => list_group_invitations(state, req, group_id).await,
+                _ => Ok(with_cors(resp(
+                    405,
+                    None,
+                    Some(http_common::json_err("method_not_allowed")),
+                ))),
+            };
+        }
     }
 
-    match (method.as_str(), path.as_str()) {
-        ("OPTIONS", "/api/links") | ("OPTIONS", "/api/me") | ("OPTIONS", "/api/groups") => {
-            Ok(with_cors(resp(204, None, None)))
+    // Ownership transfer: /api/groups/{id}/transfer
+    if path.starts_with(group_members_prefix) && path.ends_with("/transfer") {
+        let group_id = path[group_members_prefix.len()..path.len() - "/transfer".len()].to_string();
+        if !group_id.is_empty() && !group_id.contains('/') {
+            return match method.as_str() {
+                "OPTIONS" => Ok(with_cors(resp(204, None, None))),
+                "POST" => transfer_group(state, req, group_id).await,
+                _ => Ok(with_cors(resp(
+                    405,
+                    None,
+                    Some(http_common::json_err("method_not_allowed")),
+                ))),
+            };
         }
-        ("POST", "/api/links") => create_link(state, req).await,
-        ("GET", "/api/links") => list_links(state, req).await,
-        ("GET", "/api/me") => get_me(req).await,
-        ("GET", "/api/groups") => list_groups(state, req).await,
+    }
+
+    // Invitee routes: /api/invitations/{id}/accept and /api/invitations/{id}/decline
+    let invitations_prefix = "/api/invitations/";
+    if let Some(rest) = path.strip_prefix(invitations_prefix) {
+        let action = rest
+            .strip_suffix("/accept")
+            .map(|id| (id, true))
+            .or_else(|| rest.strip_suffix("/decline").map(|id| (id, false)));
+        if let Some((id, accept)) = action {
+            if !id.is_empty() && !id.contains('/') {
+                let id = id.to_string();
+                return match method.as_str() {
+                    "OPTIONS" => Ok(with_cors(resp(204, None, None))),
+                    "POST" => respond_to_invitation(state, req, id, accept).await,
+                    _ => Ok(with_cors(resp(
+                        405,
+                        None,
+                        Some(http_common::json_err("method_not_allowed")),
+                    ))),
+                };
+            }
+        }
+    }
+
+    // Short domain routes: /api/domains/{host}
+    if let Some(host) = path.strip_prefix("/api/domains/") {
+        if !host.is_empty() && !host.contains('/') {
+            let host = host.to_string();
+            return match method.as_str() {
+                "OPTIONS" => Ok(with_cors(resp(204, None, None))),
+                "PUT" => put_domain(state, req, host).await,
+                "DELETE" => delete_domain(state, req, host).await,
+                _ => Ok(with_cors(resp(
+                    405,
+                    None,
+                    Some(http_common::json_err("method_not_allowed")),
+                ))),
+            };
+        }
+    }
+
+    // Namespace routes: /api/namespaces/{path}; the path may span segments
+    if let Some(ns_path) = path.strip_prefix("/api/namespaces/") {
+        if !ns_path.is_empty() {
+            let ns_path = decode_path_slug(ns_path);
+            return match method.as_str() {
+                "OPTIONS" => Ok(with_cors(resp(204, None, None))),
+                "PUT" => put_namespace(state, req, ns_path).await,
+                "DELETE" => delete_namespace(state, req, ns_path).await,
+                _ => Ok(with_cors(resp(
+                    405,
+                    None,
+                    Some(http_common::json_err("method_not_allowed")),
+                ))),
+            };
+        }
+    }
+
+    // Organization routes: /api/orgs/{id}
+    if let Some(org_id) = path.strip_prefix("/api/orgs/") {
+        if !org_id.is_empty() && !org_id.contains('/') {
+            let org_id = org_id.to_string();
+            return match method.as_str() {
+                "OPTIONS" => Ok(with_cors(resp(204, None, None))),
+                "PUT" => put_organization(state, req, org_id).await,
+                _ => Ok(with_cors(resp(
+                    405,
+                    None,
+                    Some(http_common::json_err("method_not_allowed")),
+                ))),
+            };
+        }
+    }
+
+    // Group routes: /api/groups/{id}
+    let groups_prefix = "/api/groups/";
+    if path.starts_with(groups_prefix)
+        && path.len() > groups_prefix.len()
+        && !path.contains("/members")
+        && !path.contains("/invitations")
+        && !path.ends_with("/transfer")
+    {
+        let group_id = path[groups_prefix.len()..].to_string();
+        return match method.as_str() {
+            "OPTIONS" => Ok(with_cors(resp(204, None, None))),
+            "GET" => get_group(state, req, group_id).await,
+            "PATCH" => update_group(state, req, group_id).await,
+            "DELETE" => delete_group(state, req, group_id).await,
+            _ => Ok(with_cors(resp(
+                405,
+                None,
+                Some(http_common::json_err("method_not_allowed")),
+            ))),
+        };
+    }
+
+    match (method.as_str(), path.as_str()) {
+        ("OPTIONS", "/api/links")
+        | ("OPTIONS", "/api/me")
+        | ("OPTIONS", "/api/groups")
+        | ("OPTIONS", "/api/invitations")
+        | ("OPTIONS", "/api/orgs")
+        | ("OPTIONS", "/api/domains")
+        | ("OPTIONS", "/api/namespaces")
+        | ("OPTIONS", "/api/slugs/check")
+        | ("OPTIONS", "/api/slugs/suggest") => Ok(with_cors(resp(204, None, None))),
+        ("POST", "/api/links") => create_link(state, req).await,
+        ("GET", "/api/links") => list_links(state, req).await,
+        ("GET", "/api/me") => get_me(state, req).await,
+        ("GET", "/api/orgs") => list_organizations(state, req).await,
+        ("GET", "/api/domains") => list_domains(state, req).await,
+        ("GET", "/api/namespaces") => list_namespaces(state, req).await,
+        ("GET", "/api/slugs/check") => check_slug(state, req).await,
+        ("GET", "/api/slugs/suggest") => suggest_slugs(state, req).await,
+        ("GET", "/api/groups") => list_groups(state, req).await,
         ("POST", "/api/groups") => create_group(state, req).await,
+        ("GET", "/api/invitations") => list_my_invitations(state, req).await,
         _ => Ok(with_cors(resp(
             404,
             None,
@@ -412,8 +1129,65 @@ async fn route(state: AppState, req: Request) -> Result<Response<Body>, Error> {
     }
 }
 
+/// Attempts at finding a free generated slug before giving up.
+const SLUG_GENERATION_ATTEMPTS: usize = 10;
+
+/// Generate a slug in `style` that is free on `short_domain`. Word slugs can
+/// collide with existing links, so taken ones are retried with a fresh counter
+/// value.
+fn generate_slug(
+    state: &AppState,
+    style: SlugStyle,
+    short_domain: &Option<String>,
+) -> Result<Slug, CoreError> {
+    for _ in 0..SLUG_GENERATION_ATTEMPTS {
+        let id = state.repo.increment_global_counter()?;
+        let slug = match style {
+            SlugStyle::Base62 => state.slugger.next_slug(id),
+            SlugStyle::Words => state.word_slugger.next_slug(id),
+        };
+        let candidate = slug.clone().on_domain(short_domain.clone());
+        if state.repo.get(&candidate)?.is_none() {
+            return Ok(slug);
+        }
+    }
+    Err(CoreError::Repository(
+        "no free slug after repeated attempts".into(),
+    ))
+}
+
+/// Links considered when looking for an existing link to the same target.
+const DEDUPE_CANDIDATES: usize = 100;
+
+/// The oldest link `owner` can reuse instead of creating a new one to `url`
+/// (see [`domain::ShortLink::dedupes`]). Group links are only reused for
+/// members of the group.
+fn find_duplicate_link(
+    state: &AppState,
+    url: &str,
+    owner: &UserEmail,
+    group_id: Option<&str>,
+    short_domain: Option<&str>,
+) -> Result<Option<domain::ShortLink>, CoreError> {
+    if let Some(gid) = group_id {
+        if !state.is_admin(owner.as_str()) && state.repo.effective_role(gid, owner)?.is_none() {
+            return Ok(None);
+        }
+    }
+    let now = state.clock.now();
+    Ok(state
+        .repo
+        .list_by_target(
+            &domain::validate::canonical_url(url),
+            TargetMatch::Exact,
+            DEDUPE_CANDIDATES,
+        )?
+        .into_iter()
+        .find(|link| link.dedupes(owner, group_id, short_domain, now)))
+}
+
 async fn create_link(state: AppState, req: Request) -> Result<Response<Body>, Error> {
-    let verified = match verify_request_user(&req).await {
+    let verified = match verify_request_user(&state, &req).await {
         Ok(v) => v,
         Err(AuthHttp::Unauthorized) => {
             return Ok(with_cors(resp_with_error(
@@ -477,7 +1251,94 @@ async fn create_link(state: AppState, req: Request) -> Result<Response<Body>, Er
     // Prepare created_at
     let created_at = state.clock.now();
 
+    let slug_style = match payload.slug_style.as_deref().map(SlugStyle::parse) {
+        None => SlugStyle::default(),
+        Some(Some(style)) => style,
+        Some(None) => {
+            return Ok(with_cors(resp_with_error(
+                400,
+                "invalid_request",
+                "invalid slug_style, use: base62 or words",
+            )))
+        }
+    };
+
+    // Links on an extra short domain need a registered domain the caller may use
+    let short_domain = match payload.domain.as_deref().filter(|d| !d.trim().is_empty()) {
+        None => None,
+        Some(d) => {
+            let Ok(host) = domain::normalize_host(d) else {
+                return Ok(with_cors(resp_with_error(
+                    400,
+                    "invalid_request",
+                    "invalid domain",
+                )));
+            };
+            let registered = match state.repo.get_domain(&host) {
+                Ok(Some(sd)) => sd,
+                Ok(None) => {
+                    return Ok(with_cors(resp_with_error(
+                        400,
+                        "invalid_request",
+                        "unknown domain",
+                    )))
+                }
+                Err(e) => {
+                    error!(err=?e, "get domain error");
+                    return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+                }
+            };
+            if !state.is_admin(&verified.email) {
+                let group_ids: Vec<String> = match state.repo.get_user_groups(&user_email) {
+                    Ok(groups) => groups.into_iter().map(|(g, _)| g.id).collect(),
+                    Err(e) => {
+                        error!(err=?e, "get user groups error");
+                        return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+                    }
+                };
+                if !registered.may_use(&user_email, &group_ids) {
+                    return Ok(with_cors(resp_with_error(
+                        403,
+                        "forbidden",
+                        "not allowed to create links on this domain",
+                    )));
+                }
+            }
+            Some(host)
+        }
+    };
+
+    // Hand out an existing link to the same target instead of minting a slug
+    if payload.dedupe && payload.alias.is_none() {
+        match find_duplicate_link(
+            &state,
+            &payload.original_url,
+            &user_email,
+            payload.group_id.as_deref(),
+            short_domain.as_deref(),
+        ) {
+            Ok(Some(existing)) => {
+                let host = state.short_host(&req);
+                info!(slug = %existing.slug.as_str(), "create deduplicated");
+                return Ok(with_cors(resp(
+                    200,
+                    None,
+                    Some(
+                        serde_json::to_value(link_to_out(existing, host))
+                            .expect("LinkOut serialization"),
+                    ),
+                )));
+            }
+            Ok(None) => {}
+            Err(e) => {
+                error!(err=?e, "dedupe lookup error");
+                return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+            }
+        }
+    }
+
     // Determine slug
+    let generated_slug = payload.alias.is_none();
     let slug = if let Some(alias) = &payload.alias {
         if !http_common::is_valid_alias(alias) {
             return Ok(with_cors(resp_with_error(
@@ -497,23 +1358,30 @@ async fn create_link(state: AppState, req: Request) -> Result<Response<Body>, Er
             }
         }
     } else {
-        let id = match state.repo.increment_global_counter() {
-            Ok(v) => v,
+        match generate_slug(&state, slug_style, &short_domain) {
+            Ok(s) => s,
             Err(e) => {
-                error!(err=?e, "counter error");
+                error!(err=?e, "slug generation error");
                 return Ok(with_cors(resp_with_error(
                     500,
                     "internal",
-                    "counter failure",
+                    "could not generate a unique slug",
                 )));
             }
-        };
-        state.slugger.next_slug(id)
+        }
     };
 
+    if let Some(resp) = check_namespace(&state, &slug, &user_email) {
+        return Ok(resp);
+    }
+
     // Persist
-    let mut link =
-        domain::ShortLink::new(slug, payload.original_url.clone(), created_at, user_email);
+    let mut link = domain::ShortLink::new(
+        slug.on_domain(short_domain),
+        payload.original_url.clone(),
+        created_at,
+        user_email,
+    );
 
     // Apply optional fields
     link.description = payload.description;
@@ -522,10 +1390,27 @@ async fn create_link(state: AppState, req: Request) -> Result<Response<Body>, Er
         .and_then(|s| http_common::parse_rfc3339(&s).ok());
     link.redirect_delay = payload.redirect_delay;
     link.group_id = payload.group_id;
+    if let Some(v) = payload.visibility {
+        link.visibility = match LinkVisibility::parse(&v) {
+            Some(v) => v,
+            None => {
+                return Ok(with_cors(resp_with_error(
+                    400,
+                    "invalid_request",
+                    "invalid visibility, use: public or workspace",
+                )))
+            }
+        };
+    }
+    if let Some(resp) =
+        enforce_link_settings(&state, &mut link, GroupCheck::Create { generated_slug })
+    {
+        return Ok(resp);
+    }
 
-    match state.repo.put(link.clone()) {
+    match state.links.put(link.clone()) {
         Ok(()) => {
-            let host = get_host(&req);
+            let host = state.short_host(&req);
             info!(slug = %link.slug.as_str(), "create ok");
             Ok(with_cors(resp(
                 201,
@@ -554,7 +1439,7 @@ async fn update_link(
     slug_str: String,
 ) -> Result<Response<Body>, Error> {
     // Auth required
-    let verified = match verify_request_user(&req).await {
+    let verified = match verify_request_user(&state, &req).await {
         Ok(v) => v,
         Err(AuthHttp::Unauthorized) => {
             return Ok(with_cors(resp_with_error(
@@ -572,10 +1457,10 @@ async fn update_link(
         }
     };
 
-    let user_is_admin = is_admin(&verified.email);
+    let user_is_admin = state.is_admin(&verified.email);
 
     // Parse slug
-    let slug = match Slug::new(slug_str.clone()) {
+    let slug = match Slug::from_key(&slug_str) {
         Ok(s) => s,
         Err(_) => {
             return Ok(with_cors(resp_with_error(
@@ -631,19 +1516,26 @@ async fn update_link(
     // - System admins can edit any link
     // - Link creator can edit their own link
     // - Group editors/admins can edit links in their group
+    // - Collaborators with the editor role can edit the shared link
     if !user_is_admin && link.created_by.as_str() != verified.email {
         // Check if link belongs to a group and user has edit access
         let user_email = UserEmail::new(verified.email.clone()).unwrap();
         let can_edit_via_group = if let Some(ref gid) = link.group_id {
-            match state.repo.get_member(gid, &user_email) {
-                Ok(Some(member)) => member.role.can_edit(),
+            match state.repo.effective_role(gid, &user_email) {
+                Ok(Some(role)) => role.can_edit(),
                 _ => false,
             }
         } else {
             false
         };
+        // Or the link was shared with them directly as an editor
+        let can_edit_via_grant = !can_edit_via_group
+            && matches!(
+                state.repo.get_grant(&link.slug, &user_email),
+                Ok(Some(grant)) if grant.role.can_edit()
+            );
 
-        if !can_edit_via_group {
+        if !can_edit_via_group && !can_edit_via_grant {
             warn!(user = %verified.email, link_owner = %link.created_by.as_str(), "unauthorized edit attempt");
             return Ok(with_cors(resp_with_error(
                 403,
@@ -654,6 +1546,8 @@ async fn update_link(
     }
 
     // Apply updates
+    let previous_group = link.group_id.clone();
+    let url_changed = payload.original_url.is_some();
     if let Some(new_url) = payload.original_url {
         if let Err(e) = domain::validate::validate_original_url(&new_url) {
             return Ok(with_cors(resp_with_error(
@@ -695,12 +1589,32 @@ async fn update_link(
     if let Some(gid) = payload.group_id {
         link.group_id = gid;
     }
+    if let Some(v) = payload.visibility {
+        link.visibility = match LinkVisibility::parse(&v) {
+            Some(v) => v,
+            None => {
+                return Ok(with_cors(resp_with_error(
+                    400,
+                    "invalid_request",
+                    "invalid visibility, use: public or workspace",
+                )))
+            }
+        };
+    }
+    let moved_in = link.group_id != previous_group;
+    if url_changed || moved_in {
+        if let Some(resp) =
+            enforce_link_settings(&state, &mut link, GroupCheck::Update { moved_in })
+        {
+            return Ok(resp);
+        }
+    }
     link.updated_at = Some(state.clock.now());
 
     // Persist update
-    match state.repo.update(&link) {
+    match state.links.update(&link) {
         Ok(()) => {
-            let host = get_host(&req);
+            let host = state.short_host(&req);
             info!(slug = %link.slug.as_str(), "update ok");
             Ok(with_cors(resp(
                 200,
@@ -720,8 +1634,224 @@ async fn update_link(
     }
 }
 
-async fn get_me(req: Request) -> Result<Response<Body>, Error> {
-    let verified = match verify_request_user(&req).await {
+/// Which group rules apply to a link being written.
+enum GroupCheck {
+    /// New link: fill in group defaults (and prefix a generated slug), then
+    /// check the rules and the link limit.
+    Create { generated_slug: bool },
+    /// Existing link whose URL or group changed; the limit only applies when it
+    /// is moved into the group.
+    Update { moved_in: bool },
+}
+
+/// Apply the organization's link rules and the settings of the link's group.
+/// Returns the error response to send when the link breaks one of them.
+fn enforce_link_settings(
+    state: &AppState,
+    link: &mut ShortLink,
+    check: GroupCheck,
+) -> Option<Response<Body>> {
+    if let Err(e) = state.org.settings.check_link(link) {
+        return Some(with_cors(resp_with_error(
+            400,
+            "invalid_request",
+            &format!("{}", e),
+        )));
+    }
+    // Links outside a group have no further rules
+    let group_id = link.group_id.clone()?;
+    let group = match state.repo.get_group(&group_id) {
+        Ok(Some(g)) => g,
+        Ok(None) => {
+            return Some(with_cors(resp_with_error(
+                400,
+                "invalid_request",
+                "group not found",
+            )))
+        }
+        Err(e) => {
+            error!(err=?e, "get group error");
+            return Some(with_cors(resp_with_error(500, "internal", "server error")));
+        }
+    };
+    let settings = &group.settings;
+
+    let counts_towards_limit = match check {
+        GroupCheck::Create { generated_slug } => {
+            if generated_slug {
+                link.slug = settings.prefix_slug(link.slug.clone());
+            }
+            settings.apply_defaults(link);
+            true
+        }
+        GroupCheck::Update { moved_in } => moved_in,
+    };
+
+    if let Err(e) = settings.check_link(link) {
+        return Some(with_cors(resp_with_error(
+            400,
+            "invalid_request",
+            &format!("{}", e),
+        )));
+    }
+
+    if let (Some(max), true) = (settings.max_links, counts_towards_limit) {
+        match state.repo.list_by_group(&group_id, max as usize) {
+            Ok(links) if links.len() >= max as usize => {
+                return Some(with_cors(resp_with_error(
+                    409,
+                    "conflict",
+                    &format!("group link limit of {max} reached"),
+                )))
+            }
+            Ok(_) => {}
+            Err(e) => {
+                error!(err=?e, "list by group error");
+                return Some(with_cors(resp_with_error(500, "internal", "server error")));
+            }
+        }
+    }
+    None
+}
+
+/// Apply the `sort`, `order`, `status`, `created_from`/`_to` (likewise
+/// `updated_`, `expires_`) and `host` parameters of a link listing to
+/// `options`, or return the message of the 400 to send.
+fn apply_list_filters(
+    query: Option<&str>,
+    options: &mut domain::ListOptions,
+) -> Result<(), String> {
+    let param = |name: &str| http_common::parse_query_param(query, name);
+    if let Some(sort) = param("sort") {
+        options.sort = domain::LinkSort::parse(&sort)
+            .ok_or("invalid sort, use: created_at, updated_at, click_count or expires_at")?;
+    }
+    if let Some(order) = param("order") {
+        options.direction =
+            domain::SortDirection::parse(&order).ok_or("invalid order, use: asc or desc")?;
+    }
+    if let Some(status) = param("status") {
+        options.status = Some(
+            domain::LinkStatus::parse(&status)
+                .ok_or("invalid status, use: active, inactive, expired, scheduled or deleted")?,
+        );
+    }
+    let time = |name: &str| {
+        param(name)
+            .map(|s| {
+                http_common::parse_rfc3339(&s)
+                    .map_err(|_| format!("invalid {name}, use an RFC 3339 time"))
+            })
+            .transpose()
+    };
+    options.created = domain::TimeRange {
+        from: time("created_from")?,
+        to: time("created_to")?,
+    };
+    options.updated = domain::TimeRange {
+        from: time("updated_from")?,
+        to: time("updated_to")?,
+    };
+    options.expires = domain::TimeRange {
+        from: time("expires_from")?,
+        to: time("expires_to")?,
+    };
+    if let Some(host) = param("host") {
+        options.target_host =
+            Some(domain::normalize_host(&host).map_err(|_| "invalid host".to_string())?);
+    }
+    Ok(())
+}
+
+/// `GET /api/links?scope=accessible`: the union of the caller's own links, links in
+/// their groups and links shared with them, paginated as a single list.
+fn list_accessible_links(
+    state: &AppState,
+    req: &Request,
+    user_email: UserEmail,
+    user_is_admin: bool,
+    mut options: domain::ListOptions,
+) -> Result<Response<Body>, Error> {
+    let groups = match state.repo.get_user_groups(&user_email) {
+        Ok(g) => g,
+        Err(e) => {
+            error!(err=?e, "get user groups error");
+            return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+        }
+    };
+    let grants = match state.repo.list_grants_for_user(&user_email) {
+        Ok(g) => g,
+        Err(e) => {
+            error!(err=?e, "list grants error");
+            return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+        }
+    };
+
+    let group_roles: std::collections::HashMap<String, GroupRole> =
+        groups.into_iter().map(|(g, role)| (g.id, role)).collect();
+    let grant_roles: std::collections::HashMap<Slug, GroupRole> =
+        grants.into_iter().map(|g| (g.slug, g.role)).collect();
+
+    options.accessible_to = Some(domain::AccessScope {
+        user: user_email.clone(),
+        group_ids: group_roles.keys().cloned().collect(),
+        shared_slugs: grant_roles.keys().cloned().collect(),
+    });
+
+    match state.repo.list_paginated(&options) {
+        Ok(result) => {
+            let host = state.short_host(req);
+            let links: Vec<LinkOut> = result
+                .items
+                .into_iter()
+                .map(|l| {
+                    // Effective role: owner beats everything, else the strongest of
+                    // the group role and the per-link grant
+                    let role = if l.created_by.as_str() == user_email.as_str() {
+                        "owner"
+                    } else {
+                        let via_group = l.group_id.as_ref().and_then(|g| group_roles.get(g));
+                        let via_grant = grant_roles.get(&l.slug);
+                        via_group
+                            .max(via_grant)
+                            .map(|r| r.as_str())
+                            .unwrap_or("viewer")
+                    };
+                    let mut out = link_to_out(l, host);
+                    out.role = Some(role);
+                    out
+                })
+                .collect();
+            let out = ListOut {
+                links,
+                total: result.total,
+                has_more: result.has_more,
+                next_cursor: result.next_cursor,
+                user: Some(UserInfo {
+                    email: user_email.as_str().to_string(),
+                    is_admin: user_is_admin,
+                }),
+            };
+            Ok(with_cors(resp(
+                200,
+                None,
+                Some(serde_json::to_value(out).expect("ListOut serialization")),
+            )))
+        }
+        Err(CoreError::InvalidCursor) => Ok(with_cors(resp_with_error(
+            400,
+            "invalid_request",
+            "invalid cursor",
+        ))),
+        Err(e) => {
+            error!(err=?e, "list error");
+            Ok(with_cors(resp_with_error(500, "internal", "server error")))
+        }
+    }
+}
+
+async fn get_me(state: AppState, req: Request) -> Result<Response<Body>, Error> {
+    let verified = match verify_request_user(&state, &req).await {
         Ok(v) => v,
         Err(AuthHttp::Unauthorized) => {
             return Ok(with_cors(resp_with_error(
@@ -739,20 +1869,24 @@ async fn get_me(req: Request) -> Result<Response<Body>, Error> {
         }
     };
 
-    let user_info = UserInfo {
-        email: verified.email.clone(),
-        is_admin: is_admin(&verified.email),
+    let out = MeOut {
+        user: UserInfo {
+            email: verified.email.clone(),
+            is_admin: state.is_admin(&verified.email),
+        },
+        tenant: state.org.id.as_str().to_string(),
+        organization: state.org.name.clone(),
     };
     Ok(with_cors(resp(
         200,
         None,
-        Some(serde_json::to_value(user_info).expect("UserInfo serialization")),
+        Some(serde_json::to_value(out).expect("MeOut serialization")),
     )))
 }
 
 async fn list_links(state: AppState, req: Request) -> Result<Response<Body>, Error> {
     // Auth required
-    let verified = match verify_request_user(&req).await {
+    let verified = match verify_request_user(&state, &req).await {
         Ok(v) => v,
         Err(AuthHttp::Unauthorized) => {
             return Ok(with_cors(resp_with_error(
@@ -772,20 +1906,44 @@ async fn list_links(state: AppState, req: Request) -> Result<Response<Body>, Err
 
     let user_email_str = verified.email.clone();
     let user_email = UserEmail::new(user_email_str.clone()).unwrap();
-    let user_is_admin = is_admin(&user_email_str);
+    let user_is_admin = state.is_admin(&user_email_str);
 
     // Parse query parameters
     let query = req.uri().query();
     let limit = http_common::parse_limit_query(query).unwrap_or(50);
-    let offset: usize = http_common::parse_query_param(query, "offset")
-        .and_then(|s| s.parse().ok())
-        .unwrap_or(0);
+    let cursor = http_common::parse_query_param(query, "cursor");
     let search = http_common::parse_query_param(query, "search");
     let group_id = http_common::parse_query_param(query, "group_id");
     let include_deleted = http_common::parse_query_param(query, "include_deleted")
         .map(|s| s == "true" || s == "1")
         .unwrap_or(false);
     let created_by_filter = http_common::parse_query_param(query, "created_by");
+    let accessible =
+        http_common::parse_query_param(query, "scope").as_deref() == Some("accessible");
+    let mut filters = domain::ListOptions {
+        now: Some(state.clock.now()),
+        ..Default::default()
+    };
+    if let Err(msg) = apply_list_filters(query, &mut filters) {
+        return Ok(with_cors(resp_with_error(400, "invalid_request", &msg)));
+    }
+
+    if accessible {
+        return list_accessible_links(
+            &state,
+            &req,
+            user_email,
+            user_is_admin,
+            domain::ListOptions {
+                limit,
+                search,
+                group_id,
+                include_deleted,
+                cursor,
+                ..filters
+            },
+        );
+    }
 
     // Determine effective filter:
     // - Admins can see all links or filter by any creator
@@ -795,7 +1953,7 @@ async fn list_links(state: AppState, req: Request) -> Result<Response<Body>, Err
         created_by_filter.and_then(|e| UserEmail::new(e).ok())
     } else if let Some(ref gid) = group_id {
         // Check if user is a member of this group
-        match state.repo.get_member(gid, &user_email) {
+        match state.repo.effective_role(gid, &user_email) {
             Ok(Some(_)) => {
                 // User is a member - show all links in the group (no created_by filter)
                 None
@@ -820,16 +1978,17 @@ async fn list_links(state: AppState, req: Request) -> Result<Response<Body>, Err
 
     let options = domain::ListOptions {
         limit,
-        offset,
         search,
         created_by,
         group_id,
         include_deleted,
+        cursor,
+        ..filters
     };
 
     match state.repo.list_paginated(&options) {
         Ok(result) => {
-            let host = get_host(&req);
+            let host = state.short_host(&req);
             let links: Vec<LinkOut> = result
                 .items
                 .into_iter()
@@ -843,6 +2002,7 @@ async fn list_links(state: AppState, req: Request) -> Result<Response<Body>, Err
                 links,
                 total: result.total,
                 has_more: result.has_more,
+                next_cursor: result.next_cursor,
                 user: Some(user),
             };
             Ok(with_cors(resp(
@@ -851,6 +2011,11 @@ async fn list_links(state: AppState, req: Request) -> Result<Response<Body>, Err
                 Some(serde_json::to_value(out).expect("ListOut serialization")),
             )))
         }
+        Err(CoreError::InvalidCursor) => Ok(with_cors(resp_with_error(
+            400,
+            "invalid_request",
+            "invalid cursor",
+        ))),
         Err(e) => {
             error!(err=?e, "list error");
             Ok(with_cors(resp_with_error(500, "internal", "server error")))
@@ -864,7 +2029,7 @@ async fn delete_link(
     slug_str: String,
 ) -> Result<Response<Body>, Error> {
     // Auth required
-    let verified = match verify_request_user(&req).await {
+    let verified = match verify_request_user(&state, &req).await {
         Ok(v) => v,
         Err(AuthHttp::Unauthorized) => {
             return Ok(with_cors(resp_with_error(
@@ -882,10 +2047,10 @@ async fn delete_link(
         }
     };
 
-    let user_is_admin = is_admin(&verified.email);
+    let user_is_admin = state.is_admin(&verified.email);
 
     // Parse slug
-    let slug = match Slug::new(slug_str.clone()) {
+    let slug = match Slug::from_key(&slug_str) {
         Ok(s) => s,
         Err(_) => {
             return Ok(with_cors(resp_with_error(
@@ -916,19 +2081,26 @@ async fn delete_link(
     // - System admins can delete any link
     // - Link creator can delete their own link
     // - Group editors/admins can delete links in their group
+    // - Collaborators with the editor role can delete the shared link
     if !user_is_admin && link.created_by.as_str() != verified.email {
         // Check if link belongs to a group and user has edit access
         let user_email = UserEmail::new(verified.email.clone()).unwrap();
         let can_delete_via_group = if let Some(ref gid) = link.group_id {
-            match state.repo.get_member(gid, &user_email) {
-                Ok(Some(member)) => member.role.can_edit(),
+            match state.repo.effective_role(gid, &user_email) {
+                Ok(Some(role)) => role.can_edit(),
                 _ => false,
             }
         } else {
             false
         };
+        // Or the link was shared with them directly as an editor
+        let can_delete_via_grant = !can_delete_via_group
+            && matches!(
+                state.repo.get_grant(&link.slug, &user_email),
+                Ok(Some(grant)) if grant.role.can_edit()
+            );
 
-        if !can_delete_via_group {
+        if !can_delete_via_group && !can_delete_via_grant {
             warn!(user = %verified.email, link_owner = %link.created_by.as_str(), "unauthorized delete attempt");
             return Ok(with_cors(resp_with_error(
                 403,
@@ -940,7 +2112,7 @@ async fn delete_link(
 
     // Soft delete
     let deleted_at = state.clock.now();
-    match state.repo.delete(&slug, deleted_at) {
+    match state.links.delete(&link.slug, deleted_at) {
         Ok(()) => {
             info!(slug = %slug_str, "delete ok");
             Ok(with_cors(resp(204, None, None)))
@@ -959,7 +2131,7 @@ async fn delete_link(
 
 async fn bulk_delete_links(state: AppState, req: Request) -> Result<Response<Body>, Error> {
     // Auth required
-    let verified = match verify_request_user(&req).await {
+    let verified = match verify_request_user(&state, &req).await {
         Ok(v) => v,
         Err(AuthHttp::Unauthorized) => {
             return Ok(with_cors(resp_with_error(
@@ -978,7 +2150,7 @@ async fn bulk_delete_links(state: AppState, req: Request) -> Result<Response<Bod
     };
 
     // Only admins can bulk delete
-    if !is_admin(&verified.email) {
+    if !state.is_admin(&verified.email) {
         return Ok(with_cors(resp_with_error(
             403,
             "forbidden",
@@ -1015,7 +2187,7 @@ async fn bulk_delete_links(state: AppState, req: Request) -> Result<Response<Bod
     let slugs: Vec<Slug> = payload
         .slugs
         .iter()
-        .filter_map(|s| Slug::new(s.clone()).ok())
+        .filter_map(|s| Slug::from_key(s).ok())
         .collect();
 
     if slugs.is_empty() {
@@ -1027,7 +2199,7 @@ async fn bulk_delete_links(state: AppState, req: Request) -> Result<Response<Bod
     }
 
     let deleted_at = state.clock.now();
-    match state.repo.bulk_delete(&slugs, deleted_at) {
+    match state.links.bulk_delete(&slugs, deleted_at) {
         Ok(affected) => {
             info!(count = affected, "bulk delete ok");
             Ok(with_cors(resp(
@@ -1057,7 +2229,7 @@ async fn bulk_update_active_impl(
     is_active: bool,
 ) -> Result<Response<Body>, Error> {
     // Auth required
-    let verified = match verify_request_user(&req).await {
+    let verified = match verify_request_user(&state, &req).await {
         Ok(v) => v,
         Err(AuthHttp::Unauthorized) => {
             return Ok(with_cors(resp_with_error(
@@ -1076,7 +2248,7 @@ async fn bulk_update_active_impl(
     };
 
     // Only admins can bulk update
-    if !is_admin(&verified.email) {
+    if !state.is_admin(&verified.email) {
         return Ok(with_cors(resp_with_error(
             403,
             "forbidden",
@@ -1113,7 +2285,7 @@ async fn bulk_update_active_impl(
     let slugs: Vec<Slug> = payload
         .slugs
         .iter()
-        .filter_map(|s| Slug::new(s.clone()).ok())
+        .filter_map(|s| Slug::from_key(s).ok())
         .collect();
 
     if slugs.is_empty() {
@@ -1125,7 +2297,10 @@ async fn bulk_update_active_impl(
     }
 
     let updated_at = state.clock.now();
-    match state.repo.bulk_update_active(&slugs, is_active, updated_at) {
+    match state
+        .links
+        .bulk_update_active(&slugs, is_active, updated_at)
+    {
         Ok(affected) => {
             info!(
                 count = affected,
@@ -1150,7 +2325,7 @@ async fn bulk_update_active_impl(
 // -------------------------
 
 async fn list_groups(state: AppState, req: Request) -> Result<Response<Body>, Error> {
-    let verified = match verify_request_user(&req).await {
+    let verified = match verify_request_user(&state, &req).await {
         Ok(v) => v,
         Err(AuthHttp::Unauthorized) => {
             return Ok(with_cors(resp_with_error(
@@ -1181,9 +2356,12 @@ async fn list_groups(state: AppState, req: Request) -> Result<Response<Body>, Er
 
     match state.repo.get_user_groups(&user_email) {
         Ok(groups_with_roles) => {
-            let groups: Vec<GroupOut> = groups_with_roles
-                .iter()
-                .map(|(g, r)| group_to_out(g, Some(*r)))
+            let groups: Vec<GroupOut> = hierarchy::tree_order(groups_with_roles)
+                .into_iter()
+                .map(|(g, r, depth)| GroupOut {
+                    depth: Some(depth),
+                    ..group_to_out(&g, Some(r))
+                })
                 .collect();
             let out = GroupListOut { groups };
             Ok(with_cors(resp(
@@ -1200,7 +2378,7 @@ async fn list_groups(state: AppState, req: Request) -> Result<Response<Body>, Er
 }
 
 async fn create_group(state: AppState, req: Request) -> Result<Response<Body>, Error> {
-    let verified = match verify_request_user(&req).await {
+    let verified = match verify_request_user(&state, &req).await {
         Ok(v) => v,
         Err(AuthHttp::Unauthorized) => {
             return Ok(with_cors(resp_with_error(
@@ -1265,12 +2443,21 @@ async fn create_group(state: AppState, req: Request) -> Result<Response<Body>, E
     let group_id = format!("grp_{}", http_common::generate_id());
     let now = state.clock.now();
 
+    let parent_id = payload.parent_id.filter(|p| !p.is_empty());
+    if let Some(ref parent) = parent_id {
+        if let Some(resp) = check_group_parent(&state, &user_email, &group_id, parent) {
+            return Ok(resp);
+        }
+    }
+
     let group = LinkGroup {
         id: group_id.clone(),
         name: payload.name,
         description: payload.description,
         created_at: now,
         created_by: user_email.clone(),
+        settings: GroupSettings::default(),
+        parent_id,
     };
 
     // Create the group
@@ -1307,7 +2494,7 @@ async fn get_group(
     req: Request,
     group_id: String,
 ) -> Result<Response<Body>, Error> {
-    let verified = match verify_request_user(&req).await {
+    let verified = match verify_request_user(&state, &req).await {
         Ok(v) => v,
         Err(AuthHttp::Unauthorized) => {
             return Ok(with_cors(resp_with_error(
@@ -1353,12 +2540,12 @@ async fn get_group(
     };
 
     // Check membership (unless admin)
-    let is_system_admin = is_admin(&verified.email);
+    let is_system_admin = state.is_admin(&verified.email);
     let member_role = if is_system_admin {
         Some(GroupRole::Admin)
     } else {
-        match state.repo.get_member(&group_id, &user_email) {
-            Ok(Some(m)) => Some(m.role),
+        match state.repo.effective_role(&group_id, &user_email) {
+            Ok(Some(role)) => Some(role),
             Ok(None) => {
                 return Ok(with_cors(resp_with_error(
                     403,
@@ -1385,7 +2572,7 @@ async fn update_group(
     req: Request,
     group_id: String,
 ) -> Result<Response<Body>, Error> {
-    let verified = match verify_request_user(&req).await {
+    let verified = match verify_request_user(&state, &req).await {
         Ok(v) => v,
         Err(AuthHttp::Unauthorized) => {
             return Ok(with_cors(resp_with_error(
@@ -1431,10 +2618,10 @@ async fn update_group(
     };
 
     // Check if user has admin access
-    let is_system_admin = is_admin(&verified.email);
+    let is_system_admin = state.is_admin(&verified.email);
     if !is_system_admin {
-        match state.repo.get_member(&group_id, &user_email) {
-            Ok(Some(m)) if m.role.can_manage() => {}
+        match state.repo.effective_role(&group_id, &user_email) {
+            Ok(Some(role)) if role.can_manage() => {}
             Ok(_) => {
                 return Ok(with_cors(resp_with_error(
                     403,
@@ -1488,6 +2675,28 @@ async fn update_group(
     if let Some(desc) = payload.description {
         group.description = desc;
     }
+    if let Some(settings) = payload.settings {
+        let settings = GroupSettings::from(settings);
+        if let Err(e) = settings.validate() {
+            return Ok(with_cors(resp_with_error(
+                400,
+                "invalid_request",
+                &format!("{}", e),
+            )));
+        }
+        group.settings = settings;
+    }
+    if let Some(parent) = payload.parent_id {
+        let parent = (!parent.is_empty()).then_some(parent);
+        if parent != group.parent_id {
+            if let Some(ref p) = parent {
+                if let Some(resp) = check_group_parent(&state, &user_email, &group_id, p) {
+                    return Ok(resp);
+                }
+            }
+            group.parent_id = parent;
+        }
+    }
 
     match state.repo.update_group(&group) {
         Ok(()) => {
@@ -1510,12 +2719,54 @@ async fn update_group(
     }
 }
 
+/// Check that the caller may nest `group_id` below `parent_id`: they need the
+/// admin role on the parent and the tree must stay acyclic and shallow enough.
+/// Returns the error response to send otherwise.
+fn check_group_parent(
+    state: &AppState,
+    user_email: &UserEmail,
+    group_id: &str,
+    parent_id: &str,
+) -> Option<Response<Body>> {
+    if !state.is_admin(user_email.as_str()) {
+        match state.repo.effective_role(parent_id, user_email) {
+            Ok(Some(role)) if role.can_manage() => {}
+            Ok(_) => {
+                return Some(with_cors(resp_with_error(
+                    403,
+                    "forbidden",
+                    "admin role required in the parent group",
+                )))
+            }
+            Err(e) => {
+                error!(err=?e, "get member error");
+                return Some(with_cors(resp_with_error(500, "internal", "server error")));
+            }
+        }
+    }
+    match hierarchy::check_parent(&state.repo, group_id, parent_id) {
+        Ok(()) => None,
+        Err(CoreError::NotFound) => Some(with_cors(resp_with_error(
+            404,
+            "not_found",
+            "parent group not found",
+        ))),
+        Err(CoreError::Conflict(msg)) => {
+            Some(with_cors(resp_with_error(400, "invalid_request", &msg)))
+        }
+        Err(e) => {
+            error!(err=?e, "check group parent error");
+            Some(with_cors(resp_with_error(500, "internal", "server error")))
+        }
+    }
+}
+
 async fn delete_group(
     state: AppState,
     req: Request,
     group_id: String,
 ) -> Result<Response<Body>, Error> {
-    let verified = match verify_request_user(&req).await {
+    let verified = match verify_request_user(&state, &req).await {
         Ok(v) => v,
         Err(AuthHttp::Unauthorized) => {
             return Ok(with_cors(resp_with_error(
@@ -1551,10 +2802,10 @@ async fn delete_group(
     }
 
     // Check if user has admin access
-    let is_system_admin = is_admin(&verified.email);
+    let is_system_admin = state.is_admin(&verified.email);
     if !is_system_admin {
-        match state.repo.get_member(&group_id, &user_email) {
-            Ok(Some(m)) if m.role.can_manage() => {}
+        match state.repo.effective_role(&group_id, &user_email) {
+            Ok(Some(role)) if role.can_manage() => {}
             Ok(_) => {
                 return Ok(with_cors(resp_with_error(
                     403,
@@ -1569,16 +2820,82 @@ async fn delete_group(
         }
     }
 
-    match state.repo.delete_group(&group_id) {
-        Ok(()) => {
-            info!(group_id = %group_id, "group deleted");
-            Ok(with_cors(resp(204, None, None)))
-        }
+    // The caller must say what happens to the group's links
+    let query = req.uri().query();
+    let target_group = http_common::parse_query_param(query, "target_group_id");
+    let disposition = match http_common::parse_query_param(query, "links")
+        .and_then(|l| GroupLinkDisposition::parse(&l, target_group.as_deref()))
+    {
+        Some(d) => d,
+        None => {
+            return Ok(with_cors(resp_with_error(
+                400,
+                "invalid_request",
+                "links must be one of: reassign (with target_group_id), detach, soft_delete",
+            )))
+        }
+    };
+
+    // Moving links into another group requires edit access there
+    if let GroupLinkDisposition::Reassign(target) = &disposition {
+        if !is_system_admin {
+            match state.repo.effective_role(target, &user_email) {
+                Ok(Some(role)) if role.can_edit() => {}
+                Ok(_) => {
+                    return Ok(with_cors(resp_with_error(
+                        403,
+                        "forbidden",
+                        "editor role required in the target group",
+                    )))
+                }
+                Err(e) => {
+                    error!(err=?e, "get member error");
+                    return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+                }
+            }
+        }
+    }
+
+    // Every disposition changes the group's links, so their cached redirects go
+    let group_links = match state.repo.list_by_group(&group_id, usize::MAX) {
+        Ok(links) => links,
+        Err(e) => {
+            error!(err=?e, "list group links error");
+            return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+        }
+    };
+    match state
+        .repo
+        .delete_group(&group_id, &disposition, state.clock.now())
+    {
+        Ok(affected) => {
+            info!(group_id = %group_id, links = ?disposition, affected, "group deleted");
+            let slugs: Vec<Slug> = group_links.into_iter().map(|l| l.slug).collect();
+            if let Err(e) = state.links.forget(&slugs) {
+                warn!(err=?e, group_id = %group_id, "redirect cache invalidation error");
+            }
+            // The group's namespaces open up again
+            let released = state.repo.list_namespaces().and_then(|namespaces| {
+                namespaces
+                    .iter()
+                    .filter(|ns| ns.group_id == group_id)
+                    .try_for_each(|ns| state.repo.delete_namespace(&ns.path))
+            });
+            if let Err(e) = released {
+                warn!(err=?e, group_id = %group_id, "release namespaces error");
+            }
+            Ok(with_cors(resp(
+                200,
+                None,
+                Some(serde_json::to_value(BulkResultOut { affected }).expect("serialize")),
+            )))
+        }
         Err(CoreError::NotFound) => Ok(with_cors(resp_with_error(
             404,
             "not_found",
             "group not found",
         ))),
+        Err(CoreError::Conflict(msg)) => Ok(with_cors(resp_with_error(409, "conflict", &msg))),
         Err(e) => {
             error!(err=?e, "delete group error");
             Ok(with_cors(resp_with_error(500, "internal", "server error")))
@@ -1586,12 +2903,127 @@ async fn delete_group(
     }
 }
 
+async fn transfer_group(
+    state: AppState,
+    req: Request,
+    group_id: String,
+) -> Result<Response<Body>, Error> {
+    let verified = match verify_request_user(&state, &req).await {
+        Ok(v) => v,
+        Err(AuthHttp::Unauthorized) => {
+            return Ok(with_cors(resp_with_error(
+                401,
+                "unauthorized",
+                "missing or invalid token",
+            )))
+        }
+        Err(AuthHttp::Forbidden) => {
+            return Ok(with_cors(resp_with_error(
+                403,
+                "forbidden",
+                "domain not allowed",
+            )))
+        }
+    };
+
+    let group = match state.repo.get_group(&group_id) {
+        Ok(Some(g)) => g,
+        Ok(None) => {
+            return Ok(with_cors(resp_with_error(
+                404,
+                "not_found",
+                "group not found",
+            )))
+        }
+        Err(e) => {
+            error!(err=?e, "get group error");
+            return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+        }
+    };
+
+    // Only the current owner (or a system admin) can give the group away
+    if !state.is_admin(&verified.email)
+        && !group
+            .created_by
+            .as_str()
+            .eq_ignore_ascii_case(&verified.email)
+    {
+        return Ok(with_cors(resp_with_error(
+            403,
+            "forbidden",
+            "only the group owner can transfer ownership",
+        )));
+    }
+
+    let body_str = match req.body() {
+        Body::Empty => {
+            return Ok(with_cors(resp_with_error(
+                400,
+                "invalid_request",
+                "missing body",
+            )))
+        }
+        Body::Text(s) => s.clone(),
+        Body::Binary(b) => String::from_utf8(b.clone()).unwrap_or_default(),
+        _ => String::new(),
+    };
+
+    let payload: TransferGroupReq = match serde_json::from_str(&body_str) {
+        Ok(p) => p,
+        Err(_) => {
+            return Ok(with_cors(resp_with_error(
+                400,
+                "invalid_request",
+                "bad json",
+            )))
+        }
+    };
+
+    let new_owner = match UserEmail::new(payload.email.trim().to_lowercase()) {
+        Ok(e) => e,
+        Err(_) => {
+            return Ok(with_cors(resp_with_error(
+                400,
+                "invalid_request",
+                "invalid email",
+            )))
+        }
+    };
+
+    match state.repo.transfer_ownership(&group_id, &new_owner) {
+        Ok(()) => {
+            info!(group_id = %group_id, new_owner = %new_owner.as_str(), "group ownership transferred");
+            let updated = LinkGroup {
+                created_by: new_owner,
+                ..group
+            };
+            Ok(with_cors(resp(
+                200,
+                None,
+                Some(serde_json::to_value(group_to_out(&updated, None)).expect("serialize")),
+            )))
+        }
+        Err(CoreError::NotFound) => Ok(with_cors(resp_with_error(
+            404,
+            "not_found",
+            "group not found",
+        ))),
+        Err(CoreError::Conflict(msg)) => {
+            Ok(with_cors(resp_with_error(400, "invalid_request", &msg)))
+        }
+        Err(e) => {
+            error!(err=?e, "transfer ownership error");
+            Ok(with_cors(resp_with_error(500, "internal", "server error")))
+        }
+    }
+}
+
 async fn list_group_members(
     state: AppState,
     req: Request,
     group_id: String,
 ) -> Result<Response<Body>, Error> {
-    let verified = match verify_request_user(&req).await {
+    let verified = match verify_request_user(&state, &req).await {
         Ok(v) => v,
         Err(AuthHttp::Unauthorized) => {
             return Ok(with_cors(resp_with_error(
@@ -1621,9 +3053,9 @@ async fn list_group_members(
     };
 
     // Check membership (unless system admin)
-    let is_system_admin = is_admin(&verified.email);
+    let is_system_admin = state.is_admin(&verified.email);
     if !is_system_admin {
-        match state.repo.get_member(&group_id, &user_email) {
+        match state.repo.effective_role(&group_id, &user_email) {
             Ok(Some(_)) => {}
             Ok(None) => {
                 return Ok(with_cors(resp_with_error(
@@ -1662,7 +3094,7 @@ async fn add_group_member(
     req: Request,
     group_id: String,
 ) -> Result<Response<Body>, Error> {
-    let verified = match verify_request_user(&req).await {
+    let verified = match verify_request_user(&state, &req).await {
         Ok(v) => v,
         Err(AuthHttp::Unauthorized) => {
             return Ok(with_cors(resp_with_error(
@@ -1692,15 +3124,15 @@ async fn add_group_member(
     };
 
     // Check if user has admin access to the group
-    let is_system_admin = is_admin(&verified.email);
+    let is_system_admin = state.is_admin(&verified.email);
     if !is_system_admin {
-        match state.repo.get_member(&group_id, &user_email) {
-            Ok(Some(m)) if m.role.can_manage() => {}
+        match state.repo.effective_role(&group_id, &user_email) {
+            Ok(Some(role)) if role.can_manage() => {}
             Ok(_) => {
                 return Ok(with_cors(resp_with_error(
                     403,
                     "forbidden",
-                    "admin role required to add members",
+                    "admin role required to invite members",
                 )))
             }
             Err(e) => {
@@ -1735,7 +3167,7 @@ async fn add_group_member(
         }
     };
 
-    let new_member_email = match UserEmail::new(payload.email.clone()) {
+    let new_member_email = match UserEmail::new(payload.email.trim().to_lowercase()) {
         Ok(e) => e,
         Err(_) => {
             return Ok(with_cors(resp_with_error(
@@ -1757,25 +3189,78 @@ async fn add_group_member(
         }
     };
 
-    let member = GroupMember {
+    let days = payload.expires_in_days.unwrap_or(DEFAULT_INVITATION_DAYS);
+    if days == 0 || days > MAX_INVITATION_DAYS {
+        return Ok(with_cors(resp_with_error(
+            400,
+            "invalid_request",
+            "expires_in_days must be between 1 and 30",
+        )));
+    }
+
+    let group = match state.repo.get_group(&group_id) {
+        Ok(Some(g)) => g,
+        Ok(None) => {
+            return Ok(with_cors(resp_with_error(
+                404,
+                "not_found",
+                "group not found",
+            )))
+        }
+        Err(e) => {
+            error!(err=?e, "get group error");
+            return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+        }
+    };
+
+    match state.repo.get_member(&group_id, &new_member_email) {
+        Ok(None) => {}
+        Ok(Some(_)) => {
+            return Ok(with_cors(resp_with_error(
+                409,
+                "conflict",
+                "user is already a member",
+            )))
+        }
+        Err(e) => {
+            error!(err=?e, "get member error");
+            return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+        }
+    }
+
+    let now = state.clock.now();
+    let invitation = GroupInvitation {
+        id: format!("inv_{}", http_common::generate_id()),
         group_id: group_id.clone(),
-        user_email: new_member_email,
+        email: new_member_email,
         role,
-        added_at: state.clock.now(),
-        added_by: user_email,
+        invited_by: user_email,
+        created_at: now,
+        expires_at: now + std::time::Duration::from_secs(u64::from(days) * 24 * 60 * 60),
+        status: InvitationStatus::Pending,
+        responded_at: None,
     };
 
-    match state.repo.add_member(member.clone()) {
+    match state.repo.create_invitation(invitation.clone()) {
         Ok(()) => {
-            info!(group_id = %group_id, member = %payload.email, "member added");
+            info!(group_id = %group_id, invitee = %invitation.email.as_str(), "invitation created");
+            record_invitation_audit(
+                &state,
+                &invitation.invited_by,
+                AuditAction::Invite,
+                &invitation,
+            );
             Ok(with_cors(resp(
-                201,
+                202,
                 None,
-                Some(serde_json::to_value(member_to_out(&member)).expect("serialize")),
+                Some(
+                    serde_json::to_value(invitation_to_out(&invitation, Some(group.name)))
+                        .expect("serialize"),
+                ),
             )))
         }
         Err(e) => {
-            error!(err=?e, "add member error");
+            error!(err=?e, "create invitation error");
             Ok(with_cors(resp_with_error(500, "internal", "server error")))
         }
     }
@@ -1787,7 +3272,7 @@ async fn remove_group_member(
     group_id: String,
     member_email_str: String,
 ) -> Result<Response<Body>, Error> {
-    let verified = match verify_request_user(&req).await {
+    let verified = match verify_request_user(&state, &req).await {
         Ok(v) => v,
         Err(AuthHttp::Unauthorized) => {
             return Ok(with_cors(resp_with_error(
@@ -1831,10 +3316,10 @@ async fn remove_group_member(
     };
 
     // Check if user has admin access to the group
-    let is_system_admin = is_admin(&verified.email);
+    let is_system_admin = state.is_admin(&verified.email);
     if !is_system_admin {
-        match state.repo.get_member(&group_id, &user_email) {
-            Ok(Some(m)) if m.role.can_manage() => {}
+        match state.repo.effective_role(&group_id, &user_email) {
+            Ok(Some(role)) if role.can_manage() => {}
             Ok(_) => {
                 return Ok(with_cors(resp_with_error(
                     403,
@@ -1854,6 +3339,7 @@ async fn remove_group_member(
             info!(group_id = %group_id, member = %member_email_decoded, "member removed");
             Ok(with_cors(resp(204, None, None)))
         }
+        Err(CoreError::Conflict(msg)) => Ok(with_cors(resp_with_error(409, "conflict", &msg))),
         Err(e) => {
             error!(err=?e, "remove member error");
             Ok(with_cors(resp_with_error(500, "internal", "server error")))
@@ -1861,36 +3347,2133 @@ async fn remove_group_member(
     }
 }
 
-enum AuthHttp {
-    Unauthorized,
-    Forbidden,
+// -------------------------
+// Group Invitations
+// -------------------------
+
+/// Record an invitation lifecycle event. Audit failures are logged but never
+/// fail the request that triggered them.
+fn record_invitation_audit(
+    state: &AppState,
+    actor: &UserEmail,
+    action: AuditAction,
+    invitation: &GroupInvitation,
+) {
+    let changes = serde_json::json!({
+        "group_id": invitation.group_id,
+        "email": invitation.email.as_str(),
+        "role": invitation.role.as_str(),
+    });
+    let entry = AuditEntry {
+        id: http_common::generate_id(),
+        timestamp: state.clock.now(),
+        actor_email: actor.clone(),
+        action,
+        target_type: "group_invitation".into(),
+        target_id: invitation.id.clone(),
+        changes: Some(changes.to_string()),
+    };
+    if let Err(e) = state.repo.log(entry) {
+        warn!(err=?e, invitation = %invitation.id, "audit log error");
+    }
 }
 
-async fn verify_request_user(req: &Request) -> Result<VerifiedUser, AuthHttp> {
-    // Find Authorization header
-    let auth = req
-        .headers()
-        .get("authorization")
-        .and_then(|v| v.to_str().ok())
-        .ok_or(AuthHttp::Unauthorized)?;
-    let token = auth.strip_prefix("Bearer ").ok_or(AuthHttp::Unauthorized)?;
-    let aud = std::env::var("GOOGLE_OAUTH_CLIENT_ID").map_err(|_| AuthHttp::Unauthorized)?;
-    let allowed = std::env::var("ALLOWED_DOMAIN").map_err(|_| AuthHttp::Unauthorized)?;
-    match google_auth::verify_async(token, &aud, &allowed).await {
-        Ok(u) => Ok(u),
-        Err(GAuthError::DomainNotAllowed) => {
-            warn!("auth failed: domain not allowed");
-            Err(AuthHttp::Forbidden)
+async fn list_group_invitations(
+    state: AppState,
+    req: Request,
+    group_id: String,
+) -> Result<Response<Body>, Error> {
+    let verified = match verify_request_user(&state, &req).await {
+        Ok(v) => v,
+        Err(AuthHttp::Unauthorized) => {
+            return Ok(with_cors(resp_with_error(
+                401,
+                "unauthorized",
+                "missing or invalid token",
+            )))
+        }
+        Err(AuthHttp::Forbidden) => {
+            return Ok(with_cors(resp_with_error(
+                403,
+                "forbidden",
+                "domain not allowed",
+            )))
+        }
+    };
+
+    let user_email = match UserEmail::new(verified.email.clone()) {
+        Ok(u) => u,
+        Err(_) => {
+            return Ok(with_cors(resp_with_error(
+                401,
+                "unauthorized",
+                "invalid user email",
+            )))
+        }
+    };
+
+    if !state.is_admin(&verified.email) {
+        match state.repo.effective_role(&group_id, &user_email) {
+            Ok(Some(role)) if role.can_manage() => {}
+            Ok(_) => {
+                return Ok(with_cors(resp_with_error(
+                    403,
+                    "forbidden",
+                    "admin role required to view invitations",
+                )))
+            }
+            Err(e) => {
+                error!(err=?e, "get member error");
+                return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+            }
+        }
+    }
+
+    let now = state.clock.now();
+    match state.repo.list_invitations_for_group(&group_id) {
+        Ok(invitations) => {
+            let out = InvitationListOut {
+                invitations: invitations
+                    .iter()
+                    .filter(|inv| inv.is_open(now))
+                    .map(|inv| invitation_to_out(inv, None))
+                    .collect(),
+            };
+            Ok(with_cors(resp(
+                200,
+                None,
+                Some(serde_json::to_value(out).expect("serialize")),
+            )))
         }
         Err(e) => {
-            warn!(err=?e, "auth failed");
-            Err(AuthHttp::Unauthorized)
+            error!(err=?e, "list group invitations error");
+            Ok(with_cors(resp_with_error(500, "internal", "server error")))
         }
     }
 }
 
-// Note: Response builders (resp, resp_with_error, with_cors), validation (is_valid_alias),
-// time utilities (system_time_to_rfc3339), URL building (build_short_url_from_host),
-// and query parsing (parse_limit_query) are now provided by the http-common crate.
+async fn list_my_invitations(state: AppState, req: Request) -> Result<Response<Body>, Error> {
+    let verified = match verify_request_user(&state, &req).await {
+        Ok(v) => v,
+        Err(AuthHttp::Unauthorized) => {
+            return Ok(with_cors(resp_with_error(
+                401,
+                "unauthorized",
+                "missing or invalid token",
+            )))
+        }
+        Err(AuthHttp::Forbidden) => {
+            return Ok(with_cors(resp_with_error(
+                403,
+                "forbidden",
+                "domain not allowed",
+            )))
+        }
+    };
 
-// Tests for shared utilities are in http-common crate.
+    // Invitations are stored with lowercased emails
+    let user_email = match UserEmail::new(verified.email.to_lowercase()) {
+        Ok(u) => u,
+        Err(_) => {
+            return Ok(with_cors(resp_with_error(
+                401,
+                "unauthorized",
+                "invalid user email",
+            )))
+        }
+    };
+
+    let invitations = match state.repo.list_invitations_for_user(&user_email) {
+        Ok(list) => list,
+        Err(e) => {
+            error!(err=?e, "list invitations error");
+            return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+        }
+    };
+
+    // Include the group name so the frontend can say "You've been invited to X"
+    let now = state.clock.now();
+    let mut out = Vec::new();
+    for inv in invitations.iter().filter(|inv| inv.is_open(now)) {
+        let group_name = match state.repo.get_group(&inv.group_id) {
+            Ok(Some(g)) => g.name,
+            // Group deleted since the invite was sent
+            Ok(None) => continue,
+            Err(e) => {
+                error!(err=?e, "get group error");
+                return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+            }
+        };
+        out.push(invitation_to_out(inv, Some(group_name)));
+    }
+
+    Ok(with_cors(resp(
+        200,
+        None,
+        Some(serde_json::to_value(InvitationListOut { invitations: out }).expect("serialize")),
+    )))
+}
+
+async fn respond_to_invitation(
+    state: AppState,
+    req: Request,
+    invitation_id: String,
+    accept: bool,
+) -> Result<Response<Body>, Error> {
+    let verified = match verify_request_user(&state, &req).await {
+        Ok(v) => v,
+        Err(AuthHttp::Unauthorized) => {
+            return Ok(with_cors(resp_with_error(
+                401,
+                "unauthorized",
+                "missing or invalid token",
+            )))
+        }
+        Err(AuthHttp::Forbidden) => {
+            return Ok(with_cors(resp_with_error(
+                403,
+                "forbidden",
+                "domain not allowed",
+            )))
+        }
+    };
+
+    let user_email = match UserEmail::new(verified.email.to_lowercase()) {
+        Ok(u) => u,
+        Err(_) => {
+            return Ok(with_cors(resp_with_error(
+                401,
+                "unauthorized",
+                "invalid user email",
+            )))
+        }
+    };
+
+    let invitation = match state.repo.get_invitation(&invitation_id) {
+        Ok(Some(inv)) => inv,
+        Ok(None) => {
+            return Ok(with_cors(resp_with_error(
+                404,
+                "not_found",
+                "invitation not found",
+            )))
+        }
+        Err(e) => {
+            error!(err=?e, "get invitation error");
+            return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+        }
+    };
+
+    // Don't reveal other users' invitations
+    if !invitation
+        .email
+        .as_str()
+        .eq_ignore_ascii_case(user_email.as_str())
+    {
+        return Ok(with_cors(resp_with_error(
+            404,
+            "not_found",
+            "invitation not found",
+        )));
+    }
+
+    let now = state.clock.now();
+    if !invitation.is_open(now) {
+        return Ok(with_cors(resp_with_error(
+            409,
+            "conflict",
+            "invitation is no longer pending",
+        )));
+    }
+
+    if accept {
+        match state.repo.get_group(&invitation.group_id) {
+            Ok(Some(_)) => {}
+            Ok(None) => {
+                return Ok(with_cors(resp_with_error(
+                    404,
+                    "not_found",
+                    "group not found",
+                )))
+            }
+            Err(e) => {
+                error!(err=?e, "get group error");
+                return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+            }
+        }
+    }
+
+    let (status, action) = if accept {
+        (InvitationStatus::Accepted, AuditAction::AcceptInvite)
+    } else {
+        (InvitationStatus::Declined, AuditAction::DeclineInvite)
+    };
+
+    // Answer first: only the request that moves the invitation out of pending
+    // goes on to add the member
+    match state.repo.respond_invitation(&invitation.id, status, now) {
+        Ok(()) => {}
+        Err(CoreError::NotFound) => {
+            return Ok(with_cors(resp_with_error(
+                409,
+                "conflict",
+                "invitation is no longer pending",
+            )))
+        }
+        Err(e) => {
+            error!(err=?e, "respond invitation error");
+            return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+        }
+    }
+
+    if accept {
+        let member = GroupMember {
+            group_id: invitation.group_id.clone(),
+            user_email: invitation.email.clone(),
+            role: invitation.role,
+            added_at: now,
+            added_by: invitation.invited_by.clone(),
+        };
+        // An existing membership keeps its role, even when it is higher
+        match state.repo.add_member(member) {
+            Ok(()) | Err(CoreError::AlreadyExists) => {}
+            Err(e) => {
+                error!(err=?e, invitation = %invitation.id, "add member error after accepting");
+                return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+            }
+        }
+    }
+
+    info!(invitation = %invitation.id, group_id = %invitation.group_id, status = status.as_str(), "invitation answered");
+    record_invitation_audit(&state, &user_email, action, &invitation);
+
+    let answered = GroupInvitation {
+        status,
+        responded_at: Some(now),
+        ..invitation
+    };
+    Ok(with_cors(resp(
+        200,
+        None,
+        Some(serde_json::to_value(invitation_to_out(&answered, None)).expect("serialize")),
+    )))
+}
+
+// -------------------------
+// Link Collaborators
+// -------------------------
+
+/// Whether `email` may manage the collaborators of `link`; see
+/// [`ShortLink::can_manage_collaborators`].
+fn can_manage_collaborators(state: &AppState, link: &ShortLink, email: &UserEmail) -> bool {
+    let allowed =
+        link.can_manage_collaborators(email.as_str(), state.is_admin(email.as_str()), |gid| {
+            state.repo.effective_role(gid, email)
+        });
+    allowed.unwrap_or_else(|e| {
+        error!(err=?e, "effective role error");
+        false
+    })
+}
+
+async fn list_link_collaborators(
+    state: AppState,
+    req: Request,
+    slug_str: String,
+) -> Result<Response<Body>, Error> {
+    let verified = match verify_request_user(&state, &req).await {
+        Ok(v) => v,
+        Err(AuthHttp::Unauthorized) => {
+            return Ok(with_cors(resp_with_error(
+                401,
+                "unauthorized",
+                "missing or invalid token",
+            )))
+        }
+        Err(AuthHttp::Forbidden) => {
+            return Ok(with_cors(resp_with_error(
+                403,
+                "forbidden",
+                "domain not allowed",
+            )))
+        }
+    };
+
+    let user_email = match UserEmail::new(verified.email.clone()) {
+        Ok(u) => u,
+        Err(_) => {
+            return Ok(with_cors(resp_with_error(
+                401,
+                "unauthorized",
+                "invalid user email",
+            )))
+        }
+    };
+
+    let slug = match Slug::from_key(&slug_str) {
+        Ok(s) => s,
+        Err(_) => {
+            return Ok(with_cors(resp_with_error(
+                400,
+                "invalid_request",
+                "invalid slug",
+            )))
+        }
+    };
+
+    let link = match state.repo.get(&slug) {
+        Ok(Some(l)) => l,
+        Ok(None) => {
+            return Ok(with_cors(resp_with_error(
+                404,
+                "not_found",
+                "link not found",
+            )))
+        }
+        Err(e) => {
+            error!(err=?e, "get error");
+            return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+        }
+    };
+
+    let grants = match state.repo.list_grants(&link.slug) {
+        Ok(g) => g,
+        Err(e) => {
+            error!(err=?e, "list grants error");
+            return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+        }
+    };
+
+    // Managers and the collaborators themselves may see who the link is shared with
+    let is_collaborator = grants
+        .iter()
+        .any(|g| g.user_email.as_str() == user_email.as_str());
+    if !is_collaborator && !can_manage_collaborators(&state, &link, &user_email) {
+        return Ok(with_cors(resp_with_error(
+            403,
+            "forbidden",
+            "you do not have access to this link",
+        )));
+    }
+
+    let out = CollaboratorListOut {
+        collaborators: grants.iter().map(grant_to_out).collect(),
+    };
+    Ok(with_cors(resp(
+        200,
+        None,
+        Some(serde_json::to_value(out).expect("serialize")),
+    )))
+}
+
+async fn add_link_collaborator(
+    state: AppState,
+    req: Request,
+    slug_str: String,
+) -> Result<Response<Body>, Error> {
+    let verified = match verify_request_user(&state, &req).await {
+        Ok(v) => v,
+        Err(AuthHttp::Unauthorized) => {
+            return Ok(with_cors(resp_with_error(
+                401,
+                "unauthorized",
+                "missing or invalid token",
+            )))
+        }
+        Err(AuthHttp::Forbidden) => {
+            return Ok(with_cors(resp_with_error(
+                403,
+                "forbidden",
+                "domain not allowed",
+            )))
+        }
+    };
+
+    let user_email = match UserEmail::new(verified.email.clone()) {
+        Ok(u) => u,
+        Err(_) => {
+            return Ok(with_cors(resp_with_error(
+                401,
+                "unauthorized",
+                "invalid user email",
+            )))
+        }
+    };
+
+    let slug = match Slug::from_key(&slug_str) {
+        Ok(s) => s,
+        Err(_) => {
+            return Ok(with_cors(resp_with_error(
+                400,
+                "invalid_request",
+                "invalid slug",
+            )))
+        }
+    };
+
+    let link = match state.repo.get(&slug) {
+        Ok(Some(l)) => l,
+        Ok(None) => {
+            return Ok(with_cors(resp_with_error(
+                404,
+                "not_found",
+                "link not found",
+            )))
+        }
+        Err(e) => {
+            error!(err=?e, "get error");
+            return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+        }
+    };
+
+    if !can_manage_collaborators(&state, &link, &user_email) {
+        return Ok(with_cors(resp_with_error(
+            403,
+            "forbidden",
+            "only the link owner or a group admin can share this link",
+        )));
+    }
+
+    // Parse body
+    let body_str = match req.body() {
+        Body::Empty => {
+            return Ok(with_cors(resp_with_error(
+                400,
+                "invalid_request",
+                "missing body",
+            )))
+        }
+        Body::Text(s) => s.clone(),
+        Body::Binary(b) => String::from_utf8(b.clone()).unwrap_or_default(),
+        _ => String::new(),
+    };
+
+    let payload: AddCollaboratorReq = match serde_json::from_str(&body_str) {
+        Ok(p) => p,
+        Err(_) => {
+            return Ok(with_cors(resp_with_error(
+                400,
+                "invalid_request",
+                "bad json",
+            )))
+        }
+    };
+
+    let collaborator_email = match UserEmail::new(payload.email.clone()) {
+        Ok(e) => e,
+        Err(_) => {
+            return Ok(with_cors(resp_with_error(
+                400,
+                "invalid_request",
+                "invalid email",
+            )))
+        }
+    };
+
+    let role = match GroupRole::parse(&payload.role) {
+        Some(r @ (GroupRole::Viewer | GroupRole::Editor)) => r,
+        _ => {
+            return Ok(with_cors(resp_with_error(
+                400,
+                "invalid_request",
+                "invalid role, use: viewer or editor",
+            )))
+        }
+    };
+
+    let grant = LinkGrant {
+        slug: link.slug,
+        user_email: collaborator_email,
+        role,
+        granted_at: state.clock.now(),
+        granted_by: user_email,
+    };
+
+    match state.repo.put_grant(grant.clone()) {
+        Ok(()) => {
+            info!(slug = %grant.slug.as_str(), collaborator = %payload.email, "collaborator added");
+            Ok(with_cors(resp(
+                201,
+                None,
+                Some(serde_json::to_value(grant_to_out(&grant)).expect("serialize")),
+            )))
+        }
+        Err(e) => {
+            error!(err=?e, "put grant error");
+            Ok(with_cors(resp_with_error(500, "internal", "server error")))
+        }
+    }
+}
+
+async fn remove_link_collaborator(
+    state: AppState,
+    req: Request,
+    slug_str: String,
+    collaborator_email_str: String,
+) -> Result<Response<Body>, Error> {
+    let verified = match verify_request_user(&state, &req).await {
+        Ok(v) => v,
+        Err(AuthHttp::Unauthorized) => {
+            return Ok(with_cors(resp_with_error(
+                401,
+                "unauthorized",
+                "missing or invalid token",
+            )))
+        }
+        Err(AuthHttp::Forbidden) => {
+            return Ok(with_cors(resp_with_error(
+                403,
+                "forbidden",
+                "domain not allowed",
+            )))
+        }
+    };
+
+    let user_email = match UserEmail::new(verified.email.clone()) {
+        Ok(u) => u,
+        Err(_) => {
+            return Ok(with_cors(resp_with_error(
+                401,
+                "unauthorized",
+                "invalid user email",
+            )))
+        }
+    };
+
+    let slug = match Slug::from_key(&slug_str) {
+        Ok(s) => s,
+        Err(_) => {
+            return Ok(with_cors(resp_with_error(
+                400,
+                "invalid_request",
+                "invalid slug",
+            )))
+        }
+    };
+
+    // URL decode the email (it may contain @)
+    let collaborator_email_decoded = urlencoding::decode(&collaborator_email_str)
+        .unwrap_or_else(|_| collaborator_email_str.clone().into());
+    let collaborator_email = match UserEmail::new(collaborator_email_decoded.to_string()) {
+        Ok(e) => e,
+        Err(_) => {
+            return Ok(with_cors(resp_with_error(
+                400,
+                "invalid_request",
+                "invalid email",
+            )))
+        }
+    };
+
+    let link = match state.repo.get(&slug) {
+        Ok(Some(l)) => l,
+        Ok(None) => {
+            return Ok(with_cors(resp_with_error(
+                404,
+                "not_found",
+                "link not found",
+            )))
+        }
+        Err(e) => {
+            error!(err=?e, "get error");
+            return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+        }
+    };
+
+    // Collaborators may always remove themselves
+    let is_self = collaborator_email.as_str() == user_email.as_str();
+    if !is_self && !can_manage_collaborators(&state, &link, &user_email) {
+        return Ok(with_cors(resp_with_error(
+            403,
+            "forbidden",
+            "only the link owner or a group admin can remove collaborators",
+        )));
+    }
+
+    match state.repo.remove_grant(&link.slug, &collaborator_email) {
+        Ok(()) => {
+            info!(slug = %slug.as_str(), collaborator = %collaborator_email_decoded, "collaborator removed");
+            Ok(with_cors(resp(204, None, None)))
+        }
+        Err(CoreError::NotFound) => Ok(with_cors(resp_with_error(
+            404,
+            "not_found",
+            "collaborator not found",
+        ))),
+        Err(e) => {
+            error!(err=?e, "remove grant error");
+            Ok(with_cors(resp_with_error(500, "internal", "server error")))
+        }
+    }
+}
+
+// -------------------------
+// Link Alias API Handlers
+// -------------------------
+
+/// Admins, the link's owner, editors of its group and editor collaborators.
+fn can_edit_link(state: &AppState, link: &ShortLink, email: &UserEmail) -> bool {
+    if state.is_admin(email.as_str()) || link.created_by.as_str() == email.as_str() {
+        return true;
+    }
+    let via_group = link.group_id.as_ref().is_some_and(
+        |gid| matches!(state.repo.effective_role(gid, email), Ok(Some(role)) if role.can_edit()),
+    );
+    via_group
+        || matches!(
+            state.repo.get_grant(&link.slug, email),
+            Ok(Some(grant)) if grant.role.can_edit()
+        )
+}
+
+/// Parse a new slug for `link`; it stays on the link's short domain.
+fn parse_new_slug(link: &ShortLink, value: &str) -> Option<Slug> {
+    if !http_common::is_valid_alias(value) {
+        return None;
+    }
+    Slug::new(value)
+        .ok()
+        .map(|s| s.on_domain(link.slug.domain().map(str::to_string)))
+}
+
+/// A slug taken from a path segment; namespaced slugs arrive percent-encoded
+/// (`hr%2Fonboarding`).
+fn decode_path_slug(segment: &str) -> String {
+    urlencoding::decode(segment)
+        .map(|s| s.into_owned())
+        .unwrap_or_else(|_| segment.to_string())
+}
+
+/// Why `email` may not use `slug`: it lies in a namespace reserved by a group
+/// the caller is not an editor of. Admins may use any namespace.
+fn namespace_violation(
+    state: &AppState,
+    slug: &Slug,
+    email: &UserEmail,
+) -> Result<Option<String>, CoreError> {
+    if state.is_admin(email.as_str()) {
+        return Ok(None);
+    }
+    let Some(namespace) = state.repo.reserved_namespace(slug)? else {
+        return Ok(None);
+    };
+    match state.repo.effective_role(&namespace.group_id, email)? {
+        Some(role) if role.can_edit() => Ok(None),
+        _ => Ok(Some(format!(
+            "namespace {} is reserved by another group",
+            namespace.path
+        ))),
+    }
+}
+
+/// Refuse `slug` when it lies in a namespace `email` may not use.
+fn check_namespace(state: &AppState, slug: &Slug, email: &UserEmail) -> Option<Response<Body>> {
+    match namespace_violation(state, slug, email) {
+        Ok(None) => None,
+        Ok(Some(message)) => Some(with_cors(resp_with_error(403, "forbidden", &message))),
+        Err(e) => {
+            error!(err=?e, "namespace check error");
+            Some(with_cors(resp_with_error(500, "internal", "server error")))
+        }
+    }
+}
+
+fn alias_to_out(alias: &Slug, host: &str) -> AliasOut {
+    let host = alias.domain().unwrap_or(host);
+    AliasOut {
+        slug: alias.key().into_owned(),
+        short_url: http_common::build_short_url_from_host(host, alias.as_str()),
+    }
+}
+
+/// Authenticate the caller and load the link (following aliases) for the alias
+/// endpoints. Returns the error response to send otherwise.
+async fn load_link_for_alias(
+    state: &AppState,
+    req: &Request,
+    slug_str: &str,
+) -> Result<(UserEmail, ShortLink), Box<Response<Body>>> {
+    let fail = |status: u16, code: &str, message: &str| {
+        Box::new(with_cors(resp_with_error(status, code, message)))
+    };
+    let verified = match verify_request_user(state, req).await {
+        Ok(v) => v,
+        Err(AuthHttp::Unauthorized) => {
+            return Err(fail(401, "unauthorized", "missing or invalid token"))
+        }
+        Err(AuthHttp::Forbidden) => return Err(fail(403, "forbidden", "domain not allowed")),
+    };
+    let Ok(user_email) = UserEmail::new(verified.email) else {
+        return Err(fail(401, "unauthorized", "invalid user email"));
+    };
+    let Ok(slug) = Slug::from_key(slug_str) else {
+        return Err(fail(400, "invalid_request", "invalid slug"));
+    };
+    match state.repo.get(&slug) {
+        Ok(Some(link)) => Ok((user_email, link)),
+        Ok(None) => Err(fail(404, "not_found", "link not found")),
+        Err(e) => {
+            error!(err=?e, "get error");
+            Err(fail(500, "internal", "server error"))
+        }
+    }
+}
+
+/// Parse the JSON request body.
+fn json_body<T: serde::de::DeserializeOwned>(req: &Request) -> Option<T> {
+    let body_str = match req.body() {
+        Body::Text(s) => s.clone(),
+        Body::Binary(b) => String::from_utf8(b.clone()).unwrap_or_default(),
+        _ => return None,
+    };
+    serde_json::from_str(&body_str).ok()
+}
+
+async fn rename_link(
+    state: AppState,
+    req: Request,
+    slug_str: String,
+) -> Result<Response<Body>, Error> {
+    let (user_email, link) = match load_link_for_alias(&state, &req, &slug_str).await {
+        Ok(v) => v,
+        Err(resp) => return Ok(*resp),
+    };
+    if !can_edit_link(&state, &link, &user_email) {
+        return Ok(with_cors(resp_with_error(
+            403,
+            "forbidden",
+            "you can only rename your own links or links in groups you have editor access to",
+        )));
+    }
+    let Some(payload) = json_body::<RenameLinkReq>(&req) else {
+        return Ok(with_cors(resp_with_error(
+            400,
+            "invalid_request",
+            "bad json",
+        )));
+    };
+    let Some(new_slug) = parse_new_slug(&link, &payload.slug) else {
+        return Ok(with_cors(resp_with_error(
+            400,
+            "invalid_request",
+            "slug must be 3-32 characters, alphanumeric with hyphens/underscores (namespaces separated by /)",
+        )));
+    };
+    if let Some(resp) = check_namespace(&state, &new_slug, &user_email) {
+        return Ok(resp);
+    }
+
+    let renamed = match state.links.rename(&link.slug, &new_slug) {
+        Ok(l) => l,
+        Err(CoreError::AlreadyExists) => {
+            return Ok(with_cors(resp_with_error(
+                409,
+                "conflict",
+                "slug already exists",
+            )))
+        }
+        Err(CoreError::NotFound) => {
+            return Ok(with_cors(resp_with_error(
+                404,
+                "not_found",
+                "link not found",
+            )))
+        }
+        Err(e) => {
+            error!(err=?e, "rename error");
+            return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+        }
+    };
+    info!(slug = %link.slug.key(), renamed_to = %renamed.slug.key(), "rename ok");
+    let host = state.short_host(&req);
+    Ok(with_cors(resp(
+        200,
+        None,
+        Some(serde_json::to_value(link_to_out(renamed, host)).expect("LinkOut serialization")),
+    )))
+}
+
+async fn list_link_aliases(
+    state: AppState,
+    req: Request,
+    slug_str: String,
+) -> Result<Response<Body>, Error> {
+    let (user_email, link) = match load_link_for_alias(&state, &req, &slug_str).await {
+        Ok(v) => v,
+        Err(resp) => return Ok(*resp),
+    };
+    let is_collaborator = matches!(state.repo.get_grant(&link.slug, &user_email), Ok(Some(_)));
+    if !is_collaborator && !can_edit_link(&state, &link, &user_email) {
+        return Ok(with_cors(resp_with_error(
+            403,
+            "forbidden",
+            "you do not have access to this link",
+        )));
+    }
+    match state.repo.list_aliases(&link.slug) {
+        Ok(aliases) => {
+            let host = state.short_host(&req);
+            let out = AliasListOut {
+                aliases: aliases.iter().map(|a| alias_to_out(a, host)).collect(),
+            };
+            Ok(with_cors(resp(
+                200,
+                None,
+                Some(serde_json::to_value(out).expect("serialize")),
+            )))
+        }
+        Err(e) => {
+            error!(err=?e, "list aliases error");
+            Ok(with_cors(resp_with_error(500, "internal", "server error")))
+        }
+    }
+}
+
+async fn add_link_alias(
+    state: AppState,
+    req: Request,
+    slug_str: String,
+) -> Result<Response<Body>, Error> {
+    let (user_email, link) = match load_link_for_alias(&state, &req, &slug_str).await {
+        Ok(v) => v,
+        Err(resp) => return Ok(*resp),
+    };
+    if !can_edit_link(&state, &link, &user_email) {
+        return Ok(with_cors(resp_with_error(
+            403,
+            "forbidden",
+            "you can only add aliases to links you can edit",
+        )));
+    }
+    let Some(payload) = json_body::<AddAliasReq>(&req) else {
+        return Ok(with_cors(resp_with_error(
+            400,
+            "invalid_request",
+            "bad json",
+        )));
+    };
+    let Some(alias) = parse_new_slug(&link, &payload.alias) else {
+        return Ok(with_cors(resp_with_error(
+            400,
+            "invalid_request",
+            "alias must be 3-32 characters, alphanumeric with hyphens/underscores (namespaces separated by /)",
+        )));
+    };
+    if let Some(resp) = check_namespace(&state, &alias, &user_email) {
+        return Ok(resp);
+    }
+    match state.links.add_alias(&alias, &link.slug) {
+        Ok(()) => {
+            info!(slug = %link.slug.key(), alias = %alias.key(), "alias added");
+            let out = alias_to_out(&alias, state.short_host(&req));
+            Ok(with_cors(resp(
+                201,
+                None,
+                Some(serde_json::to_value(out).expect("serialize")),
+            )))
+        }
+        Err(CoreError::AlreadyExists) => Ok(with_cors(resp_with_error(
+            409,
+            "conflict",
+            "alias already exists",
+        ))),
+        Err(CoreError::NotFound) => Ok(with_cors(resp_with_error(
+            404,
+            "not_found",
+            "link not found",
+        ))),
+        Err(e) => {
+            error!(err=?e, "add alias error");
+            Ok(with_cors(resp_with_error(500, "internal", "server error")))
+        }
+    }
+}
+
+async fn remove_link_alias(
+    state: AppState,
+    req: Request,
+    slug_str: String,
+    alias_str: String,
+) -> Result<Response<Body>, Error> {
+    let (user_email, link) = match load_link_for_alias(&state, &req, &slug_str).await {
+        Ok(v) => v,
+        Err(resp) => return Ok(*resp),
+    };
+    if !can_edit_link(&state, &link, &user_email) {
+        return Ok(with_cors(resp_with_error(
+            403,
+            "forbidden",
+            "you can only remove aliases from links you can edit",
+        )));
+    }
+    // Only aliases of this link can be removed through it
+    let alias_decoded =
+        urlencoding::decode(&alias_str).unwrap_or_else(|_| alias_str.clone().into());
+    let result = Slug::from_key(&alias_decoded).and_then(|alias| {
+        if state.repo.list_aliases(&link.slug)?.contains(&alias) {
+            state.links.remove_alias(&alias)
+        } else {
+            Err(CoreError::NotFound)
+        }
+    });
+    match result {
+        Ok(()) => {
+            info!(slug = %link.slug.key(), alias = %alias_decoded, "alias removed");
+            Ok(with_cors(resp(204, None, None)))
+        }
+        Err(CoreError::NotFound | CoreError::InvalidSlug(_)) => Ok(with_cors(resp_with_error(
+            404,
+            "not_found",
+            "alias not found",
+        ))),
+        Err(e) => {
+            error!(err=?e, "remove alias error");
+            Ok(with_cors(resp_with_error(500, "internal", "server error")))
+        }
+    }
+}
+
+// -------------------------
+// Organization API Handlers
+// -------------------------
+
+async fn list_organizations(state: AppState, req: Request) -> Result<Response<Body>, Error> {
+    let verified = match verify_request_user(&state, &req).await {
+        Ok(v) => v,
+        Err(AuthHttp::Unauthorized) => {
+            return Ok(with_cors(resp_with_error(
+                401,
+                "unauthorized",
+                "missing or invalid token",
+            )))
+        }
+        Err(AuthHttp::Forbidden) => {
+            return Ok(with_cors(resp_with_error(
+                403,
+                "forbidden",
+                "domain not allowed",
+            )))
+        }
+    };
+    // Organizations span tenants, so only deployment admins manage them
+    if !is_admin(&verified.email) {
+        return Ok(with_cors(resp_with_error(
+            403,
+            "forbidden",
+            "admin access required",
+        )));
+    }
+
+    match state.repo.list_organizations() {
+        Ok(orgs) => {
+            let out = OrganizationListOut {
+                organizations: orgs.iter().map(organization_to_out).collect(),
+            };
+            Ok(with_cors(resp(
+                200,
+                None,
+                Some(serde_json::to_value(out).expect("serialize")),
+            )))
+        }
+        Err(e) => {
+            error!(err=?e, "list organizations error");
+            Ok(with_cors(resp_with_error(500, "internal", "server error")))
+        }
+    }
+}
+
+async fn put_organization(
+    state: AppState,
+    req: Request,
+    org_id: String,
+) -> Result<Response<Body>, Error> {
+    let verified = match verify_request_user(&state, &req).await {
+        Ok(v) => v,
+        Err(AuthHttp::Unauthorized) => {
+            return Ok(with_cors(resp_with_error(
+                401,
+                "unauthorized",
+                "missing or invalid token",
+            )))
+        }
+        Err(AuthHttp::Forbidden) => {
+            return Ok(with_cors(resp_with_error(
+                403,
+                "forbidden",
+                "domain not allowed",
+            )))
+        }
+    };
+    if !is_admin(&verified.email) {
+        return Ok(with_cors(resp_with_error(
+            403,
+            "forbidden",
+            "admin access required",
+        )));
+    }
+
+    let Ok(id) = TenantId::new(org_id) else {
+        return Ok(with_cors(resp_with_error(
+            400,
+            "invalid_request",
+            "organization id must be lowercase letters, digits or '-'",
+        )));
+    };
+    let payload: PutOrganizationReq = match serde_json::from_slice(req.body().as_ref()) {
+        Ok(p) => p,
+        Err(_) => {
+            return Ok(with_cors(resp_with_error(
+                400,
+                "invalid_request",
+                "invalid JSON body",
+            )))
+        }
+    };
+    if payload.name.trim().is_empty() {
+        return Ok(with_cors(resp_with_error(
+            400,
+            "invalid_request",
+            "organization name is required",
+        )));
+    }
+    let Ok(admins) = payload
+        .admins
+        .into_iter()
+        .map(UserEmail::new)
+        .collect::<Result<Vec<_>, _>>()
+    else {
+        return Ok(with_cors(resp_with_error(
+            400,
+            "invalid_request",
+            "invalid admin email",
+        )));
+    };
+
+    let created_at = match state.repo.get_organization(&id) {
+        Ok(existing) => existing.map_or_else(|| state.clock.now(), |o| o.created_at),
+        Err(e) => {
+            error!(err=?e, "get organization error");
+            return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+        }
+    };
+    let lower = |v: Vec<String>| -> Vec<String> {
+        v.into_iter()
+            .map(|s| s.trim().to_ascii_lowercase())
+            .filter(|s| !s.is_empty())
+            .collect()
+    };
+    let org = Organization {
+        id,
+        name: payload.name.trim().to_string(),
+        hosts: lower(payload.hosts),
+        auth_domains: lower(payload.auth_domains),
+        admins,
+        settings: OrganizationSettings {
+            shortlink_domain: payload.shortlink_domain.filter(|d| !d.trim().is_empty()),
+            allowed_hosts: lower(payload.allowed_hosts),
+        },
+        created_at,
+    };
+    match state.repo.put_organization(org.clone()) {
+        Ok(()) => {
+            info!(tenant = %org.id.as_str(), "organization saved");
+            Ok(with_cors(resp(
+                200,
+                None,
+                Some(serde_json::to_value(organization_to_out(&org)).expect("serialize")),
+            )))
+        }
+        Err(e) => {
+            error!(err=?e, "put organization error");
+            Ok(with_cors(resp_with_error(500, "internal", "server error")))
+        }
+    }
+}
+
+async fn list_domains(state: AppState, req: Request) -> Result<Response<Body>, Error> {
+    let verified = match verify_request_user(&state, &req).await {
+        Ok(v) => v,
+        Err(AuthHttp::Unauthorized) => {
+            return Ok(with_cors(resp_with_error(
+                401,
+                "unauthorized",
+                "missing or invalid token",
+            )))
+        }
+        Err(AuthHttp::Forbidden) => {
+            return Ok(with_cors(resp_with_error(
+                403,
+                "forbidden",
+                "domain not allowed",
+            )))
+        }
+    };
+    let Ok(user_email) = UserEmail::new(verified.email.clone()) else {
+        return Ok(with_cors(resp_with_error(
+            401,
+            "unauthorized",
+            "invalid user email in token",
+        )));
+    };
+
+    let domains = match state.repo.list_domains() {
+        Ok(d) => d,
+        Err(e) => {
+            error!(err=?e, "list domains error");
+            return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+        }
+    };
+    let group_ids: Vec<String> = match state.repo.get_user_groups(&user_email) {
+        Ok(groups) => groups.into_iter().map(|(g, _)| g.id).collect(),
+        Err(e) => {
+            error!(err=?e, "get user groups error");
+            return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+        }
+    };
+    let user_is_admin = state.is_admin(&verified.email);
+    let out = DomainListOut {
+        domains: domains
+            .iter()
+            .map(|d| domain_to_out(d, user_is_admin || d.may_use(&user_email, &group_ids)))
+            .collect(),
+    };
+    Ok(with_cors(resp(
+        200,
+        None,
+        Some(serde_json::to_value(out).expect("serialize")),
+    )))
+}
+
+async fn put_domain(state: AppState, req: Request, host: String) -> Result<Response<Body>, Error> {
+    let verified = match verify_request_user(&state, &req).await {
+        Ok(v) => v,
+        Err(AuthHttp::Unauthorized) => {
+            return Ok(with_cors(resp_with_error(
+                401,
+                "unauthorized",
+                "missing or invalid token",
+            )))
+        }
+        Err(AuthHttp::Forbidden) => {
+            return Ok(with_cors(resp_with_error(
+                403,
+                "forbidden",
+                "domain not allowed",
+            )))
+        }
+    };
+    if !state.is_admin(&verified.email) {
+        return Ok(with_cors(resp_with_error(
+            403,
+            "forbidden",
+            "admin access required",
+        )));
+    }
+    let Ok(created_by) = UserEmail::new(verified.email.clone()) else {
+        return Ok(with_cors(resp_with_error(
+            401,
+            "unauthorized",
+            "invalid user email in token",
+        )));
+    };
+
+    let Ok(host) = domain::normalize_host(&host) else {
+        return Ok(with_cors(resp_with_error(
+            400,
+            "invalid_request",
+            "invalid domain",
+        )));
+    };
+    // Other organizations are picked by host, so they may only use their own
+    if !state.org.id.is_default() && !state.org.serves_host(&host) {
+        return Ok(with_cors(resp_with_error(
+            400,
+            "invalid_request",
+            "domain is not one of the organization's hosts",
+        )));
+    }
+    let payload: PutDomainReq = match serde_json::from_slice(req.body().as_ref()) {
+        Ok(p) => p,
+        Err(_) => {
+            return Ok(with_cors(resp_with_error(
+                400,
+                "invalid_request",
+                "invalid JSON body",
+            )))
+        }
+    };
+    let Ok(allowed_users) = payload
+        .allowed_users
+        .into_iter()
+        .map(UserEmail::new)
+        .collect::<Result<Vec<_>, _>>()
+    else {
+        return Ok(with_cors(resp_with_error(
+            400,
+            "invalid_request",
+            "invalid user email",
+        )));
+    };
+    for group_id in &payload.allowed_groups {
+        match state.repo.get_group(group_id) {
+            Ok(Some(_)) => {}
+            Ok(None) => {
+                return Ok(with_cors(resp_with_error(
+                    400,
+                    "invalid_request",
+                    "group not found",
+                )))
+            }
+            Err(e) => {
+                error!(err=?e, "get group error");
+                return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+            }
+        }
+    }
+
+    let (created_at, created_by) = match state.repo.get_domain(&host) {
+        Ok(Some(existing)) => (existing.created_at, existing.created_by),
+        Ok(None) => (state.clock.now(), created_by),
+        Err(e) => {
+            error!(err=?e, "get domain error");
+            return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+        }
+    };
+    let short_domain = ShortDomain {
+        host,
+        allowed_users,
+        allowed_groups: payload.allowed_groups,
+        created_at,
+        created_by,
+    };
+    match state.repo.put_domain(short_domain.clone()) {
+        Ok(()) => {
+            info!(host = %short_domain.host, "short domain saved");
+            Ok(with_cors(resp(
+                200,
+                None,
+                Some(serde_json::to_value(domain_to_out(&short_domain, true)).expect("serialize")),
+            )))
+        }
+        Err(e) => {
+            error!(err=?e, "put domain error");
+            Ok(with_cors(resp_with_error(500, "internal", "server error")))
+        }
+    }
+}
+
+async fn delete_domain(
+    state: AppState,
+    req: Request,
+    host: String,
+) -> Result<Response<Body>, Error> {
+    let verified = match verify_request_user(&state, &req).await {
+        Ok(v) => v,
+        Err(AuthHttp::Unauthorized) => {
+            return Ok(with_cors(resp_with_error(
+                401,
+                "unauthorized",
+                "missing or invalid token",
+            )))
+        }
+        Err(AuthHttp::Forbidden) => {
+            return Ok(with_cors(resp_with_error(
+                403,
+                "forbidden",
+                "domain not allowed",
+            )))
+        }
+    };
+    if !state.is_admin(&verified.email) {
+        return Ok(with_cors(resp_with_error(
+            403,
+            "forbidden",
+            "admin access required",
+        )));
+    }
+    let host = host.to_ascii_lowercase();
+    match state.repo.delete_domain(&host) {
+        Ok(()) => {
+            info!(host = %host, "short domain deleted");
+            Ok(with_cors(resp(204, None, None)))
+        }
+        Err(CoreError::NotFound) => Ok(with_cors(resp_with_error(
+            404,
+            "not_found",
+            "domain not found",
+        ))),
+        Err(e) => {
+            error!(err=?e, "delete domain error");
+            Ok(with_cors(resp_with_error(500, "internal", "server error")))
+        }
+    }
+}
+
+/// Authenticate the caller of a namespace endpoint. Returns the error response
+/// to send otherwise.
+async fn verify_namespace_caller(
+    state: &AppState,
+    req: &Request,
+) -> Result<UserEmail, Box<Response<Body>>> {
+    let fail = |status: u16, code: &str, message: &str| {
+        Box::new(with_cors(resp_with_error(status, code, message)))
+    };
+    match verify_request_user(state, req).await {
+        Ok(v) => UserEmail::new(v.email)
+            .map_err(|_| fail(401, "unauthorized", "invalid user email in token")),
+        Err(AuthHttp::Unauthorized) => Err(fail(401, "unauthorized", "missing or invalid token")),
+        Err(AuthHttp::Forbidden) => Err(fail(403, "forbidden", "domain not allowed")),
+    }
+}
+
+/// Admins and admins of the group may reserve and release its namespaces.
+fn can_manage_namespace(
+    state: &AppState,
+    group_id: &str,
+    user: &UserEmail,
+) -> Result<bool, CoreError> {
+    if state.is_admin(user.as_str()) {
+        return Ok(true);
+    }
+    Ok(state
+        .repo
+        .effective_role(group_id, user)?
+        .is_some_and(|r| r.can_manage()))
+}
+
+async fn list_namespaces(state: AppState, req: Request) -> Result<Response<Body>, Error> {
+    if let Err(resp) = verify_namespace_caller(&state, &req).await {
+        return Ok(*resp);
+    }
+    match state.repo.list_namespaces() {
+        Ok(namespaces) => {
+            let out = NamespaceListOut {
+                namespaces: namespaces.iter().map(namespace_to_out).collect(),
+            };
+            Ok(with_cors(resp(
+                200,
+                None,
+                Some(serde_json::to_value(out).expect("serialize")),
+            )))
+        }
+        Err(e) => {
+            error!(err=?e, "list namespaces error");
+            Ok(with_cors(resp_with_error(500, "internal", "server error")))
+        }
+    }
+}
+
+async fn put_namespace(
+    state: AppState,
+    req: Request,
+    path: String,
+) -> Result<Response<Body>, Error> {
+    let user_email = match verify_namespace_caller(&state, &req).await {
+        Ok(u) => u,
+        Err(resp) => return Ok(*resp),
+    };
+    if Slug::new(path.as_str()).is_err() {
+        return Ok(with_cors(resp_with_error(
+            400,
+            "invalid_request",
+            "invalid namespace",
+        )));
+    }
+    let Some(payload) = json_body::<PutNamespaceReq>(&req) else {
+        return Ok(with_cors(resp_with_error(
+            400,
+            "invalid_request",
+            "bad json",
+        )));
+    };
+    match state.repo.get_group(&payload.group_id) {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return Ok(with_cors(resp_with_error(
+                404,
+                "not_found",
+                "group not found",
+            )))
+        }
+        Err(e) => {
+            error!(err=?e, "get group error");
+            return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+        }
+    }
+    match can_manage_namespace(&state, &payload.group_id, &user_email) {
+        Ok(true) => {}
+        Ok(false) => {
+            return Ok(with_cors(resp_with_error(
+                403,
+                "forbidden",
+                "group admin access required",
+            )))
+        }
+        Err(e) => {
+            error!(err=?e, "get role error");
+            return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+        }
+    }
+
+    let namespace = Namespace {
+        path,
+        group_id: payload.group_id,
+        created_at: state.clock.now(),
+        created_by: user_email,
+    };
+    match state.repo.put_namespace(namespace.clone()) {
+        Ok(()) => {
+            info!(path = %namespace.path, group_id = %namespace.group_id, "namespace reserved");
+            Ok(with_cors(resp(
+                201,
+                None,
+                Some(serde_json::to_value(namespace_to_out(&namespace)).expect("serialize")),
+            )))
+        }
+        Err(CoreError::AlreadyExists) => Ok(with_cors(resp_with_error(
+            409,
+            "conflict",
+            "namespace already reserved",
+        ))),
+        Err(e) => {
+            error!(err=?e, "put namespace error");
+            Ok(with_cors(resp_with_error(500, "internal", "server error")))
+        }
+    }
+}
+
+async fn delete_namespace(
+    state: AppState,
+    req: Request,
+    path: String,
+) -> Result<Response<Body>, Error> {
+    let user_email = match verify_namespace_caller(&state, &req).await {
+        Ok(u) => u,
+        Err(resp) => return Ok(*resp),
+    };
+    let namespace = match state.repo.get_namespace(&path) {
+        Ok(Some(ns)) => ns,
+        Ok(None) => {
+            return Ok(with_cors(resp_with_error(
+                404,
+                "not_found",
+                "namespace not reserved",
+            )))
+        }
+        Err(e) => {
+            error!(err=?e, "get namespace error");
+            return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+        }
+    };
+    match can_manage_namespace(&state, &namespace.group_id, &user_email) {
+        Ok(true) => {}
+        Ok(false) => {
+            return Ok(with_cors(resp_with_error(
+                403,
+                "forbidden",
+                "group admin access required",
+            )))
+        }
+        Err(e) => {
+            error!(err=?e, "get role error");
+            return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+        }
+    }
+    match state.repo.delete_namespace(&path) {
+        Ok(()) => {
+            info!(path = %path, "namespace released");
+            Ok(with_cors(resp(204, None, None)))
+        }
+        Err(CoreError::NotFound) => Ok(with_cors(resp_with_error(
+            404,
+            "not_found",
+            "namespace not reserved",
+        ))),
+        Err(e) => {
+            error!(err=?e, "delete namespace error");
+            Ok(with_cors(resp_with_error(500, "internal", "server error")))
+        }
+    }
+}
+
+/// The registered short domain named by a query parameter; `Ok(None)` for the
+/// default domain, `NotFound` when it is not registered.
+fn query_short_domain(state: &AppState, domain: Option<&str>) -> Result<Option<String>, CoreError> {
+    let Some(d) = domain.filter(|d| !d.trim().is_empty()) else {
+        return Ok(None);
+    };
+    let host = domain::normalize_host(d).map_err(|_| CoreError::NotFound)?;
+    match state.repo.get_domain(&host)? {
+        Some(sd) => Ok(Some(sd.host)),
+        None => Err(CoreError::NotFound),
+    }
+}
+
+async fn check_slug(state: AppState, req: Request) -> Result<Response<Body>, Error> {
+    let user_email = match verify_namespace_caller(&state, &req).await {
+        Ok(u) => u,
+        Err(resp) => return Ok(*resp),
+    };
+    let query = req.uri().query();
+    let Some(alias) = http_common::parse_query_param(query, "alias") else {
+        return Ok(with_cors(resp_with_error(
+            400,
+            "invalid_request",
+            "alias is required",
+        )));
+    };
+    let internal = |e: CoreError| {
+        error!(err=?e, "check slug error");
+        Ok(with_cors(resp_with_error(500, "internal", "server error")))
+    };
+    let mut violations: Vec<String> = http_common::alias_violations(&alias)
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+    let mut taken = false;
+    if let (true, Ok(slug)) = (violations.is_empty(), Slug::new(alias.as_str())) {
+        let domain = http_common::parse_query_param(query, "domain");
+        match query_short_domain(&state, domain.as_deref()) {
+            Ok(short_domain) => match state.repo.get(&slug.clone().on_domain(short_domain)) {
+                Ok(link) => taken = link.is_some(),
+                Err(e) => return internal(e),
+            },
+            Err(CoreError::NotFound) => violations.push("unknown domain".into()),
+            Err(e) => return internal(e),
+        }
+        match namespace_violation(&state, &slug, &user_email) {
+            Ok(v) => violations.extend(v),
+            Err(e) => return internal(e),
+        }
+        if let Some(group_id) = http_common::parse_query_param(query, "group_id") {
+            match state.repo.get_group(&group_id) {
+                Ok(Some(group)) => {
+                    if let Err(e) = group.settings.check_slug(&slug) {
+                        violations.push(e.to_string());
+                    }
+                }
+                Ok(None) => violations.push("group not found".into()),
+                Err(e) => return internal(e),
+            }
+        }
+    }
+    let out = SlugCheckOut {
+        available: violations.is_empty() && !taken,
+        alias,
+        taken,
+        violations,
+    };
+    Ok(with_cors(resp(
+        200,
+        None,
+        Some(serde_json::to_value(out).expect("serialize")),
+    )))
+}
+
+async fn suggest_slugs(state: AppState, req: Request) -> Result<Response<Body>, Error> {
+    if let Err(resp) = verify_namespace_caller(&state, &req).await {
+        return Ok(*resp);
+    }
+    let query = req.uri().query();
+    let url = http_common::parse_query_param(query, "url").unwrap_or_default();
+    if let Err(e) = domain::validate::validate_original_url(&url) {
+        return Ok(with_cors(resp_with_error(
+            400,
+            "invalid_request",
+            &e.to_string(),
+        )));
+    }
+    let domain = http_common::parse_query_param(query, "domain");
+    let short_domain = match query_short_domain(&state, domain.as_deref()) {
+        Ok(d) => d,
+        Err(CoreError::NotFound) => {
+            return Ok(with_cors(resp_with_error(
+                400,
+                "invalid_request",
+                "unknown domain",
+            )))
+        }
+        Err(e) => {
+            error!(err=?e, "get domain error");
+            return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+        }
+    };
+    let limit = http_common::parse_limit_query(query)
+        .unwrap_or(5)
+        .clamp(1, 20);
+    let title = http_common::parse_query_param(query, "title");
+    let host = state.short_host(&req);
+    let mut suggestions = Vec::new();
+    for slug in domain::slug::suggest_slugs(&url, title.as_deref()) {
+        if suggestions.len() >= limit {
+            break;
+        }
+        let slug = slug.on_domain(short_domain.clone());
+        match state.repo.get(&slug) {
+            Ok(Some(_)) => {}
+            Ok(None) => suggestions.push(alias_to_out(&slug, host)),
+            Err(e) => {
+                error!(err=?e, "suggest slugs error");
+                return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+            }
+        }
+    }
+    Ok(with_cors(resp(
+        200,
+        None,
+        Some(serde_json::to_value(SlugSuggestOut { suggestions }).expect("serialize")),
+    )))
+}
+
+/// Links read per reverse lookup before filtering by what the caller may see.
+const TARGET_LOOKUP_LIMIT: usize = 1000;
+
+/// Parse the `match` parameter and the lookup key for `url`, or the 400 to send.
+fn parse_target_lookup(
+    url: &str,
+    mode: Option<&str>,
+) -> Result<(TargetMatch, String), Box<Response<Body>>> {
+    let bad_request =
+        |message: &str| Box::new(with_cors(resp_with_error(400, "invalid_request", message)));
+    let mode = match mode {
+        None => TargetMatch::default(),
+        Some(m) => TargetMatch::parse(m)
+            .ok_or_else(|| bad_request("invalid match, use: exact, prefix or host"))?,
+    };
+    let key = mode
+        .lookup_key(url)
+        .map_err(|e| bad_request(&e.to_string()))?;
+    Ok((mode, key))
+}
+
+/// Links matching `key` that `user` may see: every link for admins, otherwise
+/// their own, their groups' and the ones shared with them. Oldest first.
+fn visible_links_by_target(
+    state: &AppState,
+    user: &UserEmail,
+    key: &str,
+    mode: TargetMatch,
+) -> Result<Vec<ShortLink>, CoreError> {
+    let links = state.repo.list_by_target(key, mode, TARGET_LOOKUP_LIMIT)?;
+    if state.is_admin(user.as_str()) {
+        return Ok(links);
+    }
+    let scope = domain::AccessScope {
+        user: user.clone(),
+        group_ids: state
+            .repo
+            .get_user_groups(user)?
+            .into_iter()
+            .map(|(group, _)| group.id)
+            .collect(),
+        shared_slugs: state
+            .repo
+            .list_grants_for_user(user)?
+            .into_iter()
+            .map(|grant| grant.slug)
+            .collect(),
+    };
+    Ok(links.into_iter().filter(|l| scope.contains(l)).collect())
+}
+
+/// `GET /api/links/by-target?url=&match=`: the caller's visible links pointing
+/// at a destination.
+async fn links_by_target(state: AppState, req: Request) -> Result<Response<Body>, Error> {
+    let user_email = match verify_namespace_caller(&state, &req).await {
+        Ok(u) => u,
+        Err(resp) => return Ok(*resp),
+    };
+    let query = req.uri().query();
+    let url = http_common::parse_query_param(query, "url").unwrap_or_default();
+    let mode = http_common::parse_query_param(query, "match");
+    let (mode, key) = match parse_target_lookup(&url, mode.as_deref()) {
+        Ok(lookup) => lookup,
+        Err(resp) => return Ok(*resp),
+    };
+    let limit = http_common::parse_limit_query(query)
+        .unwrap_or(100)
+        .clamp(1, 500);
+    match visible_links_by_target(&state, &user_email, &key, mode) {
+        Ok(mut links) => {
+            let total = links.len();
+            links.truncate(limit);
+            let host = state.short_host(&req);
+            let out = ListOut {
+                links: links.into_iter().map(|l| link_to_out(l, host)).collect(),
+                total: Some(total),
+                has_more: total > limit,
+                next_cursor: None,
+                user: None,
+            };
+            Ok(with_cors(resp(
+                200,
+                None,
+                Some(serde_json::to_value(out).expect("serialize")),
+            )))
+        }
+        Err(e) => {
+            error!(err=?e, "links by target error");
+            Ok(with_cors(resp_with_error(500, "internal", "server error")))
+        }
+    }
+}
+
+/// `POST /api/links/by-target/repoint`: move every matching link the caller may
+/// edit to a new destination.
+async fn repoint_links_by_target(state: AppState, req: Request) -> Result<Response<Body>, Error> {
+    let user_email = match verify_namespace_caller(&state, &req).await {
+        Ok(u) => u,
+        Err(resp) => return Ok(*resp),
+    };
+    let payload: RepointReq = match serde_json::from_slice(req.body().as_ref()) {
+        Ok(p) => p,
+        Err(_) => {
+            return Ok(with_cors(resp_with_error(
+                400,
+                "invalid_request",
+                "invalid JSON body",
+            )))
+        }
+    };
+    let (mode, key) = match parse_target_lookup(&payload.url, payload.mode.as_deref()) {
+        Ok(lookup) => lookup,
+        Err(resp) => return Ok(*resp),
+    };
+    if let Err(e) = domain::validate::validate_original_url(&payload.to) {
+        return Ok(with_cors(resp_with_error(
+            400,
+            "invalid_request",
+            &format!("{}", e),
+        )));
+    }
+    let links = match visible_links_by_target(&state, &user_email, &key, mode) {
+        Ok(links) => links,
+        Err(e) => {
+            error!(err=?e, "repoint links error");
+            return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+        }
+    };
+    let now = state.clock.now();
+    let mut out = RepointOut {
+        affected: 0,
+        slugs: Vec::new(),
+        skipped: Vec::new(),
+    };
+    for link in links {
+        let slug = link.slug.key().into_owned();
+        let Some(mut link) = repointed(&state, &user_email, link, mode, &key, &payload.to) else {
+            out.skipped.push(slug);
+            continue;
+        };
+        link.updated_at = Some(now);
+        match state.links.update(&link) {
+            Ok(()) => out.slugs.push(slug),
+            Err(CoreError::NotFound) => out.skipped.push(slug),
+            Err(e) => {
+                error!(err=?e, "repoint links error");
+                return Ok(with_cors(resp_with_error(500, "internal", "server error")));
+            }
+        }
+    }
+    out.affected = out.slugs.len();
+    info!(count = out.affected, "repoint links ok");
+    Ok(with_cors(resp(
+        200,
+        None,
+        Some(serde_json::to_value(out).expect("serialize")),
+    )))
+}
+
+/// `link` moved to `to` (as matched by `key`), or `None` when `user` may not
+/// edit it or the new destination breaks a URL, organization or group rule.
+fn repointed(
+    state: &AppState,
+    user: &UserEmail,
+    mut link: ShortLink,
+    mode: TargetMatch,
+    key: &str,
+    to: &str,
+) -> Option<ShortLink> {
+    let url = mode.rewrite(key, &link, to);
+    if !can_edit_link(state, &link, user) || domain::validate::validate_original_url(&url).is_err()
+    {
+        return None;
+    }
+    link.original_url = url;
+    enforce_link_settings(state, &mut link, GroupCheck::Update { moved_in: false })
+        .is_none()
+        .then_some(link)
+}
+
+enum AuthHttp {
+    Unauthorized,
+    Forbidden,
+}
+
+fn bearer_token(req: &Request) -> Option<&str> {
+    req.headers()
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())?
+        .strip_prefix("Bearer ")
+}
+
+async fn verify_request_user(state: &AppState, req: &Request) -> Result<VerifiedUser, AuthHttp> {
+    let token = bearer_token(req).ok_or(AuthHttp::Unauthorized)?;
+    let aud = std::env::var("GOOGLE_OAUTH_CLIENT_ID").map_err(|_| AuthHttp::Unauthorized)?;
+    // The token's domain only selects which of the organization's domains to
+    // verify against; verification itself enforces it.
+    let allowed = if state.org.auth_domains.is_empty() {
+        std::env::var("ALLOWED_DOMAIN").map_err(|_| AuthHttp::Unauthorized)?
+    } else {
+        match google_auth::token_domain(token) {
+            Some(d) if state.org.allows_domain(&d) => d,
+            _ => {
+                warn!(tenant = %state.org.id.as_str(), "auth failed: domain not allowed");
+                return Err(AuthHttp::Forbidden);
+            }
+        }
+    };
+    match google_auth::verify_async(token, &aud, &allowed).await {
+        Ok(u) => Ok(u),
+        Err(GAuthError::DomainNotAllowed) => {
+            warn!("auth failed: domain not allowed");
+            Err(AuthHttp::Forbidden)
+        }
+        Err(e) => {
+            warn!(err=?e, "auth failed");
+            Err(AuthHttp::Unauthorized)
+        }
+    }
+}
+
+// Note: Response builders (resp, resp_with_error, with_cors), validation (is_valid_alias),
+// time utilities (system_time_to_rfc3339), URL building (build_short_url_from_host),
+// and query parsing (parse_limit_query) are now provided by the http-common crate.
+
+// Tests for shared utilities are in http-common crate.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::Engine;
+
+    /// State for code paths that never reach storage: the repository points
+    /// at an endpoint nothing listens on.
+    fn offline_state(org: Organization) -> AppState {
+        let tables = aws_dynamo::DynamoTables::new("links", "counters");
+        let repo = DynamoRepo::with_endpoint(tables, "http://127.0.0.1:9").unwrap();
+        AppState {
+            links: CachedLinkRepo::new(repo.clone(), CacheOptions::default()),
+            repo,
+            slugger: Base62SlugGenerator::new(5),
+            word_slugger: WordSlugGenerator::default(),
+            clock: StdClock,
+            orgs: Arc::new(OrganizationCache::default()),
+            org,
+        }
+    }
+
+    fn acme() -> Organization {
+        Organization {
+            id: TenantId::new("acme").unwrap(),
+            name: "Acme".into(),
+            hosts: vec!["go.acme.test".into()],
+            auth_domains: vec!["acme.test".into()],
+            admins: vec![UserEmail::new("boss@acme.test").unwrap()],
+            settings: OrganizationSettings {
+                shortlink_domain: Some("acme.link".into()),
+                allowed_hosts: vec!["*.acme.test".into()],
+            },
+            ..Organization::fallback()
+        }
+    }
+
+    fn request(authorization: Option<&str>) -> Request {
+        let mut req = http::Request::builder()
+            .uri("/api/links")
+            .header("host", "admin.example.com");
+        if let Some(value) = authorization {
+            req = req.header("authorization", value);
+        }
+        req.body(Body::Empty).unwrap()
+    }
+
+    #[test]
+    fn bearer_tokens_come_from_the_authorization_header() {
+        assert_eq!(
+            bearer_token(&request(Some("Bearer abc.def"))),
+            Some("abc.def")
+        );
+        assert_eq!(bearer_token(&request(Some("Basic YTpi"))), None);
+        assert_eq!(bearer_token(&request(None)), None);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn tokens_of_other_sign_in_domains_are_refused() {
+        std::env::set_var("GOOGLE_OAUTH_CLIENT_ID", "client");
+        let state = offline_state(acme());
+        // Unsigned: the hosted domain is refused before the token is verified
+        let claims = r#"{"email":"x@acme.test","hd":"contractor.test"}"#;
+        let payload = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(claims);
+        let req = request(Some(&format!("Bearer e30.{payload}.sig")));
+        assert!(matches!(
+            verify_request_user(&state, &req).await,
+            Err(AuthHttp::Forbidden)
+        ));
+    }
+
+    #[test]
+    fn organizations_bring_their_admins_short_domain_and_target_hosts() {
+        let state = offline_state(acme());
+        assert!(state.is_admin("Boss@acme.test"));
+        assert!(!state.is_admin("x@acme.test"));
+        assert_eq!(state.short_host(&request(None)), "acme.link");
+        assert_eq!(
+            offline_state(Organization::fallback()).short_host(&request(None)),
+            "admin.example.com"
+        );
+
+        let link = |url: &str| {
+            ShortLink::new(
+                Slug::new("docs").unwrap(),
+                url.into(),
+                std::time::UNIX_EPOCH,
+                UserEmail::new("x@acme.test").unwrap(),
+            )
+        };
+        let check = || GroupCheck::Create {
+            generated_slug: false,
+        };
+        let refused =
+            enforce_link_settings(&state, &mut link("https://evil.test/x"), check()).unwrap();
+        assert_eq!(refused.status(), 400);
+        assert!(
+            enforce_link_settings(&state, &mut link("https://wiki.acme.test/x"), check()).is_none()
+        );
+    }
+
+    #[test]
+    fn links_on_short_domains_get_short_urls_there() {
+        let link = |domain: Option<&str>| {
+            ShortLink::new(
+                Slug::new("docs")
+                    .unwrap()
+                    .on_domain(domain.map(str::to_string)),
+                "https://example.com".into(),
+                std::time::UNIX_EPOCH,
+                UserEmail::new("a@acme.test").unwrap(),
+            )
+        };
+        let out = link_to_out(link(Some("go.team.no")), "acme.link");
+        assert_eq!(out.domain.as_deref(), Some("go.team.no"));
+        assert!(
+            out.short_url.contains("go.team.no/docs"),
+            "{}",
+            out.short_url
+        );
+
+        let out = link_to_out(link(None), "acme.link");
+        assert_eq!(out.domain, None);
+        assert!(
+            out.short_url.contains("acme.link/docs"),
+            "{}",
+            out.short_url
+        );
+    }
+
+    #[test]
+    fn new_slugs_and_aliases_stay_on_the_link_domain() {
+        let link = ShortLink::new(
+            Slug::new("docs")
+                .unwrap()
+                .on_domain(Some("go.team.no".into())),
+            "https://example.com".into(),
+            std::time::UNIX_EPOCH,
+            UserEmail::new("a@acme.test").unwrap(),
+        );
+        let renamed = parse_new_slug(&link, "handbook").unwrap();
+        assert_eq!(renamed.as_str(), "handbook");
+        assert_eq!(renamed.domain(), Some("go.team.no"));
+        assert!(parse_new_slug(&link, "no").is_none());
+
+        let out = alias_to_out(&renamed, "acme.link");
+        assert_eq!(out.slug, renamed.key());
+        assert!(
+            out.short_url.contains("go.team.no/handbook"),
+            "{}",
+            out.short_url
+        );
+    }
+
+    #[test]
+    fn creators_and_admins_may_edit_links_without_a_role_lookup() {
+        let state = offline_state(acme());
+        let link = ShortLink::new(
+            Slug::new("docs").unwrap(),
+            "https://wiki.acme.test".into(),
+            std::time::UNIX_EPOCH,
+            UserEmail::new("a@acme.test").unwrap(),
+        );
+        for email in ["a@acme.test", "boss@acme.test"] {
+            assert!(can_edit_link(
+                &state,
+                &link,
+                &UserEmail::new(email).unwrap()
+            ));
+        }
+    }
+
+    #[test]
+    fn path_slugs_are_percent_decoded() {
+        assert_eq!(decode_path_slug("hr%2Fonboarding"), "hr/onboarding");
+        assert_eq!(decode_path_slug("docs"), "docs");
+        assert_eq!(decode_path_slug("bad%ff"), "bad%ff");
+    }
+
+    #[test]
+    fn admins_may_use_and_manage_any_namespace() {
+        let state = offline_state(acme());
+        let boss = UserEmail::new("boss@acme.test").unwrap();
+        let slug = Slug::new("hr/onboarding").unwrap();
+        assert!(check_namespace(&state, &slug, &boss).is_none());
+        assert!(can_manage_namespace(&state, "hr-team", &boss).unwrap());
+    }
+
+    #[test]
+    fn target_lookups_parse_the_match_mode() {
+        let (mode, key) = parse_target_lookup("https://Example.com/a", Some("host")).unwrap();
+        assert_eq!(mode, TargetMatch::Host);
+        assert_eq!(key, "example.com");
+        let (mode, _) = parse_target_lookup("https://example.com/a", None).unwrap();
+        assert_eq!(mode, TargetMatch::default());
+        let err = parse_target_lookup("https://example.com/a", Some("fuzzy")).unwrap_err();
+        assert_eq!(err.status(), 400);
+        let err = parse_target_lookup("not a url", Some("exact")).unwrap_err();
+        assert_eq!(err.status(), 400);
+    }
+
+    #[test]
+    fn repointed_links_keep_to_the_organization_hosts() {
+        let state = offline_state(acme());
+        let creator = UserEmail::new("a@acme.test").unwrap();
+        let link = ShortLink::new(
+            Slug::new("docs").unwrap(),
+            "https://wiki.acme.test/team/docs".into(),
+            std::time::UNIX_EPOCH,
+            creator.clone(),
+        );
+        let (mode, key) = parse_target_lookup("https://wiki.acme.test", Some("host")).unwrap();
+
+        let moved = repointed(
+            &state,
+            &creator,
+            link.clone(),
+            mode,
+            &key,
+            "https://docs.acme.test",
+        )
+        .unwrap();
+        assert_eq!(moved.original_url, "https://docs.acme.test/team/docs");
+        assert!(repointed(&state, &creator, link, mode, &key, "https://evil.test").is_none());
+    }
+}
@@ -3,6 +3,8 @@
 //! Purpose
 //! - Handle API Gateway HTTP API (v2) events.
 //! - Resolve `/:slug` via the `LinkService` backed by the DynamoDB adapter.
+//!   Slugs may be namespaced (`/hr/onboarding`); the whole path after the API
+//!   stage is the slug.
 //! - Return `308 Permanent Redirect` with `Location` header on success; map
 //!   domain errors to sensible HTTP codes for API Gateway responses.
 //!
@@ -11,20 +13,74 @@
 //! - `/{slug}.qr` — QR code image (SVG) for the short URL
 //! - `/{slug}+.qr` — QR code that points to the preview page
 //!
+//! Workspace-only links
+//! - Links with `visibility = workspace` are only followed for visitors holding a
+//!   valid session cookie (`sl_session`); everyone else gets a Google sign-in page.
+//! - `POST /auth/session` exchanges a Google ID token for that cookie. The token and
+//!   the cookie are both checked against the organization's sign-in domains, or
+//!   `ALLOWED_DOMAIN` when it has none.
+//! - Requires `GOOGLE_OAUTH_CLIENT_ID`, `ALLOWED_DOMAIN` and `SESSION_SECRET`;
+//!   `SESSION_TTL_SECS` is optional (default 12 hours).
+//!
+//! Tenancy
+//! - The organization serving the request's `Host` decides which tenant's links
+//!   are resolved; unknown hosts use the default tenant. Hosts are looked up
+//!   one at a time and kept for `ORG_CACHE_TTL_SECS` (default 30).
+//! - Within the tenant, links are resolved by (Host, slug): a host registered as a
+//!   short domain resolves that domain's links, any other host the default ones.
+//!
+//! Caching
+//! - The organization, the short domain and the link behind a request are all
+//!   cached in process (`OrganizationCache`, `domain::cache::CachedDomainRepo`
+//!   and `CachedLinkRepo`), so a warm container serves hot slugs, and unknown
+//!   ones, without a DynamoDB read. Only links are shared through Redis; see
+//!   `link_cache_from_env` for the settings.
+//! - lambda-admin given the same `LINK_CACHE_REDIS_URL` drops the Redis
+//!   entries of the links it writes. This container's own entries are not
+//!   invalidated: a change (a deleted or disabled link included) shows up
+//!   once they expire, so `LINK_CACHE_TTL_SECS`,
+//!   `LINK_CACHE_NEGATIVE_TTL_SECS` and `ORG_CACHE_TTL_SECS` bound how stale
+//!   a redirect can be.
+//!
+//! Click counting
+//! - Redirects don't write the click counter themselves: clicks and their
+//!   `ClickEvent`s are buffered and sent in batches (`domain::clicks`) to the
+//!   SQS queue at `CLICK_QUEUE_URL`, where `click-aggregator` applies them.
+//!   Without a queue, the function applies its own batches from a timer;
+//!   that is meant for local runs, and the SAM stack always sets the queue.
+//! - Buffered clicks are sent when the function shuts down (see `shutdown`);
+//!   `Clicks::from_env` lists the batching settings.
+//!
 //! Notes
-//! - This crate depends only on the `domain` and `aws-dynamo` adapter for data.
+//! - This crate depends only on the `domain` and `aws-dynamo` adapter for data,
+//!   plus `redis-cache` when lookups are shared through Redis and `aws-sqs`
+//!   when clicks go through a queue.
 //! - It initializes minimal `tracing` logging compatible with Lambda CloudWatch.
 
 use aws_dynamo::DynamoRepo;
+use aws_sqs::SqsClickQueue;
+use domain::adapters::memory_queue::MemoryClickQueue;
+use domain::cache::{CacheOptions, CachedDomainRepo, CachedLinkRepo};
+use domain::clicks::{BufferedClickSink, ClickAggregator, ClickQueue, ClickSink, ClickSinkOptions};
 use domain::service::LinkService;
 use domain::slug::Base62SlugGenerator;
-use domain::{Clock, Slug};
+use domain::tenant::OrganizationCache;
+use domain::{
+    ClickEvent, ClickRepository, Clock, CoreError, DomainRepository, LinkRepository, Organization,
+    Slug, TenantId, TenantScoped,
+};
 use http_common::lambda::resp;
-use lambda_http::{run, service_fn, Body, Error, Request, Response};
+use lambda_http::{run, service_fn, Body, Error, Request, RequestExt, Response};
 use qrcode::render::svg;
 use qrcode::QrCode;
 use std::sync::Arc;
+use std::time::Duration;
 use tracing::{error, info, warn};
+
+mod shutdown;
+
+/// How often buffered clicks are checked for a due batch.
+const CLICK_TICK: Duration = Duration::from_secs(1);
 use tracing_subscriber::{fmt, prelude::*, EnvFilter};
 
 /// Wrap a response with CORS headers (allow all origins for public endpoints)
@@ -37,7 +93,195 @@ fn with_cors(mut response: Response<Body>) -> Response<Body> {
 
 #[derive(Clone)]
 struct AppState {
-    svc: Arc<LinkService<DynamoRepo, Base62SlugGenerator, StdClock>>,
+    repo: DynamoRepo,
+    /// `repo` behind the link lookup cache, shared by all requests the
+    /// container serves.
+    links: CachedLinkRepo<DynamoRepo>,
+    /// `repo` behind the short domain lookup cache.
+    domains: CachedDomainRepo<DynamoRepo>,
+    /// Organizations by host, shared by all requests the container serves.
+    orgs: Arc<OrganizationCache>,
+    clicks: Arc<Clicks>,
+    /// Sign-in settings for workspace-only links; `None` when not configured.
+    session: Option<Arc<SessionConfig>>,
+}
+
+struct SessionConfig {
+    google_oauth_client_id: String,
+    allowed_domain: String,
+    secret: Vec<u8>,
+    ttl: std::time::Duration,
+}
+
+impl SessionConfig {
+    fn from_env() -> Option<Self> {
+        let non_empty = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        Some(Self {
+            google_oauth_client_id: non_empty("GOOGLE_OAUTH_CLIENT_ID")?,
+            allowed_domain: non_empty("ALLOWED_DOMAIN")?,
+            secret: non_empty("SESSION_SECRET")?.into_bytes(),
+            ttl: std::time::Duration::from_secs(
+                non_empty("SESSION_TTL_SECS")
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(12 * 60 * 60),
+            ),
+        })
+    }
+}
+
+/// Lookup cache settings from env: `LINK_CACHE_CAPACITY` (default 1024, 0
+/// turns the in-process layer off), `LINK_CACHE_TTL_SECS` (60) and
+/// `LINK_CACHE_NEGATIVE_TTL_SECS` (10). Short domains use them too.
+fn cache_options_from_env() -> CacheOptions {
+    let defaults = CacheOptions::default();
+    let var = |name: &str| std::env::var(name).ok()?.trim().parse::<u64>().ok();
+    CacheOptions {
+        capacity: var("LINK_CACHE_CAPACITY")
+            .and_then(|n| usize::try_from(n).ok())
+            .unwrap_or(defaults.capacity),
+        ttl: var("LINK_CACHE_TTL_SECS")
+            .map(std::time::Duration::from_secs)
+            .unwrap_or(defaults.ttl),
+        negative_ttl: var("LINK_CACHE_NEGATIVE_TTL_SECS")
+            .map(std::time::Duration::from_secs)
+            .unwrap_or(defaults.negative_ttl),
+    }
+}
+
+/// Link lookup cache with `options`, sharing entries through the
+/// Redis-compatible server at `LINK_CACHE_REDIS_URL` when set.
+fn link_cache_from_env(
+    repo: DynamoRepo,
+    options: CacheOptions,
+) -> Result<CachedLinkRepo<DynamoRepo>, CoreError> {
+    let cache = CachedLinkRepo::new(repo, options);
+    match std::env::var("LINK_CACHE_REDIS_URL") {
+        Ok(url) if !url.is_empty() => {
+            Ok(cache.with_backend(Arc::new(redis_cache::RedisLinkCache::new(&url)?)))
+        }
+        _ => Ok(cache),
+    }
+}
+
+/// Organization cache from env: `ORG_CACHE_TTL_SECS` (default 30).
+fn org_cache_from_env() -> OrganizationCache {
+    std::env::var("ORG_CACHE_TTL_SECS")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .map(|secs| OrganizationCache::new(Duration::from_secs(secs)))
+        .unwrap_or_default()
+}
+
+/// Click counting off the request path: redirects report to `sink`, which
+/// sends batches to the click queue. With an in-process queue, `local`
+/// applies them from the timer and on shutdown.
+struct Clicks<L = CachedLinkRepo<DynamoRepo>, C = DynamoRepo> {
+    sink: BufferedClickSink,
+    /// Whether `ClickEvent`s are kept besides the counts.
+    events: bool,
+    local: Option<ClickAggregator<L, C>>,
+}
+
+impl Clicks {
+    /// Send batches to the SQS queue at `CLICK_QUEUE_URL`, or keep them in
+    /// process without it. A batch goes out once `CLICK_BATCH_SIZE` clicks
+    /// and events (default 100) are buffered or the oldest has waited
+    /// `CLICK_FLUSH_SECS` (default 10). Click events are kept only when
+    /// `DYNAMO_TABLE_CLICKS` names a table for them.
+    fn from_env(links: &CachedLinkRepo<DynamoRepo>, repo: &DynamoRepo) -> Result<Self, CoreError> {
+        let defaults = ClickSinkOptions::default();
+        let var = |name: &str| std::env::var(name).ok()?.trim().parse::<u64>().ok();
+        let options = ClickSinkOptions {
+            max_batch: var("CLICK_BATCH_SIZE")
+                .and_then(|n| usize::try_from(n).ok())
+                .unwrap_or(defaults.max_batch),
+            max_delay: var("CLICK_FLUSH_SECS")
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.max_delay),
+        };
+        let events = std::env::var("DYNAMO_TABLE_CLICKS").is_ok_and(|t| !t.is_empty());
+        match std::env::var("CLICK_QUEUE_URL") {
+            Ok(url) if !url.is_empty() => Ok(Self {
+                sink: BufferedClickSink::new(Arc::new(SqsClickQueue::new(&url)?), options),
+                events,
+                local: None,
+            }),
+            _ => {
+                let queue: Arc<dyn ClickQueue> = Arc::new(MemoryClickQueue::new());
+                Ok(Self {
+                    sink: BufferedClickSink::new(Arc::clone(&queue), options),
+                    events,
+                    local: Some(ClickAggregator::new(queue, links.clone(), repo.clone())),
+                })
+            }
+        }
+    }
+}
+
+impl<L, C> Clicks<L, C>
+where
+    L: LinkRepository + TenantScoped,
+    C: ClickRepository + TenantScoped,
+{
+    /// Count a redirect. Failures are logged; the clicks stay buffered.
+    fn count(&self, tenant: &TenantId, event: ClickEvent) {
+        let slug = event.slug.clone();
+        let mut result = self.sink.increment(tenant, &slug);
+        if self.events {
+            result = result.and(self.sink.record(tenant, event));
+        }
+        if let Err(e) = result {
+            warn!(slug = %slug.as_str(), err = ?e, "click batch not sent");
+        }
+    }
+
+    /// Send a due batch and apply the in-process queue.
+    fn tick(&self) {
+        if let Err(e) = self.sink.flush_due() {
+            warn!(err = ?e, "click batch not sent");
+        }
+        self.apply_local();
+    }
+
+    /// Send everything buffered and apply the in-process queue.
+    fn shutdown(&self) {
+        if let Err(e) = self.sink.flush() {
+            let pending = self.sink.pending().unwrap_or_default();
+            error!(err = ?e, pending, "buffered clicks lost on shutdown");
+        }
+        self.apply_local();
+    }
+
+    fn apply_local(&self) {
+        if let Some(local) = &self.local {
+            if let Err(e) = local.drain(usize::MAX) {
+                warn!(err = ?e, "applying click batches failed");
+            }
+        }
+    }
+}
+
+/// Analytics event for a redirect of `req` to `slug`.
+fn click_event(req: &Request, slug: &Slug) -> ClickEvent {
+    let header = |name: &str| {
+        req.headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+    };
+    ClickEvent {
+        slug: slug.clone(),
+        clicked_at: std::time::SystemTime::now(),
+        user_agent: header("user-agent"),
+        referrer: header("referer"),
+        country: header("cloudfront-viewer-country"),
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct SessionReq {
+    credential: String,
 }
 
 #[derive(Clone)]
@@ -53,12 +297,38 @@ async fn main() -> Result<(), Error> {
     init_tracing();
     // Build repo from env; if it fails, crash early to surface misconfiguration.
     let repo = DynamoRepo::from_env().map_err(|e| format!("dynamo init error: {e}"))?;
+    let session = SessionConfig::from_env().map(Arc::new);
+    if session.is_none() {
+        warn!("workspace sign-in not configured; workspace-only links cannot be followed");
+    }
+    let cache_options = cache_options_from_env();
+    let domains = CachedDomainRepo::new(repo.clone(), cache_options.clone());
+    let links = link_cache_from_env(repo.clone(), cache_options)
+        .map_err(|e| format!("link cache init error: {e}"))?;
+    let clicks =
+        Clicks::from_env(&links, &repo).map_err(|e| format!("click counting init error: {e}"))?;
+    let clicks = Arc::new(clicks);
+
+    let ticker = Arc::clone(&clicks);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CLICK_TICK);
+        loop {
+            interval.tick().await;
+            ticker.tick();
+        }
+    });
+    let on_shutdown = Arc::clone(&clicks);
+    if let Err(e) = shutdown::on_shutdown(move || on_shutdown.shutdown()).await {
+        warn!(err = %e, "no shutdown hook; clicks buffered at shutdown are lost");
+    }
+
     let state = AppState {
-        svc: Arc::new(LinkService::new(
-            repo,
-            Base62SlugGenerator::new(1),
-            StdClock,
-        )),
+        repo,
+        links,
+        domains,
+        orgs: Arc::new(org_cache_from_env()),
+        clicks,
+        session,
     };
 
     let handler = service_fn(move |req: Request| {
@@ -85,11 +355,39 @@ enum RequestMode {
     CountdownRedirect { delay: u32 }, // Auto-redirect with countdown
 }
 
+/// Organization serving the request's host, or the fallback organization.
+fn organization_for(state: &AppState, req: &Request) -> Result<Organization, CoreError> {
+    let host = req.headers().get("host").and_then(|h| h.to_str().ok());
+    Ok(state
+        .orgs
+        .resolve(&state.repo, host, None)?
+        .unwrap_or_else(Organization::fallback))
+}
+
+/// Domain a session token must belong to: the token's own domain when the
+/// organization allows it, or `ALLOWED_DOMAIN` for organizations without
+/// sign-in domains.
+fn session_domain(cfg: &SessionConfig, org: &Organization, token: &str) -> Option<String> {
+    if org.auth_domains.is_empty() {
+        return Some(cfg.allowed_domain.clone());
+    }
+    google_auth::token_domain(token).filter(|d| org.allows_domain(d))
+}
+
 async fn handle_request(state: AppState, req: Request) -> Result<Response<Body>, Error> {
+    let org = match organization_for(&state, &req) {
+        Ok(o) => o,
+        Err(e) => {
+            error!(err = ?e, "organization lookup error");
+            return Ok(resp(500, None, Some(http_common::json_err("error"))));
+        }
+    };
     let raw_path = req.uri().path();
-    // API Gateway HTTP API includes stage prefix in rawPath (e.g., /dev/abc123)
-    // Strip the stage prefix if present by taking only the last path segment
-    let slug_str = raw_path.rsplit('/').next().unwrap_or("");
+    if req.method() == "POST" && raw_path.ends_with("/auth/session") {
+        return create_session(&state, &org, &req).await;
+    }
+    let (_, slug_path) = split_slug_path(&req);
+    let slug_str = slug_path.as_str();
 
     // Expect a non-empty slug
     if slug_str.is_empty() {
@@ -131,8 +429,24 @@ async fn handle_request(state: AppState, req: Request) -> Result<Response<Body>,
         .unwrap_or("example.com");
     let short_url = format!("https://{}/{}{}", host, slug.as_str(), qr_suffix);
 
+    // A host registered as a short domain resolves that domain's links
+    let domains = state.domains.for_tenant(&org.id);
+    let bare_host = host.split(':').next().unwrap_or(host).to_ascii_lowercase();
+    let slug = match domains.get_domain(&bare_host) {
+        Ok(Some(short_domain)) => slug.on_domain(Some(short_domain.host)),
+        Ok(None) => slug,
+        Err(e) => {
+            error!(err = ?e, host = %bare_host, "short domain lookup error");
+            let r = resp(500, None, Some(http_common::json_err("error")));
+            return Ok(if is_qr_request { with_cors(r) } else { r });
+        }
+    };
+
+    let links = state.links.for_tenant(&org.id);
+    let svc = LinkService::new(links, Base62SlugGenerator::new(1), StdClock);
+
     // Get the full link to check is_active, expiration, and other status
-    let response = match state.svc.get(&slug) {
+    let response = match svc.get(&slug) {
         Ok(Some(link)) => {
             let now = std::time::SystemTime::now();
 
@@ -155,6 +469,10 @@ async fn handle_request(state: AppState, req: Request) -> Result<Response<Body>,
             else if !link.is_active {
                 warn!(slug = %slug.as_str(), "link inactive");
                 resp(404, None, Some(http_common::json_err("not_found")))
+            }
+            // Workspace-only links need a signed-in visitor (QR codes only encode the short URL)
+            else if let Some(login) = workspace_gate(&state, &org, &req, &link, is_qr_request) {
+                login
             } else {
                 // Determine actual mode - check if link has redirect_delay
                 let actual_mode = match mode {
@@ -187,12 +505,19 @@ async fn handle_request(state: AppState, req: Request) -> Result<Response<Body>,
                         render_countdown_page(&link, &short_url, delay)
                     }
                     RequestMode::Redirect => {
-                        // Fire-and-forget click increment (don't fail redirect on counter error)
-                        if let Err(e) = state.svc.increment_click(&slug) {
-                            warn!(slug = %slug.as_str(), err = ?e, "click increment failed");
-                        }
+                        // Counted in batches; a failure never fails the redirect
+                        state.clicks.count(&org.id, click_event(&req, &link.slug));
                         info!(slug = %slug.as_str(), redirect_to = %link.original_url, "resolve ok");
-                        resp(308, Some(("Location", link.original_url)), None)
+                        let private = link.requires_login();
+                        let mut r = resp(308, Some(("Location", link.original_url)), None);
+                        if private {
+                            // Keep shared caches from serving an internal destination
+                            r.headers_mut().insert(
+                                lambda_http::http::header::CACHE_CONTROL,
+                                lambda_http::http::HeaderValue::from_static("private, no-store"),
+                            );
+                        }
+                        r
                     }
                 }
             }
@@ -215,6 +540,157 @@ async fn handle_request(state: AppState, req: Request) -> Result<Response<Body>,
     })
 }
 
+/// Split the request path into the API stage prefix (e.g. `/dev`) and the slug
+/// path after it, suffixes included. API Gateway HTTP API keeps the stage in
+/// rawPath but passes the greedy `{slug+}` parameter without it; without the
+/// parameter the whole path is the slug.
+fn split_slug_path(req: &Request) -> (String, String) {
+    let raw_path = req.uri().path();
+    let slug_path = req
+        .path_parameters_ref()
+        .and_then(|p| p.first("slug"))
+        .map(str::to_string)
+        .unwrap_or_else(|| raw_path.trim_start_matches('/').to_string());
+    let prefix = raw_path
+        .strip_suffix(slug_path.as_str())
+        .map(|p| p.trim_end_matches('/').to_string())
+        .unwrap_or_default();
+    (prefix, slug_path)
+}
+
+/// Returns a sign-in page when `link` is workspace-only and the visitor has no valid session.
+fn workspace_gate(
+    state: &AppState,
+    org: &Organization,
+    req: &Request,
+    link: &domain::ShortLink,
+    is_qr_request: bool,
+) -> Option<Response<Body>> {
+    if !link.requires_login() || is_qr_request {
+        return None;
+    }
+    let (prefix, _) = split_slug_path(req);
+    let session_path = format!("{}/auth/session", prefix);
+
+    let Some(cfg) = state.session.as_deref() else {
+        warn!(slug = %link.slug.as_str(), "workspace link but sign-in not configured");
+        return Some(render_login_page(
+            503,
+            None,
+            &session_path,
+            Some("Sign-in is not configured for this service."),
+        ));
+    };
+
+    let cookie = req.headers().get("cookie").and_then(|v| v.to_str().ok());
+    let message = match http_common::get_cookie(cookie, http_common::SESSION_COOKIE) {
+        None => None,
+        Some(token) => {
+            let verified = match session_domain(cfg, org, &token) {
+                Some(domain) => google_auth::verify_session(&token, &cfg.secret, &domain),
+                None => Err(google_auth::AuthError::DomainNotAllowed),
+            };
+            match verified {
+                Ok(user) => {
+                    info!(slug = %link.slug.as_str(), user = %user.email, "workspace visitor ok");
+                    return None;
+                }
+                Err(google_auth::AuthError::DomainNotAllowed) => {
+                    Some("Your account is not part of this workspace.")
+                }
+                Err(e) => {
+                    warn!(err = ?e, "invalid session cookie");
+                    None
+                }
+            }
+        }
+    };
+    Some(render_login_page(
+        401,
+        Some(&cfg.google_oauth_client_id),
+        &session_path,
+        message,
+    ))
+}
+
+/// `POST /auth/session` — exchange a Google ID token for a session cookie.
+async fn create_session(
+    state: &AppState,
+    org: &Organization,
+    req: &Request,
+) -> Result<Response<Body>, Error> {
+    let Some(cfg) = state.session.as_deref() else {
+        return Ok(resp(
+            503,
+            None,
+            Some(http_common::json_err("not_configured")),
+        ));
+    };
+    let body_str = match req.body() {
+        Body::Text(s) => s.clone(),
+        Body::Binary(b) => String::from_utf8(b.clone()).unwrap_or_default(),
+        _ => String::new(),
+    };
+    let payload: SessionReq = match serde_json::from_str(&body_str) {
+        Ok(p) => p,
+        Err(_) => return Ok(resp(400, None, Some(http_common::json_err("bad_request")))),
+    };
+
+    let Some(domain) = session_domain(cfg, org, &payload.credential) else {
+        warn!(tenant = %org.id.as_str(), "session rejected: domain not allowed");
+        return Ok(resp(403, None, Some(http_common::json_err("forbidden"))));
+    };
+    let user =
+        match google_auth::verify_async(&payload.credential, &cfg.google_oauth_client_id, &domain)
+            .await
+        {
+            Ok(u) => u,
+            Err(google_auth::AuthError::DomainNotAllowed) => {
+                warn!("session rejected: domain not allowed");
+                return Ok(resp(403, None, Some(http_common::json_err("forbidden"))));
+            }
+            Err(e) => {
+                warn!(err = ?e, "session rejected");
+                return Ok(resp(401, None, Some(http_common::json_err("unauthorized"))));
+            }
+        };
+    match google_auth::issue_session(&user, &cfg.secret, cfg.ttl) {
+        Ok(token) => {
+            info!(user = %user.email, "session issued");
+            Ok(resp(
+                204,
+                Some((
+                    "Set-Cookie",
+                    http_common::session_cookie(&token, cfg.ttl.as_secs()),
+                )),
+                None,
+            ))
+        }
+        Err(e) => {
+            error!(err = ?e, "session issue failed");
+            Ok(resp(500, None, Some(http_common::json_err("error"))))
+        }
+    }
+}
+
+fn render_login_page(
+    status: u16,
+    google_client_id: Option<&str>,
+    session_path: &str,
+    message: Option<&str>,
+) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header("Content-Type", "text/html; charset=utf-8")
+        .header("Cache-Control", "no-store")
+        .body(Body::from(http_common::login_page_html(
+            google_client_id,
+            session_path,
+            message,
+        )))
+        .expect("response build")
+}
+
 fn render_qr_code(url: &str) -> Response<Body> {
     match QrCode::new(url.as_bytes()) {
         Ok(code) => {
@@ -683,3 +1159,82 @@ fn render_countdown_page(
 
 // Note: Response builders (resp) and JSON helpers (json_err) are now provided
 // by the http-common crate.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use domain::adapters::memory_repo::{InMemoryClickRepo, InMemoryRepo};
+    use domain::{ShortLink, UserEmail};
+    use std::time::UNIX_EPOCH;
+
+    fn docs() -> Slug {
+        Slug::new("docs").unwrap()
+    }
+
+    fn event() -> ClickEvent {
+        ClickEvent {
+            slug: docs(),
+            clicked_at: UNIX_EPOCH,
+            user_agent: None,
+            referrer: None,
+            country: None,
+        }
+    }
+
+    /// Sink holding clicks until it is flushed.
+    fn sink(queue: Arc<dyn ClickQueue>) -> BufferedClickSink {
+        let options = ClickSinkOptions {
+            max_batch: 100,
+            max_delay: Duration::from_secs(3600),
+        };
+        BufferedClickSink::new(queue, options)
+    }
+
+    #[test]
+    fn shutdown_sends_buffered_clicks_to_the_queue() {
+        let queue = Arc::new(MemoryClickQueue::new());
+        let clicks: Clicks<InMemoryRepo, InMemoryClickRepo> = Clicks {
+            sink: sink(queue.clone()),
+            events: true,
+            local: None,
+        };
+        let tenant = TenantId::default();
+        clicks.count(&tenant, event());
+        clicks.count(&tenant, event());
+        clicks.tick();
+        assert!(queue.is_empty(), "not due yet");
+
+        clicks.shutdown();
+        let received = queue.receive(10).unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].batch.counts[0].clicks, 2);
+        assert_eq!(received[0].batch.events.len(), 2);
+    }
+
+    #[test]
+    fn shutdown_applies_clicks_kept_in_process() {
+        let queue: Arc<dyn ClickQueue> = Arc::new(MemoryClickQueue::new());
+        let links = InMemoryRepo::new();
+        let link = ShortLink::new(
+            docs(),
+            "https://example.com".into(),
+            UNIX_EPOCH,
+            UserEmail::new("a@example.com").unwrap(),
+        );
+        links.put(link).unwrap();
+        let tenant = TenantId::default();
+        let clicks = Clicks {
+            sink: sink(Arc::clone(&queue)),
+            events: false,
+            local: Some(ClickAggregator::new(
+                queue,
+                links.for_tenant(&tenant),
+                InMemoryClickRepo::new(),
+            )),
+        };
+        clicks.count(&tenant, event());
+        clicks.shutdown();
+
+        assert_eq!(links.get(&docs()).unwrap().unwrap().click_count, 1);
+    }
+}
@@ -0,0 +1,79 @@
+//! Run a hook before Lambda shuts the execution environment down.
+//!
+//! Lambda only sends the runtime `SIGTERM` before a shutdown when an extension
+//! is registered, so this registers an internal extension subscribed to no
+//! events and waits for the signal. The hook gets about 500ms before the
+//! process is killed.
+
+use std::time::Duration;
+
+use lambda_http::Error;
+use lambda_runtime_api_client::{body::Body, build_request, Client};
+use tokio::signal::unix::{signal, SignalKind};
+use tracing::{info, warn};
+
+const EXTENSION_NAME: &str = "lambda-redirect-shutdown-hook";
+
+/// Longest init waits for the Extensions API.
+const REGISTER_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Register the extension and spawn the task running `hook` on `SIGTERM`,
+/// then exiting. Call it before `run`: Lambda accepts extensions only while
+/// the function initializes.
+pub async fn on_shutdown(hook: impl FnOnce() + Send + 'static) -> Result<(), Error> {
+    let api = std::env::var("AWS_LAMBDA_RUNTIME_API").map_err(|_| "not running in Lambda")?;
+    let mut terminate = signal(SignalKind::terminate())?;
+    let client = Client::builder()
+        .with_endpoint(format!("http://{api}").parse()?)
+        .build()?;
+
+    let register = build_request()
+        .method("POST")
+        .uri("/2020-01-01/extension/register")
+        .header("Lambda-Extension-Name", EXTENSION_NAME)
+        .body(Body::from(r#"{"events":[]}"#))?;
+    let registered = tokio::time::timeout(REGISTER_TIMEOUT, client.call(register)).await??;
+    if !registered.status().is_success() {
+        return Err(format!("extension registration failed: {}", registered.status()).into());
+    }
+    let id = registered
+        .headers()
+        .get("Lambda-Extension-Identifier")
+        .cloned()
+        .ok_or("extension registration returned no identifier")?;
+
+    // Init completes once every extension has asked for its next event;
+    // subscribed to none, the request stays open until shutdown.
+    tokio::spawn(async move {
+        loop {
+            let next = build_request()
+                .method("GET")
+                .uri("/2020-01-01/extension/event/next")
+                .header("Lambda-Extension-Identifier", id.clone())
+                .body(Body::empty());
+            let result = match next {
+                Ok(req) => client.call(req).await.map(|r| r.status()),
+                Err(e) => Err(e.into()),
+            };
+            match result {
+                Ok(status) if status.is_success() => {}
+                Ok(status) => {
+                    warn!(%status, "extension event request failed");
+                    break;
+                }
+                Err(e) => {
+                    warn!(err = %e, "extension event request failed");
+                    break;
+                }
+            }
+        }
+    });
+
+    tokio::spawn(async move {
+        terminate.recv().await;
+        info!("shutting down");
+        hook();
+        std::process::exit(0);
+    });
+    Ok(())
+}
@@ -0,0 +1,75 @@
+//! Behavior every storage adapter must share, run from each adapter's tests
+//! against a fresh store of its own.
+
+use std::time::UNIX_EPOCH;
+
+use crate::{
+    GroupLinkDisposition, GroupRepository, GroupRole, GroupSettings, LinkGrant,
+    LinkGrantRepository, LinkGroup, LinkRepository, ShortLink, Slug, UserEmail,
+};
+
+/// Deleting links, one at a time, in bulk or with their group, drops their
+/// collaborator grants, so a restored or reused slug doesn't bring them back.
+pub fn deleting_links_drops_their_grants<L, G, R>(links: &L, grants: &G, groups: &R)
+where
+    L: LinkRepository,
+    G: LinkGrantRepository,
+    R: GroupRepository,
+{
+    let slug = |s: &str| Slug::new(s).unwrap();
+    let owner = UserEmail::new("owner@example.com").unwrap();
+    let bob = UserEmail::new("bob@example.com").unwrap();
+    groups
+        .create_group(LinkGroup {
+            id: "grp_grants".into(),
+            name: "Grants".into(),
+            description: None,
+            created_at: UNIX_EPOCH,
+            created_by: owner.clone(),
+            settings: GroupSettings::default(),
+            parent_id: None,
+        })
+        .unwrap();
+    for (name, group) in [
+        ("single", None),
+        ("bulk-a", None),
+        ("bulk-b", None),
+        ("grouped", Some("grp_grants")),
+        ("kept", None),
+    ] {
+        let mut link = ShortLink::new(
+            slug(name),
+            "https://example.com".into(),
+            UNIX_EPOCH,
+            owner.clone(),
+        );
+        link.group_id = group.map(str::to_string);
+        links.put(link).unwrap();
+        grants
+            .put_grant(LinkGrant {
+                slug: slug(name),
+                user_email: bob.clone(),
+                role: GroupRole::Viewer,
+                granted_at: UNIX_EPOCH,
+                granted_by: owner.clone(),
+            })
+            .unwrap();
+    }
+
+    links.delete(&slug("single"), UNIX_EPOCH).unwrap();
+    let deleted = links
+        .bulk_delete(&[slug("bulk-a"), slug("bulk-b")], UNIX_EPOCH)
+        .unwrap();
+    assert_eq!(deleted, 2);
+    groups
+        .delete_group("grp_grants", &GroupLinkDisposition::SoftDelete, UNIX_EPOCH)
+        .unwrap();
+
+    let left: Vec<Slug> = grants
+        .list_grants_for_user(&bob)
+        .unwrap()
+        .into_iter()
+        .map(|g| g.slug)
+        .collect();
+    assert_eq!(left, vec![slug("kept")]);
+}
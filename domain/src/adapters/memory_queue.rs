@@ -0,0 +1,159 @@
+//! In-process [`ClickQueue`].
+//!
+//! Batches never leave the process, so the sink and the aggregator must share
+//! one queue, e.g. a server applying its own clicks from a background task.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::clicks::{ClickBatch, ClickQueue, QueuedBatch};
+use crate::CoreError;
+
+/// Queue holding batches in memory, with SQS-like receive semantics.
+pub struct MemoryClickQueue {
+    state: Mutex<State>,
+    visibility_timeout: Duration,
+}
+
+#[derive(Default)]
+struct State {
+    waiting: VecDeque<ClickBatch>,
+    /// Received batches by receipt, with the time they become visible again.
+    in_flight: HashMap<String, (Instant, ClickBatch)>,
+    next_receipt: u64,
+}
+
+impl MemoryClickQueue {
+    /// Empty queue; received batches come back after 30 seconds unless
+    /// acknowledged.
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(State::default()),
+            visibility_timeout: Duration::from_secs(30),
+        }
+    }
+
+    pub fn with_visibility_timeout(mut self, timeout: Duration) -> Self {
+        self.visibility_timeout = timeout;
+        self
+    }
+
+    /// Batches waiting or received but not acknowledged.
+    pub fn len(&self) -> usize {
+        self.state()
+            .map(|s| s.waiting.len() + s.in_flight.len())
+            .unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn state(&self) -> Result<MutexGuard<'_, State>, CoreError> {
+        self.state
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))
+    }
+}
+
+impl Default for MemoryClickQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClickQueue for MemoryClickQueue {
+    fn send(&self, batch: &ClickBatch) -> Result<(), CoreError> {
+        self.state()?.waiting.push_back(batch.clone());
+        Ok(())
+    }
+
+    fn receive(&self, max: usize) -> Result<Vec<QueuedBatch>, CoreError> {
+        let now = Instant::now();
+        let mut state = self.state()?;
+        let expired: Vec<String> = state
+            .in_flight
+            .iter()
+            .filter(|(_, (visible_at, _))| *visible_at <= now)
+            .map(|(receipt, _)| receipt.clone())
+            .collect();
+        for receipt in expired {
+            if let Some((_, batch)) = state.in_flight.remove(&receipt) {
+                state.waiting.push_back(batch);
+            }
+        }
+
+        let mut received = Vec::new();
+        while received.len() < max {
+            let Some(batch) = state.waiting.pop_front() else {
+                break;
+            };
+            state.next_receipt += 1;
+            let receipt = state.next_receipt.to_string();
+            state.in_flight.insert(
+                receipt.clone(),
+                (now + self.visibility_timeout, batch.clone()),
+            );
+            received.push(QueuedBatch { receipt, batch });
+        }
+        Ok(received)
+    }
+
+    fn ack(&self, receipts: &[String]) -> Result<(), CoreError> {
+        let mut state = self.state()?;
+        for receipt in receipts {
+            state.in_flight.remove(receipt);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clicks::ClickCount;
+    use crate::{Slug, TenantId};
+
+    fn batch(clicks: u64) -> ClickBatch {
+        ClickBatch {
+            counts: vec![ClickCount {
+                tenant: TenantId::default(),
+                slug: Slug::new("docs").unwrap(),
+                clicks,
+            }],
+            events: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn received_batches_return_unless_acknowledged() {
+        let queue = MemoryClickQueue::new().with_visibility_timeout(Duration::ZERO);
+        queue.send(&batch(1)).unwrap();
+        queue.send(&batch(2)).unwrap();
+
+        let first = queue.receive(1).unwrap();
+        assert_eq!(first[0].batch, batch(1));
+        queue.ack(&[first[0].receipt.clone()]).unwrap();
+
+        let second = queue.receive(10).unwrap();
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].batch, batch(2));
+        let again = queue.receive(10).unwrap();
+        assert_eq!(again[0].batch, batch(2), "not acknowledged, so redelivered");
+        assert_ne!(again[0].receipt, second[0].receipt);
+
+        queue.ack(&[again[0].receipt.clone()]).unwrap();
+        assert!(queue.is_empty());
+        assert!(queue.receive(10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn received_batches_stay_hidden_until_the_timeout() {
+        let queue = MemoryClickQueue::new();
+        queue.send(&batch(1)).unwrap();
+        assert_eq!(queue.receive(10).unwrap().len(), 1);
+        assert!(queue.receive(10).unwrap().is_empty());
+        assert_eq!(queue.len(), 1);
+    }
+}
@@ -1,44 +1,163 @@
-use std::collections::BTreeMap;
-use std::sync::Mutex;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex, MutexGuard};
 use std::time::SystemTime;
 
+use crate::validate::url_host;
 use crate::{
-    AuditEntry, AuditRepository, ClickEvent, ClickRepository, CoreError, GroupMember,
-    GroupRepository, GroupRole, LinkGroup, LinkRepository, ListOptions, ListResult, ShortLink,
-    Slug, UserEmail,
+    hierarchy, AuditEntry, AuditRepository, ClickEvent, ClickRepository, CoreError,
+    DomainRepository, GroupInvitation, GroupLinkDisposition, GroupMember, GroupRepository,
+    GroupRole, InvitationRepository, InvitationStatus, LinkGrant, LinkGrantRepository, LinkGroup,
+    LinkRepository, ListOptions, ListResult, Namespace, NamespaceRepository, Organization,
+    OrganizationRepository, ShortDomain, ShortLink, Slug, TargetMatch, TenantId, TenantScoped,
+    UserEmail,
 };
 
+/// Storage shared by every tenant-scoped handle of an in-memory repository,
+/// split into one partition per tenant.
+struct Partitioned<T> {
+    parts: Arc<Mutex<BTreeMap<TenantId, T>>>,
+    tenant: TenantId,
+}
+
+impl<T: Default> Partitioned<T> {
+    fn new() -> Self {
+        Self {
+            parts: Arc::new(Mutex::new(BTreeMap::new())),
+            tenant: TenantId::default(),
+        }
+    }
+
+    fn scoped(&self, tenant: &TenantId) -> Self {
+        Self {
+            parts: Arc::clone(&self.parts),
+            tenant: tenant.clone(),
+        }
+    }
+
+    /// Lock the store and return this handle's partition.
+    fn lock(&self) -> Result<Partition<'_, T>, CoreError> {
+        let mut guard = self
+            .parts
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        guard.entry(self.tenant.clone()).or_default();
+        Ok(Partition {
+            guard,
+            tenant: &self.tenant,
+        })
+    }
+}
+
+struct Partition<'a, T> {
+    guard: MutexGuard<'a, BTreeMap<TenantId, T>>,
+    tenant: &'a TenantId,
+}
+
+impl<T> Deref for Partition<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard[self.tenant]
+    }
+}
+
+impl<T> DerefMut for Partition<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.guard
+            .get_mut(self.tenant)
+            .expect("partition is created when locking")
+    }
+}
+
 /// Simple in-memory repository for tests. Not thread-safe for high concurrency
 /// beyond the internal mutex guarding the map.
+///
+/// Methods touching several maps lock `aliases`, then `inner`, then `targets`,
+/// then `grants`.
 pub struct InMemoryRepo {
-    inner: Mutex<BTreeMap<String, ShortLink>>,
+    inner: Partitioned<BTreeMap<String, ShortLink>>,
+    /// Alias key -> key of the link it points at.
+    aliases: Partitioned<BTreeMap<String, String>>,
+    /// Target key -> keys of the links pointing at it.
+    targets: Partitioned<BTreeMap<String, BTreeSet<String>>>,
+    /// Per-link grants, dropped when a link is deleted and moved when it is
+    /// renamed. See [`InMemoryRepo::grants`].
+    grants: Partitioned<Vec<LinkGrant>>,
 }
 
 /// In-memory group repository for tests.
+///
+/// Group deletion applies its link disposition to the link store the repo
+/// was built with: a private one from [`InMemoryGroupRepo::new`], or a
+/// shared one from [`InMemoryGroupRepo::with_links`] or [`InMemoryRepo::groups`].
 pub struct InMemoryGroupRepo {
-    groups: Mutex<BTreeMap<String, LinkGroup>>,
-    members: Mutex<Vec<GroupMember>>,
+    groups: Partitioned<BTreeMap<String, LinkGroup>>,
+    members: Partitioned<Vec<GroupMember>>,
+    links: Arc<InMemoryRepo>,
+}
+
+/// In-memory group invitation repository for tests.
+pub struct InMemoryInvitationRepo {
+    invitations: Partitioned<BTreeMap<String, GroupInvitation>>,
+}
+
+/// In-memory per-link grant repository for tests.
+pub struct InMemoryLinkGrantRepo {
+    grants: Partitioned<Vec<LinkGrant>>,
 }
 
 /// In-memory click repository for tests.
 pub struct InMemoryClickRepo {
-    clicks: Mutex<Vec<ClickEvent>>,
+    clicks: Partitioned<Vec<ClickEvent>>,
 }
 
 /// In-memory audit repository for tests.
 pub struct InMemoryAuditRepo {
-    entries: Mutex<Vec<AuditEntry>>,
+    entries: Partitioned<Vec<AuditEntry>>,
+}
+
+/// In-memory short domain repository for tests.
+pub struct InMemoryDomainRepo {
+    domains: Partitioned<BTreeMap<String, ShortDomain>>,
+}
+
+/// In-memory slug namespace repository for tests.
+pub struct InMemoryNamespaceRepo {
+    namespaces: Partitioned<BTreeMap<String, Namespace>>,
+}
+
+/// In-memory organization repository for tests. Organizations are global.
+pub struct InMemoryOrganizationRepo {
+    orgs: Mutex<BTreeMap<TenantId, Organization>>,
 }
 
 impl InMemoryRepo {
     pub fn new() -> Self {
         Self {
-            inner: Mutex::new(BTreeMap::new()),
+            inner: Partitioned::new(),
+            aliases: Partitioned::new(),
+            targets: Partitioned::new(),
+            grants: Partitioned::new(),
         }
     }
 
+    /// Grant repository on this repository's grant store, so grants follow
+    /// their links through `delete` and `rename`.
+    pub fn grants(&self) -> InMemoryLinkGrantRepo {
+        InMemoryLinkGrantRepo {
+            grants: self.grants.scoped(&self.grants.tenant),
+        }
+    }
+
+    /// Group repository on this repository's link store, so `delete_group`
+    /// reassigns, detaches or soft-deletes these links.
+    pub fn groups(&self) -> InMemoryGroupRepo {
+        InMemoryGroupRepo::with_links(Arc::new(self.for_tenant(self.tenant())))
+    }
+
     fn key(slug: &Slug) -> String {
-        slug.as_str().to_string()
+        slug.key().into_owned()
     }
 }
 
@@ -48,24 +167,57 @@ impl Default for InMemoryRepo {
     }
 }
 
+impl TenantScoped for InMemoryRepo {
+    fn for_tenant(&self, tenant: &TenantId) -> Self {
+        Self {
+            inner: self.inner.scoped(tenant),
+            aliases: self.aliases.scoped(tenant),
+            targets: self.targets.scoped(tenant),
+            grants: self.grants.scoped(tenant),
+        }
+    }
+
+    fn tenant(&self) -> &TenantId {
+        &self.inner.tenant
+    }
+}
+
 impl LinkRepository for InMemoryRepo {
     fn get(&self, slug: &Slug) -> Result<Option<ShortLink>, CoreError> {
+        let aliases = self
+            .aliases
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
         let map = self
             .inner
             .lock()
             .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
-        Ok(map.get(&Self::key(slug)).cloned())
+        let key = Self::key(slug);
+        let key = aliases.get(&key).unwrap_or(&key);
+        Ok(map.get(key).cloned())
     }
 
     fn put(&self, link: ShortLink) -> Result<(), CoreError> {
+        let aliases = self
+            .aliases
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
         let mut map = self
             .inner
             .lock()
             .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let mut targets = self
+            .targets
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
         let key = Self::key(&link.slug);
-        if map.contains_key(&key) {
+        if map.contains_key(&key) || aliases.contains_key(&key) {
             return Err(CoreError::AlreadyExists);
         }
+        targets
+            .entry(link.target_key())
+            .or_default()
+            .insert(key.clone());
         map.insert(key, link);
         Ok(())
     }
@@ -88,11 +240,23 @@ impl LinkRepository for InMemoryRepo {
             .inner
             .lock()
             .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let mut targets = self
+            .targets
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
         let key = Self::key(&link.slug);
-        if !map.contains_key(&key) {
-            return Err(CoreError::NotFound);
+        let old_target = map.get(&key).ok_or(CoreError::NotFound)?.target_key();
+        let new_target = link.target_key();
+        map.insert(key.clone(), link.clone());
+        if old_target != new_target {
+            if let Some(keys) = targets.get_mut(&old_target) {
+                keys.remove(&key);
+                if keys.is_empty() {
+                    targets.remove(&old_target);
+                }
+            }
+            targets.entry(new_target).or_default().insert(key);
         }
-        map.insert(key, link.clone());
         Ok(())
     }
 
@@ -133,10 +297,15 @@ impl LinkRepository for InMemoryRepo {
             .inner
             .lock()
             .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let mut grants = self
+            .grants
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
         let key = Self::key(slug);
         match map.get_mut(&key) {
             Some(link) => {
                 link.deleted_at = Some(deleted_at);
+                grants.retain(|g| &g.slug != slug);
                 Ok(())
             }
             None => Err(CoreError::NotFound),
@@ -170,59 +339,12 @@ impl LinkRepository for InMemoryRepo {
             .inner
             .lock()
             .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
-        let mut items: Vec<_> = map
+        let items = map
             .values()
-            .filter(|link| {
-                // Filter deleted
-                if !options.include_deleted && link.deleted_at.is_some() {
-                    return false;
-                }
-                // Filter by creator
-                if let Some(ref email) = options.created_by {
-                    if link.created_by.as_str() != email.as_str() {
-                        return false;
-                    }
-                }
-                // Filter by group
-                if let Some(ref gid) = options.group_id {
-                    if link.group_id.as_ref() != Some(gid) {
-                        return false;
-                    }
-                }
-                // Filter by search
-                if let Some(ref q) = options.search {
-                    let ql = q.to_lowercase();
-                    if !link.slug.as_str().to_lowercase().contains(&ql)
-                        && !link.original_url.to_lowercase().contains(&ql)
-                        && !link
-                            .description
-                            .as_ref()
-                            .is_some_and(|d| d.to_lowercase().contains(&ql))
-                    {
-                        return false;
-                    }
-                }
-                true
-            })
+            .filter(|link| options.matches(link))
             .cloned()
             .collect();
-
-        // Sort by created_at desc
-        items.sort_by(|a, b| b.created_at.cmp(&a.created_at));
-
-        let total = items.len();
-        let has_more = options.offset + options.limit < total;
-        let items: Vec<_> = items
-            .into_iter()
-            .skip(options.offset)
-            .take(options.limit)
-            .collect();
-
-        Ok(ListResult {
-            items,
-            total,
-            has_more,
-        })
+        options.page(items)
     }
 
     fn list_by_group(&self, group_id: &str, limit: usize) -> Result<Vec<ShortLink>, CoreError> {
@@ -240,16 +362,62 @@ impl LinkRepository for InMemoryRepo {
             .collect())
     }
 
+    fn list_by_target(
+        &self,
+        key: &str,
+        mode: TargetMatch,
+        limit: usize,
+    ) -> Result<Vec<ShortLink>, CoreError> {
+        let map = self
+            .inner
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let targets = self
+            .targets
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        // Targets are ordered, so prefixes are a range; hosts need every target
+        let matching: Box<dyn Iterator<Item = &BTreeSet<String>>> = match mode {
+            TargetMatch::Exact => Box::new(targets.get(key).into_iter()),
+            TargetMatch::Prefix => Box::new(
+                targets
+                    .range(key.to_string()..)
+                    .take_while(|(target, _)| target.starts_with(key))
+                    .map(|(_, keys)| keys),
+            ),
+            TargetMatch::Host => Box::new(
+                targets
+                    .iter()
+                    .filter(|(target, _)| url_host(target).as_deref() == Some(key))
+                    .map(|(_, keys)| keys),
+            ),
+        };
+        let mut links: Vec<ShortLink> = matching
+            .flatten()
+            .filter_map(|key| map.get(key))
+            .filter(|link| link.deleted_at.is_none())
+            .cloned()
+            .collect();
+        links.sort_by_key(|link| link.created_at);
+        links.truncate(limit);
+        Ok(links)
+    }
+
     fn bulk_delete(&self, slugs: &[Slug], deleted_at: SystemTime) -> Result<usize, CoreError> {
         let mut map = self
             .inner
             .lock()
             .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let mut grants = self
+            .grants
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
         let mut count = 0;
         for slug in slugs {
             let key = Self::key(slug);
             if let Some(link) = map.get_mut(&key) {
                 link.deleted_at = Some(deleted_at);
+                grants.retain(|g| &g.slug != slug);
                 count += 1;
             }
         }
@@ -277,6 +445,89 @@ impl LinkRepository for InMemoryRepo {
         }
         Ok(count)
     }
+
+    fn add_alias(&self, alias: &Slug, target: &Slug) -> Result<(), CoreError> {
+        let mut aliases = self
+            .aliases
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let map = self
+            .inner
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let key = Self::key(alias);
+        if map.contains_key(&key) || aliases.contains_key(&key) {
+            return Err(CoreError::AlreadyExists);
+        }
+        let target = Self::key(target);
+        if !map.contains_key(&target) {
+            return Err(CoreError::NotFound);
+        }
+        aliases.insert(key, target);
+        Ok(())
+    }
+
+    fn remove_alias(&self, alias: &Slug) -> Result<(), CoreError> {
+        let mut aliases = self
+            .aliases
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        match aliases.remove(&Self::key(alias)) {
+            Some(_) => Ok(()),
+            None => Err(CoreError::NotFound),
+        }
+    }
+
+    fn list_aliases(&self, target: &Slug) -> Result<Vec<Slug>, CoreError> {
+        let aliases = self
+            .aliases
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let target = Self::key(target);
+        aliases
+            .iter()
+            .filter(|(_, t)| **t == target)
+            .map(|(alias, _)| Slug::from_key(alias))
+            .collect()
+    }
+
+    fn rename(&self, old: &Slug, new: &Slug) -> Result<ShortLink, CoreError> {
+        let mut aliases = self
+            .aliases
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let mut map = self
+            .inner
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let (old_key, new_key) = (Self::key(old), Self::key(new));
+        if map.contains_key(&new_key) || aliases.contains_key(&new_key) {
+            return Err(CoreError::AlreadyExists);
+        }
+        let mut targets = self
+            .targets
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let mut link = map.remove(&old_key).ok_or(CoreError::NotFound)?;
+        link.slug = new.clone();
+        map.insert(new_key.clone(), link.clone());
+        if let Some(keys) = targets.get_mut(&link.target_key()) {
+            keys.remove(&old_key);
+            keys.insert(new_key.clone());
+        }
+        for target in aliases.values_mut().filter(|t| **t == old_key) {
+            *target = new_key.clone();
+        }
+        aliases.insert(old_key, new_key);
+        let mut grants = self
+            .grants
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        for grant in grants.iter_mut().filter(|g| &g.slug == old) {
+            grant.slug = new.clone();
+        }
+        Ok(link)
+    }
 }
 
 // ============ InMemoryGroupRepo ============
@@ -284,8 +535,17 @@ impl LinkRepository for InMemoryRepo {
 impl InMemoryGroupRepo {
     pub fn new() -> Self {
         Self {
-            groups: Mutex::new(BTreeMap::new()),
-            members: Mutex::new(Vec::new()),
+            groups: Partitioned::new(),
+            members: Partitioned::new(),
+            links: Arc::new(InMemoryRepo::new()),
+        }
+    }
+
+    /// Share a link store so `delete_group` applies its link disposition to it.
+    pub fn with_links(links: Arc<InMemoryRepo>) -> Self {
+        Self {
+            links,
+            ..Self::new()
         }
     }
 }
@@ -296,6 +556,20 @@ impl Default for InMemoryGroupRepo {
     }
 }
 
+impl TenantScoped for InMemoryGroupRepo {
+    fn for_tenant(&self, tenant: &TenantId) -> Self {
+        Self {
+            groups: self.groups.scoped(tenant),
+            members: self.members.scoped(tenant),
+            links: Arc::new(self.links.for_tenant(tenant)),
+        }
+    }
+
+    fn tenant(&self) -> &TenantId {
+        &self.groups.tenant
+    }
+}
+
 impl GroupRepository for InMemoryGroupRepo {
     fn create_group(&self, group: LinkGroup) -> Result<(), CoreError> {
         let mut groups = self
@@ -355,7 +629,24 @@ impl GroupRepository for InMemoryGroupRepo {
         Ok(())
     }
 
-    fn delete_group(&self, id: &str) -> Result<(), CoreError> {
+    fn list_child_groups(&self, parent_id: &str) -> Result<Vec<LinkGroup>, CoreError> {
+        let groups = self
+            .groups
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        Ok(groups
+            .values()
+            .filter(|g| g.parent_id.as_deref() == Some(parent_id))
+            .cloned()
+            .collect())
+    }
+
+    fn delete_group(
+        &self,
+        id: &str,
+        links: &GroupLinkDisposition,
+        at: SystemTime,
+    ) -> Result<usize, CoreError> {
         let mut groups = self
             .groups
             .lock()
@@ -364,11 +655,57 @@ impl GroupRepository for InMemoryGroupRepo {
             .members
             .lock()
             .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
-        if groups.remove(id).is_none() {
+        if !groups.contains_key(id) {
             return Err(CoreError::NotFound);
         }
+        if let GroupLinkDisposition::Reassign(target) = links {
+            if target == id {
+                return Err(CoreError::Conflict(
+                    "cannot reassign links to the group being deleted".into(),
+                ));
+            }
+            if !groups.contains_key(target) {
+                return Err(CoreError::NotFound);
+            }
+        }
+
+        let mut affected = 0;
+        let mut inner = self
+            .links
+            .inner
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let mut grants = self
+            .links
+            .grants
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        for link in inner
+            .values_mut()
+            .filter(|l| l.group_id.as_deref() == Some(id))
+        {
+            match links {
+                GroupLinkDisposition::Reassign(target) => link.group_id = Some(target.clone()),
+                GroupLinkDisposition::Detach => link.group_id = None,
+                GroupLinkDisposition::SoftDelete => {
+                    link.group_id = None;
+                    link.deleted_at.get_or_insert(at);
+                    grants.retain(|g| g.slug != link.slug);
+                }
+            }
+            link.updated_at = Some(at);
+            affected += 1;
+        }
+
+        let parent = groups.remove(id).and_then(|g| g.parent_id);
+        for child in groups
+            .values_mut()
+            .filter(|g| g.parent_id.as_deref() == Some(id))
+        {
+            child.parent_id = parent.clone();
+        }
         members.retain(|m| m.group_id != id);
-        Ok(())
+        Ok(affected)
     }
 
     fn add_member(&self, member: GroupMember) -> Result<(), CoreError> {
@@ -400,12 +737,39 @@ impl GroupRepository for InMemoryGroupRepo {
             .members
             .lock()
             .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
-        let initial_len = members.len();
-        members
-            .retain(|m| !(m.group_id == group_id && m.user_email.as_str() == user_email.as_str()));
-        if members.len() == initial_len {
-            return Err(CoreError::NotFound);
+        let pos = members
+            .iter()
+            .position(|m| m.group_id == group_id && m.user_email.as_str() == user_email.as_str())
+            .ok_or(CoreError::NotFound)?;
+        let admins = members
+            .iter()
+            .filter(|m| m.group_id == group_id && m.role == GroupRole::Admin)
+            .count();
+        if members[pos].role == GroupRole::Admin && admins <= 1 {
+            return Err(CoreError::Conflict(
+                "group must keep at least one admin".into(),
+            ));
         }
+        members.remove(pos);
+        Ok(())
+    }
+
+    fn transfer_ownership(&self, group_id: &str, new_owner: &UserEmail) -> Result<(), CoreError> {
+        let mut groups = self
+            .groups
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let mut members = self
+            .members
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let group = groups.get_mut(group_id).ok_or(CoreError::NotFound)?;
+        let member = members
+            .iter_mut()
+            .find(|m| m.group_id == group_id && m.user_email.as_str() == new_owner.as_str())
+            .ok_or_else(|| CoreError::Conflict("new owner must be a group member".into()))?;
+        member.role = GroupRole::Admin;
+        group.created_by = new_owner.clone();
         Ok(())
     }
 
@@ -469,8 +833,200 @@ impl GroupRepository for InMemoryGroupRepo {
                 }
             }
         }
+        drop(members);
+        drop(groups);
 
-        Ok(result)
+        hierarchy::with_inherited(self, result)
+    }
+}
+
+// ============ InMemoryInvitationRepo ============
+
+impl InMemoryInvitationRepo {
+    pub fn new() -> Self {
+        Self {
+            invitations: Partitioned::new(),
+        }
+    }
+}
+
+impl Default for InMemoryInvitationRepo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TenantScoped for InMemoryInvitationRepo {
+    fn for_tenant(&self, tenant: &TenantId) -> Self {
+        Self {
+            invitations: self.invitations.scoped(tenant),
+        }
+    }
+
+    fn tenant(&self) -> &TenantId {
+        &self.invitations.tenant
+    }
+}
+
+impl InvitationRepository for InMemoryInvitationRepo {
+    fn create_invitation(&self, invitation: GroupInvitation) -> Result<(), CoreError> {
+        let mut invitations = self
+            .invitations
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        if invitations.contains_key(&invitation.id) {
+            return Err(CoreError::AlreadyExists);
+        }
+        invitations.insert(invitation.id.clone(), invitation);
+        Ok(())
+    }
+
+    fn get_invitation(&self, id: &str) -> Result<Option<GroupInvitation>, CoreError> {
+        let invitations = self
+            .invitations
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        Ok(invitations.get(id).cloned())
+    }
+
+    fn list_invitations_for_user(
+        &self,
+        email: &UserEmail,
+    ) -> Result<Vec<GroupInvitation>, CoreError> {
+        let invitations = self
+            .invitations
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        Ok(invitations
+            .values()
+            .filter(|i| i.email.as_str() == email.as_str())
+            .cloned()
+            .collect())
+    }
+
+    fn list_invitations_for_group(
+        &self,
+        group_id: &str,
+    ) -> Result<Vec<GroupInvitation>, CoreError> {
+        let invitations = self
+            .invitations
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        Ok(invitations
+            .values()
+            .filter(|i| i.group_id == group_id)
+            .cloned()
+            .collect())
+    }
+
+    fn respond_invitation(
+        &self,
+        id: &str,
+        status: InvitationStatus,
+        responded_at: SystemTime,
+    ) -> Result<(), CoreError> {
+        let mut invitations = self
+            .invitations
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        match invitations.get_mut(id) {
+            Some(inv) if inv.status == InvitationStatus::Pending => {
+                inv.status = status;
+                inv.responded_at = Some(responded_at);
+                Ok(())
+            }
+            _ => Err(CoreError::NotFound),
+        }
+    }
+}
+
+// ============ InMemoryLinkGrantRepo ============
+
+impl InMemoryLinkGrantRepo {
+    pub fn new() -> Self {
+        Self {
+            grants: Partitioned::new(),
+        }
+    }
+}
+
+impl Default for InMemoryLinkGrantRepo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TenantScoped for InMemoryLinkGrantRepo {
+    fn for_tenant(&self, tenant: &TenantId) -> Self {
+        Self {
+            grants: self.grants.scoped(tenant),
+        }
+    }
+
+    fn tenant(&self) -> &TenantId {
+        &self.grants.tenant
+    }
+}
+
+impl LinkGrantRepository for InMemoryLinkGrantRepo {
+    fn put_grant(&self, grant: LinkGrant) -> Result<(), CoreError> {
+        let mut grants = self
+            .grants
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        grants.retain(|g| {
+            !(g.slug == grant.slug && g.user_email.as_str() == grant.user_email.as_str())
+        });
+        grants.push(grant);
+        Ok(())
+    }
+
+    fn remove_grant(&self, slug: &Slug, user_email: &UserEmail) -> Result<(), CoreError> {
+        let mut grants = self
+            .grants
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        let initial_len = grants.len();
+        grants.retain(|g| !(&g.slug == slug && g.user_email.as_str() == user_email.as_str()));
+        if grants.len() == initial_len {
+            return Err(CoreError::NotFound);
+        }
+        Ok(())
+    }
+
+    fn list_grants(&self, slug: &Slug) -> Result<Vec<LinkGrant>, CoreError> {
+        let grants = self
+            .grants
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        Ok(grants.iter().filter(|g| &g.slug == slug).cloned().collect())
+    }
+
+    fn get_grant(
+        &self,
+        slug: &Slug,
+        user_email: &UserEmail,
+    ) -> Result<Option<LinkGrant>, CoreError> {
+        let grants = self
+            .grants
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        Ok(grants
+            .iter()
+            .find(|g| &g.slug == slug && g.user_email.as_str() == user_email.as_str())
+            .cloned())
+    }
+
+    fn list_grants_for_user(&self, user_email: &UserEmail) -> Result<Vec<LinkGrant>, CoreError> {
+        let grants = self
+            .grants
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        Ok(grants
+            .iter()
+            .filter(|g| g.user_email.as_str() == user_email.as_str())
+            .cloned()
+            .collect())
     }
 }
 
@@ -479,7 +1035,7 @@ impl GroupRepository for InMemoryGroupRepo {
 impl InMemoryClickRepo {
     pub fn new() -> Self {
         Self {
-            clicks: Mutex::new(Vec::new()),
+            clicks: Partitioned::new(),
         }
     }
 }
@@ -490,6 +1046,18 @@ impl Default for InMemoryClickRepo {
     }
 }
 
+impl TenantScoped for InMemoryClickRepo {
+    fn for_tenant(&self, tenant: &TenantId) -> Self {
+        Self {
+            clicks: self.clicks.scoped(tenant),
+        }
+    }
+
+    fn tenant(&self) -> &TenantId {
+        &self.clicks.tenant
+    }
+}
+
 impl ClickRepository for InMemoryClickRepo {
     fn record_click(&self, event: ClickEvent) -> Result<(), CoreError> {
         let mut clicks = self
@@ -505,12 +1073,8 @@ impl ClickRepository for InMemoryClickRepo {
             .clicks
             .lock()
             .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
-        let mut matching: Vec<_> = clicks
-            .iter()
-            .filter(|c| c.slug.as_str() == slug.as_str())
-            .cloned()
-            .collect();
-        matching.sort_by(|a, b| b.clicked_at.cmp(&a.clicked_at));
+        let mut matching: Vec<_> = clicks.iter().filter(|c| c.slug == *slug).cloned().collect();
+        matching.sort_by_key(|x| std::cmp::Reverse(x.clicked_at));
         Ok(matching.into_iter().take(limit).collect())
     }
 
@@ -521,7 +1085,7 @@ impl ClickRepository for InMemoryClickRepo {
             .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
         Ok(clicks
             .iter()
-            .filter(|c| c.slug.as_str() == slug.as_str() && c.clicked_at >= since)
+            .filter(|c| c.slug == *slug && c.clicked_at >= since)
             .count() as u64)
     }
 
@@ -540,7 +1104,7 @@ impl ClickRepository for InMemoryClickRepo {
         let mut by_day: HashMap<String, u64> = HashMap::new();
 
         for click in clicks.iter() {
-            if click.slug.as_str() != slug.as_str() || click.clicked_at < cutoff {
+            if click.slug != *slug || click.clicked_at < cutoff {
                 continue;
             }
             // Format as YYYY-MM-DD using duration since UNIX_EPOCH
@@ -566,7 +1130,7 @@ impl ClickRepository for InMemoryClickRepo {
 impl InMemoryAuditRepo {
     pub fn new() -> Self {
         Self {
-            entries: Mutex::new(Vec::new()),
+            entries: Partitioned::new(),
         }
     }
 }
@@ -577,6 +1141,18 @@ impl Default for InMemoryAuditRepo {
     }
 }
 
+impl TenantScoped for InMemoryAuditRepo {
+    fn for_tenant(&self, tenant: &TenantId) -> Self {
+        Self {
+            entries: self.entries.scoped(tenant),
+        }
+    }
+
+    fn tenant(&self) -> &TenantId {
+        &self.entries.tenant
+    }
+}
+
 impl AuditRepository for InMemoryAuditRepo {
     fn log(&self, entry: AuditEntry) -> Result<(), CoreError> {
         let mut entries = self
@@ -602,7 +1178,7 @@ impl AuditRepository for InMemoryAuditRepo {
             .filter(|e| e.target_type == target_type && e.target_id == target_id)
             .cloned()
             .collect();
-        matching.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        matching.sort_by_key(|x| std::cmp::Reverse(x.timestamp));
         Ok(matching.into_iter().take(limit).collect())
     }
 
@@ -620,7 +1196,7 @@ impl AuditRepository for InMemoryAuditRepo {
             .filter(|e| e.actor_email.as_str() == actor_email.as_str())
             .cloned()
             .collect();
-        matching.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        matching.sort_by_key(|x| std::cmp::Reverse(x.timestamp));
         Ok(matching.into_iter().take(limit).collect())
     }
 
@@ -630,15 +1206,190 @@ impl AuditRepository for InMemoryAuditRepo {
             .lock()
             .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
         let mut all: Vec<_> = entries.iter().cloned().collect();
-        all.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        all.sort_by_key(|x| std::cmp::Reverse(x.timestamp));
         Ok(all.into_iter().take(limit).collect())
     }
 }
 
+// ============ InMemoryDomainRepo ============
+
+impl InMemoryDomainRepo {
+    pub fn new() -> Self {
+        Self {
+            domains: Partitioned::new(),
+        }
+    }
+}
+
+impl Default for InMemoryDomainRepo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TenantScoped for InMemoryDomainRepo {
+    fn for_tenant(&self, tenant: &TenantId) -> Self {
+        Self {
+            domains: self.domains.scoped(tenant),
+        }
+    }
+
+    fn tenant(&self) -> &TenantId {
+        &self.domains.tenant
+    }
+}
+
+impl DomainRepository for InMemoryDomainRepo {
+    fn put_domain(&self, domain: ShortDomain) -> Result<(), CoreError> {
+        let mut domains = self
+            .domains
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        domains.insert(domain.host.clone(), domain);
+        Ok(())
+    }
+
+    fn get_domain(&self, host: &str) -> Result<Option<ShortDomain>, CoreError> {
+        let domains = self
+            .domains
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        Ok(domains.get(host).cloned())
+    }
+
+    fn list_domains(&self) -> Result<Vec<ShortDomain>, CoreError> {
+        let domains = self
+            .domains
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        Ok(domains.values().cloned().collect())
+    }
+
+    fn delete_domain(&self, host: &str) -> Result<(), CoreError> {
+        let mut domains = self
+            .domains
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        domains.remove(host).map(|_| ()).ok_or(CoreError::NotFound)
+    }
+}
+
+// ============ InMemoryNamespaceRepo ============
+
+impl InMemoryNamespaceRepo {
+    pub fn new() -> Self {
+        Self {
+            namespaces: Partitioned::new(),
+        }
+    }
+}
+
+impl Default for InMemoryNamespaceRepo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TenantScoped for InMemoryNamespaceRepo {
+    fn for_tenant(&self, tenant: &TenantId) -> Self {
+        Self {
+            namespaces: self.namespaces.scoped(tenant),
+        }
+    }
+
+    fn tenant(&self) -> &TenantId {
+        &self.namespaces.tenant
+    }
+}
+
+impl NamespaceRepository for InMemoryNamespaceRepo {
+    fn put_namespace(&self, namespace: Namespace) -> Result<(), CoreError> {
+        let mut namespaces = self
+            .namespaces
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        if namespaces.contains_key(&namespace.path) {
+            return Err(CoreError::AlreadyExists);
+        }
+        namespaces.insert(namespace.path.clone(), namespace);
+        Ok(())
+    }
+
+    fn get_namespace(&self, path: &str) -> Result<Option<Namespace>, CoreError> {
+        let namespaces = self
+            .namespaces
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        Ok(namespaces.get(path).cloned())
+    }
+
+    fn list_namespaces(&self) -> Result<Vec<Namespace>, CoreError> {
+        let namespaces = self
+            .namespaces
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        Ok(namespaces.values().cloned().collect())
+    }
+
+    fn delete_namespace(&self, path: &str) -> Result<(), CoreError> {
+        let mut namespaces = self
+            .namespaces
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        namespaces
+            .remove(path)
+            .map(|_| ())
+            .ok_or(CoreError::NotFound)
+    }
+}
+
+// ============ InMemoryOrganizationRepo ============
+
+impl InMemoryOrganizationRepo {
+    pub fn new() -> Self {
+        Self {
+            orgs: Mutex::new(BTreeMap::new()),
+        }
+    }
+}
+
+impl Default for InMemoryOrganizationRepo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OrganizationRepository for InMemoryOrganizationRepo {
+    fn put_organization(&self, org: Organization) -> Result<(), CoreError> {
+        let mut orgs = self
+            .orgs
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        orgs.insert(org.id.clone(), org);
+        Ok(())
+    }
+
+    fn get_organization(&self, id: &TenantId) -> Result<Option<Organization>, CoreError> {
+        let orgs = self
+            .orgs
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        Ok(orgs.get(id).cloned())
+    }
+
+    fn list_organizations(&self) -> Result<Vec<Organization>, CoreError> {
+        let orgs = self
+            .orgs
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))?;
+        Ok(orgs.values().cloned().collect())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{Slug, UserEmail};
+    use crate::{GroupSettings, LinkSort, LinkStatus, Slug, SortDirection, UserEmail};
     use std::time::SystemTime;
 
     fn mk_link(slug: &str) -> ShortLink {
@@ -659,6 +1410,35 @@ mod tests {
         assert_eq!(got.original_url, "https://example.com");
     }
 
+    #[test]
+    fn tenant_handles_see_only_their_partition() {
+        let repo = InMemoryRepo::new();
+        let acme = repo.for_tenant(&TenantId::new("acme").unwrap());
+        repo.put(mk_link("abc")).unwrap();
+        assert!(acme.get(&Slug::new("abc").unwrap()).unwrap().is_none());
+        acme.put(mk_link("abc")).unwrap();
+        acme.put(mk_link("xyz")).unwrap();
+        assert_eq!(repo.list(10).unwrap().len(), 1);
+        assert_eq!(acme.list(10).unwrap().len(), 2);
+        assert_eq!(acme.tenant().as_str(), "acme");
+
+        let groups = InMemoryGroupRepo::with_links(Arc::new(repo));
+        let acme_groups = groups.for_tenant(&TenantId::new("acme").unwrap());
+        acme_groups
+            .create_group(LinkGroup {
+                id: "grp".into(),
+                name: "grp".into(),
+                description: None,
+                created_at: SystemTime::UNIX_EPOCH,
+                created_by: UserEmail::new("user@example.com").unwrap(),
+                settings: GroupSettings::default(),
+                parent_id: None,
+            })
+            .unwrap();
+        assert!(groups.get_group("grp").unwrap().is_none());
+        assert!(acme_groups.get_group("grp").unwrap().is_some());
+    }
+
     #[test]
     fn put_rejects_duplicate() {
         let repo = InMemoryRepo::new();
@@ -668,6 +1448,266 @@ mod tests {
         assert!(matches!(err, CoreError::AlreadyExists));
     }
 
+    #[test]
+    fn slugs_are_unique_per_domain() {
+        let repo = InMemoryRepo::new();
+        let mut other = mk_link("same");
+        other.slug = other.slug.on_domain(Some("jpro.link".into()));
+        other.original_url = "https://jpro.example".into();
+        repo.put(mk_link("same")).unwrap();
+        repo.put(other.clone()).unwrap();
+        assert_eq!(repo.get(&other.slug).unwrap().unwrap(), other);
+        assert_eq!(
+            repo.get(&Slug::new("same").unwrap())
+                .unwrap()
+                .unwrap()
+                .original_url,
+            "https://example.com"
+        );
+    }
+
+    #[test]
+    fn list_paginated_follows_cursors() {
+        let repo = InMemoryRepo::new();
+        let by = UserEmail::new("user@example.com").unwrap();
+        for (slug, secs) in [("a", 1), ("b", 2), ("c", 2), ("d", 3)] {
+            repo.put(ShortLink::new(
+                Slug::new(slug).unwrap(),
+                "https://example.com".into(),
+                SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(secs),
+                by.clone(),
+            ))
+            .unwrap();
+        }
+        let mut options = ListOptions {
+            limit: 3,
+            ..Default::default()
+        };
+        let mut seen = Vec::new();
+        loop {
+            let page = repo.list_paginated(&options).unwrap();
+            assert_eq!(page.total, Some(4));
+            seen.extend(page.items.iter().map(|l| l.slug.as_str().to_string()));
+            assert_eq!(page.has_more, page.next_cursor.is_some());
+            match page.next_cursor {
+                Some(cursor) => options.cursor = Some(cursor),
+                None => break,
+            }
+        }
+        assert_eq!(seen, ["d", "c", "b", "a"]);
+
+        options.cursor = None;
+        options.offset = 2;
+        let page = repo.list_paginated(&options).unwrap();
+        assert_eq!(page.items.len(), 2);
+        assert!(!page.has_more);
+
+        options.cursor = Some("not-a-cursor".into());
+        assert!(matches!(
+            repo.list_paginated(&options),
+            Err(CoreError::InvalidCursor)
+        ));
+    }
+
+    #[test]
+    fn list_paginated_sorts_and_filters() {
+        let repo = InMemoryRepo::new();
+        let by = UserEmail::new("user@example.com").unwrap();
+        let at = |secs| SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(secs);
+        for (slug, clicks, url, expires) in [
+            ("a", 3, "https://docs.example.com/a", Some(10)),
+            ("b", 8, "https://blog.example.com/b", None),
+            ("c", 1, "https://docs.example.com/c", Some(500)),
+        ] {
+            let mut link = ShortLink::new(Slug::new(slug).unwrap(), url.into(), at(1), by.clone());
+            link.click_count = clicks;
+            link.expires_at = expires.map(at);
+            repo.put(link).unwrap();
+        }
+        let list = |options: ListOptions| -> Vec<String> {
+            repo.list_paginated(&ListOptions {
+                limit: 10,
+                now: Some(at(100)),
+                ..options
+            })
+            .unwrap()
+            .items
+            .iter()
+            .map(|l| l.slug.as_str().to_string())
+            .collect()
+        };
+        assert_eq!(
+            list(ListOptions {
+                sort: LinkSort::ClickCount,
+                ..Default::default()
+            }),
+            ["b", "a", "c"]
+        );
+        assert_eq!(
+            list(ListOptions {
+                sort: LinkSort::ExpiresAt,
+                direction: SortDirection::Asc,
+                target_host: Some("docs.example.com".into()),
+                ..Default::default()
+            }),
+            ["a", "c"]
+        );
+        assert_eq!(
+            list(ListOptions {
+                status: Some(LinkStatus::Expired),
+                ..Default::default()
+            }),
+            ["a"]
+        );
+        assert_eq!(
+            list(ListOptions {
+                expires: crate::TimeRange {
+                    from: Some(at(100)),
+                    to: Some(at(1000)),
+                },
+                ..Default::default()
+            }),
+            ["c"]
+        );
+    }
+
+    #[test]
+    fn links_are_found_by_canonical_target() {
+        let repo = InMemoryRepo::new();
+        let by = UserEmail::new("user@example.com").unwrap();
+        let link = |slug: &str, url: &str, secs: u64| {
+            ShortLink::new(
+                Slug::new(slug).unwrap(),
+                url.into(),
+                SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(secs),
+                by.clone(),
+            )
+        };
+        repo.put(link("second", "https://Example.com:443/docs#intro", 2))
+            .unwrap();
+        repo.put(link("first", "https://example.com/docs", 1))
+            .unwrap();
+        repo.put(link("other", "https://example.com/docs/", 3))
+            .unwrap();
+        let slugs = |url: &str| -> Vec<String> {
+            repo.list_by_target(&crate::validate::canonical_url(url), TargetMatch::Exact, 10)
+                .unwrap()
+                .into_iter()
+                .map(|l| l.slug.as_str().to_string())
+                .collect()
+        };
+        assert_eq!(slugs("HTTPS://EXAMPLE.COM/docs"), ["first", "second"]);
+        let matches = |key: &str, mode| -> Vec<String> {
+            repo.list_by_target(key, mode, 10)
+                .unwrap()
+                .into_iter()
+                .map(|l| l.slug.as_str().to_string())
+                .collect()
+        };
+        assert_eq!(
+            matches("https://example.com/docs", TargetMatch::Prefix),
+            ["first", "second", "other"]
+        );
+        assert_eq!(
+            matches("example.com", TargetMatch::Host),
+            ["first", "second", "other"]
+        );
+        assert!(matches("ample.com", TargetMatch::Host).is_empty());
+
+        let mut moved = repo.get(&Slug::new("first").unwrap()).unwrap().unwrap();
+        moved.original_url = "https://example.com/elsewhere".into();
+        repo.update(&moved).unwrap();
+        repo.rename(
+            &Slug::new("second").unwrap(),
+            &Slug::new("renamed").unwrap(),
+        )
+        .unwrap();
+        assert_eq!(slugs("https://example.com/docs"), ["renamed"]);
+        assert_eq!(slugs("https://example.com/elsewhere"), ["first"]);
+
+        repo.delete(&Slug::new("renamed").unwrap(), SystemTime::UNIX_EPOCH)
+            .unwrap();
+        assert!(slugs("https://example.com/docs").is_empty());
+    }
+
+    #[test]
+    fn innermost_reserved_namespace_wins() {
+        let repo = InMemoryNamespaceRepo::new();
+        let reserve = |path: &str, group: &str| Namespace {
+            path: path.into(),
+            group_id: group.into(),
+            created_at: SystemTime::UNIX_EPOCH,
+            created_by: UserEmail::new("admin@example.com").unwrap(),
+        };
+        repo.put_namespace(reserve("events", "g1")).unwrap();
+        repo.put_namespace(reserve("events/2026", "g2")).unwrap();
+        assert!(matches!(
+            repo.put_namespace(reserve("events", "g3")),
+            Err(CoreError::AlreadyExists)
+        ));
+        let owner = |s: &str| {
+            repo.reserved_namespace(&Slug::new(s).unwrap())
+                .unwrap()
+                .map(|ns| ns.group_id)
+        };
+        assert_eq!(owner("events/2026/kickoff").as_deref(), Some("g2"));
+        assert_eq!(owner("events/party").as_deref(), Some("g1"));
+        assert_eq!(owner("events"), None);
+        assert_eq!(owner("hr/onboarding"), None);
+        repo.delete_namespace("events/2026").unwrap();
+        assert_eq!(owner("events/2026/kickoff").as_deref(), Some("g1"));
+        assert!(matches!(
+            repo.delete_namespace("events/2026"),
+            Err(CoreError::NotFound)
+        ));
+    }
+
+    #[test]
+    fn renamed_links_keep_forwarding_aliases() {
+        let repo = InMemoryRepo::new();
+        let slug = |s: &str| Slug::new(s).unwrap();
+        repo.put(mk_link("tpyo")).unwrap();
+        repo.put(mk_link("other")).unwrap();
+        repo.add_alias(&slug("extra"), &slug("tpyo")).unwrap();
+        assert!(matches!(
+            repo.add_alias(&slug("other"), &slug("tpyo")),
+            Err(CoreError::AlreadyExists)
+        ));
+        assert!(matches!(
+            repo.add_alias(&slug("dangling"), &slug("missing")),
+            Err(CoreError::NotFound)
+        ));
+
+        repo.increment_click(&slug("tpyo")).unwrap();
+        let renamed = repo.rename(&slug("tpyo"), &slug("typo")).unwrap();
+        assert_eq!(renamed.slug, slug("typo"));
+        assert_eq!(renamed.click_count, 1);
+        assert!(matches!(
+            repo.rename(&slug("typo"), &slug("extra")),
+            Err(CoreError::AlreadyExists)
+        ));
+
+        // Old slug and existing aliases both follow the rename
+        for s in ["tpyo", "extra", "typo"] {
+            assert_eq!(repo.get(&slug(s)).unwrap().unwrap().slug, slug("typo"));
+        }
+        assert_eq!(
+            repo.list_aliases(&slug("typo")).unwrap(),
+            vec![slug("extra"), slug("tpyo")]
+        );
+        assert!(matches!(
+            repo.put(mk_link("tpyo")),
+            Err(CoreError::AlreadyExists)
+        ));
+
+        repo.remove_alias(&slug("extra")).unwrap();
+        assert!(repo.get(&slug("extra")).unwrap().is_none());
+        assert!(matches!(
+            repo.remove_alias(&slug("typo")),
+            Err(CoreError::NotFound)
+        ));
+    }
+
     #[test]
     fn list_honors_limit() {
         let repo = InMemoryRepo::new();
@@ -678,4 +1718,201 @@ mod tests {
         let v = repo.list(5).unwrap();
         assert_eq!(v.len(), 5);
     }
+
+    #[test]
+    fn invitation_can_only_be_answered_once() {
+        let repo = InMemoryInvitationRepo::new();
+        let bob = UserEmail::new("bob@example.com").unwrap();
+        repo.create_invitation(GroupInvitation {
+            id: "inv_1".into(),
+            group_id: "grp_1".into(),
+            email: bob.clone(),
+            role: GroupRole::Viewer,
+            invited_by: UserEmail::new("user@example.com").unwrap(),
+            created_at: SystemTime::UNIX_EPOCH,
+            expires_at: SystemTime::UNIX_EPOCH,
+            status: InvitationStatus::Pending,
+            responded_at: None,
+        })
+        .unwrap();
+        assert_eq!(repo.list_invitations_for_user(&bob).unwrap().len(), 1);
+        assert_eq!(repo.list_invitations_for_group("grp_1").unwrap().len(), 1);
+
+        repo.respond_invitation("inv_1", InvitationStatus::Accepted, SystemTime::UNIX_EPOCH)
+            .unwrap();
+        assert!(matches!(
+            repo.respond_invitation("inv_1", InvitationStatus::Declined, SystemTime::UNIX_EPOCH),
+            Err(CoreError::NotFound)
+        ));
+        let inv = repo.get_invitation("inv_1").unwrap().unwrap();
+        assert_eq!(inv.status, InvitationStatus::Accepted);
+        assert!(inv.responded_at.is_some());
+    }
+
+    fn mk_group(repo: &InMemoryGroupRepo, id: &str, admin: &UserEmail) {
+        repo.create_group(LinkGroup {
+            id: id.into(),
+            name: id.into(),
+            description: None,
+            created_at: SystemTime::UNIX_EPOCH,
+            created_by: admin.clone(),
+            settings: GroupSettings::default(),
+            parent_id: None,
+        })
+        .unwrap();
+        repo.add_member(GroupMember {
+            group_id: id.into(),
+            user_email: admin.clone(),
+            role: GroupRole::Admin,
+            added_at: SystemTime::UNIX_EPOCH,
+            added_by: admin.clone(),
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn group_keeps_last_admin_and_transfers_ownership() {
+        let repo = InMemoryGroupRepo::new();
+        let alice = UserEmail::new("alice@example.com").unwrap();
+        let bob = UserEmail::new("bob@example.com").unwrap();
+        mk_group(&repo, "grp_1", &alice);
+
+        assert!(matches!(
+            repo.remove_member("grp_1", &alice),
+            Err(CoreError::Conflict(_))
+        ));
+        assert!(matches!(
+            repo.transfer_ownership("grp_1", &bob),
+            Err(CoreError::Conflict(_))
+        ));
+
+        repo.add_member(GroupMember {
+            group_id: "grp_1".into(),
+            user_email: bob.clone(),
+            role: GroupRole::Viewer,
+            added_at: SystemTime::UNIX_EPOCH,
+            added_by: alice.clone(),
+        })
+        .unwrap();
+        repo.transfer_ownership("grp_1", &bob).unwrap();
+        assert_eq!(repo.get_group("grp_1").unwrap().unwrap().created_by, bob);
+        assert_eq!(
+            repo.get_member("grp_1", &bob).unwrap().unwrap().role,
+            GroupRole::Admin
+        );
+
+        // Bob is now an admin too, so Alice may leave
+        repo.remove_member("grp_1", &alice).unwrap();
+        assert!(matches!(
+            repo.remove_member("grp_1", &bob),
+            Err(CoreError::Conflict(_))
+        ));
+    }
+
+    #[test]
+    fn delete_group_applies_link_disposition() {
+        let links = InMemoryRepo::new();
+        let repo = links.groups();
+        let alice = UserEmail::new("alice@example.com").unwrap();
+        for id in ["grp_a", "grp_b", "grp_c", "grp_d"] {
+            mk_group(&repo, id, &alice);
+        }
+        for (slug, group) in [("a1", "grp_a"), ("b1", "grp_b"), ("c1", "grp_c")] {
+            let mut link = mk_link(slug);
+            link.group_id = Some(group.into());
+            links.put(link).unwrap();
+        }
+        let at = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(10);
+
+        assert!(matches!(
+            repo.delete_group("grp_a", &GroupLinkDisposition::Reassign("grp_a".into()), at),
+            Err(CoreError::Conflict(_))
+        ));
+        let moved = repo
+            .delete_group("grp_a", &GroupLinkDisposition::Reassign("grp_d".into()), at)
+            .unwrap();
+        assert_eq!(moved, 1);
+        let a1 = links.get(&Slug::new("a1").unwrap()).unwrap().unwrap();
+        assert_eq!(a1.group_id.as_deref(), Some("grp_d"));
+        assert!(repo.get_group("grp_a").unwrap().is_none());
+        assert!(repo.list_members("grp_a").unwrap().is_empty());
+
+        repo.delete_group("grp_b", &GroupLinkDisposition::Detach, at)
+            .unwrap();
+        let b1 = links.get(&Slug::new("b1").unwrap()).unwrap().unwrap();
+        assert_eq!(b1.group_id, None);
+        assert_eq!(b1.deleted_at, None);
+
+        repo.delete_group("grp_c", &GroupLinkDisposition::SoftDelete, at)
+            .unwrap();
+        let c1 = links.get(&Slug::new("c1").unwrap()).unwrap().unwrap();
+        assert_eq!(c1.group_id, None);
+        assert_eq!(c1.deleted_at, Some(at));
+    }
+
+    #[test]
+    fn link_grants_upsert_and_remove() {
+        let repo = InMemoryLinkGrantRepo::new();
+        let slug = Slug::new("shared").unwrap();
+        let bob = UserEmail::new("bob@example.com").unwrap();
+        let grant = LinkGrant {
+            slug: slug.clone(),
+            user_email: bob.clone(),
+            role: GroupRole::Viewer,
+            granted_at: SystemTime::UNIX_EPOCH,
+            granted_by: UserEmail::new("user@example.com").unwrap(),
+        };
+        repo.put_grant(grant.clone()).unwrap();
+        repo.put_grant(LinkGrant {
+            role: GroupRole::Editor,
+            ..grant
+        })
+        .unwrap();
+
+        let grants = repo.list_grants(&slug).unwrap();
+        assert_eq!(grants.len(), 1);
+        assert_eq!(grants[0].role, GroupRole::Editor);
+        assert!(repo.get_grant(&slug, &bob).unwrap().is_some());
+
+        repo.remove_grant(&slug, &bob).unwrap();
+        assert!(repo.get_grant(&slug, &bob).unwrap().is_none());
+        assert!(matches!(
+            repo.remove_grant(&slug, &bob),
+            Err(CoreError::NotFound)
+        ));
+    }
+    #[test]
+    fn deleting_links_drops_their_grants() {
+        let repo = InMemoryRepo::new();
+        crate::adapters::conformance::deleting_links_drops_their_grants(
+            &repo,
+            &repo.grants(),
+            &repo.groups(),
+        );
+    }
+
+    #[test]
+    fn link_grants_follow_rename_and_delete() {
+        let repo = InMemoryRepo::new();
+        let grants = repo.grants();
+        let slug = |s: &str| Slug::new(s).unwrap();
+        let bob = UserEmail::new("bob@example.com").unwrap();
+        repo.put(mk_link("tpyo")).unwrap();
+        grants
+            .put_grant(LinkGrant {
+                slug: slug("tpyo"),
+                user_email: bob.clone(),
+                role: GroupRole::Editor,
+                granted_at: SystemTime::UNIX_EPOCH,
+                granted_by: UserEmail::new("user@example.com").unwrap(),
+            })
+            .unwrap();
+
+        repo.rename(&slug("tpyo"), &slug("typo")).unwrap();
+        assert!(grants.get_grant(&slug("tpyo"), &bob).unwrap().is_none());
+        assert!(grants.get_grant(&slug("typo"), &bob).unwrap().is_some());
+
+        repo.delete(&slug("typo"), SystemTime::UNIX_EPOCH).unwrap();
+        assert!(grants.list_grants_for_user(&bob).unwrap().is_empty());
+    }
 }
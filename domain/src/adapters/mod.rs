@@ -3,4 +3,6 @@
 //! These are intended purely for unit testing and local demos. Real adapters
 //! (DynamoDB, Firestore, etc.) will live in separate crates.
 
+pub mod conformance;
+pub mod memory_queue;
 pub mod memory_repo;
@@ -0,0 +1,727 @@
+//! Read-through caches for link and short domain lookups.
+//!
+//! [`CachedLinkRepo`] wraps a [`LinkRepository`] and answers `get` from an
+//! in-process LRU before asking the repository. Lookups that find nothing are
+//! cached too, for a shorter time, so probes for unknown slugs don't reach
+//! storage either. An optional [`LinkCacheBackend`] (e.g. Redis) sits between
+//! the two and is shared by every process that uses it.
+//!
+//! Writes made through the decorator drop the entries of the slugs they touch,
+//! aliases included, from both layers. Writes made elsewhere only show up once
+//! the entries expire, so the TTLs bound how stale a lookup can be. Click
+//! increments patch the local entry instead of dropping it, so a link stays
+//! cached while it is being followed.
+//!
+//! [`CachedDomainRepo`] does the same for [`DomainRepository::get_domain`],
+//! in process only.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::{
+    tenant, CoreError, DomainRepository, LinkRepository, ListOptions, ListResult, SearchHit,
+    ShortDomain, ShortLink, Slug, TargetMatch, TenantId, TenantScoped, UserEmail,
+};
+
+/// Sizing and lifetimes of a [`CachedLinkRepo`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheOptions {
+    /// Most entries kept in process; 0 disables the local layer.
+    pub capacity: usize,
+    /// How long a found link is served from cache.
+    pub ttl: Duration,
+    /// How long a lookup that found nothing is served from cache.
+    pub negative_ttl: Duration,
+}
+
+impl Default for CacheOptions {
+    fn default() -> Self {
+        Self {
+            capacity: 1024,
+            ttl: Duration::from_secs(60),
+            negative_ttl: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Cached result of looking up one slug.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CachedLink {
+    Found(Box<ShortLink>),
+    /// The slug resolved to nothing.
+    Missing,
+}
+
+impl From<Option<ShortLink>> for CachedLink {
+    fn from(link: Option<ShortLink>) -> Self {
+        link.map_or(Self::Missing, |l| Self::Found(Box::new(l)))
+    }
+}
+
+impl From<CachedLink> for Option<ShortLink> {
+    fn from(entry: CachedLink) -> Self {
+        match entry {
+            CachedLink::Found(link) => Some(*link),
+            CachedLink::Missing => None,
+        }
+    }
+}
+
+/// Cache shared between processes, consulted after the local LRU misses.
+///
+/// Keys are tenant-scoped slug keys (see [`tenant::scoped_key`]). Failures
+/// never fail a lookup or write: they count as misses, and entries that could
+/// not be removed expire with their TTL.
+pub trait LinkCacheBackend: Send + Sync {
+    /// Entry stored under `key`, if any.
+    fn get(&self, key: &str) -> Result<Option<CachedLink>, CoreError>;
+    /// Store `entry` under `key` for `ttl`.
+    fn put(&self, key: &str, entry: &CachedLink, ttl: Duration) -> Result<(), CoreError>;
+    /// Drop the entries under `keys`.
+    fn remove(&self, keys: &[String]) -> Result<(), CoreError>;
+}
+
+/// [`LinkRepository`] decorator caching `get`; see the module docs.
+#[derive(Clone)]
+pub struct CachedLinkRepo<R> {
+    inner: R,
+    local: Arc<Mutex<Lru<CachedLink>>>,
+    backend: Option<Arc<dyn LinkCacheBackend>>,
+    options: CacheOptions,
+}
+
+impl<R> CachedLinkRepo<R> {
+    pub fn new(inner: R, options: CacheOptions) -> Self {
+        Self {
+            inner,
+            local: Arc::new(Mutex::new(Lru::new(options.capacity))),
+            backend: None,
+            options,
+        }
+    }
+
+    /// Also share entries through `backend`.
+    pub fn with_backend(mut self, backend: Arc<dyn LinkCacheBackend>) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+
+    /// The wrapped repository, for calls that bypass the cache.
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    fn local(&self) -> Result<MutexGuard<'_, Lru<CachedLink>>, CoreError> {
+        self.local
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))
+    }
+
+    fn ttl(&self, entry: &CachedLink) -> Duration {
+        match entry {
+            CachedLink::Found(_) => self.options.ttl,
+            CachedLink::Missing => self.options.negative_ttl,
+        }
+    }
+}
+
+impl<R: TenantScoped> TenantScoped for CachedLinkRepo<R> {
+    fn for_tenant(&self, tenant: &TenantId) -> Self {
+        Self {
+            inner: self.inner.for_tenant(tenant),
+            local: Arc::clone(&self.local),
+            backend: self.backend.clone(),
+            options: self.options.clone(),
+        }
+    }
+
+    fn tenant(&self) -> &TenantId {
+        self.inner.tenant()
+    }
+}
+
+impl<R: LinkRepository + TenantScoped> CachedLinkRepo<R> {
+    fn key(&self, slug: &Slug) -> String {
+        tenant::scoped_key(self.inner.tenant(), &slug.key())
+    }
+
+    /// Drop the entries under `keys` from both layers.
+    fn forget_keys(&self, keys: &[String]) -> Result<(), CoreError> {
+        let mut local = self.local()?;
+        for key in keys {
+            local.remove(key);
+        }
+        drop(local);
+        if let Some(backend) = &self.backend {
+            let _ = backend.remove(keys);
+        }
+        Ok(())
+    }
+
+    /// Drop the cached entries of `slugs` and their aliases after they changed
+    /// without going through this decorator, e.g. with their group.
+    pub fn forget(&self, slugs: &[Slug]) -> Result<(), CoreError> {
+        self.forget_links(slugs)
+    }
+
+    /// Drop the entries of `slugs` and of every alias pointing at them. If the
+    /// aliases can't be listed, the whole local layer goes instead.
+    fn forget_links<'a>(&self, slugs: impl IntoIterator<Item = &'a Slug>) -> Result<(), CoreError> {
+        let mut keys = Vec::new();
+        for slug in slugs {
+            keys.push(self.key(slug));
+            match self.inner.list_aliases(slug) {
+                Ok(aliases) => keys.extend(aliases.iter().map(|a| self.key(a))),
+                Err(_) => self.local()?.clear(),
+            }
+        }
+        self.forget_keys(&keys)
+    }
+}
+
+impl<R: LinkRepository + TenantScoped> LinkRepository for CachedLinkRepo<R> {
+    fn get(&self, slug: &Slug) -> Result<Option<ShortLink>, CoreError> {
+        let key = self.key(slug);
+        if let Some(entry) = self.local()?.get(&key, Instant::now()) {
+            return Ok(entry.into());
+        }
+        if let Some(Ok(Some(entry))) = self.backend.as_ref().map(|b| b.get(&key)) {
+            let expires_at = Instant::now() + self.ttl(&entry);
+            self.local()?.insert(key, entry.clone(), expires_at);
+            return Ok(entry.into());
+        }
+
+        let entry = CachedLink::from(self.inner.get(slug)?);
+        let ttl = self.ttl(&entry);
+        if let Some(backend) = &self.backend {
+            let _ = backend.put(&key, &entry, ttl);
+        }
+        self.local()?
+            .insert(key, entry.clone(), Instant::now() + ttl);
+        Ok(entry.into())
+    }
+
+    fn put(&self, link: ShortLink) -> Result<(), CoreError> {
+        let key = self.key(&link.slug);
+        self.inner.put(link)?;
+        self.forget_keys(&[key])
+    }
+
+    fn list(&self, limit: usize) -> Result<Vec<ShortLink>, CoreError> {
+        self.inner.list(limit)
+    }
+
+    fn update(&self, link: &ShortLink) -> Result<(), CoreError> {
+        self.inner.update(link)?;
+        self.forget_links([&link.slug])
+    }
+
+    fn increment_click(&self, slug: &Slug) -> Result<(), CoreError> {
+        self.inner.increment_click(slug)?;
+        if let Some(CachedLink::Found(link)) = self.local()?.get_mut(&self.key(slug)) {
+            link.click_count += 1;
+        }
+        Ok(())
+    }
+
+    fn add_clicks(&self, slug: &Slug, clicks: u64) -> Result<(), CoreError> {
+        self.inner.add_clicks(slug, clicks)?;
+        if let Some(CachedLink::Found(link)) = self.local()?.get_mut(&self.key(slug)) {
+            link.click_count += clicks;
+        }
+        Ok(())
+    }
+
+    fn list_by_creator(
+        &self,
+        email: &UserEmail,
+        limit: usize,
+    ) -> Result<Vec<ShortLink>, CoreError> {
+        self.inner.list_by_creator(email, limit)
+    }
+
+    fn delete(&self, slug: &Slug, deleted_at: SystemTime) -> Result<(), CoreError> {
+        self.inner.delete(slug, deleted_at)?;
+        self.forget_links([slug])
+    }
+
+    fn search(&self, query: &str, limit: usize) -> Result<Vec<ShortLink>, CoreError> {
+        self.inner.search(query, limit)
+    }
+
+    fn list_paginated(&self, options: &ListOptions) -> Result<ListResult<ShortLink>, CoreError> {
+        self.inner.list_paginated(options)
+    }
+
+    fn search_ranked(&self, options: &ListOptions) -> Result<ListResult<SearchHit>, CoreError> {
+        self.inner.search_ranked(options)
+    }
+
+    fn list_by_group(&self, group_id: &str, limit: usize) -> Result<Vec<ShortLink>, CoreError> {
+        self.inner.list_by_group(group_id, limit)
+    }
+
+    fn list_by_target(
+        &self,
+        key: &str,
+        mode: TargetMatch,
+        limit: usize,
+    ) -> Result<Vec<ShortLink>, CoreError> {
+        self.inner.list_by_target(key, mode, limit)
+    }
+
+    fn bulk_delete(&self, slugs: &[Slug], deleted_at: SystemTime) -> Result<usize, CoreError> {
+        let deleted = self.inner.bulk_delete(slugs, deleted_at)?;
+        self.forget_links(slugs)?;
+        Ok(deleted)
+    }
+
+    fn bulk_update_active(
+        &self,
+        slugs: &[Slug],
+        is_active: bool,
+        updated_at: SystemTime,
+    ) -> Result<usize, CoreError> {
+        let updated = self
+            .inner
+            .bulk_update_active(slugs, is_active, updated_at)?;
+        self.forget_links(slugs)?;
+        Ok(updated)
+    }
+
+    fn add_alias(&self, alias: &Slug, target: &Slug) -> Result<(), CoreError> {
+        self.inner.add_alias(alias, target)?;
+        self.forget_keys(&[self.key(alias)])
+    }
+
+    fn remove_alias(&self, alias: &Slug) -> Result<(), CoreError> {
+        self.inner.remove_alias(alias)?;
+        self.forget_keys(&[self.key(alias)])
+    }
+
+    fn list_aliases(&self, target: &Slug) -> Result<Vec<Slug>, CoreError> {
+        self.inner.list_aliases(target)
+    }
+
+    fn rename(&self, old: &Slug, new: &Slug) -> Result<ShortLink, CoreError> {
+        let link = self.inner.rename(old, new)?;
+        // The old slug is now one of the new one's aliases
+        self.forget_links([new])?;
+        Ok(link)
+    }
+}
+
+/// [`DomainRepository`] decorator caching `get_domain` in process, hosts that
+/// are not short domains included. Uses the TTLs of [`CacheOptions`].
+#[derive(Clone)]
+pub struct CachedDomainRepo<R> {
+    inner: R,
+    local: Arc<Mutex<Lru<Option<ShortDomain>>>>,
+    options: CacheOptions,
+}
+
+impl<R> CachedDomainRepo<R> {
+    pub fn new(inner: R, options: CacheOptions) -> Self {
+        Self {
+            inner,
+            local: Arc::new(Mutex::new(Lru::new(options.capacity))),
+            options,
+        }
+    }
+
+    fn local(&self) -> Result<MutexGuard<'_, Lru<Option<ShortDomain>>>, CoreError> {
+        self.local
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))
+    }
+}
+
+impl<R: TenantScoped> TenantScoped for CachedDomainRepo<R> {
+    fn for_tenant(&self, tenant: &TenantId) -> Self {
+        Self {
+            inner: self.inner.for_tenant(tenant),
+            local: Arc::clone(&self.local),
+            options: self.options.clone(),
+        }
+    }
+
+    fn tenant(&self) -> &TenantId {
+        self.inner.tenant()
+    }
+}
+
+impl<R: DomainRepository + TenantScoped> CachedDomainRepo<R> {
+    fn key(&self, host: &str) -> String {
+        tenant::scoped_key(self.inner.tenant(), host)
+    }
+}
+
+impl<R: DomainRepository + TenantScoped> DomainRepository for CachedDomainRepo<R> {
+    fn put_domain(&self, domain: ShortDomain) -> Result<(), CoreError> {
+        let key = self.key(&domain.host);
+        self.inner.put_domain(domain)?;
+        self.local()?.remove(&key);
+        Ok(())
+    }
+
+    fn get_domain(&self, host: &str) -> Result<Option<ShortDomain>, CoreError> {
+        let key = self.key(host);
+        if let Some(domain) = self.local()?.get(&key, Instant::now()) {
+            return Ok(domain);
+        }
+        let domain = self.inner.get_domain(host)?;
+        let ttl = match domain {
+            Some(_) => self.options.ttl,
+            None => self.options.negative_ttl,
+        };
+        self.local()?
+            .insert(key, domain.clone(), Instant::now() + ttl);
+        Ok(domain)
+    }
+
+    fn list_domains(&self) -> Result<Vec<ShortDomain>, CoreError> {
+        self.inner.list_domains()
+    }
+
+    fn delete_domain(&self, host: &str) -> Result<(), CoreError> {
+        self.inner.delete_domain(host)?;
+        self.local()?.remove(&self.key(host));
+        Ok(())
+    }
+}
+
+/// Least recently used entries with an expiry each.
+struct Lru<V> {
+    capacity: usize,
+    entries: HashMap<String, LruEntry<V>>,
+    /// Keys by last use, oldest first.
+    order: BTreeMap<u64, String>,
+    tick: u64,
+}
+
+struct LruEntry<V> {
+    value: V,
+    expires_at: Instant,
+    used: u64,
+}
+
+impl<V: Clone> Lru<V> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            tick: 0,
+        }
+    }
+
+    fn get(&mut self, key: &str, now: Instant) -> Option<V> {
+        let expired = now >= self.entries.get(key)?.expires_at;
+        if expired {
+            self.remove(key);
+            return None;
+        }
+        self.get_mut(key).cloned()
+    }
+
+    /// Entry under `key`, marked as just used; expired ones included.
+    fn get_mut(&mut self, key: &str) -> Option<&mut V> {
+        self.tick += 1;
+        let entry = self.entries.get_mut(key)?;
+        self.order.remove(&entry.used);
+        self.order.insert(self.tick, key.to_string());
+        entry.used = self.tick;
+        Some(&mut entry.value)
+    }
+
+    fn insert(&mut self, key: String, value: V, expires_at: Instant) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.remove(&key);
+        self.tick += 1;
+        self.order.insert(self.tick, key.clone());
+        self.entries.insert(
+            key,
+            LruEntry {
+                value,
+                expires_at,
+                used: self.tick,
+            },
+        );
+        while self.entries.len() > self.capacity {
+            let Some((_, oldest)) = self.order.pop_first() else {
+                break;
+            };
+            self.entries.remove(&oldest);
+        }
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.order.remove(&entry.used);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::memory_repo::InMemoryRepo;
+    use std::time::UNIX_EPOCH;
+
+    fn link(slug: &str) -> ShortLink {
+        ShortLink::new(
+            Slug::new(slug).unwrap(),
+            format!("https://example.com/{slug}"),
+            UNIX_EPOCH,
+            UserEmail::new("a@acme.com").unwrap(),
+        )
+    }
+
+    /// Backend keeping entries in a map, ignoring TTLs.
+    #[derive(Default)]
+    struct MapBackend(Mutex<HashMap<String, CachedLink>>);
+
+    impl LinkCacheBackend for MapBackend {
+        fn get(&self, key: &str) -> Result<Option<CachedLink>, CoreError> {
+            Ok(self.0.lock().unwrap().get(key).cloned())
+        }
+
+        fn put(&self, key: &str, entry: &CachedLink, _ttl: Duration) -> Result<(), CoreError> {
+            self.0.lock().unwrap().insert(key.into(), entry.clone());
+            Ok(())
+        }
+
+        fn remove(&self, keys: &[String]) -> Result<(), CoreError> {
+            let mut map = self.0.lock().unwrap();
+            for key in keys {
+                map.remove(key);
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn lookups_are_served_from_cache_until_written_through_it() {
+        // Writes to `store` bypass the cache, so they show what it serves
+        let store = InMemoryRepo::new();
+        let repo = CachedLinkRepo::new(
+            store.for_tenant(&TenantId::default()),
+            CacheOptions::default(),
+        );
+        let docs = Slug::new("docs").unwrap();
+
+        assert_eq!(repo.get(&docs).unwrap(), None);
+        store.put(link("docs")).unwrap();
+        assert_eq!(repo.get(&docs).unwrap(), None);
+
+        repo.put(link("other")).unwrap();
+        assert_eq!(repo.get(&docs).unwrap(), None);
+        repo.delete(&docs, UNIX_EPOCH).unwrap();
+        assert!(repo.get(&docs).unwrap().unwrap().deleted_at.is_some());
+
+        let mut moved = store.get(&docs).unwrap().unwrap();
+        moved.original_url = "https://example.com/moved".into();
+        store.update(&moved).unwrap();
+        assert_eq!(
+            repo.get(&docs).unwrap().unwrap().original_url,
+            "https://example.com/docs"
+        );
+    }
+
+    #[test]
+    fn entries_expire_and_writes_drop_aliases() {
+        let store = InMemoryRepo::new();
+        let repo = CachedLinkRepo::new(
+            store.for_tenant(&TenantId::default()),
+            CacheOptions {
+                negative_ttl: Duration::ZERO,
+                ..CacheOptions::default()
+            },
+        );
+        let (docs, wiki) = (Slug::new("docs").unwrap(), Slug::new("wiki").unwrap());
+
+        // Misses that expire at once are looked up again
+        assert_eq!(repo.get(&docs).unwrap(), None);
+        store.put(link("docs")).unwrap();
+        assert!(repo.get(&docs).unwrap().is_some());
+
+        repo.add_alias(&wiki, &docs).unwrap();
+        assert_eq!(repo.get(&wiki).unwrap().unwrap().slug, docs);
+        let mut off = link("docs");
+        off.is_active = false;
+        repo.update(&off).unwrap();
+        assert!(!repo.get(&wiki).unwrap().unwrap().is_active);
+
+        let renamed = Slug::new("handbook").unwrap();
+        repo.rename(&docs, &renamed).unwrap();
+        assert_eq!(repo.get(&docs).unwrap().unwrap().slug, renamed);
+        assert_eq!(repo.get(&wiki).unwrap().unwrap().slug, renamed);
+    }
+
+    #[test]
+    fn least_recently_used_entries_are_evicted() {
+        let store = InMemoryRepo::new();
+        let repo = CachedLinkRepo::new(
+            store.for_tenant(&TenantId::default()),
+            CacheOptions {
+                capacity: 2,
+                ..CacheOptions::default()
+            },
+        );
+        let slugs: Vec<Slug> = ["a", "b", "c"].map(|s| Slug::new(s).unwrap()).into();
+        for slug in &slugs {
+            assert_eq!(repo.get(slug).unwrap(), None);
+            if slug.as_str() == "b" {
+                // Touch "a" so "b" is the oldest when "c" arrives
+                repo.get(&slugs[0]).unwrap();
+            }
+        }
+        for slug in ["a", "b", "c"] {
+            store.put(link(slug)).unwrap();
+        }
+        // Cached misses still read as None; "b" is looked up last as that
+        // brings it back and evicts another
+        let still_missing = |i: usize| repo.get(&slugs[i]).unwrap().is_none();
+        assert!(still_missing(2) && still_missing(0));
+        assert!(!still_missing(1));
+    }
+
+    #[test]
+    fn clicks_update_cached_links_and_tenants_stay_apart() {
+        let store = InMemoryRepo::new();
+        let repo = CachedLinkRepo::new(
+            store.for_tenant(&TenantId::default()),
+            CacheOptions::default(),
+        );
+        let acme = repo.for_tenant(&TenantId::new("acme").unwrap());
+        let docs = Slug::new("docs").unwrap();
+
+        repo.put(link("docs")).unwrap();
+        assert!(repo.get(&docs).unwrap().is_some());
+        assert_eq!(acme.get(&docs).unwrap(), None);
+
+        repo.increment_click(&docs).unwrap();
+        repo.increment_click(&docs).unwrap();
+        assert_eq!(repo.get(&docs).unwrap().unwrap().click_count, 2);
+        assert!(matches!(
+            acme.increment_click(&docs),
+            Err(CoreError::NotFound)
+        ));
+    }
+
+    #[test]
+    fn backend_is_shared_and_invalidated() {
+        let store = InMemoryRepo::new();
+        let backend = Arc::new(MapBackend::default());
+        let first = CachedLinkRepo::new(
+            store.for_tenant(&TenantId::default()),
+            CacheOptions::default(),
+        )
+        .with_backend(backend.clone());
+        let second = CachedLinkRepo::new(
+            store.for_tenant(&TenantId::default()),
+            CacheOptions::default(),
+        )
+        .with_backend(backend.clone());
+        let docs = Slug::new("docs").unwrap();
+
+        first.put(link("docs")).unwrap();
+        assert!(first.get(&docs).unwrap().is_some());
+        assert!(matches!(
+            backend.get("docs").unwrap(),
+            Some(CachedLink::Found(_))
+        ));
+
+        // The second process finds the entry in the backend, not the store
+        store.delete(&docs, UNIX_EPOCH).unwrap();
+        assert_eq!(second.get(&docs).unwrap().unwrap().deleted_at, None);
+
+        first
+            .bulk_update_active(std::slice::from_ref(&docs), false, UNIX_EPOCH)
+            .unwrap();
+        assert_eq!(backend.get("docs").unwrap(), None);
+    }
+
+    #[test]
+    fn writers_without_a_local_layer_only_drop_backend_entries() {
+        let store = InMemoryRepo::new();
+        let backend = Arc::new(MapBackend::default());
+        let reader = CachedLinkRepo::new(
+            store.for_tenant(&TenantId::default()),
+            CacheOptions {
+                capacity: 0,
+                ..CacheOptions::default()
+            },
+        )
+        .with_backend(backend.clone());
+        let writer = CachedLinkRepo::new(
+            store.for_tenant(&TenantId::default()),
+            CacheOptions {
+                capacity: 0,
+                ..CacheOptions::default()
+            },
+        )
+        .with_backend(backend.clone());
+        let docs = Slug::new("docs").unwrap();
+        let guide = Slug::new("guide").unwrap();
+        store.put(link("docs")).unwrap();
+        store.add_alias(&guide, &docs).unwrap();
+
+        reader.get(&docs).unwrap();
+        reader.get(&guide).unwrap();
+        assert!(backend.get("guide").unwrap().is_some());
+        let mut disabled = store.get(&docs).unwrap().unwrap();
+        disabled.is_active = false;
+        writer.update(&disabled).unwrap();
+        assert_eq!(backend.get("docs").unwrap(), None);
+        assert_eq!(backend.get("guide").unwrap(), None);
+
+        // Changes made around the decorator are dropped on request
+        reader.get(&docs).unwrap();
+        store.delete(&docs, UNIX_EPOCH).unwrap();
+        writer.forget(std::slice::from_ref(&docs)).unwrap();
+        assert_eq!(backend.get("docs").unwrap(), None);
+        assert!(reader.get(&docs).unwrap().unwrap().deleted_at.is_some());
+    }
+
+    #[test]
+    fn domain_lookups_are_cached_per_tenant() {
+        use crate::adapters::memory_repo::InMemoryDomainRepo;
+
+        let store = InMemoryDomainRepo::new();
+        let repo = CachedDomainRepo::new(
+            store.for_tenant(&TenantId::default()),
+            CacheOptions::default(),
+        );
+        let acme = repo.for_tenant(&TenantId::new("acme").unwrap());
+        let domain = |host: &str| ShortDomain {
+            host: host.into(),
+            allowed_users: Vec::new(),
+            allowed_groups: Vec::new(),
+            created_at: UNIX_EPOCH,
+            created_by: UserEmail::new("a@acme.com").unwrap(),
+        };
+
+        assert_eq!(repo.get_domain("go.team.no").unwrap(), None);
+        store.put_domain(domain("go.team.no")).unwrap();
+        assert_eq!(repo.get_domain("go.team.no").unwrap(), None);
+
+        repo.put_domain(domain("go.team.no")).unwrap();
+        assert!(repo.get_domain("go.team.no").unwrap().is_some());
+        assert_eq!(acme.get_domain("go.team.no").unwrap(), None);
+
+        store.delete_domain("go.team.no").unwrap();
+        assert!(repo.get_domain("go.team.no").unwrap().is_some());
+        store.put_domain(domain("go.team.no")).unwrap();
+        repo.delete_domain("go.team.no").unwrap();
+        assert_eq!(repo.get_domain("go.team.no").unwrap(), None);
+    }
+}
@@ -0,0 +1,612 @@
+//! Batched click counting.
+//!
+//! Redirects report clicks to a [`ClickSink`] instead of writing the counter
+//! while the visitor waits. [`BufferedClickSink`] sums them per link and sends
+//! them to a [`ClickQueue`] as one [`ClickBatch`] once `max_batch` clicks are
+//! buffered or the oldest has waited `max_delay`, and whenever it is flushed
+//! (e.g. when the process shuts down). [`ClickAggregator`] takes the batches
+//! off the queue and applies them to storage, moving each link's counter once
+//! per batch.
+//!
+//! Delivery is at least once: a batch that fails to apply stays on the queue
+//! and is applied again later, so no click the queue accepted is lost. A batch
+//! failing partway is replaced by what it has left to apply, so the writes
+//! that went through aren't repeated. Counts still run high when a batch is
+//! applied but not acknowledged, e.g. the aggregator stops in between or the
+//! acknowledgement or the replacement fails to send: the whole batch is then
+//! applied again.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::{ClickEvent, ClickRepository, CoreError, LinkRepository, Slug, TenantId, TenantScoped};
+
+/// Clicks on one link within a batch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClickCount {
+    pub tenant: TenantId,
+    pub slug: Slug,
+    pub clicks: u64,
+}
+
+/// Clicks collected by a [`BufferedClickSink`], sent as one queue message.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClickBatch {
+    /// One entry per link, sorted by tenant and slug.
+    pub counts: Vec<ClickCount>,
+    /// Events for click analytics, oldest first.
+    pub events: Vec<(TenantId, ClickEvent)>,
+}
+
+impl ClickBatch {
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty() && self.events.is_empty()
+    }
+}
+
+/// A batch taken off a [`ClickQueue`], acknowledged by `receipt` once applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueuedBatch {
+    pub receipt: String,
+    pub batch: ClickBatch,
+}
+
+/// Transport carrying batches from sinks to the aggregator.
+pub trait ClickQueue: Send + Sync {
+    fn send(&self, batch: &ClickBatch) -> Result<(), CoreError>;
+    /// Up to `max` waiting batches. Received batches are hidden from other
+    /// receivers, and come back unless acknowledged in time.
+    fn receive(&self, max: usize) -> Result<Vec<QueuedBatch>, CoreError>;
+    /// Remove applied batches; unknown receipts are ignored.
+    fn ack(&self, receipts: &[String]) -> Result<(), CoreError>;
+}
+
+/// Port redirects report their clicks to.
+pub trait ClickSink: Send + Sync {
+    /// Count a click on `slug` in `tenant`.
+    fn increment(&self, tenant: &TenantId, slug: &Slug) -> Result<(), CoreError>;
+    /// Keep `event` for click analytics. Doesn't count the click.
+    fn record(&self, tenant: &TenantId, event: ClickEvent) -> Result<(), CoreError>;
+    /// Send everything reported so far.
+    fn flush(&self) -> Result<(), CoreError>;
+}
+
+/// When a [`BufferedClickSink`] sends its buffer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClickSinkOptions {
+    /// Clicks and events buffered before they are sent.
+    pub max_batch: usize,
+    /// Longest a click waits in the buffer, checked whenever one is reported
+    /// and by [`BufferedClickSink::flush_due`].
+    pub max_delay: Duration,
+}
+
+impl Default for ClickSinkOptions {
+    fn default() -> Self {
+        Self {
+            max_batch: 100,
+            max_delay: Duration::from_secs(10),
+        }
+    }
+}
+
+/// [`ClickSink`] sending batches to a [`ClickQueue`]; see the module docs.
+///
+/// A batch the queue rejects goes back into the buffer and is retried once
+/// `max_delay` has passed, or on `flush`. Counts are kept until then, but
+/// events beyond `max_batch` are dropped, oldest first, so a failing queue
+/// doesn't grow the buffer without bound.
+pub struct BufferedClickSink {
+    queue: Arc<dyn ClickQueue>,
+    buffer: Mutex<Buffer>,
+    options: ClickSinkOptions,
+}
+
+#[derive(Default)]
+struct Buffer {
+    counts: BTreeMap<(TenantId, Slug), u64>,
+    /// Sum of `counts`.
+    clicks: usize,
+    events: Vec<(TenantId, ClickEvent)>,
+    /// When the oldest buffered click was reported.
+    since: Option<Instant>,
+    /// No sends before this but flushes, after the queue failed.
+    retry_at: Option<Instant>,
+}
+
+impl Buffer {
+    fn len(&self) -> usize {
+        self.clicks + self.events.len()
+    }
+
+    fn take(&mut self) -> ClickBatch {
+        self.clicks = 0;
+        self.since = None;
+        ClickBatch {
+            counts: std::mem::take(&mut self.counts)
+                .into_iter()
+                .map(|((tenant, slug), clicks)| ClickCount {
+                    tenant,
+                    slug,
+                    clicks,
+                })
+                .collect(),
+            events: std::mem::take(&mut self.events),
+        }
+    }
+
+    /// Put back a batch that couldn't be sent.
+    fn restore(&mut self, batch: ClickBatch, max_events: usize) {
+        for count in batch.counts {
+            *self.counts.entry((count.tenant, count.slug)).or_default() += count.clicks;
+            self.clicks += usize::try_from(count.clicks).unwrap_or(usize::MAX);
+        }
+        let newer = std::mem::replace(&mut self.events, batch.events);
+        self.events.extend(newer);
+        let excess = self.events.len().saturating_sub(max_events);
+        self.events.drain(..excess);
+        self.since.get_or_insert_with(Instant::now);
+    }
+}
+
+impl BufferedClickSink {
+    pub fn new(queue: Arc<dyn ClickQueue>, options: ClickSinkOptions) -> Self {
+        Self {
+            queue,
+            buffer: Mutex::new(Buffer::default()),
+            options,
+        }
+    }
+
+    /// Send the buffer if its oldest click has waited `max_delay`. Call this
+    /// from a timer so clicks don't wait for the next one to be reported.
+    pub fn flush_due(&self) -> Result<(), CoreError> {
+        let batch = {
+            let mut buffer = self.buffer()?;
+            if !self.is_due(&buffer) {
+                return Ok(());
+            }
+            buffer.take()
+        };
+        self.send(batch)
+    }
+
+    /// Clicks and events waiting to be sent.
+    pub fn pending(&self) -> Result<usize, CoreError> {
+        Ok(self.buffer()?.len())
+    }
+
+    fn buffer(&self) -> Result<MutexGuard<'_, Buffer>, CoreError> {
+        self.buffer
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))
+    }
+
+    fn is_due(&self, buffer: &Buffer) -> bool {
+        let now = Instant::now();
+        buffer.len() > 0
+            && buffer.retry_at.is_none_or(|at| now >= at)
+            && (buffer.len() >= self.options.max_batch
+                || buffer
+                    .since
+                    .is_some_and(|since| now.duration_since(since) >= self.options.max_delay))
+    }
+
+    fn add(&self, f: impl FnOnce(&mut Buffer)) -> Result<(), CoreError> {
+        let batch = {
+            let mut buffer = self.buffer()?;
+            f(&mut buffer);
+            buffer.since.get_or_insert_with(Instant::now);
+            if !self.is_due(&buffer) {
+                return Ok(());
+            }
+            buffer.take()
+        };
+        self.send(batch)
+    }
+
+    fn send(&self, batch: ClickBatch) -> Result<(), CoreError> {
+        let result = self.queue.send(&batch);
+        let mut buffer = self.buffer()?;
+        match result {
+            Ok(()) => {
+                buffer.retry_at = None;
+                Ok(())
+            }
+            Err(e) => {
+                buffer.restore(batch, self.options.max_batch);
+                buffer.retry_at = Some(Instant::now() + self.options.max_delay);
+                Err(e)
+            }
+        }
+    }
+}
+
+impl ClickSink for BufferedClickSink {
+    fn increment(&self, tenant: &TenantId, slug: &Slug) -> Result<(), CoreError> {
+        self.add(|buffer| {
+            *buffer
+                .counts
+                .entry((tenant.clone(), slug.clone()))
+                .or_default() += 1;
+            buffer.clicks += 1;
+        })
+    }
+
+    fn record(&self, tenant: &TenantId, event: ClickEvent) -> Result<(), CoreError> {
+        self.add(|buffer| buffer.events.push((tenant.clone(), event)))
+    }
+
+    fn flush(&self) -> Result<(), CoreError> {
+        let batch = self.buffer()?.take();
+        if batch.is_empty() {
+            return Ok(());
+        }
+        self.send(batch)
+    }
+}
+
+/// Applies queued batches: counts through `links`, events through `clicks`.
+pub struct ClickAggregator<L, C> {
+    queue: Arc<dyn ClickQueue>,
+    links: L,
+    clicks: C,
+}
+
+impl<L, C> ClickAggregator<L, C>
+where
+    L: LinkRepository + TenantScoped,
+    C: ClickRepository + TenantScoped,
+{
+    pub fn new(queue: Arc<dyn ClickQueue>, links: L, clicks: C) -> Self {
+        Self {
+            queue,
+            links,
+            clicks,
+        }
+    }
+
+    /// Receive up to `max` batches and apply them. Returns how many were
+    /// received, so 0 means the queue is empty. Batches that failed stay
+    /// queued, reduced to what is left to apply; the first failure is returned
+    /// once the others are acknowledged.
+    pub fn run_once(&self, max: usize) -> Result<usize, CoreError> {
+        let received = self.queue.receive(max)?;
+        let mut applied = Vec::with_capacity(received.len());
+        let mut failure = None;
+        for queued in &received {
+            match self.apply_until_failure(&queued.batch) {
+                Ok(()) => applied.push(queued.receipt.clone()),
+                Err((rest, e)) => {
+                    // Swap the batch for its rest, or redelivery repeats the
+                    // writes that succeeded. If that fails, it is redelivered
+                    // whole.
+                    if rest != queued.batch && self.queue.send(&rest).is_ok() {
+                        applied.push(queued.receipt.clone());
+                    }
+                    failure.get_or_insert(e);
+                }
+            }
+        }
+        if !applied.is_empty() {
+            self.queue.ack(&applied)?;
+        }
+        match failure {
+            Some(e) => Err(e),
+            None => Ok(received.len()),
+        }
+    }
+
+    /// Apply batches until the queue is empty; returns how many were applied.
+    pub fn drain(&self, max: usize) -> Result<usize, CoreError> {
+        let mut total = 0;
+        loop {
+            match self.run_once(max)? {
+                0 => return Ok(total),
+                n => total += n,
+            }
+        }
+    }
+
+    /// Apply one batch. Counts of links deleted in the meantime are dropped.
+    /// Writes made before a failure stay applied.
+    pub fn apply(&self, batch: &ClickBatch) -> Result<(), CoreError> {
+        self.apply_until_failure(batch).map_err(|(_, e)| e)
+    }
+
+    /// [`Self::apply`], returning the part of `batch` not applied on failure.
+    fn apply_until_failure(&self, batch: &ClickBatch) -> Result<(), (ClickBatch, CoreError)> {
+        for (i, count) in batch.counts.iter().enumerate() {
+            match self
+                .links
+                .for_tenant(&count.tenant)
+                .add_clicks(&count.slug, count.clicks)
+            {
+                Ok(()) | Err(CoreError::NotFound) => {}
+                Err(e) => {
+                    let rest = ClickBatch {
+                        counts: batch.counts[i..].to_vec(),
+                        events: batch.events.clone(),
+                    };
+                    return Err((rest, e));
+                }
+            }
+        }
+        for (i, (tenant, event)) in batch.events.iter().enumerate() {
+            if let Err(e) = self.clicks.for_tenant(tenant).record_click(event.clone()) {
+                let rest = ClickBatch {
+                    counts: Vec::new(),
+                    events: batch.events[i..].to_vec(),
+                };
+                return Err((rest, e));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::memory_queue::MemoryClickQueue;
+    use crate::adapters::memory_repo::{InMemoryClickRepo, InMemoryRepo};
+    use crate::{ShortLink, UserEmail};
+    use std::time::UNIX_EPOCH;
+
+    fn slug(s: &str) -> Slug {
+        Slug::new(s).unwrap()
+    }
+
+    fn event(s: &str) -> ClickEvent {
+        ClickEvent {
+            slug: slug(s),
+            clicked_at: UNIX_EPOCH,
+            user_agent: Some("curl/8".into()),
+            referrer: None,
+            country: None,
+        }
+    }
+
+    fn options(max_batch: usize, max_delay: Duration) -> ClickSinkOptions {
+        ClickSinkOptions {
+            max_batch,
+            max_delay,
+        }
+    }
+
+    /// Queue that rejects every batch.
+    struct DownQueue;
+
+    impl ClickQueue for DownQueue {
+        fn send(&self, _batch: &ClickBatch) -> Result<(), CoreError> {
+            Err(CoreError::Repository("queue down".into()))
+        }
+
+        fn receive(&self, _max: usize) -> Result<Vec<QueuedBatch>, CoreError> {
+            Ok(Vec::new())
+        }
+
+        fn ack(&self, _receipts: &[String]) -> Result<(), CoreError> {
+            Ok(())
+        }
+    }
+
+    /// Click repository failing the first `failures` writes.
+    #[derive(Clone)]
+    struct FlakyClicks {
+        inner: Arc<InMemoryClickRepo>,
+        failures: Arc<Mutex<usize>>,
+    }
+
+    impl FlakyClicks {
+        fn new(failures: usize) -> Self {
+            Self {
+                inner: Arc::new(InMemoryClickRepo::new()),
+                failures: Arc::new(Mutex::new(failures)),
+            }
+        }
+    }
+
+    impl TenantScoped for FlakyClicks {
+        fn for_tenant(&self, tenant: &TenantId) -> Self {
+            Self {
+                inner: Arc::new(self.inner.for_tenant(tenant)),
+                failures: Arc::clone(&self.failures),
+            }
+        }
+
+        fn tenant(&self) -> &TenantId {
+            self.inner.tenant()
+        }
+    }
+
+    impl ClickRepository for FlakyClicks {
+        fn record_click(&self, event: ClickEvent) -> Result<(), CoreError> {
+            let mut failures = self.failures.lock().unwrap();
+            if *failures > 0 {
+                *failures -= 1;
+                return Err(CoreError::Repository("clicks table down".into()));
+            }
+            self.inner.record_click(event)
+        }
+
+        fn get_clicks(&self, slug: &Slug, limit: usize) -> Result<Vec<ClickEvent>, CoreError> {
+            self.inner.get_clicks(slug, limit)
+        }
+
+        fn get_click_count_since(
+            &self,
+            slug: &Slug,
+            since: std::time::SystemTime,
+        ) -> Result<u64, CoreError> {
+            self.inner.get_click_count_since(slug, since)
+        }
+
+        fn get_clicks_by_day(
+            &self,
+            slug: &Slug,
+            days: usize,
+        ) -> Result<Vec<(String, u64)>, CoreError> {
+            self.inner.get_clicks_by_day(slug, days)
+        }
+    }
+
+    /// Repo holding link `docs` in `tenant`.
+    fn links_with_docs(tenant: &TenantId) -> InMemoryRepo {
+        let links = InMemoryRepo::new();
+        let docs = ShortLink::new(
+            slug("docs"),
+            "https://example.com".into(),
+            UNIX_EPOCH,
+            UserEmail::new("a@acme.com").unwrap(),
+        );
+        links.for_tenant(tenant).put(docs).unwrap();
+        links
+    }
+
+    fn docs_clicks(tenant: &TenantId, clicks: u64) -> ClickCount {
+        ClickCount {
+            tenant: tenant.clone(),
+            slug: slug("docs"),
+            clicks,
+        }
+    }
+
+    #[test]
+    fn sends_summed_counts_once_the_batch_is_full() {
+        let queue = Arc::new(MemoryClickQueue::new());
+        let sink = BufferedClickSink::new(queue.clone(), options(3, Duration::from_secs(3600)));
+        let acme = TenantId::new("acme").unwrap();
+        sink.increment(&acme, &slug("docs")).unwrap();
+        sink.increment(&acme, &slug("docs")).unwrap();
+        assert_eq!(queue.len(), 0);
+        sink.increment(&TenantId::default(), &slug("docs")).unwrap();
+        assert_eq!(sink.pending().unwrap(), 0);
+
+        let received = queue.receive(10).unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(
+            received[0].batch.counts,
+            vec![
+                ClickCount {
+                    tenant: acme,
+                    slug: slug("docs"),
+                    clicks: 2,
+                },
+                ClickCount {
+                    tenant: TenantId::default(),
+                    slug: slug("docs"),
+                    clicks: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn sends_when_the_oldest_click_is_due_or_on_flush() {
+        let queue = Arc::new(MemoryClickQueue::new());
+        let waiting =
+            BufferedClickSink::new(queue.clone(), options(100, Duration::from_secs(3600)));
+        waiting.increment(&TenantId::default(), &slug("a")).unwrap();
+        waiting.flush_due().unwrap();
+        assert_eq!(queue.len(), 0);
+        waiting.flush().unwrap();
+        assert_eq!(queue.len(), 1);
+        waiting.flush().unwrap();
+        assert_eq!(queue.len(), 1, "nothing left to send");
+
+        let due = BufferedClickSink::new(queue.clone(), options(100, Duration::ZERO));
+        due.record(&TenantId::default(), event("a")).unwrap();
+        assert_eq!(queue.len(), 2);
+    }
+
+    #[test]
+    fn failed_sends_keep_counts_and_one_batch_of_events() {
+        let sink =
+            BufferedClickSink::new(Arc::new(DownQueue), options(2, Duration::from_secs(3600)));
+        let tenant = TenantId::default();
+        sink.record(&tenant, event("a")).unwrap();
+        assert!(sink.increment(&tenant, &slug("a")).is_err());
+        assert_eq!(sink.pending().unwrap(), 2);
+
+        // Backing off: full batches wait for `max_delay` instead of retrying.
+        sink.record(&tenant, event("b")).unwrap();
+        sink.record(&tenant, event("c")).unwrap();
+        sink.increment(&tenant, &slug("a")).unwrap();
+        assert_eq!(sink.pending().unwrap(), 5);
+
+        assert!(sink.flush().is_err());
+        let buffer = sink.buffer().unwrap();
+        assert_eq!(buffer.counts[&(tenant.clone(), slug("a"))], 2);
+        let kept: Vec<_> = buffer.events.iter().map(|(_, e)| e.slug.clone()).collect();
+        assert_eq!(kept, vec![slug("b"), slug("c")]);
+    }
+
+    #[test]
+    fn aggregator_applies_counts_and_events_per_tenant() {
+        let queue = Arc::new(MemoryClickQueue::new());
+        let acme = TenantId::new("acme").unwrap();
+        let links = links_with_docs(&acme);
+        let clicks = InMemoryClickRepo::new();
+
+        queue
+            .send(&ClickBatch {
+                counts: vec![
+                    docs_clicks(&acme, 3),
+                    ClickCount {
+                        tenant: acme.clone(),
+                        slug: slug("gone"),
+                        clicks: 1,
+                    },
+                ],
+                events: vec![(acme.clone(), event("docs"))],
+            })
+            .unwrap();
+        let aggregator = ClickAggregator::new(queue.clone(), links, clicks);
+        assert_eq!(aggregator.drain(10).unwrap(), 1);
+        assert!(queue.is_empty());
+
+        let links = aggregator.links.for_tenant(&acme);
+        assert_eq!(links.get(&slug("docs")).unwrap().unwrap().click_count, 3);
+        let recorded = aggregator.clicks.for_tenant(&acme);
+        assert_eq!(recorded.get_clicks(&slug("docs"), 10).unwrap().len(), 1);
+        let default = aggregator.clicks.for_tenant(&TenantId::default());
+        assert!(default.get_clicks(&slug("docs"), 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn batches_failing_partway_are_requeued_without_what_was_applied() {
+        let queue = Arc::new(MemoryClickQueue::new().with_visibility_timeout(Duration::ZERO));
+        let acme = TenantId::new("acme").unwrap();
+        let batch = ClickBatch {
+            counts: vec![docs_clicks(&acme, 3)],
+            events: vec![(acme.clone(), event("docs")), (acme.clone(), event("docs"))],
+        };
+        queue.send(&batch).unwrap();
+        let aggregator =
+            ClickAggregator::new(queue.clone(), links_with_docs(&acme), FlakyClicks::new(1));
+
+        assert!(aggregator.run_once(10).is_err());
+        let requeued = queue.receive(10).unwrap();
+        assert_eq!(requeued.len(), 1, "the original batch is acknowledged");
+        assert_eq!(
+            requeued[0].batch,
+            ClickBatch {
+                counts: Vec::new(),
+                events: batch.events.clone(),
+            }
+        );
+
+        assert_eq!(aggregator.drain(10).unwrap(), 1);
+        assert!(queue.is_empty());
+        let link = aggregator
+            .links
+            .for_tenant(&acme)
+            .get(&slug("docs"))
+            .unwrap();
+        assert_eq!(link.unwrap().click_count, 3, "counted once");
+        let recorded = aggregator.clicks.for_tenant(&acme);
+        assert_eq!(recorded.get_clicks(&slug("docs"), 10).unwrap().len(), 2);
+    }
+}
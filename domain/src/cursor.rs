@@ -0,0 +1,108 @@
+//! Opaque cursors for paging through list results.
+//!
+//! A cursor marks the position after the last item of a page. Adapters decide
+//! what the position holds (a sort key, a DynamoDB `LastEvaluatedKey`); callers
+//! only hand it back as `ListOptions::cursor`. Parts are hex-encoded so cursors
+//! travel unescaped in query strings.
+
+use crate::{CoreError, LinkSort, SortDirection};
+
+/// Separates the parts of a cursor before encoding.
+const SEPARATOR: char = '\u{1f}';
+
+/// Encode `parts` as a cursor.
+pub fn encode<S: AsRef<str>>(parts: &[S]) -> String {
+    let joined = parts
+        .iter()
+        .map(AsRef::as_ref)
+        .collect::<Vec<_>>()
+        .join(&SEPARATOR.to_string());
+    joined.bytes().map(|b| format!("{b:02x}")).collect()
+}
+
+/// The parts of a cursor made by [`encode`].
+pub fn decode(cursor: &str) -> Result<Vec<String>, CoreError> {
+    if cursor.is_empty() || !cursor.len().is_multiple_of(2) {
+        return Err(CoreError::InvalidCursor);
+    }
+    let bytes = (0..cursor.len())
+        .step_by(2)
+        .map(|i| {
+            cursor
+                .get(i..i + 2)
+                .and_then(|h| u8::from_str_radix(h, 16).ok())
+        })
+        .collect::<Option<Vec<u8>>>()
+        .ok_or(CoreError::InvalidCursor)?;
+    let joined = String::from_utf8(bytes).map_err(|_| CoreError::InvalidCursor)?;
+    Ok(joined.split(SEPARATOR).map(str::to_string).collect())
+}
+
+/// Cursor after a link in a `list_paginated` ordering: the sort it belongs
+/// to, the link's value for the sort field (in whatever unit the adapter sorts
+/// by, `None` when the link has none) and its slug key, which breaks ties.
+pub fn encode_link_position(
+    sort: LinkSort,
+    direction: SortDirection,
+    value: Option<u128>,
+    slug_key: &str,
+) -> String {
+    let value = value.map(|v| v.to_string()).unwrap_or_default();
+    encode(&[sort.as_str(), direction.as_str(), &value, slug_key])
+}
+
+/// Inverse of [`encode_link_position`]. Cursors issued for another ordering
+/// are rejected.
+pub fn decode_link_position(
+    cursor: &str,
+    sort: LinkSort,
+    direction: SortDirection,
+) -> Result<(Option<u128>, String), CoreError> {
+    match decode(cursor)?.as_slice() {
+        [s, d, value, key] if s == sort.as_str() && d == direction.as_str() => {
+            let value = match value.as_str() {
+                "" => None,
+                v => Some(v.parse().map_err(|_| CoreError::InvalidCursor)?),
+            };
+            Ok((value, key.clone()))
+        }
+        _ => Err(CoreError::InvalidCursor),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursors_roundtrip_and_reject_garbage() {
+        let cursor = encode(&["slug", "S", "go.acme.com:docs"]);
+        assert!(cursor.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(decode(&cursor).unwrap(), ["slug", "S", "go.acme.com:docs"]);
+
+        let (sort, dir) = (LinkSort::ClickCount, SortDirection::Asc);
+        let position = encode_link_position(sort, dir, Some(42), "hr/onboarding");
+        assert_eq!(
+            decode_link_position(&position, sort, dir).unwrap(),
+            (Some(42), "hr/onboarding".to_string())
+        );
+        let unset = encode_link_position(LinkSort::ExpiresAt, dir, None, "a");
+        assert_eq!(
+            decode_link_position(&unset, LinkSort::ExpiresAt, dir).unwrap(),
+            (None, "a".to_string())
+        );
+        let wrong_order = [(LinkSort::CreatedAt, dir), (sort, SortDirection::Desc)];
+        for (s, d) in wrong_order {
+            assert!(matches!(
+                decode_link_position(&position, s, d),
+                Err(CoreError::InvalidCursor)
+            ));
+        }
+        for bad in ["", "abc", "zz", "ff", &encode(&["x"]), &encode(&["x", "y"])] {
+            assert!(matches!(
+                decode_link_position(bad, sort, dir),
+                Err(CoreError::InvalidCursor)
+            ));
+        }
+    }
+}
@@ -0,0 +1,269 @@
+//! Nested link groups. A role held on a group also applies to every group
+//! below it, so the effective role is the highest one found on the group or
+//! any of its ancestors.
+
+use std::collections::VecDeque;
+
+use crate::{CoreError, GroupRepository, GroupRole, LinkGroup, UserEmail};
+
+/// Maximum nesting depth. Also bounds every walk in case a cycle slips into
+/// storage.
+pub const MAX_GROUP_DEPTH: usize = 8;
+
+/// Ids of `group_id` and its ancestors, nearest first.
+pub fn lineage<R: GroupRepository + ?Sized>(
+    repo: &R,
+    group_id: &str,
+) -> Result<Vec<String>, CoreError> {
+    let mut ids = vec![group_id.to_string()];
+    let mut next = repo.get_group(group_id)?.and_then(|g| g.parent_id);
+    while let Some(id) = next {
+        if ids.len() >= MAX_GROUP_DEPTH || ids.contains(&id) {
+            break;
+        }
+        next = repo.get_group(&id)?.and_then(|g| g.parent_id);
+        ids.push(id);
+    }
+    Ok(ids)
+}
+
+/// Highest role the user holds on the group or any of its ancestors.
+pub fn effective_role<R: GroupRepository + ?Sized>(
+    repo: &R,
+    group_id: &str,
+    user_email: &UserEmail,
+) -> Result<Option<GroupRole>, CoreError> {
+    let mut role = None;
+    for id in lineage(repo, group_id)? {
+        if let Some(member) = repo.get_member(&id, user_email)? {
+            role = role.max(Some(member.role));
+        }
+    }
+    Ok(role)
+}
+
+/// Extend a user's direct groups with every group below them. Each group
+/// carries the highest role that reaches it; direct groups come first.
+pub fn with_inherited<R: GroupRepository + ?Sized>(
+    repo: &R,
+    direct: Vec<(LinkGroup, GroupRole)>,
+) -> Result<Vec<(LinkGroup, GroupRole)>, CoreError> {
+    let mut out: Vec<(LinkGroup, GroupRole)> = Vec::new();
+    let mut queue: VecDeque<_> = direct.into_iter().map(|(g, r)| (g, r, 0)).collect();
+    while let Some((group, role, depth)) = queue.pop_front() {
+        match out.iter_mut().find(|(g, _)| g.id == group.id) {
+            Some((_, existing)) if *existing >= role => continue,
+            Some((_, existing)) => *existing = role,
+            None => out.push((group.clone(), role)),
+        }
+        if depth < MAX_GROUP_DEPTH {
+            for child in repo.list_child_groups(&group.id)? {
+                queue.push_back((child, role, depth + 1));
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Check that `group_id` may be placed below `parent_id`: the parent exists,
+/// the move creates no cycle and the tree stays within [`MAX_GROUP_DEPTH`].
+pub fn check_parent<R: GroupRepository + ?Sized>(
+    repo: &R,
+    group_id: &str,
+    parent_id: &str,
+) -> Result<(), CoreError> {
+    if group_id == parent_id {
+        return Err(CoreError::Conflict(
+            "a group cannot be its own parent".into(),
+        ));
+    }
+    if repo.get_group(parent_id)?.is_none() {
+        return Err(CoreError::NotFound);
+    }
+    let above = lineage(repo, parent_id)?;
+    if above.iter().any(|id| id == group_id) {
+        return Err(CoreError::Conflict(
+            "a group cannot be moved below its own subgroup".into(),
+        ));
+    }
+    if above.len() + subtree_height(repo, group_id)? > MAX_GROUP_DEPTH {
+        return Err(CoreError::Conflict(format!(
+            "groups can be nested at most {MAX_GROUP_DEPTH} levels deep"
+        )));
+    }
+    Ok(())
+}
+
+/// Number of levels in the subtree rooted at `group_id`, counting the group.
+fn subtree_height<R: GroupRepository + ?Sized>(
+    repo: &R,
+    group_id: &str,
+) -> Result<usize, CoreError> {
+    let mut height = 0;
+    let mut level = vec![group_id.to_string()];
+    while !level.is_empty() && height <= MAX_GROUP_DEPTH {
+        height += 1;
+        let mut next = Vec::new();
+        for id in &level {
+            next.extend(repo.list_child_groups(id)?.into_iter().map(|g| g.id));
+        }
+        level = next;
+    }
+    Ok(height)
+}
+
+/// Order groups depth-first for display, pairing each with its depth. Groups
+/// whose parent is not in the list are shown as roots; siblings sort by name.
+pub fn tree_order<T>(mut items: Vec<(LinkGroup, T)>) -> Vec<(LinkGroup, T, usize)> {
+    items.sort_by(|a, b| a.0.name.cmp(&b.0.name));
+    let ids: Vec<String> = items.iter().map(|(g, _)| g.id.clone()).collect();
+    let roots: Vec<usize> = items
+        .iter()
+        .enumerate()
+        .filter(|(_, (g, _))| g.parent_id.as_ref().is_none_or(|p| !ids.contains(p)))
+        .map(|(i, _)| i)
+        .collect();
+    let mut pending: Vec<Option<(LinkGroup, T)>> = items.into_iter().map(Some).collect();
+
+    let mut out = Vec::with_capacity(pending.len());
+    // Groups caught in a cycle are unreachable from any root; they go last
+    let starts: Vec<usize> = roots.into_iter().chain(0..pending.len()).collect();
+    for start in starts {
+        let mut stack = vec![(start, 0)];
+        while let Some((idx, depth)) = stack.pop() {
+            let Some((group, value)) = pending[idx].take() else {
+                continue;
+            };
+            // Push in reverse so the first sibling by name is visited first
+            for i in (0..pending.len()).rev() {
+                if pending[i]
+                    .as_ref()
+                    .is_some_and(|(c, _)| c.parent_id.as_deref() == Some(group.id.as_str()))
+                {
+                    stack.push((i, depth + 1));
+                }
+            }
+            out.push((group, value, depth));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::memory_repo::InMemoryGroupRepo;
+    use crate::{GroupMember, GroupSettings};
+    use std::time::SystemTime;
+
+    fn group(id: &str, parent: Option<&str>) -> LinkGroup {
+        LinkGroup {
+            id: id.into(),
+            name: id.into(),
+            description: None,
+            created_at: SystemTime::UNIX_EPOCH,
+            created_by: UserEmail::new("owner@example.com").unwrap(),
+            settings: GroupSettings::default(),
+            parent_id: parent.map(str::to_string),
+        }
+    }
+
+    fn member(group_id: &str, email: &UserEmail, role: GroupRole) -> GroupMember {
+        GroupMember {
+            group_id: group_id.into(),
+            user_email: email.clone(),
+            role,
+            added_at: SystemTime::UNIX_EPOCH,
+            added_by: email.clone(),
+        }
+    }
+
+    #[test]
+    fn roles_inherit_down_the_tree() {
+        let repo = InMemoryGroupRepo::new();
+        repo.create_group(group("dept", None)).unwrap();
+        repo.create_group(group("team", Some("dept"))).unwrap();
+        repo.create_group(group("squad", Some("team"))).unwrap();
+        repo.create_group(group("other", None)).unwrap();
+        let u = UserEmail::new("u@example.com").unwrap();
+        repo.add_member(member("dept", &u, GroupRole::Editor))
+            .unwrap();
+        repo.add_member(member("team", &u, GroupRole::Viewer))
+            .unwrap();
+
+        assert_eq!(
+            effective_role(&repo, "squad", &u).unwrap(),
+            Some(GroupRole::Editor)
+        );
+        assert_eq!(effective_role(&repo, "other", &u).unwrap(), None);
+        assert_eq!(lineage(&repo, "squad").unwrap(), ["squad", "team", "dept"]);
+
+        let groups = repo.get_user_groups(&u).unwrap();
+        let mut roles: Vec<_> = groups.iter().map(|(g, r)| (g.id.as_str(), *r)).collect();
+        roles.sort();
+        assert_eq!(
+            roles,
+            [
+                ("dept", GroupRole::Editor),
+                ("squad", GroupRole::Editor),
+                ("team", GroupRole::Editor),
+            ]
+        );
+    }
+
+    #[test]
+    fn parent_checks_reject_cycles_and_deep_trees() {
+        let repo = InMemoryGroupRepo::new();
+        repo.create_group(group("a", None)).unwrap();
+        repo.create_group(group("b", Some("a"))).unwrap();
+        assert!(matches!(
+            check_parent(&repo, "a", "a"),
+            Err(CoreError::Conflict(_))
+        ));
+        assert!(matches!(
+            check_parent(&repo, "a", "b"),
+            Err(CoreError::Conflict(_))
+        ));
+        assert!(matches!(
+            check_parent(&repo, "a", "missing"),
+            Err(CoreError::NotFound)
+        ));
+
+        let mut parent = "b".to_string();
+        for i in 2..MAX_GROUP_DEPTH {
+            let id = format!("g{i}");
+            repo.create_group(group(&id, Some(&parent))).unwrap();
+            parent = id;
+        }
+        assert!(matches!(
+            check_parent(&repo, "new", &parent),
+            Err(CoreError::Conflict(_))
+        ));
+        assert!(check_parent(&repo, "new", "b").is_ok());
+    }
+
+    #[test]
+    fn tree_order_nests_children_under_parents() {
+        let groups = vec![
+            (group("zeta", None), ()),
+            (group("team-b", Some("dept")), ()),
+            (group("dept", None), ()),
+            (group("team-a", Some("dept")), ()),
+            (group("orphan", Some("gone")), ()),
+        ];
+        let ordered: Vec<_> = tree_order(groups)
+            .into_iter()
+            .map(|(g, _, depth)| (g.id, depth))
+            .collect();
+        assert_eq!(
+            ordered,
+            [
+                ("dept".to_string(), 0),
+                ("team-a".to_string(), 1),
+                ("team-b".to_string(), 1),
+                ("orphan".to_string(), 0),
+                ("zeta".to_string(), 0),
+            ]
+        );
+    }
+}
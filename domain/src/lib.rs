@@ -4,33 +4,103 @@
 //! the domain types, ports (traits), and error definitions. Keep adapters and
 //! IO concerns out of this crate.
 
+use std::borrow::Cow;
 use std::error::Error;
 use std::fmt::{Display, Formatter};
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 
-/// A URL-safe slug identifying a short link.
+/// A URL-safe slug identifying a short link. Slugs are unique per short
+/// domain; a slug without a domain lives on the default one. Slugs may be
+/// namespaced with `/` (`hr/onboarding`, `events/2026/kickoff`).
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct Slug(String);
+pub struct Slug {
+    name: String,
+    domain: Option<String>,
+}
 
 impl Slug {
+    /// Separates the short domain from the slug in storage keys. Never valid
+    /// in slugs or hosts.
+    pub const DOMAIN_SEPARATOR: char = ':';
+    /// Separates namespace segments, e.g. `events/2026/kickoff`.
+    pub const NAMESPACE_SEPARATOR: char = '/';
+
     pub fn new<S: Into<String>>(s: S) -> Result<Self, CoreError> {
         let val = s.into();
         // Very light validation for now: non-empty and ascii
         if val.is_empty() {
             return Err(CoreError::InvalidSlug("empty".into()));
         }
-        if !val
-            .chars()
-            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
-        {
+        if val.split(Self::NAMESPACE_SEPARATOR).any(str::is_empty) {
+            return Err(CoreError::InvalidSlug("empty namespace segment".into()));
+        }
+        if !val.chars().all(|c| {
+            c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == Self::NAMESPACE_SEPARATOR
+        }) {
             return Err(CoreError::InvalidSlug("invalid characters".into()));
         }
-        Ok(Self(val))
+        Ok(Self {
+            name: val,
+            domain: None,
+        })
     }
 
+    /// The slug as it appears in the short URL path.
     pub fn as_str(&self) -> &str {
-        &self.0
+        &self.name
+    }
+
+    /// Move the slug to a short domain (`None` for the default domain).
+    pub fn on_domain(mut self, domain: Option<String>) -> Self {
+        self.domain = domain;
+        self
+    }
+
+    /// Short domain the slug lives on; `None` for the default domain.
+    pub fn domain(&self) -> Option<&str> {
+        self.domain.as_deref()
+    }
+
+    /// Namespaces enclosing the slug, innermost first: `events/2026/kickoff`
+    /// yields `events/2026`, then `events`.
+    pub fn namespaces(&self) -> impl Iterator<Item = &str> {
+        let name = self.name.as_str();
+        name.rmatch_indices(Self::NAMESPACE_SEPARATOR)
+            .map(move |(i, _)| &name[..i])
+    }
+
+    /// Key identifying the slug in storage and in admin API paths: the bare
+    /// slug on the default domain, `host:slug` otherwise.
+    pub fn key(&self) -> Cow<'_, str> {
+        match &self.domain {
+            None => Cow::Borrowed(&self.name),
+            Some(d) => Cow::Owned(format!("{d}{}{}", Self::DOMAIN_SEPARATOR, self.name)),
+        }
+    }
+
+    /// Inverse of [`Slug::key`].
+    pub fn from_key(key: &str) -> Result<Self, CoreError> {
+        match key.split_once(Self::DOMAIN_SEPARATOR) {
+            None => Self::new(key),
+            Some((domain, name)) => Ok(Self::new(name)?.on_domain(Some(normalize_host(domain)?))),
+        }
+    }
+}
+
+/// Lowercase a short-domain host and check it is a plain host name (no port,
+/// scheme or path).
+pub fn normalize_host(host: &str) -> Result<String, CoreError> {
+    let host = host.trim().to_ascii_lowercase();
+    if host.is_empty()
+        || host.len() > 253
+        || host.starts_with(['.', '-'])
+        || !host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+    {
+        return Err(CoreError::InvalidUrl(format!("invalid domain: {host}")));
     }
+    Ok(host)
 }
 
 /// Email address of the user creating links.
@@ -52,6 +122,146 @@ impl UserEmail {
     }
 }
 
+/// Identifier of an organization (tenant). Every repository handle works on
+/// the data partition of exactly one tenant.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TenantId(String);
+
+impl TenantId {
+    /// Tenant that owns data created before organizations existed.
+    pub const DEFAULT: &'static str = "default";
+
+    /// Lowercase letters, digits and `-`, at most 32 characters. Tenant ids end
+    /// up in storage keys, so anything else is rejected.
+    pub fn new<S: Into<String>>(s: S) -> Result<Self, CoreError> {
+        let val = s.into();
+        if val.is_empty()
+            || val.len() > 32
+            || !val
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        {
+            return Err(CoreError::InvalidTenant(val));
+        }
+        Ok(Self(val))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.0 == Self::DEFAULT
+    }
+}
+
+impl Default for TenantId {
+    fn default() -> Self {
+        Self(Self::DEFAULT.to_string())
+    }
+}
+
+/// A client organization with its own data partition, sign-in domains and
+/// admins.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Organization {
+    pub id: TenantId,
+    pub name: String,
+    /// Request hosts (admin UI and short-link domains) served for this organization.
+    pub hosts: Vec<String>,
+    /// Email domains allowed to sign in. Empty falls back to the deployment-wide
+    /// allowed domain.
+    pub auth_domains: Vec<String>,
+    /// Users with admin rights inside this organization only.
+    pub admins: Vec<UserEmail>,
+    pub settings: OrganizationSettings,
+    pub created_at: SystemTime,
+}
+
+impl Organization {
+    /// The organization used when a request matches no configured one.
+    pub fn fallback() -> Self {
+        Self {
+            id: TenantId::default(),
+            name: TenantId::DEFAULT.to_string(),
+            hosts: Vec::new(),
+            auth_domains: Vec::new(),
+            admins: Vec::new(),
+            settings: OrganizationSettings::default(),
+            created_at: SystemTime::UNIX_EPOCH,
+        }
+    }
+
+    pub fn is_admin(&self, email: &str) -> bool {
+        self.admins
+            .iter()
+            .any(|a| a.as_str().eq_ignore_ascii_case(email))
+    }
+
+    pub fn serves_host(&self, host: &str) -> bool {
+        let host = host.split(':').next().unwrap_or(host);
+        self.hosts.iter().any(|h| h.eq_ignore_ascii_case(host))
+    }
+
+    pub fn allows_domain(&self, domain: &str) -> bool {
+        self.auth_domains
+            .iter()
+            .any(|d| d.eq_ignore_ascii_case(domain))
+    }
+}
+
+/// Organization-wide settings.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct OrganizationSettings {
+    /// Domain used to build short URLs for this organization's links.
+    pub shortlink_domain: Option<String>,
+    /// Target hosts any link in the organization may point at (same patterns as
+    /// group settings). Empty means any host.
+    pub allowed_hosts: Vec<String>,
+}
+
+impl OrganizationSettings {
+    /// Check a link's target host against the organization's allowed hosts.
+    pub fn check_link(&self, link: &ShortLink) -> Result<(), CoreError> {
+        check_allowed_host(&self.allowed_hosts, &link.original_url, "this organization")
+    }
+}
+
+/// An extra host short links can be created on, e.g. `go.team.no`. Slugs are
+/// unique per domain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShortDomain {
+    /// Normalized host name, see [`normalize_host`].
+    pub host: String,
+    /// Users who may create links on the domain. When both this and
+    /// `allowed_groups` are empty, everyone may.
+    pub allowed_users: Vec<UserEmail>,
+    /// Members of these groups (or groups below them) may create links on the domain.
+    pub allowed_groups: Vec<String>,
+    pub created_at: SystemTime,
+    pub created_by: UserEmail,
+}
+
+impl ShortDomain {
+    /// Whether a user belonging to `group_ids` may create links on the domain.
+    pub fn may_use(&self, email: &UserEmail, group_ids: &[String]) -> bool {
+        (self.allowed_users.is_empty() && self.allowed_groups.is_empty())
+            || self.allowed_users.contains(email)
+            || self.allowed_groups.iter().any(|g| group_ids.contains(g))
+    }
+}
+
+/// A slug namespace (`hr`, `events/2026`) reserved by a group. Only editors of
+/// the group may create slugs under it; unreserved namespaces are open to all.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Namespace {
+    /// Namespace path, a valid slug without leading or trailing `/`.
+    pub path: String,
+    pub group_id: String,
+    pub created_at: SystemTime,
+    pub created_by: UserEmail,
+}
+
 /// Input data for creating a new short link.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct NewLink {
@@ -86,6 +296,8 @@ pub struct ShortLink {
     pub deleted_at: Option<SystemTime>,
     /// Optional group ID for organizing links.
     pub group_id: Option<String>,
+    /// Who may follow the link. Workspace-only links require a signed-in visitor.
+    pub visibility: LinkVisibility,
 }
 
 impl ShortLink {
@@ -110,6 +322,7 @@ impl ShortLink {
             redirect_delay: None,
             deleted_at: None,
             group_id: None,
+            visibility: LinkVisibility::Public,
         }
     }
 
@@ -128,10 +341,89 @@ impl ShortLink {
         self.deleted_at.is_some()
     }
 
+    /// The target URL in canonical form; links with equal keys point at the
+    /// same page. See [`validate::canonical_url`].
+    pub fn target_key(&self) -> String {
+        validate::canonical_url(&self.original_url)
+    }
+
+    /// Host of the target URL, lowercased and without port.
+    pub fn target_host(&self) -> Option<String> {
+        validate::url_host(&self.original_url)
+    }
+
+    /// Whether this link can be handed out instead of creating a new one to
+    /// the same target: it is available at `now`, lives on `domain`, and
+    /// belongs to `group_id` when given, otherwise to `owner` outside any group.
+    pub fn dedupes(
+        &self,
+        owner: &UserEmail,
+        group_id: Option<&str>,
+        domain: Option<&str>,
+        now: SystemTime,
+    ) -> bool {
+        let owned = match group_id {
+            Some(gid) => self.group_id.as_deref() == Some(gid),
+            None => self.group_id.is_none() && self.created_by.as_str() == owner.as_str(),
+        };
+        owned && self.slug.domain() == domain && self.is_available(now)
+    }
+
     /// Check if the link is available for redirect (active, not expired, not scheduled, not deleted).
     pub fn is_available(&self, now: SystemTime) -> bool {
         self.is_active && !self.is_expired(now) && !self.is_scheduled(now) && !self.is_deleted()
     }
+
+    /// Check if following the link requires an authenticated workspace visitor.
+    pub fn requires_login(&self) -> bool {
+        self.visibility == LinkVisibility::Workspace
+    }
+
+    /// Whether `email` may manage the link's collaborators: system admins
+    /// (`is_admin`), the creator, and admins of the link's group. `group_role`
+    /// looks up the user's effective role on a group and is only called for
+    /// links in one.
+    pub fn can_manage_collaborators(
+        &self,
+        email: &str,
+        is_admin: bool,
+        group_role: impl FnOnce(&str) -> Result<Option<GroupRole>, CoreError>,
+    ) -> Result<bool, CoreError> {
+        if is_admin || self.created_by.as_str() == email {
+            return Ok(true);
+        }
+        match self.group_id.as_deref() {
+            Some(gid) => Ok(group_role(gid)?.is_some_and(|r| r.can_manage())),
+            None => Ok(false),
+        }
+    }
+}
+
+/// Who is allowed to follow a short link.
+#[derive(Clone, Debug, PartialEq, Eq, Copy, Default)]
+pub enum LinkVisibility {
+    /// Anyone with the short URL is redirected.
+    #[default]
+    Public,
+    /// Only visitors signed in with an allowed workspace account are redirected.
+    Workspace,
+}
+
+impl LinkVisibility {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LinkVisibility::Public => "public",
+            LinkVisibility::Workspace => "workspace",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "public" => Some(LinkVisibility::Public),
+            "workspace" => Some(LinkVisibility::Workspace),
+            _ => None,
+        }
+    }
 }
 
 /// A link group for organizing links and sharing access.
@@ -142,6 +434,102 @@ pub struct LinkGroup {
     pub description: Option<String>,
     pub created_at: SystemTime,
     pub created_by: UserEmail,
+    pub settings: GroupSettings,
+    /// Parent group; members of the parent inherit their role here.
+    pub parent_id: Option<String>,
+}
+
+/// Per-group defaults and limits for links created in (or moved into) a group.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct GroupSettings {
+    /// Expiry applied to new links that don't set one, relative to creation.
+    pub default_expiry_secs: Option<u64>,
+    /// Redirect delay applied to new links that don't set one.
+    pub default_redirect_delay: Option<u32>,
+    /// Target hosts links may point at (`*.example.com` matches subdomains).
+    /// Empty means any host.
+    pub allowed_hosts: Vec<String>,
+    /// Prefix every slug in the group must start with, e.g. `mkt-`.
+    pub slug_prefix: Option<String>,
+    /// Maximum number of (non-deleted) links in the group.
+    pub max_links: Option<u32>,
+}
+
+impl GroupSettings {
+    /// Reject settings that could never be satisfied.
+    pub fn validate(&self) -> Result<(), CoreError> {
+        if let Some(prefix) = &self.slug_prefix {
+            Slug::new(prefix.clone())?;
+            if prefix.len() > 16 {
+                return Err(CoreError::InvalidSlug("slug prefix too long".into()));
+            }
+        }
+        for pattern in &self.allowed_hosts {
+            let host = pattern.strip_prefix("*.").unwrap_or(pattern);
+            if host.is_empty() || host.contains(['/', ':', '*', '@']) {
+                return Err(CoreError::InvalidUrl(format!(
+                    "invalid host pattern: {pattern}"
+                )));
+            }
+        }
+        if self.max_links == Some(0) {
+            return Err(CoreError::Conflict("max_links must be at least 1".into()));
+        }
+        Ok(())
+    }
+
+    /// Prefix a generated slug so it satisfies `slug_prefix`.
+    pub fn prefix_slug(&self, slug: Slug) -> Slug {
+        match &self.slug_prefix {
+            Some(prefix) if !slug.as_str().starts_with(prefix.as_str()) => Slug {
+                name: format!("{prefix}{}", slug.as_str()),
+                domain: slug.domain,
+            },
+            _ => slug,
+        }
+    }
+
+    /// Fill in the group defaults for fields the creator left unset.
+    pub fn apply_defaults(&self, link: &mut ShortLink) {
+        if link.expires_at.is_none() {
+            link.expires_at = self
+                .default_expiry_secs
+                .map(|secs| link.created_at + Duration::from_secs(secs));
+        }
+        if link.redirect_delay.is_none() {
+            link.redirect_delay = self.default_redirect_delay;
+        }
+    }
+
+    /// Check a slug against the group's `slug_prefix`.
+    pub fn check_slug(&self, slug: &Slug) -> Result<(), CoreError> {
+        match &self.slug_prefix {
+            Some(prefix) if !slug.as_str().starts_with(prefix.as_str()) => Err(
+                CoreError::InvalidSlug(format!("slug must start with \"{prefix}\" in this group")),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check a link's slug and target host against the group rules.
+    pub fn check_link(&self, link: &ShortLink) -> Result<(), CoreError> {
+        self.check_slug(&link.slug)?;
+        check_allowed_host(&self.allowed_hosts, &link.original_url, "this group")
+    }
+}
+
+/// Reject `url` unless its host matches one of `patterns` (empty allows any).
+fn check_allowed_host(patterns: &[String], url: &str, scope: &str) -> Result<(), CoreError> {
+    if patterns.is_empty() {
+        return Ok(());
+    }
+    let host = validate::url_host(url).unwrap_or_default();
+    if !patterns.iter().any(|p| validate::host_matches(p, &host)) {
+        return Err(CoreError::InvalidUrl(format!(
+            "host \"{host}\" is not allowed in {scope}"
+        )));
+    }
+    Ok(())
 }
 
 /// A member of a link group with their access level.
@@ -154,8 +542,8 @@ pub struct GroupMember {
     pub added_by: UserEmail,
 }
 
-/// Role/permission level for group members.
-#[derive(Clone, Debug, PartialEq, Eq, Copy)]
+/// Role/permission level for group members, ordered from least to most access.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Copy)]
 pub enum GroupRole {
     /// Can view links in the group.
     Viewer,
@@ -192,6 +580,91 @@ impl GroupRole {
     }
 }
 
+/// An invitation to join a link group. The member is only added on acceptance.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GroupInvitation {
+    pub id: String,
+    pub group_id: String,
+    pub email: UserEmail,
+    pub role: GroupRole,
+    pub invited_by: UserEmail,
+    pub created_at: SystemTime,
+    pub expires_at: SystemTime,
+    pub status: InvitationStatus,
+    pub responded_at: Option<SystemTime>,
+}
+
+impl GroupInvitation {
+    /// Pending and not yet expired at `now`.
+    pub fn is_open(&self, now: SystemTime) -> bool {
+        self.status == InvitationStatus::Pending && now < self.expires_at
+    }
+}
+
+/// Lifecycle state of a group invitation. Expiry is derived from `expires_at`.
+#[derive(Clone, Debug, PartialEq, Eq, Copy)]
+pub enum InvitationStatus {
+    Pending,
+    Accepted,
+    Declined,
+}
+
+impl InvitationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InvitationStatus::Pending => "pending",
+            InvitationStatus::Accepted => "accepted",
+            InvitationStatus::Declined => "declined",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "pending" => Some(InvitationStatus::Pending),
+            "accepted" => Some(InvitationStatus::Accepted),
+            "declined" => Some(InvitationStatus::Declined),
+            _ => None,
+        }
+    }
+}
+
+/// What happens to a group's links when the group is deleted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GroupLinkDisposition {
+    /// Move the links into another existing group.
+    Reassign(String),
+    /// Clear `group_id`, leaving the links with their creators.
+    Detach,
+    /// Soft-delete the links (and clear `group_id`).
+    SoftDelete,
+}
+
+impl GroupLinkDisposition {
+    /// Parse the admin API choice; `reassign` requires a target group id.
+    pub fn parse(s: &str, target_group: Option<&str>) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "reassign" => target_group
+                .filter(|t| !t.is_empty())
+                .map(|t| GroupLinkDisposition::Reassign(t.to_string())),
+            "detach" => Some(GroupLinkDisposition::Detach),
+            "soft_delete" => Some(GroupLinkDisposition::SoftDelete),
+            _ => None,
+        }
+    }
+}
+
+/// A per-link access grant, sharing a single link without creating a group.
+///
+/// Only `Viewer` and `Editor` are handed out by the admin APIs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LinkGrant {
+    pub slug: Slug,
+    pub user_email: UserEmail,
+    pub role: GroupRole,
+    pub granted_at: SystemTime,
+    pub granted_by: UserEmail,
+}
+
 /// A click event for analytics.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ClickEvent {
@@ -225,6 +698,9 @@ pub enum AuditAction {
     Deactivate,
     AddMember,
     RemoveMember,
+    Invite,
+    AcceptInvite,
+    DeclineInvite,
 }
 
 impl AuditAction {
@@ -238,6 +714,9 @@ impl AuditAction {
             AuditAction::Deactivate => "deactivate",
             AuditAction::AddMember => "add_member",
             AuditAction::RemoveMember => "remove_member",
+            AuditAction::Invite => "invite",
+            AuditAction::AcceptInvite => "accept_invite",
+            AuditAction::DeclineInvite => "decline_invite",
         }
     }
 
@@ -251,6 +730,9 @@ impl AuditAction {
             "deactivate" => Some(AuditAction::Deactivate),
             "add_member" => Some(AuditAction::AddMember),
             "remove_member" => Some(AuditAction::RemoveMember),
+            "invite" => Some(AuditAction::Invite),
+            "accept_invite" => Some(AuditAction::AcceptInvite),
+            "decline_invite" => Some(AuditAction::DeclineInvite),
             _ => None,
         }
     }
@@ -266,6 +748,79 @@ pub trait SlugGenerator: Send + Sync {
     fn next_slug(&self, next_id: u64) -> Slug;
 }
 
+/// How [`LinkRepository::list_by_target`] compares link targets with a URL.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TargetMatch {
+    /// Same canonical URL (see [`validate::canonical_url`]).
+    #[default]
+    Exact,
+    /// Canonical URL starting with the canonical form of the given URL.
+    Prefix,
+    /// Same host, whatever the scheme, port or path. Also accepts a bare host.
+    Host,
+}
+
+impl TargetMatch {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TargetMatch::Exact => "exact",
+            TargetMatch::Prefix => "prefix",
+            TargetMatch::Host => "host",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "exact" => Some(TargetMatch::Exact),
+            "prefix" => Some(TargetMatch::Prefix),
+            "host" => Some(TargetMatch::Host),
+            _ => None,
+        }
+    }
+
+    /// The value link targets are compared with: the canonical URL, or the
+    /// host for [`TargetMatch::Host`].
+    pub fn lookup_key(&self, url: &str) -> Result<String, CoreError> {
+        match self {
+            TargetMatch::Exact | TargetMatch::Prefix => {
+                validate::validate_original_url(url)?;
+                Ok(validate::canonical_url(url))
+            }
+            TargetMatch::Host if url.contains("://") => {
+                validate::url_host(url).ok_or_else(|| CoreError::InvalidUrl("missing host".into()))
+            }
+            TargetMatch::Host => normalize_host(url),
+        }
+    }
+
+    /// Whether `link` points at a target matching `key` (see [`Self::lookup_key`]).
+    pub fn matches(&self, key: &str, link: &ShortLink) -> bool {
+        match self {
+            TargetMatch::Exact => link.target_key() == key,
+            TargetMatch::Prefix => link.target_key().starts_with(key),
+            TargetMatch::Host => link.target_host().as_deref() == Some(key),
+        }
+    }
+
+    /// New target for a link matched by `key` when repointing matches to `to`:
+    /// exact matches become `to`, prefix matches get their prefix replaced by
+    /// `to`, and host matches keep their path and query on the origin `to`.
+    pub fn rewrite(&self, key: &str, link: &ShortLink, to: &str) -> String {
+        let current = link.target_key();
+        match self {
+            TargetMatch::Exact => to.to_string(),
+            TargetMatch::Prefix => {
+                format!("{to}{}", current.strip_prefix(key).unwrap_or_default())
+            }
+            TargetMatch::Host => {
+                let after_scheme = current.split_once("://").map_or("", |(_, rest)| rest);
+                let path = after_scheme.find('/').map_or("", |i| &after_scheme[i..]);
+                format!("{}{path}", to.trim_end_matches('/'))
+            }
+        }
+    }
+}
+
 /// Pagination parameters for list queries.
 #[derive(Clone, Debug, Default)]
 pub struct ListOptions {
@@ -275,25 +830,398 @@ pub struct ListOptions {
     pub group_id: Option<String>,
     pub search: Option<String>,
     pub include_deleted: bool,
+    /// Restrict to links the scope's user can access (combined with the other filters).
+    pub accessible_to: Option<AccessScope>,
+    /// Resume after the page that returned this `next_cursor`. Adapters that
+    /// support cursors ignore `offset`.
+    pub cursor: Option<String>,
+    /// Field to order by; ties are broken by slug in the same direction.
+    pub sort: LinkSort,
+    pub direction: SortDirection,
+    /// Only links in this state. `Deleted` lists deleted links whatever
+    /// `include_deleted` says.
+    pub status: Option<LinkStatus>,
+    /// Time `status` is judged at; the current time when unset.
+    pub now: Option<SystemTime>,
+    pub created: TimeRange,
+    pub updated: TimeRange,
+    pub expires: TimeRange,
+    /// Only links whose target is on this host (see [`ShortLink::target_host`]).
+    pub target_host: Option<String>,
+}
+
+impl ListOptions {
+    /// Time `status` is judged at.
+    pub fn status_time(&self) -> SystemTime {
+        self.now.unwrap_or_else(SystemTime::now)
+    }
+
+    /// Whether `link` passes every filter.
+    pub fn matches(&self, link: &ShortLink) -> bool {
+        let deleted_ok = match self.status {
+            Some(status) => status.matches(link, self.status_time()),
+            None => self.include_deleted || !link.is_deleted(),
+        };
+        let search_ok = self.search.as_ref().is_none_or(|q| {
+            let q = q.to_lowercase();
+            link.slug.as_str().to_lowercase().contains(&q)
+                || link.original_url.to_lowercase().contains(&q)
+                || link
+                    .description
+                    .as_ref()
+                    .is_some_and(|d| d.to_lowercase().contains(&q))
+        });
+        deleted_ok
+            && search_ok
+            && self
+                .created_by
+                .as_ref()
+                .is_none_or(|email| link.created_by.as_str() == email.as_str())
+            && self
+                .group_id
+                .as_ref()
+                .is_none_or(|gid| link.group_id.as_ref() == Some(gid))
+            && self
+                .accessible_to
+                .as_ref()
+                .is_none_or(|scope| scope.contains(link))
+            && self.created.contains(Some(link.created_at))
+            && self.updated.contains(link.updated_at)
+            && self.expires.contains(link.expires_at)
+            && self
+                .target_host
+                .as_ref()
+                .is_none_or(|host| link.target_host().as_ref() == Some(host))
+    }
+
+    /// Where `link` falls in the listing order, as carried by cursors.
+    fn position(&self, link: &ShortLink) -> (Option<u128>, String) {
+        (self.sort.value(link), link.slug.key().into_owned())
+    }
+
+    /// Sort `links` (already filtered) and cut out the page after `cursor`,
+    /// or at `offset` without one. For stores that cannot page themselves.
+    pub fn page(&self, mut links: Vec<ShortLink>) -> Result<ListResult<ShortLink>, CoreError> {
+        links.sort_by_cached_key(|l| self.position(l));
+        if self.direction == SortDirection::Desc {
+            links.reverse();
+        }
+        let total = links.len();
+        let start = match self.cursor {
+            Some(ref cursor) => {
+                let after = cursor::decode_link_position(cursor, self.sort, self.direction)?;
+                links.partition_point(|l| match self.direction {
+                    SortDirection::Asc => self.position(l) <= after,
+                    SortDirection::Desc => self.position(l) >= after,
+                })
+            }
+            None => self.offset.min(total),
+        };
+        let has_more = start + self.limit < total;
+        let items: Vec<_> = links.into_iter().skip(start).take(self.limit).collect();
+        let next_cursor = match items.last() {
+            Some(last) if has_more => {
+                let (value, key) = self.position(last);
+                Some(cursor::encode_link_position(
+                    self.sort,
+                    self.direction,
+                    value,
+                    &key,
+                ))
+            }
+            _ => None,
+        };
+        Ok(ListResult {
+            items,
+            total: Some(total),
+            has_more,
+            next_cursor,
+        })
+    }
+}
+
+/// Field a link listing is ordered by.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LinkSort {
+    #[default]
+    CreatedAt,
+    UpdatedAt,
+    ClickCount,
+    ExpiresAt,
+}
+
+impl LinkSort {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LinkSort::CreatedAt => "created_at",
+            LinkSort::UpdatedAt => "updated_at",
+            LinkSort::ClickCount => "click_count",
+            LinkSort::ExpiresAt => "expires_at",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "created_at" => Some(LinkSort::CreatedAt),
+            "updated_at" => Some(LinkSort::UpdatedAt),
+            "click_count" => Some(LinkSort::ClickCount),
+            "expires_at" => Some(LinkSort::ExpiresAt),
+            _ => None,
+        }
+    }
+
+    /// The link's value for this field (times in nanoseconds). Links without
+    /// one sort below all others.
+    pub fn value(&self, link: &ShortLink) -> Option<u128> {
+        let nanos = |t: SystemTime| {
+            t.duration_since(SystemTime::UNIX_EPOCH)
+                .map_or(0, |d| d.as_nanos())
+        };
+        match self {
+            LinkSort::CreatedAt => Some(nanos(link.created_at)),
+            LinkSort::UpdatedAt => link.updated_at.map(nanos),
+            LinkSort::ClickCount => Some(link.click_count.into()),
+            LinkSort::ExpiresAt => link.expires_at.map(nanos),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortDirection {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl SortDirection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortDirection::Asc => "asc",
+            SortDirection::Desc => "desc",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "asc" => Some(SortDirection::Asc),
+            "desc" => Some(SortDirection::Desc),
+            _ => None,
+        }
+    }
+}
+
+/// Lifecycle state a link listing can be narrowed to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LinkStatus {
+    /// Redirecting now (see [`ShortLink::is_available`]).
+    Active,
+    /// Switched off by its owner.
+    Inactive,
+    Expired,
+    /// Waiting for its `activate_at`.
+    Scheduled,
+    Deleted,
+}
+
+impl LinkStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LinkStatus::Active => "active",
+            LinkStatus::Inactive => "inactive",
+            LinkStatus::Expired => "expired",
+            LinkStatus::Scheduled => "scheduled",
+            LinkStatus::Deleted => "deleted",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "active" => Some(LinkStatus::Active),
+            "inactive" => Some(LinkStatus::Inactive),
+            "expired" => Some(LinkStatus::Expired),
+            "scheduled" => Some(LinkStatus::Scheduled),
+            "deleted" => Some(LinkStatus::Deleted),
+            _ => None,
+        }
+    }
+
+    /// Whether `link` is in this state at `now`. Only `Deleted` matches
+    /// deleted links; the others can overlap (an inactive link may also be
+    /// expired).
+    pub fn matches(&self, link: &ShortLink, now: SystemTime) -> bool {
+        match self {
+            LinkStatus::Deleted => link.is_deleted(),
+            _ if link.is_deleted() => false,
+            LinkStatus::Active => link.is_available(now),
+            LinkStatus::Inactive => !link.is_active,
+            LinkStatus::Expired => link.is_expired(now),
+            LinkStatus::Scheduled => link.is_scheduled(now),
+        }
+    }
+}
+
+/// Time window `[from, to)`; either end may be open.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TimeRange {
+    pub from: Option<SystemTime>,
+    pub to: Option<SystemTime>,
+}
+
+impl TimeRange {
+    pub fn is_unbounded(&self) -> bool {
+        self.from.is_none() && self.to.is_none()
+    }
+
+    /// Whether `t` falls in the window. A missing time is only in the
+    /// unbounded one.
+    pub fn contains(&self, t: Option<SystemTime>) -> bool {
+        match t {
+            Some(t) => self.from.is_none_or(|f| t >= f) && self.to.is_none_or(|to| t < to),
+            None => self.is_unbounded(),
+        }
+    }
+}
+
+/// The set of links a user can access: links they created, links in any of
+/// `group_ids`, and links in `shared_slugs` (per-link grants).
+#[derive(Clone, Debug)]
+pub struct AccessScope {
+    pub user: UserEmail,
+    pub group_ids: Vec<String>,
+    pub shared_slugs: Vec<Slug>,
+}
+
+impl AccessScope {
+    pub fn contains(&self, link: &ShortLink) -> bool {
+        link.created_by.as_str() == self.user.as_str()
+            || link
+                .group_id
+                .as_ref()
+                .is_some_and(|gid| self.group_ids.contains(gid))
+            || self.shared_slugs.contains(&link.slug)
+    }
 }
 
 /// Paginated list result.
 #[derive(Clone, Debug)]
 pub struct ListResult<T> {
     pub items: Vec<T>,
-    pub total: usize,
+    /// Matches across all pages, when the store counted them. Stores that
+    /// page through an index may leave this out, always or on cursor pages,
+    /// rather than re-read every match to count it.
+    pub total: Option<usize>,
     pub has_more: bool,
+    /// Pass back as `ListOptions::cursor` to fetch the next page.
+    pub next_cursor: Option<String>,
+}
+
+/// A link found by a full-text search.
+#[derive(Clone, Debug)]
+pub struct SearchHit {
+    pub link: ShortLink,
+    /// Excerpt of the best matching field with the matched terms wrapped in
+    /// `<mark>`…`</mark>`. The rest is the stored text, not HTML-escaped.
+    pub snippet: Option<String>,
+}
+
+/// Unranked results, as hits without snippets.
+impl From<ListResult<ShortLink>> for ListResult<SearchHit> {
+    fn from(page: ListResult<ShortLink>) -> Self {
+        ListResult {
+            items: page
+                .items
+                .into_iter()
+                .map(|link| SearchHit {
+                    link,
+                    snippet: None,
+                })
+                .collect(),
+            total: page.total,
+            has_more: page.has_more,
+            next_cursor: page.next_cursor,
+        }
+    }
+}
+
+/// Repositories that partition their data by tenant. A scoped handle shares
+/// the underlying storage but only ever reads and writes its tenant's data.
+pub trait TenantScoped {
+    fn for_tenant(&self, tenant: &TenantId) -> Self;
+    fn tenant(&self) -> &TenantId;
+}
+
+/// Repository port for organizations. Organizations themselves are global, not
+/// tenant-scoped.
+pub trait OrganizationRepository: Send + Sync {
+    /// Insert or replace an organization.
+    fn put_organization(&self, org: Organization) -> Result<(), CoreError>;
+    fn get_organization(&self, id: &TenantId) -> Result<Option<Organization>, CoreError>;
+    fn list_organizations(&self) -> Result<Vec<Organization>, CoreError>;
+    /// The organization serving `host` (any port ignored), if any. Stores
+    /// with a host-keyed lookup should override this full read.
+    fn get_organization_by_host(&self, host: &str) -> Result<Option<Organization>, CoreError> {
+        Ok(self
+            .list_organizations()?
+            .into_iter()
+            .find(|o| o.serves_host(host)))
+    }
+}
+
+/// Repository port for an organization's short domains.
+pub trait DomainRepository: Send + Sync {
+    /// Insert or replace a domain.
+    fn put_domain(&self, domain: ShortDomain) -> Result<(), CoreError>;
+    fn get_domain(&self, host: &str) -> Result<Option<ShortDomain>, CoreError>;
+    /// All domains, sorted by host.
+    fn list_domains(&self) -> Result<Vec<ShortDomain>, CoreError>;
+    /// Remove a domain. Links on it stay stored but stop resolving.
+    fn delete_domain(&self, host: &str) -> Result<(), CoreError>;
+}
+
+/// Repository port for reserved slug namespaces.
+pub trait NamespaceRepository: Send + Sync {
+    /// Reserve a namespace; `AlreadyExists` if it is reserved already.
+    fn put_namespace(&self, namespace: Namespace) -> Result<(), CoreError>;
+    fn get_namespace(&self, path: &str) -> Result<Option<Namespace>, CoreError>;
+    /// All reserved namespaces, sorted by path.
+    fn list_namespaces(&self) -> Result<Vec<Namespace>, CoreError>;
+    /// Release a namespace; `NotFound` if it is not reserved.
+    fn delete_namespace(&self, path: &str) -> Result<(), CoreError>;
+    /// The innermost reserved namespace enclosing `slug`, if any.
+    fn reserved_namespace(&self, slug: &Slug) -> Result<Option<Namespace>, CoreError> {
+        for path in slug.namespaces() {
+            if let Some(namespace) = self.get_namespace(path)? {
+                return Ok(Some(namespace));
+            }
+        }
+        Ok(None)
+    }
 }
 
 /// Repository port for persisting and loading links.
+///
+/// Besides the slug it is stored under, a link can be reached through aliases.
+/// Aliases live in an index next to the links, so `get` resolves them with one
+/// keyed lookup and returns the link with its own (canonical) slug. Every other
+/// slug-keyed method expects the canonical slug.
 pub trait LinkRepository: Send + Sync {
+    /// Load a link by its slug or one of its aliases.
     fn get(&self, slug: &Slug) -> Result<Option<ShortLink>, CoreError>;
+    /// Store a new link; `AlreadyExists` if the slug is taken by a link or an alias.
     fn put(&self, link: ShortLink) -> Result<(), CoreError>;
     fn list(&self, limit: usize) -> Result<Vec<ShortLink>, CoreError>;
     /// Update an existing link (original_url, is_active, updated_at).
     fn update(&self, link: &ShortLink) -> Result<(), CoreError>;
     /// Atomically increment the click count for a link.
     fn increment_click(&self, slug: &Slug) -> Result<(), CoreError>;
+    /// Add `clicks` to the click count of a link at once, e.g. a batch of
+    /// counted clicks. Stores with an atomic add override the loop.
+    fn add_clicks(&self, slug: &Slug, clicks: u64) -> Result<(), CoreError> {
+        for _ in 0..clicks {
+            self.increment_click(slug)?;
+        }
+        Ok(())
+    }
     /// List links created by a specific user.
     fn list_by_creator(&self, email: &UserEmail, limit: usize)
         -> Result<Vec<ShortLink>, CoreError>;
@@ -303,8 +1231,22 @@ pub trait LinkRepository: Send + Sync {
     fn search(&self, query: &str, limit: usize) -> Result<Vec<ShortLink>, CoreError>;
     /// List links with pagination and filters.
     fn list_paginated(&self, options: &ListOptions) -> Result<ListResult<ShortLink>, CoreError>;
+    /// Links matching `options.search` and the other filters, best match
+    /// first; `offset` pages through the ranking. Stores without a search
+    /// index return `list_paginated` without snippets.
+    fn search_ranked(&self, options: &ListOptions) -> Result<ListResult<SearchHit>, CoreError> {
+        self.list_paginated(options).map(Into::into)
+    }
     /// List links by group ID.
     fn list_by_group(&self, group_id: &str, limit: usize) -> Result<Vec<ShortLink>, CoreError>;
+    /// Links whose target matches `key` (see [`TargetMatch::lookup_key`]),
+    /// oldest first, excluding deleted ones.
+    fn list_by_target(
+        &self,
+        key: &str,
+        mode: TargetMatch,
+        limit: usize,
+    ) -> Result<Vec<ShortLink>, CoreError>;
     /// Bulk delete links (soft delete).
     fn bulk_delete(&self, slugs: &[Slug], deleted_at: SystemTime) -> Result<usize, CoreError>;
     /// Bulk update is_active status.
@@ -314,6 +1256,17 @@ pub trait LinkRepository: Send + Sync {
         is_active: bool,
         updated_at: SystemTime,
     ) -> Result<usize, CoreError>;
+    /// Point `alias` at the link stored under `target`. `AlreadyExists` if the
+    /// alias is taken by a link or another alias, `NotFound` without such a link.
+    fn add_alias(&self, alias: &Slug, target: &Slug) -> Result<(), CoreError>;
+    /// Remove an alias; `NotFound` if `alias` is not one.
+    fn remove_alias(&self, alias: &Slug) -> Result<(), CoreError>;
+    /// Aliases pointing at the link stored under `target`, sorted by key.
+    fn list_aliases(&self, target: &Slug) -> Result<Vec<Slug>, CoreError>;
+    /// Move the link stored under `old` to `new`, keeping its clicks and settings.
+    /// `old` and the link's existing aliases keep forwarding to it. Returns the
+    /// renamed link; errors like `add_alias` when `new` is taken or `old` missing.
+    fn rename(&self, old: &Slug, new: &Slug) -> Result<ShortLink, CoreError>;
 }
 
 /// Repository port for link groups.
@@ -322,19 +1275,81 @@ pub trait GroupRepository: Send + Sync {
     fn get_group(&self, id: &str) -> Result<Option<LinkGroup>, CoreError>;
     fn list_groups(&self, user_email: &UserEmail) -> Result<Vec<LinkGroup>, CoreError>;
     fn update_group(&self, group: &LinkGroup) -> Result<(), CoreError>;
-    fn delete_group(&self, id: &str) -> Result<(), CoreError>;
+    /// Groups directly below `parent_id`.
+    fn list_child_groups(&self, parent_id: &str) -> Result<Vec<LinkGroup>, CoreError>;
+    /// Delete a group and its memberships, applying `links` to every link that
+    /// still points at the group. Subgroups move up to the deleted group's
+    /// parent. Returns the number of links affected.
+    fn delete_group(
+        &self,
+        id: &str,
+        links: &GroupLinkDisposition,
+        at: SystemTime,
+    ) -> Result<usize, CoreError>;
     fn add_member(&self, member: GroupMember) -> Result<(), CoreError>;
+    /// Remove a member. Fails with `Conflict` when it would leave the group
+    /// without an admin.
     fn remove_member(&self, group_id: &str, user_email: &UserEmail) -> Result<(), CoreError>;
+    /// Make `new_owner`, who must already be a member, the group's creator and
+    /// promote them to admin.
+    fn transfer_ownership(&self, group_id: &str, new_owner: &UserEmail) -> Result<(), CoreError>;
     fn list_members(&self, group_id: &str) -> Result<Vec<GroupMember>, CoreError>;
     fn get_member(
         &self,
         group_id: &str,
         user_email: &UserEmail,
     ) -> Result<Option<GroupMember>, CoreError>;
+    /// Groups the user belongs to, plus every group below them, each with the
+    /// user's effective role.
     fn get_user_groups(
         &self,
         user_email: &UserEmail,
     ) -> Result<Vec<(LinkGroup, GroupRole)>, CoreError>;
+    /// The user's role on a group including roles inherited from ancestor groups.
+    fn effective_role(
+        &self,
+        group_id: &str,
+        user_email: &UserEmail,
+    ) -> Result<Option<GroupRole>, CoreError> {
+        hierarchy::effective_role(self, group_id, user_email)
+    }
+}
+
+/// Repository port for group invitations.
+pub trait InvitationRepository: Send + Sync {
+    fn create_invitation(&self, invitation: GroupInvitation) -> Result<(), CoreError>;
+    fn get_invitation(&self, id: &str) -> Result<Option<GroupInvitation>, CoreError>;
+    /// All invitations addressed to `email`, in any status.
+    fn list_invitations_for_user(
+        &self,
+        email: &UserEmail,
+    ) -> Result<Vec<GroupInvitation>, CoreError>;
+    /// All invitations for a group, in any status.
+    fn list_invitations_for_group(&self, group_id: &str)
+        -> Result<Vec<GroupInvitation>, CoreError>;
+    /// Move a pending invitation to `status`. Returns `NotFound` if the invitation
+    /// does not exist or was already answered, so concurrent answers cannot both win.
+    fn respond_invitation(
+        &self,
+        id: &str,
+        status: InvitationStatus,
+        responded_at: SystemTime,
+    ) -> Result<(), CoreError>;
+}
+
+/// Repository port for per-link grants (collaborators).
+pub trait LinkGrantRepository: Send + Sync {
+    /// Insert a grant, replacing the role of an existing grant for the same user.
+    fn put_grant(&self, grant: LinkGrant) -> Result<(), CoreError>;
+    fn remove_grant(&self, slug: &Slug, user_email: &UserEmail) -> Result<(), CoreError>;
+    fn list_grants(&self, slug: &Slug) -> Result<Vec<LinkGrant>, CoreError>;
+    fn get_grant(
+        &self,
+        slug: &Slug,
+        user_email: &UserEmail,
+    ) -> Result<Option<LinkGrant>, CoreError>;
+    /// All links shared with `user_email`.
+    fn list_grants_for_user(&self, user_email: &UserEmail) -> Result<Vec<LinkGrant>, CoreError>;
 }
 
 /// Repository port for click analytics.
@@ -370,6 +1385,11 @@ pub enum CoreError {
     InvalidUserEmail,
     AlreadyExists,
     NotFound,
+    InvalidTenant(String),
+    /// A pagination cursor that no adapter issued (or that is from another listing).
+    InvalidCursor,
+    /// The operation would violate an invariant (e.g. removing the last admin).
+    Conflict(String),
     Repository(String),
 }
 
@@ -381,6 +1401,9 @@ impl Display for CoreError {
             CoreError::InvalidUserEmail => write!(f, "invalid user email"),
             CoreError::AlreadyExists => write!(f, "resource already exists"),
             CoreError::NotFound => write!(f, "not found"),
+            CoreError::InvalidTenant(id) => write!(f, "invalid tenant id: {}", id),
+            CoreError::InvalidCursor => write!(f, "invalid cursor"),
+            CoreError::Conflict(msg) => write!(f, "conflict: {}", msg),
             CoreError::Repository(msg) => write!(f, "repository error: {}", msg),
         }
     }
@@ -399,14 +1422,108 @@ pub fn about() -> String {
 // Re-export modules when added
 pub mod adapters;
 pub mod base62;
+pub mod cache;
+pub mod clicks;
+pub mod cursor;
+pub mod hierarchy;
 pub mod service;
 pub mod slug;
+pub mod tenant;
 pub mod validate;
+mod words;
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn collaborators_are_managed_by_owner_admins_and_group_admins() {
+        let mut link = ShortLink::new(
+            Slug::new("docs").unwrap(),
+            "https://docs.example.com".into(),
+            SystemTime::UNIX_EPOCH,
+            UserEmail::new("owner@example.com").unwrap(),
+        );
+        let no_group = |_: &str| -> Result<Option<GroupRole>, CoreError> {
+            panic!("ungrouped links have no group role")
+        };
+        assert!(link
+            .can_manage_collaborators("owner@example.com", false, no_group)
+            .unwrap());
+        assert!(link
+            .can_manage_collaborators("root@example.com", true, no_group)
+            .unwrap());
+        assert!(!link
+            .can_manage_collaborators("other@example.com", false, no_group)
+            .unwrap());
+
+        link.group_id = Some("team".into());
+        let role = |r: GroupRole| {
+            move |gid: &str| {
+                assert_eq!(gid, "team");
+                Ok::<_, CoreError>(Some(r))
+            }
+        };
+        assert!(link
+            .can_manage_collaborators("lead@example.com", false, role(GroupRole::Admin))
+            .unwrap());
+        assert!(!link
+            .can_manage_collaborators("dev@example.com", false, role(GroupRole::Editor))
+            .unwrap());
+        assert!(matches!(
+            link.can_manage_collaborators("dev@example.com", false, |_| Err(
+                CoreError::Repository("down".into())
+            )),
+            Err(CoreError::Repository(_))
+        ));
+    }
+
+    #[test]
+    fn target_match_keys_and_rewrites() {
+        let link = ShortLink::new(
+            Slug::new("docs").unwrap(),
+            "https://Docs.example.com:443/guide/intro?v=2#top".into(),
+            SystemTime::UNIX_EPOCH,
+            UserEmail::new("user@example.com").unwrap(),
+        );
+        let key = |mode: TargetMatch, url: &str| mode.lookup_key(url).unwrap();
+
+        let exact = key(
+            TargetMatch::Exact,
+            "https://docs.example.com/guide/intro?v=2",
+        );
+        assert!(TargetMatch::Exact.matches(&exact, &link));
+        assert_eq!(
+            TargetMatch::Exact.rewrite(&exact, &link, "https://new.example.com/"),
+            "https://new.example.com/"
+        );
+
+        let prefix = key(TargetMatch::Prefix, "https://docs.example.com/guide");
+        assert!(TargetMatch::Prefix.matches(&prefix, &link));
+        assert!(!TargetMatch::Exact.matches(&prefix, &link));
+        assert_eq!(
+            TargetMatch::Prefix.rewrite(&prefix, &link, "https://example.com/handbook"),
+            "https://example.com/handbook/intro?v=2"
+        );
+
+        let host = key(TargetMatch::Host, "DOCS.example.com");
+        assert_eq!(
+            host,
+            key(TargetMatch::Host, "http://docs.example.com:8080/x")
+        );
+        assert!(TargetMatch::Host.matches(&host, &link));
+        assert!(!TargetMatch::Host.matches("example.com", &link));
+        assert_eq!(
+            TargetMatch::Host.rewrite(&host, &link, "https://wiki.example.com/"),
+            "https://wiki.example.com/guide/intro?v=2"
+        );
+
+        assert!(TargetMatch::Prefix.lookup_key("docs.example.com").is_err());
+        assert!(TargetMatch::Host.lookup_key("not a host").is_err());
+        assert_eq!(TargetMatch::parse("Prefix"), Some(TargetMatch::Prefix));
+        assert_eq!(TargetMatch::parse("fuzzy"), None);
+    }
+
     #[test]
     fn slug_new_accepts_simple_values() {
         let s = Slug::new("abc123").expect("valid slug");
@@ -422,6 +1539,154 @@ mod tests {
         }
     }
 
+    #[test]
+    fn slugs_can_be_namespaced() {
+        let s = Slug::new("events/2026/kickoff").unwrap();
+        assert_eq!(
+            s.namespaces().collect::<Vec<_>>(),
+            ["events/2026", "events"]
+        );
+        assert_eq!(Slug::new("flat").unwrap().namespaces().count(), 0);
+        for bad in ["/hr", "hr/", "hr//onboarding"] {
+            assert!(Slug::new(bad).is_err(), "{bad}");
+        }
+        let keyed = Slug::from_key("go.team.no:hr/onboarding").unwrap();
+        assert_eq!(keyed.as_str(), "hr/onboarding");
+        assert_eq!(keyed.domain(), Some("go.team.no"));
+    }
+
+    #[test]
+    fn slug_keys_carry_the_short_domain() {
+        let plain = Slug::new("abc").unwrap();
+        assert_eq!(plain.key(), "abc");
+        let team = plain.clone().on_domain(Some("go.team.no".into()));
+        assert_eq!(team.as_str(), "abc");
+        assert_eq!(team.key(), "go.team.no:abc");
+        assert_ne!(plain, team);
+        assert_eq!(Slug::from_key("Go.Team.no:abc").unwrap(), team);
+        assert_eq!(Slug::from_key("abc").unwrap(), plain);
+        assert!(Slug::from_key("go.team.no:").is_err());
+        assert!(normalize_host("jpro.link:443").is_err());
+    }
+
+    #[test]
+    fn short_domain_access() {
+        let email = |e: &str| UserEmail::new(e).unwrap();
+        let mut domain = ShortDomain {
+            host: "jpro.link".into(),
+            allowed_users: Vec::new(),
+            allowed_groups: Vec::new(),
+            created_at: SystemTime::UNIX_EPOCH,
+            created_by: email("admin@example.com"),
+        };
+        assert!(domain.may_use(&email("a@example.com"), &[]));
+        domain.allowed_users = vec![email("a@example.com")];
+        domain.allowed_groups = vec!["grp_mkt".into()];
+        assert!(domain.may_use(&email("a@example.com"), &[]));
+        assert!(domain.may_use(&email("b@example.com"), &["grp_mkt".into()]));
+        assert!(!domain.may_use(&email("b@example.com"), &["grp_eng".into()]));
+    }
+
+    #[test]
+    fn link_visibility_roundtrip() {
+        for v in [LinkVisibility::Public, LinkVisibility::Workspace] {
+            assert_eq!(LinkVisibility::parse(v.as_str()), Some(v));
+        }
+        assert_eq!(
+            LinkVisibility::parse("WORKSPACE"),
+            Some(LinkVisibility::Workspace)
+        );
+        assert_eq!(LinkVisibility::parse("private"), None);
+    }
+
+    #[test]
+    fn group_settings_defaults_and_rules() {
+        let settings = GroupSettings {
+            default_expiry_secs: Some(60),
+            default_redirect_delay: Some(3),
+            allowed_hosts: vec!["example.com".into(), "*.acme.com".into()],
+            slug_prefix: Some("mkt-".into()),
+            max_links: Some(10),
+        };
+        assert!(settings.validate().is_ok());
+
+        let slug = settings.prefix_slug(Slug::new("abc").unwrap());
+        assert_eq!(slug.as_str(), "mkt-abc");
+        let mut link = ShortLink::new(
+            slug,
+            "https://go.acme.com/x".into(),
+            SystemTime::UNIX_EPOCH,
+            UserEmail::new("u@acme.com").unwrap(),
+        );
+        settings.apply_defaults(&mut link);
+        assert_eq!(
+            link.expires_at,
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(60))
+        );
+        assert_eq!(link.redirect_delay, Some(3));
+        assert!(settings.check_link(&link).is_ok());
+
+        link.original_url = "https://evil.com".into();
+        assert!(matches!(
+            settings.check_link(&link),
+            Err(CoreError::InvalidUrl(_))
+        ));
+        link.original_url = "https://example.com".into();
+        link.slug = Slug::new("other").unwrap();
+        assert!(matches!(
+            settings.check_link(&link),
+            Err(CoreError::InvalidSlug(_))
+        ));
+
+        let bad = GroupSettings {
+            slug_prefix: Some("bad/".into()),
+            ..GroupSettings::default()
+        };
+        assert!(bad.validate().is_err());
+    }
+
+    #[test]
+    fn group_link_disposition_parse() {
+        assert_eq!(
+            GroupLinkDisposition::parse("reassign", Some("grp_2")),
+            Some(GroupLinkDisposition::Reassign("grp_2".into()))
+        );
+        assert_eq!(GroupLinkDisposition::parse("reassign", None), None);
+        assert_eq!(
+            GroupLinkDisposition::parse("DETACH", None),
+            Some(GroupLinkDisposition::Detach)
+        );
+        assert_eq!(
+            GroupLinkDisposition::parse("soft_delete", None),
+            Some(GroupLinkDisposition::SoftDelete)
+        );
+        assert_eq!(GroupLinkDisposition::parse("keep", None), None);
+    }
+
+    #[test]
+    fn invitation_open_until_answered_or_expired() {
+        let now = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(100);
+        let mut inv = GroupInvitation {
+            id: "inv_1".into(),
+            group_id: "grp_1".into(),
+            email: UserEmail::new("bob@example.com").unwrap(),
+            role: GroupRole::Editor,
+            invited_by: UserEmail::new("alice@example.com").unwrap(),
+            created_at: SystemTime::UNIX_EPOCH,
+            expires_at: now + std::time::Duration::from_secs(1),
+            status: InvitationStatus::Pending,
+            responded_at: None,
+        };
+        assert!(inv.is_open(now));
+        assert!(!inv.is_open(inv.expires_at));
+        inv.status = InvitationStatus::Declined;
+        assert!(!inv.is_open(now));
+        assert_eq!(
+            InvitationStatus::parse(inv.status.as_str()),
+            Some(InvitationStatus::Declined)
+        );
+    }
+
     #[test]
     fn user_email_basic_validation() {
         let ok = UserEmail::new("user@example.com");
@@ -1,9 +1,21 @@
 //! Slug generation strategies.
 
+use crate::CoreError;
 use crate::Slug;
 use crate::SlugGenerator;
 
 use crate::base62::encode_u64;
+use crate::validate::url_host;
+use crate::words::{ADJECTIVES, NOUNS};
+
+/// Longest suggested slug; matches the per-segment alias limit.
+const MAX_SUGGESTION_LEN: usize = 32;
+
+/// Words that carry no meaning in a slug.
+const STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "the", "of", "for", "to", "in", "on", "at", "by", "with", "www", "index",
+    "html", "htm", "php", "aspx", "jsp", "default", "home",
+];
 
 /// Base62 encoder-based slug generator. Deterministic w.r.t. `next_id`.
 /// If `min_width` is set, left-pads with '0' to reach the minimal length.
@@ -36,10 +48,291 @@ impl SlugGenerator for Base62SlugGenerator {
     }
 }
 
+/// How a slug is generated when the caller does not pick an alias.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SlugStyle {
+    /// Short base62 counter value, e.g. `0001Z`.
+    #[default]
+    Base62,
+    /// Memorable words from [`WordSlugGenerator`], e.g. `brave-otter-42`.
+    Words,
+}
+
+impl SlugStyle {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SlugStyle::Base62 => "base62",
+            SlugStyle::Words => "words",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "base62" => Some(SlugStyle::Base62),
+            "words" => Some(SlugStyle::Words),
+            _ => None,
+        }
+    }
+}
+
+/// Word-based slug generator producing slugs like `brave-otter-42`: adjectives
+/// followed by a noun and a two-digit number. Deterministic w.r.t. `next_id`,
+/// but unlike [`Base62SlugGenerator`] different ids can map to the same slug,
+/// so callers must check for collisions and retry with a fresh id.
+#[derive(Clone, Copy, Debug)]
+pub struct WordSlugGenerator {
+    dictionary_size: usize,
+    word_count: usize,
+    separator: char,
+}
+
+impl WordSlugGenerator {
+    /// Most words per slug; keeps the longest slug within 32 characters.
+    pub const MAX_WORDS: usize = 3;
+    /// Smallest dictionary that still gives a useful number of combinations.
+    pub const MIN_DICTIONARY_SIZE: usize = 16;
+
+    /// `dictionary_size` limits each word list to its most common words and is
+    /// capped at the embedded list size. `word_count` is 1 to
+    /// [`Self::MAX_WORDS`]; the separator is `-` or `_`.
+    pub fn new(
+        dictionary_size: usize,
+        word_count: usize,
+        separator: char,
+    ) -> Result<Self, CoreError> {
+        if dictionary_size < Self::MIN_DICTIONARY_SIZE {
+            return Err(CoreError::InvalidSlug(format!(
+                "word dictionary must have at least {} words",
+                Self::MIN_DICTIONARY_SIZE
+            )));
+        }
+        if !(1..=Self::MAX_WORDS).contains(&word_count) {
+            return Err(CoreError::InvalidSlug(format!(
+                "word count must be between 1 and {}",
+                Self::MAX_WORDS
+            )));
+        }
+        if !matches!(separator, '-' | '_') {
+            return Err(CoreError::InvalidSlug(
+                "word separator must be '-' or '_'".into(),
+            ));
+        }
+        Ok(Self {
+            dictionary_size: dictionary_size.min(ADJECTIVES.len().min(NOUNS.len())),
+            word_count,
+            separator,
+        })
+    }
+
+    pub fn dictionary_size(&self) -> usize {
+        self.dictionary_size
+    }
+
+    pub fn word_count(&self) -> usize {
+        self.word_count
+    }
+
+    pub fn separator(&self) -> char {
+        self.separator
+    }
+}
+
+impl Default for WordSlugGenerator {
+    /// The full dictionary, two words and `-`.
+    fn default() -> Self {
+        Self::new(usize::MAX, 2, '-').expect("default word generator config is valid")
+    }
+}
+
+impl SlugGenerator for WordSlugGenerator {
+    fn next_slug(&self, next_id: u64) -> Slug {
+        // Scramble the id so consecutive links don't share their first words.
+        let mut bits = splitmix64(next_id);
+        let mut pick = |words: &[&'static str]| {
+            let n = self.dictionary_size as u64;
+            let word = words[(bits % n) as usize];
+            bits /= n;
+            word
+        };
+        let mut parts: Vec<String> = (1..self.word_count)
+            .map(|_| pick(ADJECTIVES).to_string())
+            .collect();
+        parts.push(pick(NOUNS).to_string());
+        parts.push((10 + bits % 90).to_string());
+        let s = parts.join(&self.separator.to_string());
+        // Valid by construction — lowercase words, digits and '-' or '_'
+        Slug::new(s).unwrap_or_else(|_| Slug::new("0").expect("'0' is valid"))
+    }
+}
+
+/// SplitMix64 finalizer; a bijection on `u64` with good bit mixing.
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Readable slug candidates for a target URL, best first: the page title, the
+/// last path segment (alone and after the site name), the last two path
+/// segments, the site name, then numbered variants of the best one
+/// (`getting-started-2`) for when the others are taken. Only slugs valid for
+/// [`Slug::new`] of 3 to 32 characters are returned, without duplicates.
+/// Availability is up to the caller.
+pub fn suggest_slugs(url: &str, title: Option<&str>) -> Vec<Slug> {
+    let site = url_host(url).and_then(|host| {
+        let labels: Vec<&str> = host.split('.').filter(|l| *l != "www").collect();
+        // The label before the TLD names the site: docs.github.com -> github
+        labels
+            .len()
+            .checked_sub(2)
+            .map(|i| labels[i])
+            .or(labels.first().copied())
+            .map(str::to_string)
+    });
+    let path = url
+        .split_once("://")
+        .map_or("", |(_, rest)| rest)
+        .split(['?', '#'])
+        .next()
+        .unwrap_or("");
+    let segments: Vec<Vec<String>> = path
+        .split('/')
+        .skip(1)
+        .map(|seg| slug_words(seg.rsplit_once('.').map_or(seg, |(stem, _)| stem)))
+        .filter(|words| !words.is_empty())
+        .collect();
+    let last = segments.last().cloned().unwrap_or_default();
+    let last_two: Vec<String> = segments
+        .iter()
+        .rev()
+        .take(2)
+        .rev()
+        .flatten()
+        .cloned()
+        .collect();
+    let site_words: Vec<String> = site.iter().cloned().collect();
+
+    let candidates = [
+        title.map(slug_words).unwrap_or_default(),
+        last.clone(),
+        [site_words.clone(), last].concat(),
+        last_two,
+        site_words,
+    ];
+    let mut out: Vec<Slug> = Vec::new();
+    for words in candidates {
+        let Some(slug) = join_words(&words).and_then(|s| Slug::new(s).ok()) else {
+            continue;
+        };
+        if !out.contains(&slug) {
+            out.push(slug);
+        }
+    }
+    if let Some(best) = out.first().map(|s| s.as_str().to_string()) {
+        out.extend(
+            (2..=4)
+                .map(|n| format!("{best}-{n}"))
+                .filter(|s| s.len() <= MAX_SUGGESTION_LEN)
+                .filter_map(|s| Slug::new(s).ok()),
+        );
+    }
+    out
+}
+
+/// Lowercased alphanumeric words of `text`, without single letters, stop words
+/// and opaque ids (long tokens mixing letters and digits).
+fn slug_words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_ascii_alphanumeric())
+        .map(str::to_ascii_lowercase)
+        .filter(|w| w.len() > 1 && !STOP_WORDS.contains(&w.as_str()))
+        .filter(|w| {
+            let mixed =
+                w.chars().any(|c| c.is_ascii_digit()) && w.chars().any(|c| c.is_ascii_alphabetic());
+            !(mixed && w.len() > 8)
+        })
+        .collect()
+}
+
+/// Join words with `-`, dropping trailing words beyond the length limit.
+fn join_words(words: &[String]) -> Option<String> {
+    let mut out = String::new();
+    for word in words {
+        let sep = usize::from(!out.is_empty());
+        if out.len() + sep + word.len() > MAX_SUGGESTION_LEN {
+            break;
+        }
+        if sep == 1 {
+            out.push('-');
+        }
+        out.push_str(word);
+    }
+    (out.len() >= 3).then_some(out)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn word_slugs_are_readable_and_deterministic() {
+        let g = WordSlugGenerator::default();
+        let a = g.next_slug(42);
+        assert_eq!(a, g.next_slug(42));
+        assert_ne!(a, g.next_slug(43));
+        let parts: Vec<&str> = a.as_str().split('-').collect();
+        assert_eq!(parts.len(), 3, "{}", a.as_str());
+        assert!(ADJECTIVES.contains(&parts[0]));
+        assert!(NOUNS.contains(&parts[1]));
+        let n: u32 = parts[2].parse().unwrap();
+        assert!((10..100).contains(&n));
+    }
+
+    #[test]
+    fn word_generator_config_is_respected() {
+        let g = WordSlugGenerator::new(16, 3, '_').unwrap();
+        for id in 0..200 {
+            let slug = g.next_slug(id);
+            let parts: Vec<&str> = slug.as_str().split('_').collect();
+            assert_eq!(parts.len(), 4, "{}", slug.as_str());
+            assert!(ADJECTIVES[..16].contains(&parts[0]));
+            assert!(ADJECTIVES[..16].contains(&parts[1]));
+            assert!(NOUNS[..16].contains(&parts[2]));
+            assert!(slug.as_str().len() <= 32);
+        }
+        assert!(WordSlugGenerator::new(8, 2, '-').is_err());
+        assert!(WordSlugGenerator::new(64, 0, '-').is_err());
+        assert!(WordSlugGenerator::new(64, 4, '-').is_err());
+        assert!(WordSlugGenerator::new(64, 2, '.').is_err());
+        assert_eq!(
+            WordSlugGenerator::new(usize::MAX, 1, '-')
+                .unwrap()
+                .dictionary_size(),
+            ADJECTIVES.len().min(NOUNS.len())
+        );
+    }
+
+    #[test]
+    fn word_lists_are_lowercase_and_unique() {
+        for list in [ADJECTIVES, NOUNS] {
+            let mut seen = std::collections::HashSet::new();
+            for w in list {
+                assert!(w.chars().all(|c| c.is_ascii_lowercase()), "{w}");
+                assert!((3..=8).contains(&w.len()), "{w}");
+                assert!(seen.insert(*w), "duplicate {w}");
+            }
+        }
+        assert!(ADJECTIVES.iter().all(|w| !NOUNS.contains(w)));
+    }
+
+    #[test]
+    fn slug_style_parses() {
+        assert_eq!(SlugStyle::parse("Words"), Some(SlugStyle::Words));
+        assert_eq!(SlugStyle::parse("base62"), Some(SlugStyle::Base62));
+        assert_eq!(SlugStyle::parse("emoji"), None);
+        assert_eq!(SlugStyle::default().as_str(), "base62");
+    }
+
     #[test]
     fn deterministic_mapping() {
         let g = Base62SlugGenerator::new(0);
@@ -58,4 +351,42 @@ mod tests {
         let g2 = Base62SlugGenerator::new(2);
         assert_eq!(g2.next_slug(3843).as_str(), "zz");
     }
+
+    #[test]
+    fn suggestions_come_from_title_path_and_host() {
+        let got: Vec<String> = suggest_slugs(
+            "https://www.example.com/docs/Getting_Started.html?ref=nav",
+            Some("The Quick-Start Guide"),
+        )
+        .iter()
+        .map(|s| s.as_str().to_string())
+        .collect();
+        assert_eq!(
+            got,
+            [
+                "quick-start-guide",
+                "getting-started",
+                "example-getting-started",
+                "docs-getting-started",
+                "example",
+                "quick-start-guide-2",
+                "quick-start-guide-3",
+                "quick-start-guide-4",
+            ]
+        );
+    }
+
+    #[test]
+    fn suggestions_skip_ids_and_short_words() {
+        let got: Vec<String> = suggest_slugs("https://go.io/p/a1b2c3d4e5f6", None)
+            .iter()
+            .map(|s| s.as_str().to_string())
+            .collect();
+        assert!(got.is_empty(), "{got:?}");
+        let long = suggest_slugs(
+            "https://example.com/an-extremely-long-article-title-that-keeps-going",
+            None,
+        );
+        assert_eq!(long[0].as_str(), "extremely-long-article-title");
+    }
 }
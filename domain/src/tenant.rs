@@ -0,0 +1,260 @@
+//! Tenant helpers: resolving the organization of a request and the key prefix
+//! used by key-value stores to partition data per tenant.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::{CoreError, Organization, OrganizationRepository, TenantId};
+
+/// Separates the tenant prefix from the rest of a storage key. Never valid in
+/// tenant ids, slugs or generated ids.
+pub const KEY_SEPARATOR: char = '#';
+
+/// Storage key for `key` in `tenant`'s partition. Keys of the default tenant
+/// stay unprefixed so data written before organizations existed keeps working.
+pub fn scoped_key(tenant: &TenantId, key: &str) -> String {
+    if tenant.is_default() {
+        key.to_string()
+    } else {
+        format!("{}{KEY_SEPARATOR}{key}", tenant.as_str())
+    }
+}
+
+/// Inverse of [`scoped_key`]: the plain key if `stored` belongs to `tenant`.
+pub fn unscoped_key<'a>(tenant: &TenantId, stored: &'a str) -> Option<&'a str> {
+    if tenant.is_default() {
+        (!stored.contains(KEY_SEPARATOR)).then_some(stored)
+    } else {
+        stored
+            .strip_prefix(tenant.as_str())?
+            .strip_prefix(KEY_SEPARATOR)
+    }
+}
+
+/// Domain part of an email address.
+pub fn email_domain(email: &str) -> Option<&str> {
+    email.rsplit_once('@').map(|(_, d)| d)
+}
+
+/// Pick the organization for a request. A configured host wins; otherwise the
+/// sign-in domain from the caller's token selects it.
+pub fn resolve<'a>(
+    orgs: &'a [Organization],
+    host: Option<&str>,
+    token_domain: Option<&str>,
+) -> Option<&'a Organization> {
+    host.and_then(|h| orgs.iter().find(|o| o.serves_host(h)))
+        .or_else(|| token_domain.and_then(|d| orgs.iter().find(|o| o.allows_domain(d))))
+}
+
+/// Most hosts an [`OrganizationCache`] remembers. Hosts come from request
+/// headers, so the map is emptied rather than allowed to grow past this.
+const MAX_CACHED_HOSTS: usize = 1024;
+
+/// [`resolve`] reading through a short-lived cache, so routing a request does
+/// not read the organizations every time.
+///
+/// Hosts are looked up one at a time with
+/// [`OrganizationRepository::get_organization_by_host`], misses included. The
+/// full list is only read to match a token's sign-in domain when the host
+/// matched nothing. Organization changes show up once the TTL has passed.
+pub struct OrganizationCache {
+    ttl: Duration,
+    state: Mutex<CacheState>,
+}
+
+#[derive(Default)]
+struct CacheState {
+    hosts: HashMap<String, (Instant, Option<Organization>)>,
+    all: Option<(Instant, Arc<[Organization]>)>,
+}
+
+impl Default for OrganizationCache {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(30))
+    }
+}
+
+impl OrganizationCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            state: Mutex::default(),
+        }
+    }
+
+    /// The organization for a request, as [`resolve`] would pick it.
+    pub fn resolve<R: OrganizationRepository + ?Sized>(
+        &self,
+        repo: &R,
+        host: Option<&str>,
+        token_domain: Option<&str>,
+    ) -> Result<Option<Organization>, CoreError> {
+        if let Some(host) = host {
+            let host = host.split(':').next().unwrap_or(host).to_ascii_lowercase();
+            if let Some(org) = self.by_host(repo, host)? {
+                return Ok(Some(org));
+            }
+        }
+        let Some(domain) = token_domain else {
+            return Ok(None);
+        };
+        Ok(self
+            .all(repo)?
+            .iter()
+            .find(|o| o.allows_domain(domain))
+            .cloned())
+    }
+
+    fn state(&self) -> Result<MutexGuard<'_, CacheState>, CoreError> {
+        self.state
+            .lock()
+            .map_err(|_| CoreError::Repository("mutex poisoned".into()))
+    }
+
+    fn by_host<R: OrganizationRepository + ?Sized>(
+        &self,
+        repo: &R,
+        host: String,
+    ) -> Result<Option<Organization>, CoreError> {
+        let now = Instant::now();
+        if let Some((expires_at, org)) = self.state()?.hosts.get(&host) {
+            if now < *expires_at {
+                return Ok(org.clone());
+            }
+        }
+        let org = repo.get_organization_by_host(&host)?;
+        let mut state = self.state()?;
+        if state.hosts.len() >= MAX_CACHED_HOSTS {
+            state.hosts.retain(|_, (expires_at, _)| now < *expires_at);
+            if state.hosts.len() >= MAX_CACHED_HOSTS {
+                state.hosts.clear();
+            }
+        }
+        state.hosts.insert(host, (now + self.ttl, org.clone()));
+        Ok(org)
+    }
+
+    fn all<R: OrganizationRepository + ?Sized>(
+        &self,
+        repo: &R,
+    ) -> Result<Arc<[Organization]>, CoreError> {
+        let now = Instant::now();
+        if let Some((expires_at, orgs)) = &self.state()?.all {
+            if now < *expires_at {
+                return Ok(Arc::clone(orgs));
+            }
+        }
+        let orgs: Arc<[Organization]> = repo.list_organizations()?.into();
+        self.state()?.all = Some((now + self.ttl, Arc::clone(&orgs)));
+        Ok(orgs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::memory_repo::InMemoryOrganizationRepo;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn org(id: &str, host: &str, domain: &str) -> Organization {
+        Organization {
+            id: TenantId::new(id).unwrap(),
+            hosts: vec![host.into()],
+            auth_domains: vec![domain.into()],
+            ..Organization::fallback()
+        }
+    }
+
+    #[test]
+    fn keys_are_prefixed_outside_the_default_tenant() {
+        let acme = TenantId::new("acme").unwrap();
+        let default = TenantId::default();
+        assert_eq!(scoped_key(&acme, "abc"), "acme#abc");
+        assert_eq!(scoped_key(&default, "abc"), "abc");
+        assert_eq!(unscoped_key(&acme, "acme#abc"), Some("abc"));
+        assert_eq!(unscoped_key(&acme, "acme-2#abc"), None);
+        assert_eq!(unscoped_key(&acme, "abc"), None);
+        assert_eq!(unscoped_key(&default, "abc"), Some("abc"));
+        assert_eq!(unscoped_key(&default, "acme#abc"), None);
+        assert!(TenantId::new("Acme").is_err());
+        assert!(TenantId::new("a#b").is_err());
+    }
+
+    #[test]
+    fn host_wins_over_token_domain() {
+        let orgs = vec![
+            org("acme", "go.acme.com", "acme.com"),
+            org("globex", "go.globex.com", "globex.com"),
+        ];
+        let pick = |host, domain| resolve(&orgs, host, domain).map(|o| o.id.as_str());
+        assert_eq!(
+            pick(Some("go.acme.com:443"), Some("globex.com")),
+            Some("acme")
+        );
+        assert_eq!(pick(Some("localhost"), Some("globex.com")), Some("globex"));
+        assert_eq!(pick(None, email_domain("x@acme.com")), Some("acme"));
+        assert_eq!(pick(Some("localhost"), None), None);
+    }
+
+    /// Counts the reads that reach the store.
+    #[derive(Default)]
+    struct CountingRepo {
+        inner: InMemoryOrganizationRepo,
+        reads: AtomicUsize,
+    }
+
+    impl OrganizationRepository for CountingRepo {
+        fn put_organization(&self, org: Organization) -> Result<(), CoreError> {
+            self.inner.put_organization(org)
+        }
+
+        fn get_organization(&self, id: &TenantId) -> Result<Option<Organization>, CoreError> {
+            self.reads.fetch_add(1, Ordering::SeqCst);
+            self.inner.get_organization(id)
+        }
+
+        fn list_organizations(&self) -> Result<Vec<Organization>, CoreError> {
+            self.reads.fetch_add(1, Ordering::SeqCst);
+            self.inner.list_organizations()
+        }
+    }
+
+    #[test]
+    fn cached_lookups_expire_with_the_ttl() {
+        let repo = CountingRepo::default();
+        repo.put_organization(org("acme", "go.acme.com", "acme.com"))
+            .unwrap();
+        let reads = || repo.reads.load(Ordering::SeqCst);
+        let id = |org: Option<Organization>| org.map(|o| o.id.as_str().to_string());
+
+        let cache = OrganizationCache::new(Duration::from_secs(60));
+        for _ in 0..3 {
+            let found = cache.resolve(&repo, Some("GO.acme.com:443"), None);
+            assert_eq!(id(found.unwrap()).as_deref(), Some("acme"));
+            assert_eq!(
+                id(cache.resolve(&repo, Some("localhost"), None).unwrap()),
+                None
+            );
+        }
+        assert_eq!(reads(), 2);
+
+        // The sign-in domain is matched against the cached list
+        let by_token = |cache: &OrganizationCache| {
+            id(cache
+                .resolve(&repo, Some("localhost"), Some("globex.com"))
+                .unwrap())
+        };
+        assert_eq!(by_token(&cache), None);
+        repo.put_organization(org("globex", "go.globex.com", "globex.com"))
+            .unwrap();
+        assert_eq!(by_token(&cache), None);
+        assert_eq!(reads(), 3);
+
+        let uncached = OrganizationCache::new(Duration::ZERO);
+        assert_eq!(by_token(&uncached).as_deref(), Some("globex"));
+        assert_eq!(by_token(&uncached).as_deref(), Some("globex"));
+        assert_eq!(reads(), 7);
+    }
+}
@@ -21,6 +21,70 @@ pub fn validate_original_url(s: &str) -> Result<(), CoreError> {
     Ok(())
 }
 
+/// Extract the lowercased host from an http(s) URL, without port or userinfo.
+pub fn url_host(url: &str) -> Option<String> {
+    let rest = url.trim().split_once("://")?.1;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host_port = authority.rsplit('@').next()?;
+    let host = host_port.split(':').next()?;
+    if host.is_empty() {
+        None
+    } else {
+        Some(host.to_ascii_lowercase())
+    }
+}
+
+/// Canonical form of an http(s) URL, used to find links to the same target:
+/// scheme and host are lowercased, default ports, the fragment and a bare `?`
+/// are dropped, and an empty path becomes `/`. Path and query are kept as-is
+/// since servers may treat their case and order as significant.
+pub fn canonical_url(url: &str) -> String {
+    let trimmed = url.trim();
+    let Some((scheme, rest)) = trimmed.split_once("://") else {
+        return trimmed.to_string();
+    };
+    let scheme = scheme.to_ascii_lowercase();
+    let rest = rest.split('#').next().unwrap_or_default();
+    let (authority, tail) = rest.split_at(rest.find(['/', '?']).unwrap_or(rest.len()));
+    let (userinfo, host_port) = match authority.rsplit_once('@') {
+        Some((user, host_port)) => (Some(user), host_port),
+        None => (None, authority),
+    };
+    let default_port = match scheme.as_str() {
+        "http" => "80",
+        "https" => "443",
+        _ => "",
+    };
+    let host = match host_port.rsplit_once(':') {
+        Some((host, port)) if port == default_port => host,
+        _ => host_port,
+    };
+    let tail = tail.strip_suffix('?').unwrap_or(tail);
+
+    let mut out = format!("{scheme}://");
+    if let Some(user) = userinfo {
+        out.push_str(user);
+        out.push('@');
+    }
+    out.push_str(&host.to_ascii_lowercase());
+    if !tail.starts_with('/') {
+        out.push('/');
+    }
+    out.push_str(tail);
+    out
+}
+
+/// Match a host against a pattern: exact, or `*.example.com` for any subdomain.
+pub fn host_matches(pattern: &str, host: &str) -> bool {
+    let pattern = pattern.to_ascii_lowercase();
+    match pattern.strip_prefix("*.") {
+        Some(suffix) => host
+            .strip_suffix(suffix)
+            .is_some_and(|sub| sub.len() > 1 && sub.ends_with('.')),
+        None => host == pattern,
+    }
+}
+
 /// Validate a custom slug string using the same rules as `Slug::new`.
 pub fn validate_custom_slug(s: &str) -> Result<Slug, CoreError> {
     Slug::new(s.to_string())
@@ -38,10 +102,56 @@ mod tests {
         assert!(validate_original_url("ftp://example.com").is_err());
     }
 
+    #[test]
+    fn url_host_and_patterns() {
+        assert_eq!(
+            url_host("https://User@Go.Example.com:8443/path?q=1").as_deref(),
+            Some("go.example.com")
+        );
+        assert_eq!(
+            url_host("http://example.com").as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(url_host("example.com"), None);
+
+        assert!(host_matches("example.com", "example.com"));
+        assert!(!host_matches("example.com", "www.example.com"));
+        assert!(host_matches("*.example.com", "www.example.com"));
+        assert!(!host_matches("*.example.com", "example.com"));
+        assert!(!host_matches("*.example.com", "badexample.com"));
+    }
+
+    #[test]
+    fn canonical_urls() {
+        assert_eq!(
+            canonical_url(" HTTPS://Example.COM:443#top"),
+            "https://example.com/"
+        );
+        assert_eq!(
+            canonical_url("http://example.com:80/Docs?b=2&a=1#x"),
+            "http://example.com/Docs?b=2&a=1"
+        );
+        assert_eq!(
+            canonical_url("https://example.com:8443?"),
+            "https://example.com:8443/"
+        );
+        assert_eq!(
+            canonical_url("https://User@Example.com?q=1"),
+            "https://User@example.com/?q=1"
+        );
+        assert_ne!(
+            canonical_url("https://example.com/a/"),
+            canonical_url("https://example.com/a")
+        );
+    }
+
     #[test]
     fn slug_validation_delegates() {
         assert!(validate_custom_slug("abc-123").is_ok());
         assert!(validate_custom_slug("").is_err());
-        assert!(validate_custom_slug("bad/char").is_err());
+        assert!(validate_custom_slug("bad!char").is_err());
+        // `/` separates namespaces, but segments cannot be empty
+        assert!(validate_custom_slug("hr/onboarding").is_ok());
+        assert!(validate_custom_slug("bad/").is_err());
     }
 }
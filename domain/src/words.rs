@@ -0,0 +1,40 @@
+//! Embedded word lists for readable slugs.
+//!
+//! Words are short, lowercase ASCII and easy to spell after hearing them once:
+//! homophones (`bear`/`bare`, `lynx`/`links`), words with regional spellings
+//! (`cozy`/`cosy`, `harbor`) and silent letters (`salmon`) are left out so a
+//! slug read aloud can be typed back. The most common words come first; a
+//! generator limited to a smaller dictionary uses a prefix of each list.
+
+pub(crate) const ADJECTIVES: &[&str] = &[
+    "brave", "calm", "happy", "quick", "bright", "lucky", "swift", "kind", "clever", "gentle",
+    "sunny", "golden", "silver", "green", "wild", "wise", "warm", "proud", "noble", "jolly",
+    "eager", "fresh", "grand", "tiny", "giant", "mighty", "rapid", "ready", "smart", "steady",
+    "able", "agile", "amber", "ample", "azure", "balmy", "bouncy", "breezy", "brisk", "bubbly",
+    "candid", "cheery", "chilly", "civic", "cosmic", "crisp", "cuddly", "curly", "dapper",
+    "daring", "dashing", "dizzy", "early", "easy", "elated", "epic", "exact", "fancy", "fast",
+    "fluffy", "fond", "frank", "friendly", "frisky", "frosty", "funny", "fuzzy", "gifted", "glad",
+    "gleaming", "global", "hardy", "hasty", "hearty", "helpful", "honest", "humble", "icy",
+    "jovial", "joyful", "keen", "lavish", "lively", "lunar", "magic", "mellow", "misty", "modern",
+    "modest", "neat", "nimble", "peppy", "plucky", "polite", "regal", "rosy", "royal", "rustic",
+    "sandy", "sharp", "shiny", "silky", "simple", "sleek", "smooth", "snowy", "solar", "solid",
+    "sonic", "spicy", "sporty", "stellar", "sturdy", "super", "tidy", "trusty", "upbeat", "urban",
+    "valid", "vast", "vivid", "wavy", "windy", "witty", "young", "zany", "zesty", "zippy",
+];
+
+pub(crate) const NOUNS: &[&str] = &[
+    "otter", "tiger", "panda", "eagle", "falcon", "dolphin", "koala", "penguin", "rabbit", "zebra",
+    "river", "comet", "rocket", "planet", "maple", "meadow", "canyon", "island", "garden",
+    "castle", "badger", "beaver", "bison", "bobcat", "camel", "canary", "cheetah", "cobra",
+    "condor", "coyote", "crane", "cricket", "dingo", "donkey", "ferret", "finch", "flamingo",
+    "gecko", "gerbil", "gibbon", "giraffe", "gopher", "gorilla", "hamster", "hedgehog", "heron",
+    "hippo", "husky", "ibis", "iguana", "impala", "jackal", "jaguar", "kiwi", "lemur", "leopard",
+    "lion", "lizard", "llama", "lobster", "magpie", "mammoth", "marmot", "meerkat", "mongoose",
+    "narwhal", "octopus", "ocelot", "orca", "osprey", "ostrich", "owl", "panther", "parrot",
+    "pelican", "pigeon", "puffin", "puma", "python", "quail", "raccoon", "raven", "robin",
+    "sparrow", "seal", "shark", "sloth", "snail", "spider", "squid", "stork", "swan", "tapir",
+    "toucan", "turtle", "walrus", "wombat", "yak", "acorn", "anchor", "apple", "arrow", "banjo",
+    "basket", "beacon", "bucket", "cactus", "candle", "cloud", "compass", "coral", "desert",
+    "glacier", "jungle", "kettle", "lagoon", "lantern", "lemon", "meteor", "mountain", "nebula",
+    "orchid", "pebble", "pretzel", "prism", "summit", "tulip", "willow",
+];
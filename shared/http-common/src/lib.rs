@@ -41,15 +41,38 @@ pub fn json_error_with_message(code: &str, message: &str) -> serde_json::Value {
 /// Validate a custom alias for shortlinks.
 ///
 /// Rules:
-/// - Length must be 3-32 characters
-/// - Only ASCII alphanumeric, hyphen (-), and underscore (_) allowed
+/// - Length must be 3-32 characters per `/`-separated namespace segment, and at
+///   least 3 overall (`hr/onboarding`, `events/2026/kickoff`)
+/// - At most 4 segments, none of them empty
+/// - Only ASCII alphanumeric, hyphen (-), and underscore (_) allowed in segments
 /// - This matches the domain Slug validation
 pub fn is_valid_alias(s: &str) -> bool {
-    if s.len() < 3 || s.len() > 32 {
-        return false;
+    alias_violations(s).is_empty()
+}
+
+/// The rules of [`is_valid_alias`] that `s` breaks, as human-readable messages.
+pub fn alias_violations(s: &str) -> Vec<&'static str> {
+    let mut out = Vec::new();
+    if s.len() < 3 {
+        out.push("must be at least 3 characters");
+    }
+    let segments: Vec<&str> = s.split('/').collect();
+    if segments.len() > 4 {
+        out.push("must have at most 4 namespace segments");
+    }
+    if segments.iter().any(|seg| seg.is_empty()) {
+        out.push("namespace segments cannot be empty");
+    }
+    if segments.iter().any(|seg| seg.len() > 32) {
+        out.push("each segment must be at most 32 characters");
+    }
+    if !s
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '/')
+    {
+        out.push("only letters, digits, hyphens and underscores are allowed");
     }
-    s.chars()
-        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    out
 }
 
 // ============================================================================
@@ -153,15 +176,158 @@ pub fn parse_query_param(query: Option<&str>, name: &str) -> Option<String> {
         let key = it.next()?;
         if key == name {
             if let Some(val) = it.next() {
-                // Basic URL decoding for common cases
-                let decoded = val.replace("%40", "@").replace("%20", " ");
-                return Some(decoded);
+                return Some(percent_decode(val));
             }
         }
     }
     None
 }
 
+/// Decode a form-encoded query value: `+` is a space and `%XX` a byte.
+/// Malformed escapes are kept as they are.
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = |b: u8| (b as char).to_digit(16);
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => match (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                (Some(h), Some(l)) => {
+                    out.push((h * 16 + l) as u8);
+                    i += 2;
+                }
+                _ => out.push(b'%'),
+            },
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+// ============================================================================
+// Workspace Sessions
+// ============================================================================
+
+/// Name of the cookie holding a signed workspace session token.
+pub const SESSION_COOKIE: &str = "sl_session";
+
+/// Extract a cookie value from a `Cookie` request header.
+pub fn get_cookie(cookie_header: Option<&str>, name: &str) -> Option<String> {
+    cookie_header?
+        .split(';')
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(k, _)| *k == name)
+        .map(|(_, v)| v.to_string())
+}
+
+/// Build a `Set-Cookie` header value for the workspace session cookie.
+pub fn session_cookie(value: &str, max_age_secs: u64) -> String {
+    format!(
+        "{}={}; Path=/; Max-Age={}; HttpOnly; Secure; SameSite=Lax",
+        SESSION_COOKIE, value, max_age_secs
+    )
+}
+
+/// Render the sign-in page shown instead of a workspace-only link's destination.
+///
+/// With a Google client ID the page uses Google Identity Services and posts the
+/// ID token to `session_path`; without one (debug auth mode) it asks for an email.
+/// On success the page reloads so the original request is retried with the cookie.
+pub fn login_page_html(
+    google_client_id: Option<&str>,
+    session_path: &str,
+    message: Option<&str>,
+) -> String {
+    let message_html = message
+        .map(|m| format!(r#"<div class="message">{}</div>"#, html_escape(m)))
+        .unwrap_or_default();
+    let sign_in_html = match google_client_id {
+        Some(client_id) => format!(
+            r#"<script src="https://accounts.google.com/gsi/client" async></script>
+            <div id="g_id_onload" data-client_id="{}" data-callback="onCredential"></div>
+            <div class="g_id_signin" data-type="standard"></div>"#,
+            html_escape(client_id)
+        ),
+        None => r#"<form onsubmit="onCredential({credential: this.email.value}); return false;">
+                <input type="email" name="email" placeholder="you@example.com" required>
+                <button type="submit">Continue</button>
+            </form>"#
+            .to_string(),
+    };
+    format!(
+        r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <meta name="robots" content="noindex">
+    <title>Sign in required</title>
+    <style>
+        body {{
+            font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif;
+            background: linear-gradient(135deg, #667eea 0%, #764ba2 100%);
+            min-height: 100vh;
+            display: flex;
+            align-items: center;
+            justify-content: center;
+            margin: 0;
+        }}
+        .card {{
+            background: white;
+            border-radius: 16px;
+            padding: 32px;
+            max-width: 400px;
+            text-align: center;
+        }}
+        h1 {{ font-size: 1.25rem; color: #334155; }}
+        p {{ color: #64748b; }}
+        .message {{ color: #dc2626; margin-bottom: 16px; }}
+        input, button {{ padding: 10px; margin: 4px; border-radius: 8px; border: 1px solid #cbd5e1; }}
+        button {{ background: #6366f1; color: white; border: none; }}
+    </style>
+</head>
+<body>
+    <div class="card">
+        <h1>Sign in required</h1>
+        <p>This link is only available to members of the workspace.</p>
+        {message}
+        <div id="error" class="message"></div>
+        {sign_in}
+    </div>
+    <script>
+        async function onCredential(response) {{
+            const res = await fetch("{session_path}", {{
+                method: "POST",
+                credentials: "same-origin",
+                headers: {{ "content-type": "application/json" }},
+                body: JSON.stringify({{ credential: response.credential }}),
+            }});
+            if (res.ok) {{
+                window.location.reload();
+            }} else {{
+                document.getElementById("error").textContent = "Sign-in was not accepted for this link.";
+            }}
+        }}
+    </script>
+</body>
+</html>"##,
+        message = message_html,
+        sign_in = sign_in_html,
+        session_path = session_path.replace('\\', "\\\\").replace('"', "\\\""),
+    )
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#x27;")
+}
+
 // ============================================================================
 // Lambda HTTP Helpers (feature-gated)
 // ============================================================================
@@ -221,7 +387,7 @@ pub mod lambda {
         );
         headers.insert(
             HeaderName::from_static("access-control-allow-methods"),
-            HeaderValue::from_static("OPTIONS, GET, POST, PATCH, DELETE"),
+            HeaderValue::from_static("OPTIONS, GET, POST, PUT, PATCH, DELETE"),
         );
         resp
     }
@@ -275,6 +441,28 @@ mod tests {
         assert!(!is_valid_alias(&"a".repeat(33))); // too long
         assert!(!is_valid_alias("bad!slug")); // special chars not allowed
         assert!(!is_valid_alias("has space")); // spaces not allowed
+        assert!(is_valid_alias("hr/onboarding")); // namespaced
+        assert!(is_valid_alias("events/2026/kickoff"));
+        assert!(!is_valid_alias("hr/")); // empty segment
+        assert!(!is_valid_alias("/hr"));
+        assert!(!is_valid_alias("a/b/c/d/e")); // too deep
+        assert!(!is_valid_alias(&format!("hr/{}", "a".repeat(33)))); // segment too long
+    }
+
+    #[test]
+    fn test_alias_violations() {
+        assert!(alias_violations("hr/onboarding").is_empty());
+        assert_eq!(
+            alias_violations("a!"),
+            [
+                "must be at least 3 characters",
+                "only letters, digits, hyphens and underscores are allowed"
+            ]
+        );
+        assert_eq!(
+            alias_violations("hr//x"),
+            ["namespace segments cannot be empty"]
+        );
     }
 
     #[test]
@@ -301,10 +489,41 @@ mod tests {
             parse_query_param(Some("limit=10&created_by=test%40test.com"), "created_by"),
             Some("test@test.com".to_string())
         );
+        assert_eq!(
+            parse_query_param(
+                Some("url=https%3A%2F%2Fexample.com%2Fa%3Fb%3D1&title=Q3+plan"),
+                "url"
+            ),
+            Some("https://example.com/a?b=1".to_string())
+        );
+        assert_eq!(
+            parse_query_param(Some("title=Q3+plan+100%"), "title"),
+            Some("Q3 plan 100%".to_string())
+        );
         assert_eq!(parse_query_param(Some("foo=bar"), "missing"), None);
         assert_eq!(parse_query_param(None, "foo"), None);
     }
 
+    #[test]
+    fn test_get_cookie() {
+        let header = Some("a=1; sl_session=tok.en.sig; b=2");
+        assert_eq!(
+            get_cookie(header, SESSION_COOKIE),
+            Some("tok.en.sig".to_string())
+        );
+        assert_eq!(get_cookie(header, "missing"), None);
+        assert_eq!(get_cookie(None, SESSION_COOKIE), None);
+    }
+
+    #[test]
+    fn test_login_page_escapes_inputs() {
+        let html = login_page_html(Some("id\"><script>"), "/auth/session", Some("<b>"));
+        assert!(!html.contains("id\"><script>"));
+        assert!(html.contains("&lt;b&gt;"));
+        assert!(html.contains("accounts.google.com/gsi/client"));
+        assert!(login_page_html(None, "/auth/session", None).contains("type=\"email\""));
+    }
+
     #[test]
     fn test_build_short_url_from_host() {
         // Without SHORTLINK_DOMAIN set